@@ -1,23 +1,316 @@
 //! CLI argument definitions for infst.
 
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand, ValueHint};
+use infst::input::hotkeys::HotkeyBinding;
+use infst::{
+    DisplayTimezone, ProcessSelection, RetryOverride, ScheduledExport, TextEncoding, TitleLanguage,
+};
+use tracing_subscriber::filter::LevelFilter;
+
+use crate::exit_code;
+use crate::output::OutputFormat;
 
 #[derive(Parser)]
 #[command(name = "infst")]
 #[command(about = "INFINITAS score tracker", version)]
+#[command(after_help = exit_code::HELP)]
 pub struct Args {
     /// Load offsets from file (skip automatic detection)
-    #[arg(long, value_name = "FILE")]
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
     pub offsets_file: Option<String>,
 
+    /// BPI reference data file (JSON)
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub bpi_file: Option<String>,
+
+    /// Write stream overlay files (marquee.txt) to this directory
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub stream_dir: Option<String>,
+
+    /// Marquee template, e.g. "{title} [{diff}] {score} {lamp}{?pb_diff} ({pb_diff}){/pb_diff}"
+    #[arg(long, value_name = "TEMPLATE")]
+    pub marquee_template: Option<String>,
+
+    /// Summary copied by the 'c' key or `infst session copy`, same placeholders as --marquee-template
+    #[arg(long, value_name = "TEMPLATE")]
+    pub clipboard_template: Option<String>,
+
+    /// Marquee text shown at song select
+    #[arg(long, value_name = "TEXT")]
+    pub marquee_idle_text: Option<String>,
+
+    /// Also write a result card image (latest.png) to the stream directory
+    #[arg(long)]
+    pub result_card: bool,
+
+    /// Number of plays kept in recent_plays.json in the stream directory (0 disables)
+    #[arg(long, value_name = "N", default_value_t = infst::stream::DEFAULT_RECENT_PLAYS)]
+    pub recent_plays: usize,
+
+    /// Encoding of marquee.txt ("utf-8", "utf-8-bom" or "shift-jis")
+    #[arg(long, value_name = "ENCODING", default_value = "utf-8")]
+    pub stream_encoding: TextEncoding,
+
+    /// Title shown as {title} in stream output ("original", "english" or "romanized")
+    #[arg(long, value_name = "LANG", default_value = "original")]
+    pub stream_title: TitleLanguage,
+
+    /// Title shown as {title2} in stream output [default: English title]
+    #[arg(long, value_name = "LANG")]
+    pub stream_title2: Option<TitleLanguage>,
+
+    /// Title alias file for romanized titles (JSON, in-game title to alias)
+    /// [default: title_aliases.json in the data directory]
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub title_aliases: Option<String>,
+
+    /// Ignore list (JSON: song IDs, charts and title patterns); plays of these
+    /// charts are recorded but not exported or submitted
+    /// [default: ignore.json in the data directory]
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub ignore_file: Option<String>,
+
+    /// Encoding of tracker.tsv and session TSV files ("utf-8", "utf-8-bom" or "shift-jis")
+    #[arg(long, value_name = "ENCODING", default_value = "utf-8")]
+    pub tsv_encoding: TextEncoding,
+
+    /// Write dated tracker snapshots (tracker-YYYY-MM-DD.tsv) instead of overwriting tracker.tsv
+    #[arg(long)]
+    pub tracker_snapshots: bool,
+
+    /// Save a summary image (plays and improved charts) next to the session
+    /// file when the session closes
+    #[arg(long)]
+    pub summary_image: bool,
+
+    /// Read and process plays as usual, but only print what would be recorded:
+    /// no session files, tracker exports, overlays, caches or submissions
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Restart tracking after a crash in the tracking loop instead of exiting
+    /// (the session is saved and a crash report written either way)
+    #[arg(long)]
+    pub restart_on_panic: bool,
+
+    /// Override a retry policy, as <operation>=<attempts>x<delay>[..<max delay>] (repeatable).
+    /// Operations: memory-read (default 5x100ms..1600ms), song-db (12x5s),
+    /// offset-search (foreverx5s), api (3x1s..4s); e.g. song-db=20x5s
+    #[arg(long, value_name = "OP=POLICY")]
+    pub retry: Vec<RetryOverride>,
+
+    /// Run an export on a schedule while tracking, as <action>@<when> (repeatable).
+    /// Actions: snapshot, backup, upload:<title-mapping.json>;
+    /// when: "daily HH:MM", "weekly <day> HH:MM" or session-end
+    #[arg(long, value_name = "JOB")]
+    pub schedule: Vec<ScheduledExport>,
+
+    /// Score plays against a tournament file (chart pool and participants, JSON)
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub tournament: Option<String>,
+
+    /// Don't record plays while the session is paused (`infst session pause`)
+    #[arg(long)]
+    pub ignore_paused_plays: bool,
+
+    /// Don't record plays with impossible values (EX score or judge counts
+    /// above the note count); they are still noted on the session timeline
+    #[arg(long)]
+    pub drop_anomalies: bool,
+
+    /// How plays quit before the end of the chart are recorded
+    #[arg(long, value_enum, value_name = "MODE", default_value_t = PrematurePolicy::Flag)]
+    pub premature: PrematurePolicy,
+
+    /// Record identical results (same chart and judge counts) within this
+    /// many seconds only once (0 disables)
+    #[arg(long, value_name = "SECS", default_value_t = infst::play::DEFAULT_DUPLICATE_WINDOW_SECS)]
+    pub duplicate_window: u64,
+
+    /// Alert (console and alert.txt in the stream directory) when the green
+    /// number is more than N away from your usual one for the chart's BPM band
+    /// (needs the laneCover offset)
+    #[arg(long, value_name = "N")]
+    pub green_number_alert: Option<u16>,
+
+    /// Bind a global hotkey as <action>=<keys>, e.g. "copy=ctrl+shift+c" (repeatable).
+    /// Actions: export, pause, marquee, copy; defaults are Ctrl+Alt+E/P/M/C
+    #[arg(long, value_name = "BINDING")]
+    pub hotkey: Vec<HotkeyBinding>,
+
+    /// Don't register global hotkeys
+    #[arg(long, conflicts_with = "hotkey")]
+    pub no_hotkeys: bool,
+
+    /// Start tracking even if another tracker holds the instance lock
+    #[arg(long)]
+    pub force: bool,
+
+    /// Limit memory reads to this many per second
+    #[arg(long, value_name = "N")]
+    pub max_reads_per_sec: Option<u32>,
+
+    /// Record memory access patterns and write them to this file (JSON)
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub audit_log: Option<String>,
+
+    /// Don't read unlock state from memory
+    #[arg(long)]
+    pub no_unlock_reads: bool,
+
+    /// Don't re-scan memory for new songs at song select
+    #[arg(long)]
+    pub no_song_rescan: bool,
+
+    /// Pretend the tracker started at TIME (debugging date-dependent output;
+    /// RFC 3339 or local "YYYY-MM-DD HH:MM[:SS]")
+    #[arg(long, value_name = "TIME", value_parser = infst::clock::parse_time)]
+    pub fake_time: Option<DateTime<Utc>>,
+
+    /// Timezone for play times in session files, console and stream output
+    /// ("local", "utc" or an offset like "+09:00"; JSON keeps UTC)
+    #[arg(long, value_name = "TZ", default_value = "local")]
+    pub timezone: DisplayTimezone,
+
     /// API endpoint URL
-    #[arg(long, env = "INFST_API_ENDPOINT")]
+    #[arg(long, env = "INFST_API_ENDPOINT", value_hint = ValueHint::Url)]
     pub api_endpoint: Option<String>,
 
     /// API token
     #[arg(long, env = "INFST_API_TOKEN")]
     pub api_token: Option<String>,
 
+    /// Send lamps to the API without asking at startup (the first payload of
+    /// each session is still printed)
+    #[arg(long, env = "INFST_API_CONSENT")]
+    pub api_consent: bool,
+
+    /// Also write the first lamp payload of each session to FILE
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub preview_payload: Option<String>,
+
+    /// Lamps sent per request to servers that accept batches (1 sends each
+    /// lamp on its own)
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = infst::config::submission::BATCH_SIZE as u32,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub api_batch_size: u32,
+
+    /// Send queued lamps at the latest this many seconds after the first one
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = infst::config::submission::FLUSH_INTERVAL.as_secs()
+    )]
+    pub api_flush_interval: u64,
+
+    /// Send a push notification to URL when a play reaches a milestone
+    /// chosen with --push-on (ntfy.sh, Pushover or any HTTP endpoint)
+    #[arg(long, value_name = "URL", env = "INFST_PUSH_URL", value_hint = ValueHint::Url, requires = "push_on")]
+    pub push_url: Option<String>,
+
+    /// Milestone to notify about as <event>[:<level>][@<chart>,...] (repeatable).
+    /// Events: easy, clear, hard, exhard, fc, a grade (aa, aaa) or pb,
+    /// e.g. "clear:12" or "aaa@1000:SPA"
+    #[arg(long, value_name = "RULE", requires = "push_url")]
+    pub push_on: Vec<infst::NotifyRule>,
+
+    /// Notification body, same placeholders as --marquee-template plus {milestone}
+    #[arg(long, value_name = "TEMPLATE", default_value = infst::notify::DEFAULT_PUSH_TEMPLATE)]
+    pub push_template: String,
+
+    /// Content type of the notification (values are JSON-escaped for JSON types)
+    #[arg(long, value_name = "TYPE", default_value = infst::notify::DEFAULT_PUSH_CONTENT_TYPE)]
+    pub push_content_type: String,
+
+    /// Extra header of the notification request as "Name: value" (repeatable)
+    #[arg(long, value_name = "HEADER", value_parser = infst::notify::parse_header)]
+    pub push_header: Vec<(String, String)>,
+
+    /// Rule for choosing the game process when several are running
+    /// (first, newest, oldest, title:<text>, path:<text>)
+    #[arg(long, value_name = "RULE", env = "INFST_PROCESS_SELECT", global = true)]
+    pub process_select: Option<ProcessSelection>,
+
+    /// Executable name of the game process [default: bm2dx.exe]
+    #[arg(long, value_name = "NAME", env = "INFST_PROCESS_NAME", global = true)]
+    pub process_name: Option<String>,
+
+    /// Match the game process by window title (case-insensitive regex) instead
+    /// of executable name, e.g. for renamed launchers
+    #[arg(long, value_name = "REGEX", env = "INFST_PROCESS_TITLE", global = true)]
+    pub process_title: Option<String>,
+
+    /// Match the game process by the full path of its executable instead of its name
+    #[arg(
+        long,
+        value_name = "PATH",
+        env = "INFST_PROCESS_PATH",
+        global = true,
+        value_hint = ValueHint::FilePath
+    )]
+    pub process_path: Option<String>,
+
+    /// Keep tracker.tsv, sessions/ and caches in the current directory
+    /// instead of the per-user data directory
+    #[arg(long, global = true)]
+    pub portable: bool,
+
+    /// Keep tracker.tsv, sessions/ and caches in this directory
+    #[arg(
+        long,
+        value_name = "DIR",
+        value_hint = ValueHint::DirPath,
+        env = "INFST_DATA_DIR",
+        global = true,
+        conflicts_with = "portable"
+    )]
+    pub data_dir: Option<String>,
+
+    /// Keep this player's tracker.tsv, sessions/ and backups apart (profiles/<NAME> in the data directory)
+    #[arg(long, value_name = "NAME", env = "INFST_PROFILE", global = true)]
+    pub profile: Option<String>,
+
+    /// Re-launch as administrator when the game process denies access
+    #[arg(long, env = "INFST_RELAUNCH_AS_ADMIN", global = true)]
+    pub relaunch_as_admin: bool,
+
+    /// Also log to daily files in this directory (infst-YYYY-MM-DD.log)
+    #[arg(
+        long,
+        value_name = "DIR",
+        value_hint = ValueHint::DirPath,
+        env = "INFST_LOG_DIR",
+        global = true
+    )]
+    pub log_dir: Option<String>,
+
+    /// Level for the log files ("error", "warn", "info", "debug", "trace" or "off")
+    #[arg(long, value_name = "LEVEL", default_value = "info", global = true)]
+    pub log_level: LevelFilter,
+
+    /// Level for the console [default: RUST_LOG, else warn]
+    #[arg(long, value_name = "LEVEL", global = true)]
+    pub console_log_level: Option<LevelFilter>,
+
+    /// Start a new log file when the current one reaches this size
+    #[arg(long, value_name = "MB", default_value_t = 10, global = true)]
+    pub log_max_size: u64,
+
+    /// Result format; `json` writes one result object per command to stdout
+    /// (must precede the subcommand, e.g. `infst --output json scan`)
+    #[arg(
+        long = "output",
+        value_name = "FORMAT",
+        value_enum,
+        default_value = "text"
+    )]
+    pub output_format: OutputFormat,
+
     #[command(subcommand)]
     pub command: Option<Command>,
 }
@@ -26,9 +319,9 @@ pub struct Args {
 pub enum Command {
     /// Search for memory offsets interactively
     FindOffsets {
-        /// Output file path
-        #[arg(short, long, default_value = "offsets.txt")]
-        output: String,
+        /// Output file path [default: offsets.txt in the data directory]
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: Option<String>,
         /// Process ID (skip automatic detection)
         #[arg(long)]
         pid: Option<u32>,
@@ -45,7 +338,7 @@ pub enum Command {
     /// Show game and offset status
     Status {
         /// Load offsets from file
-        #[arg(long, value_name = "FILE")]
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
         offsets_file: Option<String>,
         /// Process ID (skip automatic detection)
         #[arg(long)]
@@ -57,19 +350,22 @@ pub enum Command {
     /// Dump memory structures
     Dump {
         /// Load offsets from file
-        #[arg(long, value_name = "FILE")]
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
         offsets_file: Option<String>,
         /// Process ID (skip automatic detection)
         #[arg(long)]
         pid: Option<u32>,
         /// Output file path (JSON)
-        #[arg(short, long)]
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
         output: Option<String>,
+        /// Also write the raw score map (all hashmap nodes) as JSON to this file
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        score_map: Option<String>,
     },
     /// Scan for song database
     Scan {
         /// Load offsets from file
-        #[arg(long, value_name = "FILE")]
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
         offsets_file: Option<String>,
         /// Process ID (skip automatic detection)
         #[arg(long)]
@@ -78,10 +374,10 @@ pub enum Command {
         #[arg(long, default_value = "1048576")]
         range: usize,
         /// TSV file for matching
-        #[arg(long)]
+        #[arg(long, value_hint = ValueHint::FilePath)]
         tsv: Option<String>,
         /// Output file path (JSON)
-        #[arg(short, long)]
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
         output: Option<String>,
         /// Entry size in bytes (default: 1200)
         #[arg(long)]
@@ -96,6 +392,30 @@ pub enum Command {
         #[arg(long)]
         pid: Option<u32>,
     },
+    /// Infer the song entry layout from the song list and write a candidate layout
+    DiscoverLayout {
+        /// Address of the first song list entry (hex, e.g., 0x1431B08A0)
+        #[arg(long)]
+        address: String,
+        /// Bytes of the song list to analyze
+        #[arg(long, default_value = "1048576")]
+        size: usize,
+        /// Read a memory dump (raw bytes or IDMP snapshot) instead of the game
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        dump: Option<String>,
+        /// Address of the first byte of a raw dump (hex)
+        #[arg(long, default_value = "0x1000")]
+        base: String,
+        /// First build the layout applies to (default: the running game's date code)
+        #[arg(long, value_name = "DATE_CODE")]
+        since: Option<String>,
+        /// Write the candidate layout (JSON) to this file
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: Option<String>,
+        /// Process ID (skip automatic detection)
+        #[arg(long)]
+        pid: Option<u32>,
+    },
     /// Dump raw bytes from memory (hexdump)
     Hexdump {
         /// Start address (hex, e.g., 0x1431B08A0)
@@ -107,15 +427,49 @@ pub enum Command {
         /// Include ASCII representation
         #[arg(long)]
         ascii: bool,
+        /// Also dump the data behind detected pointers, up to DEPTH levels (at most 4)
+        #[arg(long, value_name = "DEPTH", num_args = 0..=1, default_missing_value = "1")]
+        deref: Option<usize>,
+        /// Detect offsets and annotate rows inside known structures (without
+        /// the global --offsets-file)
+        #[arg(long)]
+        annotate: bool,
         /// Process ID (skip automatic detection)
         #[arg(long)]
         pid: Option<u32>,
     },
     /// Search for values in memory
     Search {
-        /// Search for a string (Shift-JIS encoded)
+        /// Search for a string
         #[arg(long)]
         string: Option<String>,
+        /// Search decoded strings matching a regular expression
+        #[arg(long, value_name = "REGEX")]
+        regex: Option<String>,
+        /// Encoding used for --string and --regex
+        #[arg(long, value_enum, default_value = "shift-jis")]
+        encoding: StringEncoding,
+        /// Match --string / --regex regardless of case
+        #[arg(long, short = 'i')]
+        ignore_case: bool,
+        /// Minimum decoded string length for --regex
+        #[arg(long, default_value = "4")]
+        min_len: usize,
+        /// Search for values in an inclusive range (e.g., 1000..50000, 0x10..0x20)
+        #[arg(long, value_name = "MIN..MAX", allow_hyphen_values = true)]
+        range: Option<String>,
+        /// Value width in bytes for --range (1, 2, 4 or 8)
+        #[arg(long, default_value = "4")]
+        width: usize,
+        /// Alignment for --range (default: the value width)
+        #[arg(long)]
+        align: Option<u64>,
+        /// Entry stride for --range (e.g., 0x3F0): consecutive entries must also match
+        #[arg(long)]
+        stride: Option<String>,
+        /// Number of consecutive entries required with --stride (default: 10)
+        #[arg(long)]
+        run: Option<usize>,
         /// Search for a 32-bit integer
         #[arg(long)]
         i32: Option<i32>,
@@ -132,6 +486,75 @@ pub enum Command {
         #[arg(long)]
         pid: Option<u32>,
     },
+    /// Compare one structure layout read at two addresses, field by field
+    StructDiff {
+        /// Structure layout
+        #[arg(long, value_parser = crate::commands::struct_diff::layout_parser())]
+        layout: String,
+        /// First address (hex)
+        #[arg(long)]
+        a: String,
+        /// Second address (hex)
+        #[arg(long)]
+        b: String,
+        /// Bytes to compare (required for the words layout)
+        #[arg(long)]
+        size: Option<usize>,
+        /// Only list fields that differ
+        #[arg(long)]
+        changed: bool,
+        /// Process ID (skip automatic detection)
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    /// Poll addresses and print value changes with timestamps
+    Watch {
+        /// Address to watch (hex, repeat for several)
+        #[arg(long, required = true)]
+        address: Vec<String>,
+        /// Value type
+        #[arg(long = "type", value_enum, default_value = "i32")]
+        value_type: WatchType,
+        /// Poll interval (e.g., 100ms, 1s; plain numbers are milliseconds)
+        #[arg(long, default_value = "100ms")]
+        interval: String,
+        /// Print changes as CSV rows (timestamp,address,old,new)
+        #[arg(long)]
+        csv: bool,
+        /// Process ID (skip automatic detection)
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    /// Interactive memory shell (hexdump, follow, scan, struct) on one open process
+    Shell {
+        /// Process ID (skip automatic detection)
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    /// Convert a memory dump region into a test fixture
+    Fixture {
+        /// Dump file: raw bytes, or a memory snapshot (IDMP)
+        #[arg(value_hint = ValueHint::FilePath)]
+        input: String,
+        /// Address of the first byte of a raw dump (hex)
+        #[arg(long, default_value = "0x1000")]
+        base: String,
+        /// Start of the region to keep (hex, default: start of the dump)
+        #[arg(long)]
+        address: Option<String>,
+        /// Bytes to keep (default: to the end of the dump)
+        #[arg(long)]
+        size: Option<usize>,
+        /// Zero ADDRESS:LEN (hex address) before writing, e.g. player names (repeatable)
+        #[arg(long, value_name = "ADDRESS:LEN")]
+        mask: Vec<String>,
+        /// Comment line for the fixture header (repeatable)
+        #[arg(long)]
+        comment: Vec<String>,
+        /// Output file (default: stdout)
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: Option<String>,
+    },
     /// Calculate offset between two addresses
     Offset {
         /// Start address (hex)
@@ -149,7 +572,7 @@ pub enum Command {
     /// Export all play data (scores, lamps, miss counts)
     Export {
         /// Output file path (defaults to stdout)
-        #[arg(long, short)]
+        #[arg(long, short, value_hint = ValueHint::FilePath)]
         output: Option<String>,
         /// Output format
         #[arg(long, short, value_enum, default_value = "tsv")]
@@ -157,6 +580,214 @@ pub enum Command {
         /// Process ID (skip automatic detection)
         #[arg(long)]
         pid: Option<u32>,
+        /// Session directory to read play history from (adds analytics to TSV and JSON)
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        analytics: Option<String>,
+        /// Minimum chart level
+        #[arg(long)]
+        min_level: Option<u8>,
+        /// Maximum chart level
+        #[arg(long)]
+        max_level: Option<u8>,
+        /// Only SP charts
+        #[arg(long, conflicts_with = "dp")]
+        sp: bool,
+        /// Only DP charts
+        #[arg(long)]
+        dp: bool,
+        /// Only charts that have been played
+        #[arg(long)]
+        played_only: bool,
+        /// Only unlocked charts
+        #[arg(long)]
+        unlocked_only: bool,
+        /// Only songs in these folders (comma-separated folder numbers)
+        #[arg(long, value_delimiter = ',')]
+        folder: Vec<i32>,
+        /// Only songs with this tag (repeatable; songs with any of the tags)
+        #[arg(long, value_name = "TAG")]
+        tag: Vec<String>,
+        /// Song tags file (JSON) [default: tags.json in the data directory]
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        tags_file: Option<String>,
+        /// Title in the Title column ("original", "english" or "romanized")
+        #[arg(long, value_name = "LANG", default_value = "original")]
+        title: TitleLanguage,
+        /// Also export the title in this language (a "Title 2" column, or `title2` in JSON)
+        #[arg(long, value_name = "LANG")]
+        title2: Option<TitleLanguage>,
+        /// Title alias file for romanized titles (JSON, in-game title to alias)
+        /// [default: title_aliases.json in the data directory]
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        title_aliases: Option<String>,
+        /// Chart note density file (JSON); adds each chart's density profile to JSON output
+        /// [default: chart_density.json in the data directory]
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        chart_density: Option<String>,
+        /// Ignore list (JSON); ignored charts are exported as unplayed
+        /// [default: ignore.json in the data directory]
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        ignore_file: Option<String>,
+        /// Chart-to-BMS-hash mapping file (JSON, for the beatoraja format)
+        #[arg(long, required_if_eq("format", "beatoraja"), value_hint = ValueHint::FilePath)]
+        hash_map: Option<String>,
+        /// Encoding of the TSV output file ("utf-8", "utf-8-bom" or "shift-jis")
+        #[arg(long, value_name = "ENCODING", default_value = "utf-8")]
+        encoding: TextEncoding,
+        /// Sign the TSV output with the secret in this file (HMAC-SHA256)
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        sign_key: Option<String>,
+    },
+    /// Check the signature of a signed tracker export
+    Verify {
+        /// Signed tracker TSV file
+        #[arg(value_hint = ValueHint::FilePath)]
+        file: String,
+        /// File with the secret the export was signed with
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        key: String,
+    },
+    /// List improvements (new lamps, grade-ups, EX score gains) between two tracker exports
+    Diff {
+        /// Older tracker TSV file
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        old: String,
+        /// Newer tracker TSV file [default: tracker.tsv in the data directory]
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        new: Option<String>,
+        /// Smallest EX score gain to list
+        #[arg(long, value_name = "N", default_value_t = infst::export::DEFAULT_MIN_SCORE_DELTA)]
+        min_score_delta: u32,
+    },
+    /// Save or compare snapshots of the in-game score map
+    Scores {
+        #[command(subcommand)]
+        action: ScoresAction,
+    },
+    /// Inspect the tracker's stored data
+    Tracker {
+        #[command(subcommand)]
+        action: TrackerAction,
+        /// Session directory of the tracker [default: sessions in the data directory]
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        session_dir: Option<String>,
+    },
+    /// Compare two tracker snapshots and list changed cells
+    TrackerDiff {
+        /// Older tracker TSV file
+        #[arg(value_hint = ValueHint::FilePath)]
+        old: String,
+        /// Newer tracker TSV file
+        #[arg(value_hint = ValueHint::FilePath)]
+        new: String,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Merge the tracker files of two PCs (best lamp, score and miss count per chart)
+    Merge {
+        /// First tracker TSV file (wins conflicts)
+        #[arg(value_hint = ValueHint::FilePath)]
+        a: String,
+        /// Second tracker TSV file
+        #[arg(value_hint = ValueHint::FilePath)]
+        b: String,
+        /// Merged tracker TSV file
+        #[arg(short, long, value_hint = ValueHint::FilePath)]
+        output: String,
+        /// Encoding of the merged file ("utf-8", "utf-8-bom" or "shift-jis")
+        #[arg(long, value_name = "ENCODING", default_value = "utf-8")]
+        encoding: TextEncoding,
+        /// List conflicts as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Search charts in the song database
+    Songs {
+        /// Text in the title, English title or artist, or a song ID
+        #[arg(long, value_name = "TEXT")]
+        search: Option<String>,
+        /// Only charts of this level
+        #[arg(long, conflicts_with_all = ["min_level", "max_level"])]
+        level: Option<u8>,
+        /// Minimum chart level
+        #[arg(long)]
+        min_level: Option<u8>,
+        /// Maximum chart level
+        #[arg(long)]
+        max_level: Option<u8>,
+        /// Only SP charts
+        #[arg(long, conflicts_with = "dp")]
+        sp: bool,
+        /// Only DP charts
+        #[arg(long)]
+        dp: bool,
+        /// Read the song database from a tracker export instead of game memory
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        from: Option<String>,
+        /// Process ID (skip automatic detection)
+        #[arg(long, conflicts_with = "from")]
+        pid: Option<u32>,
+    },
+    /// Recommend charts that raise DJ points the most
+    Recommend {
+        /// DJ points to gain
+        #[arg(long, default_value = "100")]
+        target_djp: f64,
+        /// Skip charts above this level
+        #[arg(long)]
+        max_level: Option<u8>,
+        /// Recommend DP charts instead of SP
+        #[arg(long)]
+        dp: bool,
+        /// Only recommend songs with this tag (repeatable; songs with any of the tags)
+        #[arg(long, value_name = "TAG")]
+        tag: Vec<String>,
+        /// Song tags file (JSON) [default: tags.json in the data directory]
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        tags_file: Option<String>,
+        /// Chart note density file (JSON); also reports whether failed plays end
+        /// in dense sections [default: chart_density.json in the data directory]
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        chart_density: Option<String>,
+        /// Process ID (skip automatic detection)
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    /// List locked bit charts by value per bit, or plan unlocks within a budget
+    Unlocks {
+        /// Choose unlocks that fit the budget
+        #[arg(long)]
+        plan: bool,
+        /// Bits to spend (defaults to the current balance, if readable)
+        #[arg(long)]
+        budget: Option<u32>,
+        /// Only charts at or above this level count as value
+        #[arg(long)]
+        min_level: Option<u8>,
+        /// Only SP charts count as value
+        #[arg(long, conflicts_with = "dp")]
+        sp: bool,
+        /// Only DP charts count as value
+        #[arg(long)]
+        dp: bool,
+        /// Custom song labels file (song_id,label per line)
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        custom_types: Option<String>,
+        /// Load offsets from file (locates the currency block for the bit balance)
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        offsets_file: Option<String>,
+        /// Process ID (skip automatic detection)
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    /// Pause, resume or annotate the running tracker session
+    Session {
+        #[command(subcommand)]
+        action: SessionAction,
+        /// Session directory of the running tracker [default: sessions in the data directory]
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        session_dir: Option<String>,
     },
     /// Login to the infst web service
     Login {
@@ -164,6 +795,7 @@ pub enum Command {
         #[arg(
             long,
             env = "INFST_API_ENDPOINT",
+            value_hint = ValueHint::Url,
             default_value = "https://infst.oidehosp.me"
         )]
         endpoint: String,
@@ -171,7 +803,7 @@ pub enum Command {
     /// Sync all play data to the web service
     Sync {
         /// API endpoint URL
-        #[arg(long, env = "INFST_API_ENDPOINT")]
+        #[arg(long, env = "INFST_API_ENDPOINT", value_hint = ValueHint::Url)]
         endpoint: Option<String>,
         /// API token
         #[arg(long, env = "INFST_API_TOKEN")]
@@ -179,11 +811,22 @@ pub enum Command {
         /// Process ID (skip automatic detection)
         #[arg(long)]
         pid: Option<u32>,
+        /// Send without asking first
+        #[arg(long, env = "INFST_API_CONSENT")]
+        api_consent: bool,
+        /// Show the exact request body before sending (to FILE, or the console
+        /// without a value); without consent nothing is sent
+        #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "-")]
+        preview_payload: Option<String>,
+        /// Ignore list (JSON); ignored charts are not sent
+        /// [default: ignore.json in the data directory]
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        ignore_file: Option<String>,
     },
     /// Launch INFINITAS in borderless window mode
     Launch {
         /// bm2dxinf:// URI to launch the game
-        #[arg(long)]
+        #[arg(long, value_hint = ValueHint::Url)]
         url: Option<String>,
         /// Process ID (skip automatic detection)
         #[arg(long)]
@@ -194,27 +837,244 @@ pub enum Command {
     },
     /// Register bm2dxinf:// URI scheme handler
     Register,
+    /// Run every memory parser against the game and print pass/fail per parser
+    ///
+    /// Run with the game at the title or song select screen.
+    Selftest {
+        /// Load offsets from file
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        offsets_file: Option<String>,
+        /// Process ID (skip automatic detection)
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    /// Diagnose privileges and game process access
+    Doctor {
+        /// Process ID (skip automatic detection)
+        #[arg(long)]
+        pid: Option<u32>,
+        /// Session directory holding the tracker instance lock [default: sessions in the data directory]
+        #[arg(long, value_hint = ValueHint::DirPath)]
+        session_dir: Option<String>,
+    },
+    /// Print a shell completion script
+    Completions {
+        /// Target shell
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Print the manpage (roff)
+    Man {
+        /// Write to this file instead of stdout
+        #[arg(long, short, value_name = "FILE", value_hint = ValueHint::FilePath)]
+        output: Option<String>,
+    },
     /// Upload tracker data to the web service
     Upload {
-        /// Tracker TSV file path
-        #[arg(long, short = 't', default_value = "tracker.tsv")]
-        tracker: String,
+        /// Tracker TSV file path [default: tracker.tsv in the data directory]
+        #[arg(long, short = 't', value_hint = ValueHint::FilePath)]
+        tracker: Option<String>,
         /// Title mapping JSON file path
-        #[arg(long, short = 'm', default_value = "title-mapping.json")]
+        #[arg(long, short = 'm', default_value = "title-mapping.json", value_hint = ValueHint::FilePath)]
         mapping: String,
         /// API endpoint URL
-        #[arg(long, env = "INFST_API_ENDPOINT")]
+        #[arg(long, env = "INFST_API_ENDPOINT", value_hint = ValueHint::Url)]
         endpoint: Option<String>,
         /// API token
         #[arg(long, env = "INFST_API_TOKEN")]
         token: Option<String>,
+        /// Send without asking first
+        #[arg(long, env = "INFST_API_CONSENT")]
+        api_consent: bool,
+        /// Show the exact request body before sending (to FILE, or the console
+        /// without a value); without consent nothing is sent
+        #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "-")]
+        preview_payload: Option<String>,
     },
 }
 
+/// Handling of premature plays (`--premature`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PrematurePolicy {
+    /// Record the play, flagged as a premature end
+    Flag,
+    /// Don't record the play
+    Ignore,
+    /// Record judge counts only (no lamp or grade)
+    JudgeOnly,
+}
+
+impl From<PrematurePolicy> for infst::PrematureMode {
+    fn from(policy: PrematurePolicy) -> Self {
+        match policy {
+            PrematurePolicy::Flag => Self::Flag,
+            PrematurePolicy::Ignore => Self::Ignore,
+            PrematurePolicy::JudgeOnly => Self::JudgeOnly,
+        }
+    }
+}
+
+/// String encoding for `search --string`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum StringEncoding {
+    #[default]
+    ShiftJis,
+    #[value(name = "utf-8")]
+    Utf8,
+    #[value(name = "utf-16le")]
+    Utf16le,
+    Ascii,
+}
+
+impl From<StringEncoding> for infst::debug::StringEncoding {
+    fn from(encoding: StringEncoding) -> Self {
+        match encoding {
+            StringEncoding::ShiftJis => Self::ShiftJis,
+            StringEncoding::Utf8 => Self::Utf8,
+            StringEncoding::Utf16le => Self::Utf16le,
+            StringEncoding::Ascii => Self::Ascii,
+        }
+    }
+}
+
+/// Value type read by `watch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum WatchType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    F32,
+    F64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
 #[derive(Clone, clap::ValueEnum)]
 pub enum ExportFormat {
     Tsv,
     Json,
+    /// beatoraja / LR2-compatible score entries (requires --hash-map)
+    Beatoraja,
+}
+
+impl Command {
+    /// Subcommand name as typed on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::FindOffsets { .. } => "find-offsets",
+            Self::Analyze { .. } => "analyze",
+            Self::Status { .. } => "status",
+            Self::Dump { .. } => "dump",
+            Self::Scan { .. } => "scan",
+            Self::Explore { .. } => "explore",
+            Self::DiscoverLayout { .. } => "discover-layout",
+            Self::Hexdump { .. } => "hexdump",
+            Self::Search { .. } => "search",
+            Self::StructDiff { .. } => "struct-diff",
+            Self::Watch { .. } => "watch",
+            Self::Shell { .. } => "shell",
+            Self::Fixture { .. } => "fixture",
+            Self::Offset { .. } => "offset",
+            Self::Validate { .. } => "validate",
+            Self::Export { .. } => "export",
+            Self::Verify { .. } => "verify",
+            Self::Diff { .. } => "diff",
+            Self::Scores { .. } => "scores",
+            Self::Tracker { .. } => "tracker",
+            Self::TrackerDiff { .. } => "tracker-diff",
+            Self::Merge { .. } => "merge",
+            Self::Songs { .. } => "songs",
+            Self::Recommend { .. } => "recommend",
+            Self::Unlocks { .. } => "unlocks",
+            Self::Session { .. } => "session",
+            Self::Login { .. } => "login",
+            Self::Sync { .. } => "sync",
+            Self::Launch { .. } => "launch",
+            Self::Register => "register",
+            Self::Selftest { .. } => "selftest",
+            Self::Doctor { .. } => "doctor",
+            Self::Completions { .. } => "completions",
+            Self::Man { .. } => "man",
+            Self::Upload { .. } => "upload",
+        }
+    }
+
+    /// Whether the command emits a result object with `--output json`
+    ///
+    /// Interactive and long-running commands have no single result and
+    /// reject JSON mode.
+    pub fn supports_json(&self) -> bool {
+        !matches!(
+            self,
+            Self::Analyze { .. }
+                | Self::Explore { .. }
+                | Self::Shell { .. }
+                | Self::Fixture { .. }
+                | Self::Offset { .. }
+                | Self::Merge { .. }
+                | Self::Login { .. }
+                | Self::Launch { .. }
+                | Self::Register
+        )
+    }
+}
+
+#[derive(Subcommand)]
+pub enum SessionAction {
+    /// Pause the session (plays are flagged, or skipped with --ignore-paused-plays)
+    Pause,
+    /// Resume a paused session
+    Resume,
+    /// Add a note to the session timeline
+    Note {
+        /// Note text (e.g., "switched to new controller")
+        text: String,
+    },
+    /// Copy the last play result to the clipboard (see --clipboard-template)
+    Copy,
+    /// Export the tracker file now
+    Export,
+    /// Hide the stream marquee, or show it again
+    Marquee,
+}
+
+#[derive(Subcommand)]
+pub enum ScoresAction {
+    /// Save the in-game best of every played chart to a JSON snapshot
+    Snapshot {
+        /// Snapshot file to write
+        #[arg(long, short, value_hint = ValueHint::FilePath)]
+        output: String,
+        /// Process ID (skip automatic detection)
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    /// List charts whose in-game best changed between two snapshots
+    Diff {
+        /// Older snapshot
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        before: String,
+        /// Newer snapshot
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        after: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TrackerAction {
+    /// List reads that were lower than the stored bests (bad reads or another profile)
+    Audit,
 }
 
 #[derive(Subcommand)]
@@ -228,4 +1088,16 @@ pub enum ValidateTarget {
         #[arg(long)]
         pid: Option<u32>,
     },
+    /// Verify the in-memory song database against a canonical song list
+    SongDb {
+        /// Canonical song list JSON (file path or http(s) URL)
+        #[arg(long, value_hint = ValueHint::FilePath)]
+        song_list: String,
+        /// Maximum number of mismatches to print
+        #[arg(long, default_value = "50")]
+        limit: usize,
+        /// Process ID (skip automatic detection)
+        #[arg(long)]
+        pid: Option<u32>,
+    },
 }
@@ -1,13 +1,208 @@
 //! Common CLI utility functions shared across commands.
 
-use anyhow::Result;
-use infst::ProcessHandle;
+use std::collections::HashSet;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::output::outln;
+use anyhow::{Context, Result, bail};
+use infst::{
+    DensityMap, IgnoreList, ProcessCandidate, ProcessHandle, ProcessMatcher, ProcessSelection,
+    SongTags, TitleAliases, TitleLanguage, TitleOptions,
+};
+
+/// Process selection rule from `--process-select` (set once at startup)
+static PROCESS_SELECTION: OnceLock<ProcessSelection> = OnceLock::new();
+
+/// Which processes count as the game, from `--process-name`/`--process-title`/`--process-path`
+static PROCESS_MATCHER: OnceLock<ProcessMatcher> = OnceLock::new();
+
+/// Set the rule used when several game processes are running.
+pub fn set_process_selection(selection: ProcessSelection) {
+    let _ = PROCESS_SELECTION.set(selection);
+}
+
+/// Set which processes count as the game (`bm2dx.exe` unless set).
+pub fn set_process_matcher(matcher: ProcessMatcher) {
+    let _ = PROCESS_MATCHER.set(matcher);
+}
+
+/// Processes that count as the game
+pub fn process_matcher() -> ProcessMatcher {
+    PROCESS_MATCHER.get().cloned().unwrap_or_default()
+}
+
+/// List the running processes that count as the game.
+pub fn enumerate_processes() -> Result<Vec<ProcessCandidate>> {
+    Ok(ProcessHandle::enumerate_matching(&process_matcher())?)
+}
 
 /// Open a game process by PID or auto-detect.
 pub fn open_process(pid: Option<u32>) -> Result<ProcessHandle> {
     if let Some(pid) = pid {
         Ok(ProcessHandle::open(pid)?)
     } else {
-        Ok(ProcessHandle::find_and_open()?)
+        find_process()
+    }
+}
+
+/// Find the game process, asking which one to use when several are running.
+///
+/// With `--process-select` (or without a terminal) the rule decides instead.
+pub fn find_process() -> Result<ProcessHandle> {
+    let candidates = enumerate_processes()?;
+    if candidates.len() <= 1 || PROCESS_SELECTION.get().is_some() || !io::stdin().is_terminal() {
+        return find_process_by_rule();
+    }
+
+    print_candidates(&candidates);
+    let index = prompt_index(candidates.len())?;
+    Ok(ProcessHandle::open(candidates[index].pid)?)
+}
+
+/// Find the game process using the `--process-select` rule (first match by default).
+///
+/// Used by polling loops, which must not block on input.
+pub fn find_process_by_rule() -> Result<ProcessHandle> {
+    let selection = PROCESS_SELECTION.get().cloned().unwrap_or_default();
+    Ok(ProcessHandle::find_and_open_matching(
+        &process_matcher(),
+        &selection,
+    )?)
+}
+
+/// Load the song tags from `--tags-file`, or `default` (the data directory's tags file).
+///
+/// A missing default file means no tags; an explicitly given file must exist.
+pub fn load_tags(explicit: Option<&str>, default: &Path) -> Result<Option<SongTags>> {
+    let path = explicit.map_or(default, Path::new);
+    match SongTags::load(path) {
+        Ok(tags) => Ok(Some(tags)),
+        Err(e) if explicit.is_none() && e.is_not_found() => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to load tags from {}", path.display())),
+    }
+}
+
+/// Load the chart density profiles from `--chart-density`, or `default` (the data directory's density file).
+///
+/// A missing default file means no profiles; an explicitly given file must exist.
+pub fn load_density(explicit: Option<&str>, default: &Path) -> Result<Option<DensityMap>> {
+    let path = explicit.map_or(default, Path::new);
+    match DensityMap::load(path) {
+        Ok(density) => Ok(Some(density)),
+        Err(e) if explicit.is_none() && e.is_not_found() => Ok(None),
+        Err(e) => {
+            Err(e).with_context(|| format!("Failed to load chart density from {}", path.display()))
+        }
+    }
+}
+
+/// Load the ignore list from `--ignore-file`, or `default` (the data directory's ignore file).
+///
+/// A missing default file means nothing is ignored; an explicitly given file must exist.
+pub fn load_ignore_list(explicit: Option<&str>, default: &Path) -> Result<IgnoreList> {
+    let path = explicit.map_or(default, Path::new);
+    match IgnoreList::load(path) {
+        Ok(list) => Ok(list),
+        Err(e) if explicit.is_none() && e.is_not_found() => Ok(IgnoreList::new()),
+        Err(e) => {
+            Err(e).with_context(|| format!("Failed to load ignore list from {}", path.display()))
+        }
+    }
+}
+
+/// Build title options from the title language flags and `--title-aliases`,
+/// or `default` (the data directory's title alias file).
+///
+/// The alias file is only read when it is given explicitly or a language is
+/// romanized. A missing default file means no aliases; an explicitly given
+/// file must exist.
+pub fn load_title_options(
+    primary: TitleLanguage,
+    secondary: Option<TitleLanguage>,
+    explicit: Option<&str>,
+    default: &Path,
+) -> Result<TitleOptions> {
+    let romanized =
+        primary == TitleLanguage::Romanized || secondary == Some(TitleLanguage::Romanized);
+    let aliases = if explicit.is_none() && !romanized {
+        TitleAliases::new()
+    } else {
+        let path = explicit.map_or(default, Path::new);
+        match TitleAliases::load(path) {
+            Ok(aliases) => aliases,
+            Err(e) if explicit.is_none() && e.is_not_found() => TitleAliases::new(),
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("Failed to load title aliases from {}", path.display())
+                });
+            }
+        }
+    };
+    Ok(TitleOptions {
+        primary,
+        secondary,
+        aliases,
+    })
+}
+
+/// Songs carrying any of the `--tag` names (`None` when no tag is given).
+pub fn tagged_songs(tags: Option<&SongTags>, names: &[String]) -> Result<Option<HashSet<u32>>> {
+    if names.is_empty() {
+        return Ok(None);
+    }
+    let Some(tags) = tags else {
+        bail!("--tag needs a tags file (tags.json in the data directory or --tags-file)");
+    };
+    Ok(Some(tags.songs_with_any(names)?))
+}
+
+/// Whether `error` is an access-denied failure opening the game process.
+pub fn is_access_denied(error: &anyhow::Error) -> bool {
+    core_error(error).is_some_and(infst::Error::is_access_denied)
+}
+
+/// The first `infst::Error` in the error chain, if any
+pub fn core_error(error: &anyhow::Error) -> Option<&infst::Error> {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<infst::Error>())
+}
+
+/// Print an error with its remediation hint and retryability
+pub fn report_error(error: &anyhow::Error) {
+    eprintln!("Error: {:#}", error);
+    if let Some(core) = core_error(error) {
+        if let Some(hint) = core.hint() {
+            eprintln!("Hint: {}", hint);
+        }
+        if core.is_retryable() {
+            eprintln!("This error may be temporary; running the command again may succeed.");
+        }
+    }
+}
+
+/// Print numbered process candidates.
+pub fn print_candidates(candidates: &[ProcessCandidate]) {
+    outln!("Multiple game processes found:");
+    for (i, candidate) in candidates.iter().enumerate() {
+        outln!("  [{}] {}", i + 1, candidate);
+    }
+}
+
+fn prompt_index(count: usize) -> Result<usize> {
+    let stdin = io::stdin();
+    loop {
+        print!("Select process [1-{}]: ", count);
+        io::stdout().flush().ok();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            bail!("No process selected");
+        }
+        match line.trim().parse::<usize>() {
+            Ok(n) if (1..=count).contains(&n) => return Ok(n - 1),
+            _ => eprintln!("Invalid selection, please try again"),
+        }
     }
 }
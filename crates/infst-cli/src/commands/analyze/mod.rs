@@ -9,13 +9,17 @@ mod song_counter;
 mod title_search;
 
 use anyhow::{Result, bail};
-use infst::{MemoryReader, OffsetSearcher, ProcessHandle, SongInfo};
+use infst::util::hex::parse_address;
+use infst::{LayoutDiscovery, MemoryReader, OffsetSearcher, ProcessHandle, SongInfo};
 
-use super::hex_utils::parse_hex_address;
+use crate::cli_utils;
 use pattern_search::search_song_patterns;
-use song_counter::{count_songs_new_structure, count_songs_old_structure};
+use song_counter::count_songs_new_structure;
 use title_search::search_for_title_strings;
 
+/// Bytes of the song list read for layout discovery (1MB)
+const DISCOVERY_SIZE: usize = 1024 * 1024;
+
 /// Run the memory structure analysis mode.
 pub fn run(address: Option<String>, pid: Option<u32>) -> Result<()> {
     let current_version = env!("CARGO_PKG_VERSION");
@@ -27,7 +31,7 @@ pub fn run(address: Option<String>, pid: Option<u32>) -> Result<()> {
         ProcessHandle::open(pid)?
     } else {
         println!("Searching for INFINITAS...");
-        ProcessHandle::find_and_open()?
+        cli_utils::find_process()?
     };
 
     println!(
@@ -39,7 +43,7 @@ pub fn run(address: Option<String>, pid: Option<u32>) -> Result<()> {
 
     // Parse address or search for it
     let analyze_addr = if let Some(addr_str) = address {
-        parse_hex_address(&addr_str)?
+        parse_address(&addr_str)?
     } else {
         // Search for new structure using song_id pattern
         println!("No address specified, searching for song data structures...");
@@ -79,11 +83,22 @@ pub fn run(address: Option<String>, pid: Option<u32>) -> Result<()> {
         Err(e) => println!("  Old structure read failed: {}", e),
     }
 
-    // Count songs with old structure
+    // Infer the entry layout instead of trying fixed entry sizes
     println!();
-    println!("=== Song count analysis ===");
-    let old_count = count_songs_old_structure(&reader, analyze_addr);
-    println!("  Old structure (0x3F0): {} songs", old_count);
+    println!("=== Entry layout discovery ===");
+    match LayoutDiscovery::new().read(&reader, analyze_addr, DISCOVERY_SIZE) {
+        Ok(found) => {
+            println!(
+                "  Stride: 0x{:X} ({} entries with a title)",
+                found.stride, found.entries
+            );
+            match found.song_entry_layout() {
+                Ok(layout) => println!("  Layout: {:?}", layout),
+                Err(e) => println!("  Incomplete layout: {}", e),
+            }
+        }
+        Err(e) => println!("  Discovery failed: {}", e),
+    }
 
     // Count songs with new structure
     let new_count = count_songs_new_structure(&reader, analyze_addr);
@@ -92,10 +107,10 @@ pub fn run(address: Option<String>, pid: Option<u32>) -> Result<()> {
     // Comprehensive search for song data in memory
     println!();
     println!("=== Searching for song data patterns in memory ===");
-    search_song_patterns(&reader, process.base_address, process.module_size as u64);
+    search_song_patterns(&reader, process.base_address, process.module_size);
 
     // Search for known song titles
-    search_for_title_strings(&reader, process.base_address, process.module_size as u64);
+    search_for_title_strings(&reader, process.base_address, process.module_size);
 
     Ok(())
 }
@@ -3,49 +3,24 @@
 use infst::{MemoryReader, ReadMemory};
 
 use super::id_search::search_consecutive_song_ids;
+use infst::debug::{SONG_IDS, SearchPattern, default_range, find_matches};
 
 /// Search for various song data patterns in memory.
-pub fn search_song_patterns(reader: &MemoryReader, base: u64, module_size: u64) {
+pub fn search_song_patterns(reader: &MemoryReader, base: u64, module_size: u32) {
     // Search for song_id=1001 followed by folder=43 pattern
     let pattern_1001_43: [u8; 8] = [0xE9, 0x03, 0x00, 0x00, 0x2B, 0x00, 0x00, 0x00];
 
     println!("  Searching for song_id=1001 + folder=43 pattern...");
 
-    // Read large chunks of memory and search
-    let search_start = base + 0x1000000; // Start 16MB into the module
-    let search_end = base + module_size.min(0x5000000); // Up to 80MB
-    let chunk_size: usize = 4 * 1024 * 1024; // 4MB chunks
+    let (search_start, search_end) = default_range(base, module_size);
+    let pattern = SearchPattern::exact(pattern_1001_43.to_vec());
+    let found_addresses = find_matches(reader, &pattern, search_start, search_end, usize::MAX);
 
-    let mut found_addresses: Vec<u64> = Vec::new();
-    let mut offset = 0u64;
+    for &found_addr in found_addresses.iter().take(10) {
+        println!("    Found at 0x{:X}", found_addr);
 
-    while search_start + offset < search_end {
-        let addr = search_start + offset;
-        let read_size = chunk_size.min((search_end - addr) as usize);
-
-        match reader.read_bytes(addr, read_size) {
-            Ok(buffer) => {
-                // Search for pattern
-                for (i, window) in buffer.windows(8).enumerate() {
-                    if window == pattern_1001_43 {
-                        let found_addr = addr + i as u64;
-                        found_addresses.push(found_addr);
-
-                        if found_addresses.len() <= 10 {
-                            println!("    Found at 0x{:X}", found_addr);
-
-                            // Try to analyze structure at this location
-                            analyze_potential_song_entry(reader, found_addr);
-                        }
-                    }
-                }
-            }
-            Err(_) => {
-                // Skip unreadable regions
-            }
-        }
-
-        offset += chunk_size as u64;
+        // Try to analyze structure at this location
+        analyze_potential_song_entry(reader, found_addr);
     }
 
     println!("  Total matches found: {}", found_addresses.len());
@@ -90,7 +65,7 @@ pub fn analyze_potential_song_entry(reader: &MemoryReader, addr: u64) {
                     i32::from_le_bytes([next_buf[4], next_buf[5], next_buf[6], next_buf[7]]);
 
                 // Check if next entry looks valid (song_id 1001-50000, folder 1-50)
-                if (1000..=50000).contains(&next_id) && (1..=50).contains(&next_folder) {
+                if SONG_IDS.contains(next_id as i64) && (1..=50).contains(&next_folder) {
                     println!(
                         "        -> Entry size {} works: next song_id={}",
                         entry_size, next_id
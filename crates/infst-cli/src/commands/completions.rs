@@ -0,0 +1,554 @@
+//! Shell completion script generation.
+//!
+//! Scripts are generated from the clap command tree, so new subcommands,
+//! possible values (export formats, output modes) and value hints (file and
+//! directory arguments) are picked up without touching this module.
+
+use std::fmt::Write;
+use std::io::Write as _;
+
+use anyhow::Result;
+use clap::{Arg, ArgAction, Command, CommandFactory, ValueHint};
+
+use crate::cli::{Args, Shell};
+use crate::output;
+
+/// Run the completions command
+pub fn run(shell: Shell) -> Result<()> {
+    let script = generate(shell, &mut Args::command());
+    if output::is_json() {
+        output::emit(&serde_json::json!({ "script": script }));
+    } else {
+        std::io::stdout().write_all(script.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Generate a completion script for `shell`
+pub fn generate(shell: Shell, cmd: &mut Command) -> String {
+    // Propagate global args and add the implicit help/version flags
+    cmd.build();
+    match shell {
+        Shell::Bash => bash(cmd),
+        Shell::Zsh => zsh(cmd),
+        Shell::Fish => fish(cmd),
+        Shell::Powershell => powershell(cmd),
+    }
+}
+
+/// Every visible command with its path of subcommand names (root has an empty path)
+pub(crate) fn command_tree(cmd: &Command) -> Vec<(Vec<String>, &Command)> {
+    fn walk<'a>(cmd: &'a Command, path: Vec<String>, out: &mut Vec<(Vec<String>, &'a Command)>) {
+        for sub in visible_subcommands(cmd) {
+            let mut sub_path = path.clone();
+            sub_path.push(sub.get_name().to_string());
+            walk(sub, sub_path, out);
+        }
+        out.push((path, cmd));
+    }
+    let mut out = Vec::new();
+    walk(cmd, Vec::new(), &mut out);
+    out.sort_by(|a, b| a.0.cmp(&b.0));
+    out
+}
+
+pub(crate) fn visible_subcommands(cmd: &Command) -> impl Iterator<Item = &Command> {
+    cmd.get_subcommands().filter(|sub| !sub.is_hide_set())
+}
+
+pub(crate) fn visible_options(cmd: &Command) -> impl Iterator<Item = &Arg> {
+    cmd.get_arguments()
+        .filter(|arg| !arg.is_hide_set() && !arg.is_positional())
+}
+
+/// First line of the argument's help text
+pub(crate) fn help_line(help: Option<&clap::builder::StyledStr>) -> String {
+    help.map(|h| h.to_string().lines().next().unwrap_or_default().to_string())
+        .unwrap_or_default()
+}
+
+pub(crate) fn possible_values(arg: &Arg) -> Vec<String> {
+    arg.get_possible_values()
+        .iter()
+        .filter(|v| !v.is_hide_set())
+        .map(|v| v.get_name().to_string())
+        .collect()
+}
+
+fn takes_value(arg: &Arg) -> bool {
+    arg.get_action().takes_values()
+}
+
+/// All spellings of an option (`-o`, `--output`)
+fn flags(arg: &Arg) -> Vec<String> {
+    let mut flags = Vec::new();
+    if let Some(short) = arg.get_short() {
+        flags.push(format!("-{}", short));
+    }
+    if let Some(long) = arg.get_long() {
+        flags.push(format!("--{}", long));
+    }
+    flags
+}
+
+fn value_name(arg: &Arg) -> String {
+    arg.get_value_names()
+        .and_then(|names| names.first())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| arg.get_id().to_string().to_uppercase())
+}
+
+fn function_name(bin: &str, path: &[String]) -> String {
+    let mut name = format!("_{}", bin);
+    for part in path {
+        name.push_str("__");
+        name.push_str(part);
+    }
+    name.replace('-', "_")
+}
+
+fn bash(cmd: &Command) -> String {
+    let bin = cmd.get_name();
+    let tree = command_tree(cmd);
+    let id = |path: &[String]| {
+        std::iter::once(bin.to_string())
+            .chain(path.iter().cloned())
+            .collect::<Vec<_>>()
+            .join("__")
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "_{}() {{", bin.replace('-', "_"));
+    let _ = writeln!(out, "    local cur prev path i");
+    let _ = writeln!(out, "    COMPREPLY=()");
+    let _ = writeln!(out, "    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"");
+    let _ = writeln!(out, "    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"");
+    let _ = writeln!(out, "    path=\"{}\"", bin);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    for ((i = 1; i < COMP_CWORD; i++)); do");
+    let _ = writeln!(out, "        case \"${{path}}:${{COMP_WORDS[i]}}\" in");
+    for (path, node) in &tree {
+        for sub in visible_subcommands(node) {
+            let mut sub_path = path.clone();
+            sub_path.push(sub.get_name().to_string());
+            let _ = writeln!(
+                out,
+                "            {}:{}) path=\"{}\" ;;",
+                id(path),
+                sub.get_name(),
+                id(&sub_path)
+            );
+        }
+    }
+    let _ = writeln!(out, "        esac");
+    let _ = writeln!(out, "    done");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    case \"${{path}}\" in");
+    for (path, node) in &tree {
+        let words: Vec<String> = visible_options(node)
+            .flat_map(flags)
+            .chain(visible_subcommands(node).map(|sub| sub.get_name().to_string()))
+            .collect();
+        let _ = writeln!(out, "        {})", id(path));
+        let valued: Vec<&Arg> = visible_options(node).filter(|a| takes_value(a)).collect();
+        if !valued.is_empty() {
+            let _ = writeln!(out, "            case \"${{prev}}\" in");
+            for arg in valued {
+                let values = possible_values(arg);
+                let reply = if !values.is_empty() {
+                    format!(
+                        "COMPREPLY=($(compgen -W \"{}\" -- \"${{cur}}\"))",
+                        values.join(" ")
+                    )
+                } else {
+                    match arg.get_value_hint() {
+                        ValueHint::FilePath | ValueHint::AnyPath | ValueHint::ExecutablePath => {
+                            "COMPREPLY=($(compgen -f -- \"${cur}\"))".to_string()
+                        }
+                        ValueHint::DirPath => "COMPREPLY=($(compgen -d -- \"${cur}\"))".to_string(),
+                        _ => "COMPREPLY=()".to_string(),
+                    }
+                };
+                let _ = writeln!(out, "                {})", flags(arg).join("|"));
+                let _ = writeln!(out, "                    {}", reply);
+                let _ = writeln!(out, "                    return 0");
+                let _ = writeln!(out, "                    ;;");
+            }
+            let _ = writeln!(out, "            esac");
+        }
+        let _ = writeln!(
+            out,
+            "            COMPREPLY=($(compgen -W \"{}\" -- \"${{cur}}\"))",
+            words.join(" ")
+        );
+        let _ = writeln!(out, "            ;;");
+    }
+    let _ = writeln!(out, "    esac");
+    let _ = writeln!(out, "}}");
+    let _ = writeln!(out);
+    // `-o default` falls back to file names for positional arguments
+    let _ = writeln!(
+        out,
+        "complete -F _{} -o bashdefault -o default {}",
+        bin.replace('-', "_"),
+        bin
+    );
+    out
+}
+
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+fn zsh_action(arg: &Arg) -> String {
+    let values = possible_values(arg);
+    if !values.is_empty() {
+        return format!("({})", values.join(" "));
+    }
+    match arg.get_value_hint() {
+        ValueHint::FilePath | ValueHint::AnyPath | ValueHint::ExecutablePath => {
+            "_files".to_string()
+        }
+        ValueHint::DirPath => "_files -/".to_string(),
+        ValueHint::Url => "_urls".to_string(),
+        _ => " ".to_string(),
+    }
+}
+
+fn zsh_specs(cmd: &Command) -> Vec<String> {
+    let mut specs = Vec::new();
+    for arg in visible_options(cmd) {
+        let all_flags = flags(arg);
+        let exclusion = if all_flags.len() > 1 {
+            format!("({})", all_flags.join(" "))
+        } else {
+            String::new()
+        };
+        let repeat = if matches!(arg.get_action(), ArgAction::Append | ArgAction::Count) {
+            "*"
+        } else {
+            ""
+        };
+        let help = zsh_escape(&help_line(arg.get_help()));
+        for flag in &all_flags {
+            let spec = if takes_value(arg) {
+                let suffix = if flag.starts_with("--") { "=" } else { "+" };
+                format!(
+                    "{}{}{}{}[{}]:{}:{}",
+                    exclusion,
+                    repeat,
+                    flag,
+                    suffix,
+                    help,
+                    zsh_escape(&value_name(arg)),
+                    zsh_action(arg)
+                )
+            } else {
+                format!("{}{}{}[{}]", exclusion, repeat, flag, help)
+            };
+            specs.push(format!("'{}'", spec));
+        }
+    }
+    for (index, arg) in cmd.get_positionals().enumerate() {
+        specs.push(format!(
+            "'{}:{} -- {}:{}'",
+            index + 1,
+            zsh_escape(arg.get_id().as_ref()),
+            zsh_escape(&help_line(arg.get_help())),
+            zsh_action(arg)
+        ));
+    }
+    specs
+}
+
+fn zsh(cmd: &Command) -> String {
+    let bin = cmd.get_name();
+    let mut out = String::new();
+    let _ = writeln!(out, "#compdef {}", bin);
+    for (path, node) in command_tree(cmd) {
+        let name = function_name(bin, &path);
+        let mut specs = zsh_specs(node);
+        let has_subcommands = visible_subcommands(node).next().is_some();
+        if has_subcommands {
+            specs.push("'1:command:->commands'".to_string());
+            specs.push("'*::arg:->args'".to_string());
+        }
+
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{}() {{", name);
+        let _ = writeln!(out, "    local curcontext=\"$curcontext\" state line");
+        let _ = write!(out, "    _arguments -C -s");
+        for spec in &specs {
+            let _ = write!(out, " \\\n        {}", spec);
+        }
+        let _ = writeln!(out);
+        if has_subcommands {
+            let _ = writeln!(out, "    case $state in");
+            let _ = writeln!(out, "        commands)");
+            let _ = writeln!(out, "            local -a commands");
+            let _ = writeln!(out, "            commands=(");
+            for sub in visible_subcommands(node) {
+                let _ = writeln!(
+                    out,
+                    "                '{}:{}'",
+                    sub.get_name(),
+                    zsh_escape(&help_line(sub.get_about()))
+                );
+            }
+            let _ = writeln!(out, "            )");
+            let _ = writeln!(out, "            _describe -t commands 'command' commands");
+            let _ = writeln!(out, "            ;;");
+            let _ = writeln!(out, "        args)");
+            let _ = writeln!(out, "            case $line[1] in");
+            for sub in visible_subcommands(node) {
+                let mut sub_path = path.clone();
+                sub_path.push(sub.get_name().to_string());
+                let _ = writeln!(
+                    out,
+                    "                {}) {} ;;",
+                    sub.get_name(),
+                    function_name(bin, &sub_path)
+                );
+            }
+            let _ = writeln!(out, "            esac");
+            let _ = writeln!(out, "            ;;");
+            let _ = writeln!(out, "    esac");
+        }
+        let _ = writeln!(out, "}}");
+    }
+    let root = function_name(bin, &[]);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "if [ \"$funcstack[1]\" = \"{}\" ]; then", root);
+    let _ = writeln!(out, "    {} \"$@\"", root);
+    let _ = writeln!(out, "else");
+    let _ = writeln!(out, "    compdef {} {}", root, bin);
+    let _ = writeln!(out, "fi");
+    out
+}
+
+fn fish_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+fn fish(cmd: &Command) -> String {
+    let bin = cmd.get_name();
+    let mut out = String::new();
+    for (path, node) in command_tree(cmd) {
+        let subs: Vec<&str> = visible_subcommands(node).map(|s| s.get_name()).collect();
+        let mut conditions: Vec<String> = path
+            .iter()
+            .map(|part| format!("__fish_seen_subcommand_from {}", part))
+            .collect();
+        if !subs.is_empty() {
+            if path.is_empty() {
+                conditions.push("__fish_use_subcommand".to_string());
+            } else {
+                conditions.push(format!(
+                    "not __fish_seen_subcommand_from {}",
+                    subs.join(" ")
+                ));
+            }
+        }
+        let condition = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" -n \"{}\"", conditions.join("; and "))
+        };
+
+        for arg in visible_options(node) {
+            let mut line = format!("complete -c {}{}", bin, condition);
+            if let Some(short) = arg.get_short() {
+                let _ = write!(line, " -s {}", short);
+            }
+            if let Some(long) = arg.get_long() {
+                let _ = write!(line, " -l {}", long);
+            }
+            if takes_value(arg) {
+                line.push_str(" -r");
+                let values = possible_values(arg);
+                if !values.is_empty() {
+                    let _ = write!(line, " -f -a \"{}\"", values.join(" "));
+                } else {
+                    match arg.get_value_hint() {
+                        ValueHint::FilePath | ValueHint::AnyPath | ValueHint::ExecutablePath => {
+                            line.push_str(" -F")
+                        }
+                        ValueHint::DirPath => {
+                            line.push_str(" -f -a \"(__fish_complete_directories)\"")
+                        }
+                        _ => line.push_str(" -f"),
+                    }
+                }
+            }
+            let _ = write!(line, " -d '{}'", fish_escape(&help_line(arg.get_help())));
+            let _ = writeln!(out, "{}", line);
+        }
+        for sub in visible_subcommands(node) {
+            let _ = writeln!(
+                out,
+                "complete -c {}{} -f -a \"{}\" -d '{}'",
+                bin,
+                condition,
+                sub.get_name(),
+                fish_escape(&help_line(sub.get_about()))
+            );
+        }
+    }
+    out
+}
+
+fn powershell_escape(text: &str) -> String {
+    text.replace('\'', "''")
+}
+
+fn powershell(cmd: &Command) -> String {
+    let bin = cmd.get_name();
+    let mut out = String::new();
+    let _ = writeln!(out, "using namespace System.Management.Automation");
+    let _ = writeln!(out, "using namespace System.Management.Automation.Language");
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "Register-ArgumentCompleter -Native -CommandName '{}' -ScriptBlock {{",
+        bin
+    );
+    let _ = writeln!(
+        out,
+        "    param($wordToComplete, $commandAst, $cursorPosition)"
+    );
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    $commandElements = $commandAst.CommandElements");
+    let _ = writeln!(out, "    $command = @(");
+    let _ = writeln!(out, "        '{}'", bin);
+    let _ = writeln!(
+        out,
+        "        for ($i = 1; $i -lt $commandElements.Count; $i++) {{"
+    );
+    let _ = writeln!(out, "            $element = $commandElements[$i]");
+    let _ = writeln!(
+        out,
+        "            if ($element -isnot [StringConstantExpressionAst] -or"
+    );
+    let _ = writeln!(
+        out,
+        "                $element.StringConstantType -ne [StringConstantType]::BareWord -or"
+    );
+    let _ = writeln!(out, "                $element.Value.StartsWith('-') -or");
+    let _ = writeln!(
+        out,
+        "                $element.Value -eq $wordToComplete) {{"
+    );
+    let _ = writeln!(out, "                break");
+    let _ = writeln!(out, "            }}");
+    let _ = writeln!(out, "            $element.Value");
+    let _ = writeln!(out, "        }}) -join ';'");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    $completions = @(switch ($command) {{");
+    for (path, node) in command_tree(cmd) {
+        let key = std::iter::once(bin.to_string())
+            .chain(path.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(";");
+        let _ = writeln!(out, "        '{}' {{", powershell_escape(&key));
+        for arg in visible_options(node) {
+            let help = help_line(arg.get_help());
+            for flag in flags(arg) {
+                let tooltip = if help.is_empty() {
+                    flag.clone()
+                } else {
+                    help.clone()
+                };
+                let _ = writeln!(
+                    out,
+                    "            [CompletionResult]::new('{0}', '{0}', [CompletionResultType]::ParameterName, '{1}')",
+                    flag,
+                    powershell_escape(&tooltip)
+                );
+            }
+        }
+        for sub in visible_subcommands(node) {
+            let about = help_line(sub.get_about());
+            let tooltip = if about.is_empty() {
+                sub.get_name().to_string()
+            } else {
+                about
+            };
+            let _ = writeln!(
+                out,
+                "            [CompletionResult]::new('{0}', '{0}', [CompletionResultType]::ParameterValue, '{1}')",
+                sub.get_name(),
+                powershell_escape(&tooltip)
+            );
+        }
+        let _ = writeln!(out, "            break");
+        let _ = writeln!(out, "        }}");
+    }
+    let _ = writeln!(out, "    }})");
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "    $completions.Where{{ $_.CompletionText -like \"$wordToComplete*\" }} |"
+    );
+    let _ = writeln!(out, "        Sort-Object -Property ListItemText");
+    let _ = writeln!(out, "}}");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(shell: Shell) -> String {
+        generate(shell, &mut Args::command())
+    }
+
+    #[test]
+    fn test_command_tree_includes_nested_subcommands() {
+        let mut cmd = Args::command();
+        cmd.build();
+        let paths: Vec<Vec<String>> = command_tree(&cmd).into_iter().map(|(p, _)| p).collect();
+        assert_eq!(paths[0], Vec::<String>::new());
+        assert!(paths.contains(&vec!["validate".to_string(), "song-db".to_string()]));
+        assert!(paths.contains(&vec!["completions".to_string()]));
+    }
+
+    #[test]
+    fn test_bash_completes_values_and_files() {
+        let bash = script(Shell::Bash);
+        assert!(bash.contains("infst__validate:song-db) path=\"infst__validate__song-db\""));
+        assert!(bash.contains("compgen -W \"tsv json beatoraja\""));
+        assert!(bash.contains("compgen -d"));
+        assert!(bash.contains("complete -F _infst"));
+    }
+
+    #[test]
+    fn test_zsh_escapes_help_text() {
+        let zsh = script(Shell::Zsh);
+        assert!(zsh.starts_with("#compdef infst\n"));
+        assert!(zsh.contains("_infst__validate() {"));
+        assert!(zsh.contains("--format=[Output format]:FORMAT:(tsv json beatoraja)'"));
+        assert!(zsh.contains("title\\:<text>"));
+    }
+
+    #[test]
+    fn test_fish_conditions() {
+        let fish = script(Shell::Fish);
+        assert!(fish.contains(
+            "complete -c infst -n \"__fish_use_subcommand\" -f -a \"scan\" -d 'Scan for song database'"
+        ));
+        assert!(fish.contains(
+            "-n \"__fish_seen_subcommand_from validate; and __fish_seen_subcommand_from song-entry\""
+        ));
+        assert!(fish.contains("-l format -r -f -a \"tsv json beatoraja\""));
+    }
+
+    #[test]
+    fn test_powershell_lists_subcommands() {
+        let ps = script(Shell::Powershell);
+        assert!(ps.contains("'infst;validate' {"));
+        assert!(ps.contains("[CompletionResult]::new('song-db', 'song-db'"));
+    }
+}
@@ -0,0 +1,36 @@
+//! Diff command for listing improvements between two tracker exports.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use infst::{Tracker, tracker_changelog};
+
+use crate::output::{self, outln};
+
+/// Print the charts that improved from `old_path` to `new_path`
+pub fn run(old_path: &str, new_path: &Path, min_score_delta: u32) -> Result<()> {
+    let old = Tracker::load(old_path).with_context(|| format!("Failed to load {}", old_path))?;
+    let new = Tracker::load(new_path)
+        .with_context(|| format!("Failed to load {}", new_path.display()))?;
+
+    let changelog = tracker_changelog(&old, &new, min_score_delta);
+    output::emit(&changelog);
+
+    if changelog.is_empty() {
+        eprintln!("No improvements");
+        return Ok(());
+    }
+    for improvement in &changelog {
+        outln!("{}", improvement);
+    }
+
+    let count = |f: fn(&infst::ChartImprovement) -> bool| changelog.iter().filter(|i| f(i)).count();
+    eprintln!(
+        "{} charts improved ({} lamps, {} grades, {} scores)",
+        changelog.len(),
+        count(|i| i.lamp.is_some()),
+        count(|i| i.grade.is_some()),
+        count(|i| i.ex_score.is_some())
+    );
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! Discover-layout command implementation.
+//!
+//! Infers the song entry layout (stride and field offsets) from the song
+//! list of the running game or a memory dump with `infst::LayoutDiscovery`,
+//! compares it with the known layouts, and writes a candidate `GameLayout`
+//! (JSON) for the layout registry. Used when a game update moves the song
+//! entry fields.
+
+use anyhow::{Context, Result, bail};
+use infst::config::{extract_date_code, find_game_version};
+use infst::util::hex::parse_address;
+use infst::{
+    DiscoveredLayout, FieldCandidate, LayoutDiscovery, LayoutRegistry, MemoryReader, ProcessHandle,
+};
+
+use super::fixture::read_dump;
+use crate::cli_utils;
+use crate::output::{self, outln};
+
+/// Source region and output options
+pub struct DiscoverLayoutOptions<'a> {
+    /// Address of the first song list entry (hex)
+    pub address: &'a str,
+    pub size: usize,
+    /// Memory dump to read instead of the game
+    pub dump: Option<&'a str>,
+    /// Address of the first byte of a raw dump (hex)
+    pub base: &'a str,
+    pub since: Option<&'a str>,
+    pub output: Option<&'a str>,
+    pub pid: Option<u32>,
+}
+
+/// Run the discover-layout command
+pub fn run(options: &DiscoverLayoutOptions) -> Result<()> {
+    let address = parse_address(options.address)?;
+    let discovery = LayoutDiscovery::new();
+
+    let (found, detected_since) = match options.dump {
+        Some(path) => {
+            let (base, bytes) = read_dump(path, options.base, Some(address))?;
+            let offset = address
+                .checked_sub(base)
+                .map(|offset| offset as usize)
+                .filter(|&offset| offset < bytes.len())
+                .context("Address is outside the dump")?;
+            let end = bytes.len().min(offset + options.size);
+            (discovery.discover(&bytes[offset..end], address)?, None)
+        }
+        None => {
+            let process = match options.pid {
+                Some(pid) => ProcessHandle::open(pid)?,
+                None => cli_utils::find_process()?,
+            };
+            let reader = MemoryReader::new(&process);
+            let version = find_game_version(&reader, process.base_address)?;
+            let since = version
+                .as_deref()
+                .and_then(extract_date_code)
+                .map(str::to_string);
+            (discovery.read(&reader, address, options.size)?, since)
+        }
+    };
+
+    print_layout(&found);
+
+    let registry = LayoutRegistry::builtin();
+    let song_entry = found.song_entry_layout();
+    match &song_entry {
+        Ok(layout) => match registry.layouts().iter().find(|l| l.song_entry == *layout) {
+            Some(known) => outln!("Matches the known layout of builds from {}", known.since),
+            None => outln!("Doesn't match any known layout"),
+        },
+        Err(e) => outln!("Incomplete layout: {}", e),
+    }
+
+    let Some(path) = options.output else {
+        output::emit(&found);
+        return Ok(());
+    };
+    let Some(since) = options.since.map(str::to_string).or(detected_since) else {
+        bail!("Pass --since <DATE_CODE> for the first build the layout applies to");
+    };
+    let layout = found.game_layout(&since)?;
+    std::fs::write(path, serde_json::to_string_pretty(&layout)?)
+        .with_context(|| format!("Failed to write {path}"))?;
+    outln!(
+        "Wrote candidate layout for builds from {} to {}",
+        since,
+        path
+    );
+    output::emit(&found);
+    Ok(())
+}
+
+fn print_layout(found: &DiscoveredLayout) {
+    let field = |candidate: Option<FieldCandidate>| match candidate {
+        Some(c) => format!("0x{:03X} ({:.0}%)", c.offset, c.confidence * 100.0),
+        None => "not found".to_string(),
+    };
+    outln!("=== Song entry layout at 0x{:X} ===", found.address);
+    outln!(
+        "  Stride:   0x{:X} ({} bytes, {:.0}% of slots titled, {} entries)",
+        found.stride,
+        found.stride,
+        found.stride_confidence * 100.0,
+        found.entries
+    );
+    let strings: Vec<String> = found
+        .strings
+        .iter()
+        .map(|s| format!("0x{:03X}", s.offset))
+        .collect();
+    outln!("  Strings:  {}", strings.join(", "));
+    outln!("  Folder:   {}", field(found.folder));
+    outln!("  Levels:   {}", field(found.levels));
+    outln!("  BPM:      {}", field(found.bpm));
+    outln!("  Notes:    {}", field(found.notes));
+    outln!("  Song ID:  {}", field(found.song_id));
+}
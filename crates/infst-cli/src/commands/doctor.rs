@@ -0,0 +1,168 @@
+//! Doctor command — environment and privilege diagnostics.
+
+use std::path::Path;
+
+use anyhow::Result;
+use infst::process::elevation;
+use infst::storage::{InstanceLock, LockState};
+use infst::{MemoryReader, OffsetSearcher, ProcessHandle, SubsystemHealth};
+use serde::Serialize;
+
+use crate::cli_utils;
+use crate::commands::status;
+use crate::output::{self, outln};
+
+fn yes_no(value: Option<bool>) -> &'static str {
+    match value {
+        Some(true) => "yes",
+        Some(false) => "no",
+        None => "unknown",
+    }
+}
+
+/// Diagnostics for one game process (JSON output)
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProcessReport {
+    pid: u32,
+    elevated: Option<bool>,
+    memory_access: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// Song DB, score map and unlock data loaded through the offsets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    health: Option<SubsystemHealth>,
+}
+
+/// Run the doctor command
+pub fn run(pid: Option<u32>, session_dir: &Path) -> Result<()> {
+    let current_version = env!("CARGO_PKG_VERSION");
+    outln!("infst {} - Doctor", current_version);
+    outln!();
+
+    outln!("=== Privileges ===");
+    let self_elevated = elevation::is_elevated();
+    outln!("  infst elevated: {}", yes_no(Some(self_elevated)));
+
+    outln!();
+    outln!("=== Tracker Lock ===");
+    let lock = lock_report(session_dir);
+
+    outln!();
+    outln!("=== Game Process ===");
+    let pids = match pid {
+        Some(pid) => vec![pid],
+        None => match cli_utils::enumerate_processes() {
+            Ok(candidates) => {
+                for candidate in &candidates {
+                    outln!("  {}", candidate);
+                }
+                candidates.iter().map(|c| c.pid).collect()
+            }
+            Err(e) => {
+                outln!("  Not found: {}", e);
+                output::emit(&serde_json::json!({
+                    "elevated": self_elevated,
+                    "trackerLock": lock,
+                    "processes": [],
+                }));
+                return Ok(());
+            }
+        },
+    };
+
+    let mut reports = Vec::new();
+    for pid in pids {
+        let game_elevated = elevation::is_process_elevated(pid);
+        outln!();
+        outln!("  PID {}", pid);
+        outln!("    Elevated:      {}", yes_no(game_elevated));
+        let mut report = ProcessReport {
+            pid,
+            elevated: game_elevated,
+            memory_access: true,
+            error: None,
+            health: None,
+        };
+        match ProcessHandle::open(pid) {
+            Ok(process) => {
+                outln!("    Memory access: OK");
+                report.health = subsystem_health(&process, session_dir);
+            }
+            Err(e) => {
+                report.memory_access = false;
+                report.error = Some(e.to_string());
+                outln!("    Memory access: FAILED ({})", e);
+                if e.is_access_denied() && !self_elevated {
+                    outln!(
+                        "    Hint: run infst as administrator, or pass --relaunch-as-admin to re-launch automatically"
+                    );
+                }
+            }
+        }
+        reports.push(report);
+    }
+
+    output::emit(&serde_json::json!({
+        "elevated": self_elevated,
+        "trackerLock": lock,
+        "processes": reports,
+    }));
+    Ok(())
+}
+
+/// Load the game data the way the tracker does; `None` when the offsets
+/// can't be found (e.g. the game is still starting)
+fn subsystem_health(process: &ProcessHandle, session_dir: &Path) -> Option<SubsystemHealth> {
+    let reader = MemoryReader::new(process);
+    let offsets = match OffsetSearcher::new(&reader).search_data_offsets() {
+        Ok(offsets) => offsets,
+        Err(e) => {
+            outln!("    Offsets:       not found ({})", e);
+            return None;
+        }
+    };
+    let mut health = SubsystemHealth::collect(&reader, &offsets);
+    health.last_play = status::last_play_time(session_dir);
+    status::print_health(&health);
+    Some(health)
+}
+
+/// Instance lock state (JSON output)
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LockReport {
+    path: String,
+    held: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+fn lock_report(session_dir: &Path) -> LockReport {
+    let path = InstanceLock::path_in(session_dir);
+    outln!("  Lock file: {}", path.display());
+    let mut report = LockReport {
+        path: path.display().to_string(),
+        held: false,
+        pid: None,
+        error: None,
+    };
+    match InstanceLock::state(session_dir) {
+        Ok(LockState::Free) => outln!("  State:     free (no tracker running)"),
+        Ok(LockState::Held { pid }) => {
+            report.held = true;
+            report.pid = pid;
+            match pid {
+                Some(pid) => outln!("  State:     held by tracker (PID {})", pid),
+                None => outln!("  State:     held by a running tracker"),
+            }
+        }
+        Err(e) => {
+            outln!("  State:     unknown ({})", e);
+            report.error = Some(e.to_string());
+        }
+    }
+    report
+}
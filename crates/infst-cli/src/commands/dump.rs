@@ -2,28 +2,38 @@
 //!
 //! Collects and exports detailed memory structure information including offsets,
 //! song entries, and raw memory dumps. Output can be saved as JSON for further
-//! analysis.
+//! analysis. The raw score map (every hashmap node) can be written separately.
 
 use anyhow::Result;
 use infst::{
-    DumpInfo, MemoryReader, OffsetSearcher, ProcessHandle, builtin_signatures, load_offsets,
+    DumpInfo, MemoryReader, OffsetSearcher, ProcessHandle, RawScoreMapDump, builtin_signatures,
+    load_offsets,
 };
 
+use crate::cli_utils;
+use crate::output::outln;
+
 /// Run the dump command
-pub fn run(offsets_file: Option<&str>, pid: Option<u32>, output: Option<&str>) -> Result<()> {
+pub fn run(
+    offsets_file: Option<&str>,
+    pid: Option<u32>,
+    output: Option<&str>,
+    score_map_output: Option<&str>,
+) -> Result<()> {
     let current_version = env!("CARGO_PKG_VERSION");
-    println!("infst {} - Dump Mode", current_version);
+    outln!("infst {} - Dump Mode", current_version);
 
     // Open process
     let process = if let Some(pid) = pid {
         ProcessHandle::open(pid)?
     } else {
-        ProcessHandle::find_and_open()?
+        cli_utils::find_process()?
     };
 
-    println!(
+    outln!(
         "Found process (PID: {}, Base: 0x{:X})",
-        process.pid, process.base_address
+        process.pid,
+        process.base_address
     );
 
     let reader = MemoryReader::new(&process);
@@ -40,53 +50,73 @@ pub fn run(offsets_file: Option<&str>, pid: Option<u32>, output: Option<&str>) -
     // Collect dump
     let dump = DumpInfo::collect(&reader, &offsets);
 
+    if let Some(path) = score_map_output {
+        let score_map = RawScoreMapDump::collect(&reader, offsets.data_map)?;
+        std::fs::write(path, serde_json::to_string_pretty(&score_map)?)?;
+        outln!(
+            "Raw score map saved to: {} ({} nodes in {} buckets)",
+            path,
+            score_map.nodes.len(),
+            score_map.non_empty_buckets
+        );
+    }
+
     if let Some(output_path) = output {
         let json = serde_json::to_string_pretty(&dump)?;
         std::fs::write(output_path, json)?;
-        println!("Dump saved to: {}", output_path);
+        outln!("Dump saved to: {}", output_path);
     } else {
         // Print summary to stdout
-        println!();
-        println!("=== Offsets ===");
-        println!("{}", serde_json::to_string_pretty(&dump.offsets)?);
+        outln!();
+        outln!("=== Offsets ===");
+        outln!("{}", serde_json::to_string_pretty(&dump.offsets)?);
 
-        println!();
-        println!("=== Song Entries (first {}) ===", dump.song_entries.len());
+        outln!();
+        outln!("=== Song Entries (first {}) ===", dump.song_entries.len());
         for entry in &dump.song_entries {
-            println!(
+            outln!(
                 "  [{}] 0x{:X}: id={}, folder={}, title={:?}",
-                entry.index, entry.address, entry.song_id, entry.folder, entry.title
+                entry.index,
+                entry.address,
+                entry.song_id,
+                entry.folder,
+                entry.title
             );
             if let (Some(meta_id), Some(meta_folder)) =
                 (entry.metadata_song_id, entry.metadata_folder)
             {
-                println!("       metadata: id={}, folder={}", meta_id, meta_folder);
+                outln!("       metadata: id={}, folder={}", meta_id, meta_folder);
             }
         }
 
         if let Some(ref song_list_dump) = dump.song_list_dump {
-            println!();
-            println!("=== SongList Memory Dump (first 256 bytes) ===");
+            outln!();
+            outln!("=== SongList Memory Dump (first 256 bytes) ===");
             for line in song_list_dump.hex_dump.iter().take(16) {
-                println!("  {}", line);
+                outln!("  {}", line);
             }
         }
 
-        println!();
-        println!(
+        outln!();
+        outln!(
             "=== Detected Songs ({} total) ===",
             dump.detected_songs.len()
         );
         for (i, song) in dump.detected_songs.iter().take(20).enumerate() {
-            println!(
+            outln!(
                 "  [{}] id={}, folder={}, title={:?} ({})",
-                i, song.song_id, song.folder, song.title, song.source
+                i,
+                song.song_id,
+                song.folder,
+                song.title,
+                song.source
             );
         }
         if dump.detected_songs.len() > 20 {
-            println!("  ... and {} more", dump.detected_songs.len() - 20);
+            outln!("  ... and {} more", dump.detected_songs.len() - 20);
         }
     }
 
+    crate::output::emit(&dump);
     Ok(())
 }
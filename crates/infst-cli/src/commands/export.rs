@@ -1,16 +1,65 @@
 //! Export command for exporting play data.
 
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use infst::chart::DEFAULT_ANCHOR_SONGS;
+use infst::storage::WriteOptions;
 use infst::{
-    MemoryReader, OffsetSearcher, ScoreMap, fetch_song_database, generate_tracker_json,
-    generate_tracker_tsv, get_unlock_states,
+    AssistedBests, BmsHashMap, DensityMap, ExportFilter, IgnoreList, MemoryReader, OffsetSearcher,
+    ScoreMap, SigningKey, SongTags, TextEncoding, TitleOptions, TrackerJsonExtras,
+    check_anchor_songs, collect_chart_analytics, fetch_song_database,
+    generate_beatoraja_json_filtered, generate_tracker_json_filtered,
+    generate_tracker_tsv_filtered, get_unlock_states, load_session_history,
 };
 
 use crate::cli::ExportFormat;
 use crate::cli_utils;
+use crate::exit_code::ExitError;
+use crate::output;
+
+/// Where and how the export is written
+pub struct OutputOptions<'a> {
+    /// Output file (stdout when `None`)
+    pub path: Option<&'a str>,
+    pub encoding: TextEncoding,
+    /// Secret file to sign the TSV output with
+    pub sign_key: Option<&'a str>,
+    /// Title columns of tracker TSV/JSON output
+    pub titles: TitleOptions,
+    /// Chart density profiles added to JSON output
+    pub density: Option<&'a DensityMap>,
+    /// Charts exported as unplayed
+    pub ignore: &'a IgnoreList,
+    /// Assisted-play bests recorded by the tracker
+    pub assisted: &'a AssistedBests,
+}
 
 /// Export all play data
-pub fn run(output: Option<&str>, format: ExportFormat, pid: Option<u32>) -> Result<()> {
+pub fn run(
+    format: ExportFormat,
+    pid: Option<u32>,
+    analytics_dir: Option<&str>,
+    tags: Option<&SongTags>,
+    filter: &ExportFilter,
+    hash_map_path: Option<&str>,
+    out: &OutputOptions,
+) -> Result<()> {
+    let output = out.path;
+    let encoding = out.encoding;
+    if encoding != TextEncoding::Utf8 && !matches!(format, ExportFormat::Tsv) {
+        bail!("--encoding only applies to TSV output (JSON is always UTF-8)");
+    }
+    if out.sign_key.is_some() && !matches!(format, ExportFormat::Tsv) {
+        bail!("--sign-key only applies to TSV output");
+    }
+    // Load the key before reading the game so a bad key fails fast
+    let sign_key = out
+        .sign_key
+        .map(|path| {
+            SigningKey::load(path).with_context(|| format!("Failed to load signing key {}", path))
+        })
+        .transpose()?;
+
     let current_version = env!("CARGO_PKG_VERSION");
     eprintln!("infst {} - Export Mode", current_version);
 
@@ -32,6 +81,8 @@ pub fn run(output: Option<&str>, format: ExportFormat, pid: Option<u32>) -> Resu
     eprintln!("Loading song database...");
     let song_db = fetch_song_database(&reader, offsets.song_list)?;
     eprintln!("Loaded {} songs", song_db.len());
+    // Refuse to export garbage titles after a layout change
+    check_anchor_songs(&song_db, DEFAULT_ANCHOR_SONGS).ensure_no_drift()?;
 
     // Load unlock data
     eprintln!("Loading unlock data...");
@@ -40,22 +91,85 @@ pub fn run(output: Option<&str>, format: ExportFormat, pid: Option<u32>) -> Resu
 
     // Load score map
     eprintln!("Loading score data...");
-    let score_map = ScoreMap::load_from_memory(&reader, offsets.data_map, &song_db)?;
+    let mut score_map = ScoreMap::load_from_memory(&reader, offsets.data_map, &song_db)?;
     eprintln!("Loaded {} score entries", score_map.len());
+    out.assisted.apply(&mut score_map);
+    let score_map = out.ignore.mask_scores(&score_map, &song_db);
+
+    // Per-chart analytics for the TSV columns and JSON fields
+    let analytics = match analytics_dir {
+        Some(dir) if matches!(format, ExportFormat::Tsv | ExportFormat::Json) => {
+            eprintln!("Loading play history from {}...", dir);
+            let plays = load_session_history(dir, &song_db)?;
+            let analytics = collect_chart_analytics(&plays);
+            eprintln!("Loaded {} plays ({} charts)", plays.len(), analytics.len());
+            Some(analytics)
+        }
+        _ => None,
+    };
 
     // Generate output based on format
     let content = match format {
-        ExportFormat::Tsv => generate_tracker_tsv(&song_db, &unlock_db, &score_map),
-        ExportFormat::Json => generate_tracker_json(&song_db, &unlock_db, &score_map)?,
+        ExportFormat::Tsv => generate_tracker_tsv_filtered(
+            &song_db,
+            &unlock_db,
+            &score_map,
+            analytics.as_ref(),
+            tags,
+            filter,
+            &out.titles,
+        ),
+        ExportFormat::Json => generate_tracker_json_filtered(
+            &song_db,
+            &unlock_db,
+            &score_map,
+            &TrackerJsonExtras {
+                tags,
+                density: out.density,
+                analytics: analytics.as_ref(),
+            },
+            filter,
+            &out.titles,
+        )
+        .context(ExitError::export_error("Failed to generate JSON export"))?,
+        ExportFormat::Beatoraja => {
+            let Some(path) = hash_map_path else {
+                bail!("--hash-map is required for the beatoraja format");
+            };
+            let hash_map = BmsHashMap::load(path)
+                .with_context(|| format!("Failed to load hash mapping from {}", path))?;
+            eprintln!("Loaded {} chart hash mappings", hash_map.len());
+            generate_beatoraja_json_filtered(&song_db, &unlock_db, &score_map, &hash_map, filter)
+                .context(ExitError::export_error(
+                    "Failed to generate beatoraja export",
+                ))?
+        }
+    };
+
+    let content = match &sign_key {
+        Some(key) => infst::sign_export(&content, key),
+        None => content,
     };
 
     // Write output
     if let Some(output_path) = output {
-        std::fs::write(output_path, &content)?;
+        infst::storage::write_text_atomic(output_path, &content, encoding, WriteOptions::default())
+            .map_err(|e| {
+                ExitError::export_error(format!("Failed to write {}: {}", output_path, e))
+            })?;
         eprintln!("Exported to: {}", output_path);
-    } else {
+    } else if !output::is_json() {
         println!("{}", content);
     }
 
+    // In JSON mode the export content is part of the result unless written to a file
+    output::emit(&serde_json::json!({
+        "format": format.to_possible_value().map(|v| v.get_name().to_string()),
+        "songs": song_db.len(),
+        "scores": score_map.len(),
+        "outputPath": output,
+        "content": output.is_none().then_some(&content),
+    }));
+
     Ok(())
 }
@@ -6,6 +6,7 @@
 //!
 //! The output file can be used as input for other commands via `--offsets-file`.
 
+use std::path::Path;
 use std::time::Duration;
 
 use anyhow::Result;
@@ -13,23 +14,25 @@ use infst::config::find_game_version;
 use infst::{MemoryReader, OffsetSearcher, OffsetsCollection, ProcessHandle, save_offsets};
 use tracing::{debug, info, warn};
 
+use crate::cli_utils;
+use crate::output::{self, outln};
 use crate::prompter::CliPrompter;
 
 /// Run the find-offsets interactive mode
-pub fn run(output: &str, pid: Option<u32>) -> Result<()> {
+pub fn run(output: &Path, pid: Option<u32>) -> Result<()> {
     let current_version = env!("CARGO_PKG_VERSION");
     info!("infst {} - Offset Search Mode", current_version);
 
     // Open process (either by PID or auto-detect)
     let process = if let Some(pid) = pid {
-        println!("Opening process with PID {}...", pid);
+        outln!("Opening process with PID {}...", pid);
         ProcessHandle::open(pid)?
     } else {
-        println!("Waiting for INFINITAS...");
+        outln!("Waiting for INFINITAS...");
 
         // Wait for process
         loop {
-            match ProcessHandle::find_and_open() {
+            match cli_utils::find_process_by_rule() {
                 Ok(p) => break p,
                 Err(_) => {
                     std::thread::sleep(Duration::from_secs(2));
@@ -48,11 +51,11 @@ pub fn run(output: &str, pid: Option<u32>) -> Result<()> {
     // Game version detection
     let game_version = match find_game_version(&reader, process.base_address) {
         Ok(Some(version)) => {
-            println!("Detected game version: {}", version);
+            outln!("Detected game version: {}", version);
             version
         }
         Ok(None) => {
-            println!("Could not detect game version, using 'unknown'");
+            outln!("Could not detect game version, using 'unknown'");
             "unknown".to_string()
         }
         Err(e) => {
@@ -69,22 +72,28 @@ pub fn run(output: &str, pid: Option<u32>) -> Result<()> {
     let result = searcher.interactive_search(&prompter, &old_offsets, &game_version)?;
 
     // Display results
-    println!();
-    println!("=== Offset Search Results ===");
-    println!("Version:      {}", result.offsets.version);
-    println!("Play Type:    {}", result.play_type.short_name());
-    println!("SongList:     0x{:X}", result.offsets.song_list);
-    println!("JudgeData:    0x{:X}", result.offsets.judge_data);
-    println!("PlaySettings: 0x{:X}", result.offsets.play_settings);
-    println!("PlayData:     0x{:X}", result.offsets.play_data);
-    println!("CurrentSong:  0x{:X}", result.offsets.current_song);
-    println!("DataMap:      0x{:X}", result.offsets.data_map);
-    println!("UnlockData:   0x{:X}", result.offsets.unlock_data);
+    outln!();
+    outln!("=== Offset Search Results ===");
+    outln!("Version:      {}", result.offsets.version);
+    outln!("Play Type:    {}", result.play_type.short_name());
+    outln!("SongList:     0x{:X}", result.offsets.song_list);
+    outln!("JudgeData:    0x{:X}", result.offsets.judge_data);
+    outln!("PlaySettings: 0x{:X}", result.offsets.play_settings);
+    outln!("PlayData:     0x{:X}", result.offsets.play_data);
+    outln!("CurrentSong:  0x{:X}", result.offsets.current_song);
+    outln!("DataMap:      0x{:X}", result.offsets.data_map);
+    outln!("UnlockData:   0x{:X}", result.offsets.unlock_data);
 
     // Save to file
     save_offsets(output, &result.offsets)?;
-    println!();
-    println!("Offsets saved to: {}", output);
+    outln!();
+    outln!("Offsets saved to: {}", output.display());
+
+    output::emit(&serde_json::json!({
+        "offsets": result.offsets,
+        "playType": result.play_type.short_name(),
+        "outputPath": output,
+    }));
 
     Ok(())
 }
@@ -0,0 +1,155 @@
+//! Fixture command implementation.
+//!
+//! Converts a region of a memory dump (raw bytes or an IDMP memory snapshot)
+//! into the text fixture format read by `MockMemoryReader::from_fixture`,
+//! optionally zeroing ranges such as player names first.
+
+use anyhow::{Context, Result, bail};
+use infst::MemorySnapshot;
+use infst::process::format_fixture;
+use infst::util::hex::parse_address;
+
+use crate::output::outln;
+
+/// Region selection and output options
+pub struct FixtureOptions<'a> {
+    /// Address of the first byte of a raw dump (hex)
+    pub base: &'a str,
+    /// Start of the region to keep (hex)
+    pub address: Option<&'a str>,
+    pub size: Option<usize>,
+    /// `ADDRESS:LEN` ranges to zero
+    pub masks: &'a [String],
+    pub comments: &'a [String],
+    pub output: Option<&'a str>,
+}
+
+/// Run the fixture command
+pub fn run(input: &str, options: &FixtureOptions) -> Result<()> {
+    let start = options.address.map(parse_address).transpose()?;
+    let (base, bytes) = read_dump(input, options.base, start)?;
+
+    let start = start.unwrap_or(base);
+    let offset = usize::try_from(start.wrapping_sub(base))
+        .ok()
+        .filter(|&offset| start >= base && offset <= bytes.len())
+        .context("Address is outside the dump")?;
+    let size = options.size.unwrap_or(bytes.len() - offset);
+    let Some(region) = offset
+        .checked_add(size)
+        .and_then(|end| bytes.get(offset..end))
+    else {
+        bail!("Region 0x{start:X}+{size} runs past the end of the dump");
+    };
+    let mut region = region.to_vec();
+
+    for mask in options.masks {
+        let (address, len) = parse_mask(mask)?;
+        let mask_offset = address
+            .checked_sub(start)
+            .and_then(|o| usize::try_from(o).ok());
+        let range = mask_offset
+            .and_then(|o| Some(o..o.checked_add(len)?))
+            .and_then(|range| region.get_mut(range));
+        match range {
+            Some(range) => range.fill(0),
+            None => bail!("Mask {mask} is outside the selected region"),
+        }
+    }
+
+    let comments: Vec<&str> = options.comments.iter().map(String::as_str).collect();
+    let text = format_fixture(start, &region, &comments);
+    match options.output {
+        Some(path) => {
+            std::fs::write(path, &text)?;
+            outln!("Wrote {} bytes at 0x{:X} to {}", region.len(), start, path);
+        }
+        None => print!("{text}"),
+    }
+    Ok(())
+}
+
+/// Read a dump: the snapshot region containing `start` (the first region
+/// when `None`), or a raw dump whose first byte is at `base` (hex); the
+/// region's address and bytes
+pub(crate) fn read_dump(input: &str, base: &str, start: Option<u64>) -> Result<(u64, Vec<u8>)> {
+    let data = std::fs::read(input).with_context(|| format!("Failed to read {input}"))?;
+    if !data.starts_with(MemorySnapshot::MAGIC) {
+        return Ok((parse_address(base)?, data));
+    }
+    let snapshot = MemorySnapshot::from_bytes(&data)?;
+    let region = snapshot
+        .regions()
+        .find(|(base, bytes)| {
+            start.is_none_or(|start| start >= *base && start < base + bytes.len() as u64)
+        })
+        .context("No snapshot region contains the requested address")?;
+    Ok((region.0, region.1.to_vec()))
+}
+
+/// Parse `ADDRESS:LEN` (hex address, decimal length)
+fn parse_mask(mask: &str) -> Result<(u64, usize)> {
+    let (address, len) = mask
+        .split_once(':')
+        .with_context(|| format!("Invalid mask {mask:?}, expected ADDRESS:LEN"))?;
+    let len = len
+        .parse()
+        .with_context(|| format!("Invalid mask length in {mask:?}"))?;
+    Ok((parse_address(address)?, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use infst::ReadMemory;
+    use infst::process::MockMemoryReader;
+
+    fn options<'a>(masks: &'a [String], output: &'a str) -> FixtureOptions<'a> {
+        FixtureOptions {
+            base: "0x140000000",
+            address: Some("0x140000010"),
+            size: Some(32),
+            masks,
+            comments: &[],
+            output: Some(output),
+        }
+    }
+
+    #[test]
+    fn test_raw_dump_region_with_mask() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("dump.bin");
+        let output = dir.path().join("out.fixture");
+        std::fs::write(&input, (1u8..=64).collect::<Vec<u8>>()).unwrap();
+
+        let masks = ["0x140000014:4".to_string()];
+        run(
+            input.to_str().unwrap(),
+            &options(&masks, output.to_str().unwrap()),
+        )
+        .unwrap();
+
+        let reader =
+            MockMemoryReader::from_fixture(&std::fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(reader.base_address(), 0x140000010);
+        assert_eq!(reader.len(), 32);
+        assert_eq!(
+            reader.read_bytes(0x140000010, 8).unwrap(),
+            vec![17, 18, 19, 20, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_rejects_out_of_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("dump.bin");
+        let output = dir.path().join("out.fixture");
+        std::fs::write(&input, [0u8; 40]).unwrap();
+
+        let out = output.to_str().unwrap();
+        assert!(run(input.to_str().unwrap(), &options(&[], out)).is_err());
+        let masks = ["0x140000000:4".to_string()];
+        std::fs::write(&input, [0u8; 64]).unwrap();
+        assert!(run(input.to_str().unwrap(), &options(&masks, out)).is_err());
+    }
+}
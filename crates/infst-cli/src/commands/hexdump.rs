@@ -3,69 +3,377 @@
 //! Displays raw memory bytes in traditional hexdump format, useful for
 //! investigating memory structures and debugging offset calculations.
 //!
+//! 8-byte aligned values that land in mapped memory are listed as pointers,
+//! and `--deref` dumps the data behind them (at most [`MAX_DEREF_DEPTH`]
+//! levels). With offsets available (the global `--offsets-file` or
+//! `--annotate`), rows inside known structures are
+//! annotated with the structure and field name.
+//!
 //! # Output Format
 //!
 //! ```text
-//! 0x000: 48 65 6C 6C 6F 20 57 6F  72 6C 64 00 00 00 00 00  |Hello World.....|
+//! 0x000: 48 65 6C 6C 6F 20 57 6F  72 6C 64 00 00 00 00 00  |Hello World.....|  ; SongList[3]+0x0 (title)
 //! ```
 
+use std::collections::HashSet;
+
 use anyhow::Result;
-use infst::{MemoryReader, ProcessHandle, ReadMemory};
+use infst::{
+    Annotation, MemoryAnnotator, MemoryReader, OffsetSearcher, PointerKind, ProcessHandle,
+    ReadMemory, builtin_signatures, classify_pointer, load_offsets,
+};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::cli_utils;
+use crate::output::{self, outln};
+
+/// Bytes dumped behind each followed pointer
+const DEREF_SIZE: usize = 64;
+
+/// Deepest pointer level followed by `--deref`
+pub const MAX_DEREF_DEPTH: usize = 4;
+
+/// Pointer following and annotation options
+#[derive(Debug, Default)]
+pub struct HexdumpOptions<'a> {
+    /// How many pointer levels to dump (0 = list pointers only)
+    pub deref_depth: usize,
+    pub offsets_file: Option<&'a str>,
+    /// Detect offsets for annotations when no offsets file is given
+    pub annotate: bool,
+}
+
+/// A pointer-like value found in a dump
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PointerEntry {
+    /// Offset within the dump
+    pub offset: usize,
+    pub value: u64,
+    pub kind: PointerKind,
+    /// Structure the pointer target belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<Annotation>,
+}
+
+/// Reader plus module range and annotations, shared by nested dumps
+pub struct Inspector<'a, R: ReadMemory + ?Sized> {
+    pub reader: &'a R,
+    pub module_base: u64,
+    pub module_size: u64,
+    pub annotator: Option<MemoryAnnotator>,
+    /// Include ASCII columns in rendered rows
+    pub ascii: bool,
+}
+
+impl<R: ReadMemory + ?Sized> Inspector<'_, R> {
+    /// 8-byte aligned values in `bytes` (read from `address`) that look like pointers
+    pub fn find_pointers(&self, address: u64, bytes: &[u8]) -> Vec<PointerEntry> {
+        let first = ((8 - (address % 8)) % 8) as usize;
+        (first..bytes.len().saturating_sub(7))
+            .step_by(8)
+            .filter_map(|offset| {
+                let value = u64::from_le_bytes(bytes[offset..offset + 8].try_into().ok()?);
+                let kind =
+                    classify_pointer(self.reader, value, self.module_base, self.module_size)?;
+                Some(PointerEntry {
+                    offset,
+                    value,
+                    kind,
+                    target: self.annotate(value),
+                })
+            })
+            .collect()
+    }
+
+    fn annotate(&self, address: u64) -> Option<Annotation> {
+        self.annotator.as_ref()?.annotate(address)
+    }
+
+    /// Render a dump with row annotations, its pointers, and (up to `depth`
+    /// levels) the data behind them
+    pub fn render(
+        &self,
+        address: u64,
+        bytes: &[u8],
+        depth: usize,
+        visited: &mut HashSet<u64>,
+        indent: &str,
+        lines: &mut Vec<String>,
+    ) -> Vec<PointerEntry> {
+        visited.insert(address);
+        for (row, line) in format_lines(bytes, self.ascii).into_iter().enumerate() {
+            match self.annotate(address + row as u64 * 16) {
+                Some(annotation) => lines.push(format!("{}{}  ; {}", indent, line, annotation)),
+                None => lines.push(format!("{}{}", indent, line)),
+            }
+        }
+
+        let pointers = self.find_pointers(address, bytes);
+        if !pointers.is_empty() {
+            lines.push(String::new());
+            lines.push(format!("{}Pointers:", indent));
+            for pointer in &pointers {
+                let mut line = format!(
+                    "{}  +0x{:03X}  0x{:X}  {}",
+                    indent, pointer.offset, pointer.value, pointer.kind
+                );
+                if let Some(target) = &pointer.target {
+                    line.push_str(&format!("  {}", target));
+                }
+                lines.push(line);
+            }
+        }
+
+        if depth > 0 {
+            let nested_indent = format!("{}    ", indent);
+            for pointer in &pointers {
+                if visited.contains(&pointer.value) {
+                    continue;
+                }
+                lines.push(String::new());
+                match self.reader.read_bytes(pointer.value, DEREF_SIZE) {
+                    Ok(target) => {
+                        lines.push(format!(
+                            "{}-> +0x{:03X}: 0x{:X} ({} bytes)",
+                            indent,
+                            pointer.offset,
+                            pointer.value,
+                            target.len()
+                        ));
+                        self.render(
+                            pointer.value,
+                            &target,
+                            depth - 1,
+                            visited,
+                            &nested_indent,
+                            lines,
+                        );
+                    }
+                    Err(e) => lines.push(format!(
+                        "{}-> +0x{:03X}: 0x{:X} unreadable ({})",
+                        indent, pointer.offset, pointer.value, e
+                    )),
+                }
+            }
+        }
+        pointers
+    }
+}
+
+/// Offsets-based annotator from a file, or from signature search with `--annotate`
+fn load_annotator(
+    reader: &MemoryReader,
+    options: &HexdumpOptions,
+) -> Result<Option<MemoryAnnotator>> {
+    if let Some(path) = options.offsets_file {
+        let offsets = load_offsets(path)?;
+        return Ok(Some(MemoryAnnotator::from_offsets(&offsets)));
+    }
+    if !options.annotate {
+        return Ok(None);
+    }
+    let mut searcher = OffsetSearcher::new(reader);
+    match searcher.search_all_with_signatures(&builtin_signatures()) {
+        Ok(offsets) => Ok(Some(MemoryAnnotator::from_offsets(&offsets))),
+        Err(e) => {
+            warn!(
+                "Offset detection failed, dumping without annotations: {}",
+                e
+            );
+            Ok(None)
+        }
+    }
+}
 
 /// Run the hexdump command
-pub fn run(address: u64, size: usize, ascii: bool, pid: Option<u32>) -> Result<()> {
+pub fn run(
+    address: u64,
+    size: usize,
+    ascii: bool,
+    pid: Option<u32>,
+    options: &HexdumpOptions,
+) -> Result<()> {
     let process = if let Some(pid) = pid {
         ProcessHandle::open(pid)?
     } else {
-        ProcessHandle::find_and_open()?
+        cli_utils::find_process()?
     };
 
     let reader = MemoryReader::new(&process);
+    let inspector = Inspector {
+        reader: &reader,
+        module_base: process.base_address,
+        module_size: process.module_size as u64,
+        annotator: load_annotator(&reader, options)?,
+        ascii,
+    };
     let bytes = reader.read_bytes(address, size)?;
 
-    println!("Hexdump at 0x{:X} ({} bytes):", address, size);
-    println!();
+    let location = inspector.annotate(address);
+    match &location {
+        Some(annotation) => outln!(
+            "Hexdump at 0x{:X} ({} bytes) [{}]:",
+            address,
+            size,
+            annotation
+        ),
+        None => outln!("Hexdump at 0x{:X} ({} bytes):", address, size),
+    }
+    outln!();
+
+    let depth = options.deref_depth.min(MAX_DEREF_DEPTH);
+    if depth < options.deref_depth {
+        warn!(
+            "--deref {} is too deep, following pointers {} levels",
+            options.deref_depth, depth
+        );
+    }
+
+    let mut lines = Vec::new();
+    let pointers = inspector.render(address, &bytes, depth, &mut HashSet::new(), "", &mut lines);
+    for line in lines {
+        outln!("{}", line);
+    }
+
+    output::emit(&serde_json::json!({
+        "address": address,
+        "size": bytes.len(),
+        "location": location,
+        "pointers": pointers,
+    }));
+    Ok(())
+}
 
+/// Format bytes as hexdump lines (offsets relative to the first byte)
+pub fn format_lines(bytes: &[u8], ascii: bool) -> Vec<String> {
+    let mut lines = Vec::new();
     for (i, chunk) in bytes.chunks(16).enumerate() {
         let offset = i * 16;
-        print!("0x{:03X}: ", offset);
+        let mut line = format!("0x{:03X}: ", offset);
 
         // Hex bytes
         for (j, byte) in chunk.iter().enumerate() {
             if j == 8 {
-                print!(" ");
+                line.push(' ');
             }
-            print!("{:02X} ", byte);
+            line.push_str(&format!("{:02X} ", byte));
         }
 
         // Padding for incomplete lines
-        if chunk.len() < 16 {
-            for j in chunk.len()..16 {
-                if j == 8 {
-                    print!(" ");
-                }
-                print!("   ");
+        for j in chunk.len()..16 {
+            if j == 8 {
+                line.push(' ');
             }
+            line.push_str("   ");
         }
 
         // ASCII representation
         if ascii {
-            print!(" |");
+            line.push_str(" |");
             for byte in chunk {
                 if *byte >= 0x20 && *byte < 0x7F {
-                    print!("{}", *byte as char);
+                    line.push(*byte as char);
                 } else {
-                    print!(".");
+                    line.push('.');
                 }
             }
             for _ in chunk.len()..16 {
-                print!(" ");
+                line.push(' ');
             }
-            print!("|");
+            line.push('|');
         }
 
-        println!();
+        lines.push(line.trim_end().to_string());
     }
+    lines
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use infst::OffsetsCollection;
+    use infst::process::MockMemoryBuilder;
+
+    #[test]
+    fn test_format_lines() {
+        let bytes: Vec<u8> = (0x41..0x41 + 18).collect();
+        let lines = format_lines(&bytes, true);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "0x000: 41 42 43 44 45 46 47 48  49 4A 4B 4C 4D 4E 4F 50  |ABCDEFGHIJKLMNOP|"
+        );
+        assert!(lines[1].starts_with("0x010: 51 52 "));
+        assert!(lines[1].ends_with("|QR              |"));
+        assert_eq!(format_lines(&bytes[..2], false), vec!["0x000: 41 42"]);
+    }
+
+    #[test]
+    fn test_render_follows_pointers() {
+        const BASE: u64 = 0x10_0000;
+        // Module at BASE: a pointer to 0x40 (module) and one to unmapped memory
+        let reader = MockMemoryBuilder::new()
+            .base(BASE)
+            .with_size(0x100)
+            .write_u64(0x08, BASE + 0x40)
+            .write_u64(0x10, 0x7FF0_0000_0000)
+            .write_u64(0x40, BASE + 0x08)
+            .write_i32(0x48, 0x1234)
+            .build();
+        let inspector = Inspector {
+            reader: &reader,
+            module_base: BASE,
+            module_size: 0x100,
+            annotator: Some(MemoryAnnotator::from_offsets(&OffsetsCollection {
+                judge_data: BASE + 0x40,
+                ..Default::default()
+            })),
+            ascii: false,
+        };
+
+        let bytes = reader.read_bytes(BASE, 0x20).unwrap();
+        let mut lines = Vec::new();
+        let pointers = inspector.render(BASE, &bytes, 1, &mut HashSet::new(), "", &mut lines);
+
+        assert_eq!(pointers.len(), 1);
+        assert_eq!(pointers[0].offset, 8);
+        assert_eq!(pointers[0].kind, PointerKind::Module);
+        assert_eq!(
+            pointers[0].target.as_ref().unwrap().to_string(),
+            "JudgeData+0x0 (P1 PGreat)"
+        );
+
+        let text = lines.join("\n");
+        assert!(text.contains("  +0x008  0x100040  module  JudgeData+0x0 (P1 PGreat)"));
+        assert!(text.contains("-> +0x008: 0x100040 (64 bytes)"));
+        // Nested dump rows are indented and annotated
+        assert!(text.contains("    0x000: 08 00 10 00 00 00 00 00  34 12"));
+        assert!(text.contains("; JudgeData+0x0 (P1 PGreat)"));
+        // The nested pointer back to the start is listed but not followed again
+        assert_eq!(text.matches("-> ").count(), 1);
+    }
+
+    #[test]
+    fn test_find_pointers_aligns_to_eight_bytes() {
+        const BASE: u64 = 0x10_0000;
+        let reader = MockMemoryBuilder::new()
+            .base(BASE)
+            .with_size(0x40)
+            .write_u64(0x10, BASE)
+            .build();
+        let inspector = Inspector {
+            reader: &reader,
+            module_base: BASE,
+            module_size: 0x40,
+            annotator: None,
+            ascii: false,
+        };
+        // Dump starting at +4: the first aligned value is at dump offset 4
+        let bytes = reader.read_bytes(BASE + 4, 0x20).unwrap();
+        let pointers = inspector.find_pointers(BASE + 4, &bytes);
+        assert_eq!(pointers.len(), 1);
+        assert_eq!(pointers[0].offset, 0x0C);
+    }
 }
@@ -5,11 +5,16 @@ use std::time::{Duration, Instant};
 
 use anyhow::{Result, bail};
 use infst::ProcessHandle;
+#[cfg(target_os = "windows")]
 use infst::input::window;
 use infst::launcher;
 
+use crate::cli_utils;
+
 const LOGIN_PAGE_URL: &str = "https://p.eagate.573.jp/game/2dx/infinitas/top/index.html";
+#[cfg(target_os = "windows")]
 const WINDOW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+#[cfg(target_os = "windows")]
 const WINDOW_POLL_TIMEOUT: Duration = Duration::from_secs(60);
 const PROCESS_POLL_INTERVAL: Duration = Duration::from_secs(2);
 
@@ -38,7 +43,7 @@ fn find_or_launch_process(
     }
 
     // Already running — use it
-    if let Ok(process) = ProcessHandle::find_and_open() {
+    if let Ok(process) = cli_utils::find_process_by_rule() {
         eprintln!("Game is already running");
         return Ok(process);
     }
@@ -69,7 +74,7 @@ fn find_or_launch_process(
             bail!("Timed out waiting for game process after {}s", timeout_secs);
         }
 
-        if let Ok(process) = ProcessHandle::find_and_open() {
+        if let Ok(process) = cli_utils::find_process_by_rule() {
             return Ok(process);
         }
 
@@ -1,5 +1,6 @@
 //! Login command for device code authentication flow.
 
+use crate::output::outln;
 use anyhow::{Context, Result, bail};
 use serde::Deserialize;
 use std::fs;
@@ -21,7 +22,7 @@ struct TokenResponse {
     token: Option<String>,
 }
 
-fn credentials_path() -> Result<PathBuf> {
+pub(crate) fn credentials_path() -> Result<PathBuf> {
     let config_dir = dirs::config_dir().context("Failed to determine config directory")?;
     Ok(config_dir.join("infst").join("credentials"))
 }
@@ -45,19 +46,19 @@ pub fn run(endpoint: &str) -> Result<()> {
         .read_json()
         .context("Failed to parse device code response")?;
 
-    println!("Please visit the following URL and enter the code:");
-    println!();
-    println!("  URL:  {}", response.verification_url);
-    println!("  Code: {}", response.user_code);
-    println!();
+    outln!("Please visit the following URL and enter the code:");
+    outln!();
+    outln!("  URL:  {}", response.verification_url);
+    outln!("  Code: {}", response.user_code);
+    outln!();
 
     // Open browser
     if let Err(e) = open::that(&response.verification_url) {
         eprintln!("Failed to open browser: {}", e);
-        println!("Please open the URL manually.");
+        outln!("Please open the URL manually.");
     }
 
-    println!("Waiting for authorization...");
+    outln!("Waiting for authorization...");
 
     // Poll for token
     let token_url = format!("{}/auth/device/token", endpoint);
@@ -97,8 +98,8 @@ pub fn run(endpoint: &str) -> Result<()> {
 
                 fs::write(&cred_path, content).context("Failed to write credentials file")?;
 
-                println!("Login successful!");
-                println!("Credentials saved to: {}", cred_path.display());
+                outln!("Login successful!");
+                outln!("Credentials saved to: {}", cred_path.display());
                 return Ok(());
             }
             "pending" => continue,
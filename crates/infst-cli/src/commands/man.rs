@@ -0,0 +1,207 @@
+//! Manpage generation (roff).
+//!
+//! Renders a single `infst(1)` page covering the global options and every
+//! subcommand from the clap command tree.
+
+use std::fmt::Write;
+use std::io::Write as _;
+
+use anyhow::Result;
+use clap::{Arg, Command, CommandFactory};
+
+use super::completions::{command_tree, help_line, possible_values, visible_options};
+use crate::cli::Args;
+use crate::exit_code::{self, ExitError};
+use crate::output::{self, outln};
+
+/// Run the man command
+pub fn run(output_path: Option<&str>) -> Result<()> {
+    let page = render(&mut Args::command());
+    match output_path {
+        Some(path) => {
+            std::fs::write(path, &page)
+                .map_err(|e| ExitError::export_error(format!("Failed to write {}: {}", path, e)))?;
+            outln!("Manpage written to: {}", path);
+            output::emit(&serde_json::json!({ "outputPath": path }));
+        }
+        None if output::is_json() => output::emit(&serde_json::json!({ "page": page })),
+        None => std::io::stdout().write_all(page.as_bytes())?,
+    }
+    Ok(())
+}
+
+/// Escape text for roff (backslashes, hyphens and leading control characters)
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+fn option_term(arg: &Arg) -> String {
+    let mut spellings = Vec::new();
+    if let Some(short) = arg.get_short() {
+        spellings.push(format!("\\fB\\-{}\\fR", short));
+    }
+    if let Some(long) = arg.get_long() {
+        spellings.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+    }
+    let mut term = spellings.join(", ");
+    if arg.get_action().takes_values() {
+        let name = arg
+            .get_value_names()
+            .and_then(|names| names.first())
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| arg.get_id().to_string().to_uppercase());
+        let _ = write!(term, " \\fI{}\\fR", escape(&name));
+    }
+    term
+}
+
+fn option_details(arg: &Arg) -> String {
+    let mut details = Vec::new();
+    let values = possible_values(arg);
+    if !values.is_empty() {
+        details.push(format!("possible values: {}", values.join(", ")));
+    }
+    let defaults: Vec<String> = arg
+        .get_default_values()
+        .iter()
+        .map(|v| v.to_string_lossy().into_owned())
+        .collect();
+    if !defaults.is_empty() && arg.get_action().takes_values() {
+        details.push(format!("default: {}", defaults.join(",")));
+    }
+    if let Some(env) = arg.get_env() {
+        details.push(format!("env: {}", env.to_string_lossy()));
+    }
+    details
+        .iter()
+        .map(|d| format!(" [{}]", escape(d)))
+        .collect()
+}
+
+fn write_arguments(out: &mut String, cmd: &Command) {
+    for arg in visible_options(cmd) {
+        let _ = writeln!(out, ".TP");
+        let _ = writeln!(out, "{}", option_term(arg));
+        let _ = writeln!(
+            out,
+            "{}{}",
+            escape(&help_line(arg.get_help())),
+            option_details(arg)
+        );
+    }
+    for arg in cmd.get_positionals() {
+        let _ = writeln!(out, ".TP");
+        let _ = writeln!(
+            out,
+            "\\fI<{}>\\fR",
+            escape(&arg.get_id().to_string().to_uppercase())
+        );
+        let _ = writeln!(out, "{}", escape(&help_line(arg.get_help())));
+    }
+}
+
+fn synopsis(bin: &str, path: &[String], cmd: &Command) -> String {
+    let mut synopsis = format!("\\fB{}", escape(bin));
+    for part in path {
+        let _ = write!(synopsis, " {}", escape(part));
+    }
+    synopsis.push_str("\\fR");
+    if visible_options(cmd).next().is_some() {
+        synopsis.push_str(" [\\fIOPTIONS\\fR]");
+    }
+    for arg in cmd.get_positionals() {
+        let _ = write!(
+            synopsis,
+            " \\fI<{}>\\fR",
+            escape(&arg.get_id().to_string().to_uppercase())
+        );
+    }
+    if cmd.has_subcommands() {
+        synopsis.push_str(if cmd.is_subcommand_required_set() {
+            " \\fICOMMAND\\fR"
+        } else {
+            " [\\fICOMMAND\\fR]"
+        });
+    }
+    synopsis
+}
+
+/// Render the manpage for `cmd`
+pub fn render(cmd: &mut Command) -> String {
+    cmd.build();
+    let bin = cmd.get_name().to_string();
+    let version = cmd.get_version().unwrap_or_default().to_string();
+    let about = help_line(cmd.get_about());
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        ".TH {} 1 \"\" \"{} {}\"",
+        bin.to_uppercase(),
+        escape(&bin),
+        escape(&version)
+    );
+    let _ = writeln!(out, ".SH NAME");
+    let _ = writeln!(out, "{} \\- {}", escape(&bin), escape(&about));
+    let _ = writeln!(out, ".SH SYNOPSIS");
+    let _ = writeln!(out, "{}", synopsis(&bin, &[], cmd));
+    let _ = writeln!(out, ".SH DESCRIPTION");
+    let _ = writeln!(
+        out,
+        "{}. Without a command, waits for the game and tracks plays.",
+        escape(&about)
+    );
+    let _ = writeln!(out, ".SH OPTIONS");
+    write_arguments(&mut out, cmd);
+
+    let _ = writeln!(out, ".SH COMMANDS");
+    for (path, node) in command_tree(cmd).into_iter().skip(1) {
+        let _ = writeln!(out, ".SS \"{} {}\"", escape(&bin), escape(&path.join(" ")));
+        let about = help_line(node.get_about());
+        if !about.is_empty() {
+            let _ = writeln!(out, "{}", escape(&about));
+        }
+        let _ = writeln!(out, ".PP");
+        let _ = writeln!(out, "{}", synopsis(&bin, &path, node));
+        write_arguments(&mut out, node);
+    }
+
+    let _ = writeln!(out, ".SH \"EXIT STATUS\"");
+    for line in exit_code::HELP.lines().skip(1) {
+        if let Some((code, meaning)) = line.trim().split_once(char::is_whitespace) {
+            let _ = writeln!(out, ".TP");
+            let _ = writeln!(out, "\\fB{}\\fR", code);
+            let _ = writeln!(out, "{}", escape(meaning.trim()));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("--output"), "\\-\\-output");
+        assert_eq!(escape(".hidden"), "\\&.hidden");
+        assert_eq!(escape("a\\b"), "a\\eb");
+    }
+
+    #[test]
+    fn test_render_covers_commands_and_exit_codes() {
+        let page = render(&mut Args::command());
+        assert!(page.starts_with(".TH INFST 1"));
+        assert!(page.contains(".SS \"infst validate song\\-db\""));
+        assert!(page.contains("\\fB\\-f\\fR, \\fB\\-\\-format\\fR \\fIFORMAT\\fR"));
+        assert!(page.contains("[possible values: tsv, json, beatoraja]"));
+        assert!(page.contains("[env: INFST_API_TOKEN]"));
+        assert!(page.contains(".SH \"EXIT STATUS\"\n.TP\n\\fB0\\fR\nSuccess\n"));
+        assert!(page.contains("\\fB30\\fR\nNetwork error\n"));
+    }
+}
@@ -0,0 +1,49 @@
+//! Merge command for combining the trackers of two PCs.
+
+use anyhow::{Context, Result};
+use infst::{TextEncoding, Tracker};
+
+use crate::output::outln;
+
+/// Merge two tracker TSV files and report cells that conflict
+pub fn run(
+    a_path: &str,
+    b_path: &str,
+    output: &str,
+    encoding: TextEncoding,
+    json: bool,
+) -> Result<()> {
+    let a = Tracker::load(a_path).with_context(|| format!("Failed to load {}", a_path))?;
+    let b = Tracker::load(b_path).with_context(|| format!("Failed to load {}", b_path))?;
+
+    let merge = a.merge(&b);
+    merge
+        .tracker
+        .save(output, encoding)
+        .with_context(|| format!("Failed to write {}", output))?;
+
+    if json {
+        outln!("{}", serde_json::to_string_pretty(&merge.conflicts)?);
+        return Ok(());
+    }
+
+    for conflict in &merge.conflicts {
+        outln!(
+            "{} ({}) {}: kept {:?}, {} has {:?}",
+            conflict.title,
+            conflict.song_id,
+            conflict.column,
+            conflict.kept,
+            b_path,
+            conflict.other
+        );
+    }
+    eprintln!(
+        "Merged {} songs into {} ({} conflicts)",
+        merge.tracker.len(),
+        output,
+        merge.conflicts.len()
+    );
+
+    Ok(())
+}
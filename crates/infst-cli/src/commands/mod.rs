@@ -3,20 +3,38 @@
 //! This module contains the implementation of each CLI command.
 
 pub mod analyze;
+pub mod completions;
+pub mod diff;
+pub mod discover_layout;
+pub mod doctor;
 pub mod dump;
 pub mod explore;
 pub mod export;
 pub mod find_offsets;
-pub mod hex_utils;
+pub mod fixture;
 pub mod hexdump;
 pub mod launch;
 pub mod login;
+pub mod man;
+pub mod merge;
 pub mod offset;
+pub mod recommend;
 pub mod register;
 pub mod scan;
+pub mod scores;
 pub mod search;
+pub mod selftest;
+pub mod session;
+pub mod shell;
+pub mod songs;
 pub mod status;
+pub mod struct_diff;
 pub mod sync;
+pub mod tracker;
+pub mod tracker_diff;
 pub mod tracking;
+pub mod unlocks;
 pub mod upload;
 pub mod validate;
+pub mod verify;
+pub mod watch;
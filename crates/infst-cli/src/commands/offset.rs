@@ -1,21 +1,20 @@
 //! Offset command implementation.
 
-use super::hex_utils::parse_hex_address;
+use crate::output::outln;
 use anyhow::Result;
+use infst::util::hex::{format_address, format_offset, offset_between, parse_address};
 
 /// Run the offset command
 pub fn run(from: &str, to: &str) -> Result<()> {
-    let from_addr = parse_hex_address(from)?;
-    let to_addr = parse_hex_address(to)?;
+    let from_addr = parse_address(from)?;
+    let to_addr = parse_address(to)?;
 
-    let diff = to_addr.abs_diff(from_addr);
+    let offset = offset_between(from_addr, to_addr);
 
-    let sign = if to_addr >= from_addr { "" } else { "-" };
-
-    println!("From: 0x{:X}", from_addr);
-    println!("To:   0x{:X}", to_addr);
-    println!();
-    println!("Offset: {}{} (0x{:X})", sign, diff, diff);
+    outln!("From: {}", format_address(from_addr));
+    outln!("To:   {}", format_address(to_addr));
+    outln!();
+    outln!("Offset: {} ({})", offset, format_offset(offset));
 
     Ok(())
 }
@@ -0,0 +1,142 @@
+//! Recommend command for DJ point improvement goals.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::Result;
+use infst::{
+    DenseFailReport, DensityMap, MemoryReader, OffsetSearcher, RecommendOptions, ScoreMap,
+    dense_section_fails, fetch_song_database, get_unlock_states, load_session_history,
+    recommend_dj_points,
+};
+
+use crate::cli_utils;
+use crate::output::{self, outln};
+
+/// Show which charts to improve to gain the target DJ points
+///
+/// `songs` restricts the recommendations to those songs (from `--tag`).
+/// With `density`, the play history in `session_dir` is also checked for
+/// fails in dense chart sections.
+pub fn run(
+    target_djp: f64,
+    max_level: Option<u8>,
+    dp: bool,
+    songs: Option<HashSet<u32>>,
+    density: Option<&DensityMap>,
+    session_dir: &Path,
+    pid: Option<u32>,
+) -> Result<()> {
+    let current_version = env!("CARGO_PKG_VERSION");
+    eprintln!("infst {} - Recommend Mode", current_version);
+
+    let process = cli_utils::open_process(pid)?;
+    let reader = MemoryReader::new(&process);
+    let mut searcher = OffsetSearcher::new(&reader);
+    let offsets = searcher.search_data_offsets()?;
+
+    eprintln!("Loading song database...");
+    let song_db = fetch_song_database(&reader, offsets.song_list)?;
+    eprintln!("Loading score data...");
+    let score_map = ScoreMap::load_from_memory(&reader, offsets.data_map, &song_db)?;
+    eprintln!("Loading unlock data...");
+    let unlock_db = get_unlock_states(&reader, offsets.unlock_data, &song_db)?;
+
+    let options = RecommendOptions {
+        target_gain: target_djp,
+        max_level,
+        double_play: dp,
+        songs,
+    };
+    let plan = recommend_dj_points(&song_db, Some(&unlock_db), &score_map, &options);
+
+    let style = if dp { "DP" } else { "SP" };
+    outln!(
+        "{} DJ Points: {:.2} -> {:.2} (+{:.2})",
+        style,
+        plan.current_total,
+        plan.projected_total,
+        plan.projected_total - plan.current_total
+    );
+    outln!();
+
+    for (i, rec) in plan.recommendations.iter().enumerate() {
+        outln!(
+            "{:>3}. [{} {:>2}] {}",
+            i + 1,
+            rec.chart.difficulty.short_name(),
+            rec.level,
+            rec.title
+        );
+        outln!(
+            "     {} {} -> {} {}  (+{:.2})",
+            rec.current_score,
+            rec.current_lamp.short_name(),
+            rec.target_score,
+            rec.target_lamp.short_name(),
+            rec.gain
+        );
+    }
+
+    if !plan.reaches(target_djp) {
+        eprintln!();
+        eprintln!(
+            "Target +{} is not reachable with the charts in range",
+            target_djp
+        );
+    }
+
+    let fail_density = match density {
+        Some(density) => {
+            eprintln!("Loading play history from {}...", session_dir.display());
+            let plays = load_session_history(session_dir, &song_db)?;
+            let report = dense_section_fails(&plays, density);
+            print_fail_density(&report);
+            Some(report)
+        }
+        None => None,
+    };
+
+    output::emit(&serde_json::json!({
+        "style": style,
+        "plan": plan,
+        "failDensity": fail_density,
+    }));
+    Ok(())
+}
+
+/// Charts listed under the fail density summary
+const FAIL_DENSITY_CHARTS: usize = 5;
+
+fn print_fail_density(report: &DenseFailReport) {
+    outln!();
+    let Some(share) = report.dense_share() else {
+        outln!("Fail points: no failed plays on charts with density data");
+        return;
+    };
+    outln!(
+        "Fail points: {} of {} fails in dense sections ({:.0}%, {:.0}% of notes are in dense sections)",
+        report.dense_fails,
+        report.fails,
+        share * 100.0,
+        report.expected_dense_share * 100.0
+    );
+    if report.fails_on_dense_sections() {
+        outln!("You fail on dense sections; charts to practice:");
+        for chart in report
+            .charts
+            .iter()
+            .filter(|chart| chart.dense_fails > 0)
+            .take(FAIL_DENSITY_CHARTS)
+        {
+            outln!(
+                "     [{}] {}  {}/{} fails in dense sections (peak {:.1} notes/s)",
+                chart.chart.difficulty.short_name(),
+                chart.title,
+                chart.dense_fails,
+                chart.fails,
+                chart.peak_notes_per_second
+            );
+        }
+    }
+}
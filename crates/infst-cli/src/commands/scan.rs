@@ -12,6 +12,9 @@ use infst::{
 };
 use tracing::warn;
 
+use crate::cli_utils;
+use crate::output::{self, outln};
+
 /// Run the scan command
 pub fn run(
     offsets_file: Option<&str>,
@@ -22,18 +25,19 @@ pub fn run(
     entry_size: Option<usize>,
 ) -> Result<()> {
     let current_version = env!("CARGO_PKG_VERSION");
-    println!("infst {} - Scan Mode", current_version);
+    outln!("infst {} - Scan Mode", current_version);
 
     // Open process
     let process = if let Some(pid) = pid {
         ProcessHandle::open(pid)?
     } else {
-        ProcessHandle::find_and_open()?
+        cli_utils::find_process()?
     };
 
-    println!(
+    outln!(
         "Found process (PID: {}, Base: 0x{:X})",
-        process.pid, process.base_address
+        process.pid,
+        process.base_address
     );
 
     let reader = MemoryReader::new(&process);
@@ -51,7 +55,7 @@ pub fn run(
     let tsv_db = if let Some(tsv_path) = tsv_file {
         match infst::chart::load_song_database_from_tsv(tsv_path) {
             Ok(db) => {
-                println!("Loaded {} songs from TSV", db.len());
+                outln!("Loaded {} songs from TSV", db.len());
                 Some(db)
             }
             Err(e) => {
@@ -66,45 +70,50 @@ pub fn run(
     // Perform scan
     if let Some(size) = entry_size {
         // Custom entry size scan
-        println!(
+        outln!(
             "Scanning with entry size {} bytes from 0x{:X}...",
-            size, offsets.song_list
+            size,
+            offsets.song_list
         );
         run_custom_entry_size_scan(&reader, offsets.song_list, range, size);
         return Ok(());
     }
 
-    println!("Scanning {} bytes from 0x{:X}...", range, offsets.song_list);
+    outln!("Scanning {} bytes from 0x{:X}...", range, offsets.song_list);
     let scan_result = ScanResult::scan(&reader, offsets.song_list, range, tsv_db.as_ref());
 
     if let Some(output_path) = output {
         let json = serde_json::to_string_pretty(&scan_result)?;
         std::fs::write(output_path, json)?;
-        println!("Scan results saved to: {}", output_path);
+        outln!("Scan results saved to: {}", output_path);
     } else {
         // Print summary to stdout
-        println!();
-        println!("=== Scan Results ===");
-        println!("Scan start: 0x{:X}", scan_result.scan_start);
-        println!("Scan range: {} bytes", scan_result.scan_range);
-        println!("Songs found: {}", scan_result.songs_found);
-
-        println!();
-        println!("=== Detected Songs ===");
+        outln!();
+        outln!("=== Scan Results ===");
+        outln!("Scan start: 0x{:X}", scan_result.scan_start);
+        outln!("Scan range: {} bytes", scan_result.scan_range);
+        outln!("Songs found: {}", scan_result.songs_found);
+
+        outln!();
+        outln!("=== Detected Songs ===");
         for (i, song) in scan_result.songs.iter().take(30).enumerate() {
-            println!(
+            outln!(
                 "  [{}] id={:5}, folder={:2}, title={:?} ({})",
-                i, song.song_id, song.folder, song.title, song.source_type
+                i,
+                song.song_id,
+                song.folder,
+                song.title,
+                song.source_type
             );
         }
         if scan_result.songs.len() > 30 {
-            println!("  ... and {} more", scan_result.songs.len() - 30);
+            outln!("  ... and {} more", scan_result.songs.len() - 30);
         }
 
         if let Some(ref matches) = scan_result.tsv_matches {
-            println!();
-            println!("=== TSV Matching ===");
-            println!(
+            outln!();
+            outln!("=== TSV Matching ===");
+            outln!(
                 "Matched: {} / {}",
                 scan_result.matched_count.unwrap_or(0),
                 matches.len()
@@ -112,18 +121,19 @@ pub fn run(
 
             let unmatched: Vec<_> = matches.iter().filter(|m| !m.matched).collect();
             if !unmatched.is_empty() {
-                println!();
-                println!("Unmatched songs:");
+                outln!();
+                outln!("Unmatched songs:");
                 for m in unmatched.iter().take(10) {
-                    println!("  id={}: {:?}", m.song_id, m.memory_title);
+                    outln!("  id={}: {:?}", m.song_id, m.memory_title);
                 }
                 if unmatched.len() > 10 {
-                    println!("  ... and {} more", unmatched.len() - 10);
+                    outln!("  ... and {} more", unmatched.len() - 10);
                 }
             }
         }
     }
 
+    output::emit(&scan_result);
     Ok(())
 }
 
@@ -136,10 +146,10 @@ fn run_custom_entry_size_scan(
 ) {
     use encoding_rs::SHIFT_JIS;
 
-    println!();
-    println!("=== Custom Entry Size Scan ===");
-    println!("Entry size: {} bytes (0x{:X})", entry_size, entry_size);
-    println!();
+    outln!();
+    outln!("=== Custom Entry Size Scan ===");
+    outln!("Entry size: {} bytes (0x{:X})", entry_size, entry_size);
+    outln!();
 
     let max_entries = (range / entry_size).min(5000);
     let mut found_songs: Vec<(u64, u32, String, [u8; 10])> = Vec::new();
@@ -230,8 +240,8 @@ fn run_custom_entry_size_scan(
         found_songs.push((entry_addr, song_id, title.to_string(), levels));
     }
 
-    println!("Found {} entries with titles", found_songs.len());
-    println!();
+    outln!("Found {} entries with titles", found_songs.len());
+    outln!();
 
     // Display results
     for (i, (addr, id, title, levels)) in found_songs.iter().take(30).enumerate() {
@@ -240,14 +250,18 @@ fn run_custom_entry_size_scan(
         } else {
             "    ?".to_string()
         };
-        println!(
+        outln!(
             "[{:3}] 0x{:X}: id={}, levels={:?}, title={:?}",
-            i, addr, id_str, levels, title
+            i,
+            addr,
+            id_str,
+            levels,
+            title
         );
     }
 
     if found_songs.len() > 30 {
-        println!("... and {} more", found_songs.len() - 30);
+        outln!("... and {} more", found_songs.len() - 30);
     }
 
     // Statistics
@@ -256,8 +270,8 @@ fn run_custom_entry_size_scan(
         .iter()
         .filter(|(_, _, _, l)| l.iter().any(|&x| x > 0))
         .count();
-    println!();
-    println!("Statistics:");
-    println!("  Entries with valid song_id: {}", with_id);
-    println!("  Entries with valid levels:  {}", with_levels);
+    outln!();
+    outln!("Statistics:");
+    outln!("  Entries with valid song_id: {}", with_id);
+    outln!("  Entries with valid levels:  {}", with_levels);
 }
@@ -0,0 +1,59 @@
+//! Scores command: snapshots of the in-game score map and their differences.
+
+use anyhow::{Context, Result};
+use infst::{MemoryReader, OffsetSearcher, ScoreMap, fetch_song_database};
+
+use crate::cli::ScoresAction;
+use crate::cli_utils;
+use crate::output::{self, outln};
+
+/// Run a scores subcommand
+pub fn run(action: ScoresAction) -> Result<()> {
+    match action {
+        ScoresAction::Snapshot { output, pid } => snapshot(&output, pid),
+        ScoresAction::Diff { before, after } => diff(&before, &after),
+    }
+}
+
+/// Save the in-game bests of every chart
+fn snapshot(output: &str, pid: Option<u32>) -> Result<()> {
+    let process = cli_utils::open_process(pid)?;
+    let reader = MemoryReader::new(&process);
+    let offsets = OffsetSearcher::new(&reader).search_data_offsets()?;
+
+    eprintln!("Loading song database...");
+    let song_db = fetch_song_database(&reader, offsets.song_list)?;
+    eprintln!("Loading score map...");
+    let score_map = ScoreMap::load_from_memory(&reader, offsets.data_map, &song_db)?;
+
+    std::fs::write(output, score_map.to_json()?)
+        .with_context(|| format!("Failed to write {}", output))?;
+    eprintln!("Saved {} songs to {}", score_map.len(), output);
+    Ok(())
+}
+
+/// Print the charts whose bests changed between two snapshots
+fn diff(before_path: &str, after_path: &str) -> Result<()> {
+    let before = ScoreMap::load_snapshot(before_path)
+        .with_context(|| format!("Failed to load {}", before_path))?;
+    let after = ScoreMap::load_snapshot(after_path)
+        .with_context(|| format!("Failed to load {}", after_path))?;
+
+    let deltas = after.diff(&before);
+    output::emit(&deltas);
+
+    if deltas.is_empty() {
+        eprintln!("No changes");
+        return Ok(());
+    }
+    for delta in &deltas {
+        if delta.is_regression() {
+            outln!("{}  (lower than before)", delta);
+        } else {
+            outln!("{}", delta);
+        }
+    }
+    let regressions = deltas.iter().filter(|d| d.is_regression()).count();
+    eprintln!("{} charts changed ({} lower)", deltas.len(), regressions);
+    Ok(())
+}
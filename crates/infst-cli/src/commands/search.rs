@@ -1,128 +1,276 @@
 //! Search command implementation.
 
 use anyhow::{Result, bail};
+use infst::debug::{
+    SearchPattern, StringEncoding, ValueRange, build_regex, default_range, find_in_range,
+    find_matches, find_strings,
+};
 use infst::{MemoryReader, ProcessHandle, ReadMemory};
 
+use crate::cli_utils;
+use crate::output::{self, outln};
+
+/// How `--string` / `--regex` text is encoded and matched
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TextOptions {
+    pub encoding: StringEncoding,
+    pub ignore_case: bool,
+}
+
+/// Consecutive entries required by default with `--stride`
+const DEFAULT_RUN: usize = 10;
+
+/// Parse a decimal or 0x-prefixed hex number
+pub fn parse_number(s: &str) -> Result<i64> {
+    let s = s.trim();
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let value = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => i64::from_str_radix(hex, 16),
+        None => digits.parse(),
+    }
+    .map_err(|_| anyhow::anyhow!("Invalid number: {}", s))?;
+    Ok(if negative { -value } else { value })
+}
+
+/// Parse an inclusive range (`MIN..MAX` or `MIN..=MAX`)
+pub fn parse_range(s: &str) -> Result<(i64, i64)> {
+    let Some((min, max)) = s.split_once("..") else {
+        bail!("Invalid range: {} (expected MIN..MAX)", s);
+    };
+    let max = max.strip_prefix('=').unwrap_or(max);
+    Ok((parse_number(min)?, parse_number(max)?))
+}
+
+/// Search inputs from the command line
+///
+/// `regex` takes precedence over `range`, which takes precedence over the
+/// byte pattern options.
+#[derive(Debug, Default)]
+pub struct SearchArgs {
+    pub string: Option<String>,
+    pub regex: Option<String>,
+    pub text: TextOptions,
+    /// Minimum decoded string length for `regex`
+    pub min_len: usize,
+    pub i32: Option<i32>,
+    pub i16: Option<i16>,
+    pub pattern: Option<String>,
+    /// Value range (`MIN..MAX`, inclusive)
+    pub range: Option<String>,
+    pub width: usize,
+    pub align: Option<u64>,
+    pub stride: Option<String>,
+    pub run: Option<usize>,
+    pub limit: usize,
+}
+
 /// Run the search command
-pub fn run(
-    string: Option<String>,
-    i32_val: Option<i32>,
-    i16_val: Option<i16>,
-    pattern: Option<String>,
-    limit: usize,
-    pid: Option<u32>,
-) -> Result<()> {
+pub fn run(args: &SearchArgs, pid: Option<u32>) -> Result<()> {
     let process = if let Some(pid) = pid {
         ProcessHandle::open(pid)?
     } else {
-        ProcessHandle::find_and_open()?
+        cli_utils::find_process()?
     };
 
-    println!(
+    outln!(
         "Found process (PID: {}, Base: 0x{:X})",
-        process.pid, process.base_address
+        process.pid,
+        process.base_address
     );
 
     let reader = MemoryReader::new(&process);
+    let (search_start, search_end) = default_range(process.base_address, process.module_size);
+
+    if let Some(ref expr) = args.regex {
+        return search_strings(&reader, expr, args, search_start, search_end);
+    }
+    if let Some(ref range) = args.range {
+        return search_range(&reader, range, args, search_start, search_end);
+    }
 
     // Determine search pattern
-    let (search_bytes, wildcard_mask): (Vec<u8>, Vec<bool>) = if let Some(ref s) = string {
-        // Encode string as Shift-JIS
-        let (encoded, _, _) = encoding_rs::SHIFT_JIS.encode(s);
-        let bytes = encoded.to_vec();
-        let mask = vec![false; bytes.len()];
-        println!(
-            "Searching for string: {:?} ({} bytes, Shift-JIS)",
+    let search = if let Some(ref s) = args.string {
+        let search = SearchPattern::text(s, args.text.encoding, args.text.ignore_case)?;
+        outln!(
+            "Searching for string: {:?} ({} bytes, {}{})",
             s,
-            bytes.len()
-        );
-        (bytes, mask)
-    } else if let Some(val) = i32_val {
-        let bytes = val.to_le_bytes().to_vec();
-        let mask = vec![false; 4];
-        println!("Searching for i32: {} (0x{:08X})", val, val as u32);
-        (bytes, mask)
-    } else if let Some(val) = i16_val {
-        let bytes = val.to_le_bytes().to_vec();
-        let mask = vec![false; 2];
-        println!("Searching for i16: {} (0x{:04X})", val, val as u16);
-        (bytes, mask)
-    } else if let Some(ref pat) = pattern {
-        // Parse byte pattern (e.g., "00 04 07 0A" or "00 ?? 07")
-        let parts: Vec<&str> = pat.split_whitespace().collect();
-        let mut bytes = Vec::new();
-        let mut mask = Vec::new();
-        for part in parts {
-            if part == "??" {
-                bytes.push(0);
-                mask.push(true); // wildcard
+            search.bytes.len(),
+            args.text.encoding.label(),
+            if args.text.ignore_case {
+                ", ignoring case"
             } else {
-                let byte = u8::from_str_radix(part, 16)
-                    .map_err(|_| anyhow::anyhow!("Invalid hex byte: {}", part))?;
-                bytes.push(byte);
-                mask.push(false);
+                ""
             }
-        }
-        println!("Searching for pattern: {} ({} bytes)", pat, bytes.len());
-        (bytes, mask)
+        );
+        search
+    } else if let Some(val) = args.i32 {
+        outln!("Searching for i32: {} (0x{:08X})", val, val as u32);
+        SearchPattern::i32(val)
+    } else if let Some(val) = args.i16 {
+        outln!("Searching for i16: {} (0x{:04X})", val, val as u16);
+        SearchPattern::i16(val)
+    } else if let Some(ref pat) = args.pattern {
+        let search = SearchPattern::parse(pat)?;
+        outln!(
+            "Searching for pattern: {} ({} bytes)",
+            pat,
+            search.bytes.len()
+        );
+        search
     } else {
-        bail!("No search pattern specified. Use --string, --i32, --i16, or --pattern");
+        bail!(
+            "No search pattern specified. Use --string, --regex, --range, --i32, --i16, or --pattern"
+        );
     };
 
     // Search in memory
-    let search_start = process.base_address + 0x1000000; // Start 16MB into the module
-    let search_end = process.base_address + (process.module_size as u64).min(0x5000000);
-    let chunk_size: usize = 4 * 1024 * 1024; // 4MB chunks
-
-    println!("Search range: 0x{:X} - 0x{:X}", search_start, search_end);
-    println!();
-
-    let mut found: Vec<u64> = Vec::new();
-    let mut offset = 0u64;
-
-    while search_start + offset < search_end && found.len() < limit {
-        let addr = search_start + offset;
-        let read_size = chunk_size.min((search_end - addr) as usize);
-
-        if let Ok(buffer) = reader.read_bytes(addr, read_size) {
-            for i in 0..=(buffer.len().saturating_sub(search_bytes.len())) {
-                let mut matches = true;
-                for (j, &byte) in search_bytes.iter().enumerate() {
-                    if !wildcard_mask[j] && buffer[i + j] != byte {
-                        matches = false;
-                        break;
-                    }
-                }
-                if matches {
-                    let found_addr = addr + i as u64;
-                    found.push(found_addr);
-
-                    println!("[{}] 0x{:X}", found.len(), found_addr);
-
-                    // Show context (32 bytes)
-                    if let Ok(context) = reader.read_bytes(found_addr, 32.min(buffer.len() - i)) {
-                        print!("     ");
-                        for byte in &context[..16.min(context.len())] {
-                            print!("{:02X} ", byte);
-                        }
-                        println!();
-                    }
-
-                    if found.len() >= limit {
-                        break;
-                    }
-                }
-            }
+    outln!("Search range: 0x{:X} - 0x{:X}", search_start, search_end);
+    outln!();
+
+    let found = find_matches(&reader, &search, search_start, search_end, args.limit);
+    for (i, &found_addr) in found.iter().enumerate() {
+        outln!("[{}] 0x{:X}", i + 1, found_addr);
+        // Show context (16 bytes)
+        if let Ok(context) = reader.read_bytes(found_addr, 16) {
+            outln!("     {}", format_bytes(&context));
         }
+    }
+
+    print_summary(&found, args.limit);
+    Ok(())
+}
 
-        offset += chunk_size as u64;
+fn search_strings(
+    reader: &MemoryReader,
+    expr: &str,
+    args: &SearchArgs,
+    start: u64,
+    end: u64,
+) -> Result<()> {
+    let regex = build_regex(expr, args.text.ignore_case)?;
+    outln!(
+        "Searching for {} strings matching /{}/{}",
+        args.text.encoding.label(),
+        expr,
+        if args.text.ignore_case { "i" } else { "" }
+    );
+    outln!("Search range: 0x{:X} - 0x{:X}", start, end);
+    outln!();
+
+    let found = find_strings(
+        reader,
+        &regex,
+        args.text.encoding,
+        args.min_len,
+        start,
+        end,
+        args.limit,
+    );
+    for (i, found) in found.iter().enumerate() {
+        outln!("[{}] 0x{:X}  {:?}", i + 1, found.address, found.text);
+    }
+
+    let matches: Vec<serde_json::Value> = found
+        .iter()
+        .map(|found| serde_json::json!({ "address": found.address, "text": found.text }))
+        .collect();
+    print_summary(&matches, args.limit);
+    Ok(())
+}
+
+fn search_range(
+    reader: &MemoryReader,
+    range: &str,
+    args: &SearchArgs,
+    start: u64,
+    end: u64,
+) -> Result<()> {
+    let (min, max) = parse_range(range)?;
+    let mut value_range = ValueRange::new(min, max, args.width)?;
+    if let Some(align) = args.align {
+        value_range = value_range.with_align(align)?;
+    }
+    if let Some(ref stride) = args.stride {
+        let stride = parse_number(stride)?;
+        let run = args.run.unwrap_or(DEFAULT_RUN);
+        if stride <= 0 || run == 0 {
+            bail!("--stride and --run must be greater than zero");
+        }
+        value_range = value_range.with_stride(stride as u64, run);
+    } else if args.run.is_some_and(|run| run > 1) {
+        bail!("--run requires --stride");
     }
 
-    println!();
-    println!("Found {} result(s)", found.len());
-    if found.len() >= limit {
-        println!("(limit reached, use --limit to increase)");
+    outln!(
+        "Searching for {}-byte values in [{}, {}] (align {}{})",
+        value_range.width,
+        min,
+        max,
+        value_range.align,
+        if value_range.run > 1 {
+            format!(
+                ", {} entries at stride 0x{:X}",
+                value_range.run, value_range.stride
+            )
+        } else {
+            String::new()
+        }
+    );
+    outln!("Search range: 0x{:X} - 0x{:X}", start, end);
+    outln!();
+
+    let found = find_in_range(reader, &value_range, start, end, args.limit);
+    for (i, &address) in found.iter().enumerate() {
+        let values: Vec<String> = (0..value_range.run.min(4))
+            .filter_map(|k| value_range.read(reader, address + k as u64 * value_range.stride))
+            .map(|v| v.to_string())
+            .collect();
+        outln!("[{}] 0x{:X}  {}", i + 1, address, values.join(", "));
     }
 
+    print_summary(&found, args.limit);
     Ok(())
 }
+
+/// Print the match count and emit the matches
+fn print_summary<T: serde::Serialize>(matches: &[T], limit: usize) {
+    let count = matches.len();
+    outln!();
+    outln!("Found {} result(s)", count);
+    if count >= limit {
+        outln!("(limit reached, use --limit to increase)");
+    }
+    output::emit(&serde_json::json!({
+        "matches": matches,
+        "limitReached": count >= limit,
+    }));
+}
+
+/// Space-separated hex bytes
+pub fn format_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("1000..50000").unwrap(), (1000, 50000));
+        assert_eq!(parse_range("0x10..=0x20").unwrap(), (16, 32));
+        assert_eq!(parse_range("-5..5").unwrap(), (-5, 5));
+        assert!(parse_range("1000").is_err());
+        assert!(parse_range("a..b").is_err());
+    }
+}
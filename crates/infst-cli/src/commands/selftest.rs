@@ -0,0 +1,56 @@
+//! Selftest command implementation.
+//!
+//! Runs every memory parser once against the running game (at the title or
+//! song select screen) and prints a pass/fail matrix, so a game update that
+//! breaks one parser shows up without starting a tracking session.
+
+use anyhow::Result;
+use infst::{MemoryReader, OffsetSearcher, SelfTestReport, builtin_signatures, load_offsets};
+
+use crate::cli_utils;
+use crate::exit_code::ExitError;
+use crate::output::{self, outln};
+
+/// Run the selftest command
+pub fn run(offsets_file: Option<&str>, pid: Option<u32>) -> Result<()> {
+    let current_version = env!("CARGO_PKG_VERSION");
+    outln!("infst {} - Self-Test", current_version);
+
+    let process = cli_utils::open_process(pid)?;
+    outln!(
+        "Found process (PID: {}, Base: 0x{:X})",
+        process.pid,
+        process.base_address
+    );
+    let reader = MemoryReader::new(&process);
+
+    let offsets = if let Some(path) = offsets_file {
+        load_offsets(path)?
+    } else {
+        OffsetSearcher::new(&reader).search_all_with_signatures(&builtin_signatures())?
+    };
+
+    let report = SelfTestReport::run(&reader, &offsets);
+    outln!();
+    for check in &report.checks {
+        outln!(
+            "  {:<11}{}  {}",
+            check.parser,
+            check.status.as_str(),
+            check.detail
+        );
+    }
+    output::emit(&report);
+
+    if !report.passed() {
+        return Err(ExitError::validation_failed(format!(
+            "{} of {} parsers failed",
+            report.failures(),
+            report.checks.len()
+        ))
+        .into());
+    }
+    outln!();
+    outln!("All parsers passed");
+    Ok(())
+}
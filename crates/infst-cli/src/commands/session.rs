@@ -0,0 +1,51 @@
+//! Session control command implementation.
+//!
+//! Queues pause/resume/note/copy/export/marquee commands for a tracker running in another
+//! process; the tracker applies them on its next poll.
+
+use std::path::Path;
+
+use anyhow::Result;
+use infst::{SessionCommand, SessionControl};
+
+use crate::cli::SessionAction;
+use crate::output::{self, outln};
+
+/// Map a CLI action to the queued session command
+pub fn command_for(action: SessionAction) -> SessionCommand {
+    match action {
+        SessionAction::Pause => SessionCommand::Pause,
+        SessionAction::Resume => SessionCommand::Resume,
+        SessionAction::Note { text } => SessionCommand::Annotate { text },
+        SessionAction::Copy => SessionCommand::Copy,
+        SessionAction::Export => SessionCommand::Export,
+        SessionAction::Marquee => SessionCommand::ToggleMarquee,
+    }
+}
+
+/// Run the session command
+pub fn run(action: SessionAction, session_dir: &Path) -> Result<()> {
+    let command = command_for(action);
+    let control = SessionControl::new(session_dir);
+    control.send(&command)?;
+
+    match &command {
+        SessionCommand::Pause => outln!("Pause requested"),
+        SessionCommand::Resume => outln!("Resume requested"),
+        SessionCommand::Annotate { text } => outln!("Note queued: {}", text),
+        SessionCommand::Copy => outln!("Copy of the last play requested"),
+        SessionCommand::Export => outln!("Tracker export requested"),
+        SessionCommand::TogglePause => outln!("Pause toggle requested"),
+        SessionCommand::ToggleMarquee => outln!("Marquee toggle requested"),
+    }
+    outln!(
+        "(applied by the running tracker; queued in {})",
+        control.path().display()
+    );
+
+    output::emit(&serde_json::json!({
+        "queued": command,
+        "path": control.path(),
+    }));
+    Ok(())
+}
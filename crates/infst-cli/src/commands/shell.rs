@@ -0,0 +1,466 @@
+//! Interactive memory exploration shell.
+//!
+//! Keeps one process handle open across commands so layout investigation
+//! does not pay for process detection on every hexdump or search.
+//!
+//! ```text
+//! infst> scan i32 9003
+//! infst> hexdump $ 64
+//! infst> follow base+0x3A8F1C0 0x10 0x8
+//! infst> struct song $
+//! ```
+
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use anyhow::{Result, anyhow, bail};
+use infst::debug::{SearchPattern, StringEncoding, default_range, find_matches};
+use infst::util::hex::{apply_offset, parse_address, parse_offset};
+use infst::{
+    Currency, LaneCover, MemoryReader, PlayType, ProfileInfo, ReadMemory, SongInfo, UnlockData,
+};
+
+use super::hexdump;
+use super::search;
+use crate::cli_utils;
+
+/// Number of history entries kept on disk
+const HISTORY_LIMIT: usize = 500;
+
+/// Default number of bytes shown by `hexdump`
+const DEFAULT_DUMP_SIZE: usize = 256;
+
+/// Number of bytes shown at the end of `follow`
+const FOLLOW_DUMP_SIZE: usize = 64;
+
+/// Default number of `scan` results
+const DEFAULT_SCAN_LIMIT: usize = 20;
+
+const HELP: &str = "\
+Addresses: hex (0x1431B08A0), base[+/-offset], or $ (last address)
+
+  hexdump <addr> [size]              Dump bytes (alias: x)
+  follow <addr> [offset...]          Follow a pointer chain, dump the target
+  scan <i32|i16|str|wstr|bytes> <v>  Search module memory (wstr = UTF-16LE)
+  struct <kind> <addr>               Decode song, unlock, profile, currency or lanecover
+  limit [n]                          Show or set the scan result limit
+  history                            List previous commands
+  !! / !<n>                          Repeat the last / n-th command
+  help                               Show this help
+  quit                               Exit (alias: exit, q)";
+
+/// What the shell loop should do after a command
+#[derive(Debug, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    Quit,
+}
+
+/// Shell state bound to one open process
+pub struct MemoryShell<'a> {
+    reader: &'a dyn ReadMemory,
+    base_address: u64,
+    module_size: u32,
+    last_address: Option<u64>,
+    scan_limit: usize,
+    history: Vec<String>,
+}
+
+impl<'a> MemoryShell<'a> {
+    pub fn new(reader: &'a dyn ReadMemory, base_address: u64, module_size: u32) -> Self {
+        Self {
+            reader,
+            base_address,
+            module_size,
+            last_address: None,
+            scan_limit: DEFAULT_SCAN_LIMIT,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn with_history(mut self, history: Vec<String>) -> Self {
+        self.history = history;
+        self
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Resolve history references (`!!`, `!n`) and run one input line
+    pub fn execute_line(&mut self, line: &str, out: &mut String) -> Result<Flow> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(Flow::Continue);
+        }
+
+        let line = if let Some(reference) = line.strip_prefix('!') {
+            let index = if reference == "!" {
+                self.history.len()
+            } else {
+                reference
+                    .parse::<usize>()
+                    .map_err(|_| anyhow!("Invalid history reference: !{}", reference))?
+            };
+            let recalled = index
+                .checked_sub(1)
+                .and_then(|i| self.history.get(i))
+                .cloned()
+                .ok_or_else(|| anyhow!("No history entry {}", index))?;
+            let _ = writeln!(out, "{}", recalled);
+            recalled
+        } else {
+            line.to_string()
+        };
+
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+        }
+        self.execute(&line, out)
+    }
+
+    /// Run one command
+    pub fn execute(&mut self, line: &str, out: &mut String) -> Result<Flow> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Ok(Flow::Continue);
+        };
+        let args: Vec<&str> = words.collect();
+
+        match command {
+            "help" | "?" => {
+                let _ = writeln!(out, "{}", HELP);
+            }
+            "quit" | "exit" | "q" => return Ok(Flow::Quit),
+            "hexdump" | "x" => {
+                let address = self.address(args.first().copied())?;
+                let size = match args.get(1) {
+                    Some(size) => parse_size(size)?,
+                    None => DEFAULT_DUMP_SIZE,
+                };
+                self.dump(address, size, out)?;
+            }
+            "follow" => self.follow(&args, out)?,
+            "scan" => self.scan(&args, line, out)?,
+            "struct" => self.decode_struct(&args, out)?,
+            "limit" => {
+                if let Some(limit) = args.first() {
+                    self.scan_limit = limit.parse()?;
+                }
+                let _ = writeln!(out, "Scan limit: {}", self.scan_limit);
+            }
+            "history" => {
+                for (i, entry) in self.history.iter().enumerate() {
+                    let _ = writeln!(out, "{:>4}  {}", i + 1, entry);
+                }
+            }
+            other => bail!("Unknown command: {} (type `help`)", other),
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// Parse an address argument: hex, `base[+/-offset]`, or `$`
+    fn address(&self, arg: Option<&str>) -> Result<u64> {
+        let arg = arg.ok_or_else(|| anyhow!("Missing address"))?;
+        if arg == "$" {
+            return self
+                .last_address
+                .ok_or_else(|| anyhow!("No previous address ($)"));
+        }
+        if let Some(rest) = arg.strip_prefix("base") {
+            if rest.is_empty() {
+                return Ok(self.base_address);
+            }
+            if rest.starts_with(['+', '-']) {
+                return Ok(apply_offset(self.base_address, parse_offset(rest)?));
+            }
+            bail!("Invalid address: {}", arg);
+        }
+        Ok(parse_address(arg)?)
+    }
+
+    fn dump(&mut self, address: u64, size: usize, out: &mut String) -> Result<()> {
+        let bytes = self.reader.read_bytes(address, size)?;
+        let _ = writeln!(out, "Hexdump at 0x{:X} ({} bytes):", address, size);
+        for line in hexdump::format_lines(&bytes, true) {
+            let _ = writeln!(out, "{}", line);
+        }
+        self.last_address = Some(address);
+        Ok(())
+    }
+
+    /// `follow <addr> [offset...]`: read the pointer at `addr`, then for each
+    /// offset read the pointer at `previous + offset`; dump the final target
+    fn follow(&mut self, args: &[&str], out: &mut String) -> Result<()> {
+        let start = self.address(args.first().copied())?;
+        let mut pointer = self.reader.read_u64(start)?;
+        let _ = writeln!(out, "[0x{:X}] -> 0x{:X}", start, pointer);
+        for offset in args.iter().skip(1) {
+            let offset = parse_offset(offset)?;
+            let target = self.reader.read_u64(apply_offset(pointer, offset))?;
+            let sign = if offset < 0 { '-' } else { '+' };
+            let _ = writeln!(
+                out,
+                "[0x{:X} {} 0x{:X}] -> 0x{:X}",
+                pointer,
+                sign,
+                offset.unsigned_abs(),
+                target
+            );
+            pointer = target;
+        }
+        self.dump(pointer, FOLLOW_DUMP_SIZE, out)
+    }
+
+    fn scan(&mut self, args: &[&str], line: &str, out: &mut String) -> Result<()> {
+        let (kind, value) = match args {
+            [kind, value, ..] => (*kind, *value),
+            _ => bail!("Usage: scan <i32|i16|str|wstr|bytes> <value>"),
+        };
+        let pattern = match kind {
+            "i32" => SearchPattern::i32(value.parse()?),
+            "i16" => SearchPattern::i16(value.parse()?),
+            // Strings and byte patterns may contain spaces: use the rest of the line
+            "str" | "wstr" | "bytes" => {
+                let rest = line
+                    .split_once(kind)
+                    .map(|(_, rest)| rest.trim())
+                    .unwrap_or(value);
+                match kind {
+                    "str" => SearchPattern::string(rest),
+                    "wstr" => SearchPattern::text(rest, StringEncoding::Utf16le, false)?,
+                    _ => SearchPattern::parse(rest)?,
+                }
+            }
+            other => bail!("Unknown scan type: {} (i32, i16, str, wstr, bytes)", other),
+        };
+
+        let (start, end) = default_range(self.base_address, self.module_size);
+        let found = find_matches(self.reader, &pattern, start, end, self.scan_limit);
+        for (i, &address) in found.iter().enumerate() {
+            let context = self.reader.read_bytes(address, 16).unwrap_or_default();
+            let _ = writeln!(
+                out,
+                "[{}] 0x{:X}  {}",
+                i + 1,
+                address,
+                search::format_bytes(&context)
+            );
+        }
+        let _ = writeln!(out, "Found {} result(s)", found.len());
+        if let Some(&first) = found.first() {
+            self.last_address = Some(first);
+        }
+        Ok(())
+    }
+
+    fn decode_struct(&mut self, args: &[&str], out: &mut String) -> Result<()> {
+        let kind = args.first().ok_or_else(|| {
+            anyhow!("Usage: struct <song|unlock|profile|currency|lanecover> <addr>")
+        })?;
+        let address = self.address(args.get(1).copied())?;
+        let decoded = match *kind {
+            "song" => {
+                let bytes = self.reader.read_bytes(address, SongInfo::MEMORY_SIZE)?;
+                SongInfo::parse_from_buffer(&bytes, 0)?.map(|song| format!("{:#?}", song))
+            }
+            "unlock" => {
+                let bytes = self.reader.read_bytes(address, UnlockData::MEMORY_SIZE)?;
+                UnlockData::from_bytes(&bytes).map(|unlock| format!("{:#?}", unlock))
+            }
+            "profile" => {
+                let bytes = self.reader.read_bytes(address, ProfileInfo::MEMORY_SIZE)?;
+                ProfileInfo::from_bytes(&bytes).map(|profile| format!("{:#?}", profile))
+            }
+            "currency" => {
+                let bytes = self.reader.read_bytes(address, Currency::MEMORY_SIZE)?;
+                Currency::from_bytes(&bytes).map(|currency| format!("{:#?}", currency))
+            }
+            "lanecover" => {
+                let bytes = self.reader.read_bytes(address, LaneCover::MEMORY_SIZE)?;
+                let sides =
+                    [PlayType::P1, PlayType::P2].map(|side| LaneCover::from_bytes(&bytes, side));
+                sides
+                    .iter()
+                    .any(Option::is_some)
+                    .then(|| format!("P1: {:#?}\nP2: {:#?}", sides[0], sides[1]))
+            }
+            other => bail!(
+                "Unknown structure: {} (song, unlock, profile, currency, lanecover)",
+                other
+            ),
+        };
+        match decoded {
+            Some(text) => {
+                let _ = writeln!(out, "{}", text);
+            }
+            None => {
+                let _ = writeln!(out, "No valid {} at 0x{:X}", kind, address);
+            }
+        }
+        self.last_address = Some(address);
+        Ok(())
+    }
+}
+
+fn parse_size(s: &str) -> Result<usize> {
+    let size = if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        usize::from_str_radix(hex, 16)?
+    } else {
+        s.parse()?
+    };
+    Ok(size)
+}
+
+fn history_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("infst").join("shell_history"))
+}
+
+fn load_history() -> Vec<String> {
+    history_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|text| text.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+fn save_history(history: &[String]) {
+    let Some(path) = history_path() else {
+        return;
+    };
+    let start = history.len().saturating_sub(HISTORY_LIMIT);
+    let mut text = history[start..].join("\n");
+    text.push('\n');
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    if let Err(e) = std::fs::write(&path, text) {
+        eprintln!("Failed to save history to {}: {}", path.display(), e);
+    }
+}
+
+/// Run the interactive shell
+pub fn run(pid: Option<u32>) -> Result<()> {
+    let current_version = env!("CARGO_PKG_VERSION");
+    println!("infst {} - Memory Shell", current_version);
+
+    let process = cli_utils::open_process(pid)?;
+    println!(
+        "Attached to PID {} (Base: 0x{:X}, Size: 0x{:X}). Type `help` for commands.",
+        process.pid, process.base_address, process.module_size
+    );
+
+    let reader = MemoryReader::new(&process);
+    let mut shell = MemoryShell::new(&reader, process.base_address, process.module_size)
+        .with_history(load_history());
+
+    let stdin = io::stdin();
+    let mut input = String::new();
+    loop {
+        print!("infst> ");
+        io::stdout().flush()?;
+
+        input.clear();
+        if stdin.lock().read_line(&mut input)? == 0 {
+            println!();
+            break;
+        }
+
+        let mut out = String::new();
+        let result = shell.execute_line(&input, &mut out);
+        print!("{}", out);
+        match result {
+            Ok(Flow::Continue) => {}
+            Ok(Flow::Quit) => break,
+            Err(e) => println!("Error: {:#}", e),
+        }
+    }
+
+    save_history(shell.history());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use infst::process::MockMemoryBuilder;
+
+    const BASE: u64 = 0x1000;
+
+    fn run_line(shell: &mut MemoryShell<'_>, line: &str) -> String {
+        let mut out = String::new();
+        shell.execute_line(line, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_hexdump_and_last_address() {
+        let reader = MockMemoryBuilder::new()
+            .base(BASE)
+            .with_size(0x100)
+            .write_bytes(0x20, b"INFINITAS")
+            .build();
+        let mut shell = MemoryShell::new(&reader, BASE, 0x100);
+
+        let out = run_line(&mut shell, "x base+0x20 16");
+        assert!(out.contains("Hexdump at 0x1020 (16 bytes)"));
+        assert!(out.contains("|INFINITAS.......|"));
+
+        let out = run_line(&mut shell, "hexdump $ 0x10");
+        assert!(out.contains("Hexdump at 0x1020"));
+    }
+
+    #[test]
+    fn test_follow_pointer_chain() {
+        let reader = MockMemoryBuilder::new()
+            .base(BASE)
+            .with_size(0x100)
+            .write_u64(0x00, BASE + 0x40)
+            .write_u64(0x50, BASE + 0x80)
+            .write_bytes(0x80, &[0xDE, 0xAD])
+            .build();
+        let mut shell = MemoryShell::new(&reader, BASE, 0x100);
+
+        let out = run_line(&mut shell, "follow 0x1000 0x10");
+        assert!(out.contains("[0x1000] -> 0x1040"));
+        assert!(out.contains("[0x1040 + 0x10] -> 0x1080"));
+        assert!(out.contains("0x000: DE AD"));
+    }
+
+    #[test]
+    fn test_history_recall() {
+        let reader = MockMemoryBuilder::new().base(BASE).with_size(0x40).build();
+        let mut shell = MemoryShell::new(&reader, BASE, 0x40);
+
+        run_line(&mut shell, "limit 5");
+        run_line(&mut shell, "hexdump base 4");
+        let out = run_line(&mut shell, "!1");
+        assert!(out.starts_with("limit 5\nScan limit: 5"));
+        let out = run_line(&mut shell, "!!");
+        assert!(out.contains("Scan limit: 5"));
+
+        // Repeats are recorded once
+        assert_eq!(shell.history(), ["limit 5", "hexdump base 4", "limit 5"]);
+
+        let mut out = String::new();
+        assert!(shell.execute_line("!9", &mut out).is_err());
+        assert!(shell.execute_line("bogus", &mut out).is_err());
+        assert_eq!(shell.execute_line("quit", &mut out).unwrap(), Flow::Quit);
+    }
+
+    #[test]
+    fn test_struct_unlock() {
+        let reader = MockMemoryBuilder::new()
+            .base(BASE)
+            .with_size(0x40)
+            .write_u32(0x00, 1000)
+            .write_i32(0x04, 2)
+            .write_i32(0x08, 0b111)
+            .build();
+        let mut shell = MemoryShell::new(&reader, BASE, 0x40);
+
+        let out = run_line(&mut shell, "struct unlock 0x1000");
+        assert!(out.contains("song_id: 1000"));
+        assert!(out.contains("Bits"));
+    }
+}
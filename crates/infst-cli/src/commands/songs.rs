@@ -0,0 +1,59 @@
+//! Songs command for searching charts in the song database.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use infst::{
+    ChartQuery, MemoryReader, OffsetSearcher, SongInfo, Tracker, fetch_song_database, search_charts,
+};
+
+use crate::cli_utils;
+use crate::output::{self, outln};
+
+/// Print the charts matching `query`
+///
+/// The song database comes from `from` (a tracker export) when given, else
+/// from game memory. When the game isn't running, the tracker file in the
+/// data directory (`cached`) is used if it exists.
+pub fn run(query: &ChartQuery, from: Option<&str>, cached: &Path, pid: Option<u32>) -> Result<()> {
+    let song_db = match from {
+        Some(path) => load_tracker(Path::new(path))?,
+        None => match cli_utils::open_process(pid) {
+            Ok(process) => {
+                let reader = MemoryReader::new(&process);
+                let mut searcher = OffsetSearcher::new(&reader);
+                let offsets = searcher.search_data_offsets()?;
+                eprintln!("Loading song database...");
+                fetch_song_database(&reader, offsets.song_list)?
+            }
+            Err(e) if pid.is_none() && cached.exists() => {
+                eprintln!("{:#}; using {}", e, cached.display());
+                load_tracker(cached)?
+            }
+            Err(e) => return Err(e),
+        },
+    };
+
+    let matches = search_charts(&song_db, query);
+    for found in &matches {
+        outln!(
+            "{:>5}  {:<3} {:>2}  {:>4} notes  {}",
+            found.chart.song_id,
+            found.chart.difficulty.short_name(),
+            found.level,
+            found.total_notes,
+            found.title
+        );
+    }
+    outln!("{} charts", matches.len());
+
+    output::emit(&serde_json::json!({ "charts": matches }));
+    Ok(())
+}
+
+fn load_tracker(path: &Path) -> Result<HashMap<u32, SongInfo>> {
+    let tracker = Tracker::load(path)
+        .with_context(|| format!("Failed to load tracker export {}", path.display()))?;
+    Ok(tracker.song_database())
+}
@@ -1,28 +1,56 @@
 //! Status command implementation.
 
+use std::collections::HashMap;
+use std::path::Path;
+
 use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
 use infst::config::find_game_version;
+use infst::stats::PLAY_COUNTS_FILE;
+use infst::util::hex::format_address_padded;
 use infst::{
-    MemoryReader, OffsetSearcher, ProcessHandle, StatusInfo, builtin_signatures, load_offsets,
+    MemoryReader, OffsetSearcher, PlayCounters, PlayCounts, ProcessHandle, ScheduleEntry,
+    StatusInfo, SubsystemHealth, builtin_signatures, load_offsets, load_schedule_status,
+    load_session_history, merge_signature_file,
 };
+use tracing::warn;
+
+use crate::cli_utils;
+use crate::environment::{Environment, OffsetsSource};
+use crate::output::{self, outln};
 
 /// Run the status command
-pub fn run(offsets_file: Option<&str>, pid: Option<u32>, json: bool) -> Result<()> {
+pub fn run(
+    offsets_file: Option<&str>,
+    signatures_file: &Path,
+    pid: Option<u32>,
+    json: bool,
+    mut environment: Environment,
+) -> Result<()> {
+    let session_dir = environment.session_dir.clone();
     let current_version = env!("CARGO_PKG_VERSION");
-    println!("infst {} - Status Mode", current_version);
+    outln!("infst {} - Status Mode", current_version);
+
+    // Run times recorded by the tracker's export scheduler
+    let schedule = load_schedule_status(&session_dir).unwrap_or_else(|e| {
+        warn!("Failed to read export schedule: {}", e);
+        Vec::new()
+    });
 
     // Open process
     let process = if let Some(pid) = pid {
-        println!("Opening process with PID {}...", pid);
+        outln!("Opening process with PID {}...", pid);
         ProcessHandle::open(pid)?
     } else {
-        println!("Searching for INFINITAS...");
-        ProcessHandle::find_and_open()?
+        outln!("Searching for INFINITAS...");
+        cli_utils::find_process()?
     };
 
-    println!(
+    outln!(
         "Found process (PID: {}, Base: 0x{:X}, Size: 0x{:X})",
-        process.pid, process.base_address, process.module_size
+        process.pid,
+        process.base_address,
+        process.module_size
     );
 
     let reader = MemoryReader::new(&process);
@@ -30,24 +58,31 @@ pub fn run(offsets_file: Option<&str>, pid: Option<u32>, json: bool) -> Result<(
     // Game version detection
     let game_version = match find_game_version(&reader, process.base_address) {
         Ok(Some(version)) => {
-            println!("Game version: {}", version);
+            outln!("Game version: {}", version);
             Some(version)
         }
         Ok(None) => {
-            println!("Could not detect game version");
+            outln!("Could not detect game version");
             None
         }
         Err(e) => {
-            println!("Failed to check game version: {}", e);
+            outln!("Failed to check game version: {}", e);
             None
         }
     };
 
     // Load or search for offsets
+    let mut signatures = builtin_signatures();
+    merge_signature_file(&mut signatures, signatures_file);
     let offsets = if let Some(path) = offsets_file {
         match load_offsets(path) {
-            Ok(offsets) => {
-                println!("Loaded offsets from {}", path);
+            Ok(mut offsets) => {
+                outln!("Loaded offsets from {}", path);
+                let searcher = OffsetSearcher::new(&reader);
+                for name in searcher.clear_invalid_optional_offsets(&mut offsets) {
+                    outln!("Ignoring invalid {} offset", name);
+                }
+                searcher.search_optional_offsets(&signatures, &mut offsets);
                 offsets
             }
             Err(e) => {
@@ -55,8 +90,7 @@ pub fn run(offsets_file: Option<&str>, pid: Option<u32>, json: bool) -> Result<(
             }
         }
     } else {
-        println!("Searching for offsets...");
-        let signatures = builtin_signatures();
+        outln!("Searching for offsets...");
         let mut searcher = OffsetSearcher::new(&reader);
         match searcher.search_all_with_signatures(&signatures) {
             Ok(mut offsets) => {
@@ -71,8 +105,15 @@ pub fn run(offsets_file: Option<&str>, pid: Option<u32>, json: bool) -> Result<(
         }
     };
 
+    environment.game_version = game_version.clone();
+    environment.offsets_source = Some(if offsets_file.is_some() {
+        OffsetsSource::File
+    } else {
+        OffsetsSource::Search
+    });
+
     // Collect status
-    let status = StatusInfo::collect(
+    let mut status = StatusInfo::collect(
         &reader,
         process.pid,
         process.base_address,
@@ -80,99 +121,159 @@ pub fn run(offsets_file: Option<&str>, pid: Option<u32>, json: bool) -> Result<(
         game_version,
         &offsets,
     );
+    status.health.last_play = last_play_time(&session_dir);
+    let play_counts = PlayCounters::load(session_dir.join(PLAY_COUNTS_FILE))
+        .map(|counters| counters.counts(chrono::Local::now().date_naive()))
+        .unwrap_or_else(|e| {
+            warn!("Failed to read play counts: {}", e);
+            PlayCounts::default()
+        });
 
-    if json {
-        println!("{}", serde_json::to_string_pretty(&status)?);
+    if output::is_json() || json {
+        let mut value = serde_json::to_value(&status)?;
+        value["schedule"] = serde_json::to_value(&schedule)?;
+        value["playCounts"] = serde_json::to_value(play_counts)?;
+        value["environment"] = serde_json::to_value(&environment)?;
+        if output::is_json() {
+            output::emit(&value);
+        } else {
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        }
     } else {
-        println!();
-        println!("=== Offset Status ===");
-        println!(
-            "SongList:     0x{:016X}  {}",
-            status.offsets.song_list.address,
+        outln!();
+        outln!("=== Offset Status ===");
+        outln!(
+            "SongList:     {}  {}",
+            format_address_padded(status.offsets.song_list.address, 16),
             if status.offsets.song_list.valid {
                 "✓"
             } else {
                 "✗"
             }
         );
-        println!("              {}", status.offsets.song_list.reason);
-        println!(
-            "JudgeData:    0x{:016X}  {}",
-            status.offsets.judge_data.address,
+        outln!("              {}", status.offsets.song_list.reason);
+        outln!(
+            "JudgeData:    {}  {}",
+            format_address_padded(status.offsets.judge_data.address, 16),
             if status.offsets.judge_data.valid {
                 "✓"
             } else {
                 "✗"
             }
         );
-        println!("              {}", status.offsets.judge_data.reason);
-        println!(
-            "PlaySettings: 0x{:016X}  {}",
-            status.offsets.play_settings.address,
+        outln!("              {}", status.offsets.judge_data.reason);
+        outln!(
+            "PlaySettings: {}  {}",
+            format_address_padded(status.offsets.play_settings.address, 16),
             if status.offsets.play_settings.valid {
                 "✓"
             } else {
                 "✗"
             }
         );
-        println!("              {}", status.offsets.play_settings.reason);
-        println!(
-            "PlayData:     0x{:016X}  {}",
-            status.offsets.play_data.address,
+        outln!("              {}", status.offsets.play_settings.reason);
+        outln!(
+            "PlayData:     {}  {}",
+            format_address_padded(status.offsets.play_data.address, 16),
             if status.offsets.play_data.valid {
                 "✓"
             } else {
                 "✗"
             }
         );
-        println!("              {}", status.offsets.play_data.reason);
-        println!(
-            "CurrentSong:  0x{:016X}  {}",
-            status.offsets.current_song.address,
+        outln!("              {}", status.offsets.play_data.reason);
+        outln!(
+            "CurrentSong:  {}  {}",
+            format_address_padded(status.offsets.current_song.address, 16),
             if status.offsets.current_song.valid {
                 "✓"
             } else {
                 "✗"
             }
         );
-        println!("              {}", status.offsets.current_song.reason);
-        println!(
-            "DataMap:      0x{:016X}  {}",
-            status.offsets.data_map.address,
+        outln!("              {}", status.offsets.current_song.reason);
+        outln!(
+            "DataMap:      {}  {}",
+            format_address_padded(status.offsets.data_map.address, 16),
             if status.offsets.data_map.valid {
                 "✓"
             } else {
                 "✗"
             }
         );
-        println!("              {}", status.offsets.data_map.reason);
-        println!(
-            "UnlockData:   0x{:016X}  {}",
-            status.offsets.unlock_data.address,
+        outln!("              {}", status.offsets.data_map.reason);
+        outln!(
+            "UnlockData:   {}  {}",
+            format_address_padded(status.offsets.unlock_data.address, 16),
             if status.offsets.unlock_data.valid {
                 "✓"
             } else {
                 "✗"
             }
         );
-        println!("              {}", status.offsets.unlock_data.reason);
+        outln!("              {}", status.offsets.unlock_data.reason);
 
-        println!();
-        println!("=== Song Database ===");
-        println!("Songs found: {}", status.song_count);
+        outln!();
+        outln!("=== Song Database ===");
+        outln!("Songs found: {}", status.song_count);
 
         if let Some(ref current) = status.current_song {
-            println!();
-            println!("=== Current Song ===");
-            println!("Song ID: {}", current.song_id);
-            println!("Difficulty: {}", current.difficulty);
+            outln!();
+            outln!("=== Current Song ===");
+            outln!("Song ID: {}", current.song_id);
+            outln!("Difficulty: {}", current.difficulty);
             if let Some(ref title) = current.title {
-                println!("Title: {}", title);
+                outln!("Title: {}", title);
             }
         }
 
-        println!();
-        println!(
+        if let Some(ref profile) = status.profile {
+            outln!();
+            outln!("=== Profile ===");
+            outln!("DJ Name: {}", profile.dj_name);
+            outln!(
+                "Dan: SP {} / DP {}",
+                profile.sp_dan.map(|d| d.display_name()).unwrap_or("-"),
+                profile.dp_dan.map(|d| d.display_name()).unwrap_or("-")
+            );
+            outln!("Play count: {}", profile.play_count);
+        }
+
+        if let Some(ref currency) = status.currency {
+            outln!();
+            outln!("=== Currency ===");
+            outln!("Bits: {}", currency.bits);
+            outln!("Tickets: {}", currency.tickets);
+        }
+
+        if let Some(ref cover) = status.lane_cover {
+            outln!();
+            outln!("=== Lane Cover (1P) ===");
+            outln!(
+                "SUDDEN+: {} / HIDDEN+: {} / LIFT: {}",
+                cover.sudden,
+                cover.hidden,
+                cover.lift
+            );
+            outln!("Green number: {}", cover.green_number);
+        }
+
+        print_health(&status.health);
+        outln!(
+            "Plays:        {} today, {} this week",
+            play_counts.today.plays,
+            play_counts.this_week.plays
+        );
+        print_schedule(&schedule);
+
+        outln!();
+        outln!("=== Environment ===");
+        for line in environment.lines() {
+            outln!("{}", line);
+        }
+
+        outln!();
+        outln!(
             "Overall validation: {}",
             if status.all_valid { "PASSED" } else { "FAILED" }
         );
@@ -180,3 +281,83 @@ pub fn run(offsets_file: Option<&str>, pid: Option<u32>, json: bool) -> Result<(
 
     Ok(())
 }
+
+/// Time of the last play recorded in the session files
+pub fn last_play_time(session_dir: &Path) -> Option<DateTime<Utc>> {
+    // Titles aren't needed for timestamps, so no song database
+    match load_session_history(session_dir, &HashMap::new()) {
+        Ok(plays) => plays.last().map(|play| play.timestamp),
+        Err(e) => {
+            warn!("Failed to read session history: {}", e);
+            None
+        }
+    }
+}
+
+/// Print the subsystem health section
+pub fn print_health(health: &SubsystemHealth) {
+    let mark = |ok: bool| if ok { "✓" } else { "✗" };
+    outln!();
+    outln!("=== Subsystem Health ===");
+    let song_db = &health.song_db;
+    match &song_db.error {
+        Some(e) => outln!("Song DB:      ✗  {}", e),
+        None => outln!(
+            "Song DB:      {}  {} songs, anchors {}/{} OK",
+            mark(song_db.loaded && !song_db.anchors.is_drift()),
+            song_db.song_count,
+            song_db.anchors.checked - song_db.anchors.mismatches.len(),
+            song_db.anchors.checked
+        ),
+    }
+    for mismatch in &song_db.anchors.mismatches {
+        outln!("              {}", mismatch);
+    }
+    match &health.score_map.error {
+        Some(e) => outln!("Score map:    ✗  {}", e),
+        None => outln!(
+            "Score map:    {}  {} songs with scores",
+            mark(health.score_map.loaded),
+            health.score_map.entry_count
+        ),
+    }
+    match &health.unlock_data.error {
+        Some(e) => outln!("Unlock data:  ✗  {}", e),
+        None => outln!(
+            "Unlock data:  {}  {} songs ({:.0}% of the song DB)",
+            mark(health.unlock_data.loaded),
+            health.unlock_data.entry_count,
+            health.unlock_data.coverage * 100.0
+        ),
+    }
+    outln!(
+        "Last play:    {}",
+        health.last_play.map_or("-".to_string(), |time| time
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string())
+    );
+}
+
+fn print_schedule(schedule: &[ScheduleEntry]) {
+    if schedule.is_empty() {
+        return;
+    }
+    let time = |time: Option<chrono::NaiveDateTime>| {
+        time.map_or("-".to_string(), |t| t.format("%Y-%m-%d %H:%M").to_string())
+    };
+    outln!();
+    outln!("=== Scheduled Exports ===");
+    for entry in schedule {
+        outln!(
+            "{}  last: {}  next: {}",
+            entry.job,
+            time(entry.last_run),
+            if entry.next_run.is_none() {
+                "at session end".to_string()
+            } else {
+                time(entry.next_run)
+            }
+        );
+    }
+}
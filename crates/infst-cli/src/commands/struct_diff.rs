@@ -0,0 +1,175 @@
+//! Struct diff command implementation.
+//!
+//! Reads the same layout at two addresses (P1 vs P2 judge blocks, two song
+//! entries, ...) and prints a field-by-field comparison. Differing words
+//! between the known fields are listed too, which is how per-player offsets
+//! are found after game updates.
+//!
+//! ```text
+//! Offset  Field   A     B
+//! 0x000   PGreat  1500  1400  *
+//! 0x004   Great   80    80
+//! ```
+
+use anyhow::{Result, bail};
+use clap::builder::PossibleValuesParser;
+use infst::util::hex::{format_address_padded, parse_address};
+use infst::{FieldDiff, MemoryReader, StructLayout, diff_struct};
+
+use crate::cli_utils;
+use crate::output::{self, outln};
+
+/// Layout name for unnamed 32-bit word comparisons (requires `--size`)
+const WORDS_LAYOUT: &str = "words";
+
+/// Accepted `--layout` values
+pub fn layout_parser() -> PossibleValuesParser {
+    PossibleValuesParser::new(
+        StructLayout::ALL
+            .iter()
+            .map(|layout| layout.name)
+            .chain([WORDS_LAYOUT]),
+    )
+}
+
+/// Resolve the layout name and optional size override
+pub fn resolve_layout(name: &str, size: Option<usize>) -> Result<StructLayout> {
+    let layout = if name == WORDS_LAYOUT {
+        let Some(size) = size else {
+            bail!("--size is required with --layout words");
+        };
+        StructLayout::words(size)
+    } else {
+        let Some(layout) = StructLayout::by_name(name) else {
+            bail!("Unknown layout: {}", name);
+        };
+        size.map_or(layout, |size| layout.with_size(size))
+    };
+    if layout.size == 0 {
+        bail!("Layout size must be greater than zero");
+    }
+    Ok(layout)
+}
+
+/// Format the comparison as aligned table rows
+pub fn format_table(diffs: &[FieldDiff]) -> Vec<String> {
+    let rows: Vec<[String; 4]> = diffs
+        .iter()
+        .map(|d| {
+            [
+                format_address_padded(d.offset as u64, 3),
+                d.field.unwrap_or("?").to_string(),
+                d.a.clone(),
+                d.b.clone(),
+            ]
+        })
+        .collect();
+    let header = ["Offset", "Field", "A", "B"].map(String::from);
+    let mut widths = header.clone().map(|h| h.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |row: &[String; 4], marker: &str| {
+        let line = format!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {:<w3$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            marker,
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+        );
+        line.trim_end().to_string()
+    };
+    std::iter::once(format_row(&header, ""))
+        .chain(
+            rows.iter()
+                .zip(diffs)
+                .map(|(row, d)| format_row(row, if d.changed() { "*" } else { "" })),
+        )
+        .collect()
+}
+
+/// Run the struct-diff command
+pub fn run(
+    layout: &str,
+    a: &str,
+    b: &str,
+    size: Option<usize>,
+    changed_only: bool,
+    pid: Option<u32>,
+) -> Result<()> {
+    let layout = resolve_layout(layout, size)?;
+    let a = parse_address(a)?;
+    let b = parse_address(b)?;
+
+    let process = cli_utils::open_process(pid)?;
+    let reader = MemoryReader::new(&process);
+
+    let mut diffs = diff_struct(&reader, &layout, a, b)?;
+    let changed = diffs.iter().filter(|d| d.changed()).count();
+    if changed_only {
+        diffs.retain(FieldDiff::changed);
+    }
+
+    outln!(
+        "Comparing {} ({} bytes): A=0x{:X} B=0x{:X}",
+        layout.name,
+        layout.size,
+        a,
+        b
+    );
+    outln!();
+    for line in format_table(&diffs) {
+        outln!("{}", line);
+    }
+    outln!();
+    outln!("{} field(s) differ", changed);
+
+    output::emit(&serde_json::json!({
+        "layout": layout.name,
+        "size": layout.size,
+        "a": a,
+        "b": b,
+        "fields": diffs,
+    }));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use infst::process::MockMemoryBuilder;
+
+    #[test]
+    fn test_resolve_layout() {
+        assert_eq!(resolve_layout("judge-player", None).unwrap().size, 20);
+        assert_eq!(resolve_layout("song", Some(0x40)).unwrap().size, 0x40);
+        assert_eq!(resolve_layout("words", Some(32)).unwrap().size, 32);
+        assert!(resolve_layout("words", None).is_err());
+        assert!(resolve_layout("judge", Some(0)).is_err());
+        assert!(resolve_layout("nope", None).is_err());
+    }
+
+    #[test]
+    fn test_format_table_marks_differences() {
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x40)
+            .write_i32(0x00, 1500)
+            .write_i32(0x14, 1400)
+            .build();
+        let layout = resolve_layout("judge-player", None).unwrap();
+        let diffs = diff_struct(&reader, &layout, 0x1000, 0x1014).unwrap();
+        let lines = format_table(&diffs);
+        assert_eq!(lines[0], "Offset  Field   A     B");
+        assert_eq!(lines[1], "0x000   PGreat  1500  1400  *");
+        assert_eq!(lines[2], "0x004   Great   0     0");
+    }
+}
@@ -9,13 +9,15 @@ use anyhow::{Context, Result};
 use flate2::Compression;
 use flate2::write::GzEncoder;
 use infst::{
-    MemoryReader, OffsetSearcher, ScoreMap, chart::Difficulty, fetch_song_database_bulk,
+    ChartId, IgnoreList, MemoryReader, OffsetSearcher, ScoreMap, fetch_song_database_bulk,
     score::Lamp,
 };
 use serde::{Deserialize, Serialize};
 
-use super::upload::resolve_credentials;
+use super::upload::{emit_result, resolve_credentials};
 use crate::cli_utils;
+use crate::consent::{SubmitOptions, confirm_submission};
+use crate::output::outln;
 
 #[derive(Serialize, Clone)]
 struct LampEntry {
@@ -29,19 +31,6 @@ struct LampEntry {
     miss_count: u32,
 }
 
-const ALL_DIFFICULTIES: [Difficulty; 10] = [
-    Difficulty::SpB,
-    Difficulty::SpN,
-    Difficulty::SpH,
-    Difficulty::SpA,
-    Difficulty::SpL,
-    Difficulty::DpB,
-    Difficulty::DpN,
-    Difficulty::DpH,
-    Difficulty::DpA,
-    Difficulty::DpL,
-];
-
 // --- Sync cache for differential sync ---
 
 const SYNC_CACHE_FILE: &str = ".infst-sync-cache.json";
@@ -77,7 +66,13 @@ impl SyncCache {
     }
 }
 
-pub fn run(endpoint: Option<&str>, token: Option<&str>, pid: Option<u32>) -> Result<()> {
+pub fn run(
+    endpoint: Option<&str>,
+    token: Option<&str>,
+    pid: Option<u32>,
+    ignore: &IgnoreList,
+    submit: &SubmitOptions,
+) -> Result<()> {
     let current_version = env!("CARGO_PKG_VERSION");
     eprintln!("infst {} - Sync Mode", current_version);
 
@@ -106,6 +101,7 @@ pub fn run(endpoint: Option<&str>, token: Option<&str>, pid: Option<u32>) -> Res
     eprintln!("Loading score data...");
     let score_map = ScoreMap::load_from_memory(&reader, offsets.data_map, &song_db)?;
     eprintln!("Loaded {} score entries", score_map.len());
+    let score_map = ignore.mask_scores(&score_map, &song_db);
 
     // Build LampEntry list directly from memory data
     let mut entries: Vec<LampEntry> = Vec::new();
@@ -116,16 +112,16 @@ pub fn run(endpoint: Option<&str>, token: Option<&str>, pid: Option<u32>) -> Res
             None => continue,
         };
 
-        for &diff in &ALL_DIFFICULTIES {
-            let diff_idx = diff as usize;
+        for chart in ChartId::all(*song_id) {
+            let diff = chart.difficulty;
 
             // Skip charts with no notes (chart doesn't exist)
-            if song_info.total_notes[diff_idx] == 0 {
+            if !chart.exists_in(song_info) {
                 continue;
             }
 
             // Sync only level 11/12 charts.
-            let level = song_info.levels[diff_idx];
+            let level = song_info.level_for(diff);
             if level != 11 && level != 12 {
                 continue;
             }
@@ -142,13 +138,14 @@ pub fn run(endpoint: Option<&str>, token: Option<&str>, pid: Option<u32>) -> Res
                 difficulty: diff.short_name().to_string(),
                 lamp: lamp.short_name().to_string(),
                 ex_score: score_data.get_score(diff),
-                miss_count: score_data.miss_count[diff_idx].unwrap_or(0),
+                miss_count: score_data.get_miss_count(diff).unwrap_or(0),
             });
         }
     }
 
     if entries.is_empty() {
-        println!("No play data found to sync.");
+        outln!("No play data found to sync.");
+        emit_result(0, 0, None);
         return Ok(());
     }
 
@@ -175,7 +172,16 @@ pub fn run(endpoint: Option<&str>, token: Option<&str>, pid: Option<u32>) -> Res
     };
 
     if entries_to_send.is_empty() {
-        println!("No changes detected since last sync.");
+        outln!("No changes detected since last sync.");
+        emit_result(entries.len(), 0, None);
+        return Ok(());
+    }
+
+    let body = serde_json::json!({ "entries": entries_to_send });
+    let payload = serde_json::to_string(&body).context("Failed to serialize JSON")?;
+    if !confirm_submission(&payload, entries_to_send.len(), &resolved_endpoint, submit)? {
+        outln!("Nothing was sent.");
+        emit_result(entries.len(), 0, None);
         return Ok(());
     }
 
@@ -193,8 +199,7 @@ pub fn run(endpoint: Option<&str>, token: Option<&str>, pid: Option<u32>) -> Res
         .build();
     let agent: ureq::Agent = config.into();
 
-    let body = serde_json::json!({ "entries": entries_to_send });
-    let json_bytes = serde_json::to_vec(&body).context("Failed to serialize JSON")?;
+    let json_bytes = payload.into_bytes();
 
     let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
     encoder
@@ -216,8 +221,13 @@ pub fn run(endpoint: Option<&str>, token: Option<&str>, pid: Option<u32>) -> Res
         .send(compressed.as_slice())
         .context("Failed to upload data")?;
 
-    println!("Sync complete (status: {})", response.status());
-    println!("Synced {} entries.", entries_to_send.len());
+    outln!("Sync complete (status: {})", response.status());
+    outln!("Synced {} entries.", entries_to_send.len());
+    emit_result(
+        entries.len(),
+        entries_to_send.len(),
+        Some(response.status().as_u16()),
+    );
 
     // Update cache with all current entries
     let mut new_cache = SyncCache {
@@ -0,0 +1,46 @@
+//! Tracker command: inspect the data the tracker keeps in the session directory.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use infst::ScoreAudit;
+
+use crate::cli::TrackerAction;
+use crate::output::{self, outln};
+
+/// Run a tracker subcommand
+pub fn run(action: TrackerAction, session_dir: &Path) -> Result<()> {
+    match action {
+        TrackerAction::Audit => audit(session_dir),
+    }
+}
+
+/// List the reads that were lower than the stored bests
+fn audit(session_dir: &Path) -> Result<()> {
+    let audit = ScoreAudit::new(session_dir);
+    let entries = audit
+        .entries()
+        .with_context(|| format!("Failed to read {}", audit.path().display()))?;
+    output::emit(&entries);
+
+    if entries.is_empty() {
+        eprintln!("No lower reads recorded");
+        return Ok(());
+    }
+    for entry in &entries {
+        outln!(
+            "{}  {}",
+            entry
+                .timestamp
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S"),
+            entry.delta()
+        );
+    }
+    eprintln!(
+        "{} lower reads (stored bests were kept; many at once usually means another profile)",
+        entries.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! Tracker diff command for comparing two tracker snapshots.
+
+use std::fs;
+
+use crate::output::{self, outln};
+use anyhow::{Context, Result};
+use infst::diff_tracker_tsv;
+use infst::storage::migrate::migrate;
+use infst::storage::{FileFormat, decode_text};
+
+/// Show cells that changed between two tracker TSV files
+pub fn run(old_path: &str, new_path: &str, json: bool) -> Result<()> {
+    let old =
+        decode_text(&fs::read(old_path).with_context(|| format!("Failed to read {}", old_path))?);
+    let new =
+        decode_text(&fs::read(new_path).with_context(|| format!("Failed to read {}", new_path))?);
+
+    // Upgrade old snapshots; snapshots from a newer version are rejected
+    let old = migrate(FileFormat::TrackerTsv, &old)
+        .with_context(|| format!("Failed to load {}", old_path))?
+        .content;
+    let new = migrate(FileFormat::TrackerTsv, &new)
+        .with_context(|| format!("Failed to load {}", new_path))?
+        .content;
+
+    let changes = diff_tracker_tsv(&old, &new);
+
+    if output::is_json() {
+        output::emit(&changes);
+        return Ok(());
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&changes)?);
+        return Ok(());
+    }
+
+    if changes.is_empty() {
+        eprintln!("No changes");
+        return Ok(());
+    }
+
+    let mut last_song = None;
+    for change in &changes {
+        if last_song != Some(&change.song_id) {
+            outln!("{} ({})", change.title, change.song_id);
+            last_song = Some(&change.song_id);
+        }
+        match &change.old {
+            Some(old) => outln!("  {}: {} -> {}", change.column, old, change.new),
+            None => outln!("  {}: {} (new)", change.column, change.new),
+        }
+    }
+    eprintln!("{} changed cells", changes.len());
+
+    Ok(())
+}
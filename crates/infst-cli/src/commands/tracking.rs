@@ -1,18 +1,37 @@
 //! Main tracking mode command.
 
 use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
-use infst::config::find_game_version;
+use chrono::{DateTime, Local, Utc};
+use infst::chart::SongEntryLayout;
+use infst::config::{find_game_version, submission};
+use infst::input::hotkeys::{HotkeyConfig, HotkeyListener};
+use infst::play::DEFAULT_DUPLICATE_WINDOW_SECS;
+use infst::storage::InstanceLock;
+use infst::stream::DEFAULT_CLIPBOARD_TEMPLATE;
 use infst::{
-    ApiConfig, Infst, InfstConfig, MemoryReader, OffsetSearcher, OffsetsCollection, ProcessHandle,
-    ScoreMap, SongInfo, load_offsets, save_offsets_to_cache, try_load_cached_offsets,
+    AnomalyMode, ApiConfig, DisplayTimezone, IgnoreList, Infst, InfstConfig, LayoutRegistry,
+    MemoryReader, OffsetClock, OffsetSearcher, OffsetSignatureSet, OffsetsCollection, Paths,
+    PauseMode, PrematureMode, ProcessHandle, PushConfig, RetryPolicies, RetryPolicy,
+    ScheduledExport, ScoreMap, SessionControl, SharedClock, SongInfo, StreamConfig, SystemClock,
+    TextEncoding, TrackerExit, invalidate_cached_offsets, load_offsets, merge_signature_file,
+    pin_song_anchors, save_offsets_to_cache, try_load_cached_offsets,
 };
 use tracing::{debug, error, info, warn};
 
+use crate::cli_utils;
+use crate::consent::{self, SubmitOptions};
+use crate::crash;
+use crate::environment::{Environment, OffsetsSource};
 use crate::input;
+use crate::output::outln;
 use crate::retry::{load_song_database_with_retry, search_offsets_with_retry};
 use crate::shutdown::ShutdownSignal;
 
@@ -20,43 +39,212 @@ use crate::shutdown::ShutdownSignal;
 ///
 /// Extracts the token from the URI, launches the game, then enters
 /// the normal tracking loop which will pick up the newly started process.
-pub fn run_with_uri(uri: &str, api_endpoint: Option<&str>, api_token: Option<&str>) -> Result<()> {
-    println!("infst v{}", env!("CARGO_PKG_VERSION"));
-    println!("Launching game from URI...");
+pub fn run_with_uri(uri: &str, api: ApiOptions) -> Result<()> {
+    outln!("infst v{}", env!("CARGO_PKG_VERSION"));
+    outln!("Launching game from URI...");
 
     let token = infst::launcher::extract_token_from_uri(uri)?;
     let pid = infst::launcher::launch_game(&token)?;
-    println!("Game launched (PID: {})", pid);
+    outln!("Game launched (PID: {})", pid);
+
+    run(
+        None,
+        None,
+        SessionOptions {
+            paths: Paths::resolve(false, None),
+            ..SessionOptions::default()
+        },
+        None,
+        AccessOptions::default(),
+        api,
+    )
+}
+
+/// Web API settings for lamp submissions
+#[derive(Default)]
+pub struct ApiOptions {
+    pub endpoint: Option<String>,
+    pub token: Option<String>,
+    /// Send without asking at startup (`--api-consent`)
+    pub consent: bool,
+    /// File for the first payload of each session (`--preview-payload`)
+    pub preview_payload: Option<String>,
+    /// Lamps per batch request (`--api-batch-size`)
+    pub batch_size: Option<usize>,
+    /// Longest a lamp waits for its batch (`--api-flush-interval`)
+    pub flush_interval: Option<Duration>,
+}
 
-    run(None, api_endpoint, api_token)
+/// Session output options for the tracker
+#[derive(Default)]
+pub struct SessionOptions {
+    pub tracker_snapshots: bool,
+    /// Save a summary image at session close (`--summary-image`)
+    pub summary_image: bool,
+    /// Print what would be recorded instead of writing or submitting (`--dry-run`)
+    pub dry_run: bool,
+    /// Keep tracking after the loop panicked (`--restart-on-panic`)
+    pub restart_on_panic: bool,
+    /// Retry policies with `--retry` overrides applied
+    pub retry: RetryPolicies,
+    /// Exports run on a schedule or at session end (`--schedule`)
+    pub schedule: Vec<ScheduledExport>,
+    /// Don't record plays while the session is paused
+    pub ignore_paused_plays: bool,
+    /// Don't record plays that fail validation
+    pub drop_anomalies: bool,
+    /// How premature plays are recorded
+    pub premature_mode: PrematureMode,
+    /// Window in which an identical result is recorded once (library default when `None`)
+    pub duplicate_window: Option<Duration>,
+    /// Green number alert threshold (`--green-number-alert`)
+    pub green_number_alert: Option<u16>,
+    /// Tournament file to score the session against
+    pub tournament: Option<String>,
+    /// Ignore the instance lock held by another tracker
+    pub force: bool,
+    /// Run the tracker clock from this time (`--fake-time`)
+    pub fake_time: Option<DateTime<Utc>>,
+    /// Timezone for displayed play times (`--timezone`)
+    pub timezone: DisplayTimezone,
+    /// Encoding of the tracker and session TSV files (`--tsv-encoding`)
+    pub tsv_encoding: TextEncoding,
+    /// Template for the copied last play summary (`--clipboard-template`)
+    pub clipboard_template: Option<String>,
+    /// Push notifications for milestones (`--push-url`, `--push-on`)
+    pub push: Option<PushConfig>,
+    /// Charts kept out of exports and submissions (`--ignore-file`)
+    pub ignore: IgnoreList,
+    /// Global hotkeys (`--hotkey`, `--no-hotkeys`)
+    pub hotkeys: HotkeyConfig,
+    /// Where the tracker, sessions and offset cache live
+    pub paths: Paths,
+}
+
+/// Memory access limits for the tracker
+pub struct AccessOptions {
+    pub max_reads_per_sec: Option<u32>,
+    pub audit_log: Option<String>,
+    pub unlock_reads: bool,
+    pub song_db_rescan: bool,
+}
+
+impl Default for AccessOptions {
+    fn default() -> Self {
+        Self {
+            max_reads_per_sec: None,
+            audit_log: None,
+            unlock_reads: true,
+            song_db_rescan: true,
+        }
+    }
 }
 
 /// Run the main tracking mode
 pub fn run(
     offsets_file: Option<&str>,
-    api_endpoint: Option<&str>,
-    api_token: Option<&str>,
+    bpi_file: Option<&str>,
+    session: SessionOptions,
+    stream: Option<StreamConfig>,
+    access: AccessOptions,
+    api: ApiOptions,
 ) -> Result<()> {
-    let shutdown = setup_shutdown_handler();
-    let (initial_offsets, offsets_from_file) = load_initial_offsets(offsets_file);
-
-    let config = build_config(api_endpoint, api_token);
-    let mut infst = Infst::with_config(initial_offsets, config);
-
-    println!("Waiting for INFINITAS... (Press Esc or q to quit)");
+    let shutdown = setup_shutdown_handler(&session.paths.sessions());
+    let (initial_offsets, mut offsets_from_file) = load_initial_offsets(offsets_file);
+
+    let force = session.force;
+    let dry_run = session.dry_run;
+    let restart_on_panic = session.restart_on_panic;
+    let paths = session.paths.clone();
+    if dry_run {
+        outln!("Dry run: plays are printed, nothing is written or submitted");
+    } else {
+        paths.create_root()?;
+    }
+    crash::install_panic_hook((!dry_run).then(|| paths.crashes()));
+    let clock: SharedClock = match session.fake_time {
+        Some(start) => {
+            outln!(
+                "Using fake time starting at {}",
+                start.with_timezone(&Local)
+            );
+            Arc::new(OffsetClock::starting_at(start))
+        }
+        None => SystemClock::shared(),
+    };
+    let hotkeys = session.hotkeys.clone();
+    let mut config = build_config(bpi_file, session, stream, access, &api);
+    let mut environment = Environment::new(&paths).with_config(&config);
+    // Ask before the keyboard monitor takes over the terminal
+    if let Some(api_config) = &config.api_config
+        && !config.api_consent
+        && !dry_run
+        && io::stdin().is_terminal()
+    {
+        config.api_consent = consent::ask_tracking_consent(&api_config.endpoint);
+    }
+    let api_consent = config.api_consent;
+    // Nothing is written, so another tracker may own the session directory
+    let _lock = if dry_run {
+        None
+    } else {
+        acquire_instance_lock(&config.session_dir, force)?
+    };
+    let _hotkeys = start_hotkeys(&hotkeys, &config.session_dir)?;
+    let mut infst = Infst::builder()
+        .offsets(initial_offsets)
+        .config(config)
+        .clock(clock)
+        .on_upload(move |tracker, mapping| {
+            let submit = SubmitOptions {
+                consent: api_consent,
+                preview_payload: None,
+                interactive: false,
+            };
+            super::upload::run(tracker, &mapping.to_string_lossy(), None, None, &submit).map_err(
+                |e| infst::Error::Network {
+                    message: format!("{:#}", e),
+                    retryable: true,
+                },
+            )
+        })
+        .build()?;
+
+    outln!("Waiting for INFINITAS... (Press Esc or q to quit)");
 
     // Open the game login page if the game is not already running
-    if ProcessHandle::find_and_open().is_err() {
+    if cli_utils::find_process_by_rule().is_err() {
         open_login_page();
     }
 
     while !shutdown.is_shutdown() {
-        if let Some(process) = wait_for_process(&shutdown) {
-            if let Err(e) = run_tracking_session(&mut infst, &process, &shutdown, offsets_from_file)
-            {
-                error!("Tracking session error: {}", e);
+        if let Some(process) = wait_for_process(&shutdown)? {
+            match run_tracking_session(
+                &mut infst,
+                &process,
+                &shutdown,
+                &paths,
+                offsets_from_file,
+                &mut environment,
+            ) {
+                Ok(TrackerExit::GameUpdated) => {
+                    // Offsets loaded from file were for the old version too
+                    offsets_from_file = false;
+                    outln!("Game update detected. Offsets will be re-detected.");
+                }
+                Ok(_) => {}
+                // Retrying can't help until the game is updated
+                Err(e) if is_unsupported_version(&e) => return Err(e),
+                Err(e) if e.is::<LoopPanicked>() => {
+                    if !restart_on_panic {
+                        return Err(e);
+                    }
+                    error!("{:#}; restarting", e);
+                    outln!("Tracking crashed; the session was saved. Restarting...");
+                }
+                Err(e) => error!("Tracking session error: {}", e),
             }
-            println!("Waiting for INFINITAS...");
+            outln!("Waiting for INFINITAS...");
         }
 
         if shutdown.wait(Duration::from_secs(5)) {
@@ -64,34 +252,162 @@ pub fn run(
         }
     }
 
-    println!("Shutdown complete.");
+    outln!("Shutdown complete.");
     Ok(())
 }
 
+/// The tracking loop panicked; the session was closed out and the tracker
+/// exported before this was returned
+#[derive(Debug)]
+struct LoopPanicked {
+    message: String,
+    report: Option<PathBuf>,
+}
+
+impl fmt::Display for LoopPanicked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Tracking loop panicked: {}", self.message)?;
+        if let Some(report) = &self.report {
+            write!(f, " (crash report: {})", report.display())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LoopPanicked {}
+
+fn is_unsupported_version(e: &anyhow::Error) -> bool {
+    matches!(
+        e.downcast_ref::<infst::Error>(),
+        Some(infst::Error::UnsupportedGameVersion { .. })
+    )
+}
+
+/// Hold the instance lock for the session directory while tracking
+///
+/// With `force`, a lock held elsewhere only produces a warning.
+fn acquire_instance_lock(session_dir: &Path, force: bool) -> Result<Option<InstanceLock>> {
+    match InstanceLock::acquire(session_dir) {
+        Ok(lock) => Ok(Some(lock)),
+        Err(e @ infst::Error::InstanceLocked { .. }) if force => {
+            warn!("{}; continuing because of --force", e);
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Register global hotkeys; pressed hotkeys are queued like `infst session`
+/// commands
+///
+/// Hotkeys another application already holds (or an OS without global
+/// hotkeys) only produce a warning; conflicting bindings are an error.
+fn start_hotkeys(config: &HotkeyConfig, session_dir: &Path) -> Result<Option<HotkeyListener>> {
+    if !config.enabled || config.bindings.is_empty() {
+        return Ok(None);
+    }
+    let control = SessionControl::new(session_dir);
+    let listener = match HotkeyListener::start(config, move |action| {
+        if let Err(e) = control.send(&action.command()) {
+            warn!("Failed to queue hotkey {}: {}", action, e);
+        }
+    }) {
+        Ok(listener) => listener,
+        Err(e @ infst::Error::InvalidConfig { .. }) => return Err(e.into()),
+        Err(e) => {
+            warn!("Global hotkeys disabled: {}", e);
+            return Ok(None);
+        }
+    };
+    for binding in listener.unavailable() {
+        warn!(
+            "Hotkey {} is in use by another application and was not registered",
+            binding
+        );
+    }
+    let active: Vec<String> = config
+        .bindings
+        .iter()
+        .filter(|binding| !listener.unavailable().contains(binding))
+        .map(ToString::to_string)
+        .collect();
+    if !active.is_empty() {
+        outln!("Hotkeys: {}", active.join(", "));
+    }
+    Ok(Some(listener))
+}
+
 /// Setup graceful shutdown handler with keyboard input
-fn setup_shutdown_handler() -> Arc<ShutdownSignal> {
+fn setup_shutdown_handler(session_dir: &Path) -> Arc<ShutdownSignal> {
     let shutdown = Arc::new(ShutdownSignal::new());
 
-    // Keyboard input monitor (Esc, q, Q to quit)
+    // Keyboard input monitor (Esc, q, Q to quit; c to copy the last play)
     let shutdown_keyboard = Arc::clone(&shutdown);
-    let _keyboard_handle = input::spawn_keyboard_monitor(shutdown_keyboard);
+    let _keyboard_handle =
+        input::spawn_keyboard_monitor(shutdown_keyboard, Some(SessionControl::new(session_dir)));
 
     let current_version = env!("CARGO_PKG_VERSION");
-    println!("infst v{}", current_version);
+    outln!("infst v{}", current_version);
 
     shutdown
 }
 
-/// Build InfstConfig with optional API configuration
+/// Build InfstConfig from tracking mode options
 ///
 /// Resolves API credentials from: args > credentials file
-fn build_config(api_endpoint: Option<&str>, api_token: Option<&str>) -> InfstConfig {
-    let api_config = resolve_api_config(api_endpoint, api_token);
+fn build_config(
+    bpi_file: Option<&str>,
+    session: SessionOptions,
+    stream: Option<StreamConfig>,
+    access: AccessOptions,
+    api: &ApiOptions,
+) -> InfstConfig {
+    let api_config = resolve_api_config(api.endpoint.as_deref(), api.token.as_deref());
     if api_config.is_some() {
         info!("API integration enabled");
     }
     InfstConfig {
         api_config,
+        api_consent: api.consent,
+        payload_preview: api.preview_payload.as_ref().map(Into::into),
+        api_batch_size: api.batch_size.unwrap_or(submission::BATCH_SIZE),
+        api_flush_interval: api.flush_interval.unwrap_or(submission::FLUSH_INTERVAL),
+        bpi_file: bpi_file.map(Into::into),
+        tracker_snapshots: session.tracker_snapshots,
+        summary_image: session.summary_image,
+        schedule: session.schedule,
+        tournament_file: session.tournament.map(Into::into),
+        pause_mode: if session.ignore_paused_plays {
+            PauseMode::Ignore
+        } else {
+            PauseMode::Flag
+        },
+        anomaly_mode: if session.drop_anomalies {
+            AnomalyMode::Drop
+        } else {
+            AnomalyMode::Flag
+        },
+        duplicate_window: session
+            .duplicate_window
+            .unwrap_or(Duration::from_secs(DEFAULT_DUPLICATE_WINDOW_SECS)),
+        premature_mode: session.premature_mode,
+        green_number_alert: session.green_number_alert,
+        stream,
+        max_reads_per_sec: access.max_reads_per_sec,
+        audit_log: access.audit_log.map(Into::into),
+        unlock_reads: access.unlock_reads,
+        song_db_rescan: access.song_db_rescan,
+        display_timezone: session.timezone,
+        tsv_encoding: session.tsv_encoding,
+        push: session.push,
+        clipboard_template: session
+            .clipboard_template
+            .unwrap_or_else(|| DEFAULT_CLIPBOARD_TEMPLATE.to_string()),
+        session_dir: session.paths.sessions(),
+        tracker_path: session.paths.tracker(),
+        dry_run: session.dry_run,
+        retry: session.retry,
+        ignore: session.ignore,
         ..InfstConfig::default()
     }
 }
@@ -142,20 +458,23 @@ fn load_initial_offsets(offsets_file: Option<&str>) -> (OffsetsCollection, bool)
 }
 
 /// Wait for the game process to become available
-fn wait_for_process(shutdown: &ShutdownSignal) -> Option<ProcessHandle> {
+///
+/// Access-denied errors are returned since retrying won't resolve them.
+fn wait_for_process(shutdown: &ShutdownSignal) -> Result<Option<ProcessHandle>> {
     if shutdown.is_shutdown() {
-        return None;
+        return Ok(None);
     }
 
-    match ProcessHandle::find_and_open() {
+    match cli_utils::find_process_by_rule() {
         Ok(process) => {
-            println!("Connected to INFINITAS (PID: {})", process.pid);
+            outln!("Connected to INFINITAS (PID: {})", process.pid);
             debug!("Process base: {:#x}", process.base_address);
-            Some(process)
+            Ok(Some(process))
         }
+        Err(e) if cli_utils::is_access_denied(&e) => Err(e),
         Err(e) => {
             debug!("Process not found: {}", e);
-            None
+            Ok(None)
         }
     }
 }
@@ -163,23 +482,31 @@ fn wait_for_process(shutdown: &ShutdownSignal) -> Option<ProcessHandle> {
 /// Validate or search for offsets
 ///
 /// Uses cached offsets if available and valid, otherwise performs a full search.
+/// Cached and file-loaded offsets get their optional offsets checked and
+/// completed. Returns the new offsets and where they came from, or `None` to
+/// keep the current ones.
 fn validate_or_search_offsets(
     infst: &Infst,
     reader: &MemoryReader,
     game_version: Option<&String>,
     offsets_from_file: bool,
+    cache: &Path,
     shutdown: &ShutdownSignal,
-) -> Result<Option<OffsetsCollection>> {
+) -> Result<Option<(OffsetsCollection, OffsetsSource)>> {
     // Try to use cached offsets first (if not loading from file)
     if !offsets_from_file
         && let Some(version) = game_version
-        && let Some(cached_offsets) = try_load_cached_offsets(version)
+        && let Some(cached_offsets) = try_load_cached_offsets(cache, version)
     {
         // Validate cached offsets still work
-        let searcher = OffsetSearcher::new(reader);
+        let searcher = OffsetSearcher::builder(reader)
+            .with_layout(infst.layout())
+            .build();
         if searcher.validate_basic_memory_access(&cached_offsets) {
             info!("Using cached offsets (validated)");
-            return Ok(Some(cached_offsets));
+            let offsets =
+                complete_optional_offsets(&searcher, cached_offsets, &infst.layout().signatures);
+            return Ok(Some((offsets, OffsetsSource::Cache)));
         } else {
             info!("Cached offsets invalid, performing fresh search...");
         }
@@ -189,16 +516,25 @@ fn validate_or_search_offsets(
         info!("Invalid offsets detected (some offsets are zero)");
         true
     } else if offsets_from_file {
-        let searcher = OffsetSearcher::new(reader);
+        let searcher = OffsetSearcher::builder(reader)
+            .with_layout(infst.layout())
+            .build();
         if searcher.validate_basic_memory_access(infst.offsets()) {
             debug!("File-loaded offsets: basic memory access validated");
-            false
+            let offsets = complete_optional_offsets(
+                &searcher,
+                infst.offsets().clone(),
+                &infst.layout().signatures,
+            );
+            return Ok(Some((offsets, OffsetsSource::File)));
         } else {
             info!("File-loaded offsets: memory access failed. Attempting signature search...");
             true
         }
     } else {
-        let searcher = OffsetSearcher::new(reader);
+        let searcher = OffsetSearcher::builder(reader)
+            .with_layout(infst.layout())
+            .build();
         if !searcher.validate_signature_offsets(infst.offsets()) {
             info!(
                 "Offset validation failed (offsets may be stale or incorrect). Attempting signature search..."
@@ -211,48 +547,83 @@ fn validate_or_search_offsets(
     };
 
     if needs_search {
-        let offsets = search_offsets_with_retry(reader, game_version, shutdown)?;
-        if let Some(ref found_offsets) = offsets {
-            debug!("Signature-based offset detection successful!");
+        let mut offsets = search_offsets_with_retry(
+            reader,
+            game_version,
+            infst.layout(),
+            &infst.config().retry.offset_search,
+            shutdown,
+        )?;
+        if let Some(found_offsets) = offsets.as_mut() {
+            // Pin song entries so a moved song list is noticed while tracking
+            found_offsets.song_anchors =
+                pin_song_anchors(reader, found_offsets.song_list, &infst.layout().song_entry);
+            info!(
+                "Offsets detected for {}: song_list=0x{:X}, judge_data=0x{:X}, play_data=0x{:X}",
+                game_version.map_or("unknown version", String::as_str),
+                found_offsets.song_list,
+                found_offsets.judge_data,
+                found_offsets.play_data
+            );
             // Save to cache for next startup
-            if let Some(version) = game_version {
-                save_offsets_to_cache(version, found_offsets);
+            if let Some(version) = game_version
+                && !infst.config().dry_run
+            {
+                save_offsets_to_cache(cache, version, found_offsets);
             }
         }
-        Ok(offsets)
+        Ok(offsets.map(|offsets| (offsets, OffsetsSource::Search)))
     } else {
         Ok(None)
     }
 }
 
+/// Drop optional offsets (profile) that no longer hold their block and
+/// search the missing ones with the layout's signatures
+fn complete_optional_offsets(
+    searcher: &OffsetSearcher<MemoryReader>,
+    mut offsets: OffsetsCollection,
+    signatures: &OffsetSignatureSet,
+) -> OffsetsCollection {
+    for name in searcher.clear_invalid_optional_offsets(&mut offsets) {
+        warn!("Ignoring invalid {} offset", name);
+    }
+    searcher.search_optional_offsets(signatures, &mut offsets);
+    offsets
+}
+
 /// Load song database using various strategies
 fn load_song_database(
     reader: &MemoryReader,
     song_list: u64,
+    tsv_path: &Path,
+    layout: &SongEntryLayout,
+    policy: &RetryPolicy,
     shutdown: &ShutdownSignal,
 ) -> Result<Option<HashMap<u32, SongInfo>>> {
-    let tsv_path = "tracker.tsv";
-
-    if std::path::Path::new(tsv_path).exists() {
+    if tsv_path.exists() {
         debug!("Building song database from TSV + memory scan...");
         let db = infst::chart::build_song_database_from_tsv_with_memory(
             reader, song_list, tsv_path, 0x100000, // 1MB scan
+            layout,
         );
 
         if db.is_empty() {
             debug!("TSV+memory approach returned empty, trying legacy...");
-            return load_song_database_with_retry(reader, song_list, shutdown);
+            return load_song_database_with_retry(reader, song_list, policy, shutdown);
         }
         return Ok(Some(db));
     }
 
     // No TSV, use memory-only approach
     debug!("No TSV file found, using memory scan...");
-    let song_db = infst::chart::fetch_song_database_from_memory_scan(reader, song_list, 0x100000);
+    let song_db = infst::chart::fetch_song_database_from_memory_scan_with_layout(
+        reader, song_list, 0x100000, layout,
+    );
 
     if song_db.is_empty() {
         debug!("Memory scan found no songs, trying legacy approach...");
-        return load_song_database_with_retry(reader, song_list, shutdown);
+        return load_song_database_with_retry(reader, song_list, policy, shutdown);
     }
 
     info!("Loaded {} songs from memory scan", song_db.len());
@@ -260,62 +631,133 @@ fn load_song_database(
 }
 
 /// Run a single tracking session with a connected process
+///
+/// When the game updates itself mid-session, the cached offsets for the old
+/// version are invalidated and the current offsets are reset so the next
+/// session searches again.
 fn run_tracking_session(
     infst: &mut Infst,
     process: &ProcessHandle,
     shutdown: &ShutdownSignal,
+    paths: &Paths,
     offsets_from_file: bool,
-) -> Result<()> {
-    println!("Initializing...");
-    let reader = MemoryReader::new(process);
+    environment: &mut Environment,
+) -> Result<TrackerExit> {
+    outln!("Initializing...");
+    let reader = infst.memory_reader(process);
 
     // Game version detection
     let game_version = detect_game_version(&reader, process.base_address);
 
+    // Memory layout of this build (unknown versions get the newest layout)
+    let registry = LayoutRegistry::builtin();
+    let mut layout = match &game_version {
+        Some(version) => registry.select(version)?,
+        None => registry.newest(),
+    }
+    .clone();
+    // Signatures for the optional offsets, which the builtin set doesn't have
+    merge_signature_file(&mut layout.signatures, paths.signatures());
+    infst.set_layout(layout);
+
     // Validate or search for offsets
-    if let Some(offsets) = validate_or_search_offsets(
+    if let Some((offsets, source)) = validate_or_search_offsets(
         infst,
         &reader,
         game_version.as_ref(),
         offsets_from_file,
+        &paths.offset_cache(),
         shutdown,
     )? {
         infst.update_offsets(offsets);
+        environment.offsets_source = Some(source);
     } else if shutdown.is_shutdown() {
-        return Ok(());
+        return Ok(TrackerExit::Shutdown);
+    }
+
+    // What support needs to know, before anything else can go wrong
+    environment.game_version = game_version.clone();
+    outln!("=== Environment ===");
+    for line in environment.lines() {
+        outln!("{}", line);
     }
 
     // Load game resources
-    let song_db = match load_song_database(&reader, infst.offsets().song_list, shutdown)? {
+    let song_db = match load_song_database(
+        &reader,
+        infst.offsets().song_list,
+        &paths.tracker(),
+        &infst.layout().song_entry,
+        &infst.config().retry.song_db,
+        shutdown,
+    )? {
         Some(db) => db,
-        None => return Ok(()), // Shutdown requested
+        None => return Ok(TrackerExit::Shutdown),
     };
 
     debug!("Loaded {} songs", song_db.len());
-    infst.set_song_db(song_db.clone());
 
     // Load score map
     let score_map = load_score_map(&reader, infst.offsets().data_map, &song_db);
-    infst.set_score_map(score_map);
+    infst.load_game_data(song_db, score_map);
+    if let Some(mismatch) = infst.profile_mismatch() {
+        outln!(
+            "The game's scores don't match this tracker's ({} of {} charts lower). \
+             If another player is logged in, track them separately with --profile <NAME>.",
+            mismatch.lowered,
+            mismatch.compared
+        );
+    }
 
     // Load unlock state
     if let Err(e) = infst.load_unlock_state(&reader) {
         warn!("Failed to load unlock state: {}", e);
     }
 
-    println!("Ready to track. Waiting for plays...");
-
-    // Run tracker loop
-    if let Err(e) = infst.run(process, shutdown.as_atomic()) {
-        error!("Tracker error: {}", e);
-    }
+    outln!("Ready to track. Waiting for plays...");
+
+    // Run tracker loop; a panic still closes out the session and exports below
+    let mut panicked = None;
+    let exit = match catch_unwind(AssertUnwindSafe(|| {
+        infst.run(process, shutdown.as_atomic())
+    })) {
+        Ok(Ok(exit)) => exit,
+        Ok(Err(e)) => {
+            error!("Tracker error: {}", e);
+            TrackerExit::ProcessExited
+        }
+        Err(payload) => {
+            let message = crash::payload_message(payload.as_ref()).to_string();
+            if catch_unwind(AssertUnwindSafe(|| infst.finish_session())).is_err() {
+                error!("Failed to close out the session after a panic");
+            }
+            panicked = Some(LoopPanicked {
+                message,
+                report: crash::last_report(),
+            });
+            TrackerExit::ProcessExited
+        }
+    };
 
     // Export tracker.tsv on disconnect
-    if let Err(e) = infst.export_tracker_tsv("tracker.tsv") {
+    if let Err(e) = infst.write_tracker(&infst.tracker_export_path()) {
         error!("Failed to export tracker.tsv: {}", e);
     }
 
-    Ok(())
+    if exit == TrackerExit::GameUpdated {
+        if let Some(version) = &game_version
+            && !infst.config().dry_run
+        {
+            info!("Invalidating cached offsets for {}", version);
+            invalidate_cached_offsets(paths.offset_cache(), version);
+        }
+        infst.update_offsets(OffsetsCollection::default());
+    }
+
+    if let Some(panicked) = panicked {
+        return Err(panicked.into());
+    }
+    Ok(exit)
 }
 
 /// Detect game version (best-effort)
@@ -360,7 +802,7 @@ const LOGIN_URL: &str = "https://p.eagate.573.jp/game/infinitas/2/api/login/logi
 /// Open the INFINITAS login page in the default browser (best-effort).
 fn open_login_page() {
     match open::that(LOGIN_URL) {
-        Ok(()) => println!("Opened login page in browser"),
+        Ok(()) => outln!("Opened login page in browser"),
         Err(e) => warn!("Could not open browser: {}", e),
     }
 }
@@ -0,0 +1,149 @@
+//! Unlocks command for bit unlock planning.
+
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use infst::{
+    CustomTypes, MemoryReader, OffsetSearcher, OffsetsCollection, PlayStyle, UnlockCandidate,
+    UnlockPlanOptions, builtin_signatures, fetch_song_database, get_unlock_states, load_offsets,
+    locked_bit_charts, merge_signature_file, plan_unlocks, read_currency,
+};
+
+use crate::cli_utils;
+use crate::output::{self, outln};
+
+/// Listing and planning options
+pub struct UnlocksOptions<'a> {
+    pub plan: bool,
+    /// Bits to spend (defaults to the bit balance)
+    pub budget: Option<u32>,
+    pub min_level: Option<u8>,
+    pub style: Option<PlayStyle>,
+    pub custom_types_path: Option<&'a str>,
+}
+
+/// List locked bit charts, or plan unlocks within a bit budget
+pub fn run(
+    options: &UnlocksOptions,
+    offsets_file: Option<&str>,
+    signatures_file: &Path,
+    pid: Option<u32>,
+) -> Result<()> {
+    let UnlocksOptions {
+        plan,
+        budget,
+        min_level,
+        style,
+        custom_types_path,
+    } = *options;
+    let current_version = env!("CARGO_PKG_VERSION");
+    eprintln!("infst {} - Unlocks Mode", current_version);
+
+    let process = cli_utils::open_process(pid)?;
+    let reader = MemoryReader::new(&process);
+    let mut searcher = OffsetSearcher::new(&reader);
+    let offsets = searcher.search_data_offsets()?;
+
+    eprintln!("Loading song database...");
+    let song_db = fetch_song_database(&reader, offsets.song_list)?;
+    eprintln!("Loading unlock data...");
+    let unlock_db = get_unlock_states(&reader, offsets.unlock_data, &song_db)?;
+
+    let custom_types = match custom_types_path {
+        Some(path) => CustomTypes::load(path)
+            .with_context(|| format!("Failed to load custom types from {}", path))?,
+        None => CustomTypes::default(),
+    };
+
+    // The bit balance is read from the currency block, located by the
+    // offsets file (when still valid) or by a `currency` signature
+    let mut currency_offsets = match offsets_file {
+        Some(path) => {
+            load_offsets(path).with_context(|| format!("Failed to load offsets from {}", path))?
+        }
+        None => OffsetsCollection::default(),
+    };
+    for name in searcher.clear_invalid_optional_offsets(&mut currency_offsets) {
+        outln!("Ignoring invalid {} offset", name);
+    }
+    if currency_offsets.currency == 0 {
+        let mut signatures = builtin_signatures();
+        merge_signature_file(&mut signatures, signatures_file);
+        currency_offsets.currency = searcher.search_currency_offset(&signatures).unwrap_or(0);
+    }
+    let balance = if currency_offsets.currency != 0 {
+        let currency = read_currency(&reader, currency_offsets.currency)?;
+        outln!("Tickets: {}", currency.tickets);
+        Some(currency.bits)
+    } else {
+        None
+    };
+    if let Some(bits) = balance {
+        outln!("Bits: {}", bits);
+    }
+
+    let options = UnlockPlanOptions {
+        budget: budget.or(balance).unwrap_or(0),
+        min_level,
+        style,
+    };
+
+    if !plan {
+        let candidates = locked_bit_charts(&song_db, &unlock_db, &custom_types, &options);
+        outln!("{} locked bit unlocks", candidates.len());
+        outln!();
+        for candidate in &candidates {
+            print_candidate("", candidate);
+        }
+        output::emit(&serde_json::json!({
+            "bits": balance,
+            "candidates": candidates,
+        }));
+        return Ok(());
+    }
+
+    if budget.is_none() && balance.is_none() {
+        bail!(
+            "Bit balance is unknown; specify --budget, an offsets file with currency, or a currency signature"
+        );
+    }
+
+    let unlock_plan = plan_unlocks(&song_db, &unlock_db, &custom_types, &options);
+    outln!(
+        "Budget: {}  Spent: {}  Remaining: {}  New charts: {}",
+        unlock_plan.budget,
+        unlock_plan.spent,
+        unlock_plan.remaining(),
+        unlock_plan.total_value()
+    );
+    outln!();
+    for (i, purchase) in unlock_plan.purchases.iter().enumerate() {
+        print_candidate(&format!("{:>3}. ", i + 1), purchase);
+    }
+
+    output::emit(&serde_json::json!({
+        "bits": balance,
+        "plan": unlock_plan,
+    }));
+    Ok(())
+}
+
+fn print_candidate(prefix: &str, candidate: &UnlockCandidate) {
+    let charts: Vec<String> = candidate
+        .difficulties
+        .iter()
+        .zip(&candidate.levels)
+        .map(|(diff, level)| format!("{} {}", diff.short_name(), level))
+        .collect();
+    outln!(
+        "{}[{}] {} ({})  {} bits  +{} ({:.2} / 1000 bits)  [{}]",
+        prefix,
+        charts.join(", "),
+        candidate.title,
+        candidate.song_id,
+        candidate.cost,
+        candidate.value,
+        candidate.value_per_1000_bits(),
+        candidate.label
+    );
+}
@@ -4,9 +4,12 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::path::Path;
 use std::time::Duration;
 
 use super::login::load_credentials;
+use crate::consent::{SubmitOptions, confirm_submission};
+use crate::output::{self, outln};
 
 #[derive(Deserialize)]
 struct MappingEntry {
@@ -30,10 +33,11 @@ struct LampEntry {
 }
 
 pub fn run(
-    tracker_path: &str,
+    tracker_path: &Path,
     mapping_path: &str,
     endpoint: Option<&str>,
     token: Option<&str>,
+    submit: &SubmitOptions,
 ) -> Result<()> {
     // Resolve endpoint/token: args > credentials file
     let (resolved_endpoint, resolved_token) = resolve_credentials(endpoint, token)?;
@@ -56,9 +60,13 @@ pub fn run(
     }
 
     // Read tracker.tsv
-    let tracker_content =
-        fs::read_to_string(tracker_path).context("Failed to read tracker TSV file")?;
-    let mut lines = tracker_content.lines();
+    let tracker_content = infst::storage::decode_text(
+        &fs::read(tracker_path).context("Failed to read tracker TSV file")?,
+    );
+    // Skip the format marker line
+    let mut lines = tracker_content
+        .lines()
+        .filter(|line| !line.starts_with('#'));
 
     let header = lines.next().context("Tracker TSV is empty")?;
     let columns: Vec<&str> = header.split('\t').collect();
@@ -126,11 +134,20 @@ pub fn run(
     }
 
     if entries.is_empty() {
-        println!("No matching entries found to upload.");
+        outln!("No matching entries found to upload.");
+        emit_result(0, 0, None);
         return Ok(());
     }
 
-    println!("Uploading {} entries...", entries.len());
+    let body = serde_json::json!({ "entries": entries });
+    let payload = serde_json::to_string(&body).context("Failed to serialize JSON")?;
+    if !confirm_submission(&payload, entries.len(), &resolved_endpoint, submit)? {
+        outln!("Nothing was sent.");
+        emit_result(entries.len(), 0, None);
+        return Ok(());
+    }
+
+    outln!("Uploading {} entries...", entries.len());
 
     // POST /api/lamps/bulk
     let url = format!("{}/api/lamps/bulk", resolved_endpoint.trim_end_matches('/'));
@@ -139,19 +156,34 @@ pub fn run(
         .build();
     let agent: ureq::Agent = config.into();
 
-    let body = serde_json::json!({ "entries": entries });
     let response = agent
         .post(&url)
         .header("Authorization", &format!("Bearer {}", resolved_token))
-        .send_json(&body)
+        .header("Content-Type", "application/json")
+        .send(payload.as_bytes())
         .context("Failed to upload data")?;
 
-    println!("Upload complete (status: {})", response.status());
-    println!("Uploaded {} entries.", entries.len());
+    outln!("Upload complete (status: {})", response.status());
+    outln!("Uploaded {} entries.", entries.len());
+    emit_result(
+        entries.len(),
+        entries.len(),
+        Some(response.status().as_u16()),
+    );
 
     Ok(())
 }
 
+/// Emit the result of an upload or sync: lamps found, lamps sent and the
+/// server's response status (`None` when nothing was sent)
+pub(super) fn emit_result(entries: usize, sent: usize, status: Option<u16>) {
+    output::emit(&serde_json::json!({
+        "entries": entries,
+        "sent": sent,
+        "status": status,
+    }));
+}
+
 pub fn resolve_credentials(
     endpoint: Option<&str>,
     token: Option<&str>,
@@ -1,27 +1,119 @@
 //! Validate command implementation.
 
-use anyhow::Result;
-use infst::{MemoryReader, ProcessHandle, ReadMemory};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use infst::util::hex::parse_address;
+use infst::{
+    CanonicalSongList, MemoryReader, OffsetSearcher, ProcessHandle, ReadMemory,
+    fetch_song_database, verify_song_database,
+};
 
-use super::hex_utils::parse_hex_address;
 use crate::cli::ValidateTarget;
+use crate::cli_utils;
+use crate::exit_code::ExitError;
+use crate::output::{self, outln};
 
 /// Run the validate command
 pub fn run(target: ValidateTarget) -> Result<()> {
     match target {
         ValidateTarget::SongEntry { address, pid } => {
-            let addr = parse_hex_address(&address)?;
+            let addr = parse_address(&address)?;
             run_validate_song_entry(addr, pid)
         }
+        ValidateTarget::SongDb {
+            song_list,
+            limit,
+            pid,
+        } => run_validate_song_db(&song_list, limit, pid),
     }
 }
 
+/// Load the canonical song list from a file or URL
+fn load_canonical_song_list(source: &str) -> Result<CanonicalSongList> {
+    let json = if source.starts_with("http://") || source.starts_with("https://") {
+        let config = ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(30)))
+            .build();
+        let agent: ureq::Agent = config.into();
+        agent
+            .get(source)
+            .call()
+            .context("Failed to download song list")?
+            .body_mut()
+            .read_to_string()
+            .context("Failed to read song list response")?
+    } else {
+        std::fs::read_to_string(source)
+            .with_context(|| format!("Failed to read song list {}", source))?
+    };
+    Ok(CanonicalSongList::from_json(&json)?)
+}
+
+/// Cross-check the parsed song database against a canonical song list
+fn run_validate_song_db(song_list: &str, limit: usize, pid: Option<u32>) -> Result<()> {
+    let canonical = load_canonical_song_list(song_list)?;
+    outln!("Loaded {} songs from canonical list", canonical.len());
+
+    let process = cli_utils::open_process(pid)?;
+    let reader = MemoryReader::new(&process);
+    let mut searcher = OffsetSearcher::new(&reader);
+    let offsets = searcher.search_data_offsets()?;
+
+    let song_db = fetch_song_database(&reader, offsets.song_list)?;
+    outln!("Loaded {} songs from memory", song_db.len());
+
+    let report = verify_song_database(&song_db, &canonical);
+
+    outln!();
+    outln!("=== Song Database Verification ===");
+    outln!("Checked:         {}", report.checked);
+    outln!("Not in list:     {}", report.unknown);
+    outln!("Mismatches:      {}", report.mismatches.len());
+    outln!("Songs affected:  {}", report.mismatched_songs());
+
+    if !report.mismatches.is_empty() {
+        outln!();
+        for mismatch in report.mismatches.iter().take(limit) {
+            outln!("  {}", mismatch);
+        }
+        if report.mismatches.len() > limit {
+            outln!("  ... and {} more", report.mismatches.len() - limit);
+        }
+    }
+
+    outln!();
+    if report.is_ok() {
+        outln!("Overall: song database matches the canonical list");
+    } else if report.suggests_layout_error() {
+        outln!(
+            "Overall: most songs disagree; the song entry layout (field offsets or entry size) is likely wrong for this game version"
+        );
+    } else {
+        outln!("Overall: isolated mismatches (revised charts or an outdated song list)");
+    }
+
+    if !report.is_ok() {
+        return Err(ExitError::validation_failed(format!(
+            "{} mismatches against the canonical song list",
+            report.mismatches.len()
+        ))
+        .into());
+    }
+
+    output::emit(&serde_json::json!({
+        "checked": report.checked,
+        "unknown": report.unknown,
+    }));
+    Ok(())
+}
+
 /// Validate a song entry structure
 fn run_validate_song_entry(address: u64, pid: Option<u32>) -> Result<()> {
     let process = if let Some(pid) = pid {
         ProcessHandle::open(pid)?
     } else {
-        ProcessHandle::find_and_open()?
+        cli_utils::find_process()?
     };
 
     let reader = MemoryReader::new(&process);
@@ -30,11 +122,11 @@ fn run_validate_song_entry(address: u64, pid: Option<u32>) -> Result<()> {
     const ENTRY_SIZE: usize = 1200;
     let data = reader.read_bytes(address, ENTRY_SIZE)?;
 
-    println!("=== Song Entry Validation ===");
-    println!("Address: 0x{:X}", address);
-    println!("Entry size: {} bytes (0x{:X})", ENTRY_SIZE, ENTRY_SIZE);
-    println!();
-    println!("Fields:");
+    outln!("=== Song Entry Validation ===");
+    outln!("Address: 0x{:X}", address);
+    outln!("Entry size: {} bytes (0x{:X})", ENTRY_SIZE, ENTRY_SIZE);
+    outln!();
+    outln!("Fields:");
 
     // Helper to decode Shift-JIS string
     let decode_string = |offset: usize, max_len: usize| -> String {
@@ -58,11 +150,11 @@ fn run_validate_song_entry(address: u64, pid: Option<u32>) -> Result<()> {
 
     // Title at offset 0
     let title = decode_string(0, 64);
-    println!("  title     @    0: {:?} {}", title, check_string(&title));
+    outln!("  title     @    0: {:?} {}", title, check_string(&title));
 
     // Title English at offset 64
     let title_en = decode_string(64, 64);
-    println!(
+    outln!(
         "  title_en  @   64: {:?} {}",
         title_en,
         check_string(&title_en)
@@ -70,16 +162,16 @@ fn run_validate_song_entry(address: u64, pid: Option<u32>) -> Result<()> {
 
     // Genre at offset 128
     let genre = decode_string(128, 64);
-    println!("  genre     @  128: {:?} {}", genre, check_string(&genre));
+    outln!("  genre     @  128: {:?} {}", genre, check_string(&genre));
 
     // Artist at offset 192
     let artist = decode_string(192, 64);
-    println!("  artist    @  192: {:?} {}", artist, check_string(&artist));
+    outln!("  artist    @  192: {:?} {}", artist, check_string(&artist));
 
     // Levels at offset 480
     let levels: Vec<u8> = data[480..490].to_vec();
     let levels_valid = levels.iter().all(|&l| l <= 12);
-    println!(
+    outln!(
         "  levels    @  480: {:?} {}",
         levels,
         if levels_valid { "✓" } else { "?" }
@@ -88,7 +180,7 @@ fn run_validate_song_entry(address: u64, pid: Option<u32>) -> Result<()> {
     // Song ID at offset 816
     let song_id = i32::from_le_bytes([data[816], data[817], data[818], data[819]]);
     let song_id_valid = (1000..=90000).contains(&song_id);
-    println!(
+    outln!(
         "  song_id   @  816: {} {}",
         song_id,
         if song_id_valid { "✓" } else { "?" }
@@ -97,7 +189,7 @@ fn run_validate_song_entry(address: u64, pid: Option<u32>) -> Result<()> {
     // Folder at offset 820
     let folder = i32::from_le_bytes([data[820], data[821], data[822], data[823]]);
     let folder_valid = (1..=200).contains(&folder);
-    println!(
+    outln!(
         "  folder    @  820: {} {}",
         folder,
         if folder_valid { "✓" } else { "?" }
@@ -110,17 +202,17 @@ fn run_validate_song_entry(address: u64, pid: Option<u32>) -> Result<()> {
             u16::from_le_bytes([data[off], data[off + 1]])
         })
         .collect();
-    println!("  notes     @  500: {:?}", total_notes);
+    outln!("  notes     @  500: {:?}", total_notes);
 
     // BPM at offset 256
     let bpm = decode_string(256, 64);
-    println!("  bpm       @  256: {:?}", bpm);
+    outln!("  bpm       @  256: {:?}", bpm);
 
-    println!();
+    outln!();
 
     // Overall validation
     let valid = !title.is_empty() && title != "(empty)" && song_id_valid && levels_valid;
-    println!(
+    outln!(
         "Overall: {}",
         if valid {
             "Valid song entry"
@@ -129,5 +221,25 @@ fn run_validate_song_entry(address: u64, pid: Option<u32>) -> Result<()> {
         }
     );
 
+    if !valid {
+        return Err(ExitError::validation_failed(format!(
+            "No valid song entry at 0x{:X}",
+            address
+        ))
+        .into());
+    }
+
+    output::emit(&serde_json::json!({
+        "address": format!("0x{:X}", address),
+        "title": title,
+        "titleEn": title_en,
+        "genre": genre,
+        "artist": artist,
+        "levels": levels,
+        "songId": song_id,
+        "folder": folder,
+        "notes": total_notes,
+        "bpm": bpm,
+    }));
     Ok(())
 }
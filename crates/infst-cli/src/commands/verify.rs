@@ -0,0 +1,34 @@
+//! Verify command for checking signed tracker exports.
+
+use anyhow::{Context, Result};
+use infst::{SignatureStatus, SigningKey, verify_export};
+
+use crate::exit_code::ExitError;
+use crate::output;
+
+/// Check the signature of a signed export against a key file
+pub fn run(file: &str, key_path: &str) -> Result<()> {
+    let key = SigningKey::load(key_path)
+        .with_context(|| format!("Failed to load signing key {}", key_path))?;
+    let bytes = std::fs::read(file).with_context(|| format!("Failed to read {}", file))?;
+    let content = infst::storage::decode_text(&bytes);
+
+    match verify_export(&content, &key) {
+        SignatureStatus::Valid => {
+            output::emit(&serde_json::json!({
+                "file": file,
+                "status": SignatureStatus::Valid,
+            }));
+            eprintln!("{}: signature OK", file);
+            Ok(())
+        }
+        SignatureStatus::Invalid => Err(ExitError::validation_failed(format!(
+            "{}: signature does not match (edited after signing, or signed with another key)",
+            file
+        ))
+        .into()),
+        SignatureStatus::Unsigned => {
+            Err(ExitError::validation_failed(format!("{}: file is not signed", file)).into())
+        }
+    }
+}
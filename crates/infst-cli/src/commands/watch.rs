@@ -0,0 +1,264 @@
+//! Watch command implementation.
+//!
+//! Polls one or more addresses and prints a line whenever a value changes,
+//! which makes it easy to see which fields update during play.
+//!
+//! ```text
+//! 12:34:56.789  0x1431B08A0  0 -> 1523
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use chrono::Local;
+use infst::util::hex::parse_address;
+use infst::{MemoryReader, ReadMemory};
+use serde::Serialize;
+
+use crate::cli::WatchType;
+use crate::cli_utils;
+use crate::input;
+use crate::output::{self, outln};
+use crate::shutdown::ShutdownSignal;
+
+/// Timestamp format for change lines
+const TIME_FORMAT: &str = "%H:%M:%S%.3f";
+
+/// A detected value change
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Change {
+    pub timestamp: String,
+    pub address: u64,
+    /// `None` for the initial read (or after an unreadable poll)
+    pub old: Option<String>,
+    /// `None` when the address could not be read
+    pub new: Option<String>,
+}
+
+impl WatchType {
+    /// Size in bytes of one value
+    pub fn size(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::I64 | Self::U64 | Self::F64 => 8,
+        }
+    }
+
+    /// Decode a little-endian value for display
+    pub fn decode(self, bytes: &[u8]) -> Option<String> {
+        let bytes = bytes.get(..self.size())?;
+        let value = match self {
+            Self::I8 => (bytes[0] as i8).to_string(),
+            Self::U8 => bytes[0].to_string(),
+            Self::I16 => i16::from_le_bytes(bytes.try_into().ok()?).to_string(),
+            Self::U16 => u16::from_le_bytes(bytes.try_into().ok()?).to_string(),
+            Self::I32 => i32::from_le_bytes(bytes.try_into().ok()?).to_string(),
+            Self::U32 => u32::from_le_bytes(bytes.try_into().ok()?).to_string(),
+            Self::I64 => i64::from_le_bytes(bytes.try_into().ok()?).to_string(),
+            Self::U64 => u64::from_le_bytes(bytes.try_into().ok()?).to_string(),
+            Self::F32 => f32::from_le_bytes(bytes.try_into().ok()?).to_string(),
+            Self::F64 => f64::from_le_bytes(bytes.try_into().ok()?).to_string(),
+        };
+        Some(value)
+    }
+}
+
+/// Parse a poll interval ("100ms", "2s", "1m"; a bare number is milliseconds)
+pub fn parse_interval(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let Ok(value) = number.parse::<u64>() else {
+        bail!("Invalid interval: {:?} (e.g., 100ms, 1s)", s);
+    };
+    let duration = match unit.trim() {
+        "" | "ms" => Duration::from_millis(value),
+        "s" => Duration::from_secs(value),
+        "m" => Duration::from_secs(value * 60),
+        other => bail!("Unknown interval unit: {:?} (use ms, s or m)", other),
+    };
+    if duration.is_zero() {
+        bail!("Interval must be greater than zero");
+    }
+    Ok(duration)
+}
+
+/// Polls a fixed set of addresses and reports changes
+pub struct Watcher {
+    addresses: Vec<u64>,
+    value_type: WatchType,
+    last: Vec<Option<String>>,
+    started: bool,
+}
+
+impl Watcher {
+    pub fn new(addresses: Vec<u64>, value_type: WatchType) -> Self {
+        let last = vec![None; addresses.len()];
+        Self {
+            addresses,
+            value_type,
+            last,
+            started: false,
+        }
+    }
+
+    /// Read every address once; the first poll reports all initial values
+    pub fn poll<R: ReadMemory + ?Sized>(&mut self, reader: &R, timestamp: &str) -> Vec<Change> {
+        let first = !self.started;
+        self.started = true;
+        let mut changes = Vec::new();
+        for (address, last) in self.addresses.iter().zip(self.last.iter_mut()) {
+            let current = reader
+                .read_bytes(*address, self.value_type.size())
+                .ok()
+                .and_then(|bytes| self.value_type.decode(&bytes));
+            if first || current != *last {
+                changes.push(Change {
+                    timestamp: timestamp.to_string(),
+                    address: *address,
+                    old: last.clone(),
+                    new: current.clone(),
+                });
+                *last = current;
+            }
+        }
+        changes
+    }
+}
+
+/// Format a change as a human-readable line
+pub fn format_change(change: &Change) -> String {
+    let new = change.new.as_deref().unwrap_or("<unreadable>");
+    match &change.old {
+        Some(old) => format!(
+            "{}  0x{:X}  {} -> {}",
+            change.timestamp, change.address, old, new
+        ),
+        None => format!("{}  0x{:X}  {}", change.timestamp, change.address, new),
+    }
+}
+
+/// Format a change as a CSV row (`timestamp,address,old,new`)
+pub fn format_csv(change: &Change) -> String {
+    format!(
+        "{},0x{:X},{},{}",
+        change.timestamp,
+        change.address,
+        change.old.as_deref().unwrap_or(""),
+        change.new.as_deref().unwrap_or("")
+    )
+}
+
+/// Run the watch command
+pub fn run(
+    addresses: &[String],
+    value_type: WatchType,
+    interval: &str,
+    csv: bool,
+    pid: Option<u32>,
+) -> Result<()> {
+    let addresses = addresses
+        .iter()
+        .map(|a| parse_address(a))
+        .collect::<infst::Result<Vec<_>>>()?;
+    let interval = parse_interval(interval)?;
+
+    let process = cli_utils::open_process(pid)?;
+    let reader = MemoryReader::new(&process);
+
+    let shutdown = Arc::new(ShutdownSignal::new());
+    let _keyboard_handle = input::spawn_keyboard_monitor(Arc::clone(&shutdown), None);
+
+    outln!(
+        "Watching {} address(es) as {:?} every {:?} (press Esc or q to stop)",
+        addresses.len(),
+        value_type,
+        interval
+    );
+    if csv {
+        outln!("timestamp,address,old,new");
+    }
+
+    let mut watcher = Watcher::new(addresses, value_type);
+    let mut history = Vec::new();
+    loop {
+        let timestamp = Local::now().format(TIME_FORMAT).to_string();
+        for change in watcher.poll(&reader, &timestamp) {
+            if csv {
+                outln!("{}", format_csv(&change));
+            } else {
+                outln!("{}", format_change(&change));
+            }
+            if output::is_json() {
+                history.push(change);
+            }
+        }
+        if shutdown.wait(interval) {
+            break;
+        }
+    }
+
+    output::emit(&serde_json::json!({ "changes": history }));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use infst::process::MockMemoryBuilder;
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("100ms").unwrap(), Duration::from_millis(100));
+        assert_eq!(parse_interval("250").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_interval("2s").unwrap(), Duration::from_secs(2));
+        assert_eq!(parse_interval("1m").unwrap(), Duration::from_secs(60));
+        assert!(parse_interval("0ms").is_err());
+        assert!(parse_interval("fast").is_err());
+        assert!(parse_interval("10h").is_err());
+    }
+
+    #[test]
+    fn test_decode() {
+        let bytes = (-2i32).to_le_bytes();
+        assert_eq!(WatchType::I32.decode(&bytes).unwrap(), "-2");
+        assert_eq!(WatchType::U32.decode(&bytes).unwrap(), "4294967294");
+        assert_eq!(WatchType::I8.decode(&bytes).unwrap(), "-2");
+        assert_eq!(WatchType::F32.decode(&1.5f32.to_le_bytes()).unwrap(), "1.5");
+        assert_eq!(WatchType::I64.decode(&bytes), None);
+    }
+
+    #[test]
+    fn test_watcher_reports_initial_values_then_changes() {
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x20)
+            .write_i32(0x00, 7)
+            .write_i32(0x10, 9)
+            .build();
+        let mut watcher = Watcher::new(vec![0x1000, 0x1010, 0x9000], WatchType::I32);
+
+        let initial = watcher.poll(&reader, "t0");
+        assert_eq!(initial.len(), 3);
+        assert_eq!(format_change(&initial[0]), "t0  0x1000  7");
+        assert_eq!(format_change(&initial[2]), "t0  0x9000  <unreadable>");
+
+        // Nothing changed since the last poll
+        assert!(watcher.poll(&reader, "t1").is_empty());
+
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x20)
+            .write_i32(0x00, 7)
+            .write_i32(0x10, 10)
+            .build();
+        let changes = watcher.poll(&reader, "t2");
+        assert_eq!(changes.len(), 1);
+        assert_eq!(format_change(&changes[0]), "t2  0x1010  9 -> 10");
+        assert_eq!(format_csv(&changes[0]), "t2,0x1010,9,10");
+    }
+}
@@ -0,0 +1,156 @@
+//! Consent before scores are sent to the web service.
+//!
+//! Nothing is submitted until the user agreed: `--api-consent`
+//! (`INFST_API_CONSENT`) agrees up front, otherwise `sync`/`upload` ask on
+//! the terminal and tracking asks once at startup. `--preview-payload` shows
+//! the exact request body (or writes it to a file) before anything is sent.
+
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use anyhow::{Context, Result, bail};
+
+use crate::output::outln;
+
+/// `--preview-payload` value that prints the payload instead of writing a file
+pub const PREVIEW_TO_CONSOLE: &str = "-";
+
+/// How a submission is confirmed
+pub struct SubmitOptions {
+    /// Send without asking (`--api-consent`)
+    pub consent: bool,
+    /// Show the payload first: a file, or [`PREVIEW_TO_CONSOLE`]
+    pub preview_payload: Option<String>,
+    /// Whether the user can be asked on the terminal
+    pub interactive: bool,
+}
+
+impl SubmitOptions {
+    /// Options for a command run from the terminal (asks when stdin is one)
+    pub fn new(consent: bool, preview_payload: Option<String>) -> Self {
+        Self {
+            consent,
+            preview_payload,
+            interactive: io::stdin().is_terminal(),
+        }
+    }
+}
+
+/// Show the payload if requested, then decide whether to send it
+///
+/// Returns `Ok(false)` when the user declined, or when only a preview was
+/// asked for without consent; fails when consent can't be asked for.
+pub fn confirm_submission(
+    payload: &str,
+    entries: usize,
+    endpoint: &str,
+    options: &SubmitOptions,
+) -> Result<bool> {
+    match options.preview_payload.as_deref() {
+        Some(PREVIEW_TO_CONSOLE) => outln!("{}", payload),
+        Some(path) => {
+            fs::write(path, payload)
+                .with_context(|| format!("Failed to write payload preview to {}", path))?;
+            eprintln!("Payload written to {}", path);
+        }
+        None => {}
+    }
+
+    if options.consent {
+        return Ok(true);
+    }
+    if !options.interactive {
+        if options.preview_payload.is_some() {
+            return Ok(false);
+        }
+        bail!(
+            "Not sending scores to {} without consent: pass --api-consent (or set INFST_API_CONSENT=1), \
+             or run interactively. --preview-payload shows what would be sent.",
+            endpoint
+        );
+    }
+
+    let prompt = format!(
+        "Send {} entries ({} bytes) to {}? [y/N, p to show the payload] ",
+        entries,
+        payload.len(),
+        endpoint
+    );
+    loop {
+        match ask(&prompt).as_deref() {
+            Some("y" | "yes") => return Ok(true),
+            Some("p") => eprintln!("{}", payload),
+            _ => return Ok(false),
+        }
+    }
+}
+
+/// Ask once whether tracking may send lamps this session
+pub fn ask_tracking_consent(endpoint: &str) -> bool {
+    eprintln!(
+        "Lamps of level 11/12 plays will be sent to {} \
+         (song ID, difficulty, lamp, EX score, miss count, DJ name).",
+        endpoint
+    );
+    eprintln!("The first payload of the session is printed when it is sent.");
+    matches!(
+        ask("Send lamps this session? [y/N] ").as_deref(),
+        Some("y" | "yes")
+    )
+}
+
+/// Read a lowercase answer from the terminal
+fn ask(prompt: &str) -> Option<String> {
+    eprint!("{}", prompt);
+    io::stderr().flush().ok();
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line).ok()?;
+    Some(line.trim().to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(consent: bool, preview_payload: Option<String>) -> SubmitOptions {
+        SubmitOptions {
+            consent,
+            preview_payload,
+            interactive: false,
+        }
+    }
+
+    #[test]
+    fn test_consent_flag_sends() {
+        assert!(confirm_submission("{}", 1, "https://example.com", &options(true, None)).unwrap());
+    }
+
+    #[test]
+    fn test_no_consent_is_an_error() {
+        let err =
+            confirm_submission("{}", 1, "https://example.com", &options(false, None)).unwrap_err();
+        assert!(err.to_string().contains("--api-consent"));
+    }
+
+    #[test]
+    fn test_preview_without_consent_sends_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("payload.json");
+        let preview = Some(path.to_string_lossy().into_owned());
+        let payload = r#"{"entries":[]}"#;
+
+        assert!(
+            !confirm_submission(
+                payload,
+                0,
+                "https://example.com",
+                &options(false, preview.clone())
+            )
+            .unwrap()
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), payload);
+        assert!(
+            confirm_submission(payload, 0, "https://example.com", &options(true, preview)).unwrap()
+        );
+    }
+}
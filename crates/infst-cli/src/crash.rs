@@ -0,0 +1,120 @@
+//! Crash reports for panics.
+//!
+//! The panic hook writes the panic message, location and a backtrace to
+//! `crashes/crash-YYYYMMDD-HHMMSS.txt` in the data directory, logs it, then
+//! runs the default hook. The tracking loop catches the unwind itself
+//! (see `tracking::run_tracking_session`) so the session can be closed and
+//! the tracker exported before exiting or restarting.
+
+use std::backtrace::Backtrace;
+use std::fmt::Write as _;
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Local;
+use tracing::error;
+
+/// Report written for the most recent panic
+static LAST_REPORT: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Install the crash report hook; reports go to `dir` (printed only when `None`)
+pub fn install_panic_hook(dir: Option<PathBuf>) {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let report = format_report(info, &Backtrace::force_capture());
+        if let Ok(mut last) = LAST_REPORT.lock() {
+            *last = None;
+        }
+        error!("{}", panic_message(info));
+        match &dir {
+            Some(dir) => match write_report(dir, &report) {
+                Ok(path) => {
+                    eprintln!("Crash report written to {}", path.display());
+                    if let Ok(mut last) = LAST_REPORT.lock() {
+                        *last = Some(path);
+                    }
+                }
+                Err(e) => eprintln!("Failed to write crash report: {}\n{}", e, report),
+            },
+            None => eprintln!("{}", report),
+        }
+        default_hook(info);
+    }));
+}
+
+/// Path of the report for the most recent panic, if one was written
+pub fn last_report() -> Option<PathBuf> {
+    LAST_REPORT.lock().ok()?.clone()
+}
+
+/// Message of a caught panic payload
+pub fn payload_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let location = info
+        .location()
+        .map_or_else(String::new, |l| format!(" at {}:{}", l.file(), l.line()));
+    format!("Panic{}: {}", location, payload_message(info.payload()))
+}
+
+fn format_report(info: &PanicHookInfo<'_>, backtrace: &Backtrace) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "infst {} crash report", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "Time: {}", Local::now().to_rfc3339());
+    let _ = writeln!(
+        report,
+        "Thread: {}",
+        std::thread::current().name().unwrap_or("unnamed")
+    );
+    let _ = writeln!(report, "{}", panic_message(info));
+    let _ = writeln!(report);
+    let _ = writeln!(report, "Backtrace:");
+    let _ = write!(report, "{}", backtrace);
+    report
+}
+
+fn write_report(dir: &Path, report: &str) -> std::io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "crash-{}.txt",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_message() {
+        let payload = std::panic::catch_unwind(|| panic!("boom {}", 1)).unwrap_err();
+        assert_eq!(payload_message(payload.as_ref()), "boom 1");
+        let payload = std::panic::catch_unwind(|| panic!("static")).unwrap_err();
+        assert_eq!(payload_message(payload.as_ref()), "static");
+    }
+
+    #[test]
+    fn test_write_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_report(&dir.path().join("crashes"), "report").unwrap();
+        assert!(
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("crash-")
+        );
+        assert_eq!(fs::read_to_string(path).unwrap(), "report");
+    }
+}
@@ -0,0 +1,183 @@
+//! Summary of the files and settings a run uses, for the tracking start
+//! banner and the `environment` block of `status --json`.
+
+use std::path::PathBuf;
+
+use infst::{InfstConfig, Paths};
+use serde::Serialize;
+
+use crate::commands::login;
+
+/// Where the offsets in use came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OffsetsSource {
+    /// `--offsets-file`
+    File,
+    /// Offset cache of an earlier run
+    Cache,
+    /// Signature search
+    Search,
+}
+
+impl OffsetsSource {
+    fn label(self) -> &'static str {
+        match self {
+            Self::File => "offsets file",
+            Self::Cache => "cache",
+            Self::Search => "signature search",
+        }
+    }
+}
+
+/// Files and settings of this run
+#[derive(Debug, Clone, Serialize)]
+pub struct Environment {
+    pub version: &'static str,
+    /// Credentials file saved by `login`, when present
+    pub config_file: Option<PathBuf>,
+    /// Data directory (empty in portable mode)
+    pub data_dir: PathBuf,
+    pub profile: Option<String>,
+    pub tracker: PathBuf,
+    pub session_dir: PathBuf,
+    pub offsets_source: Option<OffsetsSource>,
+    pub game_version: Option<String>,
+    /// Outputs besides the session files (tracking only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sinks: Option<Vec<&'static str>>,
+}
+
+impl Environment {
+    pub fn new(paths: &Paths) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            config_file: login::credentials_path().ok().filter(|path| path.is_file()),
+            data_dir: paths.root().to_path_buf(),
+            profile: paths.profile().map(str::to_string),
+            tracker: paths.tracker(),
+            session_dir: paths.sessions(),
+            offsets_source: None,
+            game_version: None,
+            sinks: None,
+        }
+    }
+
+    /// Take the paths and enabled outputs from the tracker's configuration
+    pub fn with_config(mut self, config: &InfstConfig) -> Self {
+        self.tracker = config.tracker_path.clone();
+        self.session_dir = config.session_dir.clone();
+        self.sinks = Some(sinks(config));
+        self
+    }
+
+    /// Lines of the startup banner
+    pub fn lines(&self) -> Vec<String> {
+        let or_none = |value: Option<String>| value.unwrap_or_else(|| "none".to_string());
+        let data_dir = if self.data_dir.as_os_str().is_empty() {
+            "current directory (portable)".to_string()
+        } else {
+            self.data_dir.display().to_string()
+        };
+        let mut lines = vec![
+            format!("infst:    v{}", self.version),
+            format!(
+                "Config:   {}",
+                or_none(self.config_file.as_ref().map(|p| p.display().to_string()))
+            ),
+            format!("Data:     {}", data_dir),
+        ];
+        if let Some(profile) = &self.profile {
+            lines.push(format!("Profile:  {}", profile));
+        }
+        lines.push(format!("Tracker:  {}", self.tracker.display()));
+        lines.push(format!("Sessions: {}", self.session_dir.display()));
+        lines.push(format!(
+            "Offsets:  {}",
+            or_none(self.offsets_source.map(|s| s.label().to_string()))
+        ));
+        lines.push(format!(
+            "Game:     {}",
+            self.game_version.as_deref().unwrap_or("unknown version")
+        ));
+        if let Some(sinks) = &self.sinks {
+            lines.push(format!(
+                "Outputs:  {}",
+                if sinks.is_empty() {
+                    "none".to_string()
+                } else {
+                    sinks.join(", ")
+                }
+            ));
+        }
+        lines
+    }
+}
+
+/// Names of the outputs the tracker writes or sends plays to
+fn sinks(config: &InfstConfig) -> Vec<&'static str> {
+    if config.dry_run {
+        return Vec::new();
+    }
+    let mut sinks = Vec::new();
+    if config.auto_export {
+        sinks.push(if config.tracker_snapshots {
+            "tracker snapshots"
+        } else {
+            "tracker"
+        });
+    }
+    if !config.schedule.is_empty() {
+        sinks.push("scheduled exports");
+    }
+    if config.api_config.is_some() {
+        sinks.push("api");
+    }
+    if config.stream.is_some() {
+        sinks.push("stream");
+    }
+    if config.push.is_some() {
+        sinks.push("push");
+    }
+    if config.summary_image {
+        sinks.push("summary image");
+    }
+    if config.tournament_file.is_some() {
+        sinks.push("tournament");
+    }
+    sinks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sinks() {
+        let config = InfstConfig {
+            auto_export: true,
+            summary_image: true,
+            ..InfstConfig::default()
+        };
+        assert_eq!(sinks(&config), vec!["tracker", "summary image"]);
+
+        let dry_run = InfstConfig {
+            dry_run: true,
+            ..config
+        };
+        assert!(sinks(&dry_run).is_empty());
+    }
+
+    #[test]
+    fn test_lines() {
+        let paths = Paths::portable().with_profile("sister").unwrap();
+        let mut environment = Environment::new(&paths);
+        environment.offsets_source = Some(OffsetsSource::Cache);
+        let lines = environment.lines();
+        assert!(lines.contains(&"Data:     current directory (portable)".to_string()));
+        assert!(lines.contains(&"Profile:  sister".to_string()));
+        assert!(lines.contains(&"Offsets:  cache".to_string()));
+        assert!(lines.contains(&"Game:     unknown version".to_string()));
+        assert!(!lines.iter().any(|line| line.starts_with("Outputs:")));
+    }
+}
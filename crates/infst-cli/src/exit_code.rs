@@ -0,0 +1,170 @@
+//! Process exit codes.
+//!
+//! Codes are part of the CLI contract for batch scripts and launchers;
+//! existing values must never change (see the test below).
+
+use std::fmt;
+
+use infst::ErrorCategory;
+
+pub const OK: u8 = 0;
+/// Unclassified failure
+pub const GENERAL_ERROR: u8 = 1;
+/// Invalid arguments (emitted by clap)
+pub const USAGE_ERROR: u8 = 2;
+pub const PROCESS_NOT_FOUND: u8 = 10;
+pub const OFFSETS_NOT_FOUND: u8 = 11;
+pub const VALIDATION_FAILED: u8 = 12;
+pub const ACCESS_DENIED: u8 = 13;
+pub const MEMORY_READ_FAILED: u8 = 14;
+pub const EXPORT_ERROR: u8 = 20;
+pub const PARSE_ERROR: u8 = 21;
+pub const NETWORK_ERROR: u8 = 30;
+
+/// Exit code table shown in `--help`
+pub const HELP: &str = "\
+Exit codes:
+   0  Success
+   1  Unclassified error
+   2  Invalid arguments
+  10  Game process not found
+  11  Offsets not found
+  12  Validation failed
+  13  Access to the game process denied
+  14  Memory read failed
+  20  Export or file write error
+  21  Input file could not be parsed
+  30  Network error";
+
+/// Error carrying an explicit exit code
+///
+/// Commands return this (via `anyhow`) when the failure is not an
+/// `infst::Error`, or to override its category-based code.
+#[derive(Debug)]
+pub struct ExitError {
+    pub code: u8,
+    pub message: String,
+}
+
+impl ExitError {
+    pub fn new(code: u8, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn validation_failed(message: impl Into<String>) -> Self {
+        Self::new(VALIDATION_FAILED, message)
+    }
+
+    pub fn export_error(message: impl Into<String>) -> Self {
+        Self::new(EXPORT_ERROR, message)
+    }
+}
+
+impl fmt::Display for ExitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ExitError {}
+
+/// Exit code for a core library error
+pub fn for_core_error(error: &infst::Error) -> u8 {
+    match error {
+        infst::Error::ProcessNotFound(_) | infst::Error::ProcessOpenFailed(_) => PROCESS_NOT_FOUND,
+        infst::Error::ProcessAccessDenied { .. } => ACCESS_DENIED,
+        infst::Error::MemoryReadFailed { .. } => MEMORY_READ_FAILED,
+        other => match other.category() {
+            ErrorCategory::OffsetSearch => OFFSETS_NOT_FOUND,
+            ErrorCategory::Parse => PARSE_ERROR,
+            ErrorCategory::Storage => EXPORT_ERROR,
+            ErrorCategory::Network => NETWORK_ERROR,
+            ErrorCategory::ProcessAccess => PROCESS_NOT_FOUND,
+            ErrorCategory::Internal => GENERAL_ERROR,
+        },
+    }
+}
+
+/// Exit code for an error returned by a command
+///
+/// An [`ExitError`] (as the error or its context) wins over an `infst::Error`.
+pub fn for_error(error: &anyhow::Error) -> u8 {
+    if let Some(exit) = error.downcast_ref::<ExitError>().or_else(|| {
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<ExitError>())
+    }) {
+        return exit.code;
+    }
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<infst::Error>())
+        .map(for_core_error)
+        .unwrap_or(GENERAL_ERROR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_are_stable() {
+        assert_eq!(OK, 0);
+        assert_eq!(GENERAL_ERROR, 1);
+        assert_eq!(USAGE_ERROR, 2);
+        assert_eq!(PROCESS_NOT_FOUND, 10);
+        assert_eq!(OFFSETS_NOT_FOUND, 11);
+        assert_eq!(VALIDATION_FAILED, 12);
+        assert_eq!(ACCESS_DENIED, 13);
+        assert_eq!(MEMORY_READ_FAILED, 14);
+        assert_eq!(EXPORT_ERROR, 20);
+        assert_eq!(PARSE_ERROR, 21);
+        assert_eq!(NETWORK_ERROR, 30);
+    }
+
+    #[test]
+    fn test_help_lists_every_code() {
+        for code in [
+            OK,
+            GENERAL_ERROR,
+            USAGE_ERROR,
+            PROCESS_NOT_FOUND,
+            OFFSETS_NOT_FOUND,
+            VALIDATION_FAILED,
+            ACCESS_DENIED,
+            MEMORY_READ_FAILED,
+            EXPORT_ERROR,
+            PARSE_ERROR,
+            NETWORK_ERROR,
+        ] {
+            assert!(
+                HELP.lines()
+                    .any(|line| line.trim_start().starts_with(&format!("{} ", code))),
+                "exit code {} missing from help",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn test_for_error_mapping() {
+        let not_found = anyhow::Error::new(infst::Error::ProcessNotFound("x".to_string()));
+        assert_eq!(for_error(&not_found), PROCESS_NOT_FOUND);
+
+        let offsets = anyhow::Error::new(infst::Error::offset_search_failed("none"))
+            .context("Failed to detect offsets");
+        assert_eq!(for_error(&offsets), OFFSETS_NOT_FOUND);
+
+        let validation = anyhow::Error::new(ExitError::validation_failed("bad"));
+        assert_eq!(for_error(&validation), VALIDATION_FAILED);
+
+        let export = anyhow::Error::new(infst::Error::parse("json", "x"))
+            .context(ExitError::export_error("Failed to generate export"));
+        assert_eq!(for_error(&export), EXPORT_ERROR);
+
+        assert_eq!(for_error(&anyhow::anyhow!("other")), GENERAL_ERROR);
+    }
+}
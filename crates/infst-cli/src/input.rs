@@ -1,31 +1,48 @@
 use crate::shutdown::ShutdownSignal;
-use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use infst::{SessionCommand, SessionControl};
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
 use std::time::Duration;
-use tracing::debug;
+use tracing::{debug, warn};
 
-/// Spawn a thread that monitors keyboard input for shutdown keys (Esc, q, Q).
+/// Spawn a thread that monitors keyboard input for shutdown keys (Esc, q, Q)
+/// and session hotkeys.
 ///
 /// The thread polls for keyboard events and triggers shutdown when:
 /// - Esc key is pressed
 /// - 'q' or 'Q' key is pressed
 ///
+/// Hotkeys (see [`hotkey_command`]) are queued on `control` for the tracker
+/// (ignored when `None`).
+///
 /// Returns a JoinHandle that can be used to wait for the thread to finish.
-pub fn spawn_keyboard_monitor(shutdown: Arc<ShutdownSignal>) -> JoinHandle<()> {
+pub fn spawn_keyboard_monitor(
+    shutdown: Arc<ShutdownSignal>,
+    control: Option<SessionControl>,
+) -> JoinHandle<()> {
     thread::spawn(move || {
         debug!("Keyboard monitor started");
 
         while !shutdown.is_shutdown() {
             // Poll for events with a timeout to allow checking shutdown state
-            if event::poll(Duration::from_millis(100)).unwrap_or(false)
-                && let Ok(Event::Key(key_event)) = event::read()
-                && should_shutdown(&key_event)
-            {
+            if !event::poll(Duration::from_millis(100)).unwrap_or(false) {
+                continue;
+            }
+            let Ok(Event::Key(key_event)) = event::read() else {
+                continue;
+            };
+            if should_shutdown(&key_event) {
                 debug!("Shutdown key pressed: {:?}", key_event.code);
                 shutdown.trigger();
                 break;
             }
+            if let Some(control) = &control
+                && let Some(command) = hotkey_command(&key_event)
+                && let Err(e) = control.send(&command)
+            {
+                warn!("Failed to queue {:?}: {}", command, e);
+            }
         }
 
         debug!("Keyboard monitor stopped");
@@ -40,6 +57,18 @@ fn should_shutdown(event: &KeyEvent) -> bool {
     )
 }
 
+/// Session command bound to a key ('c' copies the last play result).
+fn hotkey_command(event: &KeyEvent) -> Option<SessionCommand> {
+    // Ctrl+C is the interrupt, not a copy
+    if event.modifiers.contains(KeyModifiers::CONTROL) {
+        return None;
+    }
+    match event.code {
+        KeyCode::Char('c') | KeyCode::Char('C') => Some(SessionCommand::Copy),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,4 +103,16 @@ mod tests {
         let event = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE);
         assert!(!should_shutdown(&event));
     }
+
+    #[test]
+    fn test_hotkey_copy() {
+        let event = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE);
+        assert_eq!(hotkey_command(&event), Some(SessionCommand::Copy));
+
+        let event = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(hotkey_command(&event), None);
+
+        let event = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE);
+        assert_eq!(hotkey_command(&event), None);
+    }
 }
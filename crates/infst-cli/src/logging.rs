@@ -0,0 +1,271 @@
+//! Console and rotating file logging.
+//!
+//! The console gets `warn` and above unless `RUST_LOG` or `--console-log-level`
+//! says otherwise. With `--log-dir`, events at `--log-level` (info by default)
+//! are also appended to `infst-YYYY-MM-DD.log` in that directory. A new file
+//! starts every day and whenever the current one reaches `--log-max-size`;
+//! the oldest files beyond [`MAX_LOG_FILES`] are deleted.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDate};
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+
+use crate::output;
+
+/// Log files kept in the log directory
+pub const MAX_LOG_FILES: usize = 14;
+
+const LOG_PREFIX: &str = "infst-";
+const LOG_EXTENSION: &str = "log";
+
+/// Where and how much to log
+pub struct LogOptions {
+    /// Console level; `RUST_LOG` is used (or `warn`) when `None`
+    pub console_level: Option<LevelFilter>,
+    /// Directory for log files (no file logging when `None`)
+    pub dir: Option<PathBuf>,
+    /// File level
+    pub file_level: LevelFilter,
+    /// Size at which a new log file is started, in bytes
+    pub max_file_size: u64,
+}
+
+impl Default for LogOptions {
+    fn default() -> Self {
+        Self {
+            console_level: None,
+            dir: None,
+            file_level: LevelFilter::INFO,
+            max_file_size: 10 * 1024 * 1024,
+        }
+    }
+}
+
+/// Filter for infst's own crates at `level`
+fn crate_filter(level: LevelFilter) -> EnvFilter {
+    EnvFilter::new(format!("infst_cli={level},infst={level}"))
+}
+
+/// Install the global subscriber
+pub fn init(options: &LogOptions) -> Result<()> {
+    let console_filter = match options.console_level {
+        Some(level) => crate_filter(level),
+        None => {
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| crate_filter(LevelFilter::WARN))
+        }
+    };
+    // Keep stdout reserved for the result object in JSON mode
+    let console = if output::is_json() {
+        tracing_subscriber::fmt::layer()
+            .with_writer(io::stderr)
+            .with_filter(console_filter)
+            .boxed()
+    } else {
+        tracing_subscriber::fmt::layer()
+            .with_filter(console_filter)
+            .boxed()
+    };
+
+    let file = match &options.dir {
+        Some(dir) => {
+            let writer = RotatingFile::new(dir, options.max_file_size, MAX_LOG_FILES)
+                .with_context(|| format!("Failed to open log directory {}", dir.display()))?;
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(Mutex::new(writer))
+                    .with_filter(crate_filter(options.file_level)),
+            )
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry()
+        .with(console)
+        .with(file)
+        .init();
+    Ok(())
+}
+
+/// Log file writer that starts a new file per day and per `max_size` bytes
+pub struct RotatingFile {
+    dir: PathBuf,
+    max_size: u64,
+    max_files: usize,
+    date: NaiveDate,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    pub fn new(dir: &Path, max_size: u64, max_files: usize) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let date = Local::now().date_naive();
+        let (file, size) = open_log(&log_path(dir, date))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            max_size,
+            max_files,
+            date,
+            file,
+            size,
+        })
+    }
+
+    /// File currently written to
+    pub fn path(&self) -> PathBuf {
+        log_path(&self.dir, self.date)
+    }
+
+    /// Start a new file if `date` is a new day or `incoming` bytes would
+    /// overflow the current one
+    fn rotate_if_needed(&mut self, date: NaiveDate, incoming: usize) -> io::Result<()> {
+        if date != self.date {
+            self.date = date;
+        } else if self.size > 0 && self.size + incoming as u64 > self.max_size {
+            // Full files of the day are numbered: infst-YYYY-MM-DD.1.log, ...
+            let current = self.path();
+            let mut n = 1;
+            let rolled = loop {
+                let candidate = current.with_extension(format!("{}.{}", n, LOG_EXTENSION));
+                if !candidate.exists() {
+                    break candidate;
+                }
+                n += 1;
+            };
+            self.file.flush()?;
+            fs::rename(&current, rolled)?;
+        } else {
+            return Ok(());
+        }
+        (self.file, self.size) = open_log(&self.path())?;
+        self.prune();
+        Ok(())
+    }
+
+    /// Delete the oldest log files beyond `max_files` (best-effort)
+    fn prune(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut logs: Vec<(std::time::SystemTime, PathBuf)> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_log_file(path))
+            .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+            .collect();
+        if logs.len() <= self.max_files {
+            return;
+        }
+        logs.sort();
+        for (_, path) in &logs[..logs.len() - self.max_files] {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    fn write_on(&mut self, date: NaiveDate, buf: &[u8]) -> io::Result<usize> {
+        self.rotate_if_needed(date, buf.len())?;
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_on(Local::now().date_naive(), buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn log_path(dir: &Path, date: NaiveDate) -> PathBuf {
+    dir.join(format!(
+        "{}{}.{}",
+        LOG_PREFIX,
+        date.format("%Y-%m-%d"),
+        LOG_EXTENSION
+    ))
+}
+
+fn is_log_file(path: &Path) -> bool {
+    path.is_file()
+        && path.extension().is_some_and(|ext| ext == LOG_EXTENSION)
+        && path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(LOG_PREFIX))
+}
+
+/// Open `path` for appending, with its current size
+fn open_log(path: &Path) -> io::Result<(File, u64)> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let size = file.metadata()?.len();
+    Ok((file, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_rotates_on_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = RotatingFile::new(dir.path(), 10, MAX_LOG_FILES).unwrap();
+        let date = log.date;
+        log.write_on(date, b"12345678\n").unwrap();
+        log.write_on(date, b"abcdefgh\n").unwrap();
+        log.write_on(date, b"ABCDEFGH\n").unwrap();
+
+        let today = log_path(dir.path(), date);
+        let stem = today.file_stem().unwrap().to_string_lossy().into_owned();
+        assert_eq!(
+            log_names(dir.path()),
+            vec![
+                format!("{stem}.1.log"),
+                format!("{stem}.2.log"),
+                format!("{stem}.log")
+            ]
+        );
+        assert_eq!(fs::read_to_string(today).unwrap(), "ABCDEFGH\n");
+    }
+
+    #[test]
+    fn test_rotates_daily_and_prunes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = RotatingFile::new(dir.path(), 1024, 2).unwrap();
+        let start = log.date;
+        for day in 1..=3 {
+            log.write_on(start + chrono::Days::new(day), b"line\n")
+                .unwrap();
+        }
+        assert_eq!(
+            log.path(),
+            log_path(dir.path(), start + chrono::Days::new(3))
+        );
+        let names = log_names(dir.path());
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&format!(
+            "infst-{}.log",
+            (start + chrono::Days::new(3)).format("%Y-%m-%d")
+        )));
+    }
+}
@@ -1,42 +1,136 @@
 mod cli;
 mod cli_utils;
 mod commands;
+mod consent;
+mod crash;
+mod environment;
+mod exit_code;
 mod input;
+mod logging;
+mod output;
 mod prompter;
 mod retry;
 mod shutdown;
 mod validation;
 
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
 use anyhow::Result;
 use clap::Parser;
-use cli::{Args, Command};
-use tracing_subscriber::EnvFilter;
+use cli::{Args, Command, ExportFormat};
+use consent::SubmitOptions;
+use infst::input::hotkeys::HotkeyConfig;
+use infst::{ExportFilter, PlayStyle};
 
-fn main() -> Result<()> {
+fn main() -> ExitCode {
+    match try_main() {
+        Ok(()) => {
+            output::finish_ok();
+            ExitCode::from(exit_code::OK)
+        }
+        Err(e) => {
+            if output::is_json() {
+                output::emit_error(&e);
+            } else {
+                cli_utils::report_error(&e);
+            }
+            ExitCode::from(exit_code::for_error(&e))
+        }
+    }
+}
+
+fn try_main() -> Result<()> {
     // URI handler: Windows passes the URI as the first argument
     if let Some(uri) = std::env::args().nth(1)
         && uri.starts_with("bm2dxinf://")
     {
-        init_logging();
-        return commands::tracking::run_with_uri(&uri, None, None);
+        logging::init(&logging::LogOptions::default())?;
+        return commands::tracking::run_with_uri(&uri, commands::tracking::ApiOptions::default());
+    }
+
+    let args = Args::try_parse().unwrap_or_else(|e| {
+        // --help/--version also end up here and exit successfully
+        let code = if e.use_stderr() {
+            exit_code::USAGE_ERROR
+        } else {
+            exit_code::OK
+        };
+        let _ = e.print();
+        std::process::exit(code.into());
+    });
+    let command = args.command.as_ref().map_or("tracking", Command::name);
+    output::init(args.output_format, command);
+    if output::is_json() && !args.command.as_ref().is_some_and(Command::supports_json) {
+        return Err(exit_code::ExitError::new(
+            exit_code::USAGE_ERROR,
+            format!("{} does not support --output json", command),
+        )
+        .into());
     }
+    logging::init(&logging::LogOptions {
+        console_level: args.console_log_level,
+        dir: args.log_dir.as_ref().map(PathBuf::from),
+        file_level: args.log_level,
+        max_file_size: args.log_max_size.saturating_mul(1024 * 1024),
+    })?;
 
-    let args = Args::parse();
-    init_logging();
+    if let Some(selection) = args.process_select.clone() {
+        cli_utils::set_process_selection(selection);
+    }
+    cli_utils::set_process_matcher(infst::ProcessMatcher::new(
+        args.process_name.as_deref(),
+        args.process_title.as_deref(),
+        args.process_path.as_deref(),
+    )?);
 
+    let relaunch_as_admin = args.relaunch_as_admin;
+    let result = run(args);
+    if relaunch_as_admin
+        && let Err(e) = &result
+        && cli_utils::is_access_denied(e)
+        && !infst::process::elevation::is_elevated()
+    {
+        eprintln!("{}", e);
+        eprintln!("Re-launching as administrator...");
+        return infst::process::elevation::relaunch_as_admin();
+    }
+    result
+}
+
+fn run(args: Args) -> Result<()> {
+    let mut paths = infst::Paths::resolve(args.portable, args.data_dir.map(PathBuf::from));
+    if let Some(profile) = &args.profile {
+        paths = paths.with_profile(profile)?;
+    }
     match args.command {
-        Some(Command::FindOffsets { output, pid }) => commands::find_offsets::run(&output, pid),
+        Some(Command::FindOffsets { output, pid }) => {
+            commands::find_offsets::run(&path_or(output, paths.offsets()), pid)
+        }
         Some(Command::Analyze { address, pid }) => commands::analyze::run(address, pid),
         Some(Command::Status {
             offsets_file,
             pid,
             json,
-        }) => commands::status::run(offsets_file.as_deref(), pid, json),
+        }) => commands::status::run(
+            offsets_file.as_deref(),
+            &paths.signatures(),
+            pid,
+            json,
+            environment::Environment::new(&paths),
+        ),
         Some(Command::Dump {
             offsets_file,
             pid,
             output,
-        }) => commands::dump::run(offsets_file.as_deref(), pid, output.as_deref()),
+            score_map,
+        }) => commands::dump::run(
+            offsets_file.as_deref(),
+            pid,
+            output.as_deref(),
+            score_map.as_deref(),
+        ),
         Some(Command::Scan {
             offsets_file,
             pid,
@@ -53,59 +147,423 @@ fn main() -> Result<()> {
             entry_size,
         ),
         Some(Command::Explore { address, pid }) => {
-            let addr = commands::hex_utils::parse_hex_address(&address)?;
+            let addr = infst::util::hex::parse_address(&address)?;
             commands::explore::run(addr, pid)
         }
+        Some(Command::DiscoverLayout {
+            address,
+            size,
+            dump,
+            base,
+            since,
+            output,
+            pid,
+        }) => commands::discover_layout::run(&commands::discover_layout::DiscoverLayoutOptions {
+            address: &address,
+            size,
+            dump: dump.as_deref(),
+            base: &base,
+            since: since.as_deref(),
+            output: output.as_deref(),
+            pid,
+        }),
         Some(Command::Hexdump {
             address,
             size,
             ascii,
+            deref,
+            annotate,
             pid,
         }) => {
-            let addr = commands::hex_utils::parse_hex_address(&address)?;
-            commands::hexdump::run(addr, size, ascii, pid)
+            let addr = infst::util::hex::parse_address(&address)?;
+            let options = commands::hexdump::HexdumpOptions {
+                deref_depth: deref.unwrap_or(0),
+                offsets_file: args.offsets_file.as_deref(),
+                annotate,
+            };
+            commands::hexdump::run(addr, size, ascii, pid, &options)
         }
         Some(Command::Search {
             string,
+            regex,
+            encoding,
+            ignore_case,
+            min_len,
+            range,
+            width,
+            align,
+            stride,
+            run,
             i32,
             i16,
             pattern,
             limit,
             pid,
-        }) => commands::search::run(string, i32, i16, pattern, limit, pid),
+        }) => commands::search::run(
+            &commands::search::SearchArgs {
+                string,
+                regex,
+                text: commands::search::TextOptions {
+                    encoding: encoding.into(),
+                    ignore_case,
+                },
+                min_len,
+                i32,
+                i16,
+                pattern,
+                range,
+                width,
+                align,
+                stride,
+                run,
+                limit,
+            },
+            pid,
+        ),
+        Some(Command::StructDiff {
+            layout,
+            a,
+            b,
+            size,
+            changed,
+            pid,
+        }) => commands::struct_diff::run(&layout, &a, &b, size, changed, pid),
+        Some(Command::Watch {
+            address,
+            value_type,
+            interval,
+            csv,
+            pid,
+        }) => commands::watch::run(&address, value_type, &interval, csv, pid),
+        Some(Command::Shell { pid }) => commands::shell::run(pid),
+        Some(Command::Fixture {
+            input,
+            base,
+            address,
+            size,
+            mask,
+            comment,
+            output,
+        }) => commands::fixture::run(
+            &input,
+            &commands::fixture::FixtureOptions {
+                base: &base,
+                address: address.as_deref(),
+                size,
+                masks: &mask,
+                comments: &comment,
+                output: output.as_deref(),
+            },
+        ),
         Some(Command::Offset { from, to }) => commands::offset::run(&from, &to),
         Some(Command::Validate { target }) => commands::validate::run(target),
+        Some(Command::Session {
+            action,
+            session_dir,
+        }) => commands::session::run(action, &path_or(session_dir, paths.sessions())),
         Some(Command::Export {
             output,
             format,
             pid,
-        }) => commands::export::run(output.as_deref(), format, pid),
+            analytics,
+            min_level,
+            max_level,
+            sp,
+            dp,
+            played_only,
+            unlocked_only,
+            folder,
+            tag,
+            tags_file,
+            title,
+            title2,
+            title_aliases,
+            chart_density,
+            ignore_file,
+            hash_map,
+            encoding,
+            sign_key,
+        }) => {
+            let ignore = cli_utils::load_ignore_list(ignore_file.as_deref(), &paths.ignore_list())?;
+            let tags = cli_utils::load_tags(tags_file.as_deref(), &paths.tags())?;
+            let assisted = infst::AssistedBests::load(
+                paths.sessions().join(infst::score::ASSISTED_BESTS_FILE),
+            )?;
+            let density = match format {
+                ExportFormat::Json => {
+                    cli_utils::load_density(chart_density.as_deref(), &paths.chart_density())?
+                }
+                _ => None,
+            };
+            let titles = cli_utils::load_title_options(
+                title,
+                title2,
+                title_aliases.as_deref(),
+                &paths.title_aliases(),
+            )?;
+            let filter = ExportFilter {
+                min_level,
+                max_level,
+                style: play_style(sp, dp),
+                played_only,
+                unlocked_only,
+                folders: folder,
+                songs: cli_utils::tagged_songs(tags.as_ref(), &tag)?,
+            };
+            commands::export::run(
+                format,
+                pid,
+                analytics.as_deref(),
+                tags.as_ref(),
+                &filter,
+                hash_map.as_deref(),
+                &commands::export::OutputOptions {
+                    path: output.as_deref(),
+                    encoding,
+                    sign_key: sign_key.as_deref(),
+                    titles,
+                    density: density.as_ref(),
+                    ignore: &ignore,
+                    assisted: &assisted,
+                },
+            )
+        }
+        Some(Command::Verify { file, key }) => commands::verify::run(&file, &key),
+        Some(Command::Diff {
+            old,
+            new,
+            min_score_delta,
+        }) => commands::diff::run(&old, &path_or(new, paths.tracker()), min_score_delta),
+        Some(Command::Scores { action }) => commands::scores::run(action),
+        Some(Command::Tracker {
+            action,
+            session_dir,
+        }) => commands::tracker::run(action, &path_or(session_dir, paths.sessions())),
+        Some(Command::TrackerDiff { old, new, json }) => {
+            commands::tracker_diff::run(&old, &new, json)
+        }
+        Some(Command::Merge {
+            a,
+            b,
+            output,
+            encoding,
+            json,
+        }) => commands::merge::run(&a, &b, &output, encoding, json),
+        Some(Command::Unlocks {
+            plan,
+            budget,
+            min_level,
+            sp,
+            dp,
+            custom_types,
+            offsets_file,
+            pid,
+        }) => commands::unlocks::run(
+            &commands::unlocks::UnlocksOptions {
+                plan,
+                budget,
+                min_level,
+                style: play_style(sp, dp),
+                custom_types_path: custom_types.as_deref(),
+            },
+            offsets_file.as_deref(),
+            &paths.signatures(),
+            pid,
+        ),
+        Some(Command::Songs {
+            search,
+            level,
+            min_level,
+            max_level,
+            sp,
+            dp,
+            from,
+            pid,
+        }) => {
+            let query = infst::ChartQuery {
+                text: search,
+                min_level: level.or(min_level),
+                max_level: level.or(max_level),
+                style: play_style(sp, dp),
+            };
+            commands::songs::run(&query, from.as_deref(), &paths.tracker(), pid)
+        }
+        Some(Command::Recommend {
+            target_djp,
+            max_level,
+            dp,
+            tag,
+            tags_file,
+            chart_density,
+            pid,
+        }) => {
+            let tags = cli_utils::load_tags(tags_file.as_deref(), &paths.tags())?;
+            let songs = cli_utils::tagged_songs(tags.as_ref(), &tag)?;
+            let density =
+                cli_utils::load_density(chart_density.as_deref(), &paths.chart_density())?;
+            commands::recommend::run(
+                target_djp,
+                max_level,
+                dp,
+                songs,
+                density.as_ref(),
+                &paths.sessions(),
+                pid,
+            )
+        }
         Some(Command::Login { endpoint }) => commands::login::run(&endpoint),
         Some(Command::Sync {
             endpoint,
             token,
             pid,
-        }) => commands::sync::run(endpoint.as_deref(), token.as_deref(), pid),
+            api_consent,
+            preview_payload,
+            ignore_file,
+        }) => commands::sync::run(
+            endpoint.as_deref(),
+            token.as_deref(),
+            pid,
+            &cli_utils::load_ignore_list(ignore_file.as_deref(), &paths.ignore_list())?,
+            &SubmitOptions::new(api_consent, preview_payload),
+        ),
         Some(Command::Launch { url, pid, timeout }) => {
             commands::launch::run(url.as_deref(), pid, timeout)
         }
         Some(Command::Register) => commands::register::run(),
+        Some(Command::Selftest { offsets_file, pid }) => {
+            commands::selftest::run(offsets_file.as_deref(), pid)
+        }
+        Some(Command::Doctor { pid, session_dir }) => {
+            commands::doctor::run(pid, &path_or(session_dir, paths.sessions()))
+        }
+        Some(Command::Completions { shell }) => commands::completions::run(shell),
+        Some(Command::Man { output }) => commands::man::run(output.as_deref()),
         Some(Command::Upload {
             tracker,
             mapping,
             endpoint,
             token,
-        }) => commands::upload::run(&tracker, &mapping, endpoint.as_deref(), token.as_deref()),
-        None => commands::tracking::run(
-            args.offsets_file.as_deref(),
-            args.api_endpoint.as_deref(),
-            args.api_token.as_deref(),
+            api_consent,
+            preview_payload,
+        }) => commands::upload::run(
+            &path_or(tracker, paths.tracker()),
+            &mapping,
+            endpoint.as_deref(),
+            token.as_deref(),
+            &SubmitOptions::new(api_consent, preview_payload),
         ),
+        None => {
+            let stream_titles = cli_utils::load_title_options(
+                args.stream_title,
+                args.stream_title2,
+                args.title_aliases.as_deref(),
+                &paths.title_aliases(),
+            )?;
+            commands::tracking::run(
+                args.offsets_file.as_deref(),
+                args.bpi_file.as_deref(),
+                commands::tracking::SessionOptions {
+                    tracker_snapshots: args.tracker_snapshots,
+                    summary_image: args.summary_image,
+                    dry_run: args.dry_run,
+                    restart_on_panic: args.restart_on_panic,
+                    retry: args.retry.iter().fold(
+                        infst::RetryPolicies::default(),
+                        |mut policies, setting| {
+                            policies.set(setting.operation, setting.policy);
+                            policies
+                        },
+                    ),
+                    schedule: args.schedule,
+                    ignore_paused_plays: args.ignore_paused_plays,
+                    drop_anomalies: args.drop_anomalies,
+                    premature_mode: args.premature.into(),
+                    duplicate_window: Some(Duration::from_secs(args.duplicate_window)),
+                    green_number_alert: args.green_number_alert,
+                    tournament: args.tournament,
+                    force: args.force,
+                    fake_time: args.fake_time,
+                    timezone: args.timezone,
+                    tsv_encoding: args.tsv_encoding,
+                    clipboard_template: args.clipboard_template,
+                    push: args.push_url.map(|url| infst::PushConfig {
+                        url,
+                        template: args.push_template,
+                        content_type: args.push_content_type,
+                        headers: args.push_header,
+                        rules: args.push_on,
+                    }),
+                    ignore: cli_utils::load_ignore_list(
+                        args.ignore_file.as_deref(),
+                        &paths.ignore_list(),
+                    )?,
+                    hotkeys: if args.no_hotkeys {
+                        HotkeyConfig::disabled()
+                    } else {
+                        HotkeyConfig::default().with_overrides(args.hotkey)
+                    },
+                    paths,
+                },
+                stream_config(
+                    args.stream_dir,
+                    args.marquee_template,
+                    args.marquee_idle_text,
+                    args.result_card,
+                    args.recent_plays,
+                    args.stream_encoding,
+                    stream_titles,
+                ),
+                commands::tracking::AccessOptions {
+                    max_reads_per_sec: args.max_reads_per_sec,
+                    audit_log: args.audit_log,
+                    unlock_reads: !args.no_unlock_reads,
+                    song_db_rescan: !args.no_song_rescan,
+                },
+                commands::tracking::ApiOptions {
+                    endpoint: args.api_endpoint,
+                    token: args.api_token,
+                    consent: args.api_consent,
+                    preview_payload: args.preview_payload,
+                    batch_size: Some(args.api_batch_size as usize),
+                    flush_interval: Some(Duration::from_secs(args.api_flush_interval)),
+                },
+            )
+        }
+    }
+}
+
+/// Path given on the command line, or its default in the data directory
+fn path_or(arg: Option<String>, default: PathBuf) -> PathBuf {
+    arg.map_or(default, PathBuf::from)
+}
+
+/// Map `--sp` / `--dp` flags to a play style restriction
+fn play_style(sp: bool, dp: bool) -> Option<PlayStyle> {
+    match (sp, dp) {
+        (true, _) => Some(PlayStyle::Sp),
+        (_, true) => Some(PlayStyle::Dp),
+        _ => None,
     }
 }
 
-fn init_logging() {
-    let env_filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("infst_cli=warn,infst=warn"));
-    tracing_subscriber::fmt().with_env_filter(env_filter).init();
+/// Build the stream overlay config from CLI args (enabled by `--stream-dir`)
+fn stream_config(
+    dir: Option<String>,
+    template: Option<String>,
+    idle_text: Option<String>,
+    result_card: bool,
+    recent_plays: usize,
+    encoding: infst::TextEncoding,
+    titles: infst::TitleOptions,
+) -> Option<infst::StreamConfig> {
+    let default = infst::StreamConfig::default();
+    Some(infst::StreamConfig {
+        dir: dir?.into(),
+        marquee_template: template.unwrap_or(default.marquee_template),
+        idle_text: idle_text.unwrap_or(default.idle_text),
+        result_card,
+        recent_plays,
+        encoding,
+        titles,
+    })
 }
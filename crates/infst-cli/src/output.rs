@@ -0,0 +1,175 @@
+//! Output mode (`--output text|json`).
+//!
+//! In JSON mode every command writes exactly one result object to stdout:
+//!
+//! ```json
+//! { "command": "scan", "ok": true, "result": { ... } }
+//! { "command": "scan", "ok": false, "error": { "message": "...", "exitCode": 10, ... } }
+//! ```
+//!
+//! Human-readable progress text printed with [`outln!`] goes to stderr in
+//! JSON mode so stdout stays machine-readable.
+
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::cli_utils;
+use crate::exit_code;
+
+/// Output format for command results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+static COMMAND: OnceLock<&'static str> = OnceLock::new();
+static EMITTED: AtomicBool = AtomicBool::new(false);
+
+/// Set the output format and the name of the running command (once at startup)
+pub fn init(format: OutputFormat, command: &'static str) {
+    let _ = FORMAT.set(format);
+    let _ = COMMAND.set(command);
+}
+
+pub fn is_json() -> bool {
+    FORMAT.get().copied().unwrap_or_default() == OutputFormat::Json
+}
+
+fn command() -> &'static str {
+    COMMAND.get().copied().unwrap_or("tracking")
+}
+
+/// Print a line of human-readable output (stderr in JSON mode)
+macro_rules! outln {
+    () => {
+        if $crate::output::is_json() {
+            eprintln!();
+        } else {
+            println!();
+        }
+    };
+    ($($arg:tt)*) => {
+        if $crate::output::is_json() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+pub(crate) use outln;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelope<'a, T: Serialize> {
+    command: &'a str,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorObject>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorObject {
+    message: String,
+    exit_code: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hint: Option<&'static str>,
+    retryable: bool,
+}
+
+fn print_envelope<T: Serialize>(envelope: &Envelope<'_, T>) {
+    EMITTED.store(true, Ordering::SeqCst);
+    match serde_json::to_string_pretty(envelope) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("Failed to serialize result: {}", e),
+    }
+}
+
+/// Emit the command's structured result (JSON mode only)
+pub fn emit<T: Serialize>(result: &T) {
+    if !is_json() {
+        return;
+    }
+    print_envelope(&Envelope {
+        command: command(),
+        ok: true,
+        result: Some(result),
+        error: None::<ErrorObject>,
+    });
+}
+
+/// Emit a result-less success object if the command did not emit one
+pub fn finish_ok() {
+    if is_json() && !EMITTED.load(Ordering::SeqCst) {
+        print_envelope(&Envelope {
+            command: command(),
+            ok: true,
+            result: None::<()>,
+            error: None,
+        });
+    }
+}
+
+/// Emit an error object (JSON mode only)
+pub fn emit_error(error: &anyhow::Error) {
+    let core = cli_utils::core_error(error);
+    print_envelope(&Envelope {
+        command: command(),
+        ok: false,
+        result: None::<()>,
+        error: Some(ErrorObject {
+            message: format!("{:#}", error),
+            exit_code: exit_code::for_error(error),
+            category: core.map(|e| e.category().to_string()),
+            hint: core.and_then(infst::Error::hint),
+            retryable: core.is_some_and(infst::Error::is_retryable),
+        }),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_shape() {
+        let ok = serde_json::to_value(Envelope {
+            command: "scan",
+            ok: true,
+            result: Some(serde_json::json!({ "songsFound": 3 })),
+            error: None,
+        })
+        .unwrap();
+        assert_eq!(
+            ok,
+            serde_json::json!({ "command": "scan", "ok": true, "result": { "songsFound": 3 } })
+        );
+
+        let err = serde_json::to_value(Envelope {
+            command: "status",
+            ok: false,
+            result: None::<()>,
+            error: Some(ErrorObject {
+                message: "boom".to_string(),
+                exit_code: exit_code::GENERAL_ERROR,
+                category: None,
+                hint: None,
+                retryable: false,
+            }),
+        })
+        .unwrap();
+        assert_eq!(err["error"]["exitCode"], 1);
+        assert!(err.get("result").is_none());
+        assert!(err["error"].get("category").is_none());
+    }
+}
@@ -3,10 +3,11 @@
 use std::collections::HashMap;
 
 use anyhow::{Result, bail};
+use infst::chart::DEFAULT_ANCHOR_SONGS;
 use infst::config::database;
 use infst::{
-    MemoryReader, OffsetSearcher, OffsetsCollection, SongInfo, builtin_signatures,
-    fetch_song_database,
+    GameLayout, MemoryReader, OffsetSearcher, OffsetsCollection, RetryPolicy, SongInfo,
+    check_anchor_songs, fetch_song_database,
 };
 use tracing::{debug, info, warn};
 
@@ -15,11 +16,14 @@ use crate::validation::{ValidationResult, validate_song_database};
 
 /// Load song database with retry logic.
 ///
-/// Waits for the game to fully populate the song database before returning.
-/// Returns `Ok(None)` if shutdown was signaled.
+/// Waits for the game to fully populate the song database before returning,
+/// retrying under `policy`. Returns `Ok(None)` if shutdown was signaled. Errors that retrying cannot
+/// fix (see [`infst::Error::is_retryable`]) are returned immediately, as is
+/// a populated database whose anchor song titles show layout drift.
 pub fn load_song_database_with_retry(
     reader: &MemoryReader,
     song_list: u64,
+    policy: &RetryPolicy,
     shutdown: &ShutdownSignal,
 ) -> Result<Option<HashMap<u32, SongInfo>>> {
     let mut attempts = 0u32;
@@ -30,14 +34,18 @@ pub fn load_song_database_with_retry(
             return Ok(None);
         }
 
-        if attempts >= database::MAX_LOAD_ATTEMPTS {
+        if !policy.allows_attempt(attempts) {
             bail!(
                 "Failed to load song database after {} attempts: {}",
-                database::MAX_LOAD_ATTEMPTS,
+                attempts,
                 last_error.unwrap_or_else(|| "unknown error".to_string())
             );
         }
         attempts += 1;
+        let delay = policy.delay_after(attempts - 1);
+        let max_attempts = policy
+            .max_attempts
+            .map_or_else(|| "-".to_string(), |n| n.to_string());
 
         // Wait for data initialization on retry only (interruptible)
         if attempts > 1 && shutdown.wait(database::EXTRA_DELAY) {
@@ -46,75 +54,75 @@ pub fn load_song_database_with_retry(
 
         match fetch_song_database(reader, song_list) {
             Ok(db) => match validate_song_database(&db) {
-                ValidationResult::Valid => return Ok(Some(db)),
+                ValidationResult::Valid => {
+                    check_anchor_songs(&db, DEFAULT_ANCHOR_SONGS).ensure_no_drift()?;
+                    return Ok(Some(db));
+                }
                 ValidationResult::TooFewSongs(count) => {
                     last_error = Some(format!("song list too small ({})", count));
                     warn!(
-                        "Song list not fully populated ({} songs), retrying in {}s (attempt {}/{})",
-                        count,
-                        database::RETRY_DELAY.as_secs(),
-                        attempts,
-                        database::MAX_LOAD_ATTEMPTS
+                        "Song list not fully populated ({} songs), retrying in {:?} (attempt {}/{})",
+                        count, delay, attempts, max_attempts
                     );
                 }
                 ValidationResult::NotecountTooSmall(notes) => {
                     last_error = Some(format!("notecount too small ({})", notes));
                     warn!(
-                        "Song data not fully loaded (reference song notecount: {}), retrying in {}s (attempt {}/{})",
-                        notes,
-                        database::RETRY_DELAY.as_secs(),
-                        attempts,
-                        database::MAX_LOAD_ATTEMPTS
+                        "Song data not fully loaded (reference song notecount: {}), retrying in {:?} (attempt {}/{})",
+                        notes, delay, attempts, max_attempts
                     );
                 }
                 ValidationResult::ReferenceSongMissing => {
                     last_error = Some("reference song missing".to_string());
                     warn!(
-                        "Reference song not yet loaded, retrying in {}s (attempt {}/{})",
-                        database::RETRY_DELAY.as_secs(),
-                        attempts,
-                        database::MAX_LOAD_ATTEMPTS
+                        "Reference song not yet loaded, retrying in {:?} (attempt {}/{})",
+                        delay, attempts, max_attempts
                     );
                 }
             },
+            Err(e) if !e.is_retryable() => return Err(e.into()),
             Err(e) => {
                 last_error = Some(e.to_string());
                 debug!(
-                    "Error loading song database: {}. Retrying in {}s (attempt {}/{})",
-                    e,
-                    database::RETRY_DELAY.as_secs(),
-                    attempts,
-                    database::MAX_LOAD_ATTEMPTS
+                    "Error loading song database: {}. Retrying in {:?} (attempt {}/{})",
+                    e, delay, attempts, max_attempts
                 );
             }
         }
 
         // Wait before retry (interruptible)
-        if shutdown.wait(database::RETRY_DELAY) {
+        if shutdown.wait(delay) {
             return Ok(None);
         }
     }
 }
 
-/// Search for offsets with retry logic.
+/// Search for offsets with retry logic, using the signatures and relative
+/// offsets of `layout`, retrying under `policy`.
 ///
-/// Returns `Ok(None)` if shutdown was signaled.
+/// Returns `Ok(None)` if shutdown was signaled. Errors that retrying cannot
+/// fix are returned immediately, as is the last failure once `policy` runs
+/// out of attempts.
 pub fn search_offsets_with_retry(
     reader: &MemoryReader,
     game_version: Option<&String>,
+    layout: &GameLayout,
+    policy: &RetryPolicy,
     shutdown: &ShutdownSignal,
 ) -> Result<Option<OffsetsCollection>> {
-    let signatures = builtin_signatures();
-
+    let mut attempts = 0u32;
     loop {
         // Check for shutdown signal
         if shutdown.is_shutdown() {
             return Ok(None);
         }
+        attempts += 1;
+        let delay = policy.delay_after(attempts - 1);
+        let give_up = !policy.allows_attempt(attempts);
 
-        let mut searcher = OffsetSearcher::new(reader);
+        let mut searcher = OffsetSearcher::builder(reader).with_layout(layout).build();
 
-        match searcher.search_all_with_signatures(&signatures) {
+        match searcher.search_all_with_signatures(&layout.signatures) {
             Ok(mut offsets) => {
                 if let Some(version) = game_version {
                     offsets.version = version.clone();
@@ -126,23 +134,24 @@ pub fn search_offsets_with_retry(
                 if offsets.is_valid() {
                     return Ok(Some(offsets));
                 }
+                if give_up {
+                    bail!("Offset detection incomplete after {} attempts", attempts);
+                }
 
-                info!(
-                    "Offset detection incomplete, retrying in {}s...",
-                    database::RETRY_DELAY.as_secs()
-                );
+                info!("Offset detection incomplete, retrying in {:?}...", delay);
             }
+            Err(e) if !e.is_retryable() => return Err(e.into()),
+            Err(e) if give_up => return Err(e.into()),
             Err(e) => {
                 info!(
-                    "Offset detection failed ({}), retrying in {}s...",
-                    e,
-                    database::RETRY_DELAY.as_secs()
+                    "Offset detection failed ({}), retrying in {:?}...",
+                    e, delay
                 );
             }
         }
 
         // Wait before retry (interruptible)
-        if shutdown.wait(database::RETRY_DELAY) {
+        if shutdown.wait(delay) {
             return Ok(None);
         }
     }
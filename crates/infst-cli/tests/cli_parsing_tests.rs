@@ -4,6 +4,7 @@
 //! without actually executing the commands (which would require the game process).
 
 use clap::Parser;
+use tracing_subscriber::filter::LevelFilter;
 
 // Re-create Args structure for testing since it's not publicly exported
 #[derive(Parser)]
@@ -12,6 +13,188 @@ struct Args {
     #[arg(long, value_name = "FILE")]
     offsets_file: Option<String>,
 
+    #[arg(long, value_name = "FILE")]
+    bpi_file: Option<String>,
+
+    #[arg(long, value_name = "DIR")]
+    stream_dir: Option<String>,
+
+    #[arg(long, value_name = "TEMPLATE")]
+    marquee_template: Option<String>,
+
+    #[arg(long, value_name = "TEMPLATE")]
+    clipboard_template: Option<String>,
+
+    #[arg(long, value_name = "TEXT")]
+    marquee_idle_text: Option<String>,
+
+    #[arg(long)]
+    result_card: bool,
+
+    #[arg(long, value_name = "N", default_value_t = infst::stream::DEFAULT_RECENT_PLAYS)]
+    recent_plays: usize,
+
+    #[arg(long, value_name = "ENCODING", default_value = "utf-8")]
+    stream_encoding: infst::TextEncoding,
+
+    #[arg(long, value_name = "LANG", default_value = "original")]
+    stream_title: infst::TitleLanguage,
+
+    #[arg(long, value_name = "LANG")]
+    stream_title2: Option<infst::TitleLanguage>,
+
+    #[arg(long, value_name = "FILE")]
+    title_aliases: Option<String>,
+
+    #[arg(long, value_name = "FILE")]
+    ignore_file: Option<String>,
+
+    #[arg(long, value_name = "ENCODING", default_value = "utf-8")]
+    tsv_encoding: infst::TextEncoding,
+
+    #[arg(long)]
+    tracker_snapshots: bool,
+
+    #[arg(long)]
+    summary_image: bool,
+
+    #[arg(long)]
+    dry_run: bool,
+
+    #[arg(long)]
+    restart_on_panic: bool,
+
+    #[arg(long, value_name = "OP=POLICY")]
+    retry: Vec<infst::RetryOverride>,
+
+    #[arg(long, value_name = "JOB")]
+    schedule: Vec<infst::ScheduledExport>,
+
+    #[arg(long, value_name = "FILE")]
+    tournament: Option<String>,
+
+    #[arg(long)]
+    ignore_paused_plays: bool,
+
+    #[arg(long)]
+    drop_anomalies: bool,
+
+    #[arg(long, value_enum, value_name = "MODE", default_value_t = PrematurePolicy::Flag)]
+    premature: PrematurePolicy,
+
+    #[arg(long, value_name = "SECS", default_value_t = infst::play::DEFAULT_DUPLICATE_WINDOW_SECS)]
+    duplicate_window: u64,
+
+    #[arg(long, value_name = "N")]
+    green_number_alert: Option<u16>,
+
+    #[arg(long, value_name = "BINDING")]
+    hotkey: Vec<infst::input::hotkeys::HotkeyBinding>,
+
+    #[arg(long, conflicts_with = "hotkey")]
+    no_hotkeys: bool,
+
+    #[arg(long)]
+    force: bool,
+
+    #[arg(long, value_name = "N")]
+    max_reads_per_sec: Option<u32>,
+
+    #[arg(long, value_name = "FILE")]
+    audit_log: Option<String>,
+
+    #[arg(long)]
+    no_unlock_reads: bool,
+
+    #[arg(long)]
+    no_song_rescan: bool,
+
+    #[arg(long, value_name = "TIME", value_parser = infst::clock::parse_time)]
+    fake_time: Option<chrono::DateTime<chrono::Utc>>,
+
+    #[arg(long, value_name = "TZ", default_value = "local")]
+    timezone: infst::DisplayTimezone,
+
+    #[arg(long)]
+    api_consent: bool,
+
+    #[arg(long, value_name = "FILE")]
+    preview_payload: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = infst::config::submission::BATCH_SIZE as u32,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    api_batch_size: u32,
+
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = infst::config::submission::FLUSH_INTERVAL.as_secs()
+    )]
+    api_flush_interval: u64,
+
+    #[arg(long, value_name = "URL", requires = "push_on")]
+    push_url: Option<String>,
+
+    #[arg(long, value_name = "RULE", requires = "push_url")]
+    push_on: Vec<infst::NotifyRule>,
+
+    #[arg(long, value_name = "TEMPLATE", default_value = infst::notify::DEFAULT_PUSH_TEMPLATE)]
+    push_template: String,
+
+    #[arg(long, value_name = "TYPE", default_value = infst::notify::DEFAULT_PUSH_CONTENT_TYPE)]
+    push_content_type: String,
+
+    #[arg(long, value_name = "HEADER", value_parser = infst::notify::parse_header)]
+    push_header: Vec<(String, String)>,
+
+    #[arg(long, value_name = "RULE", global = true)]
+    process_select: Option<infst::ProcessSelection>,
+
+    #[arg(long, value_name = "NAME", global = true)]
+    process_name: Option<String>,
+
+    #[arg(long, value_name = "REGEX", global = true)]
+    process_title: Option<String>,
+
+    #[arg(long, value_name = "PATH", global = true)]
+    process_path: Option<String>,
+
+    #[arg(long, global = true)]
+    portable: bool,
+
+    #[arg(long, value_name = "DIR", global = true, conflicts_with = "portable")]
+    data_dir: Option<String>,
+
+    #[arg(long, value_name = "NAME", global = true)]
+    profile: Option<String>,
+
+    #[arg(long, global = true)]
+    relaunch_as_admin: bool,
+
+    #[arg(long, value_name = "DIR", global = true)]
+    log_dir: Option<String>,
+
+    #[arg(long, value_name = "LEVEL", default_value = "info", global = true)]
+    log_level: LevelFilter,
+
+    #[arg(long, value_name = "LEVEL", global = true)]
+    console_log_level: Option<LevelFilter>,
+
+    #[arg(long, value_name = "MB", default_value_t = 10, global = true)]
+    log_max_size: u64,
+
+    #[arg(
+        long = "output",
+        value_name = "FORMAT",
+        value_enum,
+        default_value = "text"
+    )]
+    output_format: OutputFormat,
+
     #[command(subcommand)]
     command: Option<Command>,
 }
@@ -19,8 +202,8 @@ struct Args {
 #[derive(clap::Subcommand)]
 enum Command {
     FindOffsets {
-        #[arg(short, long, default_value = "offsets.txt")]
-        output: String,
+        #[arg(short, long)]
+        output: Option<String>,
         #[arg(long)]
         pid: Option<u32>,
     },
@@ -39,6 +222,41 @@ enum Command {
         size: usize,
         #[arg(long)]
         ascii: bool,
+        #[arg(long, num_args = 0..=1, default_missing_value = "1")]
+        deref: Option<usize>,
+        #[arg(long)]
+        annotate: bool,
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    Fixture {
+        input: String,
+        #[arg(long, default_value = "0x1000")]
+        base: String,
+        #[arg(long)]
+        address: Option<String>,
+        #[arg(long)]
+        size: Option<usize>,
+        #[arg(long)]
+        mask: Vec<String>,
+        #[arg(long)]
+        comment: Vec<String>,
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    DiscoverLayout {
+        #[arg(long)]
+        address: String,
+        #[arg(long, default_value = "1048576")]
+        size: usize,
+        #[arg(long)]
+        dump: Option<String>,
+        #[arg(long, default_value = "0x1000")]
+        base: String,
+        #[arg(long)]
+        since: Option<String>,
+        #[arg(short, long)]
+        output: Option<String>,
         #[arg(long)]
         pid: Option<u32>,
     },
@@ -55,6 +273,133 @@ enum Command {
         format: ExportFormat,
         #[arg(long)]
         pid: Option<u32>,
+        #[arg(long)]
+        analytics: Option<String>,
+        #[arg(long)]
+        min_level: Option<u8>,
+        #[arg(long)]
+        max_level: Option<u8>,
+        #[arg(long, conflicts_with = "dp")]
+        sp: bool,
+        #[arg(long)]
+        dp: bool,
+        #[arg(long)]
+        played_only: bool,
+        #[arg(long)]
+        unlocked_only: bool,
+        #[arg(long, value_delimiter = ',')]
+        folder: Vec<i32>,
+        #[arg(long, value_name = "TAG")]
+        tag: Vec<String>,
+        #[arg(long, value_name = "FILE")]
+        tags_file: Option<String>,
+        #[arg(long, value_name = "LANG", default_value = "original")]
+        title: infst::TitleLanguage,
+        #[arg(long, value_name = "LANG")]
+        title2: Option<infst::TitleLanguage>,
+        #[arg(long, value_name = "FILE")]
+        title_aliases: Option<String>,
+        #[arg(long, value_name = "FILE")]
+        chart_density: Option<String>,
+        #[arg(long, value_name = "FILE")]
+        ignore_file: Option<String>,
+        #[arg(long, required_if_eq("format", "beatoraja"))]
+        hash_map: Option<String>,
+        #[arg(long, value_name = "ENCODING", default_value = "utf-8")]
+        encoding: infst::TextEncoding,
+        #[arg(long)]
+        sign_key: Option<String>,
+    },
+    Verify {
+        file: String,
+        #[arg(long)]
+        key: String,
+    },
+    Diff {
+        #[arg(long)]
+        old: String,
+        #[arg(long)]
+        new: Option<String>,
+        #[arg(long, default_value_t = infst::export::DEFAULT_MIN_SCORE_DELTA)]
+        min_score_delta: u32,
+    },
+    Scores {
+        #[command(subcommand)]
+        action: ScoresAction,
+    },
+    Tracker {
+        #[command(subcommand)]
+        action: TrackerAction,
+        #[arg(long)]
+        session_dir: Option<String>,
+    },
+    TrackerDiff {
+        old: String,
+        new: String,
+        #[arg(long)]
+        json: bool,
+    },
+    Merge {
+        a: String,
+        b: String,
+        #[arg(short, long)]
+        output: String,
+        #[arg(long, default_value = "utf-8")]
+        encoding: infst::TextEncoding,
+        #[arg(long)]
+        json: bool,
+    },
+    Unlocks {
+        #[arg(long)]
+        plan: bool,
+        #[arg(long)]
+        budget: Option<u32>,
+        #[arg(long)]
+        min_level: Option<u8>,
+        #[arg(long, conflicts_with = "dp")]
+        sp: bool,
+        #[arg(long)]
+        dp: bool,
+        #[arg(long, value_name = "FILE")]
+        custom_types: Option<String>,
+        #[arg(long, value_name = "FILE")]
+        offsets_file: Option<String>,
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    Songs {
+        #[arg(long, value_name = "TEXT")]
+        search: Option<String>,
+        #[arg(long, conflicts_with_all = ["min_level", "max_level"])]
+        level: Option<u8>,
+        #[arg(long)]
+        min_level: Option<u8>,
+        #[arg(long)]
+        max_level: Option<u8>,
+        #[arg(long, conflicts_with = "dp")]
+        sp: bool,
+        #[arg(long)]
+        dp: bool,
+        #[arg(long, value_name = "FILE")]
+        from: Option<String>,
+        #[arg(long, conflicts_with = "from")]
+        pid: Option<u32>,
+    },
+    Recommend {
+        #[arg(long, default_value = "100")]
+        target_djp: f64,
+        #[arg(long)]
+        max_level: Option<u8>,
+        #[arg(long)]
+        dp: bool,
+        #[arg(long, value_name = "TAG")]
+        tag: Vec<String>,
+        #[arg(long, value_name = "FILE")]
+        tags_file: Option<String>,
+        #[arg(long, value_name = "FILE")]
+        chart_density: Option<String>,
+        #[arg(long)]
+        pid: Option<u32>,
     },
     Launch {
         #[arg(long)]
@@ -65,12 +410,131 @@ enum Command {
         timeout: u64,
     },
     Register,
+    Selftest {
+        #[arg(long)]
+        offsets_file: Option<String>,
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    Doctor {
+        #[arg(long)]
+        pid: Option<u32>,
+        #[arg(long)]
+        session_dir: Option<String>,
+    },
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    Man {
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+    StructDiff {
+        #[arg(long)]
+        layout: String,
+        #[arg(long)]
+        a: String,
+        #[arg(long)]
+        b: String,
+        #[arg(long)]
+        size: Option<usize>,
+        #[arg(long)]
+        changed: bool,
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    Watch {
+        #[arg(long, required = true)]
+        address: Vec<String>,
+        #[arg(long = "type", value_enum, default_value = "i32")]
+        value_type: WatchType,
+        #[arg(long, default_value = "100ms")]
+        interval: String,
+        #[arg(long)]
+        csv: bool,
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    Session {
+        #[command(subcommand)]
+        action: SessionAction,
+        #[arg(long)]
+        session_dir: Option<String>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq, clap::Subcommand)]
+enum TrackerAction {
+    Audit,
+}
+
+#[derive(Debug, PartialEq, Eq, clap::Subcommand)]
+enum ScoresAction {
+    Snapshot {
+        #[arg(long, short)]
+        output: String,
+        #[arg(long)]
+        pid: Option<u32>,
+    },
+    Diff {
+        #[arg(long)]
+        before: String,
+        #[arg(long)]
+        after: String,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq, clap::Subcommand)]
+enum SessionAction {
+    Pause,
+    Resume,
+    Note { text: String },
+    Copy,
+    Export,
+    Marquee,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum WatchType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    F32,
+    F64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum PrematurePolicy {
+    Flag,
+    Ignore,
+    JudgeOnly,
 }
 
 #[derive(Clone, clap::ValueEnum)]
 enum ExportFormat {
     Tsv,
     Json,
+    Beatoraja,
 }
 
 #[test]
@@ -85,7 +549,7 @@ fn test_parse_find_offsets() {
     let args = Args::try_parse_from(["infst", "find-offsets"]).unwrap();
     match args.command {
         Some(Command::FindOffsets { output, pid }) => {
-            assert_eq!(output, "offsets.txt");
+            assert!(output.is_none());
             assert!(pid.is_none());
         }
         _ => panic!("Expected FindOffsets command"),
@@ -97,7 +561,7 @@ fn test_parse_find_offsets_with_output() {
     let args = Args::try_parse_from(["infst", "find-offsets", "-o", "custom.txt"]).unwrap();
     match args.command {
         Some(Command::FindOffsets { output, .. }) => {
-            assert_eq!(output, "custom.txt");
+            assert_eq!(output.as_deref(), Some("custom.txt"));
         }
         _ => panic!("Expected FindOffsets command"),
     }
@@ -145,6 +609,90 @@ fn test_parse_hexdump_with_ascii() {
     }
 }
 
+#[test]
+fn test_parse_hexdump_deref() {
+    let args =
+        Args::try_parse_from(["infst", "hexdump", "--address", "0x1000", "--deref"]).unwrap();
+    match args.command {
+        Some(Command::Hexdump { deref, .. }) => assert_eq!(deref, Some(1)),
+        _ => panic!("Expected Hexdump command"),
+    }
+
+    // Offsets for annotations come from the global flag
+    let args = Args::try_parse_from([
+        "infst",
+        "--offsets-file",
+        "offsets.txt",
+        "hexdump",
+        "--address",
+        "0x1000",
+        "--deref",
+        "3",
+    ])
+    .unwrap();
+    assert_eq!(args.offsets_file, Some("offsets.txt".to_string()));
+    match args.command {
+        Some(Command::Hexdump {
+            deref, annotate, ..
+        }) => {
+            assert_eq!(deref, Some(3));
+            assert!(!annotate);
+        }
+        _ => panic!("Expected Hexdump command"),
+    }
+    assert!(
+        Args::try_parse_from([
+            "infst",
+            "hexdump",
+            "--address",
+            "0x1000",
+            "--offsets-file",
+            "offsets.txt",
+        ])
+        .is_err()
+    );
+}
+
+#[test]
+fn test_parse_fixture() {
+    let args = Args::try_parse_from([
+        "infst",
+        "fixture",
+        "dump.bin",
+        "--address",
+        "0x1431B08A0",
+        "--size",
+        "4800",
+        "--mask",
+        "0x1431B08A0:64",
+        "--mask",
+        "0x1431B08E0:64",
+        "-o",
+        "songs.fixture",
+    ])
+    .unwrap();
+    match args.command {
+        Some(Command::Fixture {
+            input,
+            base,
+            address,
+            size,
+            mask,
+            comment,
+            output,
+        }) => {
+            assert_eq!(input, "dump.bin");
+            assert_eq!(base, "0x1000");
+            assert_eq!(address.as_deref(), Some("0x1431B08A0"));
+            assert_eq!(size, Some(4800));
+            assert_eq!(mask.len(), 2);
+            assert!(comment.is_empty());
+            assert_eq!(output.as_deref(), Some("songs.fixture"));
+        }
+        _ => panic!("Expected Fixture command"),
+    }
+}
+
 #[test]
 fn test_parse_offset() {
     let args =
@@ -184,61 +732,1241 @@ fn test_parse_export_json_format() {
 }
 
 #[test]
-fn test_parse_global_offsets_file() {
-    let args = Args::try_parse_from(["infst", "--offsets-file", "my-offsets.txt"]).unwrap();
-    assert_eq!(args.offsets_file, Some("my-offsets.txt".to_string()));
+fn test_parse_export_analytics() {
+    let args = Args::try_parse_from(["infst", "export", "--analytics", "sessions"]).unwrap();
+    match args.command {
+        Some(Command::Export { analytics, .. }) => {
+            assert_eq!(analytics, Some("sessions".to_string()));
+        }
+        _ => panic!("Expected Export command"),
+    }
 }
 
 #[test]
-fn test_parse_with_pid() {
-    let args = Args::try_parse_from(["infst", "status", "--pid", "12345"]).unwrap();
+fn test_parse_recommend() {
+    let args = Args::try_parse_from([
+        "infst",
+        "recommend",
+        "--target-djp",
+        "50",
+        "--max-level",
+        "11",
+    ])
+    .unwrap();
     match args.command {
-        Some(Command::Status { pid, .. }) => {
-            assert_eq!(pid, Some(12345));
+        Some(Command::Recommend {
+            target_djp,
+            max_level,
+            dp,
+            ..
+        }) => {
+            assert!((target_djp - 50.0).abs() < f64::EPSILON);
+            assert_eq!(max_level, Some(11));
+            assert!(!dp);
         }
-        _ => panic!("Expected Status command"),
+        _ => panic!("Expected Recommend command"),
     }
 }
 
 #[test]
-fn test_invalid_command_fails() {
-    let result = Args::try_parse_from(["infst", "invalid-command"]);
-    assert!(result.is_err());
+fn test_parse_global_bpi_file() {
+    let args = Args::try_parse_from(["infst", "--bpi-file", "bpi.json"]).unwrap();
+    assert_eq!(args.bpi_file, Some("bpi.json".to_string()));
+    assert!(args.command.is_none());
 }
 
 #[test]
-fn test_missing_required_arg_fails() {
-    // hexdump requires --address
-    let result = Args::try_parse_from(["infst", "hexdump"]);
-    assert!(result.is_err());
+fn test_parse_global_stream_options() {
+    let args = Args::try_parse_from([
+        "infst",
+        "--stream-dir",
+        "obs",
+        "--marquee-template",
+        "{title} [{diff}]",
+        "--marquee-idle-text",
+        "Selecting...",
+        "--result-card",
+        "--recent-plays",
+        "5",
+    ])
+    .unwrap();
+    assert!(args.result_card);
+    assert_eq!(args.recent_plays, 5);
+    assert_eq!(args.stream_dir, Some("obs".to_string()));
+    assert_eq!(args.marquee_template, Some("{title} [{diff}]".to_string()));
+    assert_eq!(args.marquee_idle_text, Some("Selecting...".to_string()));
 }
 
 #[test]
-fn test_parse_launch() {
-    let args = Args::try_parse_from(["infst", "launch"]).unwrap();
+fn test_parse_export_filters() {
+    let args = Args::try_parse_from([
+        "infst",
+        "export",
+        "--min-level",
+        "11",
+        "--max-level",
+        "12",
+        "--sp",
+        "--played-only",
+        "--unlocked-only",
+        "--folder",
+        "30,31",
+    ])
+    .unwrap();
     match args.command {
-        Some(Command::Launch { url, pid, timeout }) => {
-            assert!(url.is_none());
-            assert!(pid.is_none());
-            assert_eq!(timeout, 120);
+        Some(Command::Export {
+            min_level,
+            max_level,
+            sp,
+            dp,
+            played_only,
+            unlocked_only,
+            folder,
+            ..
+        }) => {
+            assert_eq!(min_level, Some(11));
+            assert_eq!(max_level, Some(12));
+            assert!(sp);
+            assert!(!dp);
+            assert!(played_only);
+            assert!(unlocked_only);
+            assert_eq!(folder, vec![30, 31]);
         }
-        _ => panic!("Expected Launch command"),
+        _ => panic!("Expected Export command"),
     }
 }
 
 #[test]
-fn test_parse_launch_with_url() {
-    let args = Args::try_parse_from(["infst", "launch", "--url", "bm2dxinf://test"]).unwrap();
+fn test_parse_tags() {
+    let args = Args::try_parse_from([
+        "infst",
+        "export",
+        "--tag",
+        "scratch heavy",
+        "--tag",
+        "speed practice",
+        "--tags-file",
+        "my-tags.json",
+    ])
+    .unwrap();
     match args.command {
-        Some(Command::Launch { url, .. }) => {
-            assert_eq!(url, Some("bm2dxinf://test".to_string()));
+        Some(Command::Export { tag, tags_file, .. }) => {
+            assert_eq!(tag, vec!["scratch heavy", "speed practice"]);
+            assert_eq!(tags_file.as_deref(), Some("my-tags.json"));
         }
-        _ => panic!("Expected Launch command"),
+        _ => panic!("Expected Export command"),
+    }
+
+    let args = Args::try_parse_from(["infst", "recommend", "--tag", "scratch heavy"]).unwrap();
+    match args.command {
+        Some(Command::Recommend { tag, tags_file, .. }) => {
+            assert_eq!(tag, vec!["scratch heavy"]);
+            assert_eq!(tags_file, None);
+        }
+        _ => panic!("Expected Recommend command"),
     }
 }
 
 #[test]
-fn test_parse_register() {
-    let args = Args::try_parse_from(["infst", "register"]).unwrap();
-    assert!(matches!(args.command, Some(Command::Register)));
+fn test_parse_export_beatoraja() {
+    let args = Args::try_parse_from([
+        "infst",
+        "export",
+        "-f",
+        "beatoraja",
+        "--hash-map",
+        "hashes.json",
+    ])
+    .unwrap();
+    match args.command {
+        Some(Command::Export {
+            format, hash_map, ..
+        }) => {
+            assert!(matches!(format, ExportFormat::Beatoraja));
+            assert_eq!(hash_map, Some("hashes.json".to_string()));
+        }
+        _ => panic!("Expected Export command"),
+    }
+
+    // Mapping file is required for the beatoraja format
+    assert!(Args::try_parse_from(["infst", "export", "-f", "beatoraja"]).is_err());
+}
+
+#[test]
+fn test_parse_output_encodings() {
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert_eq!(args.stream_encoding, infst::TextEncoding::Utf8);
+    assert_eq!(args.tsv_encoding, infst::TextEncoding::Utf8);
+
+    let args = Args::try_parse_from([
+        "infst",
+        "--stream-encoding",
+        "shift-jis",
+        "--tsv-encoding",
+        "utf-8-bom",
+    ])
+    .unwrap();
+    assert_eq!(args.stream_encoding, infst::TextEncoding::ShiftJis);
+    assert_eq!(args.tsv_encoding, infst::TextEncoding::Utf8Bom);
+
+    let args = Args::try_parse_from(["infst", "export", "--encoding", "sjis"]).unwrap();
+    match args.command {
+        Some(Command::Export { encoding, .. }) => {
+            assert_eq!(encoding, infst::TextEncoding::ShiftJis)
+        }
+        _ => panic!("Expected Export command"),
+    }
+
+    assert!(Args::try_parse_from(["infst", "--tsv-encoding", "latin1"]).is_err());
+}
+
+#[test]
+fn test_parse_export_sp_dp_conflict() {
+    assert!(Args::try_parse_from(["infst", "export", "--sp", "--dp"]).is_err());
+}
+
+#[test]
+fn test_parse_unlocks_plan() {
+    let args = Args::try_parse_from([
+        "infst",
+        "unlocks",
+        "--plan",
+        "--budget",
+        "50000",
+        "--min-level",
+        "12",
+        "--sp",
+        "--custom-types",
+        "customtypes.txt",
+    ])
+    .unwrap();
+    match args.command {
+        Some(Command::Unlocks {
+            plan,
+            budget,
+            min_level,
+            sp,
+            custom_types,
+            ..
+        }) => {
+            assert!(plan);
+            assert_eq!(budget, Some(50000));
+            assert_eq!(min_level, Some(12));
+            assert!(sp);
+            assert_eq!(custom_types, Some("customtypes.txt".to_string()));
+        }
+        _ => panic!("Expected Unlocks command"),
+    }
+}
+
+#[test]
+fn test_parse_tracker_diff() {
+    let args = Args::try_parse_from([
+        "infst",
+        "--tracker-snapshots",
+        "tracker-diff",
+        "tracker-2025-06-01.tsv",
+        "tracker-2025-06-08.tsv",
+    ])
+    .unwrap();
+    assert!(args.tracker_snapshots);
+    match args.command {
+        Some(Command::TrackerDiff { old, new, json }) => {
+            assert_eq!(old, "tracker-2025-06-01.tsv");
+            assert_eq!(new, "tracker-2025-06-08.tsv");
+            assert!(!json);
+        }
+        _ => panic!("Expected TrackerDiff command"),
+    }
+}
+
+#[test]
+fn test_parse_diff() {
+    let args = Args::try_parse_from(["infst", "diff", "--old", "tracker-old.tsv"]).unwrap();
+    match args.command {
+        Some(Command::Diff {
+            old,
+            new,
+            min_score_delta,
+        }) => {
+            assert_eq!(old, "tracker-old.tsv");
+            assert!(new.is_none());
+            assert_eq!(min_score_delta, 10);
+        }
+        _ => panic!("Expected Diff command"),
+    }
+
+    let args = Args::try_parse_from([
+        "infst",
+        "diff",
+        "--old",
+        "a.tsv",
+        "--new",
+        "b.tsv",
+        "--min-score-delta",
+        "50",
+    ])
+    .unwrap();
+    assert!(matches!(
+        args.command,
+        Some(Command::Diff { new: Some(ref new), min_score_delta: 50, .. }) if new == "b.tsv"
+    ));
+
+    assert!(Args::try_parse_from(["infst", "diff"]).is_err());
+}
+
+#[test]
+fn test_parse_schedule() {
+    let args = Args::try_parse_from([
+        "infst",
+        "--schedule",
+        "snapshot@daily 04:00",
+        "--schedule",
+        "upload:title-mapping.json@weekly sun 03:00",
+        "--schedule",
+        "backup@session-end",
+    ])
+    .unwrap();
+    let jobs: Vec<String> = args.schedule.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        jobs,
+        [
+            "snapshot@daily 04:00",
+            "upload:title-mapping.json@weekly sun 03:00",
+            "backup@session-end"
+        ]
+    );
+    assert_eq!(args.schedule[2].trigger, infst::ScheduleTrigger::SessionEnd);
+
+    assert!(Args::try_parse_from(["infst", "--schedule", "snapshot@hourly"]).is_err());
+    assert!(Args::try_parse_from(["infst", "--schedule", "archive@session-end"]).is_err());
+}
+
+#[test]
+fn test_parse_sign_and_verify() {
+    let args = Args::try_parse_from(["infst", "export", "--sign-key", "secret.key"]).unwrap();
+    match args.command {
+        Some(Command::Export { sign_key, .. }) => {
+            assert_eq!(sign_key.as_deref(), Some("secret.key"))
+        }
+        _ => panic!("Expected Export command"),
+    }
+
+    let args =
+        Args::try_parse_from(["infst", "verify", "tracker.tsv", "--key", "secret.key"]).unwrap();
+    match args.command {
+        Some(Command::Verify { file, key }) => {
+            assert_eq!(file, "tracker.tsv");
+            assert_eq!(key, "secret.key");
+        }
+        _ => panic!("Expected Verify command"),
+    }
+
+    // The key is required
+    assert!(Args::try_parse_from(["infst", "verify", "tracker.tsv"]).is_err());
+}
+
+#[test]
+fn test_parse_merge() {
+    let args = Args::try_parse_from([
+        "infst",
+        "merge",
+        "laptop.tsv",
+        "desktop.tsv",
+        "-o",
+        "merged.tsv",
+    ])
+    .unwrap();
+    match args.command {
+        Some(Command::Merge {
+            a,
+            b,
+            output,
+            encoding,
+            json,
+        }) => {
+            assert_eq!(a, "laptop.tsv");
+            assert_eq!(b, "desktop.tsv");
+            assert_eq!(output, "merged.tsv");
+            assert_eq!(encoding, infst::TextEncoding::Utf8);
+            assert!(!json);
+        }
+        _ => panic!("Expected Merge command"),
+    }
+
+    // The output file is required
+    assert!(Args::try_parse_from(["infst", "merge", "laptop.tsv", "desktop.tsv"]).is_err());
+}
+
+#[test]
+fn test_parse_process_select() {
+    let args = Args::try_parse_from(["infst", "--process-select", "title:INFINITAS"]).unwrap();
+    assert_eq!(
+        args.process_select,
+        Some(infst::ProcessSelection::WindowTitle(
+            "INFINITAS".to_string()
+        ))
+    );
+
+    // Global: accepted after a subcommand too
+    let args = Args::try_parse_from(["infst", "status", "--process-select", "newest"]).unwrap();
+    assert_eq!(args.process_select, Some(infst::ProcessSelection::Newest));
+
+    assert!(Args::try_parse_from(["infst", "--process-select", "latest"]).is_err());
+}
+
+#[test]
+fn test_parse_access_options() {
+    let args = Args::try_parse_from([
+        "infst",
+        "--max-reads-per-sec",
+        "200",
+        "--audit-log",
+        "audit.json",
+        "--no-unlock-reads",
+        "--no-song-rescan",
+    ])
+    .unwrap();
+    assert_eq!(args.max_reads_per_sec, Some(200));
+    assert_eq!(args.audit_log, Some("audit.json".to_string()));
+    assert!(args.no_unlock_reads);
+    assert!(args.no_song_rescan);
+    assert!(args.command.is_none());
+}
+
+#[test]
+fn test_parse_fake_time() {
+    let args = Args::try_parse_from(["infst", "--fake-time", "2026-03-01T23:59:00Z"]).unwrap();
+    assert_eq!(
+        args.fake_time.map(|time| time.to_rfc3339()),
+        Some("2026-03-01T23:59:00+00:00".to_string())
+    );
+    assert!(Args::try_parse_from(["infst", "--fake-time", "soon"]).is_err());
+}
+
+#[test]
+fn test_parse_timezone() {
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert_eq!(args.timezone, infst::DisplayTimezone::Local);
+    let args = Args::try_parse_from(["infst", "--timezone", "utc"]).unwrap();
+    assert_eq!(args.timezone, infst::DisplayTimezone::Utc);
+    let args = Args::try_parse_from(["infst", "--timezone", "+09:00"]).unwrap();
+    assert_eq!(args.timezone.to_string(), "+09:00");
+    assert!(Args::try_parse_from(["infst", "--timezone", "Mars/Olympus"]).is_err());
+}
+
+#[test]
+fn test_parse_global_offsets_file() {
+    let args = Args::try_parse_from(["infst", "--offsets-file", "my-offsets.txt"]).unwrap();
+    assert_eq!(args.offsets_file, Some("my-offsets.txt".to_string()));
+}
+
+#[test]
+fn test_parse_with_pid() {
+    let args = Args::try_parse_from(["infst", "status", "--pid", "12345"]).unwrap();
+    match args.command {
+        Some(Command::Status { pid, .. }) => {
+            assert_eq!(pid, Some(12345));
+        }
+        _ => panic!("Expected Status command"),
+    }
+}
+
+#[test]
+fn test_invalid_command_fails() {
+    let result = Args::try_parse_from(["infst", "invalid-command"]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_missing_required_arg_fails() {
+    // hexdump requires --address
+    let result = Args::try_parse_from(["infst", "hexdump"]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_parse_launch() {
+    let args = Args::try_parse_from(["infst", "launch"]).unwrap();
+    match args.command {
+        Some(Command::Launch { url, pid, timeout }) => {
+            assert!(url.is_none());
+            assert!(pid.is_none());
+            assert_eq!(timeout, 120);
+        }
+        _ => panic!("Expected Launch command"),
+    }
+}
+
+#[test]
+fn test_parse_launch_with_url() {
+    let args = Args::try_parse_from(["infst", "launch", "--url", "bm2dxinf://test"]).unwrap();
+    match args.command {
+        Some(Command::Launch { url, .. }) => {
+            assert_eq!(url, Some("bm2dxinf://test".to_string()));
+        }
+        _ => panic!("Expected Launch command"),
+    }
+}
+
+#[test]
+fn test_parse_portable_and_data_dir() {
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert!(!args.portable);
+    assert!(args.data_dir.is_none());
+
+    let args = Args::try_parse_from(["infst", "--portable"]).unwrap();
+    assert!(args.portable);
+
+    // Global, so it can follow the subcommand
+    let args = Args::try_parse_from(["infst", "doctor", "--data-dir", "D:/infst"]).unwrap();
+    assert_eq!(args.data_dir.as_deref(), Some("D:/infst"));
+
+    assert!(Args::try_parse_from(["infst", "--portable", "--data-dir", "D:/infst"]).is_err());
+}
+
+#[test]
+fn test_parse_register() {
+    let args = Args::try_parse_from(["infst", "register"]).unwrap();
+    assert!(matches!(args.command, Some(Command::Register)));
+}
+
+#[test]
+fn test_parse_doctor_with_relaunch() {
+    let args =
+        Args::try_parse_from(["infst", "doctor", "--pid", "1234", "--relaunch-as-admin"]).unwrap();
+    assert!(args.relaunch_as_admin);
+    match args.command {
+        Some(Command::Doctor { pid, session_dir }) => {
+            assert_eq!(pid, Some(1234));
+            assert!(session_dir.is_none());
+        }
+        _ => panic!("Expected Doctor command"),
+    }
+}
+
+#[test]
+fn test_parse_output_format() {
+    let args = Args::try_parse_from(["infst", "doctor"]).unwrap();
+    assert_eq!(args.output_format, OutputFormat::Text);
+
+    let args = Args::try_parse_from(["infst", "--output", "json", "doctor"]).unwrap();
+    assert_eq!(args.output_format, OutputFormat::Json);
+    assert!(matches!(args.command, Some(Command::Doctor { .. })));
+
+    // Subcommands with their own --output keep it
+    let args =
+        Args::try_parse_from(["infst", "--output", "json", "export", "--output", "x.tsv"]).unwrap();
+    match args.command {
+        Some(Command::Export { output, .. }) => assert_eq!(output, Some("x.tsv".to_string())),
+        _ => panic!("Expected Export command"),
+    }
+
+    assert!(Args::try_parse_from(["infst", "--output", "xml"]).is_err());
+}
+
+#[test]
+fn test_parse_completions() {
+    let args = Args::try_parse_from(["infst", "completions", "powershell"]).unwrap();
+    match args.command {
+        Some(Command::Completions { shell }) => assert_eq!(shell, Shell::Powershell),
+        _ => panic!("Expected Completions command"),
+    }
+
+    assert!(Args::try_parse_from(["infst", "completions"]).is_err());
+    assert!(Args::try_parse_from(["infst", "completions", "tcsh"]).is_err());
+}
+
+#[test]
+fn test_parse_man() {
+    let args = Args::try_parse_from(["infst", "man", "-o", "infst.1"]).unwrap();
+    match args.command {
+        Some(Command::Man { output }) => assert_eq!(output, Some("infst.1".to_string())),
+        _ => panic!("Expected Man command"),
+    }
+}
+
+#[test]
+fn test_watch_command() {
+    let args = Args::try_parse_from([
+        "infst",
+        "watch",
+        "--address",
+        "0x1431B08A0",
+        "--address",
+        "0x1431B08A4",
+        "--type",
+        "u16",
+        "--interval",
+        "250ms",
+        "--csv",
+    ])
+    .unwrap();
+    match args.command {
+        Some(Command::Watch {
+            address,
+            value_type,
+            interval,
+            csv,
+            pid,
+        }) => {
+            assert_eq!(address, vec!["0x1431B08A0", "0x1431B08A4"]);
+            assert_eq!(value_type, WatchType::U16);
+            assert_eq!(interval, "250ms");
+            assert!(csv);
+            assert_eq!(pid, None);
+        }
+        _ => panic!("Expected Watch command"),
+    }
+
+    let args = Args::try_parse_from(["infst", "watch", "--address", "0x10"]).unwrap();
+    match args.command {
+        Some(Command::Watch {
+            value_type,
+            interval,
+            csv,
+            ..
+        }) => {
+            assert_eq!(value_type, WatchType::I32);
+            assert_eq!(interval, "100ms");
+            assert!(!csv);
+        }
+        _ => panic!("Expected Watch command"),
+    }
+
+    assert!(Args::try_parse_from(["infst", "watch"]).is_err());
+}
+
+#[test]
+fn test_struct_diff_command() {
+    let args = Args::try_parse_from([
+        "infst",
+        "struct-diff",
+        "--layout",
+        "judge-player",
+        "--a",
+        "0x1431B08A0",
+        "--b",
+        "0x1431B08B4",
+        "--changed",
+    ])
+    .unwrap();
+    match args.command {
+        Some(Command::StructDiff {
+            layout,
+            a,
+            b,
+            size,
+            changed,
+            pid,
+        }) => {
+            assert_eq!(layout, "judge-player");
+            assert_eq!(a, "0x1431B08A0");
+            assert_eq!(b, "0x1431B08B4");
+            assert_eq!(size, None);
+            assert!(changed);
+            assert_eq!(pid, None);
+        }
+        _ => panic!("Expected StructDiff command"),
+    }
+
+    assert!(
+        Args::try_parse_from(["infst", "struct-diff", "--layout", "song", "--a", "0x10"]).is_err()
+    );
+}
+
+#[test]
+fn test_session_command() {
+    let args = Args::try_parse_from(["infst", "session", "pause"]).unwrap();
+    match args.command {
+        Some(Command::Session {
+            action,
+            session_dir,
+        }) => {
+            assert_eq!(action, SessionAction::Pause);
+            assert!(session_dir.is_none());
+        }
+        _ => panic!("Expected Session command"),
+    }
+
+    let args = Args::try_parse_from([
+        "infst",
+        "session",
+        "--session-dir",
+        "my_sessions",
+        "note",
+        "switched to new controller",
+    ])
+    .unwrap();
+    match args.command {
+        Some(Command::Session {
+            action,
+            session_dir,
+        }) => {
+            assert_eq!(
+                action,
+                SessionAction::Note {
+                    text: "switched to new controller".to_string()
+                }
+            );
+            assert_eq!(session_dir.as_deref(), Some("my_sessions"));
+        }
+        _ => panic!("Expected Session command"),
+    }
+
+    let args = Args::try_parse_from(["infst", "session", "copy"]).unwrap();
+    assert!(matches!(
+        args.command,
+        Some(Command::Session {
+            action: SessionAction::Copy,
+            ..
+        })
+    ));
+
+    assert!(Args::try_parse_from(["infst", "session", "note"]).is_err());
+    assert!(Args::try_parse_from(["infst", "session"]).is_err());
+}
+
+#[test]
+fn test_parse_hotkeys() {
+    use infst::input::hotkeys::HotkeyAction;
+
+    let args = Args::try_parse_from([
+        "infst",
+        "--hotkey",
+        "copy=ctrl+shift+c",
+        "--hotkey",
+        "marquee=alt+f9",
+    ])
+    .unwrap();
+    let bound: Vec<_> = args.hotkey.iter().map(|b| b.action).collect();
+    assert_eq!(bound, vec![HotkeyAction::Copy, HotkeyAction::Marquee]);
+    assert_eq!(args.hotkey[1].to_string(), "marquee=Alt+F9");
+    assert!(!args.no_hotkeys);
+
+    assert!(
+        Args::try_parse_from(["infst", "--no-hotkeys"])
+            .unwrap()
+            .no_hotkeys
+    );
+    assert!(Args::try_parse_from(["infst", "--hotkey", "copy"]).is_err());
+    assert!(Args::try_parse_from(["infst", "--hotkey", "launch=ctrl+l"]).is_err());
+    assert!(
+        Args::try_parse_from(["infst", "--no-hotkeys", "--hotkey", "copy=ctrl+alt+c"]).is_err()
+    );
+
+    for (action, expected) in [
+        ("export", SessionAction::Export),
+        ("marquee", SessionAction::Marquee),
+    ] {
+        let args = Args::try_parse_from(["infst", "session", action]).unwrap();
+        match args.command {
+            Some(Command::Session { action, .. }) => assert_eq!(action, expected),
+            _ => panic!("Expected Session command"),
+        }
+    }
+}
+
+#[test]
+fn test_parse_clipboard_template() {
+    let args = Args::try_parse_from(["infst", "--clipboard-template", "{title} {score}"]).unwrap();
+    assert_eq!(args.clipboard_template.as_deref(), Some("{title} {score}"));
+    assert!(
+        Args::try_parse_from(["infst"])
+            .unwrap()
+            .clipboard_template
+            .is_none()
+    );
+}
+
+#[test]
+fn test_parse_drop_anomalies() {
+    let args = Args::try_parse_from(["infst", "--drop-anomalies"]).unwrap();
+    assert!(args.drop_anomalies);
+    assert!(!Args::try_parse_from(["infst"]).unwrap().drop_anomalies);
+}
+
+#[test]
+fn test_parse_recent_plays_default() {
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert_eq!(args.recent_plays, 10);
+}
+
+#[test]
+fn test_parse_duplicate_window() {
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert_eq!(args.duplicate_window, 60);
+    let args = Args::try_parse_from(["infst", "--duplicate-window", "0"]).unwrap();
+    assert_eq!(args.duplicate_window, 0);
+}
+
+#[test]
+fn test_parse_green_number_alert() {
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert_eq!(args.green_number_alert, None);
+    let args = Args::try_parse_from(["infst", "--green-number-alert", "30"]).unwrap();
+    assert_eq!(args.green_number_alert, Some(30));
+    assert!(Args::try_parse_from(["infst", "--green-number-alert", "-5"]).is_err());
+}
+
+#[test]
+fn test_parse_title_languages() {
+    use infst::TitleLanguage;
+
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert_eq!(args.stream_title, TitleLanguage::Original);
+    assert_eq!(args.stream_title2, None);
+
+    let args = Args::try_parse_from([
+        "infst",
+        "--stream-title",
+        "romanized",
+        "--stream-title2",
+        "original",
+        "--title-aliases",
+        "aliases.json",
+    ])
+    .unwrap();
+    assert_eq!(args.stream_title, TitleLanguage::Romanized);
+    assert_eq!(args.stream_title2, Some(TitleLanguage::Original));
+    assert_eq!(args.title_aliases.as_deref(), Some("aliases.json"));
+    assert!(Args::try_parse_from(["infst", "--stream-title", "japanese"]).is_err());
+
+    let args = Args::try_parse_from([
+        "infst", "export", "--title", "english", "--title2", "original",
+    ])
+    .unwrap();
+    match args.command {
+        Some(Command::Export { title, title2, .. }) => {
+            assert_eq!(title, TitleLanguage::English);
+            assert_eq!(title2, Some(TitleLanguage::Original));
+        }
+        _ => panic!("Expected Export command"),
+    }
+}
+
+#[test]
+fn test_parse_premature() {
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert_eq!(args.premature, PrematurePolicy::Flag);
+    let args = Args::try_parse_from(["infst", "--premature", "judge-only"]).unwrap();
+    assert_eq!(args.premature, PrematurePolicy::JudgeOnly);
+    let args = Args::try_parse_from(["infst", "--premature", "ignore"]).unwrap();
+    assert_eq!(args.premature, PrematurePolicy::Ignore);
+    assert!(Args::try_parse_from(["infst", "--premature", "skip"]).is_err());
+}
+
+#[test]
+fn test_parse_ignore_paused_plays() {
+    let args = Args::try_parse_from(["infst", "--ignore-paused-plays"]).unwrap();
+    assert!(args.ignore_paused_plays);
+    assert!(args.command.is_none());
+}
+
+#[test]
+fn test_parse_tournament() {
+    let args = Args::try_parse_from(["infst", "--tournament", "arena.json"]).unwrap();
+    assert_eq!(args.tournament, Some("arena.json".to_string()));
+    assert!(args.command.is_none());
+
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert_eq!(args.tournament, None);
+}
+
+#[test]
+fn test_parse_force() {
+    let args = Args::try_parse_from(["infst", "--force"]).unwrap();
+    assert!(args.force);
+    assert!(args.command.is_none());
+
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert!(!args.force);
+}
+
+#[test]
+fn test_parse_doctor_session_dir() {
+    let args = Args::try_parse_from(["infst", "doctor", "--session-dir", "D:/sessions"]).unwrap();
+    match args.command {
+        Some(Command::Doctor { session_dir, .. }) => {
+            assert_eq!(session_dir.as_deref(), Some("D:/sessions"))
+        }
+        _ => panic!("Expected Doctor command"),
+    }
+}
+
+#[test]
+fn test_parse_songs_command() {
+    let args = Args::try_parse_from([
+        "infst",
+        "songs",
+        "--search",
+        "gigadelic",
+        "--level",
+        "12",
+        "--sp",
+    ])
+    .unwrap();
+    match args.command {
+        Some(Command::Songs {
+            search,
+            level,
+            sp,
+            dp,
+            from,
+            ..
+        }) => {
+            assert_eq!(search.as_deref(), Some("gigadelic"));
+            assert_eq!(level, Some(12));
+            assert!(sp && !dp);
+            assert_eq!(from, None);
+        }
+        _ => panic!("Expected Songs command"),
+    }
+
+    assert!(
+        Args::try_parse_from(["infst", "songs", "--level", "12", "--min-level", "11"]).is_err()
+    );
+    assert!(
+        Args::try_parse_from(["infst", "songs", "--from", "tracker.tsv", "--pid", "1"]).is_err()
+    );
+}
+
+#[test]
+fn test_parse_dry_run() {
+    let args = Args::try_parse_from(["infst", "--dry-run"]).unwrap();
+    assert!(args.dry_run);
+    assert!(!Args::try_parse_from(["infst"]).unwrap().dry_run);
+}
+
+#[test]
+fn test_parse_logging_options() {
+    let args = Args::try_parse_from([
+        "infst",
+        "status",
+        "--log-dir",
+        "logs",
+        "--log-level",
+        "debug",
+        "--console-log-level",
+        "error",
+        "--log-max-size",
+        "5",
+    ])
+    .unwrap();
+    assert_eq!(args.log_dir.as_deref(), Some("logs"));
+    assert_eq!(args.log_level, LevelFilter::DEBUG);
+    assert_eq!(args.console_log_level, Some(LevelFilter::ERROR));
+    assert_eq!(args.log_max_size, 5);
+
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert_eq!(args.log_dir, None);
+    assert_eq!(args.log_level, LevelFilter::INFO);
+    assert_eq!(args.console_log_level, None);
+    assert!(Args::try_parse_from(["infst", "--log-level", "loud"]).is_err());
+}
+
+#[test]
+fn test_parse_restart_on_panic() {
+    let args = Args::try_parse_from(["infst", "--restart-on-panic"]).unwrap();
+    assert!(args.restart_on_panic);
+    assert!(!Args::try_parse_from(["infst"]).unwrap().restart_on_panic);
+}
+
+#[test]
+fn test_parse_retry_overrides() {
+    let args = Args::try_parse_from([
+        "infst",
+        "--retry",
+        "song-db=20x5s",
+        "--retry",
+        "api=5x500ms..8s",
+    ])
+    .unwrap();
+    assert_eq!(args.retry.len(), 2);
+    assert_eq!(args.retry[0].operation, infst::RetryOperation::SongDb);
+    assert_eq!(
+        args.retry[1].policy.max_delay,
+        std::time::Duration::from_secs(8)
+    );
+
+    assert!(Args::try_parse_from(["infst", "--retry", "song-db=0x5s"]).is_err());
+    assert!(Args::try_parse_from(["infst", "--retry", "disk=3x1s"]).is_err());
+}
+
+#[test]
+fn test_parse_process_matching() {
+    let args = Args::try_parse_from([
+        "infst",
+        "--process-name",
+        "launcher.exe",
+        "--process-title",
+        "beatmania IIDX \\d+",
+        "--process-path",
+        "D:\\Games\\spice64.exe",
+    ])
+    .unwrap();
+    assert_eq!(args.process_name.as_deref(), Some("launcher.exe"));
+    assert_eq!(args.process_title.as_deref(), Some("beatmania IIDX \\d+"));
+    assert_eq!(args.process_path.as_deref(), Some("D:\\Games\\spice64.exe"));
+
+    let args = Args::try_parse_from(["infst", "status", "--process-title", "IIDX"]).unwrap();
+    assert_eq!(args.process_title.as_deref(), Some("IIDX"));
+    assert!(args.process_name.is_none());
+}
+
+#[test]
+fn test_parse_api_consent_and_payload_preview() {
+    let args = Args::try_parse_from([
+        "infst",
+        "--api-consent",
+        "--preview-payload",
+        "payload.json",
+    ])
+    .unwrap();
+    assert!(args.api_consent);
+    assert_eq!(args.preview_payload.as_deref(), Some("payload.json"));
+
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert!(!args.api_consent);
+    assert!(args.preview_payload.is_none());
+}
+
+#[test]
+fn test_parse_api_batching() {
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert_eq!(
+        args.api_batch_size as usize,
+        infst::config::submission::BATCH_SIZE
+    );
+    assert_eq!(args.api_flush_interval, 60);
+
+    let args = Args::try_parse_from([
+        "infst",
+        "--api-batch-size",
+        "25",
+        "--api-flush-interval",
+        "10",
+    ])
+    .unwrap();
+    assert_eq!(args.api_batch_size, 25);
+    assert_eq!(args.api_flush_interval, 10);
+
+    assert!(Args::try_parse_from(["infst", "--api-batch-size", "0"]).is_err());
+}
+
+#[test]
+fn test_parse_push_notifications() {
+    let args = Args::try_parse_from([
+        "infst",
+        "--push-url",
+        "https://ntfy.sh/my-topic",
+        "--push-on",
+        "clear:12",
+        "--push-on",
+        "aaa@1000:SPA",
+        "--push-header",
+        "Title: infst",
+    ])
+    .unwrap();
+    assert_eq!(args.push_url.as_deref(), Some("https://ntfy.sh/my-topic"));
+    assert_eq!(
+        args.push_on
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        vec!["clear:12", "aaa@1000:SPA"]
+    );
+    assert_eq!(args.push_template, infst::notify::DEFAULT_PUSH_TEMPLATE);
+    assert_eq!(
+        args.push_header,
+        vec![("Title".to_string(), "infst".to_string())]
+    );
+
+    // A URL needs milestones and the other way round
+    assert!(Args::try_parse_from(["infst", "--push-url", "https://ntfy.sh/t"]).is_err());
+    assert!(Args::try_parse_from(["infst", "--push-on", "clear"]).is_err());
+    assert!(
+        Args::try_parse_from([
+            "infst",
+            "--push-url",
+            "https://ntfy.sh/t",
+            "--push-on",
+            "clears"
+        ])
+        .is_err()
+    );
+}
+
+#[test]
+fn test_parse_summary_image() {
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert!(!args.summary_image);
+
+    let args = Args::try_parse_from(["infst", "--summary-image"]).unwrap();
+    assert!(args.summary_image);
+}
+
+#[test]
+fn test_parse_chart_density() {
+    let args = Args::try_parse_from([
+        "infst",
+        "export",
+        "-f",
+        "json",
+        "--chart-density",
+        "density.json",
+    ])
+    .unwrap();
+    match args.command {
+        Some(Command::Export { chart_density, .. }) => {
+            assert_eq!(chart_density.as_deref(), Some("density.json"));
+        }
+        _ => panic!("Expected Export command"),
+    }
+
+    let args = Args::try_parse_from(["infst", "recommend"]).unwrap();
+    match args.command {
+        Some(Command::Recommend { chart_density, .. }) => assert!(chart_density.is_none()),
+        _ => panic!("Expected Recommend command"),
+    }
+}
+
+#[test]
+fn test_parse_selftest() {
+    let args = Args::try_parse_from(["infst", "selftest", "--pid", "1234"]).unwrap();
+    match args.command {
+        Some(Command::Selftest { offsets_file, pid }) => {
+            assert!(offsets_file.is_none());
+            assert_eq!(pid, Some(1234));
+        }
+        _ => panic!("Expected Selftest command"),
+    }
+}
+
+#[test]
+fn test_parse_scores() {
+    let args = Args::try_parse_from([
+        "infst",
+        "scores",
+        "diff",
+        "--before",
+        "snap1.json",
+        "--after",
+        "snap2.json",
+    ])
+    .unwrap();
+    match args.command {
+        Some(Command::Scores { action }) => assert_eq!(
+            action,
+            ScoresAction::Diff {
+                before: "snap1.json".to_string(),
+                after: "snap2.json".to_string(),
+            }
+        ),
+        _ => panic!("Expected Scores command"),
+    }
+
+    let args = Args::try_parse_from(["infst", "scores", "snapshot", "-o", "snap.json"]).unwrap();
+    match args.command {
+        Some(Command::Scores { action }) => assert_eq!(
+            action,
+            ScoresAction::Snapshot {
+                output: "snap.json".to_string(),
+                pid: None,
+            }
+        ),
+        _ => panic!("Expected Scores command"),
+    }
+}
+
+#[test]
+fn test_parse_ignore_file() {
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert!(args.ignore_file.is_none());
+
+    let args = Args::try_parse_from(["infst", "--ignore-file", "ignore.json"]).unwrap();
+    assert_eq!(args.ignore_file.as_deref(), Some("ignore.json"));
+
+    let args = Args::try_parse_from(["infst", "export", "--ignore-file", "ignore.json"]).unwrap();
+    match args.command {
+        Some(Command::Export { ignore_file, .. }) => {
+            assert_eq!(ignore_file.as_deref(), Some("ignore.json"));
+        }
+        _ => panic!("Expected Export command"),
+    }
+}
+
+#[test]
+fn test_parse_tracker_audit() {
+    let args = Args::try_parse_from(["infst", "tracker", "audit"]).unwrap();
+    match args.command {
+        Some(Command::Tracker {
+            action,
+            session_dir,
+        }) => {
+            assert_eq!(action, TrackerAction::Audit);
+            assert!(session_dir.is_none());
+        }
+        _ => panic!("Expected Tracker command"),
+    }
+
+    let args = Args::try_parse_from(["infst", "tracker", "--session-dir", "s", "audit"]).unwrap();
+    match args.command {
+        Some(Command::Tracker { session_dir, .. }) => {
+            assert_eq!(session_dir.as_deref(), Some("s"));
+        }
+        _ => panic!("Expected Tracker command"),
+    }
+    assert!(Args::try_parse_from(["infst", "tracker"]).is_err());
+}
+
+#[test]
+fn test_parse_profile() {
+    let args = Args::try_parse_from(["infst"]).unwrap();
+    assert!(args.profile.is_none());
+
+    let args = Args::try_parse_from(["infst", "--profile", "sister"]).unwrap();
+    assert_eq!(args.profile.as_deref(), Some("sister"));
+
+    // Global, so it also follows a subcommand
+    let args = Args::try_parse_from(["infst", "tracker", "audit", "--profile", "sister"]).unwrap();
+    assert_eq!(args.profile.as_deref(), Some("sister"));
+}
+
+#[test]
+fn test_parse_discover_layout() {
+    let args = Args::try_parse_from([
+        "infst",
+        "discover-layout",
+        "--address",
+        "0x1431B08A0",
+        "--dump",
+        "song_list.bin",
+        "--since",
+        "2026050100",
+        "-o",
+        "layout.json",
+    ])
+    .unwrap();
+    match args.command {
+        Some(Command::DiscoverLayout {
+            address,
+            size,
+            dump,
+            base,
+            since,
+            output,
+            pid,
+        }) => {
+            assert_eq!(address, "0x1431B08A0");
+            assert_eq!(size, 1048576);
+            assert_eq!(dump.as_deref(), Some("song_list.bin"));
+            assert_eq!(base, "0x1000");
+            assert_eq!(since.as_deref(), Some("2026050100"));
+            assert_eq!(output.as_deref(), Some("layout.json"));
+            assert!(pid.is_none());
+        }
+        _ => panic!("Expected DiscoverLayout command"),
+    }
+    assert!(Args::try_parse_from(["infst", "discover-layout"]).is_err());
 }
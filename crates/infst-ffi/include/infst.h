@@ -0,0 +1,54 @@
+/*
+ * C API for the infst tracker (infst_ffi.dll / libinfst_ffi.so)
+ *
+ * All functions return immediately; tracking runs on a background thread.
+ * Strings returned by the library must be released with infst_string_free.
+ */
+
+#ifndef INFST_H
+#define INFST_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* infst_poll error codes */
+#define INFST_ERR_NULL_HANDLE (-1)
+#define INFST_ERR_STOPPED (-2)
+#define INFST_ERR_PANIC (-3)
+
+typedef struct InfstTracker InfstTracker;
+
+/*
+ * Start the tracker. config_json is a JSON object with the optional keys
+ * "data_dir", "session_dir", "tracker_path", "api_endpoint", "api_token" and
+ * "api_consent" (lamps are only sent when true), or NULL for defaults. Returns NULL if the configuration is invalid.
+ */
+InfstTracker *infst_init(const char *config_json);
+
+/* Plays recorded since the previous call, or a negative INFST_ERR_* code */
+int32_t infst_poll(InfstTracker *tracker);
+
+/* Most recent play as a JSON object, or NULL if nothing was played yet */
+char *infst_get_last_play_json(InfstTracker *tracker);
+
+/*
+ * Tracker status as a JSON object: "state" (waiting_for_process,
+ * initializing, tracking or stopped), "pid", "game_version", "plays",
+ * "last_error"
+ */
+char *infst_get_status_json(InfstTracker *tracker);
+
+/* Stop tracking, wait for the background thread and free the handle */
+void infst_shutdown(InfstTracker *tracker);
+
+/* Free a string returned by this library */
+void infst_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* INFST_H */
@@ -0,0 +1,251 @@
+//! C ABI for embedding the infst tracker
+//!
+//! Built as a `cdylib` (`infst_ffi.dll`) so overlay tools written in C#, C++
+//! or anything else with a C FFI can run the tracker in-process instead of
+//! launching the CLI and tailing its output files. The declarations are in
+//! `include/infst.h`.
+//!
+//! ```c
+//! InfstTracker *tracker = infst_init("{\"session_dir\": \"sessions\"}");
+//! while (running) {
+//!     if (infst_poll(tracker) > 0) {
+//!         char *play = infst_get_last_play_json(tracker);
+//!         show(play);
+//!         infst_string_free(play);
+//!     }
+//!     sleep_ms(500);
+//! }
+//! infst_shutdown(tracker);
+//! ```
+//!
+//! The tracker runs on its own thread; all functions return immediately and
+//! may be called from any one thread at a time per handle. Strings returned
+//! by the library are owned by the caller and must be released with
+//! [`infst_string_free`].
+
+mod tracker;
+
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr;
+
+use tracing::error;
+
+pub use tracker::{FfiConfig, Status, TrackerState};
+use tracker::{Tracker, lock};
+
+/// `infst_poll` result for a null handle
+pub const INFST_ERR_NULL_HANDLE: i32 = -1;
+/// `infst_poll` result when the tracker thread has stopped
+pub const INFST_ERR_STOPPED: i32 = -2;
+/// `infst_poll` result when the library panicked
+pub const INFST_ERR_PANIC: i32 = -3;
+
+/// Opaque tracker handle (`InfstTracker *` in C)
+pub struct InfstTracker {
+    tracker: Tracker,
+}
+
+/// Run `f`, turning a panic into `fallback` so it never unwinds into C
+fn guard<T>(fallback: T, f: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| {
+        error!("panic in infst FFI call");
+        fallback
+    })
+}
+
+/// Hand a string to the caller; interior NULs cannot occur in JSON output
+fn into_c_string(s: String) -> *mut c_char {
+    CString::new(s).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Start the tracker on a background thread
+///
+/// `config_json` is a JSON object with the optional keys `data_dir`,
+/// `session_dir`, `tracker_path`, `api_endpoint`, `api_token` and
+/// `api_consent` (lamps are only sent when true), or null for defaults.
+/// Files go under `data_dir` (the current directory if omitted);
+/// `session_dir` and `tracker_path` override single locations.
+/// Returns null if the configuration is invalid.
+///
+/// # Safety
+///
+/// `config_json` must be null or point to a NUL-terminated UTF-8 string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn infst_init(config_json: *const c_char) -> *mut InfstTracker {
+    guard(ptr::null_mut(), || {
+        let json = if config_json.is_null() {
+            None
+        } else {
+            // SAFETY: non-null and NUL-terminated per the caller contract
+            match unsafe { CStr::from_ptr(config_json) }.to_str() {
+                Ok(s) => Some(s),
+                Err(e) => {
+                    error!("infst_init: config is not UTF-8: {}", e);
+                    return ptr::null_mut();
+                }
+            }
+        };
+        match FfiConfig::parse(json).and_then(Tracker::spawn) {
+            Ok(tracker) => Box::into_raw(Box::new(InfstTracker { tracker })),
+            Err(e) => {
+                error!("infst_init: {}", e);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Number of plays recorded since the previous call
+///
+/// Returns a negative `INFST_ERR_*` code for a null handle, a stopped
+/// tracker, or an internal panic.
+///
+/// # Safety
+///
+/// `tracker` must be null or a live handle from [`infst_init`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn infst_poll(tracker: *mut InfstTracker) -> i32 {
+    // SAFETY: live handle per the caller contract
+    let Some(handle) = (unsafe { tracker.as_ref() }) else {
+        return INFST_ERR_NULL_HANDLE;
+    };
+    guard(INFST_ERR_PANIC, || {
+        let mut status = lock(&handle.tracker.status);
+        if status.state == TrackerState::Stopped {
+            return INFST_ERR_STOPPED;
+        }
+        i32::try_from(status.take_new_plays()).unwrap_or(i32::MAX)
+    })
+}
+
+/// The most recent play as a JSON object, or null if nothing was played yet
+///
+/// Uses the same fields as the JSON session files. Free the result with
+/// [`infst_string_free`].
+///
+/// # Safety
+///
+/// `tracker` must be null or a live handle from [`infst_init`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn infst_get_last_play_json(tracker: *mut InfstTracker) -> *mut c_char {
+    // SAFETY: live handle per the caller contract
+    let Some(handle) = (unsafe { tracker.as_ref() }) else {
+        return ptr::null_mut();
+    };
+    guard(ptr::null_mut(), || {
+        let play = lock(&handle.tracker.status).last_play.clone();
+        play.map_or(ptr::null_mut(), |play| into_c_string(play.to_string()))
+    })
+}
+
+/// Tracker status as a JSON object
+///
+/// Keys: `state` (`waiting_for_process`, `initializing`, `tracking` or
+/// `stopped`), `pid`, `game_version`, `plays` and `last_error`. Free the
+/// result with [`infst_string_free`].
+///
+/// # Safety
+///
+/// `tracker` must be null or a live handle from [`infst_init`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn infst_get_status_json(tracker: *mut InfstTracker) -> *mut c_char {
+    // SAFETY: live handle per the caller contract
+    let Some(handle) = (unsafe { tracker.as_ref() }) else {
+        return ptr::null_mut();
+    };
+    guard(ptr::null_mut(), || {
+        let status = lock(&handle.tracker.status);
+        serde_json::to_string(&*status).map_or(ptr::null_mut(), into_c_string)
+    })
+}
+
+/// Stop the tracker, wait for its thread and free the handle
+///
+/// The current session is closed and the tracker file exported as when the
+/// CLI exits. Null is ignored.
+///
+/// # Safety
+///
+/// `tracker` must be null or a live handle from [`infst_init`]; it is
+/// invalid after this call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn infst_shutdown(tracker: *mut InfstTracker) {
+    if tracker.is_null() {
+        return;
+    }
+    // SAFETY: created by Box::into_raw in infst_init and not yet freed
+    let mut handle = unsafe { Box::from_raw(tracker) };
+    guard((), || handle.tracker.shutdown());
+}
+
+/// Free a string returned by this library; null is ignored
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by this library that has not been
+/// freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn infst_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: created by CString::into_raw in into_c_string
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take_string(s: *mut c_char) -> Option<String> {
+        if s.is_null() {
+            return None;
+        }
+        let owned = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+        unsafe { infst_string_free(s) };
+        Some(owned)
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = CString::new(
+            serde_json::json!({
+                "session_dir": dir.path().join("sessions"),
+                "tracker_path": dir.path().join("tracker.tsv"),
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let tracker = unsafe { infst_init(config.as_ptr()) };
+        assert!(!tracker.is_null());
+        assert_eq!(unsafe { infst_poll(tracker) }, 0);
+        assert_eq!(
+            take_string(unsafe { infst_get_last_play_json(tracker) }),
+            None
+        );
+
+        let status: serde_json::Value =
+            serde_json::from_str(&take_string(unsafe { infst_get_status_json(tracker) }).unwrap())
+                .unwrap();
+        assert_eq!(status["plays"], 0);
+        assert!(status["state"].is_string());
+
+        unsafe { infst_shutdown(tracker) };
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let config = CString::new(r#"{"api_endpoint": "ftp://x", "api_token": "t"}"#).unwrap();
+        assert!(unsafe { infst_init(config.as_ptr()) }.is_null());
+        let config = CString::new("not json").unwrap();
+        assert!(unsafe { infst_init(config.as_ptr()) }.is_null());
+
+        let null = ptr::null_mut();
+        assert_eq!(unsafe { infst_poll(null) }, INFST_ERR_NULL_HANDLE);
+        assert!(unsafe { infst_get_status_json(null) }.is_null());
+        unsafe { infst_shutdown(null) };
+        unsafe { infst_string_free(ptr::null_mut()) };
+    }
+}
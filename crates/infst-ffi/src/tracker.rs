@@ -0,0 +1,441 @@
+//! Background tracker thread behind the C API
+//!
+//! Runs the same attach → detect offsets → load data → track cycle as the
+//! CLI's tracking mode, without console output or keyboard handling, and
+//! publishes its progress in a [`Shared`] state the C functions read from.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use tracing::{debug, warn};
+
+use infst::chart::{
+    SongEntryLayout, fetch_song_database, fetch_song_database_from_memory_scan_with_layout,
+};
+use infst::export::format_json_entry;
+use infst::{
+    ApiConfig, GameLayout, Infst, InfstConfig, LayoutRegistry, MemoryReader, OffsetSearcher,
+    OffsetsCollection, Paths, PlayData, ProcessHandle, ScoreMap, SongInfo, TrackerExit,
+    find_game_version, invalidate_cached_offsets, save_offsets_to_cache, try_load_cached_offsets,
+};
+
+/// How long to wait between attempts to find the game or detect offsets
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+/// Granularity of interruptible waits
+const WAIT_SLICE: Duration = Duration::from_millis(100);
+/// Bytes of the song list scanned for entries
+const SONG_SCAN_SIZE: usize = 0x100000;
+
+/// Settings accepted by `infst_init` as a JSON object
+///
+/// Every field is optional; omitted fields use the tracker defaults.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FfiConfig {
+    /// Directory for all tracker files (defaults to the current directory)
+    pub data_dir: Option<PathBuf>,
+    pub session_dir: Option<PathBuf>,
+    pub tracker_path: Option<PathBuf>,
+    pub api_endpoint: Option<String>,
+    pub api_token: Option<String>,
+    /// Lamps are only sent to the API when the host application set this
+    /// after asking the user
+    pub api_consent: bool,
+}
+
+impl FfiConfig {
+    /// Parse the `infst_init` argument (`None` for a null pointer)
+    pub fn parse(json: Option<&str>) -> infst::Result<Self> {
+        match json {
+            None => Ok(Self::default()),
+            Some(s) if s.trim().is_empty() => Ok(Self::default()),
+            Some(s) => serde_json::from_str(s)
+                .map_err(|e| infst::Error::invalid_config("config", e.to_string())),
+        }
+    }
+
+    /// Where the tracker keeps its files
+    pub fn paths(&self) -> Paths {
+        self.data_dir
+            .as_ref()
+            .map_or_else(Paths::portable, Paths::in_dir)
+    }
+
+    /// Create the tracker, validating the settings
+    pub fn build(self, on_play: impl FnMut(&PlayData) + Send + 'static) -> infst::Result<Infst> {
+        let paths = self.paths();
+        paths.create_root()?;
+        let mut builder = Infst::builder()
+            .config(InfstConfig {
+                api_consent: self.api_consent,
+                ..InfstConfig::default()
+            })
+            .paths(&paths)
+            .on_play(on_play);
+        if let Some(session_dir) = self.session_dir {
+            builder = builder.session_dir(session_dir);
+        }
+        if let Some(tracker_path) = self.tracker_path {
+            builder = builder.tracker_path(tracker_path);
+        }
+        match (self.api_endpoint, self.api_token) {
+            (Some(endpoint), Some(token)) => {
+                builder = builder.api(ApiConfig { endpoint, token });
+            }
+            (None, None) => {}
+            _ => {
+                return Err(infst::Error::invalid_config(
+                    "api_config",
+                    "api_endpoint and api_token must be given together",
+                ));
+            }
+        }
+        builder.build()
+    }
+}
+
+/// What the tracker thread is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackerState {
+    /// Game process not found yet
+    WaitingForProcess,
+    /// Attached; detecting offsets and loading the song database
+    Initializing,
+    /// Recording plays
+    Tracking,
+    /// Thread has exited
+    Stopped,
+}
+
+/// State shared between the tracker thread and the C API
+#[derive(Debug, Serialize)]
+pub struct Status {
+    pub state: TrackerState,
+    pub pid: Option<u32>,
+    pub game_version: Option<String>,
+    /// Plays recorded since `infst_init`
+    pub plays: u64,
+    pub last_error: Option<String>,
+    #[serde(skip)]
+    pub last_play: Option<JsonValue>,
+    /// `plays` at the last `infst_poll`
+    #[serde(skip)]
+    pub polled_plays: u64,
+}
+
+impl Default for Status {
+    fn default() -> Self {
+        Self {
+            state: TrackerState::WaitingForProcess,
+            pid: None,
+            game_version: None,
+            plays: 0,
+            last_error: None,
+            last_play: None,
+            polled_plays: 0,
+        }
+    }
+}
+
+impl Status {
+    /// Record a finished play
+    pub fn record_play(&mut self, play: &PlayData) {
+        self.plays += 1;
+        self.last_play = Some(format_json_entry(play));
+    }
+
+    /// Number of plays recorded since the previous call
+    pub fn take_new_plays(&mut self) -> u64 {
+        let new = self.plays - self.polled_plays;
+        self.polled_plays = self.plays;
+        new
+    }
+}
+
+pub type Shared = Arc<Mutex<Status>>;
+
+pub fn lock(shared: &Shared) -> MutexGuard<'_, Status> {
+    // Status fields are plain values; a panicked holder cannot leave them torn
+    shared.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A running tracker thread
+pub struct Tracker {
+    pub status: Shared,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Tracker {
+    /// Start tracking on a background thread
+    pub fn spawn(config: FfiConfig) -> infst::Result<Self> {
+        let status: Shared = Arc::default();
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let paths = config.paths();
+        let handler_status = status.clone();
+        let mut infst = config.build(move |play| lock(&handler_status).record_play(play))?;
+
+        let thread = {
+            let status = status.clone();
+            let shutdown = shutdown.clone();
+            thread::Builder::new()
+                .name("infst-tracker".to_string())
+                .spawn(move || {
+                    run(&mut infst, &paths, &status, &shutdown);
+                    lock(&status).state = TrackerState::Stopped;
+                })
+                .map_err(infst::Error::Io)?
+        };
+
+        Ok(Self {
+            status,
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    /// Ask the thread to stop and wait for it
+    ///
+    /// The tracking loop checks the flag once per poll, so this returns
+    /// within a few hundred milliseconds outside of an offset search.
+    pub fn shutdown(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take()
+            && thread.join().is_err()
+        {
+            warn!("Tracker thread panicked");
+        }
+    }
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Sleep for `duration` unless shutdown is requested; returns true on shutdown
+fn wait(shutdown: &AtomicBool, duration: Duration) -> bool {
+    let deadline = Instant::now() + duration;
+    while Instant::now() < deadline {
+        if shutdown.load(Ordering::SeqCst) {
+            return true;
+        }
+        thread::sleep(WAIT_SLICE.min(deadline - Instant::now()));
+    }
+    shutdown.load(Ordering::SeqCst)
+}
+
+fn set_error(status: &Shared, error: impl ToString) {
+    lock(status).last_error = Some(error.to_string());
+}
+
+/// Attach to the game whenever it is running until shutdown is requested
+fn run(infst: &mut Infst, paths: &Paths, status: &Shared, shutdown: &AtomicBool) {
+    while !shutdown.load(Ordering::SeqCst) {
+        {
+            let mut status = lock(status);
+            status.state = TrackerState::WaitingForProcess;
+            status.pid = None;
+        }
+        match ProcessHandle::find_and_open() {
+            Ok(process) => {
+                {
+                    let mut status = lock(status);
+                    status.state = TrackerState::Initializing;
+                    status.pid = Some(process.pid);
+                }
+                if let Err(e) = run_session(infst, &process, paths, status, shutdown) {
+                    warn!("Tracking session error: {}", e);
+                    set_error(status, e);
+                }
+            }
+            Err(e) => {
+                debug!("Process not found: {}", e);
+                if e.is_access_denied() {
+                    set_error(status, e);
+                }
+            }
+        }
+        if wait(shutdown, RETRY_INTERVAL) {
+            break;
+        }
+    }
+}
+
+/// Track one game process until it exits, updates or shutdown is requested
+fn run_session(
+    infst: &mut Infst,
+    process: &ProcessHandle,
+    paths: &Paths,
+    status: &Shared,
+    shutdown: &AtomicBool,
+) -> infst::Result<()> {
+    let reader = infst.memory_reader(process);
+    let game_version = find_game_version(&reader, process.base_address)
+        .ok()
+        .flatten();
+    lock(status).game_version = game_version.clone();
+
+    let registry = LayoutRegistry::builtin();
+    let layout = match &game_version {
+        Some(version) => registry.select(version)?,
+        None => registry.newest(),
+    };
+    infst.set_layout(layout.clone());
+
+    let cache = paths.offset_cache();
+    let Some(offsets) = detect_offsets(
+        &reader,
+        &cache,
+        game_version.as_deref(),
+        layout,
+        status,
+        shutdown,
+    ) else {
+        return Ok(());
+    };
+    infst.update_offsets(offsets);
+
+    let Some(song_db) = load_song_database(
+        &reader,
+        infst.offsets().song_list,
+        &layout.song_entry,
+        shutdown,
+    ) else {
+        return Ok(());
+    };
+    let score_map = ScoreMap::load_from_memory(&reader, infst.offsets().data_map, &song_db)
+        .unwrap_or_else(|e| {
+            warn!("Failed to load score map: {}", e);
+            ScoreMap::new()
+        });
+    infst.load_game_data(song_db, score_map);
+    if let Err(e) = infst.load_unlock_state(&reader) {
+        warn!("Failed to load unlock state: {}", e);
+    }
+
+    lock(status).state = TrackerState::Tracking;
+    let exit = infst.run(process, shutdown)?;
+    if let Err(e) = infst.export_tracker_tsv(infst.tracker_export_path()) {
+        warn!("Failed to export tracker: {}", e);
+    }
+    if exit == TrackerExit::GameUpdated {
+        if let Some(version) = &game_version {
+            invalidate_cached_offsets(&cache, version);
+        }
+        infst.update_offsets(OffsetsCollection::default());
+    }
+    Ok(())
+}
+
+/// Use validated cached offsets, or search until found or shutdown
+fn detect_offsets(
+    reader: &MemoryReader,
+    cache: &Path,
+    game_version: Option<&str>,
+    layout: &GameLayout,
+    status: &Shared,
+    shutdown: &AtomicBool,
+) -> Option<OffsetsCollection> {
+    if let Some(version) = game_version
+        && let Some(cached) = try_load_cached_offsets(cache, version)
+        && OffsetSearcher::new(reader).validate_basic_memory_access(&cached)
+    {
+        return Some(cached);
+    }
+
+    loop {
+        let mut searcher = OffsetSearcher::builder(reader).with_layout(layout).build();
+        match searcher.search_all_with_signatures(&layout.signatures) {
+            Ok(mut offsets) if offsets.is_valid() => {
+                if let Some(version) = game_version {
+                    offsets.version = version.to_string();
+                    save_offsets_to_cache(cache, version, &offsets);
+                }
+                return Some(offsets);
+            }
+            Ok(_) => debug!("Offset detection incomplete, retrying"),
+            Err(e) => set_error(status, e),
+        }
+        if wait(shutdown, RETRY_INTERVAL) {
+            return None;
+        }
+    }
+}
+
+/// Scan the song list, falling back to the entry-by-entry reader
+fn load_song_database(
+    reader: &MemoryReader,
+    song_list: u64,
+    layout: &SongEntryLayout,
+    shutdown: &AtomicBool,
+) -> Option<HashMap<u32, SongInfo>> {
+    loop {
+        let song_db = fetch_song_database_from_memory_scan_with_layout(
+            reader,
+            song_list,
+            SONG_SCAN_SIZE,
+            layout,
+        );
+        if !song_db.is_empty() {
+            return Some(song_db);
+        }
+        match fetch_song_database(reader, song_list) {
+            Ok(song_db) if !song_db.is_empty() => return Some(song_db),
+            Ok(_) => debug!("Song list is empty, retrying"),
+            Err(e) => debug!("Failed to load song database: {}", e),
+        }
+        if wait(shutdown, RETRY_INTERVAL) {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use infst::Grade;
+    use infst::play::test_support::PlayBuilder;
+
+    fn make_play() -> PlayData {
+        PlayBuilder::new().grade(Grade::Aa).build()
+    }
+
+    #[test]
+    fn test_parse_config() {
+        assert!(FfiConfig::parse(None).unwrap().session_dir.is_none());
+        let config = FfiConfig::parse(Some(r#"{"session_dir":"out"}"#)).unwrap();
+        assert_eq!(config.session_dir, Some(PathBuf::from("out")));
+        assert!(FfiConfig::parse(Some(r#"{"sesion_dir":"out"}"#)).is_err());
+
+        let half_api = FfiConfig::parse(Some(r#"{"api_endpoint":"https://x"}"#)).unwrap();
+        assert!(half_api.build(|_| {}).is_err());
+        let consent = FfiConfig::parse(Some(r#"{"api_consent":true}"#)).unwrap();
+        assert!(consent.api_consent);
+        assert!(!FfiConfig::parse(None).unwrap().api_consent);
+    }
+
+    #[test]
+    fn test_poll_counts_new_plays() {
+        let mut status = Status::default();
+        status.record_play(&make_play());
+        status.record_play(&make_play());
+        assert_eq!(status.take_new_plays(), 2);
+        assert_eq!(status.take_new_plays(), 0);
+        assert_eq!(status.last_play.as_ref().unwrap()["title"], "Test Song");
+
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["state"], "waiting_for_process");
+        assert_eq!(json["plays"], 2);
+        assert!(json.get("last_play").is_none());
+    }
+}
@@ -0,0 +1,121 @@
+//! Pattern scan benchmark: naive `windows()` scans vs `process::pattern`
+//!
+//! Run with `cargo bench -p infst --bench pattern_scan`. The synthetic
+//! buffer is 300MB (the offset searcher's maximum half-window) unless
+//! `INFST_BENCH_MB` says otherwise.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use infst::process::pattern::{MultiPattern, find_masked_pattern, find_pattern};
+
+const DEFAULT_SIZE_MB: usize = 300;
+
+/// Deterministic buffer resembling game memory: mostly small integers and
+/// zeros, with the searched patterns planted near the end
+fn synthetic_buffer(size: usize, planted: &[&[u8]]) -> Vec<u8> {
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut buffer: Vec<u8> = (0..size)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            if state.is_multiple_of(4) {
+                0
+            } else {
+                (state >> 56) as u8
+            }
+        })
+        .collect();
+    for (i, pattern) in planted.iter().enumerate() {
+        let at = size - (i + 1) * 4096;
+        buffer[at..at + pattern.len()].copy_from_slice(pattern);
+    }
+    buffer
+}
+
+fn naive_find(buffer: &[u8], pattern: &[u8]) -> Vec<usize> {
+    buffer
+        .windows(pattern.len())
+        .enumerate()
+        .filter(|(_, window)| *window == pattern)
+        .map(|(pos, _)| pos)
+        .collect()
+}
+
+fn naive_masked(buffer: &[u8], pattern: &[Option<u8>]) -> Vec<usize> {
+    buffer
+        .windows(pattern.len())
+        .enumerate()
+        .filter(|(_, window)| {
+            window
+                .iter()
+                .zip(pattern)
+                .all(|(byte, expected)| expected.is_none_or(|value| *byte == value))
+        })
+        .map(|(pos, _)| pos)
+        .collect()
+}
+
+fn time<T>(label: &str, size: usize, f: impl Fn() -> T) -> (Duration, T) {
+    // Best of three runs
+    let mut best = Duration::MAX;
+    let mut result = None;
+    for _ in 0..3 {
+        let start = Instant::now();
+        let value = black_box(f());
+        best = best.min(start.elapsed());
+        result = Some(value);
+    }
+    let throughput = size as f64 / 1024.0 / 1024.0 / best.as_secs_f64();
+    println!("  {label:<28} {best:>12.2?}  {throughput:>9.0} MB/s");
+    (best, result.expect("ran at least once"))
+}
+
+fn main() {
+    let size_mb = std::env::var("INFST_BENCH_MB")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_SIZE_MB);
+    let size = size_mb * 1024 * 1024;
+
+    let version: &[u8] = b"5.1.1.";
+    let unlock: &[u8] = &[0xE8, 0x03, 0, 0, 1, 0, 0, 0, 0xCE, 0x01, 0, 0];
+    let lea: [Option<u8>; 7] = [Some(0x48), Some(0x8D), Some(0x0D), None, None, None, None];
+    let lea_bytes: &[u8] = &[0x48, 0x8D, 0x0D, 0x10, 0x20, 0x30, 0x00];
+
+    println!("Building {size_mb}MB synthetic buffer...");
+    let buffer = synthetic_buffer(size, &[version, unlock, lea_bytes]);
+
+    println!("Single pattern ({} bytes)", version.len());
+    let (naive, expected) = time("naive windows()", size, || naive_find(&buffer, version));
+    let (fast, found) = time("memmem", size, || find_pattern(&buffer, version));
+    assert_eq!(found, expected);
+    println!(
+        "  speedup: {:.1}x",
+        naive.as_secs_f64() / fast.as_secs_f64()
+    );
+
+    println!("Pattern set (2 patterns)");
+    let (naive, expected) = time("naive windows() per pattern", size, || {
+        [version, unlock].map(|pattern| naive_find(&buffer, pattern))
+    });
+    let multi = MultiPattern::new(&[version, unlock]).expect("valid pattern set");
+    let (fast, found) = time("MultiPattern (one pass)", size, || multi.find_all(&buffer));
+    assert_eq!(found, expected);
+    println!(
+        "  speedup: {:.1}x",
+        naive.as_secs_f64() / fast.as_secs_f64()
+    );
+
+    println!("Wildcard pattern (LEA rcx, [rip+??])");
+    let (naive, expected) = time("naive windows()", size, || naive_masked(&buffer, &lea));
+    let (fast, found) = time("anchored memmem", size, || {
+        find_masked_pattern(&buffer, &lea)
+    });
+    assert_eq!(found, expected);
+    println!(
+        "  speedup: {:.1}x",
+        naive.as_secs_f64() / fast.as_secs_f64()
+    );
+}
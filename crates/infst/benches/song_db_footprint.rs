@@ -0,0 +1,136 @@
+//! Song metadata footprint of the real load paths
+//!
+//! Run with `cargo bench -p infst --bench song_db_footprint`. Decodes a
+//! 2000-song list from memory twice with `SongInfo::parse_from_buffer`
+//! (initial load plus one rescan, as the tracker does when new songs appear)
+//! and deserializes a play history of `SongInfo` rows from JSON, then
+//! compares the heap held by distinct string allocations with what one
+//! allocation per field would take. Finally everything is dropped to check
+//! that the pool lets go of the strings. `INFST_BENCH_SONGS` overrides the
+//! song count.
+
+use std::collections::HashSet;
+use std::hint::black_box;
+use std::sync::Arc;
+use std::time::Instant;
+
+use infst::chart::intern::{PoolStats, pool_stats, purge_pool};
+use infst::chart::{SongEntryLayout, SongInfo};
+
+const DEFAULT_SONGS: usize = 2000;
+const PLAYS: usize = 3000;
+/// Per-allocation overhead of an `Arc<str>` (strong + weak counts)
+const ARC_HEADER: usize = 2 * size_of::<usize>();
+/// Size of each string field in a song entry
+const SLAB: usize = 64;
+
+/// Text fields of song `i`: unique titles, shared artists/genres
+fn text_fields(i: usize) -> [String; 4] {
+    [
+        format!("Song Title {i:04}"),
+        format!("SONG TITLE {i:04}"),
+        format!("GENRE {:03}", i % 150),
+        format!("Artist {:03}", i % 800),
+    ]
+}
+
+/// Song list memory with `songs` entries in the current layout
+fn song_list(songs: usize) -> Vec<u8> {
+    let layout = SongEntryLayout::CURRENT;
+    let mut memory = vec![0u8; songs * layout.entry_size];
+    for (i, entry) in memory.chunks_exact_mut(layout.entry_size).enumerate() {
+        for (field, text) in text_fields(i).iter().enumerate() {
+            entry[field * SLAB..][..text.len()].copy_from_slice(text.as_bytes());
+        }
+        entry[layout.folder] = 33;
+        entry[layout.levels..][..10].copy_from_slice(&[0, 5, 8, 11, 0, 0, 5, 8, 11, 0]);
+        let bpm = (120 + i % 200) as i32;
+        entry[layout.bpm..][..4].copy_from_slice(&bpm.to_le_bytes());
+        entry[layout.song_id..][..4].copy_from_slice(&(1000 + i as i32).to_le_bytes());
+    }
+    memory
+}
+
+fn load(memory: &[u8], songs: usize) -> Vec<SongInfo> {
+    let stride = SongEntryLayout::CURRENT.entry_size;
+    (0..songs)
+        .filter_map(|i| {
+            SongInfo::parse_from_buffer(memory, i * stride)
+                .ok()
+                .flatten()
+        })
+        .collect()
+}
+
+/// Heap held by the distinct allocations referenced from `songs`, and what
+/// separate allocations per field would hold
+fn footprint(songs: &[SongInfo]) -> (PoolStats, PoolStats) {
+    let mut seen = HashSet::new();
+    let mut distinct = PoolStats::default();
+    let mut separate = PoolStats::default();
+    for song in songs {
+        for s in [
+            &song.title,
+            &song.title_english,
+            &song.artist,
+            &song.genre,
+            &song.bpm,
+        ] {
+            separate.strings += 1;
+            separate.bytes += s.len() + ARC_HEADER;
+            if seen.insert(Arc::as_ptr(s) as *const u8) {
+                distinct.strings += 1;
+                distinct.bytes += s.len() + ARC_HEADER;
+            }
+        }
+    }
+    (distinct, separate)
+}
+
+fn main() {
+    let songs = std::env::var("INFST_BENCH_SONGS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_SONGS);
+    println!("{songs} songs, 2 loads + {PLAYS} history rows");
+
+    let memory = song_list(songs);
+    let start = Instant::now();
+    let first = load(&memory, songs);
+    let rescan = load(&memory, songs);
+    let load_time = start.elapsed();
+
+    let history_json: Vec<String> = (0..PLAYS)
+        .map(|play| serde_json::to_string(&first[play % first.len()]).unwrap())
+        .collect();
+    let start = Instant::now();
+    let history: Vec<SongInfo> = history_json
+        .iter()
+        .map(|row| serde_json::from_str(row).unwrap())
+        .collect();
+    let history_time = start.elapsed();
+
+    let all: Vec<SongInfo> = black_box(first.into_iter().chain(rescan).chain(history).collect());
+    let (interned, separate) = footprint(&all);
+    println!(
+        "separate   {:>7} allocations {:>9} bytes",
+        separate.strings, separate.bytes
+    );
+    println!(
+        "interned   {:>7} allocations {:>9} bytes  load {:>8.2?}  history {:>8.2?}",
+        interned.strings, interned.bytes, load_time, history_time
+    );
+    println!(
+        "interning saves {:.1}% ({} bytes)",
+        100.0 * (separate.bytes - interned.bytes) as f64 / separate.bytes as f64,
+        separate.bytes - interned.bytes
+    );
+
+    drop(all);
+    let purged = purge_pool();
+    println!(
+        "after dropping the songs: {} strings purged, {} left in the pool",
+        purged,
+        pool_stats().strings
+    );
+}
@@ -0,0 +1,23 @@
+//! Score map loading from an arbitrary address space
+//!
+//! The first 8 bytes select the data map address; the rest is memory at
+//! base 0x1000, so pointers in the input can form chains and cycles.
+#![no_main]
+
+use std::collections::HashMap;
+
+use infst::chart::SongInfo;
+use infst::process::MockMemoryReader;
+use infst::score::{ListNode, ScoreMap};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((addr, memory)) = data.split_first_chunk::<8>() else {
+        return;
+    };
+    let _ = ScoreMap::from_nodes(memory.chunks(ListNode::SIZE).map(ListNode::from_bytes));
+
+    let reader = MockMemoryReader::new(memory.to_vec());
+    let song_db: HashMap<u32, SongInfo> = (0..256).map(|id| (id, SongInfo::default())).collect();
+    let _ = ScoreMap::load_from_memory(&reader, u64::from_le_bytes(*addr), &song_db);
+});
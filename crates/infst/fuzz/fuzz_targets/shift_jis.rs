@@ -0,0 +1,12 @@
+//! Shift-JIS decoding and the title/artist encoding fixes
+#![no_main]
+
+use infst::chart::{fix_artist_encoding, fix_title_encoding};
+use infst::process::decode_shift_jis;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let decoded = decode_shift_jis(data);
+    let _ = fix_title_encoding(&decoded);
+    let _ = fix_artist_encoding(&decoded);
+});
@@ -0,0 +1,12 @@
+//! Song entries in both layouts, at every offset of the input
+#![no_main]
+
+use infst::chart::{SongEntryLayout, SongInfo};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = SongInfo::parse_from_buffer(data, 0);
+    for layout in [SongEntryLayout::CURRENT, SongEntryLayout::LEGACY_3F0] {
+        let _ = SongInfo::parse_with_layout(data, 0, &layout);
+    }
+});
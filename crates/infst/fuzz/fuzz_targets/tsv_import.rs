@@ -0,0 +1,14 @@
+//! Tracker TSV song list and session TSV imports
+#![no_main]
+
+use std::collections::HashMap;
+
+use infst::chart::parse_song_database_tsv;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_song_database_tsv(data);
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = infst::parse_session_tsv(text, &HashMap::new());
+    }
+});
@@ -0,0 +1,191 @@
+//! Song database drift detection with anchor songs
+//!
+//! When a game update moves the strings inside song entries, the parser
+//! keeps producing songs, but with garbage titles. A few anchor songs whose
+//! titles are known are compared with what was parsed; titles far from the
+//! expected ones mean the song entry layout drifted, and the song database
+//! must not be used.
+//!
+//! Titles are compared by edit distance so that small differences (encoding
+//! fixes, spacing) still pass. Anchors missing from the database are
+//! skipped; readiness checks cover songs that aren't loaded yet.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::chart::SongInfo;
+use crate::error::{Error, Result};
+
+/// Songs whose titles are known not to change between game versions
+pub const DEFAULT_ANCHOR_SONGS: &[(u32, &str)] = &[(1000, "5.1.1."), (80003, "READY FOR TAKEOFF")];
+
+/// Minimum [`title_similarity`] for a parsed title to match its anchor
+pub const ANCHOR_MIN_SIMILARITY: f64 = 0.75;
+
+/// Levenshtein distance between two strings, in characters
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Similarity of two titles from 0.0 (nothing in common) to 1.0 (equal),
+/// ignoring case and surrounding whitespace
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    let a = a.trim().to_lowercase();
+    let b = b.trim().to_lowercase();
+    let len = a.chars().count().max(b.chars().count());
+    if len == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / len as f64
+}
+
+/// An anchor song whose parsed title doesn't match
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnchorMismatch {
+    pub song_id: u32,
+    pub expected: String,
+    pub found: String,
+    pub similarity: f64,
+}
+
+impl fmt::Display for AnchorMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "song {}: expected \"{}\", found \"{}\" ({:.0}% similar)",
+            self.song_id,
+            self.expected,
+            self.found.escape_debug(),
+            self.similarity * 100.0
+        )
+    }
+}
+
+/// Result of [`check_anchor_songs`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AnchorReport {
+    /// Anchors found in the song database
+    pub checked: usize,
+    pub mismatches: Vec<AnchorMismatch>,
+}
+
+impl AnchorReport {
+    /// Whether any anchor title is off, i.e. the song entry layout probably drifted
+    pub fn is_drift(&self) -> bool {
+        !self.mismatches.is_empty()
+    }
+
+    /// Error describing the drift, if any
+    pub fn ensure_no_drift(&self) -> Result<()> {
+        if !self.is_drift() {
+            return Ok(());
+        }
+        let details: Vec<String> = self.mismatches.iter().map(ToString::to_string).collect();
+        Err(Error::song_layout_drift(details.join("; ")))
+    }
+}
+
+/// Compare the titles of `anchors` (song ID, expected title) with `song_db`
+pub fn check_anchor_songs(
+    song_db: &HashMap<u32, SongInfo>,
+    anchors: &[(u32, &str)],
+) -> AnchorReport {
+    let mut report = AnchorReport::default();
+    for &(song_id, expected) in anchors {
+        let Some(song) = song_db.get(&song_id) else {
+            continue;
+        };
+        report.checked += 1;
+        let similarity = title_similarity(expected, &song.title);
+        if similarity < ANCHOR_MIN_SIMILARITY {
+            report.mismatches.push(AnchorMismatch {
+                song_id,
+                expected: expected.to_string(),
+                found: song.title.to_string(),
+                similarity,
+            });
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCategory;
+    use std::sync::Arc;
+
+    fn song_db(titles: &[(u32, &str)]) -> HashMap<u32, SongInfo> {
+        titles
+            .iter()
+            .map(|&(id, title)| {
+                (
+                    id,
+                    SongInfo {
+                        id,
+                        title: Arc::from(title),
+                        ..SongInfo::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("冥", "冥"), 0);
+        assert_eq!(levenshtein("冥", ""), 1);
+        assert_eq!(levenshtein("flaw", "lawn"), 2);
+    }
+
+    #[test]
+    fn test_title_similarity() {
+        assert_eq!(title_similarity("5.1.1.", " 5.1.1. "), 1.0);
+        assert_eq!(title_similarity("GAMBOL", "gambol"), 1.0);
+        assert!(title_similarity("READY FOR TAKEOFF", "READY FOR TAKE OFF") > 0.9);
+        assert!(title_similarity("READY FOR TAKEOFF", "\u{1}\u{7f}ﾃﾞ") < 0.2);
+    }
+
+    #[test]
+    fn test_anchor_songs_match() {
+        let db = song_db(&[(1000, "5.1.1."), (80003, "READY FOR TAKEOFF"), (1001, "x")]);
+        let report = check_anchor_songs(&db, DEFAULT_ANCHOR_SONGS);
+        assert_eq!(report.checked, 2);
+        assert!(!report.is_drift());
+        assert!(report.ensure_no_drift().is_ok());
+
+        // Missing anchors are skipped
+        let report = check_anchor_songs(&song_db(&[(1001, "x")]), DEFAULT_ANCHOR_SONGS);
+        assert_eq!(report.checked, 0);
+        assert!(!report.is_drift());
+    }
+
+    #[test]
+    fn test_garbage_titles_are_drift() {
+        let db = song_db(&[(1000, "5.1.1."), (80003, "ｩ\u{3}ﾞ@@")]);
+        let report = check_anchor_songs(&db, DEFAULT_ANCHOR_SONGS);
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].song_id, 80003);
+
+        let err = report.ensure_no_drift().unwrap_err();
+        assert_eq!(err.category(), ErrorCategory::OffsetSearch);
+        assert!(!err.is_retryable());
+        assert!(err.to_string().contains("song 80003"));
+    }
+}
@@ -0,0 +1,71 @@
+//! User-defined song labels (`customtypes.txt`)
+//!
+//! Each line maps a song ID to a free-form label, separated by a comma or tab:
+//!
+//! ```text
+//! # song_id,label
+//! 1000,DJT
+//! 25085,Bits
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::error::Result;
+
+/// Song labels keyed by song ID
+#[derive(Debug, Clone, Default)]
+pub struct CustomTypes {
+    labels: HashMap<u32, String>,
+}
+
+impl CustomTypes {
+    /// Parse a custom types file's content
+    ///
+    /// Blank lines, `#` comments and lines without a valid song ID are skipped.
+    pub fn parse(content: &str) -> Self {
+        let labels = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (id, label) = line.split_once([',', '\t'])?;
+                let id = id.trim().parse().ok()?;
+                Some((id, label.trim().to_string()))
+            })
+            .collect();
+        Self { labels }
+    }
+
+    /// Load a custom types file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    pub fn get(&self, song_id: u32) -> Option<&str> {
+        self.labels.get(&song_id).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_custom_types() {
+        let types = CustomTypes::parse("# comment\n1000,DJT\n1001\tLeggendaria\n\nabc,Bad\n");
+        assert_eq!(types.len(), 2);
+        assert_eq!(types.get(1000), Some("DJT"));
+        assert_eq!(types.get(1001), Some("Leggendaria"));
+        assert_eq!(types.get(1002), None);
+    }
+}
@@ -0,0 +1,279 @@
+//! Note density profiles of charts
+//!
+//! A profile counts a chart's notes in equal-length time sections. The song
+//! list in game memory only carries note totals per chart; note timing lives
+//! in the chart files the game loads for play and is gone from memory by the
+//! result screen, so profiles come from an external density file keyed by
+//! chart (`song_id:DIFFICULTY`). An entry gives either the notes per section
+//! or the time of every note, from which sections are counted:
+//!
+//! ```json
+//! {
+//!   "1000:SPA": { "section_ms": 1000, "notes": [4, 6, 12, 15, 7] },
+//!   "25096:SPA": { "note_times_ms": [500, 750, 1000, 1125, 1250] }
+//! }
+//! ```
+//!
+//! Whatever the source, consumers only see [`DensityProfile`] and
+//! [`DensityMap`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::ChartId;
+use crate::error::{Error, Result};
+
+/// Section length used when an entry gives note times without `section_ms`
+pub const DEFAULT_SECTION_MS: u32 = 1000;
+
+/// A section is dense when it has at least this many times the chart's
+/// average notes per section
+pub const DENSE_SECTION_FACTOR: f64 = 1.5;
+
+/// Notes of a chart counted in equal-length time sections
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DensityProfile {
+    /// Length of each section in milliseconds
+    #[schemars(range(min = 1))]
+    pub section_ms: u32,
+    /// Notes in each section, in chart order
+    pub notes: Vec<u32>,
+}
+
+impl DensityProfile {
+    /// Count note times (milliseconds from the chart start, in any order)
+    /// into sections of `section_ms`
+    pub fn from_note_times(note_times_ms: &[u32], section_ms: u32) -> Self {
+        let section_ms = section_ms.max(1);
+        let mut notes = Vec::new();
+        for &time in note_times_ms {
+            let section = (time / section_ms) as usize;
+            if notes.len() <= section {
+                notes.resize(section + 1, 0);
+            }
+            notes[section] += 1;
+        }
+        Self { section_ms, notes }
+    }
+
+    pub fn total_notes(&self) -> u32 {
+        self.notes.iter().sum()
+    }
+
+    /// Average notes per section (0 without sections)
+    pub fn average(&self) -> f64 {
+        if self.notes.is_empty() {
+            return 0.0;
+        }
+        f64::from(self.total_notes()) / self.notes.len() as f64
+    }
+
+    /// Notes per second of the densest section
+    pub fn peak_notes_per_second(&self) -> f64 {
+        let peak = self.notes.iter().copied().max().unwrap_or(0);
+        f64::from(peak) * 1000.0 / f64::from(self.section_ms.max(1))
+    }
+
+    /// Whether a section has at least [`DENSE_SECTION_FACTOR`] times the
+    /// average notes
+    pub fn is_dense(&self, section: usize) -> bool {
+        self.notes.get(section).is_some_and(|&notes| {
+            notes > 0 && f64::from(notes) >= self.average() * DENSE_SECTION_FACTOR
+        })
+    }
+
+    /// Indices of the dense sections
+    pub fn dense_sections(&self) -> Vec<usize> {
+        (0..self.notes.len())
+            .filter(|&i| self.is_dense(i))
+            .collect()
+    }
+
+    /// Share of the chart's notes that are in dense sections (0.0 - 1.0)
+    pub fn dense_note_share(&self) -> f64 {
+        let total = self.total_notes();
+        if total == 0 {
+            return 0.0;
+        }
+        let dense: u32 = self
+            .dense_sections()
+            .into_iter()
+            .map(|i| self.notes[i])
+            .sum();
+        f64::from(dense) / f64::from(total)
+    }
+
+    /// Section holding the note at `percent` of the chart's notes, e.g. the
+    /// section a play failed in from
+    /// [`PlayData::failed_at_percent`](crate::play::PlayData::failed_at_percent)
+    pub fn section_at_note_percent(&self, percent: f64) -> Option<usize> {
+        let total = self.total_notes();
+        if total == 0 || !(0.0..=100.0).contains(&percent) {
+            return None;
+        }
+        let target = ((percent / 100.0 * f64::from(total)).ceil() as u32).max(1);
+        let mut seen = 0;
+        self.notes.iter().position(|&notes| {
+            seen += notes;
+            seen >= target
+        })
+    }
+}
+
+/// A density file entry: section counts, or note times to count
+#[derive(Debug, Deserialize)]
+struct DensityEntry {
+    section_ms: Option<u32>,
+    notes: Option<Vec<u32>>,
+    note_times_ms: Option<Vec<u32>>,
+}
+
+/// Density profiles by chart
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DensityMap {
+    charts: HashMap<ChartId, DensityProfile>,
+}
+
+impl DensityMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a density file's content
+    pub fn from_json(content: &str) -> Result<Self> {
+        let raw: BTreeMap<String, DensityEntry> = serde_json::from_str(content)?;
+        let mut map = Self::new();
+        for (key, entry) in raw {
+            let chart: ChartId = key.parse()?;
+            let section_ms = entry.section_ms.unwrap_or(DEFAULT_SECTION_MS);
+            if section_ms == 0 {
+                return Err(Error::parse(
+                    "chart density",
+                    format!("{}: section_ms must be at least 1", key),
+                ));
+            }
+            let profile = match (entry.notes, entry.note_times_ms) {
+                (Some(notes), None) => DensityProfile { section_ms, notes },
+                (None, Some(times)) => DensityProfile::from_note_times(&times, section_ms),
+                _ => {
+                    return Err(Error::parse(
+                        "chart density",
+                        format!("{}: expected one of notes or note_times_ms", key),
+                    ));
+                }
+            };
+            map.insert(chart, profile);
+        }
+        Ok(map)
+    }
+
+    /// Load a density file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| Error::storage(path, e))?;
+        Self::from_json(&content)
+    }
+
+    pub fn insert(&mut self, chart: ChartId, profile: DensityProfile) {
+        self.charts.insert(chart, profile);
+    }
+
+    pub fn get(&self, chart: ChartId) -> Option<&DensityProfile> {
+        self.charts.get(&chart)
+    }
+
+    pub fn len(&self) -> usize {
+        self.charts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.charts.is_empty()
+    }
+
+    /// Serialize to the density file format (section counts, sorted by chart)
+    pub fn to_json(&self) -> Result<String> {
+        let sorted: BTreeMap<String, &DensityProfile> = self
+            .charts
+            .iter()
+            .map(|(chart, profile)| (chart.to_string(), profile))
+            .collect();
+        Ok(serde_json::to_string_pretty(&sorted)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::Difficulty;
+
+    fn profile(notes: &[u32]) -> DensityProfile {
+        DensityProfile {
+            section_ms: 1000,
+            notes: notes.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_from_note_times() {
+        let profile = DensityProfile::from_note_times(&[2500, 100, 900, 1000], 1000);
+        assert_eq!(profile.notes, vec![2, 1, 1]);
+        assert_eq!(profile.total_notes(), 4);
+    }
+
+    #[test]
+    fn test_dense_sections() {
+        let profile = profile(&[4, 4, 12, 4, 0]);
+        // Average 4.8, dense from 7.2
+        assert_eq!(profile.dense_sections(), vec![2]);
+        assert!((profile.dense_note_share() - 0.5).abs() < 1e-9);
+        assert!((profile.peak_notes_per_second() - 12.0).abs() < 1e-9);
+        assert!(!profile.is_dense(9));
+    }
+
+    #[test]
+    fn test_section_at_note_percent() {
+        let profile = profile(&[10, 0, 30, 60]);
+        assert_eq!(profile.section_at_note_percent(0.0), Some(0));
+        assert_eq!(profile.section_at_note_percent(10.0), Some(0));
+        assert_eq!(profile.section_at_note_percent(10.5), Some(2));
+        assert_eq!(profile.section_at_note_percent(100.0), Some(3));
+        assert_eq!(profile.section_at_note_percent(101.0), None);
+        assert_eq!(
+            DensityProfile::from_note_times(&[], 1000).section_at_note_percent(50.0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_density_map_from_json() {
+        let map = DensityMap::from_json(
+            r#"{
+                "1000:SPA": { "section_ms": 500, "notes": [1, 2, 3] },
+                "1001:sph": { "note_times_ms": [0, 1500, 1600] }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(map.len(), 2);
+        let spa = map.get(ChartId::new(1000, Difficulty::SpA)).unwrap();
+        assert_eq!((spa.section_ms, spa.notes.clone()), (500, vec![1, 2, 3]));
+        let sph = map.get(ChartId::new(1001, Difficulty::SpH)).unwrap();
+        assert_eq!(sph.notes, vec![1, 2]);
+
+        // Round trip through the section count form
+        assert_eq!(DensityMap::from_json(&map.to_json().unwrap()).unwrap(), map);
+    }
+
+    #[test]
+    fn test_density_map_rejects_bad_entries() {
+        assert!(DensityMap::from_json(r#"{"1000": {"notes": [1]}}"#).is_err());
+        assert!(DensityMap::from_json(r#"{"1000:SPA": {}}"#).is_err());
+        assert!(
+            DensityMap::from_json(r#"{"1000:SPA": {"notes": [1], "note_times_ms": [0]}}"#).is_err()
+        );
+        assert!(DensityMap::from_json(r#"{"1000:SPA": {"section_ms": 0, "notes": [1]}}"#).is_err());
+    }
+}
@@ -8,6 +8,8 @@ use strum::{EnumString, FromRepr, IntoStaticStr};
     PartialEq,
     Eq,
     Hash,
+    PartialOrd,
+    Ord,
     Serialize,
     Deserialize,
     FromRepr,
@@ -39,10 +41,48 @@ pub enum Difficulty {
 }
 
 impl Difficulty {
+    /// Every difficulty, in per-difficulty array order
+    pub const ALL: [Self; 10] = [
+        Self::SpB,
+        Self::SpN,
+        Self::SpH,
+        Self::SpA,
+        Self::SpL,
+        Self::DpB,
+        Self::DpN,
+        Self::DpH,
+        Self::DpA,
+        Self::DpL,
+    ];
+
     pub fn from_u8(value: u8) -> Option<Self> {
         Self::from_repr(value)
     }
 
+    /// Difficulty from a raw `i32` read from memory (out of range is `None`)
+    pub fn from_raw(value: i32) -> Option<Self> {
+        u8::try_from(value).ok().and_then(Self::from_repr)
+    }
+
+    /// Difficulty at a per-difficulty array index
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    /// Difficulty from a tier (0 = beginner .. 4 = leggendaria) and play
+    /// type (0 = SP, 1 = DP), as stored in score map nodes
+    pub fn from_tier(tier: i32, play_type: i32) -> Option<Self> {
+        match (tier, play_type) {
+            (0..5, 0..2) => Self::from_raw(tier + play_type * 5),
+            _ => None,
+        }
+    }
+
+    /// Index into per-difficulty arrays
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
     pub fn is_sp(&self) -> bool {
         matches!(
             self,
@@ -100,6 +140,29 @@ mod tests {
         assert_eq!(Difficulty::from_u8(10), None);
     }
 
+    #[test]
+    fn test_difficulty_index_roundtrip() {
+        for (index, difficulty) in Difficulty::ALL.into_iter().enumerate() {
+            assert_eq!(difficulty.index(), index);
+            assert_eq!(Difficulty::from_index(index), Some(difficulty));
+            assert_eq!(Difficulty::from_raw(index as i32), Some(difficulty));
+        }
+        assert_eq!(Difficulty::from_index(10), None);
+        assert_eq!(Difficulty::from_raw(-1), None);
+        assert_eq!(Difficulty::from_raw(256 + 3), None);
+    }
+
+    #[test]
+    fn test_difficulty_from_tier() {
+        assert_eq!(Difficulty::from_tier(0, 0), Some(Difficulty::SpB));
+        assert_eq!(Difficulty::from_tier(3, 0), Some(Difficulty::SpA));
+        assert_eq!(Difficulty::from_tier(0, 1), Some(Difficulty::DpB));
+        assert_eq!(Difficulty::from_tier(3, 1), Some(Difficulty::DpA));
+        assert_eq!(Difficulty::from_tier(5, 0), None);
+        assert_eq!(Difficulty::from_tier(1, 2), None);
+        assert_eq!(Difficulty::from_tier(-1, 0), None);
+    }
+
     #[test]
     fn test_difficulty_is_sp_dp() {
         assert!(Difficulty::SpN.is_sp());
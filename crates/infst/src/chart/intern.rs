@@ -0,0 +1,198 @@
+//! String interning for song metadata
+//!
+//! Genres, artists and BPM strings repeat across many songs, and the same
+//! titles are decoded again on every song list rescan and history load. All
+//! metadata strings go through a process-wide pool so equal strings share a
+//! single allocation; `ChartInfo` and play data then clone the shared `Arc`.
+//!
+//! The pool only keeps strings alive while something else holds them: each
+//! time it doubles in size, strings nothing outside the pool references any
+//! more are dropped. Garbage decoded from memory scans and rejected entries
+//! is therefore freed along with the entries.
+
+use std::collections::HashSet;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use serde::{Deserialize, Deserializer};
+
+/// Pool size below which unreferenced strings are not purged
+const MIN_PURGE_LEN: usize = 1024;
+
+/// A set of shared strings
+#[derive(Debug, Default)]
+pub struct StringPool {
+    strings: Mutex<Strings>,
+}
+
+#[derive(Debug, Default)]
+struct Strings {
+    set: HashSet<Arc<str>>,
+    /// Size at which the next purge runs
+    purge_at: usize,
+}
+
+impl Strings {
+    /// Add `s` and return it, purging unreferenced strings when due
+    fn insert(&mut self, s: Arc<str>) -> Arc<str> {
+        self.set.insert(s.clone());
+        if self.set.len() >= self.purge_at {
+            self.purge();
+        }
+        s
+    }
+
+    /// Drop strings only the pool references; returns how many were dropped
+    fn purge(&mut self) -> usize {
+        let before = self.set.len();
+        self.set.retain(|s| Arc::strong_count(s) > 1);
+        self.purge_at = (self.set.len() * 2).max(MIN_PURGE_LEN);
+        before - self.set.len()
+    }
+}
+
+/// Size of a [`StringPool`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Distinct strings held
+    pub strings: usize,
+    /// Total bytes of string data held
+    pub bytes: usize,
+}
+
+impl StringPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the pooled copy of `s`, adding it if new
+    pub fn intern(&self, s: &str) -> Arc<str> {
+        let mut strings = self.lock();
+        if let Some(existing) = strings.set.get(s) {
+            return existing.clone();
+        }
+        strings.insert(Arc::from(s))
+    }
+
+    /// Like [`Self::intern`], but keeps `s`'s allocation when it is new
+    pub fn intern_arc(&self, s: Arc<str>) -> Arc<str> {
+        let mut strings = self.lock();
+        if let Some(existing) = strings.set.get(&*s) {
+            return existing.clone();
+        }
+        strings.insert(s)
+    }
+
+    /// Drop the strings nothing outside the pool references any more
+    ///
+    /// Runs automatically as the pool grows; returns how many were dropped.
+    pub fn purge(&self) -> usize {
+        self.lock().purge()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().set.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().set.is_empty()
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        let strings = self.lock();
+        PoolStats {
+            strings: strings.set.len(),
+            bytes: strings.set.iter().map(|s| s.len()).sum(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Strings> {
+        // The set stays consistent even if a holder panicked
+        self.strings.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+static POOL: LazyLock<StringPool> = LazyLock::new(StringPool::new);
+
+/// Intern `s` in the global metadata pool
+pub fn intern(s: &str) -> Arc<str> {
+    POOL.intern(s)
+}
+
+/// Intern an already allocated string in the global metadata pool
+pub fn intern_arc(s: Arc<str>) -> Arc<str> {
+    POOL.intern_arc(s)
+}
+
+/// Size of the global metadata pool
+pub fn pool_stats() -> PoolStats {
+    POOL.stats()
+}
+
+/// Drop the strings of the global metadata pool nothing references any more
+pub fn purge_pool() -> usize {
+    POOL.purge()
+}
+
+/// Serde `deserialize_with` helper that interns the decoded string
+pub fn deserialize_interned<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Arc<str>, D::Error> {
+    let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+    Ok(intern(&s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_shares_allocation() {
+        let pool = StringPool::new();
+        let a = pool.intern("GENRE");
+        let b = pool.intern(&String::from("GENRE"));
+        assert!(Arc::ptr_eq(&a, &b));
+
+        let owned: Arc<str> = Arc::from("ARTIST");
+        let c = pool.intern_arc(owned.clone());
+        assert!(Arc::ptr_eq(&c, &owned));
+        assert!(Arc::ptr_eq(&pool.intern("ARTIST"), &owned));
+
+        assert_eq!(
+            pool.stats(),
+            PoolStats {
+                strings: 2,
+                bytes: 11
+            }
+        );
+    }
+
+    #[test]
+    fn test_unreferenced_strings_are_purged() {
+        let pool = StringPool::new();
+        let kept = pool.intern("kept");
+        drop(pool.intern("garbage"));
+        assert_eq!(pool.purge(), 1);
+        assert_eq!(pool.len(), 1);
+        assert!(Arc::ptr_eq(&pool.intern("kept"), &kept));
+
+        // Growing past the threshold purges without an explicit call
+        for i in 0..MIN_PURGE_LEN * 3 {
+            pool.intern(&format!("scan garbage {i}"));
+        }
+        assert!(pool.len() < MIN_PURGE_LEN);
+        assert!(Arc::ptr_eq(&pool.intern("kept"), &kept));
+    }
+
+    #[test]
+    fn test_deserialize_interned() {
+        #[derive(Deserialize)]
+        struct Row {
+            #[serde(deserialize_with = "deserialize_interned")]
+            genre: Arc<str>,
+        }
+
+        let a: Row = serde_json::from_str(r#"{"genre":"intern-test-genre"}"#).unwrap();
+        let b: Row = serde_json::from_str(r#"{"genre":"intern-test-genre"}"#).unwrap();
+        assert!(Arc::ptr_eq(&a.genre, &b.genre));
+    }
+}
@@ -2,18 +2,36 @@
 //!
 //! This module contains types for representing charts (songs + difficulties):
 //! - `Difficulty` - difficulty levels (SPB, SPN, SPH, SPA, SPL, DPB, DPN, DPH, DPA, DPL)
-//! - `Chart`, `ChartInfo` - chart identifiers and metadata
+//! - `ChartId`, `ChartInfo` - chart identifiers and metadata
 //! - `SongInfo` - song metadata
 //! - `UnlockData` - unlock state management
+//! - `CustomTypes` - user-defined song labels
+//! - `intern` - shared pool for song metadata strings
+//! - `verify_song_database` - cross-check against a canonical song list
+//! - `search_charts` - chart search by title, level and play style
+//! - `check_anchor_songs` - song entry layout drift detection
+//! - `DensityProfile`, `DensityMap` - note density per time section (from a density file)
 
+mod anchors;
+mod custom_types;
+pub mod density;
 mod difficulty;
 mod encoding_fixes;
+pub mod intern;
+mod search;
 mod song;
 mod types;
 mod unlock;
+mod verify;
 
+pub use anchors::*;
+pub use custom_types::*;
+pub use density::{DensityMap, DensityProfile};
 pub use difficulty::*;
 pub use encoding_fixes::*;
+pub use intern::{StringPool, intern};
+pub use search::*;
 pub use song::*;
 pub use types::*;
 pub use unlock::*;
+pub use verify::*;
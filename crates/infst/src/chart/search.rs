@@ -0,0 +1,186 @@
+//! Chart search over a song database
+//!
+//! Finds charts by title text, level and play style. The text matches the
+//! in-game title, the English title or the artist (case-insensitive), or a
+//! song ID exactly.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::chart::{ChartId, Difficulty, SongInfo};
+use crate::export::PlayStyle;
+
+/// Criteria for [`search_charts`]; the default query matches every chart
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChartQuery {
+    /// Text in the title, English title or artist, or a song ID
+    pub text: Option<String>,
+    /// Minimum chart level (inclusive)
+    pub min_level: Option<u8>,
+    /// Maximum chart level (inclusive)
+    pub max_level: Option<u8>,
+    /// Restrict to SP or DP charts
+    pub style: Option<PlayStyle>,
+}
+
+impl ChartQuery {
+    /// Query for charts whose song matches `text`
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Restrict to exactly `level`
+    pub fn level(mut self, level: u8) -> Self {
+        self.min_level = Some(level);
+        self.max_level = Some(level);
+        self
+    }
+
+    /// Whether the song matches the text criterion
+    pub fn matches_song(&self, song: &SongInfo) -> bool {
+        let Some(text) = self.text.as_deref().map(str::trim) else {
+            return true;
+        };
+        if text.is_empty() || text.parse::<u32>() == Ok(song.id) {
+            return true;
+        }
+        let needle = text.to_lowercase();
+        [&song.title, &song.title_english, &song.artist]
+            .iter()
+            .any(|field| field.to_lowercase().contains(&needle))
+    }
+
+    /// Whether an existing chart of `song` matches every criterion
+    pub fn matches_chart(&self, song: &SongInfo, difficulty: Difficulty) -> bool {
+        if !ChartId::new(song.id, difficulty).exists_in(song) {
+            return false;
+        }
+        let level = song.level_for(difficulty);
+        if self.min_level.is_some_and(|min| level < min)
+            || self.max_level.is_some_and(|max| level > max)
+        {
+            return false;
+        }
+        let style_matches = match self.style {
+            Some(PlayStyle::Sp) => difficulty.is_sp(),
+            Some(PlayStyle::Dp) => !difficulty.is_sp(),
+            None => true,
+        };
+        style_matches && self.matches_song(song)
+    }
+}
+
+/// A chart found by [`search_charts`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChartMatch {
+    pub chart: ChartId,
+    pub title: String,
+    pub artist: String,
+    pub level: u8,
+    pub total_notes: u32,
+}
+
+/// Charts in `song_db` matching `query`, by title then difficulty
+pub fn search_charts(song_db: &HashMap<u32, SongInfo>, query: &ChartQuery) -> Vec<ChartMatch> {
+    let mut matches: Vec<ChartMatch> = song_db
+        .values()
+        .filter(|song| query.matches_song(song))
+        .flat_map(|song| {
+            Difficulty::ALL
+                .into_iter()
+                .filter(|&difficulty| query.matches_chart(song, difficulty))
+                .map(|difficulty| ChartMatch {
+                    chart: ChartId::new(song.id, difficulty),
+                    title: song.title.to_string(),
+                    artist: song.artist.to_string(),
+                    level: song.level_for(difficulty),
+                    total_notes: song.total_notes_for(difficulty),
+                })
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        a.title
+            .to_lowercase()
+            .cmp(&b.title.to_lowercase())
+            .then(a.chart.cmp(&b.chart))
+    });
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::play::UnlockType;
+    use std::sync::Arc;
+
+    fn song(id: u32, title: &str, artist: &str) -> SongInfo {
+        SongInfo {
+            id,
+            title: Arc::from(title),
+            title_english: Arc::from(""),
+            artist: Arc::from(artist),
+            genre: Arc::from(""),
+            bpm: Arc::from("150"),
+            folder: 1,
+            levels: [0, 4, 9, 12, 0, 0, 5, 10, 12, 0],
+            total_notes: [0, 400, 900, 1500, 0, 0, 420, 950, 1600, 0],
+            unlock_type: UnlockType::Base,
+        }
+    }
+
+    fn song_db() -> HashMap<u32, SongInfo> {
+        HashMap::from([
+            (1000, song(1000, "GIGADELIC", "テラ vs 戸田")),
+            (1001, song(1001, "Verflucht", "Zektbach")),
+            (1002, song(1002, "gigantic", "Someone")),
+        ])
+    }
+
+    #[test]
+    fn test_search_by_text_and_level() {
+        let query = ChartQuery::text("gigadelic").level(12);
+        let found = search_charts(&song_db(), &query);
+        let charts: Vec<ChartId> = found.iter().map(|m| m.chart).collect();
+        assert_eq!(
+            charts,
+            vec![
+                ChartId::new(1000, Difficulty::SpA),
+                ChartId::new(1000, Difficulty::DpA)
+            ]
+        );
+        assert_eq!(found[0].total_notes, 1500);
+
+        let query = ChartQuery {
+            style: Some(PlayStyle::Sp),
+            ..query
+        };
+        assert_eq!(search_charts(&song_db(), &query).len(), 1);
+    }
+
+    #[test]
+    fn test_search_matches_artist_and_id() {
+        let db = song_db();
+        let by_artist = search_charts(&db, &ChartQuery::text("zekt"));
+        assert!(by_artist.iter().all(|m| m.chart.song_id == 1001));
+        assert_eq!(by_artist.len(), 6);
+
+        let by_id = search_charts(&db, &ChartQuery::text("1002"));
+        assert!(by_id.iter().all(|m| m.chart.song_id == 1002));
+
+        // Sorted by title regardless of case
+        let giga = search_charts(&db, &ChartQuery::text("GIGA").level(4));
+        let titles: Vec<&str> = giga.iter().map(|m| m.title.as_str()).collect();
+        assert_eq!(titles, vec!["GIGADELIC", "gigantic"]);
+    }
+
+    #[test]
+    fn test_default_query_matches_existing_charts() {
+        let found = search_charts(&song_db(), &ChartQuery::default());
+        assert_eq!(found.len(), 18);
+        assert!(found.iter().all(|m| m.total_notes > 0));
+    }
+}
@@ -9,18 +9,64 @@ use tracing::{debug, info, warn};
 
 use crate::error::Result;
 use crate::play::UnlockType;
+use crate::process::layout::{Field, FieldKind};
 use crate::process::{ByteBuffer, ReadMemory, decode_shift_jis};
 
+use super::Difficulty;
 use super::encoding_fixes::{fix_artist_encoding, fix_title_encoding};
+use super::intern::{deserialize_interned, intern, intern_arc};
+
+/// Offsets of the numeric fields in a song entry
+///
+/// String fields (title, English title, genre, artist) sit at the same
+/// offsets in every known layout; builds from 2026012800 inserted three
+/// 64-byte fields after them, moving everything else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SongEntryLayout {
+    /// Size of one entry (stride of the song list)
+    pub entry_size: usize,
+    pub folder: usize,
+    pub levels: usize,
+    pub bpm: usize,
+    pub notes: usize,
+    pub song_id: usize,
+}
+
+impl SongEntryLayout {
+    /// Version 2026012800 and later (0x4B0 = 1200 bytes)
+    pub const CURRENT: Self = Self {
+        entry_size: SongInfo::MEMORY_SIZE,
+        folder: SongInfo::FOLDER_OFFSET,
+        levels: SongInfo::LEVELS_OFFSET,
+        bpm: SongInfo::BPM_OFFSET,
+        notes: SongInfo::NOTES_OFFSET,
+        song_id: SongInfo::SONG_ID_OFFSET,
+    };
+
+    /// Builds before 2026012800 (0x3F0 = 1008 bytes)
+    pub const LEGACY_3F0: Self = Self {
+        entry_size: 0x3F0,
+        folder: 280,
+        levels: 288,
+        bpm: 320,
+        notes: 432,
+        song_id: 624,
+    };
+}
 
 /// Song metadata
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SongInfo {
     pub id: u32,
+    #[serde(deserialize_with = "deserialize_interned")]
     pub title: Arc<str>,
+    #[serde(deserialize_with = "deserialize_interned")]
     pub title_english: Arc<str>,
+    #[serde(deserialize_with = "deserialize_interned")]
     pub artist: Arc<str>,
+    #[serde(deserialize_with = "deserialize_interned")]
     pub genre: Arc<str>,
+    #[serde(deserialize_with = "deserialize_interned")]
     pub bpm: Arc<str>,
     pub folder: i32,
     /// Level for each difficulty: SPB, SPN, SPH, SPA, SPL, DPB, DPN, DPH, DPA, DPL
@@ -66,6 +112,30 @@ impl SongInfo {
     const NOTES_OFFSET: usize = 624; // 40 bytes: 10 x i32 (estimated)
     const SONG_ID_OFFSET: usize = 816; // 4 bytes
 
+    /// Known fields as (offset, size, name, kind), for memory annotations and diffs
+    #[cfg_attr(not(feature = "debug-tools"), allow(dead_code))]
+    pub(crate) const FIELDS: &'static [Field] = &[
+        (Self::TITLE_OFFSET, Self::SLAB, "title", FieldKind::Text),
+        (
+            Self::TITLE_ENGLISH_OFFSET,
+            Self::SLAB,
+            "title_english",
+            FieldKind::Text,
+        ),
+        (Self::GENRE_OFFSET, Self::SLAB, "genre", FieldKind::Text),
+        (Self::ARTIST_OFFSET, Self::SLAB, "artist", FieldKind::Text),
+        (Self::FOLDER_OFFSET, 1, "folder", FieldKind::Bytes),
+        (Self::LEVELS_OFFSET, 10, "levels", FieldKind::Bytes),
+        (Self::BPM_OFFSET, Self::WORD * 2, "bpm", FieldKind::Int),
+        (
+            Self::NOTES_OFFSET,
+            Self::WORD * 10,
+            "total_notes",
+            FieldKind::Int,
+        ),
+        (Self::SONG_ID_OFFSET, Self::WORD, "song_id", FieldKind::Int),
+    ];
+
     /// Get level for a specific difficulty index
     pub fn get_level(&self, difficulty_index: usize) -> u8 {
         self.levels.get(difficulty_index).copied().unwrap_or(0)
@@ -76,20 +146,51 @@ impl SongInfo {
         self.total_notes.get(difficulty_index).copied().unwrap_or(0)
     }
 
+    pub fn level_for(&self, difficulty: Difficulty) -> u8 {
+        self.get_level(difficulty.index())
+    }
+
+    pub fn total_notes_for(&self, difficulty: Difficulty) -> u32 {
+        self.get_total_notes(difficulty.index())
+    }
+
     /// Parse song info from a pre-loaded buffer at the given offset.
     ///
     /// This is the buffer-based variant of `read_from_memory` that avoids
     /// individual ReadProcessMemory calls when the buffer has been bulk-loaded.
     pub fn parse_from_buffer(buffer: &[u8], offset: usize) -> Result<Option<Self>> {
-        if offset + Self::MEMORY_SIZE > buffer.len() {
-            return Ok(None);
+        match offset
+            .checked_add(Self::MEMORY_SIZE)
+            .and_then(|end| buffer.get(offset..end))
+        {
+            Some(entry) => Self::parse_entry(entry),
+            None => Ok(None),
+        }
+    }
+
+    /// Parse the entry at `offset` in `buffer` using an explicit layout
+    ///
+    /// Returns `Ok(None)` if the buffer is too short or the slot is empty.
+    pub fn parse_with_layout(
+        buffer: &[u8],
+        offset: usize,
+        layout: &SongEntryLayout,
+    ) -> Result<Option<Self>> {
+        match offset
+            .checked_add(layout.entry_size)
+            .and_then(|end| buffer.get(offset..end))
+        {
+            Some(entry) => Self::parse_entry_with_layout(entry, layout),
+            None => Ok(None),
         }
-        let entry = &buffer[offset..offset + Self::MEMORY_SIZE];
-        Self::parse_entry(entry)
     }
 
     /// Parse a single song entry from a MEMORY_SIZE-length slice
     fn parse_entry(entry: &[u8]) -> Result<Option<Self>> {
+        Self::parse_entry_with_layout(entry, &SongEntryLayout::CURRENT)
+    }
+
+    fn parse_entry_with_layout(entry: &[u8], layout: &SongEntryLayout) -> Result<Option<Self>> {
         let buf = ByteBuffer::new(entry);
 
         // Check if entry is valid (first 4 bytes should not be 0)
@@ -111,15 +212,15 @@ impl SongInfo {
         }
 
         // Parse folder (1 byte)
-        let folder = entry[Self::FOLDER_OFFSET] as i32;
+        let folder = buf.read_struct_at::<u8>(layout.folder)? as i32;
 
         // Parse difficulty levels (10 bytes)
         let mut levels = [0u8; 10];
-        levels.copy_from_slice(buf.slice_at(Self::LEVELS_OFFSET, 10)?);
+        levels.copy_from_slice(buf.slice_at(layout.levels, 10)?);
 
         // Parse BPM (8 bytes: max, min)
-        let bpm_max = buf.read_i32_at(Self::BPM_OFFSET)?;
-        let bpm_min = buf.read_i32_at(Self::BPM_OFFSET + Self::WORD)?;
+        let bpm_max = buf.read_i32_at(layout.bpm)?;
+        let bpm_min = buf.read_i32_at(layout.bpm + Self::WORD)?;
 
         let bpm: Arc<str> = if bpm_min != 0 && bpm_min != bpm_max {
             format!("{:03}~{:03}", bpm_min, bpm_max).into()
@@ -130,19 +231,19 @@ impl SongInfo {
         // Parse note counts (40 bytes = 10 x i32)
         let mut total_notes = [0u32; 10];
         for (i, note_count) in total_notes.iter_mut().enumerate() {
-            *note_count = buf.read_u32_at(Self::NOTES_OFFSET + i * Self::WORD)?;
+            *note_count = buf.read_u32_at(layout.notes + i * Self::WORD)?;
         }
 
         // Parse song ID (4 bytes)
-        let song_id = buf.read_i32_at(Self::SONG_ID_OFFSET)?;
+        let song_id = buf.read_i32_at(layout.song_id)?;
 
         Ok(Some(SongInfo {
             id: song_id as u32,
-            title,
-            title_english,
-            artist,
-            genre,
-            bpm,
+            title: intern_arc(title),
+            title_english: intern_arc(title_english),
+            artist: intern_arc(artist),
+            genre: intern_arc(genre),
+            bpm: intern_arc(bpm),
             folder,
             levels,
             total_notes,
@@ -175,7 +276,17 @@ impl SongInfo {
     ) -> Result<Option<Self>> {
         // First, try standard read
         let result = Self::read_from_memory(reader, text_address)?;
+        Self::apply_metadata_fallback(reader, result, text_base, entry_index)
+    }
 
+    /// Resolve a missing song_id from the metadata table (see
+    /// [`Self::read_from_memory_with_fallback`])
+    fn apply_metadata_fallback<R: ReadMemory>(
+        reader: &R,
+        result: Option<Self>,
+        text_base: u64,
+        entry_index: u64,
+    ) -> Result<Option<Self>> {
         match result {
             Some(mut song) if song.id == 0 && !song.title.is_empty() => {
                 // Try to read song_id from metadata table
@@ -286,6 +397,26 @@ pub fn analyze_metadata_table<R: ReadMemory>(reader: &R, text_base: u64) {
     }
 }
 
+/// Song table entries fetched per `read_many` batch
+const ENTRY_BATCH: u64 = 64;
+
+/// Read `len` bytes at `offset` within each of the table entries
+/// `indices` (`stride` bytes apart), batched so neighbouring entries share
+/// one read
+fn read_entry_batch<R: ReadMemory>(
+    reader: &R,
+    table_base: u64,
+    indices: std::ops::Range<u64>,
+    stride: usize,
+    offset: u64,
+    len: usize,
+) -> Vec<Result<Vec<u8>>> {
+    let ranges: Vec<(u64, usize)> = indices
+        .map(|i| (table_base + i * stride as u64 + offset, len))
+        .collect();
+    reader.read_many(&ranges)
+}
+
 /// Build a song_id to title mapping by scanning metadata table
 ///
 /// For new INFINITAS versions (2026012800+), the title is located 0x7E0 bytes
@@ -308,48 +439,66 @@ pub fn build_song_id_title_map<R: ReadMemory>(
 
     // Note: With lazy loading, songs may be scattered across the entry table.
     // We scan all entries without early termination to find all loaded songs.
-    for i in 0..max_entries {
-        let text_addr = text_base + i * ENTRY_SIZE;
-        let meta_addr = text_addr + METADATA_OFFSET;
+    for batch_start in (0..max_entries).step_by(ENTRY_BATCH as usize) {
+        let batch = batch_start..(batch_start + ENTRY_BATCH).min(max_entries);
 
-        // Read metadata
-        let Ok(meta_bytes) = reader.read_bytes(meta_addr, 8) else {
-            continue;
-        };
-
-        let buf = ByteBuffer::new(&meta_bytes);
-        let song_id = buf.read_i32_at(0).unwrap_or(0);
-        let folder = buf.read_i32_at(4).unwrap_or(0);
-
-        // Validate song_id and folder ranges
-        // Note: folder values vary widely in new INFINITAS versions (e.g., 1-200+)
-        if !(1000..=90000).contains(&song_id) || !(1..=200).contains(&folder) {
-            continue;
-        }
-
-        // Skip if we already have this song_id
-        if result.contains_key(&(song_id as u32)) {
-            continue;
+        // Read metadata for the whole batch, keeping entries with a plausible song_id
+        let mut candidates = Vec::new();
+        let metas = read_entry_batch(
+            reader,
+            text_base,
+            batch.clone(),
+            SongInfo::MEMORY_SIZE,
+            METADATA_OFFSET,
+            8,
+        );
+        for (i, meta_bytes) in batch.zip(metas) {
+            let Ok(meta_bytes) = meta_bytes else {
+                continue;
+            };
+
+            let buf = ByteBuffer::new(&meta_bytes);
+            let song_id = buf.read_i32_at(0).unwrap_or(0);
+            let folder = buf.read_i32_at(4).unwrap_or(0);
+
+            // Validate song_id and folder ranges
+            // Note: folder values vary widely in new INFINITAS versions (e.g., 1-200+)
+            if !(1000..=90000).contains(&song_id) || !(1..=200).contains(&folder) {
+                continue;
+            }
+            candidates.push((text_base + i * ENTRY_SIZE, song_id, folder));
         }
 
-        // Read title from text table
-        if let Ok(title_bytes) = reader.read_bytes(text_addr, 64) {
-            let mut title_arc = decode_shift_jis(&title_bytes);
-            if let Some(fixed) = fix_title_encoding(&title_arc) {
-                title_arc = fixed;
+        // Read titles from the text table
+        let title_ranges: Vec<(u64, usize)> = candidates
+            .iter()
+            .map(|&(text_addr, _, _)| (text_addr, 64))
+            .collect();
+        let titles = reader.read_many(&title_ranges);
+        for ((_, song_id, folder), title_bytes) in candidates.into_iter().zip(titles) {
+            // Skip if we already have this song_id
+            if result.contains_key(&(song_id as u32)) {
+                continue;
             }
-            let title = title_arc.trim();
-            if !title.is_empty()
-                && title
-                    .chars()
-                    .next()
-                    .is_some_and(|c| c.is_ascii_graphic() || !c.is_ascii())
-            {
-                debug!(
-                    "Mapped song_id={} to title={:?} (folder={})",
-                    song_id, title, folder
-                );
-                result.insert(song_id as u32, Arc::from(title));
+
+            if let Ok(title_bytes) = title_bytes {
+                let mut title_arc = decode_shift_jis(&title_bytes);
+                if let Some(fixed) = fix_title_encoding(&title_arc) {
+                    title_arc = fixed;
+                }
+                let title = title_arc.trim();
+                if !title.is_empty()
+                    && title
+                        .chars()
+                        .next()
+                        .is_some_and(|c| c.is_ascii_graphic() || !c.is_ascii())
+                {
+                    debug!(
+                        "Mapped song_id={} to title={:?} (folder={})",
+                        song_id, title, folder
+                    );
+                    result.insert(song_id as u32, intern(title));
+                }
             }
         }
     }
@@ -448,17 +597,29 @@ pub fn fetch_song_database<R: ReadMemory>(
     let mut entry_index: u64 = 0;
     let mut consecutive_failures = 0;
     const MAX_CONSECUTIVE_FAILURES: u32 = 10;
+    const MAX_ENTRY_INDEX: u64 = 5000;
 
+    // Entries are read a batch at a time but still processed in order
+    let mut batch = std::collections::VecDeque::new();
     loop {
-        let address = song_list_addr + entry_index * SongInfo::MEMORY_SIZE as u64;
+        if batch.is_empty() {
+            let end = (entry_index + ENTRY_BATCH).min(MAX_ENTRY_INDEX + 1);
+            batch.extend(read_entry_batch(
+                reader,
+                song_list_addr,
+                entry_index..end,
+                SongInfo::MEMORY_SIZE,
+                0,
+                SongInfo::MEMORY_SIZE,
+            ));
+        }
+        let Some(entry) = batch.pop_front() else {
+            break;
+        };
 
         // Use fallback method for new INFINITAS versions where metadata is split
-        match SongInfo::read_from_memory_with_fallback(
-            reader,
-            address,
-            song_list_addr,
-            entry_index,
-        )? {
+        let parsed = SongInfo::parse_entry(&entry?)?;
+        match SongInfo::apply_metadata_fallback(reader, parsed, song_list_addr, entry_index)? {
             Some(song) if !song.title.is_empty() && song.id > 0 => {
                 // Avoid duplicates
                 result.entry(song.id).or_insert(song);
@@ -480,7 +641,7 @@ pub fn fetch_song_database<R: ReadMemory>(
         entry_index += 1;
 
         // Safety limit
-        if entry_index > 5000 {
+        if entry_index > MAX_ENTRY_INDEX {
             warn!("Song database fetch reached safety limit of 5000 entries");
             break;
         }
@@ -504,7 +665,16 @@ pub fn load_song_database_from_tsv<P: AsRef<Path>>(
     path: P,
 ) -> std::result::Result<HashMap<Arc<str>, SongInfo>, std::io::Error> {
     let file = File::open(path)?;
-    let reader = BufReader::new(file);
+    parse_song_database_tsv(BufReader::new(file))
+}
+
+/// Parse a tracker TSV song list from any reader
+///
+/// See [`load_song_database_from_tsv`] for the columns used. Short rows and
+/// unparsable numbers read as zero; only I/O errors and invalid UTF-8 fail.
+pub fn parse_song_database_tsv<R: BufRead>(
+    reader: R,
+) -> std::result::Result<HashMap<Arc<str>, SongInfo>, std::io::Error> {
     let mut result = HashMap::new();
 
     // Column indices (0-based):
@@ -564,18 +734,18 @@ pub fn load_song_database_from_tsv<P: AsRef<Path>>(
 
         let song = SongInfo {
             id: 0, // Will be filled in when matched with memory data
-            title: Arc::from(title),
-            title_english: Arc::from(""),
-            artist: Arc::from(""),
-            genre: Arc::from(""),
-            bpm: Arc::from(""),
+            title: intern(title),
+            title_english: intern(""),
+            artist: intern(""),
+            genre: intern(""),
+            bpm: intern(""),
             folder: 0,
             levels,
             total_notes,
             unlock_type: UnlockType::default(),
         };
 
-        result.insert(Arc::from(title), song);
+        result.insert(song.title.clone(), song);
     }
 
     info!("Loaded {} songs from TSV file", result.len());
@@ -633,13 +803,12 @@ pub fn merge_song_databases(
 pub fn build_song_database_from_tsv_with_memory<R: ReadMemory>(
     reader: &R,
     song_list_addr: u64,
-    tsv_path: &str,
+    tsv_path: &Path,
     scan_size: usize,
+    layout: &SongEntryLayout,
 ) -> HashMap<u32, SongInfo> {
-    use std::path::Path;
-
     // Step 1: Load TSV database
-    let tsv_db = if Path::new(tsv_path).exists() {
+    let tsv_db = if tsv_path.exists() {
         match load_song_database_from_tsv(tsv_path) {
             Ok(db) => {
                 info!("Loaded {} songs from TSV", db.len());
@@ -651,12 +820,13 @@ pub fn build_song_database_from_tsv_with_memory<R: ReadMemory>(
             }
         }
     } else {
-        debug!("TSV file not found: {}", tsv_path);
+        debug!("TSV file not found: {}", tsv_path.display());
         HashMap::new()
     };
 
     // Step 2: Scan memory for song_id -> title mappings
-    let memory_songs = fetch_song_database_from_memory_scan(reader, song_list_addr, scan_size);
+    let memory_songs =
+        fetch_song_database_from_memory_scan_with_layout(reader, song_list_addr, scan_size, layout);
     info!("Found {} songs in memory scan", memory_songs.len());
 
     // Build reverse mapping: normalized_title -> song_id
@@ -751,29 +921,56 @@ pub fn fetch_song_by_id<R: ReadMemory>(
     song_list_addr: u64,
     target_song_id: u32,
     scan_size: usize,
+) -> Option<SongInfo> {
+    fetch_song_by_id_with_layout(
+        reader,
+        song_list_addr,
+        target_song_id,
+        scan_size,
+        &SongEntryLayout::CURRENT,
+    )
+}
+
+/// [`fetch_song_by_id`] for a song list with an explicit entry layout
+pub fn fetch_song_by_id_with_layout<R: ReadMemory>(
+    reader: &R,
+    song_list_addr: u64,
+    target_song_id: u32,
+    scan_size: usize,
+    layout: &SongEntryLayout,
 ) -> Option<SongInfo> {
     if song_list_addr == 0 {
         return None;
     }
 
-    const ENTRY_SIZE: u64 = SongInfo::MEMORY_SIZE as u64; // 0x3F0 = 1008 bytes
-
-    let max_entries = (scan_size as u64 / ENTRY_SIZE).min(5000);
+    let max_entries = (scan_size / layout.entry_size).min(5000) as u64;
 
     // Scan each entry for the target song_id
-    for i in 0..max_entries {
-        let entry_addr = song_list_addr + i * ENTRY_SIZE;
-
-        // Use the proper read_from_memory function that reads song_id from offset 624
-        match SongInfo::read_from_memory(reader, entry_addr) {
-            Ok(Some(song)) if song.id == target_song_id => {
-                debug!(
-                    "Dynamically loaded song_id={} title={:?} folder={}",
-                    song.id, song.title, song.folder
-                );
-                return Some(song);
+    for batch_start in (0..max_entries).step_by(ENTRY_BATCH as usize) {
+        let batch = batch_start..(batch_start + ENTRY_BATCH).min(max_entries);
+        let entries = read_entry_batch(
+            reader,
+            song_list_addr,
+            batch,
+            layout.entry_size,
+            0,
+            layout.entry_size,
+        );
+
+        for entry in entries {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            match SongInfo::parse_entry_with_layout(&entry, layout) {
+                Ok(Some(song)) if song.id == target_song_id => {
+                    debug!(
+                        "Dynamically loaded song_id={} title={:?} folder={}",
+                        song.id, song.title, song.folder
+                    );
+                    return Some(song);
+                }
+                _ => continue,
             }
-            _ => continue,
         }
     }
 
@@ -792,18 +989,42 @@ pub fn fetch_song_database_from_memory_scan<R: ReadMemory>(
     song_list_base: u64,
     scan_size: usize,
 ) -> HashMap<u32, SongInfo> {
-    const ENTRY_SIZE: u64 = SongInfo::MEMORY_SIZE as u64; // 0x3F0 = 1008 bytes
+    fetch_song_database_from_memory_scan_with_layout(
+        reader,
+        song_list_base,
+        scan_size,
+        &SongEntryLayout::CURRENT,
+    )
+}
 
+/// [`fetch_song_database_from_memory_scan`] for a song list with an explicit
+/// entry layout (see [`crate::offset::LayoutRegistry`])
+pub fn fetch_song_database_from_memory_scan_with_layout<R: ReadMemory>(
+    reader: &R,
+    song_list_base: u64,
+    scan_size: usize,
+    layout: &SongEntryLayout,
+) -> HashMap<u32, SongInfo> {
     let mut result = HashMap::new();
-    let max_entries = (scan_size as u64 / ENTRY_SIZE).min(5000);
+    let max_entries = (scan_size / layout.entry_size).min(5000) as u64;
 
     // Note: With lazy loading, songs may be scattered across the entry table.
     // We scan all entries to find all loaded songs.
-    for i in 0..max_entries {
-        let entry_addr = song_list_base + i * ENTRY_SIZE;
-
-        // Use the proper read_from_memory function
-        let song = match SongInfo::read_from_memory(reader, entry_addr) {
+    let entries = (0..max_entries)
+        .step_by(ENTRY_BATCH as usize)
+        .flat_map(|batch_start| {
+            let batch = batch_start..(batch_start + ENTRY_BATCH).min(max_entries);
+            read_entry_batch(
+                reader,
+                song_list_base,
+                batch,
+                layout.entry_size,
+                0,
+                layout.entry_size,
+            )
+        });
+    for entry in entries {
+        let song = match entry.and_then(|entry| SongInfo::parse_entry_with_layout(&entry, layout)) {
             Ok(Some(song)) => song,
             _ => continue,
         };
@@ -950,4 +1171,39 @@ mod tests {
             assert_eq!(bulk_song.title.as_ref(), per_song.title.as_ref());
         }
     }
+
+    #[test]
+    fn test_memory_scan_crosses_read_batches() {
+        // Songs on both sides of a read batch boundary
+        let entries = ENTRY_BATCH as usize + 6;
+        let mut buffer = vec![0u8; entries * SongInfo::MEMORY_SIZE];
+        for (index, title, id) in [(1, "Alpha", 5000), (ENTRY_BATCH as usize + 2, "Beta", 5001)] {
+            let offset = index * SongInfo::MEMORY_SIZE;
+            buffer[offset..offset + SongInfo::MEMORY_SIZE]
+                .copy_from_slice(&build_song_entry(title, id));
+        }
+
+        let base: u64 = 0x1000;
+        let reader = MockMemoryBuilder::new()
+            .base(base)
+            .write_bytes(0, &buffer)
+            .build();
+
+        let db = fetch_song_database_from_memory_scan(&reader, base, buffer.len());
+        assert_eq!(db.len(), 2);
+        assert_eq!(db[&5001].title.as_ref(), "Beta");
+
+        let found = fetch_song_by_id(&reader, base, 5001, buffer.len()).unwrap();
+        assert_eq!(found.title.as_ref(), "Beta");
+    }
+
+    #[test]
+    fn test_parsed_strings_are_interned() {
+        let entry = build_song_entry("Interned", 5002);
+        let a = SongInfo::parse_from_buffer(&entry, 0).unwrap().unwrap();
+        let b = SongInfo::parse_from_buffer(&entry, 0).unwrap().unwrap();
+
+        assert!(Arc::ptr_eq(&a.title, &b.title));
+        assert!(Arc::ptr_eq(&a.bpm, &b.bpm));
+    }
 }
@@ -3,22 +3,113 @@ use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 
 use crate::chart::{Difficulty, SongInfo};
+use crate::error::{Error, Result};
+
+use super::intern::deserialize_interned;
 
 /// Chart identifier (song + difficulty)
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct Chart {
+///
+/// The one key for a chart across score maps, unlock data, trackers and
+/// exports. Per-difficulty arrays (`SongInfo::levels`, `ScoreData::lamp`,
+/// ...) are indexed with [`index`](Self::index), in [`Difficulty`] order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ChartId {
     pub song_id: u32,
     pub difficulty: Difficulty,
 }
 
+/// Former name of [`ChartId`]
+pub type Chart = ChartId;
+
+impl ChartId {
+    pub const fn new(song_id: u32, difficulty: Difficulty) -> Self {
+        Self {
+            song_id,
+            difficulty,
+        }
+    }
+
+    /// Chart at a per-difficulty array index
+    pub fn from_index(song_id: u32, index: usize) -> Option<Self> {
+        Difficulty::from_index(index).map(|difficulty| Self::new(song_id, difficulty))
+    }
+
+    /// Chart from raw `i32` fields read from memory
+    ///
+    /// Returns `None` for negative song IDs and out-of-range difficulties,
+    /// instead of truncating them.
+    pub fn from_raw(song_id: i32, difficulty: i32) -> Option<Self> {
+        let song_id = u32::try_from(song_id).ok()?;
+        Difficulty::from_raw(difficulty).map(|difficulty| Self::new(song_id, difficulty))
+    }
+
+    /// Index into per-difficulty arrays
+    pub fn index(self) -> usize {
+        self.difficulty.index()
+    }
+
+    /// Every chart of a song, in [`Difficulty`] order
+    pub fn all(song_id: u32) -> impl Iterator<Item = Self> {
+        Difficulty::ALL
+            .into_iter()
+            .map(move |difficulty| Self::new(song_id, difficulty))
+    }
+
+    /// Whether `song` is this chart's song and has notes on this difficulty
+    pub fn exists_in(self, song: &SongInfo) -> bool {
+        song.id == self.song_id && song.total_notes_for(self.difficulty) > 0
+    }
+}
+
+impl From<(u32, Difficulty)> for ChartId {
+    fn from((song_id, difficulty): (u32, Difficulty)) -> Self {
+        Self::new(song_id, difficulty)
+    }
+}
+
+impl From<ChartId> for (u32, Difficulty) {
+    fn from(chart: ChartId) -> Self {
+        (chart.song_id, chart.difficulty)
+    }
+}
+
+impl std::fmt::Display for ChartId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.song_id, self.difficulty)
+    }
+}
+
+impl std::str::FromStr for ChartId {
+    type Err = Error;
+
+    /// Parse `song_id:DIFFICULTY` (e.g. `1000:SPA`), the [`Display`](std::fmt::Display) form
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::parse("chart", format!("expected song_id:DIFFICULTY, got {s:?}"));
+        let (song_id, difficulty) = s.split_once(':').ok_or_else(invalid)?;
+        Ok(Self::new(
+            song_id.trim().parse().map_err(|_| invalid())?,
+            difficulty
+                .trim()
+                .to_ascii_uppercase()
+                .parse()
+                .map_err(|_| invalid())?,
+        ))
+    }
+}
+
 /// Full chart information including song metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChartInfo {
     pub song_id: u32,
+    #[serde(deserialize_with = "deserialize_interned")]
     pub title: Arc<str>,
+    #[serde(deserialize_with = "deserialize_interned")]
     pub title_english: Arc<str>,
+    #[serde(deserialize_with = "deserialize_interned")]
     pub artist: Arc<str>,
+    #[serde(deserialize_with = "deserialize_interned")]
     pub genre: Arc<str>,
+    #[serde(deserialize_with = "deserialize_interned")]
     pub bpm: Arc<str>,
     pub difficulty: Difficulty,
     pub level: u8,
@@ -28,7 +119,6 @@ pub struct ChartInfo {
 
 impl ChartInfo {
     pub fn from_song_info(song: &SongInfo, difficulty: Difficulty, unlocked: bool) -> Self {
-        let diff_index = difficulty as usize;
         Self {
             song_id: song.id,
             title: song.title.clone(),
@@ -37,12 +127,16 @@ impl ChartInfo {
             genre: song.genre.clone(),
             bpm: song.bpm.clone(),
             difficulty,
-            level: song.get_level(diff_index),
-            total_notes: song.get_total_notes(diff_index),
+            level: song.level_for(difficulty),
+            total_notes: song.total_notes_for(difficulty),
             unlocked,
         }
     }
 
+    pub fn id(&self) -> ChartId {
+        ChartId::new(self.song_id, self.difficulty)
+    }
+
     /// Calculate max EX score (total_notes * 2)
     pub fn max_ex_score(&self) -> u32 {
         self.total_notes * 2
@@ -111,20 +205,73 @@ mod tests {
 
     #[test]
     fn test_chart_equality() {
-        let chart1 = Chart {
-            song_id: 1001,
-            difficulty: Difficulty::SpA,
-        };
-        let chart2 = Chart {
-            song_id: 1001,
-            difficulty: Difficulty::SpA,
-        };
-        let chart3 = Chart {
-            song_id: 1001,
-            difficulty: Difficulty::SpH,
-        };
+        let chart1 = ChartId::new(1001, Difficulty::SpA);
+        let chart2 = ChartId::from((1001, Difficulty::SpA));
+        let chart3 = ChartId::new(1001, Difficulty::SpH);
 
         assert_eq!(chart1, chart2);
         assert_ne!(chart1, chart3);
     }
+
+    #[test]
+    fn test_chart_id_index_conversions() {
+        for (index, chart) in ChartId::all(1001).enumerate() {
+            assert_eq!(chart.index(), index);
+            assert_eq!(ChartId::from_index(1001, index), Some(chart));
+        }
+        assert_eq!(ChartId::from_index(1001, 10), None);
+        assert_eq!(ChartId::new(1001, Difficulty::DpA).index(), 8);
+    }
+
+    #[test]
+    fn test_chart_id_from_raw_rejects_invalid_values() {
+        assert_eq!(
+            ChartId::from_raw(1001, 3),
+            Some(ChartId::new(1001, Difficulty::SpA))
+        );
+        assert_eq!(ChartId::from_raw(1001, 10), None);
+        assert_eq!(ChartId::from_raw(1001, -1), None);
+        // Would wrap to SPA when truncated to u8
+        assert_eq!(ChartId::from_raw(1001, 259), None);
+        assert_eq!(ChartId::from_raw(-5, 3), None);
+    }
+
+    #[test]
+    fn test_chart_id_exists_in() {
+        let song = make_test_song();
+        assert!(ChartId::new(1001, Difficulty::SpA).exists_in(&song));
+        assert!(!ChartId::new(1002, Difficulty::SpA).exists_in(&song));
+
+        let mut song = song;
+        song.total_notes[Difficulty::DpL.index()] = 0;
+        assert!(!ChartId::new(1001, Difficulty::DpL).exists_in(&song));
+    }
+
+    #[test]
+    fn test_chart_id_display_roundtrip() {
+        let chart = ChartId::new(1001, Difficulty::DpH);
+        assert_eq!(chart.to_string(), "1001:DPH");
+        assert_eq!("1001:DPH".parse::<ChartId>().unwrap(), chart);
+        assert_eq!(" 1001 : dph ".parse::<ChartId>().unwrap(), chart);
+        assert!("1001".parse::<ChartId>().is_err());
+        assert!("1001:XYZ".parse::<ChartId>().is_err());
+    }
+
+    #[test]
+    fn test_chart_id_ordering() {
+        let mut charts = vec![
+            ChartId::new(1002, Difficulty::SpN),
+            ChartId::new(1001, Difficulty::DpA),
+            ChartId::new(1001, Difficulty::SpA),
+        ];
+        charts.sort();
+        assert_eq!(
+            charts,
+            [
+                ChartId::new(1001, Difficulty::SpA),
+                ChartId::new(1001, Difficulty::DpA),
+                ChartId::new(1002, Difficulty::SpN),
+            ]
+        );
+    }
 }
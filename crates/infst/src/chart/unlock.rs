@@ -1,8 +1,11 @@
 use std::collections::HashMap;
 
-use crate::chart::{Difficulty, SongInfo};
+use serde::Serialize;
+
+use crate::chart::{ChartId, Difficulty, SongInfo};
 use crate::error::Result;
 use crate::play::UnlockType;
+use crate::process::layout::{Field, FieldKind};
 use crate::process::{ByteBuffer, ReadMemory};
 
 /// Unlock data structure from memory
@@ -17,10 +20,101 @@ impl UnlockData {
     /// Size of unlock data structure in memory (32 bytes)
     pub const MEMORY_SIZE: usize = 32;
 
+    /// Known fields as (offset, size, name, kind), for memory annotations and diffs
+    #[cfg_attr(not(feature = "debug-tools"), allow(dead_code))]
+    pub(crate) const FIELDS: &'static [Field] = &[
+        (0, 4, "song_id", FieldKind::Int),
+        (4, 4, "unlock_type", FieldKind::Int),
+        (8, 4, "unlocks", FieldKind::Int),
+    ];
+
     /// Check if a specific difficulty is unlocked (raw bit check)
     pub fn is_difficulty_unlocked(&self, difficulty: Difficulty) -> bool {
-        let bit = 1 << (difficulty as i32);
-        (self.unlocks & bit) != 0
+        (self.unlocks & Self::bit(difficulty)) != 0
+    }
+
+    /// Set a difficulty's unlock bit (e.g. to simulate a purchase)
+    pub fn set_difficulty_unlocked(&mut self, difficulty: Difficulty) {
+        self.unlocks |= Self::bit(difficulty);
+    }
+
+    /// Clear a difficulty's unlock bit
+    pub fn clear_difficulty_unlocked(&mut self, difficulty: Difficulty) {
+        self.unlocks &= !Self::bit(difficulty);
+    }
+
+    /// Difficulties whose unlock bit is set, in [`Difficulty`] order
+    pub fn unlocked_difficulties(&self) -> impl Iterator<Item = Difficulty> + '_ {
+        Difficulty::ALL
+            .into_iter()
+            .filter(|&difficulty| self.is_difficulty_unlocked(difficulty))
+    }
+
+    /// Whether a chart of this song is playable, considering special cases
+    ///
+    /// Special handling for:
+    /// - SPB (Beginner): For non-Sub songs, check if note count is non-zero
+    /// - SPL/DPL (Leggendaria): For Sub songs, requires both SPA and DPA to be unlocked
+    pub fn is_unlocked(&self, song: Option<&SongInfo>, difficulty: Difficulty) -> bool {
+        let is_sub = self.unlock_type == UnlockType::Sub;
+        match difficulty {
+            // For Sub songs, use the unlock bit; for others, check if the chart exists
+            Difficulty::SpB if !is_sub => {
+                song.is_some_and(|s| ChartId::new(self.song_id, difficulty).exists_in(s))
+            }
+            Difficulty::SpL | Difficulty::DpL if is_sub => {
+                self.is_difficulty_unlocked(Difficulty::SpA)
+                    && self.is_difficulty_unlocked(Difficulty::DpA)
+            }
+            _ => self.is_difficulty_unlocked(difficulty),
+        }
+    }
+
+    /// How a chart of this song can be unlocked
+    pub fn purchasability(&self, song: &SongInfo, difficulty: Difficulty) -> Purchasability {
+        if !ChartId::new(self.song_id, difficulty).exists_in(song) {
+            return Purchasability::NotPurchasable;
+        }
+        if self.is_unlocked(Some(song), difficulty) {
+            return Purchasability::Unlocked;
+        }
+        match self.unlock_type {
+            UnlockType::Bits => match bits_unlock_cost(song, difficulty) {
+                Some(cost) if cost > 0 => Purchasability::Bits { cost },
+                _ => Purchasability::NotPurchasable,
+            },
+            UnlockType::Sub => Purchasability::Subscription,
+            UnlockType::Base => Purchasability::NotPurchasable,
+        }
+    }
+
+    /// Bit cost of the tier containing `difficulty`, for bit songs
+    ///
+    /// Unlike [`purchasability`](Self::purchasability) this ignores whether
+    /// the tier is already unlocked.
+    pub fn bits_cost(&self, song: &SongInfo, difficulty: Difficulty) -> Option<u32> {
+        if self.unlock_type != UnlockType::Bits {
+            return None;
+        }
+        bits_unlock_cost(song, difficulty)
+    }
+
+    /// Set the unlock bits a bit purchase of `difficulty`'s tier opens
+    ///
+    /// Returns the SP and DP difficulty of the tier, or `None` (and changes
+    /// nothing) for difficulties that can't be bought.
+    pub fn apply_bits_purchase(
+        &mut self,
+        difficulty: Difficulty,
+    ) -> Option<(Difficulty, Difficulty)> {
+        let (sp, dp) = bits_unlock_tier(difficulty)?;
+        self.set_difficulty_unlocked(sp);
+        self.set_difficulty_unlocked(dp);
+        Some((sp, dp))
+    }
+
+    fn bit(difficulty: Difficulty) -> i32 {
+        1 << difficulty.index()
     }
 
     /// Parse from raw bytes
@@ -112,49 +206,68 @@ fn parse_unlock_buffer(
     extra_entries
 }
 
+/// How a chart can be unlocked, see [`UnlockData::purchasability`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Purchasability {
+    /// Already playable
+    Unlocked,
+    /// Bought with bits, together with the other play style's chart of the tier
+    Bits { cost: u32 },
+    /// Comes with the subscription pack of a Sub song
+    Subscription,
+    /// Missing chart, or unlocked by other means (events, beginner and
+    /// leggendaria charts of bit songs)
+    NotPurchasable,
+}
+
+/// Bits per level for a bit unlock
+pub const BITS_PER_LEVEL: u32 = 500;
+
+/// Bit cost of unlocking the N/H/A tier containing `difficulty`
+///
+/// A bit unlock opens the SP and DP chart of the same tier, and costs
+/// [`BITS_PER_LEVEL`] times the sum of both levels. Returns `None` for
+/// difficulties that can't be bought (beginner, leggendaria).
+pub fn bits_unlock_cost(song: &SongInfo, difficulty: Difficulty) -> Option<u32> {
+    let (sp, dp) = bits_unlock_tier(difficulty)?;
+    Some(BITS_PER_LEVEL * (song.level_for(sp) as u32 + song.level_for(dp) as u32))
+}
+
+/// SP and DP difficulty of the bit unlock tier containing `difficulty`
+pub fn bits_unlock_tier(difficulty: Difficulty) -> Option<(Difficulty, Difficulty)> {
+    match difficulty {
+        Difficulty::SpN | Difficulty::DpN => Some((Difficulty::SpN, Difficulty::DpN)),
+        Difficulty::SpH | Difficulty::DpH => Some((Difficulty::SpH, Difficulty::DpH)),
+        Difficulty::SpA | Difficulty::DpA => Some((Difficulty::SpA, Difficulty::DpA)),
+        _ => None,
+    }
+}
+
 /// Get unlock state for a specific difficulty, considering special cases
 ///
-/// Special handling for:
-/// - SPB (Beginner): For non-Sub songs, check if note count is non-zero
-/// - SPL/DPL (Leggendaria): For Sub songs, requires both SPA and DPA to be unlocked
+/// Same as [`is_chart_unlocked`] with the chart given as song ID and difficulty.
 pub fn get_unlock_state_for_difficulty(
     unlock_db: &HashMap<u32, UnlockData>,
     song_db: &HashMap<u32, SongInfo>,
     song_id: u32,
     difficulty: Difficulty,
 ) -> bool {
-    let Some(unlock_data) = unlock_db.get(&song_id) else {
-        return false;
-    };
-
-    let song_info = song_db.get(&song_id);
-
-    // Handle Beginner difficulty specially
-    if difficulty == Difficulty::SpB {
-        if unlock_data.unlock_type == UnlockType::Sub {
-            // For Sub songs, use the unlock bit
-            return unlock_data.is_difficulty_unlocked(difficulty);
-        } else {
-            // For other songs, check if note count is non-zero
-            return song_info.map(|s| s.total_notes[0] > 0).unwrap_or(false);
-        }
-    }
-
-    // Handle Leggendaria difficulties (SPL/DPL)
-    if difficulty == Difficulty::SpL || difficulty == Difficulty::DpL {
-        if unlock_data.unlock_type == UnlockType::Sub {
-            // For Sub songs, require both SPA and DPA to be unlocked
-            let spa_unlocked = unlock_data.is_difficulty_unlocked(Difficulty::SpA);
-            let dpa_unlocked = unlock_data.is_difficulty_unlocked(Difficulty::DpA);
-            return spa_unlocked && dpa_unlocked;
-        } else {
-            // For other songs, just check the unlock bit
-            return unlock_data.is_difficulty_unlocked(difficulty);
-        }
-    }
+    is_chart_unlocked(unlock_db, song_db, ChartId::new(song_id, difficulty))
+}
 
-    // Standard case: just check the unlock bit
-    unlock_data.is_difficulty_unlocked(difficulty)
+/// Get unlock state for a chart, considering special cases
+///
+/// See [`UnlockData::is_unlocked`]; charts of songs without unlock data are
+/// locked.
+pub fn is_chart_unlocked(
+    unlock_db: &HashMap<u32, UnlockData>,
+    song_db: &HashMap<u32, SongInfo>,
+    chart: ChartId,
+) -> bool {
+    unlock_db
+        .get(&chart.song_id)
+        .is_some_and(|unlock| unlock.is_unlocked(song_db.get(&chart.song_id), chart.difficulty))
 }
 
 /// Compare old and new unlock states and return only changed entries
@@ -210,6 +323,27 @@ pub fn detect_unlock_changes(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_bits_unlock_cost() {
+        let song = SongInfo {
+            id: 1000,
+            title: Arc::from("Test"),
+            title_english: Arc::from(""),
+            artist: Arc::from(""),
+            genre: Arc::from(""),
+            bpm: Arc::from("150"),
+            folder: 1,
+            levels: [0, 5, 8, 10, 12, 0, 6, 9, 11, 12],
+            total_notes: [0; 10],
+            unlock_type: UnlockType::Bits,
+        };
+
+        assert_eq!(bits_unlock_cost(&song, Difficulty::SpA), Some(500 * 21));
+        assert_eq!(bits_unlock_cost(&song, Difficulty::DpN), Some(500 * 11));
+        assert_eq!(bits_unlock_cost(&song, Difficulty::SpL), None);
+    }
 
     #[test]
     fn test_is_difficulty_unlocked() {
@@ -227,6 +361,125 @@ mod tests {
         assert!(!unlock.is_difficulty_unlocked(Difficulty::DpN));
     }
 
+    fn song(unlock_type: UnlockType) -> SongInfo {
+        let levels = [1, 5, 8, 10, 12, 0, 6, 9, 11, 12];
+        SongInfo {
+            id: 1000,
+            title: Arc::from("Test"),
+            title_english: Arc::from(""),
+            artist: Arc::from(""),
+            genre: Arc::from(""),
+            bpm: Arc::from("150"),
+            folder: 1,
+            levels,
+            total_notes: levels.map(|l| if l > 0 { 1000 } else { 0 }),
+            unlock_type,
+        }
+    }
+
+    #[test]
+    fn test_set_and_clear_unlock_bits() {
+        let mut unlock = UnlockData {
+            song_id: 1000,
+            unlock_type: UnlockType::Bits,
+            unlocks: 0,
+        };
+        unlock.set_difficulty_unlocked(Difficulty::DpH);
+        unlock.set_difficulty_unlocked(Difficulty::SpN);
+        assert_eq!(
+            unlock.unlocked_difficulties().collect::<Vec<_>>(),
+            [Difficulty::SpN, Difficulty::DpH]
+        );
+
+        unlock.clear_difficulty_unlocked(Difficulty::DpH);
+        assert_eq!(unlock.unlocks, 1 << Difficulty::SpN.index());
+
+        assert_eq!(
+            unlock.apply_bits_purchase(Difficulty::DpA),
+            Some((Difficulty::SpA, Difficulty::DpA))
+        );
+        assert!(unlock.is_difficulty_unlocked(Difficulty::SpA));
+        assert_eq!(unlock.apply_bits_purchase(Difficulty::SpL), None);
+        assert!(!unlock.is_difficulty_unlocked(Difficulty::SpL));
+    }
+
+    #[test]
+    fn test_is_unlocked_special_cases() {
+        let base = song(UnlockType::Base);
+        let unlock = UnlockData {
+            song_id: 1000,
+            unlock_type: UnlockType::Base,
+            unlocks: 0,
+        };
+        // Beginner of non-Sub songs is playable whenever it exists
+        assert!(unlock.is_unlocked(Some(&base), Difficulty::SpB));
+        assert!(!unlock.is_unlocked(None, Difficulty::SpB));
+
+        let mut sub = UnlockData {
+            song_id: 1000,
+            unlock_type: UnlockType::Sub,
+            unlocks: 0,
+        };
+        assert!(!sub.is_unlocked(Some(&base), Difficulty::SpB));
+        sub.set_difficulty_unlocked(Difficulty::SpA);
+        assert!(!sub.is_unlocked(Some(&base), Difficulty::SpL));
+        sub.set_difficulty_unlocked(Difficulty::DpA);
+        assert!(sub.is_unlocked(Some(&base), Difficulty::SpL));
+        assert!(sub.is_unlocked(Some(&base), Difficulty::DpL));
+    }
+
+    #[test]
+    fn test_purchasability() {
+        let bits_song = song(UnlockType::Bits);
+        let mut unlock = UnlockData {
+            song_id: 1000,
+            unlock_type: UnlockType::Bits,
+            unlocks: 0,
+        };
+        unlock.apply_bits_purchase(Difficulty::SpN);
+
+        assert_eq!(
+            unlock.purchasability(&bits_song, Difficulty::SpN),
+            Purchasability::Unlocked
+        );
+        assert_eq!(
+            unlock.purchasability(&bits_song, Difficulty::DpA),
+            Purchasability::Bits { cost: 500 * 21 }
+        );
+        assert_eq!(
+            unlock.purchasability(&bits_song, Difficulty::SpL),
+            Purchasability::NotPurchasable
+        );
+        // DPB has no notes
+        assert_eq!(
+            unlock.purchasability(&bits_song, Difficulty::DpB),
+            Purchasability::NotPurchasable
+        );
+        assert_eq!(
+            unlock.bits_cost(&bits_song, Difficulty::SpN),
+            Some(500 * 11)
+        );
+
+        let sub = UnlockData {
+            unlock_type: UnlockType::Sub,
+            ..unlock.clone()
+        };
+        assert_eq!(
+            sub.purchasability(&bits_song, Difficulty::SpA),
+            Purchasability::Subscription
+        );
+        assert_eq!(sub.bits_cost(&bits_song, Difficulty::SpA), None);
+
+        let base = UnlockData {
+            unlock_type: UnlockType::Base,
+            ..unlock
+        };
+        assert_eq!(
+            base.purchasability(&bits_song, Difficulty::SpL),
+            Purchasability::NotPurchasable
+        );
+    }
+
     #[test]
     fn test_from_bytes() {
         let bytes = [
@@ -0,0 +1,339 @@
+//! Song database verification against a canonical song list
+//!
+//! Cross-checks songs parsed from memory against a canonical song list
+//! (JSON) so that a wrong song-entry layout shows up as concrete mismatches
+//! (wrong levels or note counts, missing charts) instead of silently bad data.
+//!
+//! Canonical list format:
+//!
+//! ```json
+//! [
+//!   { "id": 1000, "title": "5.1.1.", "levels": [0, 3, 6, 8, 0, 0, 3, 6, 8, 0],
+//!     "notes": [0, 251, 486, 786, 0, 0, 253, 488, 780, 0] }
+//! ]
+//! ```
+//!
+//! `levels` and `notes` are optional and ordered SPB..SPL, DPB..DPL.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::chart::{Difficulty, SongInfo};
+use crate::error::Result;
+
+/// One song in the canonical list
+#[derive(Debug, Clone, Deserialize)]
+pub struct CanonicalSong {
+    pub id: u32,
+    pub title: String,
+    #[serde(default)]
+    pub levels: Option<[u8; 10]>,
+    #[serde(default)]
+    pub notes: Option<[u32; 10]>,
+}
+
+/// Canonical song list keyed by song ID
+#[derive(Debug, Clone, Default)]
+pub struct CanonicalSongList {
+    songs: HashMap<u32, CanonicalSong>,
+}
+
+impl CanonicalSongList {
+    pub fn from_json(json: &str) -> Result<Self> {
+        let songs: Vec<CanonicalSong> = serde_json::from_str(json)?;
+        Ok(Self {
+            songs: songs.into_iter().map(|s| (s.id, s)).collect(),
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.songs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.songs.is_empty()
+    }
+
+    pub fn get(&self, id: u32) -> Option<&CanonicalSong> {
+        self.songs.get(&id)
+    }
+}
+
+/// A difference between the parsed database and the canonical list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SongMismatch {
+    /// Song in the canonical list was not found in memory
+    MissingSong { id: u32, title: String },
+    TitleMismatch {
+        id: u32,
+        expected: String,
+        actual: String,
+    },
+    LevelMismatch {
+        id: u32,
+        difficulty: Difficulty,
+        expected: u8,
+        actual: u8,
+    },
+    NoteCountMismatch {
+        id: u32,
+        difficulty: Difficulty,
+        expected: u32,
+        actual: u32,
+    },
+    /// Chart exists in the canonical list but has no notes in memory
+    MissingChart { id: u32, difficulty: Difficulty },
+}
+
+impl fmt::Display for SongMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingSong { id, title } => write!(f, "{:05} missing song ({})", id, title),
+            Self::TitleMismatch {
+                id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{:05} title: expected {:?}, got {:?}",
+                id, expected, actual
+            ),
+            Self::LevelMismatch {
+                id,
+                difficulty,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{:05} {} level: expected {}, got {}",
+                id,
+                difficulty.short_name(),
+                expected,
+                actual
+            ),
+            Self::NoteCountMismatch {
+                id,
+                difficulty,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{:05} {} notes: expected {}, got {}",
+                id,
+                difficulty.short_name(),
+                expected,
+                actual
+            ),
+            Self::MissingChart { id, difficulty } => {
+                write!(f, "{:05} {} missing chart", id, difficulty.short_name())
+            }
+        }
+    }
+}
+
+/// Result of [`verify_song_database`]
+#[derive(Debug, Clone, Default)]
+pub struct VerificationReport {
+    /// Songs present in both the database and the canonical list
+    pub checked: usize,
+    /// Songs in the database that are not in the canonical list
+    pub unknown: usize,
+    pub mismatches: Vec<SongMismatch>,
+}
+
+impl VerificationReport {
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Number of checked songs with at least one level/note/title mismatch
+    pub fn mismatched_songs(&self) -> usize {
+        let mut ids: Vec<u32> = self
+            .mismatches
+            .iter()
+            .filter_map(|m| match m {
+                SongMismatch::MissingSong { .. } => None,
+                SongMismatch::TitleMismatch { id, .. }
+                | SongMismatch::LevelMismatch { id, .. }
+                | SongMismatch::NoteCountMismatch { id, .. }
+                | SongMismatch::MissingChart { id, .. } => Some(*id),
+            })
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids.len()
+    }
+
+    /// Whether most checked songs disagree, which usually means the song
+    /// entry layout (field offsets or entry size) is wrong rather than a few
+    /// songs having been revised
+    pub fn suggests_layout_error(&self) -> bool {
+        self.checked > 0 && self.mismatched_songs() * 2 > self.checked
+    }
+}
+
+/// Compare the parsed song database against the canonical list
+pub fn verify_song_database(
+    song_db: &HashMap<u32, SongInfo>,
+    canonical: &CanonicalSongList,
+) -> VerificationReport {
+    let mut report = VerificationReport::default();
+
+    let mut expected: Vec<&CanonicalSong> = canonical.songs.values().collect();
+    expected.sort_by_key(|s| s.id);
+
+    for song in expected {
+        let Some(actual) = song_db.get(&song.id) else {
+            report.mismatches.push(SongMismatch::MissingSong {
+                id: song.id,
+                title: song.title.clone(),
+            });
+            continue;
+        };
+        report.checked += 1;
+
+        if song.title.trim() != actual.title.trim() {
+            report.mismatches.push(SongMismatch::TitleMismatch {
+                id: song.id,
+                expected: song.title.clone(),
+                actual: actual.title.to_string(),
+            });
+        }
+
+        for difficulty in Difficulty::ALL {
+            let index = difficulty.index();
+            if let Some(levels) = &song.levels
+                && levels[index] != actual.levels[index]
+            {
+                report.mismatches.push(SongMismatch::LevelMismatch {
+                    id: song.id,
+                    difficulty,
+                    expected: levels[index],
+                    actual: actual.levels[index],
+                });
+            }
+            if let Some(notes) = &song.notes {
+                let (expected, actual) = (notes[index], actual.total_notes[index]);
+                if expected > 0 && actual == 0 {
+                    report.mismatches.push(SongMismatch::MissingChart {
+                        id: song.id,
+                        difficulty,
+                    });
+                } else if expected != actual {
+                    report.mismatches.push(SongMismatch::NoteCountMismatch {
+                        id: song.id,
+                        difficulty,
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+    }
+
+    report.unknown = song_db
+        .keys()
+        .filter(|id| canonical.get(**id).is_none())
+        .count();
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song(id: u32, title: &str, levels: [u8; 10], notes: [u32; 10]) -> SongInfo {
+        SongInfo {
+            id,
+            title: title.into(),
+            levels,
+            total_notes: notes,
+            ..Default::default()
+        }
+    }
+
+    const CANONICAL: &str = r#"[
+        {"id": 1000, "title": "5.1.1.", "levels": [0,3,6,8,0,0,3,6,8,0],
+         "notes": [0,251,486,786,0,0,253,488,780,0]},
+        {"id": 1001, "title": "GAMBOL"},
+        {"id": 1002, "title": "Missing"}
+    ]"#;
+
+    #[test]
+    fn test_matching_database() {
+        let canonical = CanonicalSongList::from_json(CANONICAL).unwrap();
+        let mut db = HashMap::new();
+        db.insert(
+            1000,
+            song(
+                1000,
+                "5.1.1.",
+                [0, 3, 6, 8, 0, 0, 3, 6, 8, 0],
+                [0, 251, 486, 786, 0, 0, 253, 488, 780, 0],
+            ),
+        );
+        db.insert(1001, song(1001, "GAMBOL", [0; 10], [0; 10]));
+        db.insert(9999, song(9999, "Extra", [0; 10], [0; 10]));
+
+        let report = verify_song_database(&db, &canonical);
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.unknown, 1);
+        assert_eq!(
+            report.mismatches,
+            vec![SongMismatch::MissingSong {
+                id: 1002,
+                title: "Missing".to_string()
+            }]
+        );
+        assert!(!report.suggests_layout_error());
+    }
+
+    #[test]
+    fn test_layout_error_detected() {
+        let canonical = CanonicalSongList::from_json(CANONICAL).unwrap();
+        let mut db = HashMap::new();
+        // Levels and notes shifted by one slot, as with a wrong field offset
+        db.insert(
+            1000,
+            song(
+                1000,
+                "5.1.1.",
+                [3, 6, 8, 0, 0, 3, 6, 8, 0, 0],
+                [251, 486, 786, 0, 0, 253, 488, 780, 0, 0],
+            ),
+        );
+
+        let report = verify_song_database(&db, &canonical);
+        assert!(report.mismatches.contains(&SongMismatch::MissingChart {
+            id: 1000,
+            difficulty: Difficulty::SpA,
+        }));
+        assert!(report.mismatches.contains(&SongMismatch::LevelMismatch {
+            id: 1000,
+            difficulty: Difficulty::SpN,
+            expected: 3,
+            actual: 6,
+        }));
+        assert_eq!(report.mismatched_songs(), 1);
+        assert!(report.suggests_layout_error());
+    }
+
+    #[test]
+    fn test_mismatch_display() {
+        let mismatch = SongMismatch::NoteCountMismatch {
+            id: 1000,
+            difficulty: Difficulty::SpA,
+            expected: 786,
+            actual: 0,
+        };
+        assert_eq!(mismatch.to_string(), "01000 SPA notes: expected 786, got 0");
+    }
+}
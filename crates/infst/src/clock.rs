@@ -0,0 +1,175 @@
+//! Source of the current time
+//!
+//! Play timestamps, session file names, timeline notes and dated tracker
+//! snapshots all read the time through a [`Clock`] so tests (and the CLI's
+//! `--fake-time`) can control it instead of depending on the system clock.
+
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+
+/// Provides the current time
+pub trait Clock: Send + Sync {
+    /// Current time in UTC
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Current time in the local timezone
+    fn now_local(&self) -> DateTime<Local> {
+        self.now().with_timezone(&Local)
+    }
+}
+
+/// Shared clock handle, as stored by [`crate::Infst`] and the session manager
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The system clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl SystemClock {
+    /// The system clock as a [`SharedClock`]
+    pub fn shared() -> SharedClock {
+        Arc::new(Self)
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock that only moves when told to
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl MockClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            now: Mutex::new(start),
+        }
+    }
+
+    pub fn set(&self, time: DateTime<Utc>) {
+        *self.lock() = time;
+    }
+
+    pub fn advance(&self, delta: TimeDelta) {
+        *self.lock() += delta;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, DateTime<Utc>> {
+        self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.lock()
+    }
+}
+
+/// System clock shifted so that it started at a chosen time
+///
+/// Time keeps passing at the normal rate; used by `--fake-time` to test
+/// date-dependent behavior (dated snapshots, session names) on a live game.
+#[derive(Debug, Clone, Copy)]
+pub struct OffsetClock {
+    offset: TimeDelta,
+}
+
+impl OffsetClock {
+    /// A clock that reads `start` now
+    pub fn starting_at(start: DateTime<Utc>) -> Self {
+        Self {
+            offset: start - Utc::now(),
+        }
+    }
+}
+
+impl Clock for OffsetClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now() + self.offset
+    }
+}
+
+/// Parse a time given on the command line
+///
+/// Accepts RFC 3339 (`2026-03-01T23:59:00+09:00`) or local time as
+/// `YYYY-MM-DD HH:MM[:SS]` or `YYYY-MM-DD` (midnight).
+pub fn parse_time(text: &str) -> std::result::Result<DateTime<Utc>, String> {
+    let text = text.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| {
+            format!(
+                "invalid time '{}' (expected RFC 3339, YYYY-MM-DD HH:MM[:SS] or YYYY-MM-DD)",
+                text
+            )
+        })?;
+    naive
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| format!("time '{}' does not exist in the local timezone", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_mock_clock_moves_only_when_told() {
+        let start = Utc.with_ymd_and_hms(2026, 1, 31, 23, 59, 0).unwrap();
+        let clock = MockClock::new(start);
+        assert_eq!(clock.now(), start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(TimeDelta::minutes(2));
+        assert_eq!(clock.now(), start + TimeDelta::minutes(2));
+        clock.set(start);
+        assert_eq!(clock.now_local(), start.with_timezone(&Local));
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(
+            parse_time("2026-03-01T23:59:00+09:00").unwrap(),
+            Utc.with_ymd_and_hms(2026, 3, 1, 14, 59, 0).unwrap()
+        );
+        let local = |h, m, s| {
+            Local
+                .with_ymd_and_hms(2026, 3, 1, h, m, s)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        assert_eq!(
+            parse_time("2026-03-01 23:59:30").unwrap(),
+            local(23, 59, 30)
+        );
+        assert_eq!(parse_time("2026-03-01 23:59").unwrap(), local(23, 59, 0));
+        assert_eq!(parse_time("2026-03-01").unwrap(), local(0, 0, 0));
+        assert!(parse_time("yesterday").is_err());
+        assert!(parse_time("2026-13-01").is_err());
+    }
+
+    #[test]
+    fn test_offset_clock_starts_at_requested_time() {
+        let start = Utc.with_ymd_and_hms(2030, 6, 1, 12, 0, 0).unwrap();
+        let clock = OffsetClock::starting_at(start);
+        let elapsed = clock.now() - start;
+        assert!(elapsed >= TimeDelta::zero() && elapsed < TimeDelta::seconds(5));
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! This module contains types for configuration and support files:
 //! - Version detection utilities
-//! - Polling, retry, and database configuration constants
+//! - Polling, retry, watchdog, database and submission configuration constants
 
 mod version;
 
@@ -18,6 +18,15 @@ pub mod retry {
 
     /// Delay (in ms) for each retry attempt (exponential backoff).
     pub const RETRY_DELAYS_MS: [u64; 5] = [100, 200, 400, 800, 1600];
+
+    /// Attempts to send a lamp to the web API.
+    pub const API_ATTEMPTS: u32 = 3;
+
+    /// Delay (in ms) after the first failed API request (doubled after each failure).
+    pub const API_DELAY_MS: u64 = 1000;
+
+    /// Maximum delay (in ms) between API requests.
+    pub const API_MAX_DELAY_MS: u64 = 4000;
 }
 
 /// Result screen polling configuration.
@@ -48,6 +57,42 @@ pub mod database {
     pub const EXTRA_DELAY: Duration = Duration::from_secs(1);
 }
 
+/// Read failure watchdog configuration.
+///
+/// Backoff while state reads keep failing: 200ms → 400ms → ... capped at 10s.
+pub mod watchdog {
+    use std::time::Duration;
+
+    /// Delay after the first failed poll.
+    pub const BASE_BACKOFF: Duration = Duration::from_millis(200);
+
+    /// Maximum delay between polls while failing.
+    pub const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+    /// Consecutive failed polls before the process handle is reopened
+    /// (retried after twice as many failures each time).
+    pub const REOPEN_AFTER_FAILURES: u32 = 5;
+
+    /// Times a single read refreshes the process handle after losing access
+    /// before the failure reaches the game loop.
+    pub const MAX_HANDLE_REFRESHES: u32 = 3;
+}
+
+/// Lamp submission batching (web API protocol v2).
+pub mod submission {
+    use std::time::Duration;
+
+    /// Lamps sent per batch request.
+    pub const BATCH_SIZE: usize = 10;
+
+    /// Queued lamps are sent at the latest this long after the first one.
+    pub const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+    /// Lamps kept in the offline queue while the server is unreachable
+    /// (the oldest are dropped beyond this).
+    pub const MAX_QUEUED: usize = 5000;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,4 +116,11 @@ mod tests {
         assert_eq!(database::RETRY_DELAY.as_secs(), 5);
         assert_eq!(database::EXTRA_DELAY.as_secs(), 1);
     }
+
+    #[test]
+    fn test_watchdog_constants() {
+        assert_eq!(watchdog::BASE_BACKOFF.as_millis(), 200);
+        assert_eq!(watchdog::MAX_BACKOFF.as_secs(), 10);
+        assert_eq!(watchdog::REOPEN_AFTER_FAILURES, 5);
+    }
 }
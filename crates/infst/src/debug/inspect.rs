@@ -0,0 +1,648 @@
+//! Address annotation and pointer detection for memory dumps
+//!
+//! Maps addresses onto the known game structures (song list, judge data,
+//! play settings, ...) located by the current offsets, and classifies
+//! 64-bit values that look like pointers into mapped memory. The same field
+//! tables drive [`diff_struct`], which compares one layout at two addresses.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::chart::{SongInfo, UnlockData};
+use crate::error::Result;
+use crate::offset::OffsetsCollection;
+use crate::play::{Currency, LaneCover, ProfileInfo, Settings, SettingsBlock};
+use crate::process::layout::{Field, FieldKind, judge, play};
+use crate::process::{FromBytes, ReadMemory, decode_shift_jis};
+
+/// Upper bound on song list / unlock table entries considered for annotations
+const MAX_TABLE_ENTRIES: usize = 5000;
+
+/// Lowest address treated as a pointer (the first 64KB are never mapped on Windows)
+const MIN_POINTER: u64 = 0x10000;
+
+/// Highest user-mode address on x64 Windows
+const MAX_POINTER: u64 = 0x7FFF_FFFF_FFFF;
+
+const JUDGE_FIELDS: &[Field] = &[
+    (judge::P1_PGREAT as usize, 4, "P1 PGreat", FieldKind::Int),
+    (judge::P1_GREAT as usize, 4, "P1 Great", FieldKind::Int),
+    (judge::P1_GOOD as usize, 4, "P1 Good", FieldKind::Int),
+    (judge::P1_BAD as usize, 4, "P1 Bad", FieldKind::Int),
+    (judge::P1_POOR as usize, 4, "P1 Poor", FieldKind::Int),
+    (judge::P2_PGREAT as usize, 4, "P2 PGreat", FieldKind::Int),
+    (judge::P2_GREAT as usize, 4, "P2 Great", FieldKind::Int),
+    (judge::P2_GOOD as usize, 4, "P2 Good", FieldKind::Int),
+    (judge::P2_BAD as usize, 4, "P2 Bad", FieldKind::Int),
+    (judge::P2_POOR as usize, 4, "P2 Poor", FieldKind::Int),
+    (
+        judge::P1_COMBO_BREAK as usize,
+        4,
+        "P1 ComboBreak",
+        FieldKind::Int,
+    ),
+    (
+        judge::P2_COMBO_BREAK as usize,
+        4,
+        "P2 ComboBreak",
+        FieldKind::Int,
+    ),
+    (judge::P1_FAST as usize, 4, "P1 Fast", FieldKind::Int),
+    (judge::P2_FAST as usize, 4, "P2 Fast", FieldKind::Int),
+    (judge::P1_SLOW as usize, 4, "P1 Slow", FieldKind::Int),
+    (judge::P2_SLOW as usize, 4, "P2 Slow", FieldKind::Int),
+    (
+        judge::P1_MEASURE_END as usize,
+        4,
+        "P1 MeasureEnd",
+        FieldKind::Int,
+    ),
+    (
+        judge::P2_MEASURE_END as usize,
+        4,
+        "P2 MeasureEnd",
+        FieldKind::Int,
+    ),
+    (
+        judge::STATE_MARKER_1 as usize,
+        4,
+        "StateMarker1",
+        FieldKind::Int,
+    ),
+    (
+        judge::STATE_MARKER_2 as usize,
+        4,
+        "StateMarker2",
+        FieldKind::Int,
+    ),
+];
+const JUDGE_SIZE: usize = judge::STATE_MARKER_2 as usize + 4;
+
+const PLAY_DATA_FIELDS: &[Field] = &[
+    (play::SONG_ID as usize, 4, "song_id", FieldKind::Int),
+    (play::DIFFICULTY as usize, 4, "difficulty", FieldKind::Int),
+    (play::LAMP as usize, 4, "lamp", FieldKind::Int),
+];
+const PLAY_DATA_SIZE: usize = play::LAMP as usize + 4;
+
+// Word indices as read by the tracker's settings fetch
+const SETTINGS_FIELDS: &[Field] = &[
+    (0, 4, "P1 style", FieldKind::Int),
+    (8, 4, "P1 assist", FieldKind::Int),
+    (12, 4, "flip", FieldKind::Int),
+    (16, 4, "P1 range", FieldKind::Int),
+    (20, 4, "DP style2", FieldKind::Int),
+    (32, 4, "battle", FieldKind::Int),
+    (36, 4, "P1 h_ran", FieldKind::Int),
+    (Settings::P2_OFFSET as usize, 4, "P2 style", FieldKind::Int),
+    (
+        Settings::P2_OFFSET as usize + 8,
+        4,
+        "P2 assist",
+        FieldKind::Int,
+    ),
+    (
+        Settings::P2_OFFSET as usize + 16,
+        4,
+        "P2 range",
+        FieldKind::Int,
+    ),
+    (
+        Settings::P2_OFFSET as usize + 36,
+        4,
+        "P2 h_ran",
+        FieldKind::Int,
+    ),
+];
+const SETTINGS_SIZE: usize = SettingsBlock::SIZE;
+
+const CURRENT_SONG_FIELDS: &[Field] = &[
+    (0, 4, "song_id", FieldKind::Int),
+    (4, 4, "difficulty", FieldKind::Int),
+];
+
+// One player's judge counts; P2's block starts at judge::P2_PGREAT
+const JUDGE_PLAYER_FIELDS: &[Field] = &[
+    (0, 4, "PGreat", FieldKind::Int),
+    (4, 4, "Great", FieldKind::Int),
+    (8, 4, "Good", FieldKind::Int),
+    (12, 4, "Bad", FieldKind::Int),
+    (16, 4, "Poor", FieldKind::Int),
+];
+
+/// A known structure located by the offsets
+#[derive(Debug, Clone)]
+struct Region {
+    name: &'static str,
+    start: u64,
+    entry_size: usize,
+    /// Number of entries (1 for single structures)
+    entries: usize,
+    fields: &'static [Field],
+}
+
+impl Region {
+    fn end(&self) -> u64 {
+        self.start + (self.entry_size * self.entries) as u64
+    }
+}
+
+/// Where an address falls inside a known structure
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Annotation {
+    pub region: &'static str,
+    /// Entry index for table regions (song list, unlock data)
+    pub index: Option<usize>,
+    /// Offset from the start of the structure (or table entry)
+    pub offset: u64,
+    pub field: Option<&'static str>,
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.region)?;
+        if let Some(index) = self.index {
+            write!(f, "[{}]", index)?;
+        }
+        write!(f, "+0x{:X}", self.offset)?;
+        if let Some(field) = self.field {
+            write!(f, " ({})", field)?;
+        }
+        Ok(())
+    }
+}
+
+/// Annotates addresses with the game structure they belong to
+#[derive(Debug, Clone, Default)]
+pub struct MemoryAnnotator {
+    regions: Vec<Region>,
+}
+
+impl MemoryAnnotator {
+    /// Build from the current offsets (zero offsets are skipped)
+    pub fn from_offsets(offsets: &OffsetsCollection) -> Self {
+        let candidates = [
+            Region {
+                name: "SongList",
+                start: offsets.song_list,
+                entry_size: SongInfo::MEMORY_SIZE,
+                entries: MAX_TABLE_ENTRIES,
+                fields: SongInfo::FIELDS,
+            },
+            Region {
+                name: "UnlockData",
+                start: offsets.unlock_data,
+                entry_size: UnlockData::MEMORY_SIZE,
+                entries: MAX_TABLE_ENTRIES,
+                fields: UnlockData::FIELDS,
+            },
+            Region {
+                name: "JudgeData",
+                start: offsets.judge_data,
+                entry_size: JUDGE_SIZE,
+                entries: 1,
+                fields: JUDGE_FIELDS,
+            },
+            Region {
+                name: "PlayData",
+                start: offsets.play_data,
+                entry_size: PLAY_DATA_SIZE,
+                entries: 1,
+                fields: PLAY_DATA_FIELDS,
+            },
+            Region {
+                name: "PlaySettings",
+                start: offsets.play_settings,
+                entry_size: SETTINGS_SIZE,
+                entries: 1,
+                fields: SETTINGS_FIELDS,
+            },
+            Region {
+                name: "CurrentSong",
+                start: offsets.current_song,
+                entry_size: 8,
+                entries: 1,
+                fields: CURRENT_SONG_FIELDS,
+            },
+            Region {
+                name: "DataMap",
+                start: offsets.data_map,
+                entry_size: 16,
+                entries: 1,
+                fields: &[],
+            },
+            Region {
+                name: "Profile",
+                start: offsets.profile,
+                entry_size: ProfileInfo::MEMORY_SIZE,
+                entries: 1,
+                fields: ProfileInfo::FIELDS,
+            },
+            Region {
+                name: "Currency",
+                start: offsets.currency,
+                entry_size: Currency::MEMORY_SIZE,
+                entries: 1,
+                fields: Currency::FIELDS,
+            },
+            Region {
+                name: "LaneCover",
+                start: offsets.lane_cover,
+                entry_size: LaneCover::MEMORY_SIZE,
+                entries: 1,
+                fields: LaneCover::FIELDS,
+            },
+        ];
+        Self {
+            regions: candidates.into_iter().filter(|r| r.start != 0).collect(),
+        }
+    }
+
+    /// Annotation for `address`, if it falls inside a known structure
+    ///
+    /// Single structures win over tables, which are only bounded loosely.
+    pub fn annotate(&self, address: u64) -> Option<Annotation> {
+        let region = self
+            .regions
+            .iter()
+            .filter(|r| (r.start..r.end()).contains(&address))
+            .min_by_key(|r| r.entries)?;
+        let relative = (address - region.start) as usize;
+        let (index, offset) = if region.entries > 1 {
+            (
+                Some(relative / region.entry_size),
+                relative % region.entry_size,
+            )
+        } else {
+            (None, relative)
+        };
+        let field = region
+            .fields
+            .iter()
+            .find(|(start, size, _, _)| (*start..start + size).contains(&offset))
+            .map(|(_, _, name, _)| *name);
+        Some(Annotation {
+            region: region.name,
+            index,
+            offset: offset as u64,
+            field,
+        })
+    }
+}
+
+/// A named structure layout used for field-by-field comparisons
+#[derive(Debug, Clone, Copy)]
+pub struct StructLayout {
+    pub name: &'static str,
+    pub size: usize,
+    fields: &'static [Field],
+}
+
+impl StructLayout {
+    /// Every named layout
+    pub const ALL: &'static [StructLayout] = &[
+        StructLayout {
+            name: "judge",
+            size: JUDGE_SIZE,
+            fields: JUDGE_FIELDS,
+        },
+        StructLayout {
+            name: "judge-player",
+            size: judge::P2_PGREAT as usize,
+            fields: JUDGE_PLAYER_FIELDS,
+        },
+        StructLayout {
+            name: "play-data",
+            size: PLAY_DATA_SIZE,
+            fields: PLAY_DATA_FIELDS,
+        },
+        StructLayout {
+            name: "settings",
+            size: SETTINGS_SIZE,
+            fields: SETTINGS_FIELDS,
+        },
+        StructLayout {
+            name: "current-song",
+            size: 8,
+            fields: CURRENT_SONG_FIELDS,
+        },
+        StructLayout {
+            name: "song",
+            size: SongInfo::MEMORY_SIZE,
+            fields: SongInfo::FIELDS,
+        },
+        StructLayout {
+            name: "unlock",
+            size: UnlockData::MEMORY_SIZE,
+            fields: UnlockData::FIELDS,
+        },
+        StructLayout {
+            name: "profile",
+            size: ProfileInfo::MEMORY_SIZE,
+            fields: ProfileInfo::FIELDS,
+        },
+        StructLayout {
+            name: "currency",
+            size: Currency::MEMORY_SIZE,
+            fields: Currency::FIELDS,
+        },
+        StructLayout {
+            name: "lanecover",
+            size: LaneCover::MEMORY_SIZE,
+            fields: LaneCover::FIELDS,
+        },
+    ];
+
+    /// Look up a layout by name
+    pub fn by_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|l| l.name == name).copied()
+    }
+
+    /// Unnamed layout of `size` bytes, compared as 32-bit words
+    pub fn words(size: usize) -> Self {
+        Self {
+            name: "words",
+            size,
+            fields: &[],
+        }
+    }
+
+    /// Same layout with a different size (fields beyond it are dropped)
+    pub fn with_size(self, size: usize) -> Self {
+        Self { size, ..self }
+    }
+}
+
+/// One compared field (or unnamed word) of a [`diff_struct`] result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldDiff {
+    pub offset: usize,
+    pub size: usize,
+    /// `None` for words between known fields
+    pub field: Option<&'static str>,
+    pub a: String,
+    pub b: String,
+}
+
+impl FieldDiff {
+    pub fn changed(&self) -> bool {
+        self.a != self.b
+    }
+}
+
+/// Render a field value: a single integer, a list of integers, or text
+fn format_field(bytes: &[u8], kind: FieldKind) -> String {
+    let values: Vec<String> = match kind {
+        FieldKind::Text => return format!("{:?}", decode_shift_jis(bytes)),
+        FieldKind::Int => bytes
+            .chunks_exact(4)
+            .map(|w| i32::from_le_bytes([w[0], w[1], w[2], w[3]]).to_string())
+            .collect(),
+        FieldKind::Bytes => bytes.iter().map(u8::to_string).collect(),
+    };
+    match values.as_slice() {
+        [value] => value.clone(),
+        _ => format!("[{}]", values.join(", ")),
+    }
+}
+
+/// Compare `layout` read at two addresses
+///
+/// Named fields are always listed; the 4-byte words between them are
+/// listed only where they differ, which is where new per-player or
+/// per-version fields usually show up.
+pub fn diff_struct<R: ReadMemory + ?Sized>(
+    reader: &R,
+    layout: &StructLayout,
+    a: u64,
+    b: u64,
+) -> Result<Vec<FieldDiff>> {
+    let bytes_a = reader.read_bytes(a, layout.size)?;
+    let bytes_b = reader.read_bytes(b, layout.size)?;
+    let compare = |offset: usize, size: usize, field, kind| FieldDiff {
+        offset,
+        size,
+        field,
+        a: format_field(&bytes_a[offset..offset + size], kind),
+        b: format_field(&bytes_b[offset..offset + size], kind),
+    };
+
+    let mut fields: Vec<_> = layout
+        .fields
+        .iter()
+        .filter(|(offset, size, _, _)| offset + size <= layout.size)
+        .collect();
+    fields.sort_by_key(|(offset, _, _, _)| *offset);
+
+    let mut diffs = Vec::new();
+    let mut offset = 0;
+    for &&(start, size, name, kind) in &fields {
+        diffs.extend(
+            (offset..start)
+                .step_by(4)
+                .filter(|o| o + 4 <= start)
+                .map(|o| compare(o, 4, None, FieldKind::Int))
+                .filter(FieldDiff::changed),
+        );
+        diffs.push(compare(start, size, Some(name), kind));
+        offset = offset.max(start + size);
+    }
+    diffs.extend(
+        (offset..layout.size)
+            .step_by(4)
+            .filter(|o| o + 4 <= layout.size)
+            .map(|o| compare(o, 4, None, FieldKind::Int))
+            .filter(FieldDiff::changed),
+    );
+    Ok(diffs)
+}
+
+/// Where a pointer-like value points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PointerKind {
+    /// Inside the game module image
+    Module,
+    /// Other readable memory (heap, other modules)
+    Heap,
+}
+
+impl fmt::Display for PointerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Module => f.write_str("module"),
+            Self::Heap => f.write_str("heap"),
+        }
+    }
+}
+
+/// Classify `value` as a pointer if it lands in mapped memory
+///
+/// Values inside the module image are accepted without a read; anything
+/// else must be a user-mode address that can actually be read.
+pub fn classify_pointer<R: ReadMemory + ?Sized>(
+    reader: &R,
+    value: u64,
+    module_base: u64,
+    module_size: u64,
+) -> Option<PointerKind> {
+    if !(MIN_POINTER..=MAX_POINTER).contains(&value) {
+        return None;
+    }
+    if (module_base..module_base + module_size).contains(&value) {
+        return Some(PointerKind::Module);
+    }
+    reader.read_bytes(value, 1).ok().map(|_| PointerKind::Heap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::MockMemoryBuilder;
+
+    fn offsets() -> OffsetsCollection {
+        OffsetsCollection {
+            song_list: 0x100_0000,
+            judge_data: 0x20000,
+            play_settings: 0x30000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_annotate_single_structure() {
+        let annotator = MemoryAnnotator::from_offsets(&offsets());
+        let annotation = annotator.annotate(0x20004).unwrap();
+        assert_eq!(annotation.to_string(), "JudgeData+0x4 (P1 Great)");
+        assert_eq!(
+            annotator.annotate(0x30000 + 60).unwrap().field,
+            Some("P2 style")
+        );
+        assert!(annotator.annotate(0x20000 + JUDGE_SIZE as u64).is_none());
+    }
+
+    #[test]
+    fn test_annotate_table_entry() {
+        let annotator = MemoryAnnotator::from_offsets(&offsets());
+        let address = 0x100_0000 + SongInfo::MEMORY_SIZE as u64 * 3 + 816;
+        assert_eq!(
+            annotator.annotate(address).unwrap().to_string(),
+            "SongList[3]+0x330 (song_id)"
+        );
+        // Gaps between known fields have no field name
+        let gap = annotator.annotate(0x100_0000 + 0x100).unwrap();
+        assert_eq!(gap.field, None);
+    }
+
+    #[test]
+    fn test_zero_offsets_are_skipped() {
+        let annotator = MemoryAnnotator::from_offsets(&OffsetsCollection::default());
+        assert!(annotator.annotate(0).is_none());
+        assert!(annotator.annotate(8).is_none());
+    }
+
+    #[test]
+    fn test_diff_struct_lists_fields_and_changed_gaps() {
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x100)
+            // P1 / P2 judge blocks
+            .write_i32(0x00, 1500)
+            .write_i32(0x04, 80)
+            .write_i32(0x14, 1400)
+            .write_i32(0x18, 80)
+            .build();
+        let layout = StructLayout::by_name("judge-player").unwrap();
+        let diffs = diff_struct(&reader, &layout, 0x1000, 0x1014).unwrap();
+        assert_eq!(diffs.len(), 5);
+        assert_eq!(diffs[0].field, Some("PGreat"));
+        assert_eq!((diffs[0].a.as_str(), diffs[0].b.as_str()), ("1500", "1400"));
+        assert!(diffs[0].changed());
+        assert!(!diffs[1].changed());
+
+        // Unnamed words appear only where they differ
+        let diffs = diff_struct(&reader, &StructLayout::words(0x10), 0x1000, 0x1014).unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!((diffs[0].offset, diffs[0].field), (0, None));
+
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x40)
+            .write_i32(0x04, 3)
+            .write_i32(0x0C, 9)
+            .build();
+        let layout = StructLayout::by_name("current-song")
+            .unwrap()
+            .with_size(0x10);
+        let diffs = diff_struct(&reader, &layout, 0x1000, 0x1020).unwrap();
+        let offsets: Vec<_> = diffs.iter().map(|d| (d.offset, d.field)).collect();
+        assert_eq!(
+            offsets,
+            vec![(0, Some("song_id")), (4, Some("difficulty")), (0xC, None)]
+        );
+    }
+
+    #[test]
+    fn test_format_field() {
+        assert_eq!(format_field(&[7], FieldKind::Bytes), "7");
+        assert_eq!(format_field(&(-1i32).to_le_bytes(), FieldKind::Int), "-1");
+        assert_eq!(
+            format_field(&[1, 0, 0, 0, 2, 0, 0, 0], FieldKind::Int),
+            "[1, 2]"
+        );
+        assert_eq!(format_field(&[1, 2, 3], FieldKind::Bytes), "[1, 2, 3]");
+        let mut title = vec![0u8; 64];
+        title[..3].copy_from_slice(b"fun");
+        assert_eq!(format_field(&title, FieldKind::Text), "\"fun\"");
+    }
+
+    #[test]
+    fn test_diff_struct_song_note_counts() {
+        let notes: Vec<u8> = (1..=10i32).flat_map(|n| (n * 100).to_le_bytes()).collect();
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x1000)
+            // SongInfo::NOTES_OFFSET
+            .write_bytes(624, &notes)
+            .build();
+        let layout = StructLayout::by_name("song").unwrap();
+        let diffs = diff_struct(&reader, &layout, 0x1000, 0x1000).unwrap();
+        let total_notes = diffs
+            .iter()
+            .find(|d| d.field == Some("total_notes"))
+            .unwrap();
+        assert_eq!(
+            total_notes.a,
+            "[100, 200, 300, 400, 500, 600, 700, 800, 900, 1000]"
+        );
+    }
+
+    #[test]
+    fn test_classify_pointer() {
+        let reader = MockMemoryBuilder::new()
+            .base(0x7000_0000)
+            .with_size(0x100)
+            .build();
+        // Inside the module range (no read needed)
+        assert_eq!(
+            classify_pointer(&reader, 0x1_4000_1000, 0x1_4000_0000, 0x10000),
+            Some(PointerKind::Module)
+        );
+        // Readable memory outside the module
+        assert_eq!(
+            classify_pointer(&reader, 0x7000_0010, 0x1_4000_0000, 0x10000),
+            Some(PointerKind::Heap)
+        );
+        // Unmapped, too small, or kernel-range values
+        assert_eq!(
+            classify_pointer(&reader, 0x7100_0000, 0x1_4000_0000, 0x10000),
+            None
+        );
+        assert_eq!(classify_pointer(&reader, 0x1234, 0, 0x10000), None);
+        assert_eq!(
+            classify_pointer(&reader, 0xFFFF_8000_0000_0000, 0, 0x10000),
+            None
+        );
+    }
+}
@@ -0,0 +1,436 @@
+//! Song entry layout discovery
+//!
+//! Infers the layout of the song list from a memory region starting at its
+//! first entry, for builds whose entry layout has moved. Every entry starts
+//! with its title, so the stride is the candidate whose slots most often
+//! start with text. The other fields are then located by how their values
+//! look across all populated entries:
+//!
+//! - strings: Shift-JIS text starting right after a NUL, outside the numeric
+//!   fields below
+//! - levels: 10 bytes of at most 12, with N/H levels set for SP and DP
+//! - total notes: 10 `i32` of at most [`MAX_NOTES`], with N/H set for SP and DP
+//! - BPM: max and min `i32`, `1 <= min <= max <= MAX_BPM`
+//! - song ID: an `i32` in the song ID range, distinct across entries
+//! - folder: a byte equal to the song ID's version (`song_id / 1000`)
+//!
+//! The result can be turned into a [`GameLayout`] for the layout registry.
+
+use std::collections::HashSet;
+
+use encoding_rs::SHIFT_JIS;
+use serde::Serialize;
+
+use crate::chart::SongEntryLayout;
+use crate::error::{Error, Result};
+use crate::offset::{GameLayout, RelativeOffsets, builtin_signatures};
+use crate::process::ReadMemory;
+
+/// Smallest stride considered (the four string fields alone take 0x100 bytes)
+const MIN_STRIDE: usize = 0x100;
+/// Largest stride considered
+const MAX_STRIDE: usize = 0x800;
+/// Fewest entries with a title needed to trust a stride
+const MIN_ENTRIES: usize = 4;
+
+/// Highest level of a chart
+const MAX_LEVEL: u8 = 12;
+/// Highest plausible total notes of a chart
+const MAX_NOTES: i32 = 10000;
+/// Highest plausible BPM
+const MAX_BPM: i32 = 1000;
+/// Song ID range (version * 1000 + index)
+const SONG_IDS: std::ops::RangeInclusive<i32> = 1000..=50000;
+
+/// Indices of the NORMAL and HYPER charts of both play styles, set on every
+/// song (ANOTHER is missing on some)
+const CORE_CHARTS: [usize; 4] = [1, 2, 6, 7];
+
+/// Share of populated entries a string field must start in
+const STRING_SHARE: f64 = 0.25;
+/// Share of populated entries a numeric field must fit in
+const FIELD_SHARE: f64 = 0.5;
+/// Strides scoring within this factor of the best count as ties (the
+/// smallest wins, so multiples of the real stride lose)
+const STRIDE_TIE: f64 = 0.95;
+
+/// An inferred field offset
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FieldCandidate {
+    /// Offset from the start of the entry
+    pub offset: usize,
+    /// Share of the populated entries that fit the field
+    pub confidence: f64,
+}
+
+/// Layout inferred by [`LayoutDiscovery`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiscoveredLayout {
+    /// Address of the first entry
+    pub address: u64,
+    pub stride: usize,
+    /// Share of the slots at this stride that start with a title
+    pub stride_confidence: f64,
+    /// Entries with a title, which the fields are inferred from
+    pub entries: usize,
+    /// Offsets where strings start, in entry order
+    pub strings: Vec<FieldCandidate>,
+    pub folder: Option<FieldCandidate>,
+    pub levels: Option<FieldCandidate>,
+    pub bpm: Option<FieldCandidate>,
+    pub notes: Option<FieldCandidate>,
+    pub song_id: Option<FieldCandidate>,
+}
+
+impl DiscoveredLayout {
+    /// The inferred song entry layout; fails if a field wasn't found
+    pub fn song_entry_layout(&self) -> Result<SongEntryLayout> {
+        let fields = [
+            ("folder", self.folder),
+            ("levels", self.levels),
+            ("bpm", self.bpm),
+            ("notes", self.notes),
+            ("song_id", self.song_id),
+        ];
+        let missing: Vec<&str> = fields
+            .iter()
+            .filter(|(_, field)| field.is_none())
+            .map(|(name, _)| *name)
+            .collect();
+        if !missing.is_empty() {
+            return Err(Error::parse(
+                "song entry layout",
+                format!("no candidate for {}", missing.join(", ")),
+            ));
+        }
+        let offset = |field: Option<FieldCandidate>| field.map_or(0, |f| f.offset);
+        Ok(SongEntryLayout {
+            entry_size: self.stride,
+            folder: offset(self.folder),
+            levels: offset(self.levels),
+            bpm: offset(self.bpm),
+            notes: offset(self.notes),
+            song_id: offset(self.song_id),
+        })
+    }
+
+    /// Candidate layout for the builds from `since` (date code)
+    ///
+    /// Only the song entry is inferred; the relative offsets and signatures
+    /// are those of the newest known layout.
+    pub fn game_layout(&self, since: &str) -> Result<GameLayout> {
+        Ok(GameLayout {
+            since: since.to_string(),
+            song_entry: self.song_entry_layout()?,
+            relative: RelativeOffsets::CURRENT,
+            signatures: builtin_signatures(),
+        })
+    }
+}
+
+/// Statistical song entry layout inference
+#[derive(Debug, Clone)]
+pub struct LayoutDiscovery {
+    min_stride: usize,
+    max_stride: usize,
+}
+
+impl Default for LayoutDiscovery {
+    fn default() -> Self {
+        Self {
+            min_stride: MIN_STRIDE,
+            max_stride: MAX_STRIDE,
+        }
+    }
+}
+
+impl LayoutDiscovery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only consider strides in `min..=max` (multiples of 4)
+    pub fn with_stride_range(mut self, min: usize, max: usize) -> Self {
+        self.min_stride = min.max(4);
+        self.max_stride = max.max(self.min_stride);
+        self
+    }
+
+    /// Read `size` bytes at `address` and infer the layout
+    pub fn read<R: ReadMemory>(
+        &self,
+        reader: &R,
+        address: u64,
+        size: usize,
+    ) -> Result<DiscoveredLayout> {
+        let region = reader.read_bytes(address, size)?;
+        self.discover(&region, address)
+    }
+
+    /// Infer the layout of the song list whose first entry starts `region`
+    /// (at `address`)
+    pub fn discover(&self, region: &[u8], address: u64) -> Result<DiscoveredLayout> {
+        let (stride, stride_confidence) = self.find_stride(region).ok_or_else(|| {
+            Error::parse(
+                "song entry layout",
+                format!(
+                    "no stride in 0x{:X}..=0x{:X} has {} entries with a title",
+                    self.min_stride, self.max_stride, MIN_ENTRIES
+                ),
+            )
+        })?;
+        let entries: Vec<&[u8]> = region
+            .chunks_exact(stride)
+            .filter(|entry| text_at(entry, 0))
+            .collect();
+
+        let levels = best_field(&entries, stride, 1, 10, |entry, offset| {
+            levels_at(entry, offset)
+        });
+        let notes = best_field(&entries, stride, 4, 40, |entry, offset| {
+            notes_at(entry, offset)
+        });
+        let bpm = best_field(&entries, stride, 4, 8, |entry, offset| {
+            let (max, min) = (i32_at(entry, offset), i32_at(entry, offset + 4));
+            1 <= min && min <= max && max <= MAX_BPM
+        });
+        // Total notes and the like can look like song IDs too
+        let mut taken: Vec<(usize, usize)> = [(levels, 10), (notes, 40), (bpm, 8)]
+            .into_iter()
+            .filter_map(|(field, len)| field.map(|f| (f.offset, len)))
+            .collect();
+        let song_id = find_song_id(&entries, stride, &taken);
+        let folder = song_id.and_then(|id| {
+            taken.push((id.offset, 4));
+            best_field(&entries, stride, 1, 1, |entry, offset| {
+                let version = i32_at(entry, id.offset) / 1000;
+                !overlaps(&taken, offset, 1) && i32::from(entry[offset]) == version
+            })
+        });
+        taken.extend(folder.map(|f| (f.offset, 1)));
+        // Numeric fields can read as short text
+        let strings = (0..stride)
+            .step_by(4)
+            .filter(|&offset| !overlaps(&taken, offset, 1))
+            .filter_map(|offset| {
+                let confidence = share(&entries, |entry| {
+                    (offset == 0 || entry[offset - 1] == 0) && text_at(entry, offset)
+                });
+                (confidence >= STRING_SHARE).then_some(FieldCandidate { offset, confidence })
+            })
+            .collect();
+
+        Ok(DiscoveredLayout {
+            address,
+            stride,
+            stride_confidence,
+            entries: entries.len(),
+            strings,
+            folder,
+            levels,
+            bpm,
+            notes,
+            song_id,
+        })
+    }
+
+    /// Stride whose slots most often start with a title, and that share
+    fn find_stride(&self, region: &[u8]) -> Option<(usize, f64)> {
+        let first = self.min_stride.next_multiple_of(4);
+        let candidates: Vec<(usize, f64)> = (first..=self.max_stride)
+            .step_by(4)
+            .filter_map(|stride| {
+                let slots = region.len() / stride;
+                let titled = (0..slots)
+                    .filter(|slot| text_at(&region[slot * stride..], 0))
+                    .count();
+                (titled >= MIN_ENTRIES).then(|| (stride, titled as f64 / slots as f64))
+            })
+            .collect();
+        let best = candidates
+            .iter()
+            .map(|(_, confidence)| *confidence)
+            .fold(0.0, f64::max);
+        candidates
+            .into_iter()
+            .find(|(_, confidence)| *confidence >= best * STRIDE_TIE)
+    }
+}
+
+/// Share of `entries` for which `fits` holds
+fn share(entries: &[&[u8]], fits: impl Fn(&[u8]) -> bool) -> f64 {
+    if entries.is_empty() {
+        return 0.0;
+    }
+    entries.iter().filter(|entry| fits(entry)).count() as f64 / entries.len() as f64
+}
+
+/// Offset (in steps of `align`) of a `len`-byte field that fits the most
+/// entries, if it fits at least [`FIELD_SHARE`] of them
+fn best_field(
+    entries: &[&[u8]],
+    stride: usize,
+    align: usize,
+    len: usize,
+    fits: impl Fn(&[u8], usize) -> bool,
+) -> Option<FieldCandidate> {
+    (0..stride.saturating_sub(len - 1))
+        .step_by(align)
+        .map(|offset| FieldCandidate {
+            offset,
+            confidence: share(entries, |entry| fits(entry, offset)),
+        })
+        .fold(None, |best: Option<FieldCandidate>, candidate| match best {
+            Some(best) if best.confidence >= candidate.confidence => Some(best),
+            _ => Some(candidate),
+        })
+        .filter(|best| best.confidence >= FIELD_SHARE)
+}
+
+/// Whether `len` bytes at `offset` overlap one of the `(offset, len)` fields
+fn overlaps(fields: &[(usize, usize)], offset: usize, len: usize) -> bool {
+    fields
+        .iter()
+        .any(|&(start, field_len)| offset < start + field_len && start < offset + len)
+}
+
+/// `i32` offset outside the `taken` fields whose values are song IDs, scored
+/// by the share of entries in the ID range times the share of distinct values
+fn find_song_id(
+    entries: &[&[u8]],
+    stride: usize,
+    taken: &[(usize, usize)],
+) -> Option<FieldCandidate> {
+    (0..stride.saturating_sub(3))
+        .step_by(4)
+        .filter(|&offset| !overlaps(taken, offset, 4))
+        .map(|offset| {
+            let ids: Vec<i32> = entries
+                .iter()
+                .map(|entry| i32_at(entry, offset))
+                .filter(|id| SONG_IDS.contains(id))
+                .collect();
+            let distinct = ids.iter().collect::<HashSet<_>>().len();
+            let confidence = if ids.is_empty() {
+                0.0
+            } else {
+                (ids.len() as f64 / entries.len() as f64) * (distinct as f64 / ids.len() as f64)
+            };
+            FieldCandidate { offset, confidence }
+        })
+        .fold(None, |best: Option<FieldCandidate>, candidate| match best {
+            Some(best) if best.confidence >= candidate.confidence => Some(best),
+            _ => Some(candidate),
+        })
+        .filter(|best| best.confidence >= FIELD_SHARE)
+}
+
+/// Whether Shift-JIS text starts at `offset`
+fn text_at(entry: &[u8], offset: usize) -> bool {
+    let Some(bytes) = entry.get(offset..entry.len().min(offset + 64)) else {
+        return false;
+    };
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    if len == 0 || len == bytes.len() {
+        return false;
+    }
+    let (decoded, _, had_errors) = SHIFT_JIS.decode(&bytes[..len]);
+    !had_errors && !decoded.chars().any(char::is_control)
+}
+
+/// Whether a level array sits at `offset`
+fn levels_at(entry: &[u8], offset: usize) -> bool {
+    let levels = &entry[offset..offset + 10];
+    levels.iter().all(|&level| level <= MAX_LEVEL) && CORE_CHARTS.iter().all(|&i| levels[i] > 0)
+}
+
+/// Whether a total notes array sits at `offset`
+fn notes_at(entry: &[u8], offset: usize) -> bool {
+    let notes: Vec<i32> = (0..10).map(|i| i32_at(entry, offset + i * 4)).collect();
+    notes.iter().all(|n| (0..=MAX_NOTES).contains(n)) && CORE_CHARTS.iter().all(|&i| notes[i] > 0)
+}
+
+fn i32_at(entry: &[u8], offset: usize) -> i32 {
+    entry
+        .get(offset..offset + 4)
+        .map_or(0, |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Song list of `count` entries in `layout`, with the string fields at
+    /// the offsets shared by every known layout
+    fn song_list(layout: &SongEntryLayout, count: usize) -> Vec<u8> {
+        let mut region = vec![0u8; layout.entry_size * count];
+        for (i, entry) in region.chunks_exact_mut(layout.entry_size).enumerate() {
+            let song_id = 1000 * (i as i32 % 30 + 1) + i as i32;
+            for (offset, text) in [
+                (0, format!("Song {i}")),
+                (64, format!("Song {i} (EN)")),
+                (128, "GENRE".to_string()),
+                (192, format!("Artist {}", i % 7)),
+            ] {
+                entry[offset..offset + text.len()].copy_from_slice(text.as_bytes());
+            }
+            entry[layout.folder] = (song_id / 1000) as u8;
+            let levels = [0, 3, 7, 10, 11, 0, 4, 8, 11, (i % 13) as u8];
+            entry[layout.levels..layout.levels + 10].copy_from_slice(&levels);
+            let bpm = [180 + i as i32 % 20, 150];
+            for (j, value) in bpm.iter().enumerate() {
+                let at = layout.bpm + j * 4;
+                entry[at..at + 4].copy_from_slice(&value.to_le_bytes());
+            }
+            for (j, level) in levels.iter().enumerate() {
+                let at = layout.notes + j * 4;
+                let notes = i32::from(*level) * 150 + i as i32 % 50;
+                entry[at..at + 4].copy_from_slice(&notes.to_le_bytes());
+            }
+            entry[layout.song_id..layout.song_id + 4].copy_from_slice(&song_id.to_le_bytes());
+        }
+        region
+    }
+
+    #[test]
+    fn test_discovers_known_layouts() {
+        for layout in [SongEntryLayout::CURRENT, SongEntryLayout::LEGACY_3F0] {
+            let region = song_list(&layout, 40);
+            let found = LayoutDiscovery::new().discover(&region, 0x1000).unwrap();
+            assert_eq!(found.stride, layout.entry_size);
+            assert_eq!(found.entries, 40);
+            let strings: Vec<usize> = found.strings.iter().map(|s| s.offset).collect();
+            assert_eq!(strings, [0, 64, 128, 192]);
+            assert_eq!(found.song_entry_layout().unwrap(), layout);
+        }
+    }
+
+    #[test]
+    fn test_skips_empty_slots() {
+        let layout = SongEntryLayout::CURRENT;
+        let mut region = song_list(&layout, 40);
+        for slot in [3, 10, 11, 25] {
+            let start = slot * layout.entry_size;
+            region[start..start + layout.entry_size].fill(0);
+        }
+        let found = LayoutDiscovery::new().discover(&region, 0).unwrap();
+        assert_eq!(found.stride, layout.entry_size);
+        assert_eq!(found.entries, 36);
+
+        let game_layout = found.game_layout("2026050100").unwrap();
+        assert_eq!(game_layout.since, "2026050100");
+        assert_eq!(game_layout.song_entry, layout);
+    }
+
+    #[test]
+    fn test_fails_without_entries() {
+        assert!(LayoutDiscovery::new().discover(&[0; 0x4000], 0).is_err());
+
+        // Strides below the real one only find some of the fields
+        let region = song_list(&SongEntryLayout::CURRENT, 40);
+        let narrow = LayoutDiscovery::new().with_stride_range(0x100, 0x200);
+        let found = narrow.discover(&region, 0).unwrap();
+        assert_ne!(found.stride, SongEntryLayout::CURRENT.entry_size);
+        assert!(found.song_entry_layout().is_err());
+    }
+}
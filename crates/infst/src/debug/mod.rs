@@ -4,11 +4,35 @@
 //! - Checking game and offset status (`StatusInfo`)
 //! - Dumping memory structures (`DumpInfo`)
 //! - Scanning for song data (`ScanResult`)
+//! - Dumping the raw score hashmap (`RawScoreMapDump`)
+//! - Annotating addresses and detecting pointers (`MemoryAnnotator`)
+//! - Running every parser against the live game (`SelfTestReport`)
+//! - Inferring the song entry layout of a new build (`LayoutDiscovery`)
+//! - Searching memory for bytes, values and strings (`find_matches`)
 
 mod dump;
+mod inspect;
+mod layout;
 mod scan;
+mod score_map;
+mod search;
+mod selftest;
 mod status;
 
 pub use dump::{DumpInfo, MemoryDump};
+pub use inspect::{
+    Annotation, FieldDiff, MemoryAnnotator, PointerKind, StructLayout, classify_pointer,
+    diff_struct,
+};
+pub use layout::{DiscoveredLayout, FieldCandidate, LayoutDiscovery};
 pub use scan::{ScanResult, ScannedSong};
-pub use status::{OffsetStatus, OffsetValidation, StatusInfo};
+pub use score_map::{RawScoreMapDump, RawScoreNode};
+pub use search::{
+    SONG_IDS, SearchPattern, StringEncoding, StringMatch, ValueRange, build_regex, default_range,
+    extract_strings, find_in_range, find_matches, find_strings,
+};
+pub use selftest::{CheckStatus, ParserCheck, SelfTestReport};
+pub use status::{
+    OffsetStatus, OffsetValidation, ScoreMapHealth, SongDbHealth, StatusInfo, SubsystemHealth,
+    UnlockDataHealth,
+};
@@ -0,0 +1,152 @@
+//! Raw score map dump for debugging
+//!
+//! Walks the in-game score hashmap without filtering by the song database and
+//! records every node as read, so structure regressions after game updates can
+//! be diagnosed from a user's JSON dump.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::process::ReadMemory;
+use crate::score::{HashTable, ListNode};
+
+/// Maximum number of nodes followed in a single chain
+const MAX_CHAIN_LENGTH: usize = 10_000;
+
+/// One node of the score hashmap, as read from memory
+#[derive(Debug, Clone, Serialize)]
+pub struct RawScoreNode {
+    /// Hash table bucket the chain starts from
+    pub bucket: usize,
+    pub address: String,
+    pub next: String,
+    pub prev: String,
+    pub song_id: i32,
+    /// Difficulty within the play type (0-4)
+    pub difficulty: i32,
+    /// 0 = SP, 1 = DP
+    pub playtype: i32,
+    pub score: u32,
+    /// `u32::MAX` when unavailable
+    pub miss_count: u32,
+    pub lamp: i32,
+    /// Whole node as hex bytes (includes fields not decoded above)
+    pub raw: String,
+}
+
+/// Raw dump of the score hashmap
+#[derive(Debug, Clone, Serialize)]
+pub struct RawScoreMapDump {
+    pub data_map: String,
+    pub null_object: String,
+    pub table_start: String,
+    pub table_end: String,
+    pub bucket_count: usize,
+    pub non_empty_buckets: usize,
+    pub nodes: Vec<RawScoreNode>,
+    /// Chains that ended on a read error, as (bucket, address)
+    pub read_errors: Vec<(usize, String)>,
+}
+
+impl RawScoreMapDump {
+    /// Walk every bucket chain of the score hashmap at `data_map`
+    pub fn collect<R: ReadMemory + ?Sized>(reader: &R, data_map: u64) -> Result<Self> {
+        let table = HashTable::read(reader, data_map)?;
+        let mut nodes = Vec::new();
+        let mut read_errors = Vec::new();
+        let mut visited: HashSet<u64> = HashSet::new();
+
+        for &(bucket, entry_point) in &table.entry_points {
+            let mut current = entry_point;
+            for _ in 0..MAX_CHAIN_LENGTH {
+                if !visited.insert(current) {
+                    break;
+                }
+                let bytes = match reader.read_bytes(current, ListNode::SIZE) {
+                    Ok(bytes) => bytes,
+                    Err(_) => {
+                        read_errors.push((bucket, format!("0x{:X}", current)));
+                        break;
+                    }
+                };
+                let node = ListNode::from_bytes(&bytes);
+                nodes.push(RawScoreNode {
+                    bucket,
+                    address: format!("0x{:X}", current),
+                    next: format!("0x{:X}", node.next),
+                    prev: format!("0x{:X}", node.prev),
+                    song_id: node.song,
+                    difficulty: node.diff,
+                    playtype: node.playtype,
+                    score: node.score,
+                    miss_count: node.miss_count,
+                    lamp: node.lamp,
+                    raw: bytes.iter().map(|b| format!("{:02X}", b)).collect(),
+                });
+                if node.next == 0 || node.next == table.null_obj {
+                    break;
+                }
+                current = node.next;
+            }
+        }
+
+        Ok(Self {
+            data_map: format!("0x{:X}", data_map),
+            null_object: format!("0x{:X}", table.null_obj),
+            table_start: format!("0x{:X}", table.start_address),
+            table_end: format!("0x{:X}", table.end_address),
+            bucket_count: (table.end_address.saturating_sub(table.start_address) / 8) as usize,
+            non_empty_buckets: table.entry_points.len(),
+            nodes,
+            read_errors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::MockMemoryBuilder;
+
+    #[test]
+    fn test_collect_walks_chains() {
+        // Layout: null object pointer at 0x00, data map at 0x10,
+        // 2-bucket table at 0x20, nodes at 0x40 and 0x80
+        let base = 0x1000;
+        let reader = MockMemoryBuilder::new()
+            .base(base)
+            .with_size(0x100)
+            .write_u64(0x00, 0xDEAD)
+            .write_u64(0x10, base + 0x20)
+            .write_u64(0x18, base + 0x30)
+            .write_u64(0x20, base + 0x40)
+            .write_u64(0x28, 0xDEAD)
+            // node 1 -> node 2
+            .write_u64(0x40, base + 0x80)
+            .write_i32(0x50, 3)
+            .write_i32(0x54, 1000)
+            .write_u32(0x60, 1500)
+            .write_u32(0x64, 12)
+            .write_i32(0x70, 5)
+            // node 2 (unknown song id is kept in raw dumps)
+            .write_u64(0x80, 0xDEAD)
+            .write_i32(0x94, 99999)
+            .write_i32(0x98, 1)
+            .build();
+
+        let dump = RawScoreMapDump::collect(&reader, base + 0x10).unwrap();
+        assert_eq!(dump.bucket_count, 2);
+        assert_eq!(dump.non_empty_buckets, 1);
+        assert_eq!(dump.nodes.len(), 2);
+        assert_eq!(dump.nodes[0].song_id, 1000);
+        assert_eq!(dump.nodes[0].difficulty, 3);
+        assert_eq!(dump.nodes[0].score, 1500);
+        assert_eq!(dump.nodes[0].lamp, 5);
+        assert_eq!(dump.nodes[0].raw.len(), ListNode::SIZE * 2);
+        assert_eq!(dump.nodes[1].song_id, 99999);
+        assert_eq!(dump.nodes[1].playtype, 1);
+        assert!(dump.read_errors.is_empty());
+    }
+}
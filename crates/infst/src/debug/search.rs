@@ -0,0 +1,668 @@
+//! Memory search engine behind `infst search`, `explore` and `analyze`
+//!
+//! Byte patterns (with wildcards and ASCII case folding), numeric value
+//! ranges (optionally repeated at a stride, as in tables) and decoded strings
+//! matched against a regex, all scanned in overlapping 4MB chunks.
+
+use std::ops::Range;
+
+use regex_automata::meta::Regex;
+use regex_automata::util::syntax;
+
+use crate::error::{Error, Result};
+use crate::process::ReadMemory;
+
+/// Text encoding of searched strings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StringEncoding {
+    #[default]
+    ShiftJis,
+    Utf8,
+    Utf16le,
+    Ascii,
+}
+
+impl StringEncoding {
+    /// Encode one character (`None` if the encoding can't represent it)
+    fn encode_char(self, c: char) -> Option<Vec<u8>> {
+        let mut buf = [0u8; 4];
+        let s = c.encode_utf8(&mut buf);
+        match self {
+            Self::ShiftJis => {
+                let (encoded, _, had_errors) = encoding_rs::SHIFT_JIS.encode(s);
+                (!had_errors).then(|| encoded.into_owned())
+            }
+            Self::Utf8 => Some(s.as_bytes().to_vec()),
+            Self::Utf16le => Some(
+                c.encode_utf16(&mut [0u16; 2])
+                    .iter()
+                    .flat_map(|unit| unit.to_le_bytes())
+                    .collect(),
+            ),
+            Self::Ascii => c.is_ascii().then(|| vec![c as u8]),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ShiftJis => "Shift-JIS",
+            Self::Utf8 => "UTF-8",
+            Self::Utf16le => "UTF-16LE",
+            Self::Ascii => "ASCII",
+        }
+    }
+}
+
+/// Bytes to search for; `true` in the mask marks a wildcard byte
+pub struct SearchPattern {
+    pub bytes: Vec<u8>,
+    pub mask: Vec<bool>,
+    /// `true` marks ASCII letter bytes that match either case
+    pub fold: Vec<bool>,
+}
+
+impl SearchPattern {
+    /// Exact byte sequence
+    pub fn exact(bytes: Vec<u8>) -> Self {
+        let mask = vec![false; bytes.len()];
+        let fold = vec![false; bytes.len()];
+        Self { bytes, mask, fold }
+    }
+
+    /// String encoded as Shift-JIS
+    pub fn string(s: &str) -> Self {
+        let (encoded, _, _) = encoding_rs::SHIFT_JIS.encode(s);
+        Self::exact(encoded.to_vec())
+    }
+
+    /// String in the given encoding, optionally matching ASCII letters in
+    /// either case
+    ///
+    /// Only bytes encoding ASCII letters are folded, so multi-byte
+    /// characters (e.g., Shift-JIS trail bytes) still match exactly.
+    pub fn text(s: &str, encoding: StringEncoding, ignore_case: bool) -> Result<Self> {
+        let mut bytes = Vec::new();
+        let mut fold = Vec::new();
+        for c in s.chars() {
+            let Some(encoded) = encoding.encode_char(c) else {
+                return Err(Error::parse(
+                    "search string",
+                    format!("{:?} cannot be encoded as {}", c, encoding.label()),
+                ));
+            };
+            let foldable = ignore_case && c.is_ascii_alphabetic();
+            fold.extend(encoded.iter().map(|b| foldable && b.is_ascii_alphabetic()));
+            bytes.extend(encoded);
+        }
+        if bytes.is_empty() {
+            return Err(Error::parse("search string", "empty"));
+        }
+        let mask = vec![false; bytes.len()];
+        Ok(Self { bytes, mask, fold })
+    }
+
+    pub fn i32(value: i32) -> Self {
+        Self::exact(value.to_le_bytes().to_vec())
+    }
+
+    pub fn i16(value: i16) -> Self {
+        Self::exact(value.to_le_bytes().to_vec())
+    }
+
+    /// Parse a byte pattern (e.g., "00 04 07 0A" or "00 ?? 07")
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let mut bytes = Vec::new();
+        let mut mask = Vec::new();
+        for part in pattern.split_whitespace() {
+            if part == "??" {
+                bytes.push(0);
+                mask.push(true); // wildcard
+            } else {
+                let byte = u8::from_str_radix(part, 16).map_err(|_| {
+                    Error::parse("byte pattern", format!("invalid hex byte: {}", part))
+                })?;
+                bytes.push(byte);
+                mask.push(false);
+            }
+        }
+        if bytes.is_empty() {
+            return Err(Error::parse("byte pattern", "empty"));
+        }
+        let fold = vec![false; bytes.len()];
+        Ok(Self { bytes, mask, fold })
+    }
+
+    fn matches_at(&self, buffer: &[u8]) -> bool {
+        self.bytes
+            .iter()
+            .zip(self.mask.iter().zip(&self.fold))
+            .zip(buffer)
+            .all(|((byte, (wildcard, fold)), actual)| {
+                *wildcard || byte == actual || (*fold && byte.eq_ignore_ascii_case(actual))
+            })
+    }
+}
+
+/// Default search range: from 16MB into the module up to 80MB
+pub fn default_range(base_address: u64, module_size: u32) -> (u64, u64) {
+    let start = base_address + 0x1000000;
+    let end = base_address + (module_size as u64).min(0x5000000);
+    (start, end)
+}
+
+/// Find up to `limit` addresses in `start..end` where the pattern matches
+pub fn find_matches<R: ReadMemory + ?Sized>(
+    reader: &R,
+    pattern: &SearchPattern,
+    start: u64,
+    end: u64,
+    limit: usize,
+) -> Vec<u64> {
+    let chunk_size: usize = 4 * 1024 * 1024; // 4MB chunks
+    let mut found: Vec<u64> = Vec::new();
+    let mut offset = 0u64;
+
+    while start + offset < end && found.len() < limit {
+        let addr = start + offset;
+        // Overlap chunks so matches across a chunk boundary are not missed
+        let read_size = (chunk_size + pattern.bytes.len() - 1).min((end - addr) as usize);
+
+        if let Ok(buffer) = reader.read_bytes(addr, read_size)
+            && buffer.len() >= pattern.bytes.len()
+        {
+            let last = (buffer.len() - pattern.bytes.len()).min(chunk_size - 1);
+            for i in 0..=last {
+                if pattern.matches_at(&buffer[i..]) {
+                    found.push(addr + i as u64);
+                    if found.len() >= limit {
+                        break;
+                    }
+                }
+            }
+        }
+
+        offset += chunk_size as u64;
+    }
+
+    found
+}
+
+/// Song IDs as found in song list entries
+pub const SONG_IDS: ValueRange = ValueRange {
+    min: 1000,
+    max: 50000,
+    width: 4,
+    align: 4,
+    stride: 0,
+    run: 1,
+};
+
+/// Numeric range search: little-endian signed values in `min..=max`
+///
+/// With a stride, an address only matches when `run` consecutive entries
+/// (`address + k * stride`) are all in range, which is how tables such as
+/// the song list are located.
+#[derive(Debug, Clone, Copy)]
+pub struct ValueRange {
+    pub min: i64,
+    pub max: i64,
+    /// Value width in bytes (1, 2, 4 or 8)
+    pub width: usize,
+    pub align: u64,
+    pub stride: u64,
+    pub run: usize,
+}
+
+impl ValueRange {
+    pub fn new(min: i64, max: i64, width: usize) -> Result<Self> {
+        if ![1, 2, 4, 8].contains(&width) {
+            return Err(Error::parse(
+                "value range",
+                format!("invalid value width: {} (use 1, 2, 4 or 8)", width),
+            ));
+        }
+        if min > max {
+            return Err(Error::parse(
+                "value range",
+                format!("invalid range: {} > {}", min, max),
+            ));
+        }
+        Ok(Self {
+            min,
+            max,
+            width,
+            align: width as u64,
+            stride: 0,
+            run: 1,
+        })
+    }
+
+    pub fn with_align(self, align: u64) -> Result<Self> {
+        if align == 0 {
+            return Err(Error::parse(
+                "value range",
+                "alignment must be greater than zero",
+            ));
+        }
+        Ok(Self { align, ..self })
+    }
+
+    pub fn with_stride(self, stride: u64, run: usize) -> Self {
+        Self {
+            stride,
+            run,
+            ..self
+        }
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+
+    fn value_at(&self, buffer: &[u8], offset: usize) -> Option<i64> {
+        let bytes = buffer.get(offset..offset + self.width)?;
+        Some(match self.width {
+            1 => bytes[0] as i8 as i64,
+            2 => i16::from_le_bytes(bytes.try_into().ok()?) as i64,
+            4 => i32::from_le_bytes(bytes.try_into().ok()?) as i64,
+            _ => i64::from_le_bytes(bytes.try_into().ok()?),
+        })
+    }
+
+    /// Whether the value at `offset` in `buffer` is in range
+    pub fn contains_at(&self, buffer: &[u8], offset: usize) -> bool {
+        self.value_at(buffer, offset)
+            .is_some_and(|value| self.contains(value))
+    }
+
+    /// Read one value from memory
+    pub fn read<R: ReadMemory + ?Sized>(&self, reader: &R, address: u64) -> Option<i64> {
+        let bytes = reader.read_bytes(address, self.width).ok()?;
+        self.value_at(&bytes, 0)
+    }
+
+    /// Number of consecutive in-range entries at `address`, `address + stride`, ...
+    pub fn run_length<R: ReadMemory + ?Sized>(
+        &self,
+        reader: &R,
+        address: u64,
+        stride: u64,
+        max: usize,
+    ) -> usize {
+        (0..max)
+            .take_while(|&k| {
+                self.read(reader, address + k as u64 * stride)
+                    .is_some_and(|value| self.contains(value))
+            })
+            .count()
+    }
+
+    /// Bytes spanned by one match
+    fn span(&self) -> usize {
+        (self.run.max(1) - 1) * self.stride as usize + self.width
+    }
+
+    fn matches_at(&self, buffer: &[u8], offset: usize) -> bool {
+        (0..self.run.max(1)).all(|k| self.contains_at(buffer, offset + k * self.stride as usize))
+    }
+}
+
+/// Find up to `limit` aligned addresses in `start..end` matching `range`
+pub fn find_in_range<R: ReadMemory + ?Sized>(
+    reader: &R,
+    range: &ValueRange,
+    start: u64,
+    end: u64,
+    limit: usize,
+) -> Vec<u64> {
+    let chunk_size: usize = 4 * 1024 * 1024; // 4MB chunks
+    let span = range.span();
+    let mut found: Vec<u64> = Vec::new();
+    let mut offset = 0u64;
+
+    while start + offset < end && found.len() < limit {
+        let addr = start + offset;
+        // Overlap chunks so runs across a chunk boundary are not missed
+        let read_size = (chunk_size + span - 1).min((end - addr) as usize);
+
+        if let Ok(buffer) = reader.read_bytes(addr, read_size)
+            && buffer.len() >= span
+        {
+            let first = ((range.align - addr % range.align) % range.align) as usize;
+            let last = (buffer.len() - span).min(chunk_size - 1);
+            for i in (first..=last).step_by(range.align as usize) {
+                if range.matches_at(&buffer, i) {
+                    found.push(addr + i as u64);
+                    if found.len() >= limit {
+                        break;
+                    }
+                }
+            }
+        }
+
+        offset += chunk_size as u64;
+    }
+
+    found
+}
+
+/// Longest string considered by `find_strings` (in bytes)
+const MAX_STRING_BYTES: usize = 1024;
+
+/// A decoded string found in memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringMatch {
+    pub address: u64,
+    pub text: String,
+}
+
+/// Compile a regex for `find_strings`
+pub fn build_regex(expr: &str, ignore_case: bool) -> Result<Regex> {
+    Regex::builder()
+        .syntax(syntax::Config::new().case_insensitive(ignore_case))
+        .build(expr)
+        .map_err(|e| Error::parse("regex", e))
+}
+
+/// Printable runs in `buffer` decoded with `encoding`, as (byte range, text)
+///
+/// Runs end at control characters (including NUL); runs that fail to
+/// decode or are shorter than `min_len` characters are skipped.
+pub fn extract_strings(
+    buffer: &[u8],
+    encoding: StringEncoding,
+    min_len: usize,
+) -> Vec<(Range<usize>, String)> {
+    let mut strings = Vec::new();
+    let mut push = |bytes: Range<usize>, text: Option<String>| {
+        if let Some(text) = text
+            && text.chars().count() >= min_len.max(1)
+            && !text.chars().any(char::is_control)
+        {
+            strings.push((bytes, text));
+        }
+    };
+
+    if encoding == StringEncoding::Utf16le {
+        let units: Vec<u16> = buffer
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        for (start, run) in printable_runs(&units, |unit| unit >= 0x20 && unit != 0x7F) {
+            push(
+                start * 2..(start + run.len()) * 2,
+                String::from_utf16(run).ok(),
+            );
+        }
+    } else {
+        for (start, run) in printable_runs(buffer, |byte| byte >= 0x20 && byte != 0x7F) {
+            let text = match encoding {
+                StringEncoding::ShiftJis => encoding_rs::SHIFT_JIS
+                    .decode_without_bom_handling_and_without_replacement(run)
+                    .map(|text| text.into_owned()),
+                StringEncoding::Ascii => run
+                    .is_ascii()
+                    .then(|| run.iter().map(|&b| b as char).collect()),
+                _ => std::str::from_utf8(run).ok().map(str::to_string),
+            };
+            push(start..start + run.len(), text);
+        }
+    }
+    strings
+}
+
+/// Maximal runs of elements satisfying `printable`, as (start index, run)
+fn printable_runs<T: Copy>(
+    items: &[T],
+    printable: impl Fn(T) -> bool,
+) -> impl Iterator<Item = (usize, &[T])> {
+    let mut start = 0;
+    items
+        .split(move |&item| !printable(item))
+        .filter_map(move |run| {
+            let run_start = start;
+            start += run.len() + 1;
+            (!run.is_empty()).then_some((run_start, run))
+        })
+}
+
+/// Find up to `limit` decoded strings in `start..end` that match `regex`
+pub fn find_strings<R: ReadMemory + ?Sized>(
+    reader: &R,
+    regex: &Regex,
+    encoding: StringEncoding,
+    min_len: usize,
+    start: u64,
+    end: u64,
+    limit: usize,
+) -> Vec<StringMatch> {
+    let chunk_size: usize = 4 * 1024 * 1024; // 4MB chunks
+    let mut found = Vec::new();
+    let mut offset = 0u64;
+    // Strings crossing a chunk boundary were already seen in full
+    let mut covered_until = start;
+
+    while start + offset < end && found.len() < limit {
+        let addr = start + offset;
+        let read_size = (chunk_size + MAX_STRING_BYTES).min((end - addr) as usize);
+
+        if let Ok(buffer) = reader.read_bytes(addr, read_size) {
+            for (bytes, text) in extract_strings(&buffer, encoding, min_len) {
+                let address = addr + bytes.start as u64;
+                if bytes.start >= chunk_size || address < covered_until {
+                    continue;
+                }
+                // Encoded length: decoded text can be longer or shorter
+                covered_until = addr + bytes.end as u64;
+                if regex.is_match(&text) {
+                    found.push(StringMatch { address, text });
+                    if found.len() >= limit {
+                        break;
+                    }
+                }
+            }
+        }
+
+        offset += chunk_size as u64;
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::MockMemoryBuilder;
+
+    #[test]
+    fn test_parse_pattern_with_wildcards() {
+        let pattern = SearchPattern::parse("00 ?? 0A").unwrap();
+        assert_eq!(pattern.bytes, vec![0x00, 0x00, 0x0A]);
+        assert_eq!(pattern.mask, vec![false, true, false]);
+        assert!(SearchPattern::parse("0G").is_err());
+        assert!(SearchPattern::parse("").is_err());
+    }
+
+    #[test]
+    fn test_text_encodings() {
+        let utf16 = SearchPattern::text("Aй", StringEncoding::Utf16le, false).unwrap();
+        assert_eq!(utf16.bytes, vec![0x41, 0x00, 0x39, 0x04]);
+        let utf8 = SearchPattern::text("é", StringEncoding::Utf8, false).unwrap();
+        assert_eq!(utf8.bytes, vec![0xC3, 0xA9]);
+        let sjis = SearchPattern::text("あ", StringEncoding::ShiftJis, false).unwrap();
+        assert_eq!(sjis.bytes, vec![0x82, 0xA0]);
+        assert!(SearchPattern::text("あ", StringEncoding::Ascii, false).is_err());
+        assert!(SearchPattern::text("", StringEncoding::Ascii, false).is_err());
+    }
+
+    #[test]
+    fn test_case_insensitive_folds_only_ascii_letters() {
+        let pattern = SearchPattern::text("Fun1", StringEncoding::Utf16le, true).unwrap();
+        assert_eq!(
+            pattern.fold,
+            vec![true, false, true, false, true, false, false, false]
+        );
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x40)
+            .write_bytes(0x10, &[b'f', 0, b'U', 0, b'N', 0, b'1', 0])
+            .build();
+        assert_eq!(
+            find_matches(&reader, &pattern, 0x1000, 0x1040, 10),
+            vec![0x1010]
+        );
+        let exact = SearchPattern::text("Fun1", StringEncoding::Utf16le, false).unwrap();
+        assert!(find_matches(&reader, &exact, 0x1000, 0x1040, 10).is_empty());
+
+        // Shift-JIS trail bytes in the letter range are not folded
+        let sjis = SearchPattern::text("ソa", StringEncoding::ShiftJis, true).unwrap();
+        assert_eq!(sjis.bytes, vec![0x83, 0x5C, b'a']);
+        assert_eq!(sjis.fold, vec![false, false, true]);
+    }
+
+    #[test]
+    fn test_find_matches() {
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x100)
+            .write_i32(0x10, 9003)
+            .write_i32(0x80, 9003)
+            .write_bytes(0x40, &[0xAA, 0x01, 0xBB])
+            .build();
+
+        let found = find_matches(&reader, &SearchPattern::i32(9003), 0x1000, 0x1100, 10);
+        assert_eq!(found, vec![0x1010, 0x1080]);
+
+        let found = find_matches(&reader, &SearchPattern::i32(9003), 0x1000, 0x1100, 1);
+        assert_eq!(found, vec![0x1010]);
+
+        let pattern = SearchPattern::parse("AA ?? BB").unwrap();
+        assert_eq!(
+            find_matches(&reader, &pattern, 0x1000, 0x1100, 10),
+            vec![0x1040]
+        );
+    }
+
+    #[test]
+    fn test_value_range_validation() {
+        assert!(ValueRange::new(5, 1, 4).is_err());
+        assert!(ValueRange::new(1, 5, 3).is_err());
+        assert!(SONG_IDS.with_align(0).is_err());
+    }
+
+    #[test]
+    fn test_find_in_range_aligned() {
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x40)
+            .write_i32(0x08, 1500)
+            // Unaligned in-range value is ignored
+            .write_i32(0x11, 2000)
+            .write_i32(0x20, 99)
+            .build();
+        assert_eq!(
+            find_in_range(&reader, &SONG_IDS, 0x1000, 0x1040, 10),
+            vec![0x1008]
+        );
+        let unaligned = SONG_IDS.with_align(1).unwrap();
+        assert!(find_in_range(&reader, &unaligned, 0x1000, 0x1040, 10).contains(&0x1011));
+    }
+
+    #[test]
+    fn test_find_in_range_with_stride() {
+        const STRIDE: u64 = 0x3F0;
+        let mut builder = MockMemoryBuilder::new().base(0x10000).with_size(0x4000);
+        for k in 0..5 {
+            builder = builder.write_i32(0x100 + k * STRIDE as usize, 1001 + k as i32);
+        }
+        let reader = builder.write_i32(0x40, 1234).build();
+
+        let range = SONG_IDS.with_stride(STRIDE, 5);
+        assert_eq!(
+            find_in_range(&reader, &range, 0x10000, 0x14000, 10),
+            vec![0x10100]
+        );
+        assert_eq!(SONG_IDS.run_length(&reader, 0x10100, STRIDE, 100), 5);
+        assert_eq!(SONG_IDS.run_length(&reader, 0x10040, STRIDE, 100), 1);
+    }
+
+    #[test]
+    fn test_extract_strings() {
+        let mut buffer = b"\x01\x02fun\x00ab\x00LONG TITLE\x00".to_vec();
+        let strings = extract_strings(&buffer, StringEncoding::Ascii, 3);
+        assert_eq!(
+            strings,
+            vec![(2..5, "fun".to_string()), (9..19, "LONG TITLE".to_string())]
+        );
+
+        // Shift-JIS runs that fail to decode are skipped
+        buffer = vec![0x82, 0xA0, 0x00, 0x82, 0x00];
+        let strings = extract_strings(&buffer, StringEncoding::ShiftJis, 1);
+        assert_eq!(strings, vec![(0..2, "あ".to_string())]);
+
+        let utf16: Vec<u8> = "\0Aé\0".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let strings = extract_strings(&utf16, StringEncoding::Utf16le, 2);
+        assert_eq!(strings, vec![(2..6, "Aé".to_string())]);
+    }
+
+    #[test]
+    fn test_find_strings_with_regex() {
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x80)
+            .write_bytes(0x10, b"5.1.1.\x00")
+            .write_bytes(0x20, b"GAMBOL\x00")
+            .write_bytes(0x40, b"gambol mix\x00")
+            .build();
+        let regex = build_regex("^gambol", true).unwrap();
+        let found = find_strings(
+            &reader,
+            &regex,
+            StringEncoding::Ascii,
+            4,
+            0x1000,
+            0x1080,
+            10,
+        );
+        let addresses: Vec<_> = found.iter().map(|m| m.address).collect();
+        assert_eq!(addresses, vec![0x1020, 0x1040]);
+        assert_eq!(found[1].text, "gambol mix");
+
+        let regex = build_regex(r"^\d\.\d", false).unwrap();
+        let found = find_strings(
+            &reader,
+            &regex,
+            StringEncoding::Ascii,
+            4,
+            0x1000,
+            0x1080,
+            10,
+        );
+        assert_eq!(found.len(), 1);
+        assert!(build_regex("(", false).is_err());
+    }
+
+    #[test]
+    fn test_find_strings_after_short_encoded_string() {
+        // Half-width katakana: 1 byte each in Shift-JIS, 3 bytes in UTF-8
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x40)
+            .write_bytes(0x10, &[0xB1, 0xB2, 0xB3, 0xB4, 0x00])
+            .write_bytes(0x15, b"GAMBOL\x00")
+            .build();
+        let regex = build_regex(".", false).unwrap();
+        let found = find_strings(
+            &reader,
+            &regex,
+            StringEncoding::ShiftJis,
+            4,
+            0x1000,
+            0x1040,
+            10,
+        );
+        let addresses: Vec<_> = found.iter().map(|m| m.address).collect();
+        assert_eq!(addresses, vec![0x1010, 0x1015]);
+        assert_eq!(found[0].text, "ｱｲｳｴ");
+    }
+}
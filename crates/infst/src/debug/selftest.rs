@@ -0,0 +1,375 @@
+//! Self-test of the memory parsers against the running game
+//!
+//! Meant for the title or song select screen after a game update: every
+//! reader the tracker uses reads through the offsets once, and its output is
+//! checked against invariants that hold for any valid data. Quicker than a
+//! full tracking session for finding out which parser a layout change broke.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::chart::{
+    DEFAULT_ANCHOR_SONGS, Difficulty, SongInfo, UnlockData, check_anchor_songs,
+    fetch_song_database, get_unlock_states,
+};
+use crate::offset::OffsetsCollection;
+use crate::play::{
+    AssistType, InvalidEnumValueError, PlayType, RangeType, SettingsBlock, Style, read_lane_cover,
+    read_profile,
+};
+use crate::process::ReadMemory;
+use crate::score::{PlayerJudge, RawJudgeData, ScoreMap};
+
+/// Songs and score entries checked per sample
+pub const SAMPLE_SIZE: usize = 20;
+
+/// Highest chart level
+const MAX_LEVEL: u8 = 12;
+
+/// More notes than any chart has
+const MAX_TOTAL_NOTES: u32 = 10_000;
+
+/// Outcome of one parser check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// Not run (optional offset unknown, or a parser it depends on failed)
+    Skip,
+}
+
+impl CheckStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pass => "PASS",
+            Self::Fail => "FAIL",
+            Self::Skip => "SKIP",
+        }
+    }
+}
+
+/// Result of one parser
+#[derive(Debug, Clone, Serialize)]
+pub struct ParserCheck {
+    pub parser: &'static str,
+    pub status: CheckStatus,
+    /// What was read, or the broken invariant
+    pub detail: String,
+}
+
+impl ParserCheck {
+    fn new(parser: &'static str, result: std::result::Result<String, String>) -> Self {
+        let (status, detail) = match result {
+            Ok(detail) => (CheckStatus::Pass, detail),
+            Err(detail) => (CheckStatus::Fail, detail),
+        };
+        Self {
+            parser,
+            status,
+            detail,
+        }
+    }
+
+    fn skip(parser: &'static str, detail: &str) -> Self {
+        Self {
+            parser,
+            status: CheckStatus::Skip,
+            detail: detail.to_string(),
+        }
+    }
+}
+
+/// Pass/fail per parser
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SelfTestReport {
+    pub checks: Vec<ParserCheck>,
+}
+
+impl SelfTestReport {
+    /// Run every parser once through `offsets`
+    ///
+    /// The score map and unlock checks need the song database and are
+    /// skipped when it fails to load.
+    pub fn run<R: ReadMemory>(reader: &R, offsets: &OffsetsCollection) -> Self {
+        let mut checks = Vec::new();
+
+        let song_db = fetch_song_database(reader, offsets.song_list).map_err(|e| e.to_string());
+        checks.push(ParserCheck::new(
+            "song_db",
+            song_db.as_ref().map_err(Clone::clone).and_then(check_songs),
+        ));
+
+        let settings = reader
+            .read_struct::<SettingsBlock>(offsets.play_settings)
+            .map_err(|e| e.to_string())
+            .and_then(|block| check_settings(&block));
+        checks.push(ParserCheck::new("settings", settings));
+
+        let judge = reader
+            .read_struct::<RawJudgeData>(offsets.judge_data)
+            .map_err(|e| e.to_string())
+            .and_then(|raw| check_judge(&raw));
+        checks.push(ParserCheck::new("judge", judge));
+
+        match &song_db {
+            Ok(song_db) => {
+                let scores = ScoreMap::load_from_memory(reader, offsets.data_map, song_db)
+                    .map_err(|e| e.to_string())
+                    .and_then(|score_map| check_scores(&score_map, song_db));
+                checks.push(ParserCheck::new("score_map", scores));
+                let unlocks = get_unlock_states(reader, offsets.unlock_data, song_db)
+                    .map_err(|e| e.to_string())
+                    .and_then(|unlock_db| check_unlocks(&unlock_db, song_db));
+                checks.push(ParserCheck::new("unlock", unlocks));
+            }
+            Err(_) => {
+                checks.push(ParserCheck::skip("score_map", "needs the song database"));
+                checks.push(ParserCheck::skip("unlock", "needs the song database"));
+            }
+        }
+
+        checks.push(if offsets.profile == 0 {
+            ParserCheck::skip("profile", "offset unknown")
+        } else {
+            ParserCheck::new(
+                "profile",
+                read_profile(reader, offsets.profile)
+                    .map(|profile| format!("DJ {}", profile.dj_name))
+                    .map_err(|e| e.to_string()),
+            )
+        });
+        checks.push(if offsets.lane_cover == 0 {
+            ParserCheck::skip("lane_cover", "offset unknown")
+        } else {
+            ParserCheck::new(
+                "lane_cover",
+                read_lane_cover(reader, offsets.lane_cover, PlayType::P1)
+                    .map(|cover| format!("green number {}", cover.green_number))
+                    .map_err(|e| e.to_string()),
+            )
+        });
+
+        Self { checks }
+    }
+
+    /// Whether no parser failed (skipped ones don't count)
+    pub fn passed(&self) -> bool {
+        self.failures() == 0
+    }
+
+    pub fn failures(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+            .count()
+    }
+}
+
+/// Titles of the anchor songs, and levels and note counts of a sample
+fn check_songs(song_db: &HashMap<u32, SongInfo>) -> std::result::Result<String, String> {
+    if song_db.is_empty() {
+        return Err("no songs".to_string());
+    }
+    check_anchor_songs(song_db, DEFAULT_ANCHOR_SONGS)
+        .ensure_no_drift()
+        .map_err(|e| e.to_string())?;
+
+    let mut ids: Vec<&u32> = song_db.keys().collect();
+    ids.sort();
+    for id in ids.iter().take(SAMPLE_SIZE) {
+        let song = &song_db[id];
+        if song.title.trim().is_empty() {
+            return Err(format!("song {} has no title", id));
+        }
+        for difficulty in Difficulty::ALL {
+            let (level, notes) = (song.level_for(difficulty), song.total_notes_for(difficulty));
+            if level > MAX_LEVEL || notes > MAX_TOTAL_NOTES {
+                return Err(format!(
+                    "song {} {}: level {}, {} notes",
+                    id, difficulty, level, notes
+                ));
+            }
+        }
+    }
+    Ok(format!(
+        "{} songs, {} sampled",
+        song_db.len(),
+        SAMPLE_SIZE.min(song_db.len())
+    ))
+}
+
+/// Option words of both sides are in range
+fn check_settings(block: &SettingsBlock) -> std::result::Result<String, String> {
+    let mut styles = Vec::new();
+    for play_type in [PlayType::P1, PlayType::P2] {
+        let raw = block.to_raw(play_type);
+        let invalid = |e: InvalidEnumValueError| format!("{}: {}", play_type, e);
+        let style = Style::try_from(raw.style).map_err(invalid)?;
+        Style::try_from(raw.style2).map_err(invalid)?;
+        AssistType::try_from(raw.assist).map_err(invalid)?;
+        RangeType::try_from(raw.range).map_err(invalid)?;
+        for (name, value) in [
+            ("flip", raw.flip),
+            ("battle", raw.battle),
+            ("H-RAN", raw.h_ran),
+        ] {
+            if !matches!(value, 0 | 1) {
+                return Err(format!("{}: invalid {} value {}", play_type, name, value));
+            }
+        }
+        styles.push(format!("{} {}", play_type, style.as_str()));
+    }
+    Ok(styles.join(", "))
+}
+
+/// Judge counts of both sides are plausible (zero before the first play,
+/// the last result after one)
+fn check_judge(raw: &RawJudgeData) -> std::result::Result<String, String> {
+    let check_side = |side: &str, judge: &PlayerJudge| {
+        let notes = judge.total_notes();
+        if notes > MAX_TOTAL_NOTES {
+            return Err(format!("{}: {} notes judged", side, notes));
+        }
+        if judge.fast + judge.slow > notes {
+            return Err(format!(
+                "{}: FAST {} + SLOW {} over {} notes judged",
+                side, judge.fast, judge.slow, notes
+            ));
+        }
+        Ok(notes)
+    };
+    let notes = check_side("P1", &raw.p1)? + check_side("P2", &raw.p2)?;
+    Ok(if notes == 0 {
+        "no notes judged".to_string()
+    } else {
+        format!("last result {} notes", notes)
+    })
+}
+
+/// Scores of a sample are within the chart's maximum
+fn check_scores(
+    score_map: &ScoreMap,
+    song_db: &HashMap<u32, SongInfo>,
+) -> std::result::Result<String, String> {
+    let mut entries: Vec<_> = score_map.iter().collect();
+    entries.sort_by_key(|(id, _)| **id);
+    for (id, data) in entries.iter().take(SAMPLE_SIZE) {
+        let Some(song) = song_db.get(id) else {
+            return Err(format!("score entry for unknown song {}", id));
+        };
+        for difficulty in Difficulty::ALL {
+            let score = data.get_score(difficulty);
+            let max = song.total_notes_for(difficulty) * 2;
+            if score > max {
+                return Err(format!(
+                    "song {} {}: EX score {} over the maximum {}",
+                    id, difficulty, score, max
+                ));
+            }
+        }
+    }
+    Ok(format!(
+        "{} entries, {} sampled",
+        score_map.len(),
+        SAMPLE_SIZE.min(score_map.len())
+    ))
+}
+
+/// Unlock data covers the song database
+fn check_unlocks(
+    unlock_db: &HashMap<u32, UnlockData>,
+    song_db: &HashMap<u32, SongInfo>,
+) -> std::result::Result<String, String> {
+    if unlock_db.is_empty() {
+        return Err("no unlock entries".to_string());
+    }
+    if let Some(id) = unlock_db.keys().find(|id| !song_db.contains_key(id)) {
+        return Err(format!("unlock entry for unknown song {}", id));
+    }
+    Ok(format!(
+        "{} entries ({:.0}% of songs)",
+        unlock_db.len(),
+        unlock_db.len() as f64 * 100.0 / song_db.len() as f64
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::play::Settings;
+    use crate::process::MockMemoryBuilder;
+    use crate::process::view::FromBytes;
+    use std::sync::Arc;
+
+    fn song(id: u32, level: u8, notes: u32) -> SongInfo {
+        SongInfo {
+            id,
+            title: Arc::from(format!("Song {}", id)),
+            levels: [0, 0, 0, level, 0, 0, 0, 0, 0, 0],
+            total_notes: [0, 0, 0, notes, 0, 0, 0, 0, 0, 0],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_check_settings() {
+        let mut bytes = vec![0u8; SettingsBlock::SIZE];
+        bytes[0] = 4; // P1 MIRROR
+        assert_eq!(
+            check_settings(&SettingsBlock::from_le_slice(&bytes)).unwrap(),
+            "1P MIRROR, 2P OFF"
+        );
+        bytes[Settings::P2_OFFSET as usize] = 42;
+        assert!(check_settings(&SettingsBlock::from_le_slice(&bytes)).is_err());
+    }
+
+    #[test]
+    fn test_check_judge() {
+        let mut raw = RawJudgeData::default();
+        assert_eq!(check_judge(&raw).unwrap(), "no notes judged");
+        raw.p1.pgreat = 900;
+        raw.p1.great = 100;
+        raw.p1.fast = 60;
+        raw.p1.slow = 40;
+        assert_eq!(check_judge(&raw).unwrap(), "last result 1000 notes");
+        raw.p2.fast = 5;
+        assert!(check_judge(&raw).is_err());
+    }
+
+    #[test]
+    fn test_check_scores() {
+        let song_db = HashMap::from([(1000, song(1000, 12, 1000))]);
+        let mut score_map = ScoreMap::new();
+        score_map
+            .get_or_insert(1000)
+            .set_score(Difficulty::SpA, 2000);
+        assert!(check_scores(&score_map, &song_db).is_ok());
+        score_map
+            .get_or_insert(1000)
+            .set_score(Difficulty::SpA, 2001);
+        assert!(check_scores(&score_map, &song_db).is_err());
+        score_map.get_or_insert(2000);
+        let song_db = HashMap::from([(2000, song(2000, 12, 1000))]);
+        assert!(check_scores(&score_map, &song_db).is_err());
+    }
+
+    #[test]
+    fn test_run_without_game_data() {
+        let reader = MockMemoryBuilder::new().build();
+        let report = SelfTestReport::run(&reader, &OffsetsCollection::default());
+        let status = |parser: &str| {
+            report
+                .checks
+                .iter()
+                .find(|check| check.parser == parser)
+                .unwrap()
+                .status
+        };
+        assert_eq!(status("song_db"), CheckStatus::Fail);
+        assert_eq!(status("score_map"), CheckStatus::Skip);
+        assert_eq!(status("profile"), CheckStatus::Skip);
+        assert!(!report.passed());
+    }
+}
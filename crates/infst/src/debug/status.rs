@@ -1,10 +1,18 @@
 //! Status information for debugging
 
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 
-use crate::chart::SongInfo;
+use crate::chart::{
+    AnchorReport, DEFAULT_ANCHOR_SONGS, SongInfo, check_anchor_songs, fetch_song_database,
+    get_unlock_states,
+};
 use crate::offset::{OffsetSearcher, OffsetsCollection};
+use crate::play::{
+    Currency, LaneCover, PlayType, ProfileInfo, read_currency, read_lane_cover, read_profile,
+};
 use crate::process::ReadMemory;
+use crate::score::ScoreMap;
 
 /// Validation result for an individual offset
 #[derive(Debug, Clone, Serialize)]
@@ -44,10 +52,111 @@ pub struct StatusInfo {
     pub song_count: usize,
     /// Currently selected song (if available)
     pub current_song: Option<CurrentSongInfo>,
+    /// Player profile (if the profile offset is known and readable)
+    pub profile: Option<ProfileInfo>,
+    /// Bits and tickets (if the currency offset is known and readable)
+    pub currency: Option<Currency>,
+    /// 1P side lane covers and green number (if the lane cover offset is known and readable)
+    pub lane_cover: Option<LaneCover>,
+    /// Health of the data read through the offsets
+    pub health: SubsystemHealth,
     /// Overall validation status
     pub all_valid: bool,
 }
 
+/// Song database health
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SongDbHealth {
+    pub loaded: bool,
+    pub song_count: usize,
+    /// Anchor song titles (layout drift check)
+    pub anchors: AnchorReport,
+    pub error: Option<String>,
+}
+
+/// Score map health
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScoreMapHealth {
+    pub loaded: bool,
+    /// Songs with score data
+    pub entry_count: usize,
+    pub error: Option<String>,
+}
+
+/// Unlock data health
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UnlockDataHealth {
+    pub loaded: bool,
+    /// Songs with unlock data
+    pub entry_count: usize,
+    /// Share of the song database with unlock data (0.0-1.0)
+    pub coverage: f64,
+    pub error: Option<String>,
+}
+
+/// Health of the subsystems fed by the offsets
+///
+/// Valid offsets don't guarantee usable data: the song database may parse
+/// with garbage titles, or the score map may not load. This loads each the
+/// way the tracker does and reports what came out.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SubsystemHealth {
+    pub song_db: SongDbHealth,
+    pub score_map: ScoreMapHealth,
+    pub unlock_data: UnlockDataHealth,
+    /// Time of the last play recorded in the session files (set by callers
+    /// that know the session directory)
+    pub last_play: Option<DateTime<Utc>>,
+}
+
+impl SubsystemHealth {
+    /// Load the song database, score map and unlock data through `offsets`
+    pub fn collect<R: ReadMemory>(reader: &R, offsets: &OffsetsCollection) -> Self {
+        let mut health = Self::default();
+        let song_db = match fetch_song_database(reader, offsets.song_list) {
+            Ok(song_db) => song_db,
+            Err(e) => {
+                health.song_db.error = Some(e.to_string());
+                return health;
+            }
+        };
+        health.song_db = SongDbHealth {
+            loaded: !song_db.is_empty(),
+            song_count: song_db.len(),
+            anchors: check_anchor_songs(&song_db, DEFAULT_ANCHOR_SONGS),
+            error: None,
+        };
+
+        match ScoreMap::load_from_memory(reader, offsets.data_map, &song_db) {
+            Ok(score_map) => {
+                health.score_map.loaded = true;
+                health.score_map.entry_count = score_map.len();
+            }
+            Err(e) => health.score_map.error = Some(e.to_string()),
+        }
+
+        match get_unlock_states(reader, offsets.unlock_data, &song_db) {
+            Ok(unlock_db) => {
+                health.unlock_data.loaded = true;
+                health.unlock_data.entry_count = unlock_db.len();
+                if !song_db.is_empty() {
+                    health.unlock_data.coverage = unlock_db.len() as f64 / song_db.len() as f64;
+                }
+            }
+            Err(e) => health.unlock_data.error = Some(e.to_string()),
+        }
+        health
+    }
+
+    /// Whether every subsystem loaded and the song titles look right
+    pub fn is_healthy(&self) -> bool {
+        self.song_db.loaded
+            && !self.song_db.anchors.is_drift()
+            && self.score_map.loaded
+            && self.unlock_data.loaded
+    }
+}
+
 /// Information about the currently selected song
 #[derive(Debug, Clone, Serialize)]
 pub struct CurrentSongInfo {
@@ -93,6 +202,29 @@ impl StatusInfo {
         // Get current song info
         let current_song = get_current_song_info(reader, offsets.current_song, offsets.song_list);
 
+        // Get player profile (optional offset)
+        let profile = if offsets.profile != 0 {
+            read_profile(reader, offsets.profile).ok()
+        } else {
+            None
+        };
+
+        // Get bits and tickets (optional offset)
+        let currency = if offsets.currency != 0 {
+            read_currency(reader, offsets.currency).ok()
+        } else {
+            None
+        };
+
+        // Get 1P side lane covers (optional offset)
+        let lane_cover = if offsets.lane_cover != 0 {
+            read_lane_cover(reader, offsets.lane_cover, PlayType::P1).ok()
+        } else {
+            None
+        };
+
+        let health = SubsystemHealth::collect(reader, offsets);
+
         // Overall validation
         let all_valid = searcher.validate_signature_offsets(offsets);
 
@@ -104,6 +236,10 @@ impl StatusInfo {
             offsets: offsets_status,
             song_count,
             current_song,
+            profile,
+            currency,
+            lane_cover,
+            health,
             all_valid,
         }
     }
@@ -495,3 +631,22 @@ fn get_current_song_info<R: ReadMemory>(
         title,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::MockMemoryBuilder;
+
+    #[test]
+    fn test_health_without_song_database() {
+        let reader = MockMemoryBuilder::new().build();
+        let health = SubsystemHealth::collect(&reader, &OffsetsCollection::default());
+        assert!(!health.song_db.loaded);
+        assert_eq!(health.song_db.song_count, 0);
+        assert!(!health.is_healthy());
+
+        let json = serde_json::to_value(&health).unwrap();
+        assert_eq!(json["song_db"]["anchors"]["checked"], 0);
+        assert!(json["last_play"].is_null());
+    }
+}
@@ -1,5 +1,39 @@
+use std::fmt;
+use std::path::PathBuf;
+
 use thiserror::Error;
 
+/// Broad error category, used for exit codes and user-facing messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// Finding, opening or reading the game process
+    ProcessAccess,
+    /// Locating or validating memory offsets
+    OffsetSearch,
+    /// Malformed input (offset/signature files, JSON, templates, encodings, configuration)
+    Parse,
+    /// Reading or writing local files
+    Storage,
+    /// Talking to the web service
+    Network,
+    /// Unexpected game or tracker state
+    Internal,
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::ProcessAccess => "process-access",
+            Self::OffsetSearch => "offset-search",
+            Self::Parse => "parse",
+            Self::Storage => "storage",
+            Self::Network => "network",
+            Self::Internal => "internal",
+        };
+        f.write_str(name)
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Process not found: {0}")]
@@ -8,21 +42,54 @@ pub enum Error {
     #[error("Failed to open process: {0}")]
     ProcessOpenFailed(String),
 
+    #[error("Access denied to process {pid}{}", access_denied_hint(*game_elevated))]
+    ProcessAccessDenied {
+        pid: u32,
+        /// Whether the game runs elevated (`None` if unknown)
+        game_elevated: Option<bool>,
+    },
+
     #[error("Failed to read process memory at address {address:#x}: {message}")]
     MemoryReadFailed { address: u64, message: String },
 
+    /// The process handle stopped granting access while the process runs
+    /// (recovered by refreshing the handle, see [`crate::process::with_handle_recovery`])
+    #[error("Lost access to the process handle: {0}")]
+    ProcessHandleLost(String),
+
     #[error("Invalid offset: {0}")]
     InvalidOffset(String),
 
     #[error("Offset version mismatch: expected {expected}, got {actual}")]
     OffsetVersionMismatch { expected: String, actual: String },
 
+    /// The game build is older than every known memory layout
+    #[error(
+        "Unsupported game version {version} (supported builds: {} or newer)",
+        supported.join(", ")
+    )]
+    UnsupportedGameVersion {
+        version: String,
+        supported: Vec<String>,
+    },
+
     #[error("Failed to search offset for {target}: {message}")]
     OffsetSearchFailed {
         target: &'static str,
         message: String,
     },
 
+    /// Some offsets were found before the search for `target` failed
+    #[error(
+        "Offset search incomplete: found {}, failed at {target}: {message}",
+        found.join(", ")
+    )]
+    OffsetSearchPartial {
+        found: Vec<&'static str>,
+        target: &'static str,
+        message: String,
+    },
+
     #[error("Invalid game state: expected {expected}, got {actual}")]
     InvalidGameState {
         expected: &'static str,
@@ -32,6 +99,33 @@ pub enum Error {
     #[error("Song database not loaded: {reason}")]
     SongDatabaseNotLoaded { reason: String },
 
+    /// Anchor song titles don't match: the song entry layout moved
+    #[error("Song database layout drift (titles of known songs are wrong): {details}")]
+    SongLayoutDrift { details: String },
+
+    #[error("Failed to parse {what}: {message}")]
+    Parse { what: String, message: String },
+
+    #[error("Unsupported {format} format version {version} (this build reads up to {supported})")]
+    UnsupportedFormatVersion {
+        format: &'static str,
+        version: u32,
+        supported: u32,
+    },
+
+    #[error("Failed to access {}: {source}", path.display())]
+    Storage {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Another infst tracker is already running{} (lock: {})", pid.map(|p| format!(" (PID {p})")).unwrap_or_default(), path.display())]
+    InstanceLocked { path: PathBuf, pid: Option<u32> },
+
+    #[error("Network error: {message}")]
+    Network { message: String, retryable: bool },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -40,14 +134,159 @@ pub enum Error {
 
     #[error("Encoding error: {0}")]
     EncodingError(String),
+
+    #[error("Invalid template: {0}")]
+    InvalidTemplate(String),
+
+    #[error("Invalid configuration for {field}: {message}")]
+    InvalidConfig {
+        field: &'static str,
+        message: String,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+#[cfg(feature = "network")]
+impl From<ureq::Error> for Error {
+    fn from(e: ureq::Error) -> Self {
+        let retryable = match &e {
+            ureq::Error::StatusCode(status) => *status == 429 || *status >= 500,
+            ureq::Error::Io(_)
+            | ureq::Error::Timeout(_)
+            | ureq::Error::HostNotFound
+            | ureq::Error::ConnectionFailed
+            | ureq::Error::BodyStalled => true,
+            _ => false,
+        };
+        Self::Network {
+            message: e.to_string(),
+            retryable,
+        }
+    }
+}
+
+fn access_denied_hint(game_elevated: Option<bool>) -> &'static str {
+    match game_elevated {
+        Some(true) => " (the game runs as administrator; run infst as administrator too)",
+        Some(false) => " (the game is not elevated; another program may be blocking access)",
+        None => " (insufficient privileges; try running infst as administrator)",
+    }
+}
+
 impl Error {
+    /// Category of this error
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Self::ProcessNotFound(_)
+            | Self::ProcessOpenFailed(_)
+            | Self::ProcessAccessDenied { .. }
+            | Self::MemoryReadFailed { .. }
+            | Self::ProcessHandleLost(_) => ErrorCategory::ProcessAccess,
+            Self::InvalidOffset(_)
+            | Self::OffsetVersionMismatch { .. }
+            | Self::UnsupportedGameVersion { .. }
+            | Self::OffsetSearchFailed { .. }
+            | Self::OffsetSearchPartial { .. }
+            | Self::SongLayoutDrift { .. } => ErrorCategory::OffsetSearch,
+            Self::Parse { .. }
+            | Self::Json(_)
+            | Self::UnsupportedFormatVersion { .. }
+            | Self::EncodingError(_)
+            | Self::InvalidTemplate(_)
+            | Self::InvalidConfig { .. } => ErrorCategory::Parse,
+            Self::Storage { .. } | Self::InstanceLocked { .. } | Self::Io(_) => {
+                ErrorCategory::Storage
+            }
+            Self::Network { .. } => ErrorCategory::Network,
+            Self::InvalidGameState { .. } | Self::SongDatabaseNotLoaded { .. } => {
+                ErrorCategory::Internal
+            }
+        }
+    }
+
+    /// Whether retrying the same operation later may succeed
+    ///
+    /// True for conditions that resolve on their own (game not started yet,
+    /// memory still being populated, transient network failures); false for
+    /// errors that need user action (privileges, malformed files).
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::ProcessNotFound(_)
+            | Self::MemoryReadFailed { .. }
+            | Self::ProcessHandleLost(_)
+            | Self::EncodingError(_)
+            | Self::OffsetSearchFailed { .. }
+            | Self::OffsetSearchPartial { .. }
+            | Self::InvalidGameState { .. }
+            | Self::SongDatabaseNotLoaded { .. } => true,
+            Self::Network { retryable, .. } => *retryable,
+            _ => false,
+        }
+    }
+
+    /// User-facing remediation text, if there is a known fix
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::ProcessNotFound(_) => {
+                Some("Start INFINITAS and wait for the title screen, then try again")
+            }
+            Self::ProcessOpenFailed(_) | Self::ProcessHandleLost(_) => {
+                Some("Run `infst doctor` to check privileges and game process status")
+            }
+            // The access-denied message already carries its own hint
+            Self::ProcessAccessDenied { .. } => None,
+            Self::MemoryReadFailed { .. } => {
+                Some("The game may be loading or minimized; try again once it is on screen")
+            }
+            Self::InvalidOffset(_) | Self::OffsetVersionMismatch { .. } => Some(
+                "The offsets do not match this game version; remove --offsets-file or regenerate it with `infst find-offsets`",
+            ),
+            Self::UnsupportedGameVersion { .. } => Some(
+                "Update INFINITAS from the launcher; this build's memory layout is not known to infst",
+            ),
+            Self::OffsetSearchFailed { .. } | Self::OffsetSearchPartial { .. } => Some(
+                "Make sure the game is at song select, then retry; after a game update, new signatures may be required",
+            ),
+            Self::SongLayoutDrift { .. } => Some(
+                "A game update probably changed the song entry layout; update infst before exporting again",
+            ),
+            Self::Parse { .. } | Self::Json(_) => {
+                Some("Check the file for syntax errors or regenerate it")
+            }
+            Self::UnsupportedFormatVersion { .. } => {
+                Some("The file was written by a newer infst; update infst to read it")
+            }
+            Self::InvalidConfig { .. } => Some("Fix the named setting and try again"),
+            Self::Storage { .. } | Self::Io(_) => Some(
+                "Check that the path exists and is writable, and that enough disk space is available",
+            ),
+            Self::InstanceLocked { .. } => {
+                Some("Close the other tracker first, or pass --force if it is no longer running")
+            }
+            Self::Network { .. } => Some(
+                "Check the network connection and API endpoint; run `infst login` if the token expired",
+            ),
+            Self::EncodingError(_)
+            | Self::InvalidTemplate(_)
+            | Self::InvalidGameState { .. }
+            | Self::SongDatabaseNotLoaded { .. } => None,
+        }
+    }
+
     /// Check if this error is a "file not found" error
     pub fn is_not_found(&self) -> bool {
-        matches!(self, Error::Io(e) if e.kind() == std::io::ErrorKind::NotFound)
+        match self {
+            Error::Io(e) | Error::Storage { source: e, .. } => {
+                e.kind() == std::io::ErrorKind::NotFound
+            }
+            _ => false,
+        }
+    }
+
+    /// Check if this error is an access-denied failure opening the game process
+    pub fn is_access_denied(&self) -> bool {
+        matches!(self, Error::ProcessAccessDenied { .. })
     }
 
     /// Create an OffsetSearchFailed error with a simple message (for backwards compatibility)
@@ -66,6 +305,30 @@ impl Error {
         }
     }
 
+    /// Create a Parse error
+    pub fn parse(what: impl Into<String>, message: impl fmt::Display) -> Self {
+        Self::Parse {
+            what: what.into(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Create an InvalidConfig error
+    pub fn invalid_config(field: &'static str, message: impl Into<String>) -> Self {
+        Self::InvalidConfig {
+            field,
+            message: message.into(),
+        }
+    }
+
+    /// Create a Storage error for an I/O failure on `path`
+    pub fn storage(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        Self::Storage {
+            path: path.into(),
+            source,
+        }
+    }
+
     /// Create an InvalidGameState error
     pub fn invalid_game_state(expected: &'static str, actual: impl Into<String>) -> Self {
         Self::InvalidGameState {
@@ -74,6 +337,13 @@ impl Error {
         }
     }
 
+    /// Create a SongLayoutDrift error
+    pub fn song_layout_drift(details: impl Into<String>) -> Self {
+        Self::SongLayoutDrift {
+            details: details.into(),
+        }
+    }
+
     /// Create a SongDatabaseNotLoaded error
     pub fn song_database_not_loaded(reason: impl Into<String>) -> Self {
         Self::SongDatabaseNotLoaded {
@@ -96,4 +366,70 @@ mod tests {
         let err2 = Error::Io(other_io_err);
         assert!(!err2.is_not_found());
     }
+
+    #[test]
+    fn test_error_access_denied() {
+        let err = Error::ProcessAccessDenied {
+            pid: 1234,
+            game_elevated: Some(true),
+        };
+        assert!(err.is_access_denied());
+        assert!(err.to_string().starts_with("Access denied to process 1234"));
+        assert!(err.to_string().contains("run infst as administrator"));
+        assert!(!Error::ProcessOpenFailed("x".to_string()).is_access_denied());
+    }
+
+    #[test]
+    fn test_error_category_and_retryability() {
+        let not_found = Error::ProcessNotFound("bm2dx.exe".to_string());
+        assert_eq!(not_found.category(), ErrorCategory::ProcessAccess);
+        assert!(not_found.is_retryable());
+        assert!(not_found.hint().is_some());
+
+        let denied = Error::ProcessAccessDenied {
+            pid: 1,
+            game_elevated: None,
+        };
+        assert!(!denied.is_retryable());
+
+        let parse = Error::parse("offsets file", "bad hex");
+        assert_eq!(parse.category(), ErrorCategory::Parse);
+        assert_eq!(parse.to_string(), "Failed to parse offsets file: bad hex");
+        assert!(!parse.is_retryable());
+
+        let network = Error::Network {
+            message: "timeout".to_string(),
+            retryable: true,
+        };
+        assert_eq!(network.category(), ErrorCategory::Network);
+        assert!(network.is_retryable());
+    }
+
+    #[test]
+    fn test_offset_search_partial_message() {
+        let err = Error::OffsetSearchPartial {
+            found: vec!["songList", "judgeData"],
+            target: "playSettings",
+            message: "no candidates".to_string(),
+        };
+        assert_eq!(err.category(), ErrorCategory::OffsetSearch);
+        assert_eq!(
+            err.to_string(),
+            "Offset search incomplete: found songList, judgeData, failed at playSettings: no candidates"
+        );
+    }
+
+    #[test]
+    fn test_storage_error() {
+        let err = Error::storage(
+            "sessions/a.tsv",
+            std::io::Error::new(std::io::ErrorKind::NotFound, "missing"),
+        );
+        assert!(err.is_not_found());
+        assert_eq!(err.category(), ErrorCategory::Storage);
+        assert!(
+            err.to_string()
+                .starts_with("Failed to access sessions/a.tsv")
+        );
+    }
 }
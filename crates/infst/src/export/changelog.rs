@@ -0,0 +1,248 @@
+//! Improvements between two tracker exports
+//!
+//! Unlike [`diff_tracker_tsv`](super::diff_tracker_tsv), which lists every
+//! changed cell, the changelog only reports progress per chart: a better
+//! lamp, a better grade or an EX score raised by at least a threshold.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::score::{Grade, Lamp};
+
+use super::tracker::TRACKER_DIFFICULTY_NAMES;
+use super::tracker_merge::{Tracker, parse_lamp, parse_number};
+
+/// Default smallest EX score gain reported by [`tracker_changelog`]
+pub const DEFAULT_MIN_SCORE_DELTA: u32 = 10;
+
+/// Value before and after
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Change<T> {
+    pub old: T,
+    pub new: T,
+}
+
+/// Progress on one chart between two exports
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChartImprovement {
+    pub song_id: String,
+    pub title: String,
+    /// Difficulty short name (e.g. `SPA`)
+    pub difficulty: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lamp: Option<Change<Lamp>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grade: Option<Change<Grade>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ex_score: Option<Change<u32>>,
+}
+
+impl fmt::Display for ChartImprovement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(lamp) = self.lamp {
+            parts.push(format!(
+                "{} -> {}",
+                lamp.old.short_name(),
+                lamp.new.short_name()
+            ));
+        }
+        if let Some(grade) = self.grade {
+            parts.push(format!(
+                "{} -> {}",
+                grade.old.short_name(),
+                grade.new.short_name()
+            ));
+        }
+        if let Some(score) = self.ex_score {
+            parts.push(format!(
+                "EX {} -> {} (+{})",
+                score.old,
+                score.new,
+                score.new - score.old
+            ));
+        }
+        write!(
+            f,
+            "{} [{}]: {}",
+            self.title,
+            self.difficulty,
+            parts.join(", ")
+        )
+    }
+}
+
+/// Cells of a tracker by Song ID and column name
+struct Cells<'a> {
+    columns: HashMap<&'a str, usize>,
+    rows: HashMap<&'a str, &'a [String]>,
+}
+
+impl<'a> Cells<'a> {
+    fn new(tracker: &'a Tracker) -> Self {
+        Self {
+            columns: tracker
+                .columns()
+                .iter()
+                .enumerate()
+                .map(|(i, column)| (column.as_str(), i))
+                .collect(),
+            rows: tracker
+                .rows()
+                .iter()
+                .filter_map(|row| Some((row.first()?.as_str(), row.as_slice())))
+                .collect(),
+        }
+    }
+
+    fn get(&self, song_id: &str, column: &str) -> &'a str {
+        self.rows
+            .get(song_id)
+            .zip(self.columns.get(column))
+            .and_then(|(row, &i)| row.get(i))
+            .map_or("", String::as_str)
+    }
+}
+
+/// Charts that improved from `old` to `new`, in `new`'s row order
+///
+/// A lamp counts when it is better and at least ASSIST CLEAR (a first FAILED
+/// is not progress); a grade when it is better; an EX score when it rose by
+/// `min_score_delta` or more. Lower values in `new` (e.g. a reset save) are
+/// ignored.
+pub fn tracker_changelog(
+    old: &Tracker,
+    new: &Tracker,
+    min_score_delta: u32,
+) -> Vec<ChartImprovement> {
+    let old_cells = Cells::new(old);
+    let new_cells = Cells::new(new);
+
+    let mut improvements = Vec::new();
+    for row in new.rows() {
+        let Some(song_id) = row.first() else {
+            continue;
+        };
+        for difficulty in TRACKER_DIFFICULTY_NAMES {
+            let cells = |field: &str| {
+                let column = format!("{difficulty} {field}");
+                (
+                    old_cells.get(song_id, &column),
+                    new_cells.get(song_id, &column),
+                )
+            };
+
+            let (old_lamp, new_lamp) = cells("Lamp");
+            let lamp = Change {
+                old: parse_lamp(old_lamp),
+                new: parse_lamp(new_lamp),
+            };
+            let (old_grade, new_grade) = cells("Letter");
+            let grade = Change {
+                old: old_grade.parse().unwrap_or(Grade::NoPlay),
+                new: new_grade.parse().unwrap_or(Grade::NoPlay),
+            };
+            let (old_score, new_score) = cells("EX Score");
+            let ex_score = Change {
+                old: parse_number(old_score).unwrap_or(0),
+                new: parse_number(new_score).unwrap_or(0),
+            };
+
+            let improvement = ChartImprovement {
+                song_id: song_id.clone(),
+                title: new_cells.get(song_id, "Title").to_string(),
+                difficulty: difficulty.to_string(),
+                lamp: (lamp.new > lamp.old && lamp.new >= Lamp::AssistClear).then_some(lamp),
+                grade: (grade.new > grade.old).then_some(grade),
+                ex_score: (ex_score.new > ex_score.old
+                    && ex_score.new - ex_score.old >= min_score_delta.max(1))
+                .then_some(ex_score),
+            };
+            if improvement.lamp.is_some()
+                || improvement.grade.is_some()
+                || improvement.ex_score.is_some()
+            {
+                improvements.push(improvement);
+            }
+        }
+    }
+    improvements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "Song ID\tTitle\tSPA Lamp\tSPA Letter\tSPA EX Score\t\
+                          SPH Lamp\tSPH Letter\tSPH EX Score";
+
+    fn tracker(rows: &[&str]) -> Tracker {
+        Tracker::parse(&format!("{HEADER}\n{}", rows.join("\n")))
+    }
+
+    #[test]
+    fn test_changelog() {
+        let old = tracker(&[
+            "1000\tSong A\tCLEAR\tA\t1500\tHARD\tAA\t1300",
+            "1001\tSong B\tHARD\tAA\t1700\t\t\t",
+        ]);
+        let new = tracker(&[
+            "1000\tSong A\tHARD\tAA\t1620\tHARD\tAA\t1305",
+            "1001\tSong B\tHARD\tAA\t1700\tFAILED\tF\t100",
+            "1002\tSong C\tCLEAR\tB\t900\t\t\t",
+        ]);
+
+        let changelog = tracker_changelog(&old, &new, DEFAULT_MIN_SCORE_DELTA);
+        let lines: Vec<String> = changelog.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Song A [SPA]: CLEAR -> HARD, A -> AA, EX 1500 -> 1620 (+120)",
+                // A first FAILED is not a lamp, but the score and grade are new
+                "Song B [SPH]: - -> F, EX 0 -> 100 (+100)",
+                "Song C [SPA]: NO PLAY -> CLEAR, - -> B, EX 0 -> 900 (+900)",
+            ]
+        );
+        assert_eq!(changelog[0].song_id, "1000");
+        assert_eq!(
+            changelog[0].lamp,
+            Some(Change {
+                old: Lamp::Clear,
+                new: Lamp::HardClear
+            })
+        );
+    }
+
+    #[test]
+    fn test_score_threshold_and_regressions() {
+        let old = tracker(&["1000\tSong A\tHARD\tAA\t1500\t\t\t"]);
+        let new = tracker(&["1000\tSong A\tCLEAR\tAA\t1509\t\t\t"]);
+        assert!(tracker_changelog(&old, &new, 10).is_empty());
+
+        let changelog = tracker_changelog(&old, &new, 5);
+        assert_eq!(changelog.len(), 1);
+        assert_eq!(changelog[0].lamp, None);
+        assert_eq!(changelog[0].ex_score.unwrap().new, 1509);
+
+        // A zero threshold still requires a gain
+        assert!(tracker_changelog(&old, &old, 0).is_empty());
+    }
+
+    #[test]
+    fn test_changelog_json() {
+        let old = tracker(&["1000\tSong A\tCLEAR\tA\t1500\t\t\t"]);
+        let new = tracker(&["1000\tSong A\tHARD\tA\t1500\t\t\t"]);
+        let json = serde_json::to_value(tracker_changelog(&old, &new, 10)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "song_id": "1000",
+                "title": "Song A",
+                "difficulty": "SPA",
+                "lamp": { "old": "Clear", "new": "HardClear" },
+            }])
+        );
+    }
+}
@@ -17,17 +17,19 @@ pub struct PersonalBestComparison {
 }
 
 /// Compare current play data with personal best
+///
+/// Premature plays are never reported as improvements.
 pub fn compare_with_personal_best(
     play_data: &PlayData,
     best: Option<&ScoreData>,
 ) -> PersonalBestComparison {
-    let Some(best) = best else {
+    let Some(best) = best.filter(|_| !play_data.is_premature()) else {
         return PersonalBestComparison::default();
     };
 
-    let diff_index = play_data.chart.difficulty as usize;
-    let best_score = best.score[diff_index];
-    let best_lamp = best.lamp[diff_index];
+    let difficulty = play_data.chart.difficulty;
+    let best_score = best.get_score(difficulty);
+    let best_lamp = best.get_lamp(difficulty);
 
     let mut comparison = PersonalBestComparison::default();
 
@@ -51,7 +53,7 @@ pub fn compare_with_personal_best(
 
     // Miss count comparison: only show when improved (decreased)
     if play_data.miss_count_valid() {
-        let best_miss = best.miss_count[diff_index];
+        let best_miss = best.get_miss_count(difficulty);
         if let Some(best_miss) = best_miss {
             let diff = play_data.miss_count() as i32 - best_miss as i32;
             if diff < 0 {
@@ -66,45 +68,27 @@ pub fn compare_with_personal_best(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::Arc;
 
-    use crate::chart::{ChartInfo, Difficulty};
-    use crate::play::{PlayType, Settings};
+    use crate::chart::Difficulty;
+    use crate::play::PlayType;
+    use crate::play::test_support::PlayBuilder;
     use crate::score::Judge;
 
     fn create_test_play_data(ex_score: u32, grade: Grade, lamp: Lamp) -> PlayData {
-        PlayData {
-            chart: ChartInfo {
-                song_id: 1000,
-                title: Arc::from("Test Song"),
-                title_english: Arc::from(""),
-                artist: Arc::from(""),
-                genre: Arc::from(""),
-                bpm: Arc::from("150"),
-                difficulty: Difficulty::SpA,
-                level: 12,
-                total_notes: 1000, // max EX = 2000
-                unlocked: true,
-            },
-            judge: Judge {
+        // 1000 notes: max EX = 2000
+        PlayBuilder::new()
+            .judge(Judge {
                 play_type: PlayType::P1,
                 pgreat: 900,
                 great: 100,
-                good: 0,
-                bad: 0,
-                poor: 0,
                 fast: 30,
                 slow: 20,
-                combo_break: 0,
-                premature_end: false,
-            },
-            settings: Settings::default(),
-            ex_score,
-            lamp,
-            grade,
-            data_available: true,
-            timestamp: chrono::Utc::now(),
-        }
+                ..Judge::default()
+            })
+            .ex_score(ex_score)
+            .lamp(lamp)
+            .grade(grade)
+            .build()
     }
 
     #[test]
@@ -123,8 +107,8 @@ mod tests {
         let play_data = create_test_play_data(1800, Grade::Aaa, Lamp::HardClear);
 
         let mut best = ScoreData::new(1000);
-        best.score[Difficulty::SpA as usize] = 1780; // Also AAA
-        best.lamp[Difficulty::SpA as usize] = Lamp::HardClear;
+        best.score[Difficulty::SpA.index()] = 1780; // Also AAA
+        best.lamp[Difficulty::SpA.index()] = Lamp::HardClear;
 
         let comparison = compare_with_personal_best(&play_data, Some(&best));
 
@@ -139,8 +123,8 @@ mod tests {
         let play_data = create_test_play_data(1800, Grade::Aaa, Lamp::Clear);
 
         let mut best = ScoreData::new(1000);
-        best.score[Difficulty::SpA as usize] = 1600; // AA
-        best.lamp[Difficulty::SpA as usize] = Lamp::Clear;
+        best.score[Difficulty::SpA.index()] = 1600; // AA
+        best.lamp[Difficulty::SpA.index()] = Lamp::Clear;
 
         let comparison = compare_with_personal_best(&play_data, Some(&best));
 
@@ -154,8 +138,8 @@ mod tests {
         let play_data = create_test_play_data(1800, Grade::Aaa, Lamp::HardClear);
 
         let mut best = ScoreData::new(1000);
-        best.score[Difficulty::SpA as usize] = 1800; // Same score
-        best.lamp[Difficulty::SpA as usize] = Lamp::Clear;
+        best.score[Difficulty::SpA.index()] = 1800; // Same score
+        best.lamp[Difficulty::SpA.index()] = Lamp::Clear;
 
         let comparison = compare_with_personal_best(&play_data, Some(&best));
 
@@ -164,13 +148,28 @@ mod tests {
         assert_eq!(comparison.previous_lamp, Some(Lamp::Clear));
     }
 
+    #[test]
+    fn test_premature_play_is_never_an_improvement() {
+        let mut play_data = create_test_play_data(1800, Grade::Aaa, Lamp::HardClear);
+        play_data.judge.premature_end = true;
+
+        let mut best = ScoreData::new(1000);
+        best.score[Difficulty::SpA.index()] = 1000;
+        best.lamp[Difficulty::SpA.index()] = Lamp::Failed;
+
+        let comparison = compare_with_personal_best(&play_data, Some(&best));
+        assert!(comparison.score_diff.is_none());
+        assert!(comparison.previous_grade.is_none());
+        assert!(comparison.previous_lamp.is_none());
+    }
+
     #[test]
     fn test_compare_with_personal_best_no_improvement() {
         let play_data = create_test_play_data(1600, Grade::Aa, Lamp::Clear);
 
         let mut best = ScoreData::new(1000);
-        best.score[Difficulty::SpA as usize] = 1800; // Better
-        best.lamp[Difficulty::SpA as usize] = Lamp::HardClear; // Better
+        best.score[Difficulty::SpA.index()] = 1800; // Better
+        best.lamp[Difficulty::SpA.index()] = Lamp::HardClear; // Better
 
         let comparison = compare_with_personal_best(&play_data, Some(&best));
 
@@ -185,8 +184,8 @@ mod tests {
         let play_data = create_test_play_data(1800, Grade::Aaa, Lamp::HardClear);
 
         let mut best = ScoreData::new(1000);
-        best.score[Difficulty::SpA as usize] = 0;
-        best.lamp[Difficulty::SpA as usize] = Lamp::NoPlay;
+        best.score[Difficulty::SpA.index()] = 0;
+        best.lamp[Difficulty::SpA.index()] = Lamp::NoPlay;
 
         let comparison = compare_with_personal_best(&play_data, Some(&best));
 
@@ -204,9 +203,9 @@ mod tests {
         play_data.judge.poor = 3;
 
         let mut best = ScoreData::new(1000);
-        best.score[Difficulty::SpA as usize] = 1800;
-        best.lamp[Difficulty::SpA as usize] = Lamp::HardClear;
-        best.miss_count[Difficulty::SpA as usize] = Some(10);
+        best.score[Difficulty::SpA.index()] = 1800;
+        best.lamp[Difficulty::SpA.index()] = Lamp::HardClear;
+        best.miss_count[Difficulty::SpA.index()] = Some(10);
 
         let comparison = compare_with_personal_best(&play_data, Some(&best));
         assert_eq!(comparison.miss_count_diff, Some(-5));
@@ -220,9 +219,9 @@ mod tests {
         play_data.judge.poor = 5;
 
         let mut best = ScoreData::new(1000);
-        best.score[Difficulty::SpA as usize] = 1800;
-        best.lamp[Difficulty::SpA as usize] = Lamp::HardClear;
-        best.miss_count[Difficulty::SpA as usize] = Some(5);
+        best.score[Difficulty::SpA.index()] = 1800;
+        best.lamp[Difficulty::SpA.index()] = Lamp::HardClear;
+        best.miss_count[Difficulty::SpA.index()] = Some(5);
 
         let comparison = compare_with_personal_best(&play_data, Some(&best));
         assert!(comparison.miss_count_diff.is_none());
@@ -234,8 +233,8 @@ mod tests {
         let play_data = create_test_play_data(1800, Grade::Aaa, Lamp::HardClear);
 
         let mut best = ScoreData::new(1000);
-        best.score[Difficulty::SpA as usize] = 1800;
-        best.lamp[Difficulty::SpA as usize] = Lamp::HardClear;
+        best.score[Difficulty::SpA.index()] = 1800;
+        best.lamp[Difficulty::SpA.index()] = Lamp::HardClear;
         // miss_count defaults to None
 
         let comparison = compare_with_personal_best(&play_data, Some(&best));
@@ -249,9 +248,9 @@ mod tests {
         play_data.data_available = false;
 
         let mut best = ScoreData::new(1000);
-        best.score[Difficulty::SpA as usize] = 1800;
-        best.lamp[Difficulty::SpA as usize] = Lamp::HardClear;
-        best.miss_count[Difficulty::SpA as usize] = Some(10);
+        best.score[Difficulty::SpA.index()] = 1800;
+        best.lamp[Difficulty::SpA.index()] = Lamp::HardClear;
+        best.miss_count[Difficulty::SpA.index()] = Some(10);
 
         let comparison = compare_with_personal_best(&play_data, Some(&best));
         assert!(comparison.miss_count_diff.is_none());
@@ -9,12 +9,18 @@ use crate::play::PlayData;
 use crate::score::{Grade, Lamp, ScoreData};
 
 use super::comparison::compare_with_personal_best;
+use super::timezone::DisplayTimezone;
 
 /// Format play data for console display with colored output
 ///
 /// Returns a multi-line string with a boxed format.
-/// If `personal_best` is provided, shows improvement indicators.
-pub fn format_play_data_console(play_data: &PlayData, personal_best: Option<&ScoreData>) -> String {
+/// If `personal_best` is provided, shows improvement indicators. The play
+/// time is shown in `timezone`.
+pub fn format_play_data_console(
+    play_data: &PlayData,
+    personal_best: Option<&ScoreData>,
+    timezone: DisplayTimezone,
+) -> String {
     let mut output = String::new();
 
     // Build title line: "冥 [SPA Lv.12]"
@@ -38,7 +44,10 @@ pub fn format_play_data_console(play_data: &PlayData, personal_best: Option<&Sco
     let border_dim = border.dimmed();
 
     // Build option string
-    let option = play_data.settings.style.as_str();
+    let option = match play_data.settings.lane_cover_summary() {
+        Some(cover) => format!("{} {}", play_data.settings.style.as_str(), cover.dimmed()),
+        None => play_data.settings.style.as_str().to_string(),
+    };
 
     // Compare with personal best
     let comparison = compare_with_personal_best(play_data, personal_best);
@@ -74,9 +83,56 @@ pub fn format_play_data_console(play_data: &PlayData, personal_best: Option<&Sco
     let _ = writeln!(output, "{}", border_dim);
     let _ = writeln!(output, "{}", title_content);
     let _ = writeln!(output, "{}", border_dim);
+    let _ = writeln!(
+        output,
+        "  DATE   : {}",
+        timezone.format(&play_data.timestamp, "%Y-%m-%d %H:%M:%S %:z")
+    );
     let _ = writeln!(output, "  OPTION : {}", option);
-    let _ = writeln!(output, "  LAMP   : {}", lamp_str);
-    let _ = writeln!(output, "  SCORE  : {} {}", score_str, grade_str);
+    if play_data.is_premature() {
+        let _ = writeln!(
+            output,
+            "  LAMP   : {} {}",
+            lamp_str,
+            "(premature end)".dimmed()
+        );
+    } else if let Some(percent) = play_data.failed_at_percent {
+        let _ = writeln!(
+            output,
+            "  LAMP   : {} {}",
+            lamp_str,
+            format!("(failed at {:.1}%)", percent).dimmed()
+        );
+    } else {
+        let _ = writeln!(output, "  LAMP   : {}", lamp_str);
+    }
+    match play_data.next_grade_gap() {
+        Some((next, gap)) => {
+            let _ = writeln!(
+                output,
+                "  SCORE  : {} {} {}",
+                score_str,
+                grade_str,
+                format!("({}-{})", next.short_name(), gap).dimmed()
+            );
+        }
+        None => {
+            let _ = writeln!(output, "  SCORE  : {} {}", score_str, grade_str);
+        }
+    }
+    match play_data.bpi {
+        Some(bpi) => {
+            let _ = writeln!(
+                output,
+                "  RATE   : {:.2}% (BPI {:.2})",
+                play_data.score_rate() * 100.0,
+                bpi
+            );
+        }
+        None => {
+            let _ = writeln!(output, "  RATE   : {:.2}%", play_data.score_rate() * 100.0);
+        }
+    }
     if play_data.miss_count_valid() {
         let miss = play_data.miss_count();
         match comparison.miss_count_diff {
@@ -184,47 +240,30 @@ pub fn format_play_summary(play_data: &PlayData) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::Arc;
 
-    use crate::chart::ChartInfo;
-    use crate::play::{PlayType, Settings};
+    use crate::play::PlayType;
+    use crate::play::test_support::PlayBuilder;
     use crate::score::Judge;
 
-    #[test]
-    fn test_format_play_summary() {
-        let play_data = PlayData {
-            chart: ChartInfo {
-                song_id: 1000,
-                title: Arc::from("Test Song"),
-                title_english: Arc::from(""),
-                artist: Arc::from(""),
-                genre: Arc::from(""),
-                bpm: Arc::from("150"),
-                difficulty: Difficulty::SpA,
-                level: 12,
-                total_notes: 1000,
-                unlocked: true,
-            },
-            judge: Judge {
+    fn sample_play() -> PlayData {
+        PlayBuilder::new()
+            .judge(Judge {
                 play_type: PlayType::P1,
                 pgreat: 900,
                 great: 100,
-                good: 0,
-                bad: 0,
-                poor: 0,
                 fast: 30,
                 slow: 20,
-                combo_break: 0,
-                premature_end: false,
-            },
-            settings: Settings::default(),
-            ex_score: 1900,
-            lamp: Lamp::FullCombo,
-            grade: Grade::Aaa,
-            data_available: true,
-            timestamp: chrono::Utc::now(),
-        };
+                ..Judge::default()
+            })
+            .ex_score(1900)
+            .lamp(Lamp::FullCombo)
+            .grade(Grade::Aaa)
+            .build()
+    }
 
+    #[test]
+    fn test_format_play_summary() {
+        let play_data = sample_play();
         let summary = format_play_summary(&play_data);
         assert!(summary.contains("Test Song"));
         assert!(summary.contains("SPA"));
@@ -233,4 +272,28 @@ mod tests {
         assert!(summary.contains("1900"));
         assert!(!summary.contains("INVALID"));
     }
+
+    #[test]
+    fn test_console_date_in_display_timezone() {
+        use chrono::TimeZone;
+
+        let mut play_data = sample_play();
+        play_data.timestamp = chrono::Utc
+            .with_ymd_and_hms(2026, 3, 1, 14, 59, 30)
+            .unwrap();
+        let jst = "+09:00".parse().unwrap();
+        let output = format_play_data_console(&play_data, None, jst);
+        assert!(output.contains("DATE   : 2026-03-01 23:59:30 +09:00"));
+        let output = format_play_data_console(&play_data, None, DisplayTimezone::Utc);
+        assert!(output.contains("DATE   : 2026-03-01 14:59:30 +00:00"));
+    }
+
+    #[test]
+    fn test_console_failed_at_percent() {
+        let mut play_data = sample_play();
+        play_data.lamp = Lamp::Failed;
+        play_data.failed_at_percent = Some(80.0);
+        let output = format_play_data_console(&play_data, None, DisplayTimezone::Utc);
+        assert!(output.contains("(failed at 80.0%)"));
+    }
 }
@@ -0,0 +1,239 @@
+//! Chart filters for tracker exports
+
+use std::collections::{HashMap, HashSet};
+
+use crate::chart::{ChartId, Difficulty, SongInfo, UnlockData, is_chart_unlocked};
+use crate::score::{Lamp, ScoreMap};
+
+/// Play style restriction for exports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayStyle {
+    /// Single play charts only
+    Sp,
+    /// Double play charts only
+    Dp,
+}
+
+/// Filter applied to charts when exporting tracker data
+///
+/// The default filter matches every existing chart. For TSV exports, which
+/// always contain all difficulty columns, a song is kept when any of its
+/// charts matches. JSON exports drop non-matching charts individually.
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// Minimum chart level (inclusive)
+    pub min_level: Option<u8>,
+    /// Maximum chart level (inclusive)
+    pub max_level: Option<u8>,
+    /// Restrict to SP or DP charts
+    pub style: Option<PlayStyle>,
+    /// Only charts with a recorded play
+    pub played_only: bool,
+    /// Only unlocked charts
+    pub unlocked_only: bool,
+    /// Only songs in these folders (empty = all folders)
+    pub folders: Vec<i32>,
+    /// Only these songs (e.g. the songs with a tag, see
+    /// [`SongTags::songs_with_any`](crate::storage::SongTags::songs_with_any))
+    pub songs: Option<HashSet<u32>>,
+}
+
+impl ExportFilter {
+    /// Whether the filter matches every chart
+    pub fn is_empty(&self) -> bool {
+        self.min_level.is_none()
+            && self.max_level.is_none()
+            && self.style.is_none()
+            && !self.played_only
+            && !self.unlocked_only
+            && self.folders.is_empty()
+            && self.songs.is_none()
+    }
+
+    /// Check whether a single chart passes the filter
+    pub fn matches_chart(
+        &self,
+        song: &SongInfo,
+        difficulty: Difficulty,
+        unlock_db: &HashMap<u32, UnlockData>,
+        song_db: &HashMap<u32, SongInfo>,
+        score_map: &ScoreMap,
+    ) -> bool {
+        let chart = ChartId::new(song.id, difficulty);
+        if !chart.exists_in(song) {
+            return false;
+        }
+        if !self.folders.is_empty() && !self.folders.contains(&song.folder) {
+            return false;
+        }
+        if self
+            .songs
+            .as_ref()
+            .is_some_and(|songs| !songs.contains(&song.id))
+        {
+            return false;
+        }
+
+        let level = song.level_for(difficulty);
+        if self.min_level.is_some_and(|min| level < min)
+            || self.max_level.is_some_and(|max| level > max)
+        {
+            return false;
+        }
+
+        match self.style {
+            Some(PlayStyle::Sp) if !difficulty.is_sp() => return false,
+            Some(PlayStyle::Dp) if !difficulty.is_dp() => return false,
+            _ => {}
+        }
+
+        if self.played_only {
+            let played = score_map.lamp(chart) != Lamp::NoPlay || score_map.score(chart) > 0;
+            if !played {
+                return false;
+            }
+        }
+
+        if self.unlocked_only && !is_chart_unlocked(unlock_db, song_db, chart) {
+            return false;
+        }
+
+        true
+    }
+
+    /// Check whether any of the song's charts passes the filter
+    pub fn matches_song(
+        &self,
+        song: &SongInfo,
+        difficulties: &[Difficulty],
+        unlock_db: &HashMap<u32, UnlockData>,
+        song_db: &HashMap<u32, SongInfo>,
+        score_map: &ScoreMap,
+    ) -> bool {
+        self.is_empty()
+            || difficulties
+                .iter()
+                .any(|&diff| self.matches_chart(song, diff, unlock_db, song_db, score_map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::play::UnlockType;
+    use crate::score::ScoreData;
+
+    fn song(folder: i32) -> SongInfo {
+        SongInfo {
+            id: 1000,
+            title: Arc::from("Test"),
+            title_english: Arc::from(""),
+            artist: Arc::from(""),
+            genre: Arc::from(""),
+            bpm: Arc::from("150"),
+            folder,
+            levels: [0, 5, 8, 10, 12, 0, 5, 8, 11, 0],
+            total_notes: [0, 500, 800, 1000, 1200, 0, 500, 800, 1000, 0],
+            unlock_type: UnlockType::Base,
+        }
+    }
+
+    fn dbs(song: &SongInfo) -> (HashMap<u32, SongInfo>, HashMap<u32, UnlockData>) {
+        let song_db = HashMap::from([(song.id, song.clone())]);
+        let unlock_db = HashMap::from([(
+            song.id,
+            UnlockData {
+                song_id: song.id,
+                unlock_type: UnlockType::Base,
+                // SPN, SPH, SPA unlocked
+                unlocks: 0b1110,
+            },
+        )]);
+        (song_db, unlock_db)
+    }
+
+    #[test]
+    fn test_default_filter_matches_existing_charts() {
+        let song = song(1);
+        let (song_db, unlock_db) = dbs(&song);
+        let filter = ExportFilter::default();
+        let score_map = ScoreMap::new();
+
+        assert!(filter.is_empty());
+        assert!(filter.matches_chart(&song, Difficulty::SpA, &unlock_db, &song_db, &score_map));
+        // No notes: chart does not exist
+        assert!(!filter.matches_chart(&song, Difficulty::DpL, &unlock_db, &song_db, &score_map));
+    }
+
+    #[test]
+    fn test_level_and_style_filter() {
+        let song = song(1);
+        let (song_db, unlock_db) = dbs(&song);
+        let score_map = ScoreMap::new();
+        let filter = ExportFilter {
+            min_level: Some(11),
+            style: Some(PlayStyle::Dp),
+            ..Default::default()
+        };
+
+        assert!(filter.matches_chart(&song, Difficulty::DpA, &unlock_db, &song_db, &score_map));
+        assert!(!filter.matches_chart(&song, Difficulty::SpL, &unlock_db, &song_db, &score_map));
+        assert!(!filter.matches_chart(&song, Difficulty::DpH, &unlock_db, &song_db, &score_map));
+    }
+
+    #[test]
+    fn test_played_unlocked_and_folder_filter() {
+        let song = song(3);
+        let (song_db, unlock_db) = dbs(&song);
+        let mut score_map = ScoreMap::new();
+        let mut scores = ScoreData::new(song.id);
+        scores.lamp[Difficulty::SpH.index()] = Lamp::Clear;
+        scores.lamp[Difficulty::DpA.index()] = Lamp::Failed;
+        score_map.insert(song.id, scores);
+
+        let filter = ExportFilter {
+            played_only: true,
+            unlocked_only: true,
+            ..Default::default()
+        };
+        assert!(filter.matches_chart(&song, Difficulty::SpH, &unlock_db, &song_db, &score_map));
+        // Played but locked
+        assert!(!filter.matches_chart(&song, Difficulty::DpA, &unlock_db, &song_db, &score_map));
+        // Unlocked but not played
+        assert!(!filter.matches_chart(&song, Difficulty::SpA, &unlock_db, &song_db, &score_map));
+
+        let folder_filter = ExportFilter {
+            folders: vec![1, 2],
+            ..Default::default()
+        };
+        assert!(!folder_filter.matches_song(
+            &song,
+            &[Difficulty::SpA],
+            &unlock_db,
+            &song_db,
+            &score_map
+        ));
+    }
+
+    #[test]
+    fn test_song_filter() {
+        let song = song(1);
+        let (song_db, unlock_db) = dbs(&song);
+        let score_map = ScoreMap::new();
+
+        let filter = ExportFilter {
+            songs: Some(HashSet::from([song.id])),
+            ..Default::default()
+        };
+        assert!(!filter.is_empty());
+        assert!(filter.matches_chart(&song, Difficulty::SpA, &unlock_db, &song_db, &score_map));
+
+        let filter = ExportFilter {
+            songs: Some(HashSet::from([2000])),
+            ..Default::default()
+        };
+        assert!(!filter.matches_chart(&song, Difficulty::SpA, &unlock_db, &song_db, &score_map));
+    }
+}
@@ -0,0 +1,211 @@
+//! Integrity signatures for tracker exports
+//!
+//! Leaderboards built from shared tracker files can ask players to sign
+//! their exports with a secret registered on the server. The signature is an
+//! HMAC-SHA256 over every other line of the file, appended as a comment line
+//! that TSV readers skip:
+//!
+//! ```text
+//! # infst-format: tracker-tsv 2
+//! Song ID    Title    ...
+//! ...
+//! # infst-signature: hmac-sha256 3b1f...
+//! ```
+//!
+//! Line endings and trailing blank lines are normalized before hashing, so
+//! a file re-saved by a spreadsheet on another OS still verifies as long as
+//! no cell changed.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+
+use crate::error::{Error, Result};
+
+/// Prefix of the signature line
+const SIGNATURE_PREFIX: &str = "# infst-signature: ";
+
+/// Signature algorithm name written after the prefix
+const ALGORITHM: &str = "hmac-sha256";
+
+/// Secret used to sign and verify exports
+#[derive(Clone)]
+pub struct SigningKey(Vec<u8>);
+
+impl SigningKey {
+    /// Key from raw secret bytes
+    pub fn new(secret: impl Into<Vec<u8>>) -> Result<Self> {
+        let secret = secret.into();
+        if secret.is_empty() {
+            return Err(Error::invalid_config("signing key", "the key is empty"));
+        }
+        Ok(Self(secret))
+    }
+
+    /// Key from a file (surrounding whitespace is ignored)
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| Error::storage(path, e))?;
+        Self::new(bytes.trim_ascii())
+    }
+}
+
+// Never print the secret
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SigningKey(..)")
+    }
+}
+
+/// Result of checking an export's signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SignatureStatus {
+    /// Signed with the given key and unchanged since
+    Valid,
+    /// Signed, but edited afterwards or signed with another key
+    Invalid,
+    /// No signature line
+    Unsigned,
+}
+
+/// Lines covered by the signature and the signature line, if any
+fn split_signature(content: &str) -> (String, Option<&str>) {
+    let mut signature = None;
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        match line.strip_prefix(SIGNATURE_PREFIX) {
+            Some(value) => signature = Some(value.trim()),
+            None => lines.push(line),
+        }
+    }
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    (lines.join("\n"), signature)
+}
+
+/// HMAC-SHA256 over the signed lines
+fn mac(key: &SigningKey, signed: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(&key.0).expect("HMAC takes any key length");
+    mac.update(signed.as_bytes());
+    mac
+}
+
+/// HMAC-SHA256 of the signed lines as lowercase hex
+fn digest(key: &SigningKey, signed: &str) -> String {
+    mac(key, signed).finalize().into_bytes().iter().fold(
+        String::with_capacity(64),
+        |mut hex, byte| {
+            let _ = write!(hex, "{:02x}", byte);
+            hex
+        },
+    )
+}
+
+/// Bytes of a hex string (either case), `None` if it isn't one
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Append a signature line to an export (replacing an existing one)
+pub fn sign_export(content: &str, key: &SigningKey) -> String {
+    let (signed, _) = split_signature(content);
+    let signature = digest(key, &signed);
+    format!("{signed}\n{SIGNATURE_PREFIX}{ALGORITHM} {signature}\n")
+}
+
+/// Check an export's signature against `key`
+pub fn verify_export(content: &str, key: &SigningKey) -> SignatureStatus {
+    let (signed, signature) = split_signature(content);
+    let Some(signature) = signature else {
+        return SignatureStatus::Unsigned;
+    };
+    let Some((ALGORITHM, value)) = signature.split_once(' ') else {
+        return SignatureStatus::Invalid;
+    };
+    let Some(value) = decode_hex(value.trim()) else {
+        return SignatureStatus::Invalid;
+    };
+    // Constant-time comparison, so the time taken does not reveal the prefix
+    if mac(key, &signed).verify_slice(&value).is_ok() {
+        SignatureStatus::Valid
+    } else {
+        SignatureStatus::Invalid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> SigningKey {
+        SigningKey::new("secret").unwrap()
+    }
+
+    const TRACKER: &str =
+        "# infst-format: tracker-tsv 2\nSong ID\tTitle\tSPA Lamp\n1000\tSong A\tHARD\n";
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signed = sign_export(TRACKER, &key());
+        assert!(signed.starts_with(TRACKER));
+        assert!(signed.lines().last().unwrap().starts_with(SIGNATURE_PREFIX));
+        assert_eq!(verify_export(&signed, &key()), SignatureStatus::Valid);
+
+        // Signing again replaces the signature
+        assert_eq!(sign_export(&signed, &key()), signed);
+    }
+
+    #[test]
+    fn test_edited_export_is_invalid() {
+        let signed = sign_export(TRACKER, &key());
+        let edited = signed.replace("HARD", "EX HARD");
+        assert_eq!(verify_export(&edited, &key()), SignatureStatus::Invalid);
+
+        let other_key = SigningKey::new("other").unwrap();
+        assert_eq!(verify_export(&signed, &other_key), SignatureStatus::Invalid);
+    }
+
+    #[test]
+    fn test_signature_hex_is_decoded() {
+        let signed = sign_export(TRACKER, &key());
+        let (_, signature) = signed.trim_end().rsplit_once(' ').unwrap();
+        let upper = signed.replace(signature, &signature.to_ascii_uppercase());
+        assert_eq!(verify_export(&upper, &key()), SignatureStatus::Valid);
+
+        let truncated = signed.replace(signature, &signature[..62]);
+        assert_eq!(verify_export(&truncated, &key()), SignatureStatus::Invalid);
+        let not_hex = signed.replace(signature, &format!("z{}", &signature[1..]));
+        assert_eq!(verify_export(&not_hex, &key()), SignatureStatus::Invalid);
+    }
+
+    #[test]
+    fn test_line_endings_are_normalized() {
+        let signed = sign_export(TRACKER, &key());
+        let crlf = signed.replace('\n', "\r\n");
+        assert_eq!(verify_export(&crlf, &key()), SignatureStatus::Valid);
+    }
+
+    #[test]
+    fn test_unsigned_and_unknown_algorithm() {
+        assert_eq!(verify_export(TRACKER, &key()), SignatureStatus::Unsigned);
+
+        let signed = sign_export(TRACKER, &key()).replace(ALGORITHM, "hmac-md5");
+        assert_eq!(verify_export(&signed, &key()), SignatureStatus::Invalid);
+    }
+
+    #[test]
+    fn test_empty_key_is_rejected() {
+        assert!(SigningKey::new("").is_err());
+    }
+}
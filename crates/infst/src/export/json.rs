@@ -3,7 +3,7 @@
 use serde::Serialize;
 use serde_json::{Value as JsonValue, json};
 
-use crate::play::PlayData;
+use crate::play::{CourseResult, PlayData, PlaySet};
 
 use super::format::ExportFormat;
 
@@ -28,6 +28,7 @@ pub fn format_json_entry(play_data: &PlayData) -> JsonValue {
     } else {
         None
     };
+    let next_grade = play_data.next_grade_gap();
 
     json!({
         "timestamp": play_data.timestamp.to_rfc3339(),
@@ -46,9 +47,64 @@ pub fn format_json_entry(play_data: &PlayData) -> JsonValue {
             "poor": play_data.judge.poor,
             "fast": play_data.judge.fast,
             "slow": play_data.judge.slow,
-            "combo_break": play_data.judge.combo_break
+            "combo_break": play_data.judge.combo_break,
+            "premature_end": play_data.judge.premature_end
         },
-        "miss_count": miss_count
+        "miss_count": miss_count,
+        "next_grade": next_grade.map(|(grade, _)| grade.short_name()),
+        "next_grade_gap": next_grade.map(|(_, gap)| gap),
+        "score_rate": play_data.score_rate(),
+        "bpi": play_data.bpi,
+        "failed_at_percent": play_data.failed_at_percent,
+        "lane_cover": {
+            "sudden": play_data.settings.sudden(),
+            "hidden": play_data.settings.hidden(),
+            "lift": play_data.settings.lift(),
+            "green_number": play_data.settings.green_number()
+        }
+    })
+}
+
+/// Generate JSON entry for a course result in a session file
+pub fn format_course_json(course: &CourseResult) -> JsonValue {
+    json!({ "course": course_fields(course) })
+}
+
+/// Generate JSON entry for a completed set (one credit) in a session file
+pub fn format_set_json(set: &PlaySet) -> JsonValue {
+    let mut fields = course_fields(&set.result);
+    if let Some(object) = fields.as_object_mut() {
+        object.insert("course".to_string(), JsonValue::Bool(set.course));
+    }
+    json!({ "set": fields })
+}
+
+/// Stage list and aggregates shared by course and set entries
+fn course_fields(course: &CourseResult) -> JsonValue {
+    let judge = course.total_judge();
+    json!({
+        "timestamp": course.started_at.to_rfc3339(),
+        "stages": course.stages.iter().map(|s| json!({
+            "song_id": s.chart.song_id,
+            "title": s.chart.title,
+            "difficulty": s.chart.difficulty.short_name(),
+            "ex_score": s.ex_score,
+            "lamp": s.lamp.expand_name(),
+        })).collect::<Vec<_>>(),
+        "passed": course.passed(),
+        "failed_stage": course.failed_stage().map(|i| i + 1),
+        "ex_score": course.ex_score(),
+        "percentage": course.percentage(),
+        "judge": {
+            "pgreat": judge.pgreat,
+            "great": judge.great,
+            "good": judge.good,
+            "bad": judge.bad,
+            "poor": judge.poor,
+            "fast": judge.fast,
+            "slow": judge.slow,
+            "combo_break": judge.combo_break
+        }
     })
 }
 
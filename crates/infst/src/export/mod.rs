@@ -12,6 +12,13 @@
 //! - [`console`]: Console output with colored display
 //! - [`comparison`]: Personal best comparison logic
 //! - [`tracker`]: Tracker data export (TSV/JSON)
+//! - [`filter`]: Chart filters for tracker exports
+//! - [`tracker_diff`]: Dated tracker snapshots and snapshot comparison
+//! - [`tracker_merge`]: Merging trackers from several PCs
+//! - [`changelog`]: Improvements between two tracker exports
+//! - [`integrity`]: HMAC signatures over exported files
+//! - [`timezone`]: Timezone used for displayed timestamps
+//! - [`titles`]: Title language selection for exports and overlays
 //!
 //! # ExportFormat Trait
 //!
@@ -28,11 +35,18 @@
 //! println!("{}", json.format_row(&play_data));
 //! ```
 
+mod changelog;
 mod comparison;
 mod console;
+mod filter;
 mod format;
+mod integrity;
 mod json;
+mod timezone;
+mod titles;
 mod tracker;
+mod tracker_diff;
+mod tracker_merge;
 mod tsv;
 
 // Re-export format trait
@@ -44,11 +58,18 @@ pub use tsv::TsvExporter;
 
 // Re-export TSV functions
 pub use tsv::{
-    TsvRowData, format_full_tsv_header, format_full_tsv_row, format_tsv_header, format_tsv_row,
+    TsvRowData, format_full_tsv_header, format_full_tsv_row, format_full_tsv_row_in,
+    format_tsv_header, format_tsv_row,
 };
 
+// Re-export display timezone
+pub use timezone::DisplayTimezone;
+
+// Re-export title selection
+pub use titles::{TitleAliases, TitleLanguage, TitleOptions};
+
 // Re-export JSON functions
-pub use json::{JudgeJson, PlayDataJson, format_json_entry};
+pub use json::{JudgeJson, PlayDataJson, format_course_json, format_json_entry, format_set_json};
 
 // Re-export console functions
 pub use console::{format_play_data_console, format_play_summary};
@@ -58,6 +79,24 @@ pub use comparison::{PersonalBestComparison, compare_with_personal_best};
 
 // Re-export tracker functions and types
 pub use tracker::{
-    ChartDataJson, ExportDataJson, SongDataJson, export_song_list, export_tracker_json,
-    export_tracker_tsv, format_tracker_tsv_header, generate_tracker_json, generate_tracker_tsv,
+    ChartDataJson, ExportDataJson, SongDataJson, TRACKER_JSON_SCHEMA_VERSION, TRACKER_TAGS_COLUMN,
+    TRACKER_TITLE2_COLUMN, TrackerJsonExtras, export_song_list, export_tracker_json,
+    export_tracker_tsv, format_tracker_tsv_header, format_tracker_tsv_header_with_analytics,
+    generate_tracker_json, generate_tracker_json_filtered, generate_tracker_tsv,
+    generate_tracker_tsv_filtered, generate_tracker_tsv_with_analytics, tracker_json_schema,
 };
+
+// Re-export export filter types
+pub use filter::{ExportFilter, PlayStyle};
+
+// Re-export tracker snapshot functions and types
+pub use tracker_diff::{TrackerCellChange, diff_tracker_tsv, tracker_snapshot_path};
+
+// Re-export tracker changelog
+pub use changelog::{Change, ChartImprovement, DEFAULT_MIN_SCORE_DELTA, tracker_changelog};
+
+// Re-export tracker merging
+pub use tracker_merge::{MergeConflict, Tracker, TrackerMerge};
+
+// Re-export export signatures
+pub use integrity::{SignatureStatus, SigningKey, sign_export, verify_export};
@@ -0,0 +1,119 @@
+//! Timezone for timestamps shown to people
+//!
+//! Play timestamps are stored in UTC (JSON sessions, `PlayData`); session TSV
+//! rows, the console result box and the stream marquee convert them to a
+//! [`DisplayTimezone`] so they match the clock the player sees.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset, Local, Offset, Utc};
+
+/// Timezone used when formatting timestamps for display
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DisplayTimezone {
+    /// The system's local timezone (including daylight saving changes)
+    #[default]
+    Local,
+    Utc,
+    /// A fixed UTC offset such as `+09:00`
+    Fixed(FixedOffset),
+}
+
+impl DisplayTimezone {
+    /// UTC offset in effect at `time`
+    pub fn offset_at(&self, time: &DateTime<Utc>) -> FixedOffset {
+        match self {
+            Self::Local => time.with_timezone(&Local).offset().fix(),
+            Self::Utc => Utc.fix(),
+            Self::Fixed(offset) => *offset,
+        }
+    }
+
+    /// `time` converted to this timezone
+    pub fn convert(&self, time: &DateTime<Utc>) -> DateTime<FixedOffset> {
+        time.with_timezone(&self.offset_at(time))
+    }
+
+    /// RFC 3339 with this timezone's offset (`2026-03-01T21:03:15+09:00`)
+    pub fn to_rfc3339(&self, time: &DateTime<Utc>) -> String {
+        self.convert(time).to_rfc3339()
+    }
+
+    /// Format `time` with a chrono format string
+    pub fn format(&self, time: &DateTime<Utc>, format: &str) -> String {
+        self.convert(time).format(format).to_string()
+    }
+
+    /// Name and current offset, as noted in file headers (`local (+09:00)`)
+    pub fn describe_at(&self, time: &DateTime<Utc>) -> String {
+        format!("{} ({})", self, self.offset_at(time))
+    }
+}
+
+impl fmt::Display for DisplayTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local => write!(f, "local"),
+            Self::Utc => write!(f, "UTC"),
+            Self::Fixed(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+impl FromStr for DisplayTimezone {
+    type Err = String;
+
+    /// `local`, `utc` or an offset such as `+09:00`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "local" => Ok(Self::Local),
+            "utc" | "z" => Ok(Self::Utc),
+            other => other.parse::<FixedOffset>().map(Self::Fixed).map_err(|_| {
+                format!(
+                    "invalid timezone '{}' (expected local, utc or an offset like +09:00)",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn time() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 1, 14, 59, 30).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("local".parse(), Ok(DisplayTimezone::Local));
+        assert_eq!("UTC".parse(), Ok(DisplayTimezone::Utc));
+        assert_eq!(
+            "+09:00".parse(),
+            Ok(DisplayTimezone::Fixed(
+                FixedOffset::east_opt(9 * 3600).unwrap()
+            ))
+        );
+        assert!("Asia/Tokyo".parse::<DisplayTimezone>().is_err());
+    }
+
+    #[test]
+    fn test_formatting() {
+        let jst: DisplayTimezone = "+09:00".parse().unwrap();
+        assert_eq!(jst.to_rfc3339(&time()), "2026-03-01T23:59:30+09:00");
+        assert_eq!(jst.format(&time(), "%m/%d %H:%M"), "03/01 23:59");
+        assert_eq!(jst.describe_at(&time()), "+09:00 (+09:00)");
+        assert_eq!(
+            DisplayTimezone::Utc.to_rfc3339(&time()),
+            "2026-03-01T14:59:30+00:00"
+        );
+        assert_eq!(DisplayTimezone::Utc.describe_at(&time()), "UTC (+00:00)");
+        // The same instant, whatever the display timezone
+        let local = DisplayTimezone::Local.to_rfc3339(&time());
+        assert_eq!(DateTime::parse_from_rfc3339(&local).unwrap(), time());
+    }
+}
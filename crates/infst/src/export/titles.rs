@@ -0,0 +1,186 @@
+//! Title language selection for tracker exports and stream output
+//!
+//! Songs carry the in-game title and, for most songs, an English title. A
+//! title alias file adds romanized titles for the rest:
+//!
+//! ```json
+//! { "冥": "Mei", "表裏一体": "Hyouri Ittai" }
+//! ```
+//!
+//! Session files always keep the in-game title, since infst resolves songs
+//! by title when reading them back.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString, IntoStaticStr};
+
+use crate::error::{Error, Result};
+
+/// Which title of a song to show
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    Display,
+    EnumString,
+    IntoStaticStr,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum TitleLanguage {
+    /// In-game title
+    #[default]
+    Original,
+    /// English title, falling back to the in-game title
+    English,
+    /// Alias from the title alias file, falling back to an ASCII in-game
+    /// title, then the English title
+    Romanized,
+}
+
+/// Romanized titles keyed by in-game title
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TitleAliases {
+    titles: HashMap<String, String>,
+}
+
+impl TitleAliases {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a title alias file's content
+    pub fn from_json(content: &str) -> Result<Self> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    /// Load a title alias file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| Error::storage(path, e))?;
+        Self::from_json(&content)
+    }
+
+    pub fn insert(&mut self, title: impl Into<String>, alias: impl Into<String>) {
+        self.titles.insert(title.into(), alias.into());
+    }
+
+    pub fn len(&self) -> usize {
+        self.titles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.titles.is_empty()
+    }
+
+    /// Alias of an in-game title
+    pub fn get(&self, title: &str) -> Option<&str> {
+        self.titles
+            .get(title)
+            .map(String::as_str)
+            .filter(|alias| !alias.trim().is_empty())
+    }
+}
+
+/// Title columns of an export or overlay
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TitleOptions {
+    /// Title shown in the main title column
+    pub primary: TitleLanguage,
+    /// Title shown in an extra column (none by default)
+    pub secondary: Option<TitleLanguage>,
+    /// Romanized titles for [`TitleLanguage::Romanized`]
+    pub aliases: TitleAliases,
+}
+
+impl TitleOptions {
+    /// The title of a song in `language`
+    pub fn title(&self, language: TitleLanguage, title: &str, title_english: &str) -> String {
+        let english = Some(title_english).filter(|english| !english.trim().is_empty());
+        match language {
+            TitleLanguage::Original => title,
+            TitleLanguage::English => english.unwrap_or(title),
+            TitleLanguage::Romanized => self
+                .aliases
+                .get(title)
+                .or_else(|| title.is_ascii().then_some(title))
+                .or(english)
+                .unwrap_or(title),
+        }
+        .to_string()
+    }
+
+    /// The title for the main title column
+    pub fn primary_title(&self, title: &str, title_english: &str) -> String {
+        self.title(self.primary, title, title_english)
+    }
+
+    /// The title for the extra title column, when one is configured
+    pub fn secondary_title(&self, title: &str, title_english: &str) -> Option<String> {
+        self.secondary
+            .map(|language| self.title(language, title, title_english))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(primary: TitleLanguage) -> TitleOptions {
+        let mut aliases = TitleAliases::new();
+        aliases.insert("冥", "Mei");
+        TitleOptions {
+            primary,
+            secondary: None,
+            aliases,
+        }
+    }
+
+    #[test]
+    fn test_title_languages() {
+        let original = options(TitleLanguage::Original);
+        assert_eq!(original.primary_title("冥", "MEI"), "冥");
+
+        let english = options(TitleLanguage::English);
+        assert_eq!(english.primary_title("冥", "MEI"), "MEI");
+        assert_eq!(english.primary_title("冥", " "), "冥");
+
+        let romanized = options(TitleLanguage::Romanized);
+        assert_eq!(romanized.primary_title("冥", "MEI"), "Mei");
+        assert_eq!(romanized.primary_title("Broken", ""), "Broken");
+        assert_eq!(romanized.primary_title("表裏一体", "Hyouri"), "Hyouri");
+        assert_eq!(romanized.primary_title("表裏一体", ""), "表裏一体");
+    }
+
+    #[test]
+    fn test_secondary_title() {
+        let mut titles = options(TitleLanguage::Romanized);
+        assert_eq!(titles.secondary_title("冥", "MEI"), None);
+        titles.secondary = Some(TitleLanguage::Original);
+        assert_eq!(titles.secondary_title("冥", "MEI").as_deref(), Some("冥"));
+    }
+
+    #[test]
+    fn test_parse_language_and_aliases() {
+        assert_eq!(
+            "romanized".parse::<TitleLanguage>().unwrap(),
+            TitleLanguage::Romanized
+        );
+        assert!("japanese".parse::<TitleLanguage>().is_err());
+
+        let aliases = TitleAliases::from_json(r#"{"冥": "Mei", "灼熱": ""}"#).unwrap();
+        assert_eq!(aliases.get("冥"), Some("Mei"));
+        // Empty aliases are ignored
+        assert_eq!(aliases.get("灼熱"), None);
+        assert!(TitleAliases::from_json("[]").is_err());
+    }
+}
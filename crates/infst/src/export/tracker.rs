@@ -1,44 +1,159 @@
 //! Tracker data export (TSV and JSON formats)
+//!
+//! The JSON export carries a top-level `schema_version` and is described by
+//! [`tracker_json_schema`], generated from the serde types below. The version
+//! is bumped whenever the schema changes; the tests fail if the schema changes
+//! without a new version.
 
 use std::collections::HashMap;
-use std::fs;
 use std::path::Path;
 
+use schemars::generate::SchemaSettings;
+use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
 use serde::Serialize;
 
-use crate::chart::{Difficulty, SongInfo, UnlockData, get_unlock_state_for_difficulty};
+use crate::chart::{
+    ChartId, DensityMap, DensityProfile, Difficulty, SongInfo, UnlockData, is_chart_unlocked,
+};
 use crate::error::Result;
 use crate::play::{PlayData, UnlockType, calculate_dj_points};
 use crate::score::{Grade, Lamp, ScoreMap};
+use crate::stats::ChartAnalytics;
+use crate::storage::{
+    FileFormat, SongTags, TextEncoding, WriteOptions, write_atomic, write_atomic_with,
+    write_text_atomic,
+};
+
+use super::filter::ExportFilter;
+use super::titles::TitleOptions;
+
+/// Version of the tracker JSON export format
+pub const TRACKER_JSON_SCHEMA_VERSION: u32 = 6;
+
+/// JSON Schema (draft 2020-12) describing the tracker JSON export
+pub fn tracker_json_schema() -> Schema {
+    SchemaSettings::draft2020_12()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<ExportDataJson>()
+}
 
 /// Chart data for JSON export
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct ChartDataJson {
+    #[schemars(schema_with = "difficulty_schema")]
     pub difficulty: String,
     pub level: u8,
+    #[schemars(schema_with = "lamp_schema")]
     pub lamp: String,
+    #[schemars(schema_with = "grade_schema")]
     pub grade: String,
     pub ex_score: u32,
+    /// Best miss count; null when never recorded
     pub miss_count: Option<u32>,
     pub total_notes: u32,
+    #[schemars(range(min = 0.0))]
     pub dj_points: f64,
+    /// Best of plays with assist options, kept apart from the clean best
+    /// above; present only when one was recorded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "AssistedChartJson")]
+    pub assisted: Option<AssistedChartJson>,
+    /// Notes per time section; present only when a chart density file is given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "DensityProfile")]
+    pub density: Option<DensityProfile>,
+    /// Statistics over the recorded plays; present only when play history is
+    /// given and the chart was played
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "ChartAnalytics")]
+    pub analytics: Option<ChartAnalytics>,
+}
+
+/// Assisted best of a chart for JSON export
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AssistedChartJson {
+    #[schemars(schema_with = "lamp_schema")]
+    pub lamp: String,
+    pub ex_score: u32,
+    pub miss_count: Option<u32>,
 }
 
 /// Song data for JSON export
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct SongDataJson {
     pub song_id: u32,
+    /// Title in the primary title language (in-game title by default)
     pub title: String,
+    /// Title in the secondary title language; present only when one is configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "String")]
+    pub title2: Option<String>,
     pub artist: String,
+    /// User-defined tags from the tags file, alphabetical
+    #[schemars(extend("uniqueItems" = true))]
+    pub tags: Vec<String>,
     pub charts: Vec<ChartDataJson>,
 }
 
 /// Export data for JSON export
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
+#[schemars(
+    title = "infst tracker export",
+    description = "Best lamp, grade and EX score per chart written by `infst export --format json`"
+)]
 pub struct ExportDataJson {
+    /// Always [`TRACKER_JSON_SCHEMA_VERSION`]
+    #[schemars(
+        schema_with = "schema_version_schema",
+        description = "Version of the export format"
+    )]
+    pub schema_version: u32,
     pub songs: Vec<SongDataJson>,
 }
 
+fn schema_version_schema(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({ "const": TRACKER_JSON_SCHEMA_VERSION })
+}
+
+fn difficulty_schema(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({ "enum": TRACKER_DIFFICULTY_NAMES })
+}
+
+fn lamp_schema(_: &mut SchemaGenerator) -> Schema {
+    let lamps: Vec<&str> = (0..)
+        .map_while(Lamp::from_repr)
+        .map(|lamp| lamp.expand_name())
+        .collect();
+    json_schema!({ "enum": lamps })
+}
+
+fn grade_schema(_: &mut SchemaGenerator) -> Schema {
+    let grades: Vec<&str> = (0..)
+        .map_while(Grade::from_repr)
+        .map(|grade| grade.short_name())
+        .collect();
+    json_schema!({ "enum": grades })
+}
+
+/// Difficulty column prefixes (DPB doesn't exist)
+pub(super) const TRACKER_DIFFICULTY_NAMES: [&str; 9] = [
+    "SPB", "SPN", "SPH", "SPA", "SPL", "DPN", "DPH", "DPA", "DPL",
+];
+
+/// Difficulties in tracker column order
+const TRACKER_DIFFICULTIES: [Difficulty; 9] = [
+    Difficulty::SpB,
+    Difficulty::SpN,
+    Difficulty::SpH,
+    Difficulty::SpA,
+    Difficulty::SpL,
+    Difficulty::DpN,
+    Difficulty::DpH,
+    Difficulty::DpA,
+    Difficulty::DpL,
+];
+
 /// Generate detailed tracker TSV header
 pub fn format_tracker_tsv_header() -> String {
     let mut columns = vec![
@@ -54,10 +169,7 @@ pub fn format_tracker_tsv_header() -> String {
     ];
 
     // Add columns for each difficulty (skipping DPB which doesn't exist)
-    let difficulties = [
-        "SPB", "SPN", "SPH", "SPA", "SPL", "DPN", "DPH", "DPA", "DPL",
-    ];
-    for diff in difficulties {
+    for diff in TRACKER_DIFFICULTY_NAMES {
         columns.push(format!("{} Unlocked", diff));
         columns.push(format!("{} Rating", diff));
         columns.push(format!("{} Lamp", diff));
@@ -71,26 +183,75 @@ pub fn format_tracker_tsv_header() -> String {
     columns.join("\t")
 }
 
-/// Export detailed tracker data to TSV
+/// Generate tracker TSV header including per-chart analytics columns
+///
+/// Analytics columns are appended after the regular columns so existing
+/// column positions stay unchanged.
+pub fn format_tracker_tsv_header_with_analytics() -> String {
+    let mut columns = vec![format_tracker_tsv_header()];
+    for diff in TRACKER_DIFFICULTY_NAMES {
+        columns.push(format!("{} Plays", diff));
+        columns.push(format!("{} Avg Miss", diff));
+        columns.push(format!("{} Score SD", diff));
+        columns.push(format!("{} Fast/Slow", diff));
+        columns.push(format!("{} Single Break Rate", diff));
+    }
+    columns.join("\t")
+}
+
+/// Assisted best columns, appended after the analytics columns when any
+/// chart has an assisted best
+pub fn format_tracker_tsv_assisted_header() -> String {
+    let mut columns = Vec::new();
+    for diff in TRACKER_DIFFICULTY_NAMES {
+        columns.push(format!("{} Assisted Lamp", diff));
+        columns.push(format!("{} Assisted EX Score", diff));
+    }
+    columns.join("\t")
+}
+
+/// Column with the song's tags, appended when a tags file is given
+pub const TRACKER_TAGS_COLUMN: &str = "Tags";
+
+/// Column with the song's secondary title, appended last when a secondary
+/// title language is configured
+pub const TRACKER_TITLE2_COLUMN: &str = "Title 2";
+
+/// Export detailed tracker data to TSV in `encoding`
 pub fn export_tracker_tsv<P: AsRef<Path>>(
     path: P,
     song_db: &HashMap<u32, SongInfo>,
     unlock_db: &HashMap<u32, UnlockData>,
     score_map: &ScoreMap,
+    encoding: TextEncoding,
 ) -> Result<()> {
-    let mut lines = vec![format_tracker_tsv_header()];
+    let assisted = score_map.has_assisted();
+    let mut header = format_tracker_tsv_header();
+    if assisted {
+        header.push('\t');
+        header.push_str(&format_tracker_tsv_assisted_header());
+    }
+    let mut lines = vec![FileFormat::TrackerTsv.marker(), header];
 
     // Get all song IDs from song database (sorted)
     let mut song_ids: Vec<&u32> = song_db.keys().collect();
     song_ids.sort();
 
     for &song_id in song_ids {
-        if let Some(entry) = generate_tracker_entry(song_id, song_db, unlock_db, score_map) {
+        if let Some(entry) = generate_tracker_entry(
+            song_id,
+            song_db,
+            unlock_db,
+            score_map,
+            None,
+            assisted,
+            &TitleOptions::default(),
+        ) {
             lines.push(entry);
         }
     }
 
-    fs::write(path, lines.join("\n"))?;
+    write_text_atomic(path, &lines.join("\n"), encoding, WriteOptions::DURABLE)?;
     Ok(())
 }
 
@@ -99,6 +260,9 @@ fn generate_tracker_entry(
     song_db: &HashMap<u32, SongInfo>,
     unlock_db: &HashMap<u32, UnlockData>,
     score_map: &ScoreMap,
+    analytics: Option<&HashMap<ChartId, ChartAnalytics>>,
+    assisted: bool,
+    titles: &TitleOptions,
 ) -> Option<String> {
     let song = song_db.get(&song_id)?;
     let unlock = unlock_db.get(&song_id)?;
@@ -110,7 +274,7 @@ fn generate_tracker_entry(
     columns.push(song_id.to_string());
 
     // Title
-    columns.push(song.title.to_string());
+    columns.push(titles.primary_title(&song.title, &song.title_english));
 
     // Type and Label (Label is same as Type)
     let type_name = match unlock.unlock_type {
@@ -122,15 +286,8 @@ fn generate_tracker_entry(
     columns.push(type_name.to_string()); // Label = Type
 
     // Bit costs (for N, H, A)
-    for i in [1, 2, 3] {
-        // SPN, SPH, SPA indices
-        let cost = if unlock.unlock_type == UnlockType::Bits {
-            let sp_level = song.levels[i] as i32;
-            let dp_level = song.levels[i + 5] as i32; // DPN, DPH, DPA
-            500 * (sp_level + dp_level)
-        } else {
-            0
-        };
+    for diff in [Difficulty::SpN, Difficulty::SpH, Difficulty::SpA] {
+        let cost = unlock.bits_cost(song, diff).unwrap_or(0);
         columns.push(cost.to_string());
     }
 
@@ -138,29 +295,16 @@ fn generate_tracker_entry(
     let mut sp_djp = 0.0f64;
     let mut dp_djp = 0.0f64;
 
-    // Difficulty columns
-    let difficulties = [
-        Difficulty::SpB,
-        Difficulty::SpN,
-        Difficulty::SpH,
-        Difficulty::SpA,
-        Difficulty::SpL,
-        Difficulty::DpN,
-        Difficulty::DpH,
-        Difficulty::DpA,
-        Difficulty::DpL,
-    ];
-
     let mut chart_data = Vec::new();
-    for diff in &difficulties {
-        let diff_index = *diff as usize;
-        let unlocked = get_unlock_state_for_difficulty(unlock_db, song_db, song_id, *diff);
-        let level = song.levels[diff_index];
-        let total_notes = song.total_notes[diff_index];
+    for diff in &TRACKER_DIFFICULTIES {
+        let chart = ChartId::new(song_id, *diff);
+        let unlocked = is_chart_unlocked(unlock_db, song_db, chart);
+        let level = song.level_for(*diff);
+        let total_notes = song.total_notes_for(*diff);
 
         let (lamp, grade, ex_score, miss_count, djp) = if let Some(s) = scores {
-            let lamp = s.lamp[diff_index];
-            let ex_score = s.score[diff_index];
+            let lamp = s.get_lamp(*diff);
+            let ex_score = s.get_score(*diff);
             let grade = if total_notes > 0 {
                 PlayData::calculate_grade(ex_score, total_notes)
             } else {
@@ -171,7 +315,7 @@ fn generate_tracker_entry(
             } else {
                 0.0
             };
-            let miss_count = s.miss_count[diff_index];
+            let miss_count = s.get_miss_count(*diff);
             (lamp, grade, ex_score, miss_count, djp)
         } else {
             (Lamp::NoPlay, Grade::NoPlay, 0, None, 0.0)
@@ -228,6 +372,43 @@ fn generate_tracker_entry(
         });
     }
 
+    // Analytics columns (only when requested)
+    if let Some(analytics) = analytics {
+        for diff in TRACKER_DIFFICULTIES {
+            let chart = ChartId::new(song_id, diff);
+            match analytics.get(&chart) {
+                Some(a) => {
+                    columns.push(a.play_count.to_string());
+                    columns.push(
+                        a.average_miss_count
+                            .map(|m| format!("{:.1}", m))
+                            .unwrap_or_default(),
+                    );
+                    columns.push(format!("{:.1}", a.score_std_dev));
+                    columns.push(
+                        a.fast_slow_ratio
+                            .map(|r| format!("{:.2}", r))
+                            .unwrap_or_default(),
+                    );
+                    columns.push(format!("{:.2}", a.single_break_rate));
+                }
+                None => columns.extend(std::iter::repeat_n(String::new(), 5)),
+            }
+        }
+    }
+
+    if assisted {
+        for diff in TRACKER_DIFFICULTIES {
+            match scores.and_then(|s| s.get_assisted(diff)) {
+                Some(best) => {
+                    columns.push(best.lamp.short_name().to_string());
+                    columns.push(best.score.to_string());
+                }
+                None => columns.extend(std::iter::repeat_n(String::new(), 2)),
+            }
+        }
+    }
+
     Some(columns.join("\t"))
 }
 
@@ -251,7 +432,7 @@ pub fn export_song_list<P: AsRef<Path>>(path: P, song_db: &HashMap<u32, SongInfo
         }
     }
 
-    fs::write(path, lines.join("\n"))?;
+    write_atomic(path, lines.join("\n"))?;
     Ok(())
 }
 
@@ -263,7 +444,7 @@ pub fn export_tracker_json<P: AsRef<Path>>(
     score_map: &ScoreMap,
 ) -> Result<()> {
     let content = generate_tracker_json(song_db, unlock_db, score_map)?;
-    fs::write(path, content)?;
+    write_atomic_with(path, content, WriteOptions::DURABLE)?;
     Ok(())
 }
 
@@ -272,6 +453,40 @@ pub fn generate_tracker_json(
     song_db: &HashMap<u32, SongInfo>,
     unlock_db: &HashMap<u32, UnlockData>,
     score_map: &ScoreMap,
+) -> Result<String> {
+    generate_tracker_json_filtered(
+        song_db,
+        unlock_db,
+        score_map,
+        &TrackerJsonExtras::default(),
+        &ExportFilter::default(),
+        &TitleOptions::default(),
+    )
+}
+
+/// Optional data added to the songs and charts of the JSON export
+#[derive(Clone, Copy, Default)]
+pub struct TrackerJsonExtras<'a> {
+    /// Tags listed by each song
+    pub tags: Option<&'a SongTags>,
+    /// Density profiles of the charts
+    pub density: Option<&'a DensityMap>,
+    /// Analytics of the charts (e.g. from [`collect_chart_analytics`](crate::stats::collect_chart_analytics))
+    pub analytics: Option<&'a HashMap<ChartId, ChartAnalytics>>,
+}
+
+/// Generate tracker JSON string containing only charts matching `filter`
+///
+/// Songs without any matching chart are omitted. Each song lists its tags
+/// from `extras` and its titles in the languages chosen by `titles`; charts
+/// carry their density profile and analytics when `extras` has them.
+pub fn generate_tracker_json_filtered(
+    song_db: &HashMap<u32, SongInfo>,
+    unlock_db: &HashMap<u32, UnlockData>,
+    score_map: &ScoreMap,
+    extras: &TrackerJsonExtras,
+    filter: &ExportFilter,
+    titles: &TitleOptions,
 ) -> Result<String> {
     let mut songs = Vec::new();
 
@@ -280,12 +495,33 @@ pub fn generate_tracker_json(
     song_ids.sort();
 
     for &song_id in song_ids {
-        if let Some(song_data) = generate_song_json(song_id, song_db, unlock_db, score_map) {
+        if let Some(mut song_data) =
+            generate_song_json(song_id, song_db, unlock_db, score_map, filter, titles)
+            && !song_data.charts.is_empty()
+        {
+            if let Some(tags) = extras.tags {
+                song_data.tags = tags
+                    .tags_for(song_id)
+                    .into_iter()
+                    .map(String::from)
+                    .collect();
+            }
+            for chart in &mut song_data.charts {
+                let Ok(difficulty) = chart.difficulty.parse() else {
+                    continue;
+                };
+                let id = ChartId::new(song_id, difficulty);
+                chart.density = extras.density.and_then(|d| d.get(id)).cloned();
+                chart.analytics = extras.analytics.and_then(|a| a.get(&id)).cloned();
+            }
             songs.push(song_data);
         }
     }
 
-    let export_data = ExportDataJson { songs };
+    let export_data = ExportDataJson {
+        schema_version: TRACKER_JSON_SCHEMA_VERSION,
+        songs,
+    };
     let json = serde_json::to_string_pretty(&export_data)?;
     Ok(json)
 }
@@ -295,6 +531,8 @@ fn generate_song_json(
     song_db: &HashMap<u32, SongInfo>,
     unlock_db: &HashMap<u32, UnlockData>,
     score_map: &ScoreMap,
+    filter: &ExportFilter,
+    titles: &TitleOptions,
 ) -> Option<SongDataJson> {
     let song = song_db.get(&song_id)?;
     let _unlock = unlock_db.get(&song_id)?;
@@ -314,21 +552,20 @@ fn generate_song_json(
 
     let mut charts = Vec::new();
     for diff in &difficulties {
-        let diff_index = *diff as usize;
-        let level = song.levels[diff_index];
-        let total_notes = song.total_notes[diff_index];
+        let level = song.level_for(*diff);
+        let total_notes = song.total_notes_for(*diff);
 
-        // Skip charts with no notes (non-existent difficulty)
-        if total_notes == 0 {
+        // Skip charts with no notes (non-existent difficulty) or filtered out
+        if total_notes == 0 || !filter.matches_chart(song, *diff, unlock_db, song_db, score_map) {
             continue;
         }
 
         let (lamp, grade, ex_score, miss_count, djp) = if let Some(s) = scores {
-            let lamp = s.lamp[diff_index];
-            let ex_score = s.score[diff_index];
+            let lamp = s.get_lamp(*diff);
+            let ex_score = s.get_score(*diff);
             let grade = PlayData::calculate_grade(ex_score, total_notes);
             let djp = calculate_dj_points(ex_score, grade, lamp);
-            let miss_count = s.miss_count[diff_index];
+            let miss_count = s.get_miss_count(*diff);
             (lamp, grade, ex_score, miss_count, djp)
         } else {
             (Lamp::NoPlay, Grade::NoPlay, 0, None, 0.0)
@@ -343,13 +580,24 @@ fn generate_song_json(
             miss_count,
             total_notes,
             dj_points: djp,
+            assisted: scores
+                .and_then(|s| s.get_assisted(*diff))
+                .map(|best| AssistedChartJson {
+                    lamp: best.lamp.expand_name().to_string(),
+                    ex_score: best.score,
+                    miss_count: best.miss_count,
+                }),
+            density: None,
+            analytics: None,
         });
     }
 
     Some(SongDataJson {
         song_id,
-        title: song.title.to_string(),
+        title: titles.primary_title(&song.title, &song.title_english),
+        title2: titles.secondary_title(&song.title, &song.title_english),
         artist: song.artist.to_string(),
+        tags: Vec::new(),
         charts,
     })
 }
@@ -360,14 +608,91 @@ pub fn generate_tracker_tsv(
     unlock_db: &HashMap<u32, UnlockData>,
     score_map: &ScoreMap,
 ) -> String {
-    let mut lines = vec![format_tracker_tsv_header()];
+    generate_tracker_tsv_filtered(
+        song_db,
+        unlock_db,
+        score_map,
+        None,
+        None,
+        &ExportFilter::default(),
+        &TitleOptions::default(),
+    )
+}
+
+/// Generate tracker TSV string with per-chart analytics columns appended
+pub fn generate_tracker_tsv_with_analytics(
+    song_db: &HashMap<u32, SongInfo>,
+    unlock_db: &HashMap<u32, UnlockData>,
+    score_map: &ScoreMap,
+    analytics: &HashMap<ChartId, ChartAnalytics>,
+) -> String {
+    generate_tracker_tsv_filtered(
+        song_db,
+        unlock_db,
+        score_map,
+        Some(analytics),
+        None,
+        &ExportFilter::default(),
+        &TitleOptions::default(),
+    )
+}
+
+/// Generate tracker TSV string for songs with at least one chart matching `filter`
+///
+/// The "Title" column holds the primary title from `titles`. Analytics
+/// columns are appended when `analytics` is given, then the assisted best
+/// columns when any chart has one, then a [`TRACKER_TAGS_COLUMN`] when
+/// `tags` is given, then a [`TRACKER_TITLE2_COLUMN`] when `titles` has a
+/// secondary language.
+pub fn generate_tracker_tsv_filtered(
+    song_db: &HashMap<u32, SongInfo>,
+    unlock_db: &HashMap<u32, UnlockData>,
+    score_map: &ScoreMap,
+    analytics: Option<&HashMap<ChartId, ChartAnalytics>>,
+    tags: Option<&SongTags>,
+    filter: &ExportFilter,
+    titles: &TitleOptions,
+) -> String {
+    let mut header = if analytics.is_some() {
+        format_tracker_tsv_header_with_analytics()
+    } else {
+        format_tracker_tsv_header()
+    };
+    let assisted = score_map.has_assisted();
+    if assisted {
+        header.push('\t');
+        header.push_str(&format_tracker_tsv_assisted_header());
+    }
+    if tags.is_some() {
+        header.push('\t');
+        header.push_str(TRACKER_TAGS_COLUMN);
+    }
+    if titles.secondary.is_some() {
+        header.push('\t');
+        header.push_str(TRACKER_TITLE2_COLUMN);
+    }
+    let mut lines = vec![FileFormat::TrackerTsv.marker(), header];
 
     // Get all song IDs from song database (sorted)
     let mut song_ids: Vec<&u32> = song_db.keys().collect();
     song_ids.sort();
 
     for &song_id in song_ids {
-        if let Some(entry) = generate_tracker_entry(song_id, song_db, unlock_db, score_map) {
+        let song = &song_db[&song_id];
+        if !filter.matches_song(song, &TRACKER_DIFFICULTIES, unlock_db, song_db, score_map) {
+            continue;
+        }
+        if let Some(mut entry) = generate_tracker_entry(
+            song_id, song_db, unlock_db, score_map, analytics, assisted, titles,
+        ) {
+            if let Some(tags) = tags {
+                entry.push('\t');
+                entry.push_str(&tags.tags_for(song_id).join(", "));
+            }
+            if let Some(title2) = titles.secondary_title(&song.title, &song.title_english) {
+                entry.push('\t');
+                entry.push_str(&title2);
+            }
             lines.push(entry);
         }
     }
@@ -380,6 +705,11 @@ mod tests {
     use super::*;
     use std::sync::Arc;
 
+    use crate::export::{PlayStyle, TitleAliases, TitleLanguage};
+    use crate::play::test_support::PlayBuilder;
+    use crate::score::ChartBest;
+    use crate::stats::collect_chart_analytics;
+
     fn create_test_song(id: u32, title: &str) -> SongInfo {
         SongInfo {
             id,
@@ -445,6 +775,268 @@ mod tests {
         assert!(json.contains("\"title\": \"Test Song\""));
     }
 
+    fn schema() -> serde_json::Value {
+        tracker_json_schema().to_value()
+    }
+
+    #[test]
+    fn test_tracker_json_matches_schema() {
+        let mut song_db: HashMap<u32, SongInfo> = HashMap::new();
+        song_db.insert(1000, create_test_song(1000, "Test Song"));
+        let mut unlock_db: HashMap<u32, UnlockData> = HashMap::new();
+        unlock_db.insert(
+            1000,
+            UnlockData {
+                song_id: 1000,
+                unlock_type: UnlockType::Base,
+                unlocks: 0x3FF,
+            },
+        );
+
+        // Optional fields are included with a secondary title language, an
+        // assisted best, a density profile and analytics
+        let mut score_map = ScoreMap::new();
+        score_map.get_or_insert(1000).record_assisted(
+            Difficulty::SpA,
+            ChartBest {
+                lamp: Lamp::AssistClear,
+                score: 1200,
+                miss_count: None,
+            },
+        );
+        let mut density = DensityMap::new();
+        density.insert(
+            ChartId::new(1000, Difficulty::SpA),
+            DensityProfile {
+                section_ms: 1000,
+                notes: vec![1, 2, 3],
+            },
+        );
+        let plays = [PlayBuilder::new()
+            .song_id(1000)
+            .difficulty(Difficulty::SpA)
+            .build()];
+        let analytics = collect_chart_analytics(&plays);
+        let titles = TitleOptions {
+            secondary: Some(TitleLanguage::English),
+            ..TitleOptions::default()
+        };
+        let json = generate_tracker_json_filtered(
+            &song_db,
+            &unlock_db,
+            &score_map,
+            &TrackerJsonExtras {
+                tags: None,
+                density: Some(&density),
+                analytics: Some(&analytics),
+            },
+            &ExportFilter::default(),
+            &titles,
+        )
+        .unwrap();
+        let data: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(data["schema_version"], TRACKER_JSON_SCHEMA_VERSION);
+
+        let keys = |value: &serde_json::Value| -> Vec<String> {
+            let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+            keys.sort();
+            keys
+        };
+        let required = |value: &serde_json::Value| -> Vec<String> {
+            let mut keys: Vec<String> = value
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|key| key.as_str().unwrap().to_string())
+                .collect();
+            keys.sort();
+            keys
+        };
+
+        let schema = schema();
+        assert_eq!(keys(&data), required(&schema["required"]));
+        assert_eq!(keys(&data), keys(&schema["properties"]));
+
+        let song = &data["songs"][0];
+        let song_schema = &schema["$defs"]["SongDataJson"];
+        let mut song_required = keys(song);
+        song_required.retain(|key| key != "title2");
+        assert_eq!(song_required, required(&song_schema["required"]));
+        assert_eq!(keys(song), keys(&song_schema["properties"]));
+
+        let charts = song["charts"].as_array().unwrap();
+        let chart = charts
+            .iter()
+            .find(|chart| chart["difficulty"] == "SPA")
+            .unwrap();
+        let chart_schema = &schema["$defs"]["ChartDataJson"];
+        let mut chart_required = keys(chart);
+        chart_required.retain(|key| !["density", "assisted", "analytics"].contains(&key.as_str()));
+        assert_eq!(chart_required, required(&chart_schema["required"]));
+        assert_eq!(keys(chart), keys(&chart_schema["properties"]));
+        let density = &schema["$defs"]["DensityProfile"];
+        assert_eq!(keys(&chart["density"]), required(&density["required"]));
+        let analytics = &schema["$defs"]["ChartAnalytics"];
+        assert_eq!(keys(&chart["analytics"]), keys(&analytics["properties"]));
+        let assisted = &schema["$defs"]["AssistedChartJson"];
+        assert_eq!(keys(&chart["assisted"]), required(&assisted["required"]));
+        assert_eq!(keys(&chart["assisted"]), keys(&assisted["properties"]));
+        assert_eq!(chart["assisted"]["lamp"], "ASSIST CLEAR");
+
+        let enum_of = |field: &str| chart_schema["properties"][field]["enum"].clone();
+        assert_eq!(
+            enum_of("difficulty"),
+            serde_json::json!(TRACKER_DIFFICULTY_NAMES)
+        );
+        let lamps: Vec<&str> = (0..)
+            .map_while(Lamp::from_repr)
+            .map(|lamp| lamp.expand_name())
+            .collect();
+        assert_eq!(enum_of("lamp"), serde_json::json!(lamps));
+        let grades: Vec<&str> = (0..)
+            .map_while(Grade::from_repr)
+            .map(|grade| grade.short_name())
+            .collect();
+        assert_eq!(enum_of("grade"), serde_json::json!(grades));
+    }
+
+    /// Changing the export types (and so the generated schema) requires
+    /// bumping [`TRACKER_JSON_SCHEMA_VERSION`] and updating the fingerprint below
+    #[test]
+    fn test_tracker_json_schema_version_is_bumped_on_change() {
+        // Version and SHA-256 of the generated schema it was published with
+        const FINGERPRINT: (u32, &str) = (
+            6,
+            "bd14c3e8b476b2f979a52679443e7cf69bb2e204afdea865430f1f83eb8cba3d",
+        );
+
+        let schema = schema();
+        assert_eq!(
+            schema["properties"]["schema_version"]["const"],
+            TRACKER_JSON_SCHEMA_VERSION
+        );
+
+        let canonical = serde_json::to_string(&schema).unwrap();
+        let fingerprint: String = <sha2::Sha256 as sha2::Digest>::digest(canonical.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        assert_eq!(
+            (TRACKER_JSON_SCHEMA_VERSION, fingerprint.as_str()),
+            FINGERPRINT,
+            "tracker JSON schema changed: bump TRACKER_JSON_SCHEMA_VERSION and update the fingerprint"
+        );
+    }
+
+    #[test]
+    fn test_tags_are_exported() {
+        let mut song_db: HashMap<u32, SongInfo> = HashMap::new();
+        let mut unlock_db: HashMap<u32, UnlockData> = HashMap::new();
+        for id in [1000, 1001] {
+            song_db.insert(id, create_test_song(id, "Song"));
+            unlock_db.insert(
+                id,
+                UnlockData {
+                    song_id: id,
+                    unlock_type: UnlockType::Base,
+                    unlocks: 0x3FF,
+                },
+            );
+        }
+        let tags =
+            SongTags::from_json(r#"{"scratch heavy": [1000], "speed practice": [1000]}"#).unwrap();
+        let filter = ExportFilter::default();
+
+        let tsv = generate_tracker_tsv_filtered(
+            &song_db,
+            &unlock_db,
+            &ScoreMap::new(),
+            None,
+            Some(&tags),
+            &filter,
+            &TitleOptions::default(),
+        );
+        let lines: Vec<&str> = tsv.lines().collect();
+        assert!(lines[1].ends_with("\tTags"));
+        assert!(lines[2].ends_with("\tscratch heavy, speed practice"));
+        assert!(lines[3].ends_with('\t'));
+
+        let json = generate_tracker_json_filtered(
+            &song_db,
+            &unlock_db,
+            &ScoreMap::new(),
+            &TrackerJsonExtras {
+                tags: Some(&tags),
+                ..Default::default()
+            },
+            &filter,
+            &TitleOptions::default(),
+        )
+        .unwrap();
+        let data: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            data["songs"][0]["tags"],
+            serde_json::json!(["scratch heavy", "speed practice"])
+        );
+        assert_eq!(data["songs"][1]["tags"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_title_languages_are_exported() {
+        let mut song = create_test_song(1000, "冥");
+        song.title_english = Arc::from("MEI");
+        let song_db = HashMap::from([(1000, song)]);
+        let unlock_db = HashMap::from([(
+            1000,
+            UnlockData {
+                song_id: 1000,
+                unlock_type: UnlockType::Base,
+                unlocks: 0x3FF,
+            },
+        )]);
+        let mut aliases = TitleAliases::new();
+        aliases.insert("冥", "Mei");
+        let titles = TitleOptions {
+            primary: TitleLanguage::Romanized,
+            secondary: Some(TitleLanguage::Original),
+            aliases,
+        };
+        let filter = ExportFilter::default();
+
+        let tsv = generate_tracker_tsv_filtered(
+            &song_db,
+            &unlock_db,
+            &ScoreMap::new(),
+            None,
+            None,
+            &filter,
+            &titles,
+        );
+        let lines: Vec<&str> = tsv.lines().collect();
+        assert!(lines[1].ends_with("\tTitle 2"));
+        assert!(lines[2].starts_with("1000\tMei\t"));
+        assert!(lines[2].ends_with("\t冥"));
+
+        let json = generate_tracker_json_filtered(
+            &song_db,
+            &unlock_db,
+            &ScoreMap::new(),
+            &TrackerJsonExtras::default(),
+            &filter,
+            &titles,
+        )
+        .unwrap();
+        let data: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(data["songs"][0]["title"], "Mei");
+        assert_eq!(data["songs"][0]["title2"], "冥");
+
+        // Without a secondary language there is no extra column or field
+        let json = generate_tracker_json(&song_db, &unlock_db, &ScoreMap::new()).unwrap();
+        let data: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(data["songs"][0]["title"], "冥");
+        assert!(data["songs"][0].get("title2").is_none());
+    }
+
     #[test]
     fn test_generate_tracker_tsv_header_only_when_empty() {
         let song_db: HashMap<u32, SongInfo> = HashMap::new();
@@ -454,8 +1046,166 @@ mod tests {
         let tsv = generate_tracker_tsv(&song_db, &unlock_db, &score_map);
         let lines: Vec<&str> = tsv.lines().collect();
 
-        // Should only have header
-        assert_eq!(lines.len(), 1);
-        assert!(lines[0].contains("Title"));
+        // Should only have the version marker and header
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], FileFormat::TrackerTsv.marker());
+        assert!(lines[1].contains("Title"));
+    }
+
+    #[test]
+    fn test_generate_tracker_tsv_with_analytics() {
+        let mut song_db: HashMap<u32, SongInfo> = HashMap::new();
+        song_db.insert(1000, create_test_song(1000, "Test Song"));
+
+        let mut unlock_db: HashMap<u32, UnlockData> = HashMap::new();
+        unlock_db.insert(
+            1000,
+            UnlockData {
+                song_id: 1000,
+                unlock_type: UnlockType::Base,
+                unlocks: 0x3FF,
+            },
+        );
+
+        let mut analytics = HashMap::new();
+        analytics.insert(
+            ChartId::new(1000, Difficulty::SpA),
+            ChartAnalytics {
+                play_count: 4,
+                average_score: 1500.0,
+                score_std_dev: 25.0,
+                average_miss_count: Some(12.5),
+                fast_slow_ratio: Some(1.25),
+                single_break_rate: 0.25,
+            },
+        );
+
+        let tsv =
+            generate_tracker_tsv_with_analytics(&song_db, &unlock_db, &ScoreMap::new(), &analytics);
+        let lines: Vec<&str> = tsv.lines().collect();
+        assert_eq!(lines.len(), 3);
+
+        let header: Vec<&str> = lines[1].split('\t').collect();
+        let row: Vec<&str> = lines[2].split('\t').collect();
+        assert_eq!(header.len(), row.len());
+
+        let col = |name: &str| header.iter().position(|h| *h == name).unwrap();
+        assert_eq!(row[col("SPA Plays")], "4");
+        assert_eq!(row[col("SPA Avg Miss")], "12.5");
+        assert_eq!(row[col("SPA Fast/Slow")], "1.25");
+        assert_eq!(row[col("SPA Single Break Rate")], "0.25");
+        assert_eq!(row[col("SPH Plays")], "");
+    }
+
+    #[test]
+    fn test_assisted_columns() {
+        let mut song_db = HashMap::new();
+        song_db.insert(1000, create_test_song(1000, "Test Song"));
+        let mut unlock_db = HashMap::new();
+        unlock_db.insert(
+            1000,
+            UnlockData {
+                song_id: 1000,
+                unlock_type: UnlockType::Base,
+                unlocks: 0x3FF,
+            },
+        );
+        let mut score_map = ScoreMap::new();
+        score_map
+            .get_or_insert(1000)
+            .set_lamp(Difficulty::SpA, Lamp::Clear);
+
+        let tsv = generate_tracker_tsv(&song_db, &unlock_db, &score_map);
+        assert!(!tsv.contains("Assisted"));
+
+        score_map.get_or_insert(1000).record_assisted(
+            Difficulty::SpA,
+            ChartBest {
+                lamp: Lamp::HardClear,
+                score: 1600,
+                miss_count: None,
+            },
+        );
+        let tsv = generate_tracker_tsv(&song_db, &unlock_db, &score_map);
+        let lines: Vec<&str> = tsv.lines().collect();
+        let header: Vec<&str> = lines[1].split('\t').collect();
+        let row: Vec<&str> = lines[2].split('\t').collect();
+        assert_eq!(header.len(), row.len());
+        let col = |name: &str| header.iter().position(|h| *h == name).unwrap();
+        assert_eq!(row[col("SPA Lamp")], "CLEAR");
+        assert_eq!(row[col("SPA Assisted Lamp")], "HARD");
+        assert_eq!(row[col("SPA Assisted EX Score")], "1600");
+        assert_eq!(row[col("SPH Assisted Lamp")], "");
+    }
+
+    #[test]
+    fn test_generate_tracker_filtered() {
+        let mut song_db = HashMap::new();
+        song_db.insert(1000, create_test_song(1000, "Easy Song"));
+        let mut hard_song = create_test_song(1001, "Hard Song");
+        hard_song.levels[Difficulty::SpA.index()] = 12;
+        song_db.insert(1001, hard_song);
+
+        let mut unlock_db = HashMap::new();
+        for id in [1000, 1001] {
+            unlock_db.insert(
+                id,
+                UnlockData {
+                    song_id: id,
+                    unlock_type: UnlockType::Base,
+                    unlocks: 0x3FF,
+                },
+            );
+        }
+
+        let filter = ExportFilter {
+            min_level: Some(12),
+            max_level: Some(12),
+            style: Some(PlayStyle::Sp),
+            folders: vec![1],
+            ..Default::default()
+        };
+
+        // Both songs have an SPL 12; only SPA/SPL charts remain in JSON
+        let tsv = generate_tracker_tsv_filtered(
+            &song_db,
+            &unlock_db,
+            &ScoreMap::new(),
+            None,
+            None,
+            &filter,
+            &TitleOptions::default(),
+        );
+        assert_eq!(tsv.lines().count(), 4);
+
+        let json = generate_tracker_json_filtered(
+            &song_db,
+            &unlock_db,
+            &ScoreMap::new(),
+            &TrackerJsonExtras::default(),
+            &filter,
+            &TitleOptions::default(),
+        )
+        .unwrap();
+        let data: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let songs = data["songs"].as_array().unwrap();
+        assert_eq!(songs[0]["charts"].as_array().unwrap().len(), 1);
+        assert_eq!(songs[1]["charts"].as_array().unwrap().len(), 2);
+
+        // Folder mismatch removes everything (marker and header remain)
+        let filter = ExportFilter {
+            folders: vec![2],
+            ..Default::default()
+        };
+        let tsv = generate_tracker_tsv_filtered(
+            &song_db,
+            &unlock_db,
+            &ScoreMap::new(),
+            None,
+            None,
+            &filter,
+            &TitleOptions::default(),
+        );
+        assert_eq!(tsv.lines().count(), 2);
     }
 }
@@ -0,0 +1,182 @@
+//! Dated tracker snapshots and snapshot comparison
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+/// Columns that identify a row rather than hold progress
+const KEY_COLUMNS: [&str; 2] = ["Song ID", "Title"];
+
+/// Path of the dated snapshot for `tracker_path`
+///
+/// `tracker.tsv` becomes `tracker-2025-06-01.tsv` in the same directory.
+pub fn tracker_snapshot_path(tracker_path: &Path, date: NaiveDate) -> PathBuf {
+    let stem = tracker_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "tracker".to_string());
+    let file_name = match tracker_path.extension() {
+        Some(ext) => format!(
+            "{}-{}.{}",
+            stem,
+            date.format("%Y-%m-%d"),
+            ext.to_string_lossy()
+        ),
+        None => format!("{}-{}", stem, date.format("%Y-%m-%d")),
+    };
+    tracker_path.with_file_name(file_name)
+}
+
+/// A single changed cell between two tracker snapshots
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrackerCellChange {
+    pub song_id: String,
+    pub title: String,
+    pub column: String,
+    /// Previous value (`None` when the song was not in the old snapshot)
+    pub old: Option<String>,
+    pub new: String,
+}
+
+struct TrackerTable<'a> {
+    columns: Vec<&'a str>,
+    rows: HashMap<&'a str, Vec<&'a str>>,
+    order: Vec<&'a str>,
+}
+
+fn parse_tracker_table(content: &str) -> TrackerTable<'_> {
+    // Skip the format marker (and any other comment lines)
+    let mut lines = content.lines().filter(|line| !line.starts_with('#'));
+    let columns: Vec<&str> = lines
+        .next()
+        .map(|header| header.split('\t').collect())
+        .unwrap_or_default();
+
+    let mut rows = HashMap::new();
+    let mut order = Vec::new();
+    for line in lines.filter(|l| !l.trim().is_empty()) {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if let Some(&song_id) = fields.first() {
+            order.push(song_id);
+            rows.insert(song_id, fields);
+        }
+    }
+
+    TrackerTable {
+        columns,
+        rows,
+        order,
+    }
+}
+
+/// Compare two tracker TSV snapshots and list changed cells
+///
+/// Rows are matched by Song ID and columns by header name, so snapshots
+/// written by different versions can be compared. Songs only present in
+/// `old` are ignored. Changes are returned in the order of `new`.
+pub fn diff_tracker_tsv(old: &str, new: &str) -> Vec<TrackerCellChange> {
+    let old = parse_tracker_table(old);
+    let new = parse_tracker_table(new);
+
+    let old_index: HashMap<&str, usize> = old
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (*name, i))
+        .collect();
+    let title_index = new.columns.iter().position(|c| *c == "Title");
+
+    let mut changes = Vec::new();
+    for song_id in &new.order {
+        let new_row = &new.rows[song_id];
+        let old_row = old.rows.get(song_id);
+        let title = title_index
+            .and_then(|i| new_row.get(i))
+            .copied()
+            .unwrap_or_default();
+
+        for (i, column) in new.columns.iter().enumerate() {
+            if KEY_COLUMNS.contains(column) {
+                continue;
+            }
+            let new_value = new_row.get(i).copied().unwrap_or_default();
+            let old_value = old_row.map(|row| {
+                old_index
+                    .get(column)
+                    .and_then(|&j| row.get(j))
+                    .copied()
+                    .unwrap_or_default()
+            });
+
+            if old_value == Some(new_value) {
+                continue;
+            }
+            // Skip empty cells of newly added songs
+            if old_value.is_none() && new_value.is_empty() {
+                continue;
+            }
+
+            changes.push(TrackerCellChange {
+                song_id: song_id.to_string(),
+                title: title.to_string(),
+                column: column.to_string(),
+                old: old_value.map(str::to_string),
+                new: new_value.to_string(),
+            });
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_snapshot_path() {
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        assert_eq!(
+            tracker_snapshot_path(Path::new("tracker.tsv"), date),
+            PathBuf::from("tracker-2025-06-01.tsv")
+        );
+        assert_eq!(
+            tracker_snapshot_path(Path::new("out/scores.tsv"), date),
+            PathBuf::from("out/scores-2025-06-01.tsv")
+        );
+    }
+
+    #[test]
+    fn test_diff_tracker_tsv() {
+        let old = "Song ID\tTitle\tSPA Lamp\tSPA EX Score\n\
+                   1000\tSong A\tCLEAR\t1500\n\
+                   1001\tSong B\tHARD\t1700\n";
+        let new = "Song ID\tTitle\tSPA Lamp\tSPA EX Score\n\
+                   1000\tSong A\tHARD\t1520\n\
+                   1001\tSong B\tHARD\t1700\n\
+                   1002\tSong C\tNO PLAY\t\n";
+
+        let changes = diff_tracker_tsv(old, new);
+        assert_eq!(changes.len(), 3);
+
+        assert_eq!(changes[0].song_id, "1000");
+        assert_eq!(changes[0].column, "SPA Lamp");
+        assert_eq!(changes[0].old.as_deref(), Some("CLEAR"));
+        assert_eq!(changes[0].new, "HARD");
+        assert_eq!(changes[1].column, "SPA EX Score");
+        assert_eq!(changes[1].new, "1520");
+
+        // New song: non-empty cells only, no previous value
+        assert_eq!(changes[2].song_id, "1002");
+        assert_eq!(changes[2].title, "Song C");
+        assert_eq!(changes[2].old, None);
+    }
+
+    #[test]
+    fn test_diff_tracker_tsv_matches_columns_by_name() {
+        let old = "Song ID\tSPA EX Score\tTitle\n1000\t1500\tSong A\n";
+        let new = "Song ID\tTitle\tSPA EX Score\n1000\tSong A\t1500\n";
+        assert!(diff_tracker_tsv(old, new).is_empty());
+    }
+}
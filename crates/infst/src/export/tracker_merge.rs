@@ -0,0 +1,476 @@
+//! Merging tracker TSV files from several PCs
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::chart::{Difficulty, SongInfo, intern};
+use crate::error::{Error, Result};
+use crate::play::{PlayData, UnlockType, calculate_dj_points};
+use crate::score::{Grade, Lamp};
+use crate::storage::{
+    FileFormat, TextEncoding, WriteOptions, decode_text, migrate::migrate, write_text_atomic,
+};
+
+use super::tracker::TRACKER_DIFFICULTY_NAMES;
+
+/// A tracker TSV held as rows of named columns
+///
+/// Rows are keyed by the first column (Song ID). Columns are matched by
+/// header name, so trackers written by different versions can be merged.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tracker {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+/// A cell both trackers disagree on with no merge rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MergeConflict {
+    pub song_id: String,
+    pub title: String,
+    pub column: String,
+    /// Value written to the merged tracker (from the first tracker)
+    pub kept: String,
+    /// Value of the second tracker
+    pub other: String,
+}
+
+/// Result of [`Tracker::merge`]
+#[derive(Debug, Clone, Default)]
+pub struct TrackerMerge {
+    pub tracker: Tracker,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl Tracker {
+    /// Parse tracker TSV content in the current format
+    pub fn parse(content: &str) -> Self {
+        let mut lines = content
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.trim().is_empty());
+        let columns = lines
+            .next()
+            .map(|header| header.split('\t').map(str::to_string).collect())
+            .unwrap_or_default();
+        let rows = lines
+            .map(|line| line.split('\t').map(str::to_string).collect())
+            .collect();
+        Self { columns, rows }
+    }
+
+    /// Load a tracker file, upgrading older formats
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|e| Error::storage(path, e))?;
+        let migration = migrate(FileFormat::TrackerTsv, &decode_text(&bytes))?;
+        Ok(Self::parse(&migration.content))
+    }
+
+    /// Write the tracker with the current format marker
+    pub fn save<P: AsRef<Path>>(&self, path: P, encoding: TextEncoding) -> Result<()> {
+        write_text_atomic(path, &self.to_tsv(), encoding, WriteOptions::DURABLE)
+    }
+
+    /// TSV content with the current format marker
+    pub fn to_tsv(&self) -> String {
+        let mut lines = vec![FileFormat::TrackerTsv.marker(), self.columns.join("\t")];
+        lines.extend(self.rows.iter().map(|row| row.join("\t")));
+        lines.join("\n")
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Rows in file order, cells in [`columns`](Self::columns) order
+    pub fn rows(&self) -> &[Vec<String>] {
+        &self.rows
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Value of a cell, by Song ID and column name
+    pub fn get(&self, song_id: &str, column: &str) -> Option<&str> {
+        let index = self.columns.iter().position(|c| c == column)?;
+        self.rows
+            .iter()
+            .find(|row| row.first().is_some_and(|id| id == song_id))
+            .and_then(|row| row.get(index))
+            .map(String::as_str)
+    }
+
+    /// Songs listed in the tracker, with the levels and note counts of its
+    /// Rating and Note Count columns
+    ///
+    /// Only what a tracker records is filled in: artist, genre, BPM and
+    /// folder are empty. Rows without a numeric Song ID are skipped.
+    pub fn song_database(&self) -> HashMap<u32, SongInfo> {
+        let column = |name: &str| self.columns.iter().position(|c| c == name);
+        let title = column("Title");
+        let charts: Vec<(usize, Option<usize>, Option<usize>)> = TRACKER_DIFFICULTY_NAMES
+            .iter()
+            .filter_map(|name| {
+                let difficulty: Difficulty = name.parse().ok()?;
+                Some((
+                    difficulty.index(),
+                    column(&format!("{} Rating", name)),
+                    column(&format!("{} Note Count", name)),
+                ))
+            })
+            .collect();
+
+        let mut songs = HashMap::new();
+        for row in &self.rows {
+            let Some(id) = row.first().and_then(|id| id.trim().parse::<u32>().ok()) else {
+                continue;
+            };
+            let cell = |index: Option<usize>| index.and_then(|i| row.get(i)).map(|c| c.trim());
+            let mut levels = [0u8; 10];
+            let mut total_notes = [0u32; 10];
+            for &(index, level, notes) in &charts {
+                levels[index] = cell(level).and_then(|c| c.parse().ok()).unwrap_or(0);
+                total_notes[index] = cell(notes).and_then(|c| c.parse().ok()).unwrap_or(0);
+            }
+            songs.insert(
+                id,
+                SongInfo {
+                    id,
+                    title: intern(cell(title).unwrap_or_default()),
+                    title_english: intern(""),
+                    artist: intern(""),
+                    genre: intern(""),
+                    bpm: intern(""),
+                    folder: 0,
+                    levels,
+                    total_notes,
+                    unlock_type: UnlockType::default(),
+                },
+            );
+        }
+        songs
+    }
+
+    /// Merge two trackers of the same player
+    ///
+    /// Per chart the merged row has the best lamp, the best EX score (with
+    /// its letter), the lowest miss count and a chart unlocked on either PC;
+    /// DJ points are recomputed from the merged lamp and score. Songs found in
+    /// only one tracker are copied. Any other cell that differs keeps the
+    /// value from `self` and is reported as a conflict; empty cells never
+    /// conflict. Columns are in `self`'s order, followed by columns only
+    /// `other` has; rows are sorted by Song ID.
+    pub fn merge(&self, other: &Tracker) -> TrackerMerge {
+        let mut columns = self.columns.clone();
+        for column in &other.columns {
+            if !columns.contains(column) {
+                columns.push(column.clone());
+            }
+        }
+
+        let ours = self.rows_by_id(&columns);
+        let theirs = other.rows_by_id(&columns);
+        let mut song_ids: Vec<&str> = ours.keys().chain(theirs.keys()).copied().collect();
+        song_ids.sort_by(|a, b| compare_song_ids(a, b));
+        song_ids.dedup();
+
+        let mut conflicts = Vec::new();
+        let rows = song_ids
+            .into_iter()
+            .map(|song_id| match (ours.get(song_id), theirs.get(song_id)) {
+                (Some(a), Some(b)) => merge_row(&columns, a, b, &mut conflicts),
+                (Some(row), None) | (None, Some(row)) => row.clone(),
+                (None, None) => unreachable!("song IDs come from either tracker"),
+            })
+            .collect();
+
+        TrackerMerge {
+            tracker: Tracker { columns, rows },
+            conflicts,
+        }
+    }
+
+    /// Rows keyed by Song ID, with cells rearranged to `columns`
+    fn rows_by_id(&self, columns: &[String]) -> HashMap<&str, Vec<String>> {
+        let positions: Vec<Option<usize>> = columns
+            .iter()
+            .map(|column| self.columns.iter().position(|c| c == column))
+            .collect();
+        self.rows
+            .iter()
+            .filter_map(|row| {
+                let song_id = row.first()?.as_str();
+                let cells = positions
+                    .iter()
+                    .map(|&i| i.and_then(|i| row.get(i)).cloned().unwrap_or_default())
+                    .collect();
+                Some((song_id, cells))
+            })
+            .collect()
+    }
+}
+
+/// Numeric order for Song IDs, text order for anything else
+fn compare_song_ids(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u32>(), b.parse::<u32>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+fn merge_row(
+    columns: &[String],
+    a: &[String],
+    b: &[String],
+    conflicts: &mut Vec<MergeConflict>,
+) -> Vec<String> {
+    let index = |name: &str| columns.iter().position(|c| c == name);
+    let mut merged = a.to_vec();
+    let mut handled = vec![false; columns.len()];
+    handled[0] = true;
+
+    let mut style_djp = [0.0f64; 2];
+    for difficulty in TRACKER_DIFFICULTY_NAMES {
+        let column = |field: &str| index(&format!("{difficulty} {field}"));
+
+        if let Some(i) = column("Unlocked") {
+            let unlocked = a[i] == "TRUE" || b[i] == "TRUE";
+            merged[i] = if unlocked { "TRUE" } else { "FALSE" }.to_string();
+            handled[i] = true;
+        }
+
+        let lamp = column("Lamp").map(|i| {
+            handled[i] = true;
+            let lamp = parse_lamp(&a[i]).max(parse_lamp(&b[i]));
+            if a[i].is_empty() && b[i].is_empty() {
+                merged[i].clear();
+            } else {
+                merged[i] = lamp.short_name().to_string();
+            }
+            lamp
+        });
+
+        let ex_score = column("EX Score").map(|i| {
+            handled[i] = true;
+            let (score_a, score_b) = (parse_number(&a[i]), parse_number(&b[i]));
+            let from_b = score_b > score_a;
+            if from_b {
+                merged[i] = b[i].clone();
+            }
+            if let Some(letter) = column("Letter") {
+                handled[letter] = true;
+                if from_b || a[letter].is_empty() {
+                    merged[letter] = b[letter].clone();
+                }
+            }
+            score_a.max(score_b).unwrap_or(0)
+        });
+
+        if let Some(i) = column("Miss Count") {
+            handled[i] = true;
+            let best = [&a[i], &b[i]]
+                .into_iter()
+                .filter_map(|v| parse_number(v))
+                .min();
+            if let Some(best) = best {
+                merged[i] = best.to_string();
+            } else if merged[i].is_empty() {
+                merged[i] = b[i].clone();
+            }
+        }
+
+        // Letter and DJ points follow the merged lamp and score
+        let total_notes = column("Note Count")
+            .and_then(|i| parse_number(&merged[i]).or_else(|| parse_number(&b[i])))
+            .unwrap_or(0);
+        let (Some(lamp), Some(ex_score)) = (lamp, ex_score) else {
+            continue;
+        };
+        if total_notes == 0 {
+            continue;
+        }
+        let grade = if ex_score > 0 || lamp != Lamp::NoPlay {
+            PlayData::calculate_grade(ex_score, total_notes)
+        } else {
+            Grade::NoPlay
+        };
+        if let Some(letter) = column("Letter")
+            && ex_score > 0
+        {
+            merged[letter] = grade.short_name().to_string();
+        }
+        let djp = calculate_dj_points(ex_score, grade, lamp);
+        if let Some(i) = column("DJ Points") {
+            handled[i] = true;
+            merged[i] = if djp > 0.0 {
+                format!("{}", djp)
+            } else {
+                String::new()
+            };
+        }
+        let style = usize::from(difficulty.starts_with("DP"));
+        style_djp[style] = style_djp[style].max(djp);
+    }
+
+    for (style, name) in ["SP DJ Points", "DP DJ Points"].into_iter().enumerate() {
+        if let Some(i) = index(name) {
+            handled[i] = true;
+            let existing = [&a[i], &b[i]]
+                .into_iter()
+                .filter_map(|v| v.parse::<f64>().ok())
+                .fold(0.0, f64::max);
+            let djp = if style_djp[style] > 0.0 {
+                style_djp[style]
+            } else {
+                existing
+            };
+            merged[i] = if djp > 0.0 {
+                format!("{}", djp)
+            } else {
+                String::new()
+            };
+        }
+    }
+
+    // Everything else: take the non-empty value, keep ours on conflict
+    let title = index("Title").map(|i| a[i].clone()).unwrap_or_default();
+    for (i, column) in columns.iter().enumerate() {
+        if handled[i] || a[i] == b[i] || b[i].is_empty() {
+            continue;
+        }
+        if a[i].is_empty() {
+            merged[i] = b[i].clone();
+            continue;
+        }
+        conflicts.push(MergeConflict {
+            song_id: a[0].clone(),
+            title: title.clone(),
+            column: column.clone(),
+            kept: a[i].clone(),
+            other: b[i].clone(),
+        });
+    }
+
+    merged
+}
+
+pub(super) fn parse_lamp(value: &str) -> Lamp {
+    value.parse().unwrap_or(Lamp::NoPlay)
+}
+
+pub(super) fn parse_number(value: &str) -> Option<u32> {
+    value.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "Song ID\tTitle\tType\tSP DJ Points\tDP DJ Points\t\
+                          SPA Unlocked\tSPA Rating\tSPA Lamp\tSPA Letter\tSPA EX Score\t\
+                          SPA Miss Count\tSPA Note Count\tSPA DJ Points";
+
+    fn tracker(rows: &[&str]) -> Tracker {
+        Tracker::parse(&format!("{HEADER}\n{}", rows.join("\n")))
+    }
+
+    #[test]
+    fn test_merge_takes_best_per_chart() {
+        let laptop = tracker(&["1000\tSong A\tBits\t\t\tTRUE\t12\tHARD\tA\t1400\t30\t1000\t"]);
+        let desktop = tracker(&["1000\tSong A\tBits\t\t\tFALSE\t12\tCLEAR\tAA\t1600\t12\t1000\t"]);
+
+        let merge = laptop.merge(&desktop);
+        assert!(merge.conflicts.is_empty());
+        let merged = &merge.tracker;
+        assert_eq!(merged.get("1000", "SPA Unlocked"), Some("TRUE"));
+        assert_eq!(merged.get("1000", "SPA Lamp"), Some("HARD"));
+        assert_eq!(merged.get("1000", "SPA EX Score"), Some("1600"));
+        assert_eq!(merged.get("1000", "SPA Letter"), Some("AA"));
+        assert_eq!(merged.get("1000", "SPA Miss Count"), Some("12"));
+
+        let expected = calculate_dj_points(1600, Grade::Aa, Lamp::HardClear);
+        assert_eq!(
+            merged.get("1000", "SPA DJ Points"),
+            Some(expected.to_string().as_str())
+        );
+        assert_eq!(
+            merged.get("1000", "SP DJ Points"),
+            Some(expected.to_string().as_str())
+        );
+    }
+
+    #[test]
+    fn test_song_database() {
+        let tracker = tracker(&[
+            "1000\tSong A\tBits\t\t\tTRUE\t12\tHARD\tA\t1400\t30\t1000\t",
+            "not an id\tJunk",
+        ]);
+        let songs = tracker.song_database();
+        assert_eq!(songs.len(), 1);
+        let song = &songs[&1000];
+        assert_eq!(&*song.title, "Song A");
+        assert_eq!(song.level_for(Difficulty::SpA), 12);
+        assert_eq!(song.total_notes_for(Difficulty::SpA), 1000);
+        assert_eq!(song.total_notes_for(Difficulty::SpH), 0);
+    }
+
+    #[test]
+    fn test_merge_unions_songs_and_sorts() {
+        let a = tracker(&["1002\tSong C\tBase\t\t\tTRUE\t10\tNO PLAY\t-\t0\t-\t800\t"]);
+        let b = tracker(&[
+            "1000\tSong A\tBase\t\t\tTRUE\t10\tNO PLAY\t-\t0\t-\t800\t",
+            "999\tSong Z\tBase\t\t\tTRUE\t10\tNO PLAY\t-\t0\t-\t800\t",
+        ]);
+
+        let merged = a.merge(&b).tracker;
+        assert_eq!(merged.len(), 3);
+        let ids: Vec<&str> = merged.rows.iter().map(|r| r[0].as_str()).collect();
+        assert_eq!(ids, ["999", "1000", "1002"]);
+        assert_eq!(merged.get("1002", "SPA Miss Count"), Some("-"));
+    }
+
+    #[test]
+    fn test_merge_reports_conflicts() {
+        let a = tracker(&["1000\tSong A\tBits\t\t\tTRUE\t12\tNO PLAY\t-\t0\t-\t1000\t"]);
+        let b = tracker(&["1000\tSong A (new)\tBits\t\t\tTRUE\t11\tNO PLAY\t-\t0\t-\t1000\t"]);
+
+        let merge = a.merge(&b);
+        let columns: Vec<&str> = merge.conflicts.iter().map(|c| c.column.as_str()).collect();
+        assert_eq!(columns, ["Title", "SPA Rating"]);
+        assert_eq!(merge.conflicts[1].kept, "12");
+        assert_eq!(merge.conflicts[1].other, "11");
+        assert_eq!(merge.tracker.get("1000", "Title"), Some("Song A"));
+    }
+
+    #[test]
+    fn test_merge_matches_columns_by_name() {
+        let a = Tracker::parse("Song ID\tTitle\tSPA EX Score\n1000\tSong A\t1500\n");
+        let b = Tracker::parse("Song ID\tSPA EX Score\tTitle\tSPA Plays\n1000\t1700\tSong A\t4\n");
+
+        let merge = a.merge(&b);
+        assert!(merge.conflicts.is_empty());
+        assert_eq!(
+            merge.tracker.columns(),
+            ["Song ID", "Title", "SPA EX Score", "SPA Plays"]
+        );
+        assert_eq!(merge.tracker.get("1000", "SPA EX Score"), Some("1700"));
+        assert_eq!(merge.tracker.get("1000", "SPA Plays"), Some("4"));
+    }
+
+    #[test]
+    fn test_to_tsv_roundtrip() {
+        let a = tracker(&["1000\tSong A\tBits\t\t\tTRUE\t12\tHARD\tA\t1400\t30\t1000\t"]);
+        let tsv = a.to_tsv();
+        assert!(tsv.starts_with(&FileFormat::TrackerTsv.marker()));
+        assert_eq!(Tracker::parse(&tsv), a);
+    }
+}
@@ -3,6 +3,7 @@
 use crate::play::PlayData;
 
 use super::format::ExportFormat;
+use super::timezone::DisplayTimezone;
 
 /// TSV (Tab-Separated Values) exporter
 #[derive(Debug, Clone, Copy, Default)]
@@ -88,13 +89,32 @@ pub fn format_full_tsv_header() -> String {
         "assist",
         "range",
         "date",
+        "nextgrade",
+        "nextgradegap",
+        "scorerate",
+        "bpi",
+        "premature",
+        "failedat",
+        "sudden",
+        "hidden",
+        "lift",
+        "greennumber",
     ];
 
     columns.join("\t")
 }
 
-/// Generate TSV row with all columns
+/// Generate TSV row with all columns, dated in the local timezone
 pub fn format_full_tsv_row(play_data: &PlayData) -> String {
+    format_full_tsv_row_in(play_data, DisplayTimezone::Local)
+}
+
+/// Generate TSV row with all columns, dated in `timezone`
+///
+/// The `date` column is RFC 3339 with the offset, so it still identifies
+/// the same instant when read back.
+pub fn format_full_tsv_row_in(play_data: &PlayData, timezone: DisplayTimezone) -> String {
+    let next_grade = play_data.next_grade_gap();
     let values: Vec<String> = vec![
         play_data.chart.title.to_string(),
         play_data.chart.difficulty.short_name().to_string(),
@@ -130,12 +150,37 @@ pub fn format_full_tsv_row(play_data: &PlayData) -> String {
             .to_string(),
         play_data.settings.assist.as_str().to_string(),
         play_data.settings.range.as_str().to_string(),
-        play_data.timestamp.to_rfc3339(),
+        timezone.to_rfc3339(&play_data.timestamp),
+        next_grade
+            .map(|(grade, _)| grade.short_name().to_string())
+            .unwrap_or_default(),
+        next_grade
+            .map(|(_, gap)| gap.to_string())
+            .unwrap_or_default(),
+        format!("{:.2}", play_data.score_rate() * 100.0),
+        play_data
+            .bpi
+            .map(|bpi| format!("{:.2}", bpi))
+            .unwrap_or_default(),
+        u8::from(play_data.is_premature()).to_string(),
+        play_data
+            .failed_at_percent
+            .map(|percent| format!("{:.1}", percent))
+            .unwrap_or_default(),
+        optional(play_data.settings.sudden()),
+        optional(play_data.settings.hidden()),
+        optional(play_data.settings.lift()),
+        optional(play_data.settings.green_number()),
     ];
 
     values.join("\t")
 }
 
+/// Cell for an optional value (empty when unknown)
+fn optional(value: Option<u16>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
 /// Format simple TSV row from TsvRowData
 pub fn format_tsv_row(data: &TsvRowData) -> String {
     format!(
@@ -180,6 +225,11 @@ mod tests {
         assert!(header.contains("notecount"));
         assert!(header.contains("exscore"));
         assert!(header.contains("date"));
+        assert!(header.contains("nextgradegap"));
+        assert!(header.contains("scorerate"));
+        assert!(header.contains("bpi"));
+        assert!(header.contains("premature\tfailedat"));
+        assert!(header.ends_with("sudden\thidden\tlift\tgreennumber"));
     }
 
     #[test]
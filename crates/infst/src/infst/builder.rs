@@ -0,0 +1,402 @@
+//! Builder for [`Infst`]
+//!
+//! Collects offsets, configuration, storage paths, API settings, preloaded
+//! game data and play handlers, and validates them once in
+//! [`InfstBuilder::build`] instead of leaving embedders to poke fields after
+//! construction.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::chart::SongInfo;
+use crate::clock::SharedClock;
+use crate::error::{Error, Result};
+use crate::offset::OffsetsCollection;
+use crate::paths::Paths;
+use crate::play::PlayData;
+use crate::score::ScoreMap;
+use crate::session::ExportAction;
+
+use super::{ApiConfig, Infst, InfstConfig};
+
+/// Callback invoked for every recorded play (after it is saved to the session)
+pub type PlayHandler = Box<dyn FnMut(&PlayData) + Send>;
+
+/// Callback for scheduled uploads, given the freshly written tracker file
+/// and the title mapping of the job; an error leaves the job unrecorded so
+/// it is retried
+pub type UploadHandler = Box<dyn FnMut(&Path, &Path) -> Result<()> + Send>;
+
+/// Asked before the first lamp submission of a session, given the exact
+/// JSON body; returns whether lamps may be sent for the rest of the session
+pub type ConsentHandler = Box<dyn FnMut(&str) -> bool + Send>;
+
+/// Builder for [`Infst`]
+///
+/// Explicit storage paths and API settings override the ones in
+/// [`Self::config`], whichever order they are set in; [`Self::session_dir`]
+/// and [`Self::tracker_path`] in turn override [`Self::paths`].
+#[derive(Default)]
+pub struct InfstBuilder {
+    offsets: Option<OffsetsCollection>,
+    config: Option<InfstConfig>,
+    paths: Option<Paths>,
+    session_dir: Option<PathBuf>,
+    tracker_path: Option<PathBuf>,
+    api_config: Option<ApiConfig>,
+    song_db: Option<HashMap<u32, SongInfo>>,
+    score_map: Option<ScoreMap>,
+    play_handlers: Vec<PlayHandler>,
+    upload_handler: Option<UploadHandler>,
+    #[cfg_attr(not(feature = "network"), allow(dead_code))]
+    consent_handler: Option<ConsentHandler>,
+    clock: Option<SharedClock>,
+}
+
+impl InfstBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Offsets to start with (defaults to unresolved offsets)
+    pub fn offsets(mut self, offsets: OffsetsCollection) -> Self {
+        self.offsets = Some(offsets);
+        self
+    }
+
+    /// Base configuration (defaults to [`InfstConfig::default`])
+    pub fn config(mut self, config: InfstConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Keep the session directory and tracker file under `paths`
+    pub fn paths(mut self, paths: &Paths) -> Self {
+        self.paths = Some(paths.clone());
+        self
+    }
+
+    /// Directory for session files
+    pub fn session_dir<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.session_dir = Some(path.into());
+        self
+    }
+
+    /// Path of the auto-exported tracker file
+    pub fn tracker_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.tracker_path = Some(path.into());
+        self
+    }
+
+    /// Send lamps to this web API
+    pub fn api(mut self, api_config: ApiConfig) -> Self {
+        self.api_config = Some(api_config);
+        self
+    }
+
+    /// Preloaded song database
+    pub fn song_db(mut self, song_db: HashMap<u32, SongInfo>) -> Self {
+        self.song_db = Some(song_db);
+        self
+    }
+
+    /// Preloaded score map
+    pub fn score_map(mut self, score_map: ScoreMap) -> Self {
+        self.score_map = Some(score_map);
+        self
+    }
+
+    /// Call `handler` for every recorded play; handlers run in registration order
+    pub fn on_play(mut self, handler: impl FnMut(&PlayData) + Send + 'static) -> Self {
+        self.play_handlers.push(Box::new(handler));
+        self
+    }
+
+    /// Perform the `upload` scheduled exports with `handler`
+    ///
+    /// The library has no uploader of its own; scheduling an upload without
+    /// a handler is rejected by [`Self::build`].
+    pub fn on_upload(
+        mut self,
+        handler: impl FnMut(&Path, &Path) -> Result<()> + Send + 'static,
+    ) -> Self {
+        self.upload_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Ask `handler` before the first lamp submission of each session
+    ///
+    /// Only used when [`InfstConfig::api_consent`] is off; without either,
+    /// lamps are never sent.
+    pub fn on_submission_consent(
+        mut self,
+        handler: impl FnMut(&str) -> bool + Send + 'static,
+    ) -> Self {
+        self.consent_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Time source for play timestamps, session files and dated snapshots
+    /// (defaults to the system clock)
+    pub fn clock(mut self, clock: SharedClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Validate the settings and create the tracker
+    pub fn build(self) -> Result<Infst> {
+        let mut config = self.config.unwrap_or_default();
+        if let Some(paths) = &self.paths {
+            config.session_dir = paths.sessions();
+            config.tracker_path = paths.tracker();
+        }
+        if let Some(session_dir) = self.session_dir {
+            config.session_dir = session_dir;
+        }
+        if let Some(tracker_path) = self.tracker_path {
+            config.tracker_path = tracker_path;
+        }
+        if let Some(api_config) = self.api_config {
+            config.api_config = Some(api_config);
+        }
+        validate(&config)?;
+        if self.upload_handler.is_none()
+            && config
+                .schedule
+                .iter()
+                .any(|job| matches!(job.action, ExportAction::Upload { .. }))
+        {
+            return Err(Error::invalid_config(
+                "schedule",
+                "upload exports need an upload handler (InfstBuilder::on_upload)",
+            ));
+        }
+
+        let mut infst = Infst::with_config(self.offsets.unwrap_or_default(), config);
+        if let Some(song_db) = self.song_db {
+            infst.game_data.song_db = song_db;
+        }
+        if let Some(score_map) = self.score_map {
+            infst.replace_score_map(score_map);
+        }
+        infst.play_handlers = self.play_handlers;
+        infst.upload_handler = self.upload_handler;
+        #[cfg(feature = "network")]
+        {
+            infst.consent_handler = self.consent_handler;
+        }
+        if let Some(clock) = self.clock {
+            infst.session_manager = infst.session_manager.with_clock(Arc::clone(&clock));
+            infst.clock = clock;
+        }
+        Ok(infst)
+    }
+}
+
+/// Reject settings that would only fail later, mid-session
+fn validate(config: &InfstConfig) -> Result<()> {
+    if config.session_dir.exists() && !config.session_dir.is_dir() {
+        return Err(Error::invalid_config(
+            "session_dir",
+            format!("{} is not a directory", config.session_dir.display()),
+        ));
+    }
+    if config.tracker_path.is_dir() {
+        return Err(Error::invalid_config(
+            "tracker_path",
+            format!("{} is a directory", config.tracker_path.display()),
+        ));
+    }
+    if let Some(api) = &config.api_config {
+        if !(api.endpoint.starts_with("http://") || api.endpoint.starts_with("https://")) {
+            return Err(Error::invalid_config(
+                "api_config",
+                format!("endpoint {:?} is not an http(s) URL", api.endpoint),
+            ));
+        }
+        if api.token.trim().is_empty() {
+            return Err(Error::invalid_config("api_config", "token is empty"));
+        }
+    }
+    if config.api_batch_size == 0 {
+        return Err(Error::invalid_config(
+            "api_batch_size",
+            "batch size must be at least 1",
+        ));
+    }
+    if config.max_reads_per_sec == Some(0) {
+        return Err(Error::invalid_config(
+            "max_reads_per_sec",
+            "limit must be at least 1",
+        ));
+    }
+    for job in &config.schedule {
+        if let ExportAction::Upload { mapping } = &job.action
+            && !mapping.is_file()
+        {
+            return Err(Error::invalid_config(
+                "schedule",
+                format!("{}: {} does not exist", job, mapping.display()),
+            ));
+        }
+    }
+    #[cfg(feature = "stream")]
+    if let Err(e) = crate::stream::MarqueeTemplate::parse(&config.clipboard_template) {
+        return Err(Error::invalid_config("clipboard_template", e.to_string()));
+    }
+    #[cfg(feature = "notify")]
+    if let Some(push) = &config.push {
+        push.validate()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_applies_overrides_and_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut song_db = HashMap::new();
+        song_db.insert(1000, SongInfo::default());
+
+        let infst = InfstBuilder::new()
+            .config(InfstConfig {
+                auto_export: false,
+                ..InfstConfig::default()
+            })
+            .session_dir(dir.path().join("sessions"))
+            .tracker_path(dir.path().join("tracker.tsv"))
+            .song_db(song_db)
+            .on_play(|_| {})
+            .build()
+            .unwrap();
+
+        assert_eq!(infst.config().session_dir, dir.path().join("sessions"));
+        assert_eq!(infst.config().tracker_path, dir.path().join("tracker.tsv"));
+        assert!(!infst.config().auto_export);
+        assert_eq!(infst.game_data.song_db.len(), 1);
+        assert_eq!(infst.play_handlers.len(), 1);
+    }
+
+    #[test]
+    fn test_paths_with_explicit_override() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = Paths::in_dir(dir.path());
+        let infst = InfstBuilder::new()
+            .session_dir(dir.path().join("other"))
+            .paths(&paths)
+            .build()
+            .unwrap();
+
+        assert_eq!(infst.config().session_dir, dir.path().join("other"));
+        assert_eq!(infst.config().tracker_path, paths.tracker());
+    }
+
+    #[test]
+    fn test_clock_dates_tracker_snapshots() {
+        use crate::clock::MockClock;
+        use chrono::{Local, TimeZone, Utc};
+
+        let now = Local.with_ymd_and_hms(2026, 2, 3, 12, 0, 0).unwrap();
+        let infst = InfstBuilder::new()
+            .config(InfstConfig {
+                tracker_snapshots: true,
+                ..InfstConfig::default()
+            })
+            .tracker_path("out/tracker.tsv")
+            .clock(Arc::new(MockClock::new(now.with_timezone(&Utc))))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            infst.tracker_export_path(),
+            PathBuf::from("out/tracker-2026-02-03.tsv")
+        );
+    }
+
+    #[test]
+    fn test_missing_optional_data_files_are_skipped() {
+        // Missing BPI/tournament files only disable those features, as with
+        // `Infst::with_config`
+        let dir = tempfile::tempdir().unwrap();
+        let infst = InfstBuilder::new()
+            .config(InfstConfig {
+                bpi_file: Some(dir.path().join("missing-bpi.json")),
+                tournament_file: Some(dir.path().join("missing-tournament.json")),
+                ..InfstConfig::default()
+            })
+            .session_dir(dir.path().join("sessions"))
+            .build()
+            .unwrap();
+
+        assert!(infst.game_data.bpi_table.is_none());
+        assert!(infst.tournament.is_none());
+    }
+
+    #[test]
+    fn test_build_rejects_invalid_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let field = |builder: InfstBuilder| match builder
+            .session_dir(dir.path().join("sessions"))
+            .build()
+        {
+            Err(Error::InvalidConfig { field, .. }) => field,
+            Err(e) => panic!("unexpected error: {e}"),
+            Ok(_) => panic!("expected an error"),
+        };
+
+        let api = |endpoint: &str, token: &str| ApiConfig {
+            endpoint: endpoint.to_string(),
+            token: token.to_string(),
+        };
+        assert_eq!(
+            field(InfstBuilder::new().api(api("example.com", "t"))),
+            "api_config"
+        );
+        assert_eq!(
+            field(InfstBuilder::new().api(api("https://example.com", " "))),
+            "api_config"
+        );
+        assert_eq!(
+            field(InfstBuilder::new().tracker_path(dir.path())),
+            "tracker_path"
+        );
+        assert_eq!(
+            field(InfstBuilder::new().config(InfstConfig {
+                max_reads_per_sec: Some(0),
+                ..InfstConfig::default()
+            })),
+            "max_reads_per_sec"
+        );
+        #[cfg(feature = "stream")]
+        assert_eq!(
+            field(InfstBuilder::new().config(InfstConfig {
+                clipboard_template: "{title} {unknown}".to_string(),
+                ..InfstConfig::default()
+            })),
+            "clipboard_template"
+        );
+
+        let mapping = dir.path().join("mapping.json");
+        let upload = InfstConfig::builder()
+            .scheduled_export(
+                format!("upload:{}@session-end", mapping.display())
+                    .parse()
+                    .unwrap(),
+            )
+            .build();
+        assert_eq!(
+            field(
+                InfstBuilder::new()
+                    .config(upload.clone())
+                    .on_upload(|_, _| Ok(()))
+            ),
+            "schedule"
+        );
+        std::fs::write(&mapping, "{}").unwrap();
+        assert_eq!(field(InfstBuilder::new().config(upload)), "schedule");
+    }
+}
@@ -2,78 +2,89 @@
 //!
 //! This module contains the main tracking loop and game state handling methods.
 
+use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use chrono::Utc;
 use tracing::{debug, error, info, warn};
 
 use crate::chart::{
-    ChartInfo, Difficulty, fetch_song_by_id, fetch_song_database_from_memory_scan,
-    get_unlock_states,
+    ChartId, ChartInfo, Difficulty, fetch_song_by_id_with_layout,
+    fetch_song_database_from_memory_scan_with_layout, get_unlock_states,
 };
-use crate::config::{check_version_match, find_game_version, polling, retry};
+use crate::config::{check_version_match, find_game_version, polling};
 use crate::error::Result;
 use crate::export::format_play_data_console;
-use crate::play::{AssistType, GameState, PlayData, PlayType, RawSettings, Settings};
+use crate::input::clipboard;
+use crate::offset::{OffsetSearcher, pin_song_anchors, song_list_moved};
+#[cfg(feature = "network")]
+use crate::paths::SUBMISSION_QUEUE_FILE;
+use crate::play::{
+    AnomalyMode, AssistType, GameState, PlayData, PlayType, PrematureMode, Settings, SettingsBlock,
+    read_lane_cover, read_profile,
+};
 use crate::process::layout::{judge, play, settings, timing};
-use crate::process::{MemoryReader, ProcessHandle, ReadMemory};
-use crate::score::{Grade, Judge, Lamp, PlayerJudge, RawJudgeData, ScoreMap};
-
-use super::Infst;
-
-/// Read a value from memory with a default on error.
+use crate::process::{GameProcess, ModuleFingerprint, ReadMemory, ReadOnlyMemory};
+use crate::retry::RetryPolicy;
+use crate::score::{ASSISTED_BESTS_FILE, Grade, Judge, Lamp, RawJudgeData, ScoreMap};
+use crate::session::{ExportScheduler, PauseMode, SessionCommand};
+use crate::stats::{PLAY_COUNTS_FILE, notes_hit};
+#[cfg(feature = "stream")]
+use crate::stream::marquee_values;
+
+#[cfg(feature = "network")]
+use super::submission::{LampSubmission, LampSubmitter, SubmissionOptions};
+use super::watchdog::ReadWatchdog;
+use super::{Infst, TrackerExit};
+
+/// Fingerprint the game module currently loaded in the process.
 ///
-/// This helper simplifies error handling for non-critical reads.
-fn read_with_default<T, F>(f: F, default: T, context: &str) -> T
-where
-    F: FnOnce() -> Result<T>,
-{
-    match f() {
-        Ok(v) => v,
-        Err(e) => {
-            warn!("Failed to read {}: {}", context, e);
-            default
-        }
-    }
+/// The module list is re-queried so a replaced module is noticed even though
+/// `process.base_address` is cached.
+fn read_module_fingerprint<R: ReadMemory, P: GameProcess>(
+    reader: &R,
+    process: &P,
+) -> Result<ModuleFingerprint> {
+    let base_address = process.query_module_base()?;
+    ModuleFingerprint::read(reader, base_address)
 }
 
 /// Check if memory is accessible with retry logic.
 ///
-/// Uses exponential backoff and checks process liveness between retries.
-fn verify_memory_access(reader: &MemoryReader, process: &ProcessHandle) -> bool {
-    for attempt in 0..retry::MAX_READ_RETRIES {
-        match reader.read_bytes(process.base_address, 4) {
-            Ok(_) => return true,
+/// Retries under `policy` (exponential backoff by default) and checks process
+/// liveness between retries. Returns the last read error if memory stays
+/// inaccessible.
+fn verify_memory_access<R: ReadMemory, P: GameProcess>(
+    reader: &R,
+    process: &P,
+    policy: &RetryPolicy,
+) -> Result<()> {
+    let mut attempt = 0u32;
+    loop {
+        match reader.read_bytes(process.base_address(), 4) {
+            Ok(_) => return Ok(()),
             Err(e) => {
                 // Re-check process status before retrying
                 if !process.is_alive() {
                     debug!("Process terminated during retry: {}", e);
-                    return false;
+                    return Err(e);
                 }
 
-                if attempt < retry::MAX_READ_RETRIES - 1 {
-                    let delay = retry::RETRY_DELAYS_MS[attempt as usize];
-                    debug!(
-                        "Memory read failed (attempt {}/{}, retry in {}ms): {}",
-                        attempt + 1,
-                        retry::MAX_READ_RETRIES,
-                        delay,
-                        e
-                    );
-                    thread::sleep(Duration::from_millis(delay));
-                } else {
-                    debug!(
-                        "Memory read failed after {} retries: {}",
-                        retry::MAX_READ_RETRIES,
-                        e
-                    );
+                attempt += 1;
+                if !policy.allows_attempt(attempt) {
+                    debug!("Memory read failed after {} retries: {}", attempt, e);
+                    return Err(e);
                 }
+                let delay = policy.delay_after(attempt - 1);
+                debug!(
+                    "Memory read failed (attempt {}, retry in {:?}): {}",
+                    attempt, delay, e
+                );
+                thread::sleep(delay);
             }
         }
     }
-    false
 }
 
 impl Infst {
@@ -81,86 +92,290 @@ impl Infst {
     ///
     /// The `shutdown_requested` flag is checked each iteration to allow graceful shutdown via Ctrl+C.
     /// When `shutdown_requested` is `true`, the loop exits.
-    pub fn run(&mut self, process: &ProcessHandle, shutdown_requested: &AtomicBool) -> Result<()> {
-        let reader = MemoryReader::new(process);
+    ///
+    /// The game module is fingerprinted at startup and re-checked periodically;
+    /// if it changes (the game updated itself), the session is closed and
+    /// [`TrackerExit::GameUpdated`] is returned so the caller can re-detect offsets.
+    pub fn run<P: GameProcess>(
+        &mut self,
+        process: &P,
+        shutdown_requested: &AtomicBool,
+    ) -> Result<TrackerExit> {
+        // Everything below only sees the read-only view of the game
+        let reader = ReadOnlyMemory::new(process.reader(&self.read_policy));
         let mut last_state = GameState::Unknown;
+        let mut exit = TrackerExit::ProcessExited;
+        let mut failure = None;
+
+        let module_fingerprint = match read_module_fingerprint(&reader, process) {
+            Ok(fingerprint) => {
+                debug!("Game module: {}", fingerprint);
+                Some(fingerprint)
+            }
+            Err(e) => {
+                debug!(
+                    "Could not fingerprint game module, update detection disabled: {}",
+                    e
+                );
+                None
+            }
+        };
+        let mut last_module_check = Instant::now();
 
         debug!("Starting tracker loop...");
 
+        #[cfg(feature = "stream")]
+        {
+            let counts = self.play_counts();
+            if let Some(stream) = &mut self.stream_output {
+                stream.set_play_counts(counts);
+            }
+        }
+
+        // Read player profile (optional offset)
+        if self.offsets.profile != 0 {
+            match read_profile(&reader, self.offsets.profile) {
+                Ok(profile) => {
+                    info!("{}", profile.summary());
+                    self.game_data.profile = Some(profile);
+                }
+                Err(e) => warn!("Failed to read profile: {}", e),
+            }
+        }
+
         // Start TSV session
-        self.session_manager = crate::session::SessionManager::new("sessions");
+        self.session_manager = crate::session::SessionManager::new(&self.config.session_dir)
+            .with_clock(Arc::clone(&self.clock))
+            .with_timezone(self.config.display_timezone)
+            .with_encoding(self.config.tsv_encoding)
+            .with_dry_run(self.config.dry_run);
+        self.session_manager
+            .set_profile(self.game_data.profile.clone());
         match self.session_manager.start_tsv_session() {
-            Ok(path) => debug!("Started TSV session at {:?}", path),
+            Ok(path) => info!("Session started: {}", path.display()),
             Err(e) => warn!("Failed to start TSV session: {}", e),
         }
+        // Initial table, so overlays have something to show before the first play
+        self.write_standings(false);
+        // Consent to lamp submissions is asked again in every session
+        #[cfg(feature = "network")]
+        {
+            self.submission_consent = None;
+        }
+
+        if self.config.dry_run && !self.config.schedule.is_empty() {
+            info!("Scheduled exports disabled in dry-run mode");
+        } else if self.scheduler.is_none() && !self.config.schedule.is_empty() {
+            let scheduler = ExportScheduler::new(
+                self.config.schedule.clone(),
+                &self.config.session_dir,
+                self.clock.now_local().naive_local(),
+            );
+            for entry in scheduler.entries() {
+                match entry.next_run {
+                    Some(next) => info!("Scheduled export {} (next run {})", entry.job, next),
+                    None => info!("Scheduled export {}", entry.job),
+                }
+            }
+            if let Err(e) = scheduler.save() {
+                warn!("Failed to write export schedule: {}", e);
+            }
+            self.scheduler = Some(scheduler);
+        }
+
+        let mut watchdog = ReadWatchdog::new();
+        // Replacement handle opened by the watchdog after a read failure storm
+        let mut reopened: Option<P> = None;
 
         loop {
             // Check for shutdown signal
             if shutdown_requested.load(Ordering::SeqCst) {
                 debug!("Shutdown signal received, exiting tracker loop");
+                exit = TrackerExit::Shutdown;
                 break;
             }
 
+            self.apply_session_commands();
+
+            let active = reopened.as_ref().unwrap_or(process);
+            let reader = ReadOnlyMemory::new(active.reader(&self.read_policy));
+
             // Step 1: Fast check if process is still alive via exit code
-            if !process.is_alive() {
+            if !active.is_alive() {
                 debug!("Process terminated (exit code check)");
                 break;
             }
 
-            // Step 2: Verify memory access with retry mechanism (exponential backoff)
-            if !verify_memory_access(&reader, process) {
-                break;
-            }
+            // Step 2: Verify memory access with retry mechanism (exponential backoff),
+            // then detect game state. Failures while the process is alive (minimized
+            // window, stale handle) are handed to the watchdog instead of ending the loop.
+            let detected = verify_memory_access(&reader, active, &self.config.retry.memory_read)
+                .and_then(|()| self.detect_game_state(&reader));
+            let current_state = match detected {
+                Ok(state) => {
+                    watchdog.record_success();
+                    state
+                }
+                Err(e) => {
+                    if !active.is_alive() {
+                        debug!("Process terminated during read failure: {}", e);
+                        break;
+                    }
+                    watchdog.record_failure("game state", &e);
+                    if watchdog.should_reopen() {
+                        // The reader borrows the handle being replaced
+                        drop(reader);
+                        match process.reopen() {
+                            Ok(handle) => {
+                                info!(
+                                    "Reopened process handle after {} failed polls",
+                                    watchdog.consecutive_failures()
+                                );
+                                reopened = Some(handle);
+                            }
+                            Err(e) => debug!("Failed to reopen process handle: {}", e),
+                        }
+                    }
+                    thread::sleep(watchdog.backoff());
+                    continue;
+                }
+            };
 
-            // Detect game state
-            let current_state = self.detect_game_state(&reader)?;
+            // Step 3: Periodically check whether the game updated itself
+            if let Some(expected) = module_fingerprint
+                && last_module_check.elapsed()
+                    >= Duration::from_millis(timing::MODULE_CHECK_INTERVAL_MS)
+            {
+                last_module_check = Instant::now();
+                match read_module_fingerprint(&reader, active) {
+                    Ok(current) if current != expected => {
+                        warn!(
+                            "Game module changed ({} -> {}), ending session",
+                            expected, current
+                        );
+                        exit = TrackerExit::GameUpdated;
+                        break;
+                    }
+                    Ok(_) => {}
+                    Err(e) => debug!("Module fingerprint check failed: {}", e),
+                }
+            }
 
             if current_state != last_state {
                 debug!("State changed: {:?} -> {:?}", last_state, current_state);
-                self.handle_state_change(&reader, last_state, current_state)?;
+                if let Err(e) = self.handle_state_change(&reader, last_state, current_state) {
+                    failure = Some(e);
+                    break;
+                }
                 last_state = current_state;
             }
 
+            // Exports wait until the chart is over
+            if current_state != GameState::Playing {
+                self.run_scheduled_exports(false);
+            }
+
             thread::sleep(Duration::from_millis(timing::GAME_STATE_POLL_INTERVAL_MS));
         }
 
-        Ok(())
+        // Close out the session on every exit, errors included, so the
+        // results and the audit log are still written
+        self.finish_session();
+        if let Some(e) = failure {
+            error!("Session ended with an error: {}", e);
+            return Err(e);
+        }
+        info!("Session ended ({:?})", exit);
+
+        Ok(exit)
     }
 
-    fn detect_game_state(&mut self, reader: &MemoryReader) -> Result<GameState> {
-        let state_marker_1 = read_with_default(
-            || reader.read_i32(self.offsets.judge_data + judge::STATE_MARKER_1),
-            0,
-            "state_marker_1",
-        );
-        let state_marker_2 = read_with_default(
-            || reader.read_i32(self.offsets.judge_data + judge::STATE_MARKER_2),
-            0,
-            "state_marker_2",
-        );
-        let song_select_marker = read_with_default(
-            || {
-                reader.read_i32(
-                    self.offsets
-                        .play_settings
-                        .wrapping_sub(settings::SONG_SELECT_MARKER),
-                )
-            },
-            0,
-            "song_select_marker",
-        );
+    /// Close out the session: record the open course and set, print the
+    /// summary, run the session-end exports and write the audit log
+    ///
+    /// [`run`](Self::run) does this whenever the loop ends, including when
+    /// it returns an error; call it after the loop panicked so the session's results are still written.
+    pub fn finish_session(&mut self) {
+        self.finish_play_time();
+        self.finish_course();
+        if let Err(e) = self.session_manager.close_set() {
+            error!("Failed to write set result: {}", e);
+        }
+        self.apply_session_commands();
+        if let Some(summary) = self.session_manager.summary() {
+            info!("{}", summary);
+            println!("{}", summary);
+            let counts = self.play_counts();
+            info!("{}", counts);
+            println!("{}", counts);
+        }
+        #[cfg(feature = "render")]
+        if self.config.summary_image
+            && let Err(e) = self.save_summary_image()
+        {
+            warn!("Failed to save session summary image: {}", e);
+        }
+        self.run_scheduled_exports(true);
+        #[cfg(feature = "network")]
+        if let Some(submitter) = self.lamp_submitter.take() {
+            submitter.finish();
+        }
+        if let Err(e) = self.write_audit_log() {
+            warn!("Failed to write audit log: {}", e);
+        }
+    }
+
+    /// Run the scheduled exports that are due, and the session-end ones
+    /// when the session is over
+    fn run_scheduled_exports(&mut self, session_end: bool) {
+        let Some(scheduler) = &mut self.scheduler else {
+            return;
+        };
+        let now = self.clock.now_local().naive_local();
+        let mut jobs = scheduler.due(now);
+        if session_end {
+            jobs.extend(scheduler.session_end());
+        }
+        for job in jobs {
+            info!("Running scheduled export {}", job);
+            let result = self.run_export_action(&job.action);
+            let Some(scheduler) = &mut self.scheduler else {
+                return;
+            };
+            match result {
+                Ok(()) => scheduler.record_run(&job, now),
+                Err(e) => {
+                    error!("Scheduled export {} failed: {}", job, e);
+                    scheduler.retry_later(&job, now);
+                }
+            }
+        }
+    }
+
+    fn detect_game_state<R: ReadMemory>(&mut self, reader: &R) -> Result<GameState> {
+        // The two markers are adjacent words
+        let [state_marker_1, state_marker_2]: [i32; 2] =
+            reader.read_struct(self.offsets.judge_data + judge::STATE_MARKER_1)?;
+        let song_select_marker = reader.read_i32(
+            self.offsets
+                .play_settings
+                .wrapping_sub(settings::SONG_SELECT_MARKER),
+        )?;
 
         Ok(self
             .state_detector
             .detect(state_marker_1, state_marker_2, song_select_marker))
     }
 
-    fn handle_state_change(
+    fn handle_state_change<R: ReadMemory>(
         &mut self,
-        reader: &MemoryReader,
-        _old_state: GameState,
+        reader: &R,
+        old_state: GameState,
         new_state: GameState,
     ) -> Result<()> {
+        if old_state == GameState::Playing {
+            self.finish_play_time();
+        }
         match new_state {
             GameState::ResultScreen => self.handle_result_screen(reader),
             GameState::SongSelect => self.handle_song_select(reader),
@@ -171,7 +386,7 @@ impl Infst {
     }
 
     /// Handle transition to result screen
-    fn handle_result_screen(&mut self, reader: &MemoryReader) {
+    fn handle_result_screen<R: ReadMemory>(&mut self, reader: &R) {
         info!("Detected result screen, waiting for data...");
 
         // Initial delay to allow game data to settle (matching C# implementation)
@@ -193,10 +408,7 @@ impl Infst {
 
                     // Validate song_id and difficulty match current_playing (if available)
                     let chart_valid = match self.current_playing {
-                        Some((expected_id, expected_diff)) => {
-                            play_data.chart.song_id == expected_id
-                                && play_data.chart.difficulty == expected_diff
-                        }
+                        Some(expected) => play_data.chart.id() == expected,
                         None => true, // No reference, accept any
                     };
 
@@ -254,58 +466,390 @@ impl Infst {
         self.current_playing = None;
     }
 
+    /// Apply pause/resume/annotate commands queued by `infst session`
+    fn apply_session_commands(&mut self) {
+        let commands = match self.session_control.take() {
+            Ok(commands) => commands,
+            Err(e) => {
+                warn!("Failed to read session commands: {}", e);
+                return;
+            }
+        };
+        for command in commands {
+            let result = match &command {
+                SessionCommand::Pause => self.session_manager.pause().map(|changed| {
+                    if changed {
+                        info!("Session paused");
+                    }
+                }),
+                SessionCommand::Resume => self.session_manager.resume().map(|changed| {
+                    if changed {
+                        info!("Session resumed");
+                    }
+                }),
+                SessionCommand::Annotate { text } => self
+                    .session_manager
+                    .annotate(text)
+                    .map(|()| info!("Note: {}", text)),
+                SessionCommand::Copy => {
+                    self.copy_last_play();
+                    Ok(())
+                }
+                SessionCommand::Export => {
+                    let path = self.tracker_export_path();
+                    if self.config.dry_run {
+                        self.write_tracker(&path)
+                    } else {
+                        self.export_tracker_tsv(&path)
+                            .map(|()| info!("Exported tracker to {}", path.display()))
+                    }
+                }
+                SessionCommand::TogglePause => {
+                    if self.session_manager.is_paused() {
+                        self.session_manager
+                            .resume()
+                            .map(|_| info!("Session resumed"))
+                    } else {
+                        self.session_manager
+                            .pause()
+                            .map(|_| info!("Session paused"))
+                    }
+                }
+                SessionCommand::ToggleMarquee => self.toggle_marquee(),
+            };
+            if let Err(e) = result {
+                warn!("Failed to apply session command {:?}: {}", command, e);
+            }
+        }
+    }
+
+    /// Blank or restore the stream marquee
+    #[cfg(feature = "stream")]
+    fn toggle_marquee(&mut self) -> Result<()> {
+        let Some(stream) = &mut self.stream_output else {
+            println!("Stream output is not enabled");
+            return Ok(());
+        };
+        let hidden = stream.toggle_marquee()?;
+        println!("Marquee {}", if hidden { "hidden" } else { "shown" });
+        Ok(())
+    }
+
+    #[cfg(not(feature = "stream"))]
+    fn toggle_marquee(&mut self) -> Result<()> {
+        warn!("Marquee toggling requires the `stream` feature");
+        Ok(())
+    }
+
+    /// Copy the last play summary to the clipboard
+    fn copy_last_play(&self) {
+        let Some(summary) = &self.last_play_summary else {
+            println!("No play to copy yet");
+            return;
+        };
+        match clipboard::set_text(summary) {
+            Ok(()) => println!("Copied: {}", summary),
+            Err(e) => warn!("Failed to copy the last play: {}", e),
+        }
+    }
+
     /// Process and save play result data
     fn process_play_result(&mut self, play_data: &PlayData) {
-        // Get personal best for comparison
-        let personal_best = self.game_data.score_map.get(play_data.chart.song_id);
+        if self.duplicate_guard.check(play_data) {
+            info!(
+                "Duplicate result for {} ({}) ignored",
+                play_data.chart.title, play_data.chart.song_id
+            );
+            self.session_manager.note_duplicate();
+            return;
+        }
+
+        let judge_only;
+        let play_data = match self.config.premature_mode {
+            PrematureMode::JudgeOnly if play_data.is_premature() => {
+                judge_only = play_data.judge_only();
+                &judge_only
+            }
+            _ => play_data,
+        };
+
+        // Get personal best for comparison (assisted plays against the
+        // assisted bests)
+        let assisted_best;
+        let personal_best = match self.game_data.score_map.get(play_data.chart.song_id) {
+            Some(best) if play_data.is_assisted() => {
+                assisted_best = best.assisted_view();
+                Some(&assisted_best)
+            }
+            best => best,
+        };
 
         // Print detailed play data to console (with PB comparison)
-        println!("{}", format_play_data_console(play_data, personal_best));
+        println!(
+            "{}",
+            format_play_data_console(play_data, personal_best, self.config.display_timezone)
+        );
+        #[cfg(feature = "stream")]
+        if let Some(template) = &self.clipboard_template {
+            self.last_play_summary = Some(template.render(&marquee_values(
+                play_data,
+                personal_best,
+                self.config.display_timezone,
+            )));
+        }
+
+        if play_data.is_premature() && self.config.premature_mode == PrematureMode::Ignore {
+            println!("(premature end, play not recorded)");
+            return;
+        }
+
+        let anomalies = play_data.validate();
+        if !anomalies.is_empty() {
+            let dropped = self.config.anomaly_mode == AnomalyMode::Drop;
+            let details: Vec<String> = anomalies.iter().map(ToString::to_string).collect();
+            warn!("Anomalous play result: {}", details.join("; "));
+            println!(
+                "(anomalous result: {}{})",
+                details.join("; "),
+                if dropped { ", play not recorded" } else { "" }
+            );
+            if let Err(e) = self
+                .session_manager
+                .flag_anomalies(play_data, &anomalies, dropped)
+            {
+                warn!("Failed to note anomalous play: {}", e);
+            }
+            if dropped {
+                return;
+            }
+        }
+
+        if self.session_manager.is_paused() && self.config.pause_mode == PauseMode::Ignore {
+            println!("(session paused, play not recorded)");
+            return;
+        }
+
+        let ignored = self.config.ignore.ignores_play(play_data);
+        if ignored {
+            println!("(ignored chart, not exported or submitted)");
+        }
+
+        // Plays today / this week
+        let date = self
+            .config
+            .display_timezone
+            .convert(&play_data.timestamp)
+            .date_naive();
+        self.play_counters.record(date, notes_hit(&play_data.judge));
+        if !self.config.dry_run
+            && let Err(e) = self
+                .play_counters
+                .save(self.config.session_dir.join(PLAY_COUNTS_FILE))
+        {
+            warn!("Failed to save play counts: {}", e);
+        }
+        #[cfg(feature = "stream")]
+        if let Some(stream) = &mut self.stream_output {
+            stream.set_play_counts(self.play_counters.counts(date));
+        }
+
+        // Update stream overlay
+        #[cfg(feature = "stream")]
+        if let Some(stream) = &mut self.stream_output
+            && let Err(e) = stream.write_play(play_data, personal_best)
+        {
+            warn!("Failed to write stream marquee: {}", e);
+        }
+
+        #[cfg(feature = "notify")]
+        if let Some(notifier) = &self.push_notifier
+            && !play_data.is_premature()
+            && !ignored
+        {
+            if self.config.dry_run {
+                for message in notifier.messages(play_data, personal_best) {
+                    println!("(dry run) would send push notification: {}", message);
+                }
+            } else {
+                notifier.notify(play_data, personal_best, self.config.retry.api);
+            }
+        }
+
+        self.session_manager
+            .record_improvement(play_data, personal_best);
+        self.record_assisted_best(play_data);
 
         // Save to session files
         self.save_session_data(play_data);
+        if ignored && let Err(e) = self.session_manager.flag_ignored(play_data) {
+            warn!("Failed to note ignored play: {}", e);
+        }
+        self.session_manager.record_notes_hit(play_data);
+        self.session_manager.record_green_number(play_data);
+        if let Some(baseline) = &mut self.green_number_baseline {
+            baseline.record(play_data);
+        }
+        self.update_tournament(play_data);
+        self.course_tracker.record_stage(play_data.clone());
+        if self.course_tracker.in_course() {
+            info!(
+                "Course stage {} recorded",
+                self.course_tracker.stage_count()
+            );
+        }
 
         // Send to API (non-blocking)
-        self.send_lamp_to_api(play_data);
+        if !ignored {
+            self.send_lamp_to_api(play_data);
+        }
+
+        for handler in &mut self.play_handlers {
+            handler(play_data);
+        }
+    }
+
+    /// Keep the best of an assisted play apart from the clean bests
+    fn record_assisted_best(&mut self, play_data: &PlayData) {
+        // Another player's plays stay out of the stored assisted bests
+        if self.profile_mismatch.is_some() || !self.assisted_bests.record(play_data) {
+            return;
+        }
+        let chart = play_data.chart.id();
+        if let Some(best) = self.assisted_bests.get(chart) {
+            self.game_data
+                .score_map
+                .get_or_insert(chart.song_id)
+                .record_assisted(chart.difficulty, best);
+        }
+        if !self.config.dry_run
+            && let Err(e) = self
+                .assisted_bests
+                .save(self.config.session_dir.join(ASSISTED_BESTS_FILE))
+        {
+            warn!("Failed to save assisted bests: {}", e);
+        }
+    }
+
+    /// Score a play for the tournament and refresh the standings file
+    fn update_tournament(&mut self, play_data: &PlayData) {
+        let Some(tournament) = &mut self.tournament else {
+            return;
+        };
+        if tournament.record_play(play_data) {
+            self.write_standings(true);
+        }
     }
 
-    /// Send lamp data to the API endpoint in a background thread
-    #[cfg(feature = "api")]
-    fn send_lamp_to_api(&self, play_data: &PlayData) {
-        let Some(ref api_config) = self.config.api_config else {
+    /// Write the tournament standings file, optionally printing the table
+    fn write_standings(&self, print_table: bool) {
+        let Some(tournament) = &self.tournament else {
             return;
         };
+        let standings = tournament.standings(self.clock.now());
+        if print_table {
+            for line in standings.format_table() {
+                println!("  {}", line);
+            }
+        }
+        if let Some(path) = &tournament.standings_path
+            && !self.config.dry_run
+            && let Err(e) = standings.write_json(path)
+        {
+            warn!("Failed to write tournament standings: {}", e);
+        }
+    }
+
+    /// Queue lamp data for the API endpoint (sent in batches by
+    /// [`LampSubmitter`], see [`super::submission`])
+    ///
+    /// The first payload of a session is shown (and written to
+    /// `InfstConfig::payload_preview`) before anything is sent; it is only
+    /// sent with `InfstConfig::api_consent` or the consent handler's approval.
+    #[cfg(feature = "network")]
+    fn send_lamp_to_api(&mut self, play_data: &PlayData) {
+        let Some(api_config) = self.config.api_config.clone() else {
+            return;
+        };
+
+        // Only level 11/12 charts are synced to the web API; a quit play's
+        // lamp is never sent.
+        if !matches!(play_data.chart.level, 11 | 12) || play_data.is_premature() {
+            return;
+        }
+
+        if self.config.dry_run {
+            println!(
+                "(dry run) would send lamp {} for {} [{}] to {}",
+                play_data.lamp.short_name(),
+                play_data.chart.title,
+                play_data.chart.difficulty.short_name(),
+                api_config.endpoint
+            );
+            return;
+        }
 
-        // Only level 11/12 charts are synced to the web API.
-        if !matches!(play_data.chart.level, 11 | 12) {
+        let lamp = LampSubmission {
+            song_id: play_data.chart.song_id,
+            difficulty: play_data.chart.difficulty.short_name().to_string(),
+            lamp: play_data.lamp.short_name().to_string(),
+            ex_score: play_data.ex_score,
+            miss_count: play_data.miss_count(),
+            dj_name: self.game_data.profile.as_ref().map(|p| p.dj_name.clone()),
+        };
+        let payload = match serde_json::to_string(&lamp) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to serialize lamp submission: {}", e);
+                return;
+            }
+        };
+        if !self.submission_allowed(&payload) {
             return;
         }
 
-        let endpoint = api_config.endpoint.clone();
-        let token = api_config.token.clone();
-        let song_id = play_data.chart.song_id;
-        let difficulty = play_data.chart.difficulty.short_name().to_string();
-        let lamp = play_data.lamp.short_name().to_string();
-        let ex_score = play_data.ex_score;
-        let miss_count = play_data.miss_count();
+        let options = SubmissionOptions {
+            batch_size: self.config.api_batch_size,
+            flush_interval: self.config.api_flush_interval,
+            retry: self.config.retry.api,
+            queue_file: Some(self.config.session_dir.join(SUBMISSION_QUEUE_FILE)),
+        };
+        self.lamp_submitter
+            .get_or_insert_with(|| LampSubmitter::start(api_config, options))
+            .submit(lamp);
+    }
+
+    /// Whether lamps may be sent this session, asking on the first submission
+    #[cfg(feature = "network")]
+    fn submission_allowed(&mut self, payload: &str) -> bool {
+        if let Some(allowed) = self.submission_consent {
+            return allowed;
+        }
 
-        thread::spawn(move || {
-            if let Err(e) = send_lamp_request(
-                &endpoint,
-                &token,
-                song_id,
-                &difficulty,
-                &lamp,
-                ex_score,
-                miss_count,
-            ) {
-                warn!("Failed to send lamp to API: {}", e);
+        println!("First lamp submission of this session:");
+        println!("  {}", payload);
+        if let Some(path) = &self.config.payload_preview {
+            match crate::storage::write_atomic(path, payload.as_bytes()) {
+                Ok(()) => info!("Lamp payload preview written to {}", path.display()),
+                Err(e) => warn!("Failed to write lamp payload preview: {}", e),
             }
-        });
+        }
+
+        let allowed = if self.config.api_consent {
+            true
+        } else if let Some(handler) = &mut self.consent_handler {
+            handler(payload)
+        } else {
+            false
+        };
+        if !allowed {
+            warn!("Lamp submissions are off for this session: no consent to send them");
+        }
+        self.submission_consent = Some(allowed);
+        allowed
     }
 
-    #[cfg(not(feature = "api"))]
-    fn send_lamp_to_api(&self, _play_data: &PlayData) {}
+    #[cfg(not(feature = "network"))]
+    fn send_lamp_to_api(&mut self, _play_data: &PlayData) {}
 
     /// Save play data to session file (TSV)
     fn save_session_data(&mut self, play_data: &PlayData) {
@@ -332,14 +876,54 @@ impl Infst {
         }
     }
 
+    /// Record the course result if the plays since the last song select formed a course
+    ///
+    /// The stages are also grouped into sets: a course is one set, single
+    /// plays fill standard-mode sets.
+    fn finish_course(&mut self) {
+        let result = match self.course_tracker.finish_run() {
+            Ok(course) => {
+                println!("{}", course.summary());
+                if let Err(e) = self.session_manager.append_course_result(&course) {
+                    error!("Failed to write course result: {}", e);
+                }
+                self.session_manager.record_course_set(course)
+            }
+            Err(stages) => stages
+                .into_iter()
+                .try_for_each(|stage| self.session_manager.record_set_play(stage)),
+        };
+        if let Err(e) = result {
+            error!("Failed to write set result: {}", e);
+        }
+    }
+
     /// Handle transition to song select screen
-    fn handle_song_select(&mut self, reader: &MemoryReader) {
+    fn handle_song_select<R: ReadMemory>(&mut self, reader: &R) {
+        self.finish_course();
+
+        #[cfg(feature = "stream")]
+        if let Some(stream) = &self.stream_output {
+            if let Err(e) = stream.write_idle() {
+                warn!("Failed to write stream marquee: {}", e);
+            }
+            if self.green_number_baseline.is_some()
+                && let Err(e) = stream.write_alert("")
+            {
+                warn!("Failed to clear stream alert: {}", e);
+            }
+        }
+
         // Re-scan for newly loaded songs (handles lazy loading)
         let prev_count = self.game_data.song_db.len();
-        self.rescan_song_database(reader);
+        if self.config.song_db_rescan && self.check_song_list_anchors(reader) {
+            self.rescan_song_database(reader);
+        }
 
         // Poll unlock state changes
-        self.poll_unlock_changes(reader);
+        if self.config.unlock_reads {
+            self.poll_unlock_changes(reader);
+        }
 
         // Reload score map if new songs were discovered
         if self.game_data.song_db.len() > prev_count {
@@ -348,7 +932,7 @@ impl Infst {
 
         // Export tracker file if auto-export is enabled
         if self.config.auto_export
-            && let Err(e) = self.export_tracker_tsv(&self.config.tracker_path)
+            && let Err(e) = self.write_tracker(&self.tracker_export_path())
         {
             error!("Failed to export tracker file: {}", e);
         }
@@ -358,11 +942,11 @@ impl Infst {
     ///
     /// Called when new songs are discovered to ensure score comparisons
     /// work for all known songs.
-    fn reload_score_map(&mut self, reader: &MemoryReader) {
+    fn reload_score_map<R: ReadMemory>(&mut self, reader: &R) {
         match ScoreMap::load_from_memory(reader, self.offsets.data_map, &self.game_data.song_db) {
             Ok(map) => {
                 info!("Reloaded score map: {} entries", map.len());
-                self.game_data.score_map = map;
+                self.replace_score_map(map);
             }
             Err(e) => warn!("Failed to reload score map: {}", e),
         }
@@ -372,9 +956,66 @@ impl Infst {
     ///
     /// This handles lazy loading in newer INFINITAS versions where songs are
     /// only loaded into memory when scrolled to in the song select screen.
-    fn rescan_song_database(&mut self, reader: &MemoryReader) {
-        let scan_result =
-            fetch_song_database_from_memory_scan(reader, self.offsets.song_list, 0x200000);
+    /// Verify the pinned song anchors, relocating the song list if it moved
+    ///
+    /// Returns false while the song list can't be found, so no titles are
+    /// read from a stale address. Anchors are pinned on the first call when
+    /// the offsets came without them.
+    fn check_song_list_anchors<R: ReadMemory>(&mut self, reader: &R) -> bool {
+        let layout = &self.layout.song_entry;
+        if self.offsets.song_anchors.is_empty() {
+            self.offsets.song_anchors = pin_song_anchors(reader, self.offsets.song_list, layout);
+            return true;
+        }
+        if !song_list_moved(
+            reader,
+            self.offsets.song_list,
+            &self.offsets.song_anchors,
+            layout,
+        ) {
+            return true;
+        }
+
+        warn!(
+            "Song list at 0x{:X} no longer matches its anchors, searching again...",
+            self.offsets.song_list
+        );
+        let mut searcher = OffsetSearcher::builder(reader)
+            .with_layout(&self.layout)
+            .build();
+        match searcher.search_song_list_offset(self.offsets.song_list) {
+            Ok(song_list) => {
+                if song_list != self.offsets.song_list {
+                    let old = self.offsets.song_list;
+                    let rebased = self.offsets.rebase_song_list(song_list, layout);
+                    info!(
+                        "Song list moved from 0x{:X} to 0x{:X} (rebased: {})",
+                        old,
+                        song_list,
+                        if rebased.is_empty() {
+                            "none".to_string()
+                        } else {
+                            rebased.join(", ")
+                        }
+                    );
+                }
+                self.offsets.song_anchors = pin_song_anchors(reader, song_list, layout);
+                true
+            }
+            Err(e) => {
+                warn!("Song list not found, skipping song re-scan: {}", e);
+                false
+            }
+        }
+    }
+
+    fn rescan_song_database<R: ReadMemory>(&mut self, reader: &R) {
+        let scan_result = fetch_song_database_from_memory_scan_with_layout(
+            reader,
+            self.offsets.song_list,
+            0x200000,
+            &self.layout.song_entry,
+        );
 
         let mut new_songs = 0usize;
         for (song_id, song) in scan_result {
@@ -399,19 +1040,28 @@ impl Infst {
         }
     }
 
+    /// Count the time since entering the Playing state towards the session
+    /// play time of the current chart
+    fn finish_play_time(&mut self) {
+        let (Some(since), Some(chart)) = (self.playing_since.take(), self.current_playing) else {
+            return;
+        };
+        let elapsed = (self.clock.now() - since).to_std().unwrap_or_default();
+        self.session_manager.record_play_time(chart, elapsed);
+    }
+
     /// Handle transition to playing state
     ///
     /// Captures current chart selection when entering Playing state.
     /// This is used for cross-validation on ResultScreen to ensure
     /// we're reading the correct play data.
-    fn handle_playing(&mut self, reader: &MemoryReader) {
+    fn handle_playing<R: ReadMemory>(&mut self, reader: &R) {
+        self.playing_since = Some(self.clock.now());
         match self.fetch_current_chart(reader) {
-            Ok((song_id, difficulty)) => {
-                debug!(
-                    "Entering Playing state: song_id={}, difficulty={:?}",
-                    song_id, difficulty
-                );
-                self.current_playing = Some((song_id, difficulty));
+            Ok(chart) => {
+                debug!("Entering Playing state: chart={}", chart);
+                self.current_playing = Some(chart);
+                self.check_green_number(reader, chart);
             }
             Err(e) => {
                 warn!("Failed to fetch current chart on Playing: {}", e);
@@ -421,7 +1071,7 @@ impl Infst {
     }
 
     /// Poll for unlock state changes
-    fn poll_unlock_changes(&mut self, reader: &MemoryReader) {
+    fn poll_unlock_changes<R: ReadMemory>(&mut self, reader: &R) {
         if self.game_data.song_db.is_empty() {
             return;
         }
@@ -452,16 +1102,16 @@ impl Infst {
     ///
     /// Used during Playing state to capture what chart is being played,
     /// enabling cross-validation when reading play data on ResultScreen.
-    fn fetch_current_chart(&self, reader: &MemoryReader) -> Result<(u32, Difficulty)> {
+    fn fetch_current_chart<R: ReadMemory>(&self, reader: &R) -> Result<ChartId> {
         let song_id = reader.read_i32(self.offsets.current_song)? as u32;
         let diff = reader.read_i32(self.offsets.current_song + 4)?;
 
-        let difficulty = Difficulty::from_u8(diff as u8).unwrap_or(Difficulty::SpN);
+        let difficulty = Difficulty::from_raw(diff).unwrap_or(Difficulty::SpN);
 
-        Ok((song_id, difficulty))
+        Ok(ChartId::new(song_id, difficulty))
     }
 
-    fn fetch_play_data(&mut self, reader: &MemoryReader) -> Result<PlayData> {
+    fn fetch_play_data<R: ReadMemory>(&mut self, reader: &R) -> Result<PlayData> {
         // Read data in same order as C# implementation:
         // 1. Judge data first (updates earliest on result screen)
         // 2. Settings
@@ -476,7 +1126,7 @@ impl Infst {
         let difficulty_val = reader.read_i32(self.offsets.play_data + play::DIFFICULTY)?;
         let lamp_val = reader.read_i32(self.offsets.play_data + play::LAMP)?;
 
-        let difficulty = Difficulty::from_u8(difficulty_val as u8).unwrap_or(Difficulty::SpN);
+        let difficulty = Difficulty::from_raw(difficulty_val).unwrap_or(Difficulty::SpN);
         let lamp = Lamp::from_u8(lamp_val as u8).unwrap_or(Lamp::NoPlay);
 
         // Calculate EX score
@@ -493,8 +1143,8 @@ impl Infst {
             Grade::NoPlay
         };
 
-        Ok(PlayData {
-            timestamp: Utc::now(),
+        let mut play_data = PlayData {
+            timestamp: self.clock.now(),
             chart,
             ex_score,
             grade,
@@ -502,13 +1152,23 @@ impl Infst {
             judge,
             settings,
             data_available,
-        })
+            bpi: None,
+            failed_at_percent: None,
+        };
+        play_data.bpi = self
+            .game_data
+            .bpi_table
+            .as_ref()
+            .and_then(|table| table.bpi_for(&play_data));
+        play_data.failed_at_percent = play_data.fail_point_percent();
+
+        Ok(play_data)
     }
 
     /// Create chart info from song database, dynamically loading from memory if not found
-    fn create_chart_info_dynamic(
+    fn create_chart_info_dynamic<R: ReadMemory>(
         &mut self,
-        reader: &MemoryReader,
+        reader: &R,
         song_id: u32,
         difficulty: Difficulty,
     ) -> ChartInfo {
@@ -518,7 +1178,13 @@ impl Infst {
         }
 
         // Try to dynamically load from memory
-        if let Some(song) = fetch_song_by_id(reader, self.offsets.song_list, song_id, 0x200000) {
+        if let Some(song) = fetch_song_by_id_with_layout(
+            reader,
+            self.offsets.song_list,
+            song_id,
+            0x200000,
+            &self.layout.song_entry,
+        ) {
             info!("Dynamically loaded song: {} ({})", song.title, song_id);
             let chart = ChartInfo::from_song_info(&song, difficulty, true);
             // Add to song database for future lookups
@@ -542,80 +1208,70 @@ impl Infst {
         }
     }
 
-    fn fetch_judge_data(&self, reader: &MemoryReader) -> Result<Judge> {
-        let base = self.offsets.judge_data;
-
-        let p1 = PlayerJudge {
-            pgreat: reader.read_u32(base + judge::P1_PGREAT)?,
-            great: reader.read_u32(base + judge::P1_GREAT)?,
-            good: reader.read_u32(base + judge::P1_GOOD)?,
-            bad: reader.read_u32(base + judge::P1_BAD)?,
-            poor: reader.read_u32(base + judge::P1_POOR)?,
-            combo_break: reader.read_u32(base + judge::P1_COMBO_BREAK)?,
-            fast: reader.read_u32(base + judge::P1_FAST)?,
-            slow: reader.read_u32(base + judge::P1_SLOW)?,
-            measure_end: reader.read_u32(base + judge::P1_MEASURE_END)?,
+    /// Alert (console and stream) when the green number strays from the
+    /// usual one for the chart's BPM band
+    fn check_green_number<R: ReadMemory>(&self, reader: &R, chart: ChartId) {
+        let (Some(threshold), Some(baseline)) =
+            (self.config.green_number_alert, &self.green_number_baseline)
+        else {
+            return;
         };
-
-        let p2 = PlayerJudge {
-            pgreat: reader.read_u32(base + judge::P2_PGREAT)?,
-            great: reader.read_u32(base + judge::P2_GREAT)?,
-            good: reader.read_u32(base + judge::P2_GOOD)?,
-            bad: reader.read_u32(base + judge::P2_BAD)?,
-            poor: reader.read_u32(base + judge::P2_POOR)?,
-            combo_break: reader.read_u32(base + judge::P2_COMBO_BREAK)?,
-            fast: reader.read_u32(base + judge::P2_FAST)?,
-            slow: reader.read_u32(base + judge::P2_SLOW)?,
-            measure_end: reader.read_u32(base + judge::P2_MEASURE_END)?,
+        if self.offsets.lane_cover == 0 {
+            return;
+        }
+        let Some(song) = self.game_data.song_db.get(&chart.song_id) else {
+            return;
         };
-
-        Ok(Judge::from_raw_data(RawJudgeData { p1, p2 }))
-    }
-
-    fn fetch_settings(&self, reader: &MemoryReader, play_type: PlayType) -> Result<Settings> {
-        let word: u64 = 4;
-        let base = self.offsets.play_settings;
-
-        let (style, assist, range, h_ran, style2) = match play_type {
-            PlayType::P1 | PlayType::Dp => {
-                let style = reader.read_i32(base)?;
-                let assist = reader.read_i32(base + word * 2)?;
-                let range = reader.read_i32(base + word * 4)?;
-                let h_ran = reader.read_i32(base + word * 9)?;
-                let style2 = if play_type == PlayType::Dp {
-                    reader.read_i32(base + word * 5)?
-                } else {
-                    0
-                };
-                (style, assist, range, h_ran, style2)
-            }
-            PlayType::P2 => {
-                let p2_offset = Settings::P2_OFFSET;
-                let style = reader.read_i32(base + p2_offset)?;
-                let assist = reader.read_i32(base + p2_offset + word * 2)?;
-                let range = reader.read_i32(base + p2_offset + word * 4)?;
-                let h_ran = reader.read_i32(base + p2_offset + word * 9)?;
-                (style, assist, range, h_ran, 0)
+        let cover = self
+            .fetch_judge_data(reader)
+            .and_then(|judge| read_lane_cover(reader, self.offsets.lane_cover, judge.play_type));
+        let cover = match cover {
+            Ok(cover) => cover,
+            Err(e) => {
+                debug!("Green number not checked: {}", e);
+                return;
             }
         };
+        let Some(alert) = baseline.check(&song.bpm, cover.green_number, threshold) else {
+            return;
+        };
 
-        let flip = reader.read_i32(base + word * 3)?;
-        let battle = reader.read_i32(base + word * 8)?;
+        warn!("{}", alert);
+        println!("{}", alert);
+        #[cfg(feature = "stream")]
+        if let Some(stream) = &self.stream_output
+            && let Err(e) = stream.write_alert(&alert.to_string())
+        {
+            warn!("Failed to write stream alert: {}", e);
+        }
+    }
+
+    fn fetch_judge_data<R: ReadMemory>(&self, reader: &R) -> Result<Judge> {
+        let raw: RawJudgeData = reader.read_struct(self.offsets.judge_data)?;
+        Ok(Judge::from_raw_data(raw))
+    }
+
+    fn fetch_settings<R: ReadMemory>(&self, reader: &R, play_type: PlayType) -> Result<Settings> {
+        let block: SettingsBlock = reader.read_struct(self.offsets.play_settings)?;
+        let mut settings = Settings::from_raw(block.to_raw(play_type));
 
-        Ok(Settings::from_raw(RawSettings {
-            play_type,
-            style,
-            style2,
-            assist,
-            range,
-            flip,
-            battle,
-            h_ran,
-        }))
+        // Lane covers are optional: a missing offset or unreadable block only
+        // leaves them out of the play
+        if self.offsets.lane_cover != 0 {
+            match read_lane_cover(reader, self.offsets.lane_cover, play_type) {
+                Ok(cover) => settings.lane_cover = Some(cover),
+                Err(e) => debug!("Lane cover not read: {}", e),
+            }
+        }
+        Ok(settings)
     }
 
     /// Load current unlock state from memory
-    pub fn load_unlock_state(&mut self, reader: &MemoryReader) -> Result<()> {
+    pub fn load_unlock_state<R: ReadMemory>(&mut self, reader: &R) -> Result<()> {
+        if !self.config.unlock_reads {
+            debug!("Unlock reads disabled, skipping unlock state");
+            return Ok(());
+        }
         if self.game_data.song_db.is_empty() {
             warn!("Song database is empty, cannot load unlock state");
             return Ok(());
@@ -633,9 +1289,9 @@ impl Infst {
     /// Check game version and compare with offsets version
     ///
     /// Returns (game_version, matches) where matches is true if versions match
-    pub fn check_game_version(
+    pub fn check_game_version<R: ReadMemory>(
         &self,
-        reader: &MemoryReader,
+        reader: &R,
         base_address: u64,
     ) -> Result<(Option<String>, bool)> {
         let game_version = find_game_version(reader, base_address)?;
@@ -649,34 +1305,61 @@ impl Infst {
     }
 }
 
-#[cfg(feature = "api")]
-fn send_lamp_request(
-    endpoint: &str,
-    token: &str,
-    song_id: u32,
-    difficulty: &str,
-    lamp: &str,
-    ex_score: u32,
-    miss_count: u32,
-) -> anyhow::Result<()> {
-    let url = format!("{}/api/lamps", endpoint.trim_end_matches('/'));
-    let body = serde_json::json!({
-        "songId": song_id,
-        "difficulty": difficulty,
-        "lamp": lamp,
-        "exScore": ex_score,
-        "missCount": miss_count,
-    });
-
-    let config = ureq::Agent::config_builder()
-        .timeout_global(Some(std::time::Duration::from_secs(5)))
-        .build();
-    let agent: ureq::Agent = config.into();
-    let response = agent
-        .post(&url)
-        .header("Authorization", &format!("Bearer {}", token))
-        .send_json(&body)?;
-
-    tracing::debug!("API response: {}", response.status());
-    Ok(())
+#[cfg(all(test, feature = "network"))]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+    use crate::infst::{ApiConfig, InfstConfig};
+
+    fn tracker(dir: &std::path::Path, api_consent: bool) -> crate::infst::InfstBuilder {
+        Infst::builder()
+            .config(InfstConfig {
+                api_consent,
+                payload_preview: Some(dir.join("payload.json")),
+                ..InfstConfig::default()
+            })
+            .session_dir(dir.join("sessions"))
+            .api(ApiConfig {
+                endpoint: "https://example.com".to_string(),
+                token: "token".to_string(),
+            })
+    }
+
+    #[test]
+    fn test_consent_is_asked_once_per_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let asked = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&asked);
+        let mut infst = tracker(dir.path(), false)
+            .on_submission_consent(move |payload| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                payload.contains("\"songId\":1000")
+            })
+            .build()
+            .unwrap();
+
+        assert!(infst.submission_allowed(r#"{"songId":1000}"#));
+        assert!(infst.submission_allowed(r#"{"songId":2000}"#));
+        assert_eq!(asked.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("payload.json")).unwrap(),
+            r#"{"songId":1000}"#
+        );
+
+        // A new session asks again
+        infst.submission_consent = None;
+        assert!(!infst.submission_allowed(r#"{"songId":2000}"#));
+        assert_eq!(asked.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_no_consent_without_flag_or_handler() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut infst = tracker(dir.path(), false).build().unwrap();
+        assert!(!infst.submission_allowed("{}"));
+
+        let mut infst = tracker(dir.path(), true).build().unwrap();
+        assert!(infst.submission_allowed("{}"));
+    }
 }
@@ -12,37 +12,69 @@
 //! use infst::infst::{Infst, InfstConfig};
 //! use infst::offset::OffsetsCollection;
 //!
-//! // Create with default configuration
-//! let offsets = OffsetsCollection::default();
-//! let mut infst = Infst::new(offsets);
-//!
-//! // Or create with custom configuration
-//! let config = InfstConfig::builder()
+//! let mut infst = Infst::builder()
+//!     .offsets(offsets)
+//!     .config(InfstConfig::builder().auto_export(false).build())
 //!     .session_dir("my_sessions")
-//!     .build();
-//! let mut infst = Infst::with_config(offsets, config);
-//!
-//! // Set up song database and score map
-//! infst.set_song_db(song_db);
-//! infst.set_score_map(score_map);
+//!     .song_db(song_db)
+//!     .score_map(score_map)
+//!     .on_play(|play| println!("{} {}", play.chart.title, play.ex_score))
+//!     .build()?;
 //!
 //! // Run the tracking loop
 //! infst.run(&process, &running)?;
 //! ```
 
+mod builder;
 mod game_loop;
+#[cfg(feature = "network")]
+mod submission;
+mod watchdog;
+
+pub use builder::{ConsentHandler, InfstBuilder, PlayHandler, UploadHandler};
 
 use std::collections::HashMap;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-use tracing::{debug, info};
+use chrono::{DateTime, NaiveDate, Utc};
+use tracing::{debug, info, warn};
 
-use crate::chart::{Difficulty, SongInfo, UnlockData};
-use crate::error::Result;
-use crate::offset::OffsetsCollection;
-use crate::play::GameStateDetector;
-use crate::score::ScoreMap;
-use crate::session::SessionManager;
+use crate::chart::{ChartId, SongInfo, UnlockData};
+use crate::clock::{SharedClock, SystemClock};
+use crate::error::{Error, Result};
+use crate::export::DisplayTimezone;
+#[cfg(feature = "notify")]
+use crate::notify::{PushConfig, PushNotifier};
+use crate::offset::{GameLayout, OffsetsCollection};
+use crate::paths::{BACKUP_DIR, Paths, SESSIONS_DIR, TRACKER_FILE};
+use crate::play::{
+    AnomalyMode, CourseTracker, DEFAULT_DUPLICATE_WINDOW_SECS, DuplicateGuard, GameStateDetector,
+    PrematureMode, ProfileInfo,
+};
+use crate::process::{MemoryReader, ProcessHandle, ReadPolicy};
+use crate::retry::RetryPolicies;
+use crate::score::{
+    ASSISTED_BESTS_FILE, AssistedBests, AuditEntry, ProfileMismatch, STORED_BESTS_FILE, ScoreAudit,
+    ScoreMap,
+};
+use crate::session::{
+    ExportAction, ExportScheduler, PauseMode, ScheduleEntry, ScheduledExport, SessionControl,
+    SessionManager, load_session_history,
+};
+use crate::stats::{
+    BpiTable, GreenNumberBaseline, GreenNumberLog, PLAY_COUNTS_FILE, PlayCounters, PlayCounts,
+    PlayTime,
+};
+use crate::storage::{IgnoreList, TextEncoding, write_atomic};
+#[cfg(feature = "stream")]
+use crate::stream::{DEFAULT_CLIPBOARD_TEMPLATE, MarqueeTemplate, StreamConfig, StreamOutput};
+use crate::tournament::Tournament;
+
+#[cfg(feature = "network")]
+use submission::LampSubmitter;
 
 /// API configuration for sending play data to the web service
 #[derive(Debug, Clone)]
@@ -51,6 +83,18 @@ pub struct ApiConfig {
     pub token: String,
 }
 
+/// Why the tracking loop ([`Infst::run`]) returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackerExit {
+    /// Shutdown was requested
+    Shutdown,
+    /// The game process exited or its memory became unreachable
+    ProcessExited,
+    /// The game module changed while running (self-update); offsets for the
+    /// old version are stale
+    GameUpdated,
+}
+
 /// Configuration for the Infst application
 #[derive(Debug, Clone)]
 pub struct InfstConfig {
@@ -60,17 +104,113 @@ pub struct InfstConfig {
     pub auto_export: bool,
     /// Path for auto-exported tracker file
     pub tracker_path: PathBuf,
+    /// Write dated snapshots (`tracker-YYYY-MM-DD.tsv`) instead of overwriting `tracker_path`
+    pub tracker_snapshots: bool,
     /// API configuration for sending play data
     pub api_config: Option<ApiConfig>,
+    /// Lamp submissions were allowed up front; otherwise the first payload of
+    /// each session goes to the consent handler
+    /// ([`InfstBuilder::on_submission_consent`]) and nothing is sent without one
+    pub api_consent: bool,
+    /// Also write the first lamp payload of each session to this file
+    pub payload_preview: Option<PathBuf>,
+    /// Lamps sent per request to servers that take batches (1 sends each
+    /// lamp on its own)
+    pub api_batch_size: usize,
+    /// Longest a lamp waits for its batch to fill before it is sent
+    pub api_flush_interval: Duration,
+    /// BPI reference data file (see [`crate::stats::bpi`])
+    pub bpi_file: Option<PathBuf>,
+    /// Stream overlay output (disabled when `None`)
+    #[cfg(feature = "stream")]
+    pub stream: Option<StreamConfig>,
+    /// Push notifications for milestones (disabled when `None`)
+    #[cfg(feature = "notify")]
+    pub push: Option<PushConfig>,
+    /// Save a summary image (plays and improved charts) next to the session
+    /// file when the session closes
+    #[cfg(feature = "render")]
+    pub summary_image: bool,
+    /// Tournament file scored against this session (see [`crate::tournament`])
+    pub tournament_file: Option<PathBuf>,
+    /// Global memory read-rate limit (unlimited when `None`)
+    pub max_reads_per_sec: Option<u32>,
+    /// Write an audit log of memory access patterns (JSON) to this file
+    pub audit_log: Option<PathBuf>,
+    /// Read unlock state from memory (initial load and polling at song select)
+    pub unlock_reads: bool,
+    /// Re-scan memory for newly loaded songs at song select
+    pub song_db_rescan: bool,
+    /// How plays are handled while the session is paused
+    pub pause_mode: PauseMode,
+    /// Timezone for play times in session TSV, console and stream output
+    /// (JSON sessions keep UTC)
+    pub display_timezone: DisplayTimezone,
+    /// What to do with plays that fail [`PlayData::validate`](crate::play::PlayData::validate)
+    pub anomaly_mode: AnomalyMode,
+    /// Identical results (same chart and judge counts) within this window are
+    /// recorded once; zero disables the check
+    pub duplicate_window: Duration,
+    /// How plays quit before the end of the chart are recorded
+    pub premature_mode: PrematureMode,
+    /// Encoding of the tracker and session TSV files
+    pub tsv_encoding: TextEncoding,
+    /// Exports run on a schedule or at session end (see [`crate::session::ExportScheduler`])
+    pub schedule: Vec<ScheduledExport>,
+    /// Template for the last play summary copied by [`SessionCommand::Copy`](crate::session::SessionCommand::Copy)
+    /// (same syntax as the marquee, see [`crate::stream::MarqueeTemplate`])
+    #[cfg(feature = "stream")]
+    pub clipboard_template: String,
+    /// Alert when the green number is more than this far from the usual one
+    /// for the chart's BPM band (disabled when `None`; needs the lane cover offset)
+    pub green_number_alert: Option<u16>,
+    /// Read memory and process plays as usual, but print what would be
+    /// recorded instead of writing files or submitting anything
+    pub dry_run: bool,
+    /// Retry policies (memory reads and API submissions are retried by the
+    /// tracker; song database loading and offset search by the caller)
+    pub retry: RetryPolicies,
+    /// Charts recorded locally but kept out of exports and submissions
+    pub ignore: IgnoreList,
 }
 
 impl Default for InfstConfig {
     fn default() -> Self {
         Self {
-            session_dir: PathBuf::from("sessions"),
+            session_dir: Paths::portable().sessions(),
             auto_export: true,
-            tracker_path: PathBuf::from("tracker.tsv"),
+            tracker_path: Paths::portable().tracker(),
+            tracker_snapshots: false,
             api_config: None,
+            api_consent: false,
+            payload_preview: None,
+            api_batch_size: crate::config::submission::BATCH_SIZE,
+            api_flush_interval: crate::config::submission::FLUSH_INTERVAL,
+            bpi_file: None,
+            #[cfg(feature = "stream")]
+            stream: None,
+            #[cfg(feature = "notify")]
+            push: None,
+            #[cfg(feature = "render")]
+            summary_image: false,
+            tournament_file: None,
+            max_reads_per_sec: None,
+            audit_log: None,
+            unlock_reads: true,
+            song_db_rescan: true,
+            pause_mode: PauseMode::default(),
+            display_timezone: DisplayTimezone::default(),
+            anomaly_mode: AnomalyMode::default(),
+            duplicate_window: Duration::from_secs(DEFAULT_DUPLICATE_WINDOW_SECS),
+            premature_mode: PrematureMode::default(),
+            tsv_encoding: TextEncoding::default(),
+            schedule: Vec::new(),
+            #[cfg(feature = "stream")]
+            clipboard_template: DEFAULT_CLIPBOARD_TEMPLATE.to_string(),
+            green_number_alert: None,
+            dry_run: false,
+            retry: RetryPolicies::default(),
+            ignore: IgnoreList::new(),
         }
     }
 }
@@ -88,7 +228,37 @@ pub struct InfstConfigBuilder {
     session_dir: Option<PathBuf>,
     auto_export: Option<bool>,
     tracker_path: Option<PathBuf>,
+    tracker_snapshots: Option<bool>,
     api_config: Option<ApiConfig>,
+    api_consent: Option<bool>,
+    payload_preview: Option<PathBuf>,
+    api_batch_size: Option<usize>,
+    api_flush_interval: Option<Duration>,
+    bpi_file: Option<PathBuf>,
+    #[cfg(feature = "stream")]
+    stream: Option<StreamConfig>,
+    #[cfg(feature = "notify")]
+    push: Option<PushConfig>,
+    #[cfg(feature = "render")]
+    summary_image: Option<bool>,
+    tournament_file: Option<PathBuf>,
+    max_reads_per_sec: Option<u32>,
+    audit_log: Option<PathBuf>,
+    unlock_reads: Option<bool>,
+    song_db_rescan: Option<bool>,
+    pause_mode: Option<PauseMode>,
+    display_timezone: Option<DisplayTimezone>,
+    anomaly_mode: Option<AnomalyMode>,
+    duplicate_window: Option<Duration>,
+    premature_mode: Option<PrematureMode>,
+    tsv_encoding: Option<TextEncoding>,
+    schedule: Vec<ScheduledExport>,
+    #[cfg(feature = "stream")]
+    clipboard_template: Option<String>,
+    green_number_alert: Option<u16>,
+    dry_run: Option<bool>,
+    retry: Option<RetryPolicies>,
+    ignore: Option<IgnoreList>,
 }
 
 impl InfstConfigBuilder {
@@ -110,12 +280,172 @@ impl InfstConfigBuilder {
         self
     }
 
+    /// Enable or disable dated tracker snapshots
+    pub fn tracker_snapshots(mut self, enabled: bool) -> Self {
+        self.tracker_snapshots = Some(enabled);
+        self
+    }
+
     /// Set API configuration
     pub fn api_config(mut self, config: ApiConfig) -> Self {
         self.api_config = Some(config);
         self
     }
 
+    /// Set the BPI reference data file
+    pub fn bpi_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.bpi_file = Some(path.into());
+        self
+    }
+
+    /// Enable stream overlay output
+    #[cfg(feature = "stream")]
+    pub fn stream(mut self, config: StreamConfig) -> Self {
+        self.stream = Some(config);
+        self
+    }
+
+    /// Score this session against a tournament file
+    pub fn tournament_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.tournament_file = Some(path.into());
+        self
+    }
+
+    /// Limit memory reads to this many per second
+    pub fn max_reads_per_sec(mut self, limit: u32) -> Self {
+        self.max_reads_per_sec = Some(limit);
+        self
+    }
+
+    /// Record memory access patterns and write them to this file
+    pub fn audit_log<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.audit_log = Some(path.into());
+        self
+    }
+
+    /// Enable or disable unlock state reads
+    pub fn unlock_reads(mut self, enabled: bool) -> Self {
+        self.unlock_reads = Some(enabled);
+        self
+    }
+
+    /// Enable or disable song database re-scans at song select
+    pub fn song_db_rescan(mut self, enabled: bool) -> Self {
+        self.song_db_rescan = Some(enabled);
+        self
+    }
+
+    /// Set how plays are handled while the session is paused
+    pub fn pause_mode(mut self, mode: PauseMode) -> Self {
+        self.pause_mode = Some(mode);
+        self
+    }
+
+    /// Set the timezone play times are shown in
+    pub fn display_timezone(mut self, timezone: DisplayTimezone) -> Self {
+        self.display_timezone = Some(timezone);
+        self
+    }
+
+    /// Set what happens to plays with impossible values
+    pub fn anomaly_mode(mut self, mode: AnomalyMode) -> Self {
+        self.anomaly_mode = Some(mode);
+        self
+    }
+
+    /// Set the window for suppressing duplicate results
+    pub fn duplicate_window(mut self, window: Duration) -> Self {
+        self.duplicate_window = Some(window);
+        self
+    }
+
+    /// Set how premature plays are recorded
+    pub fn premature_mode(mut self, mode: PrematureMode) -> Self {
+        self.premature_mode = Some(mode);
+        self
+    }
+
+    /// Set the encoding of the tracker and session TSV files
+    pub fn tsv_encoding(mut self, encoding: TextEncoding) -> Self {
+        self.tsv_encoding = Some(encoding);
+        self
+    }
+
+    /// Add a scheduled export
+    pub fn scheduled_export(mut self, job: ScheduledExport) -> Self {
+        self.schedule.push(job);
+        self
+    }
+
+    /// Set the template for the copied last play summary
+    #[cfg(feature = "stream")]
+    pub fn clipboard_template(mut self, template: impl Into<String>) -> Self {
+        self.clipboard_template = Some(template.into());
+        self
+    }
+
+    /// Alert when the green number strays more than `threshold` from the usual one
+    pub fn green_number_alert(mut self, threshold: u16) -> Self {
+        self.green_number_alert = Some(threshold);
+        self
+    }
+
+    /// Enable or disable dry-run mode (nothing written or submitted)
+    pub fn dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = Some(enabled);
+        self
+    }
+
+    /// Allow lamp submissions without asking the consent handler
+    pub fn api_consent(mut self, allowed: bool) -> Self {
+        self.api_consent = Some(allowed);
+        self
+    }
+
+    /// Write the first lamp payload of each session to this file
+    pub fn payload_preview<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.payload_preview = Some(path.into());
+        self
+    }
+
+    /// Enable push notifications for milestones
+    #[cfg(feature = "notify")]
+    pub fn push(mut self, config: PushConfig) -> Self {
+        self.push = Some(config);
+        self
+    }
+
+    /// Save a summary image next to the session file at session close
+    #[cfg(feature = "render")]
+    pub fn summary_image(mut self, enabled: bool) -> Self {
+        self.summary_image = Some(enabled);
+        self
+    }
+
+    /// Lamps sent per request to servers that take batches
+    pub fn api_batch_size(mut self, size: usize) -> Self {
+        self.api_batch_size = Some(size);
+        self
+    }
+
+    /// Longest a lamp waits for its batch to fill before it is sent
+    pub fn api_flush_interval(mut self, interval: Duration) -> Self {
+        self.api_flush_interval = Some(interval);
+        self
+    }
+
+    /// Set the retry policies
+    pub fn retry(mut self, policies: RetryPolicies) -> Self {
+        self.retry = Some(policies);
+        self
+    }
+
+    /// Set the charts kept out of exports and submissions
+    pub fn ignore(mut self, list: IgnoreList) -> Self {
+        self.ignore = Some(list);
+        self
+    }
+
     /// Build the configuration
     pub fn build(self) -> InfstConfig {
         let default = InfstConfig::default();
@@ -123,7 +453,41 @@ impl InfstConfigBuilder {
             session_dir: self.session_dir.unwrap_or(default.session_dir),
             auto_export: self.auto_export.unwrap_or(default.auto_export),
             tracker_path: self.tracker_path.unwrap_or(default.tracker_path),
+            tracker_snapshots: self.tracker_snapshots.unwrap_or(default.tracker_snapshots),
             api_config: self.api_config,
+            api_consent: self.api_consent.unwrap_or(default.api_consent),
+            payload_preview: self.payload_preview,
+            api_batch_size: self.api_batch_size.unwrap_or(default.api_batch_size),
+            api_flush_interval: self
+                .api_flush_interval
+                .unwrap_or(default.api_flush_interval),
+            bpi_file: self.bpi_file,
+            #[cfg(feature = "stream")]
+            stream: self.stream,
+            #[cfg(feature = "notify")]
+            push: self.push,
+            #[cfg(feature = "render")]
+            summary_image: self.summary_image.unwrap_or(default.summary_image),
+            tournament_file: self.tournament_file,
+            max_reads_per_sec: self.max_reads_per_sec,
+            audit_log: self.audit_log,
+            unlock_reads: self.unlock_reads.unwrap_or(default.unlock_reads),
+            song_db_rescan: self.song_db_rescan.unwrap_or(default.song_db_rescan),
+            pause_mode: self.pause_mode.unwrap_or(default.pause_mode),
+            display_timezone: self.display_timezone.unwrap_or(default.display_timezone),
+            anomaly_mode: self.anomaly_mode.unwrap_or(default.anomaly_mode),
+            duplicate_window: self.duplicate_window.unwrap_or(default.duplicate_window),
+            premature_mode: self.premature_mode.unwrap_or(default.premature_mode),
+            tsv_encoding: self.tsv_encoding.unwrap_or(default.tsv_encoding),
+            schedule: self.schedule,
+            #[cfg(feature = "stream")]
+            clipboard_template: self
+                .clipboard_template
+                .unwrap_or(default.clipboard_template),
+            green_number_alert: self.green_number_alert,
+            dry_run: self.dry_run.unwrap_or(default.dry_run),
+            retry: self.retry.unwrap_or(default.retry),
+            ignore: self.ignore.unwrap_or(default.ignore),
         }
     }
 }
@@ -136,6 +500,10 @@ pub struct GameData {
     pub score_map: ScoreMap,
     /// Current unlock state from memory
     pub unlock_state: HashMap<u32, UnlockData>,
+    /// BPI reference data (loaded from `InfstConfig::bpi_file`)
+    pub bpi_table: Option<BpiTable>,
+    /// Player profile (when the profile offset is known)
+    pub profile: Option<ProfileInfo>,
 }
 
 impl GameData {
@@ -144,6 +512,8 @@ impl GameData {
             song_db: HashMap::new(),
             score_map: ScoreMap::new(),
             unlock_state: HashMap::new(),
+            bpi_table: None,
+            profile: None,
         }
     }
 }
@@ -151,18 +521,76 @@ impl GameData {
 /// Main application
 pub struct Infst {
     pub(crate) offsets: OffsetsCollection,
+    /// Memory layout of the attached game build (see [`Infst::set_layout`])
+    pub(crate) layout: GameLayout,
     /// Application configuration
     pub(crate) config: InfstConfig,
     /// Game data from memory
     pub(crate) game_data: GameData,
     pub(crate) state_detector: GameStateDetector,
     pub(crate) session_manager: SessionManager,
+    /// Pause/resume/annotate commands from `infst session`
+    pub(crate) session_control: SessionControl,
     /// Currently playing chart (set during Playing state)
     /// Used for cross-validation when fetching play data on ResultScreen
-    pub(crate) current_playing: Option<(u32, Difficulty)>,
+    pub(crate) current_playing: Option<ChartId>,
+    /// When the current Playing state started (session play time)
+    pub(crate) playing_since: Option<DateTime<Utc>>,
+    /// Stage results since the last song select (course detection)
+    pub(crate) course_tracker: CourseTracker,
+    /// Suppresses a result screen recorded twice
+    pub(crate) duplicate_guard: DuplicateGuard,
+    /// Plays and notes hit per day, kept in `play_counts.json` in the session directory
+    pub(crate) play_counters: PlayCounters,
+    /// Bests of assisted plays, kept in `assisted_bests.json` in the session directory
+    pub(crate) assisted_bests: AssistedBests,
+    /// Bests no read may lower, kept in `bests.json` in the session directory
+    pub(crate) stored_bests: ScoreMap,
+    /// Set while the game's score map looks like another player's
+    pub(crate) profile_mismatch: Option<ProfileMismatch>,
+    /// Stream overlay output (from `InfstConfig::stream`)
+    #[cfg(feature = "stream")]
+    pub(crate) stream_output: Option<StreamOutput>,
+    /// Parsed `InfstConfig::clipboard_template`
+    #[cfg(feature = "stream")]
+    pub(crate) clipboard_template: Option<MarqueeTemplate>,
+    /// Push notifications (from `InfstConfig::push`)
+    #[cfg(feature = "notify")]
+    pub(crate) push_notifier: Option<PushNotifier>,
+    /// Summary of the last result, copied by [`SessionCommand::Copy`](crate::session::SessionCommand::Copy)
+    pub(crate) last_play_summary: Option<String>,
+    /// Tournament scored against this session (from `InfstConfig::tournament_file`)
+    pub(crate) tournament: Option<Tournament>,
+    /// Read throttling / auditing (from `InfstConfig::max_reads_per_sec` and `audit_log`)
+    pub(crate) read_policy: Arc<ReadPolicy>,
+    /// Embedder callbacks for recorded plays (see [`InfstBuilder::on_play`])
+    pub(crate) play_handlers: Vec<PlayHandler>,
+    /// Scheduled exports (from `InfstConfig::schedule`, created when tracking starts)
+    pub(crate) scheduler: Option<ExportScheduler>,
+    /// Performs scheduled uploads (see [`InfstBuilder::on_upload`])
+    pub(crate) upload_handler: Option<UploadHandler>,
+    /// Time source for play timestamps, session files and snapshots
+    pub(crate) clock: SharedClock,
+    /// Usual green numbers per BPM band (when `InfstConfig::green_number_alert` is set)
+    pub(crate) green_number_baseline: Option<GreenNumberBaseline>,
+    /// Decides on lamp submissions without `InfstConfig::api_consent` (see
+    /// [`InfstBuilder::on_submission_consent`])
+    #[cfg(feature = "network")]
+    pub(crate) consent_handler: Option<ConsentHandler>,
+    /// Whether lamps may be sent this session (`None` until the first submission)
+    #[cfg(feature = "network")]
+    pub(crate) submission_consent: Option<bool>,
+    /// Sends lamps in the background (started by the first submission)
+    #[cfg(feature = "network")]
+    pub(crate) lamp_submitter: Option<LampSubmitter>,
 }
 
 impl Infst {
+    /// Start building an Infst instance
+    pub fn builder() -> InfstBuilder {
+        InfstBuilder::new()
+    }
+
     /// Create a new Infst instance with default configuration
     pub fn new(offsets: OffsetsCollection) -> Self {
         Self::with_config(offsets, InfstConfig::default())
@@ -185,13 +613,159 @@ impl Infst {
 
         let session_dir = config.session_dir.to_string_lossy().to_string();
 
+        let mut game_data = GameData::new();
+        if let Some(path) = &config.bpi_file {
+            match BpiTable::load(path) {
+                Ok(table) => {
+                    info!("Loaded BPI data for {} charts from {:?}", table.len(), path);
+                    game_data.bpi_table = Some(table);
+                }
+                Err(e) => warn!("Failed to load BPI data from {:?}: {}", path, e),
+            }
+        }
+
+        #[cfg(feature = "stream")]
+        if config.dry_run && config.stream.is_some() {
+            info!("Stream output disabled in dry-run mode");
+        }
+        #[cfg(feature = "stream")]
+        let stream_output = config
+            .stream
+            .as_ref()
+            .filter(|_| !config.dry_run)
+            .and_then(|stream| match StreamOutput::new(stream) {
+                Ok(output) => Some(output.with_timezone(config.display_timezone)),
+                Err(e) => {
+                    warn!("Stream output disabled: {}", e);
+                    None
+                }
+            });
+
+        #[cfg(feature = "stream")]
+        let clipboard_template = match MarqueeTemplate::parse(&config.clipboard_template) {
+            Ok(template) => Some(template),
+            Err(e) => {
+                warn!("Clipboard copy disabled: {}", e);
+                None
+            }
+        };
+
+        #[cfg(feature = "notify")]
+        let push_notifier =
+            config
+                .push
+                .as_ref()
+                .and_then(|push| match PushNotifier::new(push.clone()) {
+                    Ok(notifier) => Some(notifier.with_timezone(config.display_timezone)),
+                    Err(e) => {
+                        warn!("Push notifications disabled: {}", e);
+                        None
+                    }
+                });
+
+        let tournament =
+            config
+                .tournament_file
+                .as_ref()
+                .and_then(|path| match Tournament::load(path) {
+                    Ok(tournament) => {
+                        info!(
+                            "Loaded tournament {:?} ({} charts, {} participants)",
+                            tournament.name,
+                            tournament.pool.len(),
+                            tournament.participants.len()
+                        );
+                        Some(tournament)
+                    }
+                    Err(e) => {
+                        warn!("Tournament disabled: failed to load {:?}: {}", path, e);
+                        None
+                    }
+                });
+
+        // Usual green numbers come from the recorded sessions
+        let green_number_baseline = config.green_number_alert.map(|_| {
+            match load_session_history(&config.session_dir, &HashMap::new()) {
+                Ok(plays) => GreenNumberBaseline::from_plays(&plays),
+                Err(e) => {
+                    debug!("No session history for green number alerts: {}", e);
+                    GreenNumberBaseline::new()
+                }
+            }
+        });
+
+        let play_counters = PlayCounters::load(config.session_dir.join(PLAY_COUNTS_FILE))
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Play counts reset: failed to load {}: {}",
+                    PLAY_COUNTS_FILE, e
+                );
+                PlayCounters::new()
+            });
+        let assisted_bests = AssistedBests::load(config.session_dir.join(ASSISTED_BESTS_FILE))
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Assisted bests reset: failed to load {}: {}",
+                    ASSISTED_BESTS_FILE, e
+                );
+                AssistedBests::new()
+            });
+        let stored_bests = ScoreMap::load_stored_bests(config.session_dir.join(STORED_BESTS_FILE))
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Stored bests reset: failed to load {}: {}",
+                    STORED_BESTS_FILE, e
+                );
+                ScoreMap::new()
+            });
+
+        let read_policy = Arc::new(ReadPolicy::new(
+            config.max_reads_per_sec,
+            config.audit_log.is_some(),
+        ));
+
+        let session_manager = SessionManager::new(&session_dir)
+            .with_timezone(config.display_timezone)
+            .with_encoding(config.tsv_encoding)
+            .with_dry_run(config.dry_run);
+        let duplicate_guard = DuplicateGuard::new(config.duplicate_window);
+
         Self {
             offsets,
+            layout: GameLayout::current(),
             config,
-            game_data: GameData::new(),
+            game_data,
             state_detector: GameStateDetector::new(),
-            session_manager: SessionManager::new(&session_dir),
+            session_manager,
+            session_control: SessionControl::new(&session_dir),
             current_playing: None,
+            playing_since: None,
+            course_tracker: CourseTracker::new(),
+            duplicate_guard,
+            play_counters,
+            assisted_bests,
+            stored_bests,
+            profile_mismatch: None,
+            #[cfg(feature = "stream")]
+            stream_output,
+            #[cfg(feature = "stream")]
+            clipboard_template,
+            #[cfg(feature = "notify")]
+            push_notifier,
+            last_play_summary: None,
+            tournament,
+            read_policy,
+            play_handlers: Vec::new(),
+            scheduler: None,
+            upload_handler: None,
+            clock: SystemClock::shared(),
+            green_number_baseline,
+            #[cfg(feature = "network")]
+            consent_handler: None,
+            #[cfg(feature = "network")]
+            submission_consent: None,
+            #[cfg(feature = "network")]
+            lamp_submitter: None,
         }
     }
 
@@ -200,12 +774,177 @@ impl Infst {
         &self.config
     }
 
+    /// Time spent playing and notes hit this session
+    pub fn play_time(&self) -> &PlayTime {
+        self.session_manager.play_time()
+    }
+
+    /// Green numbers played this session
+    pub fn green_numbers(&self) -> &GreenNumberLog {
+        self.session_manager.green_numbers()
+    }
+
+    /// Plays and notes hit today and this week (across sessions)
+    pub fn play_counts(&self) -> PlayCounts {
+        self.play_counters.counts(self.today())
+    }
+
+    /// Today's date in the display timezone
+    pub(crate) fn today(&self) -> NaiveDate {
+        self.config
+            .display_timezone
+            .convert(&self.clock.now())
+            .date_naive()
+    }
+
+    /// Summary of the last result rendered with `InfstConfig::clipboard_template`
+    pub fn last_play_summary(&self) -> Option<&str> {
+        self.last_play_summary.as_deref()
+    }
+
+    /// Create a memory reader that applies the configured read throttling and auditing
+    pub fn memory_reader<'a>(&self, process: &'a ProcessHandle) -> MemoryReader<'a> {
+        MemoryReader::new(process).with_policy(Arc::clone(&self.read_policy))
+    }
+
+    /// Write the memory access audit log, if `InfstConfig::audit_log` is set
+    pub fn write_audit_log(&self) -> Result<()> {
+        if let (Some(path), Some(audit)) = (&self.config.audit_log, self.read_policy.audit()) {
+            if self.config.dry_run {
+                println!("(dry run) would write audit log to {}", path.display());
+                return Ok(());
+            }
+            audit.save_json(path)?;
+            debug!("Wrote memory access audit log to {:?}", path);
+        }
+        Ok(())
+    }
+
+    /// Save the session summary image next to the session file (see
+    /// `InfstConfig::summary_image`); sessions without plays are skipped
+    #[cfg(feature = "render")]
+    pub fn save_summary_image(&self) -> Result<()> {
+        let plays = self.session_manager.play_count();
+        let session_path = self
+            .session_manager
+            .current_json_session_path()
+            .or(self.session_manager.current_session_path());
+        let Some(session_path) = session_path.filter(|_| plays > 0) else {
+            return Ok(());
+        };
+        let path = session_path.with_extension("png");
+        if self.config.dry_run {
+            println!(
+                "(dry run) would save session summary image to {}",
+                path.display()
+            );
+            return Ok(());
+        }
+
+        // The bitmap font is ASCII only; fall back to the English title
+        let improvements: Vec<_> = self
+            .session_manager
+            .improvements()
+            .iter()
+            .map(|improvement| {
+                let mut improvement = improvement.clone();
+                let english = improvement
+                    .song_id
+                    .parse()
+                    .ok()
+                    .and_then(|id: u32| self.game_data.song_db.get(&id))
+                    .map(|song| &*song.title_english)
+                    .filter(|title| !title.is_empty());
+                if let Some(english) = english.filter(|_| !improvement.title.is_ascii()) {
+                    improvement.title = english.to_string();
+                }
+                improvement
+            })
+            .collect();
+        let heading = self.clock.now_local().format("%Y-%m-%d").to_string();
+        let canvas = crate::render::render_session_summary(&crate::render::SessionSummaryCard {
+            heading: &heading,
+            dj_name: self.game_data.profile.as_ref().map(|p| p.dj_name.as_str()),
+            plays,
+            play_time: self.session_manager.play_time(),
+            improvements: &improvements,
+        });
+        canvas.save_png(&path)?;
+        info!("Saved session summary image to {:?}", path);
+        Ok(())
+    }
+
+    /// Replace the song database and score map, e.g. after attaching to a new game process
+    pub fn load_game_data(&mut self, song_db: HashMap<u32, SongInfo>, score_map: ScoreMap) {
+        self.game_data.song_db = song_db;
+        self.replace_score_map(score_map);
+    }
+
     /// Set score map
+    #[deprecated(note = "use `InfstBuilder::score_map` or `Infst::load_game_data`")]
     pub fn set_score_map(&mut self, score_map: ScoreMap) {
+        self.replace_score_map(score_map);
+    }
+
+    /// Use a score map read from the game, with the assisted bests overlaid
+    ///
+    /// Bests read lower than the stored ones are kept at the stored values;
+    /// suspicious drops go to the score audit trail. A score map that looks
+    /// like another player's is used as read, leaving the stored and assisted
+    /// bests alone.
+    pub(crate) fn replace_score_map(&mut self, mut score_map: ScoreMap) {
+        let mismatch = score_map.profile_mismatch(&self.stored_bests);
+        if mismatch.is_switch() {
+            if self.profile_mismatch.is_none() {
+                warn!(
+                    "{} of {} stored charts ({:.0}%) read lower: another profile seems to be logged in, \
+                     its scores are not merged into the stored bests",
+                    mismatch.lowered,
+                    mismatch.compared,
+                    mismatch.ratio() * 100.0
+                );
+            }
+            self.profile_mismatch = Some(mismatch);
+            self.game_data.score_map = score_map;
+            return;
+        }
+        self.profile_mismatch = None;
+        self.assisted_bests.apply(&mut score_map);
+        let lowered = score_map.keep_bests(&self.stored_bests);
+        let now = self.clock.now();
+        let entries: Vec<AuditEntry> = lowered
+            .iter()
+            .filter(|delta| delta.is_suspicious())
+            .map(|delta| AuditEntry::new(now, delta))
+            .collect();
+        if !entries.is_empty() {
+            warn!(
+                "{} charts read lower than their stored bests (kept the stored bests, see `infst tracker audit`)",
+                entries.len()
+            );
+        }
+
+        let changed = !score_map.diff(&self.stored_bests).is_empty();
+        if !self.config.dry_run {
+            if let Err(e) = ScoreAudit::new(&self.config.session_dir).append(&entries) {
+                warn!("Failed to write score audit: {}", e);
+            }
+            if changed
+                && let Err(e) = score_map.to_json().and_then(|json| {
+                    write_atomic(self.config.session_dir.join(STORED_BESTS_FILE), json)
+                })
+            {
+                warn!("Failed to save stored bests: {}", e);
+            }
+        }
+        if changed {
+            self.stored_bests = score_map.clone();
+        }
         self.game_data.score_map = score_map;
     }
 
     /// Set song database
+    #[deprecated(note = "use `InfstBuilder::song_db` or `Infst::load_game_data`")]
     pub fn set_song_db(&mut self, song_db: HashMap<u32, SongInfo>) {
         self.game_data.song_db = song_db;
     }
@@ -215,11 +954,36 @@ impl Infst {
         &self.offsets
     }
 
+    /// How the game's score map diverges from the stored bests, while it
+    /// looks like another player's
+    pub fn profile_mismatch(&self) -> Option<ProfileMismatch> {
+        self.profile_mismatch
+    }
+
+    /// Player profile read at the start of tracking, if available
+    pub fn profile(&self) -> Option<&ProfileInfo> {
+        self.game_data.profile.as_ref()
+    }
+
     /// Get the offsets version
     pub fn offsets_version(&self) -> &str {
         &self.offsets.version
     }
 
+    /// Memory layout used for song entries
+    pub fn layout(&self) -> &GameLayout {
+        &self.layout
+    }
+
+    /// Use the memory layout of the attached game build
+    ///
+    /// Select it with [`crate::offset::LayoutRegistry::select`] from the
+    /// detected version; the newest build's layout is used until then.
+    pub fn set_layout(&mut self, layout: GameLayout) {
+        debug!("Using memory layout of build {}", layout.since);
+        self.layout = layout;
+    }
+
     /// Update offsets while preserving tracker and game data
     ///
     /// This method updates the offsets without creating a new Infst instance,
@@ -234,13 +998,118 @@ impl Infst {
         self.offsets = offsets;
     }
 
-    /// Export tracker data to TSV file
+    /// Path the auto-exported tracker is written to
+    ///
+    /// With `tracker_snapshots` enabled this is today's dated snapshot.
+    pub fn tracker_export_path(&self) -> PathBuf {
+        if self.config.tracker_snapshots {
+            crate::export::tracker_snapshot_path(
+                &self.config.tracker_path,
+                self.clock.now_local().date_naive(),
+            )
+        } else {
+            self.config.tracker_path.clone()
+        }
+    }
+
+    /// Export tracker data to TSV file (ignored charts left unplayed)
     pub fn export_tracker_tsv<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         crate::export::export_tracker_tsv(
             path,
             &self.game_data.song_db,
             &self.game_data.unlock_state,
-            &self.game_data.score_map,
+            &self
+                .config
+                .ignore
+                .mask_scores(&self.game_data.score_map, &self.game_data.song_db),
+            self.config.tsv_encoding,
         )
     }
+
+    /// Export the tracker to `path`, or only print the path in dry-run mode
+    pub fn write_tracker(&self, path: &Path) -> Result<()> {
+        if self.config.dry_run {
+            println!("(dry run) would export tracker to {}", path.display());
+            return Ok(());
+        }
+        self.export_tracker_tsv(path)
+    }
+
+    /// Last and next run of each scheduled export (empty before tracking starts)
+    pub fn schedule(&self) -> Vec<ScheduleEntry> {
+        self.scheduler
+            .as_ref()
+            .map(ExportScheduler::entries)
+            .unwrap_or_default()
+    }
+
+    /// Perform an export now, as the scheduler would
+    pub fn run_export_action(&mut self, action: &ExportAction) -> Result<()> {
+        if self.game_data.song_db.is_empty() {
+            return Err(Error::song_database_not_loaded(
+                "nothing to export before the song database is loaded",
+            ));
+        }
+        if self.config.dry_run {
+            println!("(dry run) would run export {}", action);
+            return Ok(());
+        }
+        let now = self.clock.now_local();
+        match action {
+            ExportAction::Snapshot => {
+                let path = crate::export::tracker_snapshot_path(
+                    &self.config.tracker_path,
+                    now.date_naive(),
+                );
+                self.export_tracker_tsv(&path)?;
+                info!("Wrote tracker snapshot {:?}", path);
+            }
+            ExportAction::Backup => {
+                let dir = self
+                    .config
+                    .tracker_path
+                    .with_file_name(BACKUP_DIR)
+                    .join(now.format("%Y-%m-%d_%H%M%S").to_string());
+                self.write_backup(&dir)?;
+                info!("Wrote backup to {:?}", dir);
+            }
+            ExportAction::Upload { mapping } => {
+                let path = self.tracker_export_path();
+                self.export_tracker_tsv(&path)?;
+                match &mut self.upload_handler {
+                    Some(upload) => upload(&path, mapping)?,
+                    None => warn!("Scheduled upload skipped: no upload handler"),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the current tracker and copy the session files into `dir`
+    fn write_backup(&self, dir: &Path) -> Result<()> {
+        let sessions = dir.join(SESSIONS_DIR);
+        fs::create_dir_all(&sessions).map_err(|e| Error::storage(&sessions, e))?;
+        let tracker_name = self
+            .config
+            .tracker_path
+            .file_name()
+            .unwrap_or(TRACKER_FILE.as_ref());
+        self.export_tracker_tsv(dir.join(tracker_name))?;
+
+        let Ok(entries) = fs::read_dir(&self.config.session_dir) else {
+            return Ok(());
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_session = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("Session_"));
+            if is_session && path.is_file() {
+                let target = sessions.join(entry.file_name());
+                fs::copy(&path, &target).map_err(|e| Error::storage(&target, e))?;
+            }
+        }
+        Ok(())
+    }
 }
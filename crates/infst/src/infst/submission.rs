@@ -0,0 +1,695 @@
+//! Lamp submissions to the web API
+//!
+//! Lamps are handed to a background thread that sends them in batches.
+//! Before the first send it asks the server what it supports
+//! (`GET {endpoint}/api/capabilities`). Servers speaking protocol v2 take up
+//! to [`SubmissionOptions::batch_size`] lamps per request on
+//! `POST {endpoint}/api/lamps/bulk`, the endpoint `infst sync` uses; servers
+//! without the capabilities endpoint get one `POST {endpoint}/api/lamps` per
+//! lamp, as before.
+//!
+//! A batch is sent when it is full or [`SubmissionOptions::flush_interval`]
+//! after its first lamp, and whatever is left when tracking ends. Lamps that
+//! could not be sent stay in the offline queue
+//! ([`SUBMISSION_QUEUE_FILE`](crate::paths::SUBMISSION_QUEUE_FILE), one JSON
+//! lamp per line) and are retried at the next flush, or the next time
+//! tracking submits a lamp. Lamps the server rejects outright (a 4xx other
+//! than 429) are dropped.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::config::submission::{BATCH_SIZE, FLUSH_INTERVAL, MAX_QUEUED};
+use crate::error::{Error, Result};
+use crate::retry::{RetryPolicy, RetryStrategy};
+
+use super::ApiConfig;
+
+/// Timeout of a single API request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One lamp as sent to the web API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LampSubmission {
+    pub song_id: u32,
+    pub difficulty: String,
+    pub lamp: String,
+    pub ex_score: u32,
+    pub miss_count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dj_name: Option<String>,
+}
+
+/// What the server supports (`GET /api/capabilities`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ServerCapabilities {
+    /// Protocol version (1 for servers without the capabilities endpoint)
+    #[serde(default = "protocol_v1")]
+    pub protocol: u32,
+    /// Whether `POST /api/lamps/bulk` accepts batches
+    #[serde(default)]
+    pub batch_submissions: bool,
+    /// Largest batch the server accepts
+    #[serde(default)]
+    pub max_batch_size: Option<usize>,
+}
+
+fn protocol_v1() -> u32 {
+    1
+}
+
+impl ServerCapabilities {
+    /// A server that only takes single lamps
+    pub const V1: Self = Self {
+        protocol: 1,
+        batch_submissions: false,
+        max_batch_size: None,
+    };
+
+    /// Lamps per request, given the configured batch size
+    fn batch_size(&self, configured: usize) -> usize {
+        if self.protocol >= 2 && self.batch_submissions {
+            configured
+                .min(self.max_batch_size.unwrap_or(usize::MAX))
+                .max(1)
+        } else {
+            1
+        }
+    }
+}
+
+/// How lamps are batched and where unsent ones are kept
+#[derive(Debug, Clone)]
+pub(crate) struct SubmissionOptions {
+    /// Lamps per batch request (servers may lower it)
+    pub batch_size: usize,
+    /// Longest a queued lamp waits before its batch is sent
+    pub flush_interval: Duration,
+    /// Retries of each request
+    pub retry: RetryPolicy,
+    /// Offline queue file (kept in memory only when `None`)
+    pub queue_file: Option<PathBuf>,
+}
+
+impl Default for SubmissionOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: BATCH_SIZE,
+            flush_interval: FLUSH_INTERVAL,
+            retry: RetryPolicy::fixed(Some(1), Duration::ZERO),
+            queue_file: None,
+        }
+    }
+}
+
+/// Requests the submission thread makes
+pub(crate) trait Transport: Send + 'static {
+    /// Ask the server what it supports
+    fn capabilities(&self) -> Result<ServerCapabilities>;
+    /// Send a single lamp (protocol v1)
+    fn send_lamp(&self, lamp: &LampSubmission) -> Result<()>;
+    /// Send a batch of lamps (protocol v2)
+    fn send_batch(&self, lamps: &[LampSubmission]) -> Result<()>;
+}
+
+/// [`Transport`] over HTTP
+pub(crate) struct HttpTransport {
+    endpoint: String,
+    token: String,
+    agent: ureq::Agent,
+}
+
+impl HttpTransport {
+    pub fn new(api: ApiConfig) -> Self {
+        let config = ureq::Agent::config_builder()
+            .timeout_global(Some(REQUEST_TIMEOUT))
+            .build();
+        Self {
+            endpoint: api.endpoint.trim_end_matches('/').to_string(),
+            token: api.token,
+            agent: config.into(),
+        }
+    }
+
+    fn post(&self, path: &str, body: &str) -> Result<()> {
+        let response = self
+            .agent
+            .post(format!("{}{}", self.endpoint, path))
+            .header("Authorization", &format!("Bearer {}", self.token))
+            .header("Content-Type", "application/json")
+            .send(body)?;
+        debug!("API response ({}): {}", path, response.status());
+        Ok(())
+    }
+}
+
+impl Transport for HttpTransport {
+    fn capabilities(&self) -> Result<ServerCapabilities> {
+        let url = format!("{}/api/capabilities", self.endpoint);
+        let response = self
+            .agent
+            .get(&url)
+            .header("Authorization", &format!("Bearer {}", self.token))
+            .call();
+        match response {
+            Ok(mut response) => {
+                let body = response.body_mut().read_to_string()?;
+                serde_json::from_str(&body)
+                    .map_err(|e| Error::parse("server capabilities", e.to_string()))
+            }
+            // Servers from before protocol v2 don't know the endpoint
+            Err(ureq::Error::StatusCode(status))
+                if (400..500).contains(&status) && status != 429 =>
+            {
+                Ok(ServerCapabilities::V1)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn send_lamp(&self, lamp: &LampSubmission) -> Result<()> {
+        self.post("/api/lamps", &serde_json::to_string(lamp)?)
+    }
+
+    fn send_batch(&self, lamps: &[LampSubmission]) -> Result<()> {
+        let body = serde_json::json!({ "entries": lamps });
+        self.post("/api/lamps/bulk", &body.to_string())
+    }
+}
+
+/// Sends lamps, negotiating the protocol on first use
+struct Client<T: Transport> {
+    transport: T,
+    capabilities: Option<ServerCapabilities>,
+    retry: RetryPolicy,
+}
+
+impl<T: Transport> Client<T> {
+    fn new(transport: T, retry: RetryPolicy) -> Self {
+        Self {
+            transport,
+            capabilities: None,
+            retry,
+        }
+    }
+
+    fn capabilities(&mut self) -> Result<ServerCapabilities> {
+        if let Some(capabilities) = self.capabilities {
+            return Ok(capabilities);
+        }
+        let capabilities = self.transport.capabilities()?;
+        if capabilities.batch_submissions {
+            info!(
+                "Web API protocol v{}: batched lamp submissions",
+                capabilities.protocol
+            );
+        } else {
+            info!(
+                "Web API protocol v{}: single lamp submissions",
+                capabilities.protocol
+            );
+        }
+        self.capabilities = Some(capabilities);
+        Ok(capabilities)
+    }
+
+    /// Send `lamps` in order
+    ///
+    /// Returns how many lamps are done with (sent or rejected by the server)
+    /// and the error that stopped the rest, if any.
+    fn send(&mut self, lamps: &[LampSubmission], batch_size: usize) -> (usize, Option<Error>) {
+        let batch_size = match self.capabilities() {
+            Ok(capabilities) => capabilities.batch_size(batch_size),
+            Err(e) => return (0, Some(e)),
+        };
+        let mut done = 0;
+        for chunk in lamps.chunks(batch_size) {
+            let sent = self.retry.execute(|attempt| {
+                if attempt > 0 {
+                    debug!("Retrying lamp submission (attempt {})", attempt + 1);
+                }
+                match chunk {
+                    [lamp] => self.transport.send_lamp(lamp),
+                    lamps => self.transport.send_batch(lamps),
+                }
+            });
+            match sent {
+                Ok(()) => done += chunk.len(),
+                Err(e) if !e.is_retryable() => {
+                    warn!(
+                        "Web API rejected {} lamps, dropping them: {}",
+                        chunk.len(),
+                        e
+                    );
+                    done += chunk.len();
+                }
+                Err(e) => return (done, Some(e)),
+            }
+        }
+        (done, None)
+    }
+}
+
+/// Lamps waiting to be sent, mirrored to the offline queue file
+pub(crate) struct SubmissionQueue {
+    pending: Vec<LampSubmission>,
+    /// When the oldest lamp of the current batch was queued
+    batch_started: Option<Instant>,
+    /// No flush before this after a failed one, however full the queue is
+    retry_at: Option<Instant>,
+    options: SubmissionOptions,
+}
+
+impl SubmissionQueue {
+    /// Queue with the lamps left in the offline queue file
+    pub fn load(options: SubmissionOptions) -> Self {
+        let pending = match &options.queue_file {
+            Some(path) => match fs::read_to_string(path) {
+                Ok(content) => content
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .filter_map(|line| match serde_json::from_str(line) {
+                        Ok(lamp) => Some(lamp),
+                        Err(e) => {
+                            warn!("Skipping malformed queued lamp: {}", e);
+                            None
+                        }
+                    })
+                    .collect(),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => {
+                    warn!("Failed to read lamp queue {}: {}", path.display(), e);
+                    Vec::new()
+                }
+            },
+            None => Vec::new(),
+        };
+        Self {
+            batch_started: (!pending.is_empty()).then(Instant::now),
+            retry_at: None,
+            pending,
+            options,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn push(&mut self, lamp: LampSubmission, now: Instant) {
+        self.pending.push(lamp);
+        if self.pending.len() > MAX_QUEUED {
+            let dropped = self.pending.len() - MAX_QUEUED;
+            self.pending.drain(..dropped);
+            warn!("Lamp queue is full, dropped the {} oldest lamps", dropped);
+        }
+        self.batch_started.get_or_insert(now);
+        self.save();
+    }
+
+    /// How long until the queue should be flushed (`None` while empty)
+    pub fn time_until_due(&self, now: Instant) -> Option<Duration> {
+        let started = self.batch_started?;
+        if let Some(retry_at) = self.retry_at {
+            return Some(retry_at.saturating_duration_since(now));
+        }
+        if self.pending.len() >= self.options.batch_size {
+            return Some(Duration::ZERO);
+        }
+        Some(
+            self.options
+                .flush_interval
+                .saturating_sub(now.saturating_duration_since(started)),
+        )
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.time_until_due(now) == Some(Duration::ZERO)
+    }
+
+    /// Send the queued lamps, keeping what could not be sent
+    ///
+    /// Returns the number of lamps done with; on error the rest stays queued
+    /// and is due again a flush interval later, even if it fills a batch.
+    fn flush<T: Transport>(&mut self, client: &mut Client<T>, now: Instant) -> Result<usize> {
+        if self.pending.is_empty() {
+            return Ok(0);
+        }
+        let (done, error) = client.send(&self.pending, self.options.batch_size);
+        self.pending.drain(..done);
+        self.batch_started = (!self.pending.is_empty()).then_some(now);
+        self.retry_at = error.is_some().then(|| now + self.options.flush_interval);
+        self.save();
+        match error {
+            Some(e) => Err(e),
+            None => Ok(done),
+        }
+    }
+
+    /// Mirror the queue to the offline queue file (removed when empty)
+    fn save(&self) {
+        let Some(path) = &self.options.queue_file else {
+            return;
+        };
+        if self.pending.is_empty() {
+            if let Err(e) = fs::remove_file(path)
+                && e.kind() != io::ErrorKind::NotFound
+            {
+                warn!("Failed to remove lamp queue {}: {}", path.display(), e);
+            }
+            return;
+        }
+        let mut content = String::new();
+        for lamp in &self.pending {
+            match serde_json::to_string(lamp) {
+                Ok(line) => {
+                    content.push_str(&line);
+                    content.push('\n');
+                }
+                Err(e) => warn!("Failed to serialize queued lamp: {}", e),
+            }
+        }
+        if let Err(e) = crate::storage::write_atomic(path, content) {
+            warn!("Failed to write lamp queue: {}", e);
+        }
+    }
+}
+
+/// Background thread sending lamps in batches
+pub(crate) struct LampSubmitter {
+    sender: Option<Sender<LampSubmission>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl LampSubmitter {
+    /// Start sending to the web API
+    pub fn start(api: ApiConfig, options: SubmissionOptions) -> Self {
+        Self::with_transport(HttpTransport::new(api), options)
+    }
+
+    pub fn with_transport<T: Transport>(transport: T, options: SubmissionOptions) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let retry = options.retry;
+        let queue = SubmissionQueue::load(options);
+        let worker = thread::Builder::new()
+            .name("lamp-submitter".to_string())
+            .spawn(move || run_worker(Client::new(transport, retry), queue, receiver));
+        match worker {
+            Ok(worker) => Self {
+                sender: Some(sender),
+                worker: Some(worker),
+            },
+            Err(e) => {
+                warn!("Failed to start lamp submission thread: {}", e);
+                Self {
+                    sender: None,
+                    worker: None,
+                }
+            }
+        }
+    }
+
+    /// Queue a lamp
+    pub fn submit(&self, lamp: LampSubmission) {
+        if let Some(sender) = &self.sender
+            && sender.send(lamp).is_err()
+        {
+            warn!("Lamp submission thread has stopped; lamp not sent");
+        }
+    }
+
+    /// Send what is still queued and stop the thread
+    pub fn finish(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.sender.take();
+        if let Some(worker) = self.worker.take()
+            && worker.join().is_err()
+        {
+            warn!("Lamp submission thread panicked");
+        }
+    }
+}
+
+impl Drop for LampSubmitter {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn run_worker<T: Transport>(
+    mut client: Client<T>,
+    mut queue: SubmissionQueue,
+    receiver: Receiver<LampSubmission>,
+) {
+    if !queue.is_empty() {
+        info!("Sending {} lamps left in the offline queue", queue.len());
+        flush(&mut queue, &mut client);
+    }
+    loop {
+        let received = match queue.time_until_due(Instant::now()) {
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            Some(wait) => receiver.recv_timeout(wait),
+        };
+        match received {
+            Ok(lamp) => queue.push(lamp, Instant::now()),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                flush(&mut queue, &mut client);
+                return;
+            }
+        }
+        if queue.is_due(Instant::now()) {
+            flush(&mut queue, &mut client);
+        }
+    }
+}
+
+fn flush<T: Transport>(queue: &mut SubmissionQueue, client: &mut Client<T>) {
+    match queue.flush(client, Instant::now()) {
+        Ok(done) => debug!("Sent {} lamps", done),
+        Err(e) => warn!(
+            "Failed to send lamps to API ({} kept in the offline queue): {}",
+            queue.len(),
+            e
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Records requests; fails while `offline` is set
+    #[derive(Clone, Default)]
+    struct MockTransport {
+        capabilities: Option<ServerCapabilities>,
+        offline: Arc<Mutex<bool>>,
+        requests: Arc<Mutex<Vec<Vec<u32>>>>,
+    }
+
+    impl MockTransport {
+        fn v2(max_batch_size: Option<usize>) -> Self {
+            Self {
+                capabilities: Some(ServerCapabilities {
+                    protocol: 2,
+                    batch_submissions: true,
+                    max_batch_size,
+                }),
+                ..Self::default()
+            }
+        }
+
+        fn set_offline(&self, offline: bool) {
+            *self.offline.lock().unwrap() = offline;
+        }
+
+        fn requests(&self) -> Vec<Vec<u32>> {
+            self.requests.lock().unwrap().clone()
+        }
+
+        fn record(&self, lamps: &[LampSubmission]) -> Result<()> {
+            if *self.offline.lock().unwrap() {
+                return Err(Error::Network {
+                    message: "connection refused".to_string(),
+                    retryable: true,
+                });
+            }
+            self.requests
+                .lock()
+                .unwrap()
+                .push(lamps.iter().map(|lamp| lamp.song_id).collect());
+            Ok(())
+        }
+    }
+
+    impl Transport for MockTransport {
+        fn capabilities(&self) -> Result<ServerCapabilities> {
+            if *self.offline.lock().unwrap() {
+                return Err(Error::Network {
+                    message: "connection refused".to_string(),
+                    retryable: true,
+                });
+            }
+            Ok(self.capabilities.unwrap_or(ServerCapabilities::V1))
+        }
+
+        fn send_lamp(&self, lamp: &LampSubmission) -> Result<()> {
+            self.record(std::slice::from_ref(lamp))
+        }
+
+        fn send_batch(&self, lamps: &[LampSubmission]) -> Result<()> {
+            self.record(lamps)
+        }
+    }
+
+    fn lamp(song_id: u32) -> LampSubmission {
+        LampSubmission {
+            song_id,
+            difficulty: "SPA".to_string(),
+            lamp: "HARD".to_string(),
+            ex_score: 2000,
+            miss_count: 5,
+            dj_name: None,
+        }
+    }
+
+    fn options(batch_size: usize, queue_file: Option<PathBuf>) -> SubmissionOptions {
+        SubmissionOptions {
+            batch_size,
+            flush_interval: Duration::from_secs(60),
+            queue_file,
+            ..SubmissionOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_capabilities_json() {
+        let v2: ServerCapabilities =
+            serde_json::from_str(r#"{"protocol":2,"batchSubmissions":true,"maxBatchSize":50}"#)
+                .unwrap();
+        assert_eq!(v2.batch_size(100), 50);
+        assert_eq!(v2.batch_size(10), 10);
+        let bare: ServerCapabilities = serde_json::from_str("{}").unwrap();
+        assert_eq!(bare, ServerCapabilities::V1);
+        assert_eq!(bare.batch_size(10), 1);
+    }
+
+    #[test]
+    fn test_batch_is_due_when_full_or_after_the_interval() {
+        let mut queue = SubmissionQueue::load(options(2, None));
+        let start = Instant::now();
+        assert_eq!(queue.time_until_due(start), None);
+
+        queue.push(lamp(1000), start);
+        assert!(!queue.is_due(start));
+        assert!(queue.is_due(start + Duration::from_secs(60)));
+
+        queue.push(lamp(1001), start + Duration::from_secs(1));
+        assert!(queue.is_due(start + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_v2_server_gets_batches() {
+        let transport = MockTransport::v2(Some(2));
+        let mut client = Client::new(transport.clone(), SubmissionOptions::default().retry);
+        let mut queue = SubmissionQueue::load(options(10, None));
+        for id in 1000..1005 {
+            queue.push(lamp(id), Instant::now());
+        }
+        assert_eq!(queue.flush(&mut client, Instant::now()).unwrap(), 5);
+        assert_eq!(
+            transport.requests(),
+            vec![vec![1000, 1001], vec![1002, 1003], vec![1004]]
+        );
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_v1_server_gets_single_lamps() {
+        let transport = MockTransport::default();
+        let mut client = Client::new(transport.clone(), SubmissionOptions::default().retry);
+        let mut queue = SubmissionQueue::load(options(10, None));
+        queue.push(lamp(1000), Instant::now());
+        queue.push(lamp(1001), Instant::now());
+        queue.flush(&mut client, Instant::now()).unwrap();
+        assert_eq!(transport.requests(), vec![vec![1000], vec![1001]]);
+    }
+
+    #[test]
+    fn test_outage_keeps_lamps_in_the_offline_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queue.jsonl");
+        let transport = MockTransport::v2(None);
+        transport.set_offline(true);
+
+        let mut client = Client::new(transport.clone(), SubmissionOptions::default().retry);
+        let mut queue = SubmissionQueue::load(options(10, Some(path.clone())));
+        queue.push(lamp(1000), Instant::now());
+        queue.push(lamp(1001), Instant::now());
+        assert!(queue.flush(&mut client, Instant::now()).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+        // A later run picks the queue up once the server is back
+        transport.set_offline(false);
+        let mut queue = SubmissionQueue::load(options(10, Some(path.clone())));
+        assert_eq!(queue.len(), 2);
+        queue.flush(&mut client, Instant::now()).unwrap();
+        assert_eq!(transport.requests(), vec![vec![1000, 1001]]);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_failed_flush_backs_off_with_a_full_batch() {
+        let transport = MockTransport::v2(None);
+        transport.set_offline(true);
+        let mut client = Client::new(transport.clone(), SubmissionOptions::default().retry);
+        let mut queue = SubmissionQueue::load(options(2, None));
+        let start = Instant::now();
+        for id in 1000..1003 {
+            queue.push(lamp(id), start);
+        }
+        assert!(queue.is_due(start));
+
+        assert!(queue.flush(&mut client, start).is_err());
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.time_until_due(start), Some(Duration::from_secs(60)));
+        assert!(!queue.is_due(start + Duration::from_secs(59)));
+        assert!(queue.is_due(start + Duration::from_secs(60)));
+
+        // Once the server is back the full batch is due right away again
+        transport.set_offline(false);
+        let later = start + Duration::from_secs(60);
+        assert_eq!(queue.flush(&mut client, later).unwrap(), 3);
+        queue.push(lamp(1003), later);
+        queue.push(lamp(1004), later);
+        assert!(queue.is_due(later));
+    }
+
+    #[test]
+    fn test_submitter_flushes_on_finish() {
+        let transport = MockTransport::v2(None);
+        let submitter = LampSubmitter::with_transport(transport.clone(), options(10, None));
+        submitter.submit(lamp(1000));
+        submitter.submit(lamp(1001));
+        submitter.finish();
+        assert_eq!(transport.requests(), vec![vec![1000, 1001]]);
+    }
+}
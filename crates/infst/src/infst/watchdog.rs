@@ -0,0 +1,135 @@
+//! Watchdog for memory read failure storms
+//!
+//! When the game is minimized or the process handle goes stale, state reads
+//! fail on every poll. Instead of logging each failed read, the watchdog
+//! emits one warning when failures start and one message when reads recover,
+//! backs off the polling interval exponentially, and signals when the
+//! process handle should be reopened.
+
+use std::time::{Duration, Instant};
+
+use tracing::{debug, info, warn};
+
+use crate::config::watchdog::{BASE_BACKOFF, MAX_BACKOFF, REOPEN_AFTER_FAILURES};
+
+/// Tracks consecutive failed polls in the game loop
+#[derive(Debug, Default)]
+pub struct ReadWatchdog {
+    consecutive_failures: u32,
+    failing_since: Option<Instant>,
+    next_reopen_at: u32,
+    reopen_attempts: u32,
+}
+
+impl ReadWatchdog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    /// Record a failed poll
+    ///
+    /// Only the first failure of a storm is logged as a warning.
+    pub fn record_failure(&mut self, context: &str, error: &dyn std::fmt::Display) {
+        if self.consecutive_failures == 0 {
+            warn!(
+                "Memory reads failing ({}: {}); backing off until they recover",
+                context, error
+            );
+            self.failing_since = Some(Instant::now());
+            self.next_reopen_at = REOPEN_AFTER_FAILURES;
+        } else {
+            debug!("Memory read failed again ({}: {})", context, error);
+        }
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+    }
+
+    /// Record a successful poll, logging recovery if reads were failing
+    pub fn record_success(&mut self) {
+        if self.consecutive_failures == 0 {
+            return;
+        }
+        let elapsed = self.failing_since.map(|t| t.elapsed()).unwrap_or_default();
+        info!(
+            "Memory reads recovered after {} failed polls ({:.1}s, {} handle reopen attempts)",
+            self.consecutive_failures,
+            elapsed.as_secs_f64(),
+            self.reopen_attempts
+        );
+        *self = Self::default();
+    }
+
+    /// Delay before the next poll while failing (exponential, capped)
+    pub fn backoff(&self) -> Duration {
+        let exponent = self.consecutive_failures.saturating_sub(1).min(16);
+        BASE_BACKOFF.saturating_mul(1 << exponent).min(MAX_BACKOFF)
+    }
+
+    /// Whether the process handle should be reopened now
+    ///
+    /// Fires after [`REOPEN_AFTER_FAILURES`] failures, then after twice as
+    /// many failures each time.
+    pub fn should_reopen(&mut self) -> bool {
+        if self.consecutive_failures == 0 || self.consecutive_failures < self.next_reopen_at {
+            return false;
+        }
+        self.next_reopen_at = self.consecutive_failures.saturating_mul(2);
+        self.reopen_attempts += 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let mut watchdog = ReadWatchdog::new();
+        assert_eq!(watchdog.consecutive_failures(), 0);
+
+        watchdog.record_failure("test", &"err");
+        assert_eq!(watchdog.backoff(), BASE_BACKOFF);
+        watchdog.record_failure("test", &"err");
+        assert_eq!(watchdog.backoff(), BASE_BACKOFF * 2);
+
+        for _ in 0..20 {
+            watchdog.record_failure("test", &"err");
+        }
+        assert_eq!(watchdog.backoff(), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_reopen_schedule() {
+        let mut watchdog = ReadWatchdog::new();
+        let mut reopen_at = Vec::new();
+        for i in 1..=30 {
+            watchdog.record_failure("test", &"err");
+            if watchdog.should_reopen() {
+                reopen_at.push(i);
+            }
+        }
+        assert_eq!(
+            reopen_at,
+            vec![
+                REOPEN_AFTER_FAILURES,
+                REOPEN_AFTER_FAILURES * 2,
+                REOPEN_AFTER_FAILURES * 4
+            ]
+        );
+    }
+
+    #[test]
+    fn test_success_resets() {
+        let mut watchdog = ReadWatchdog::new();
+        for _ in 0..3 {
+            watchdog.record_failure("test", &"err");
+        }
+        watchdog.record_success();
+        assert_eq!(watchdog.consecutive_failures(), 0);
+        assert!(!watchdog.should_reopen());
+    }
+}
@@ -0,0 +1,57 @@
+//! System clipboard access.
+//!
+//! Used to copy the last play summary for pasting into chat.
+
+use std::io;
+
+use crate::error::Result;
+
+/// `CF_UNICODETEXT` clipboard format (UTF-16, NUL-terminated)
+#[cfg(target_os = "windows")]
+const CF_UNICODETEXT: u32 = 13;
+
+/// Replace the clipboard content with `text`.
+#[cfg(target_os = "windows")]
+pub fn set_text(text: &str) -> Result<()> {
+    use windows::Win32::Foundation::{HANDLE, HWND};
+    use windows::Win32::System::DataExchange::{
+        CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData,
+    };
+    use windows::Win32::System::Memory::{GMEM_MOVEABLE, GlobalAlloc, GlobalLock, GlobalUnlock};
+
+    let os_error = |e: windows::core::Error| io::Error::other(e.to_string());
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let size = wide.len() * std::mem::size_of::<u16>();
+
+    // SAFETY: The clipboard is opened before and closed after use on every
+    // path. The global memory block is sized for `wide`, written only while
+    // locked, and owned by the system once SetClipboardData succeeds.
+    unsafe {
+        OpenClipboard(HWND::default()).map_err(os_error)?;
+        let result = (|| {
+            EmptyClipboard()?;
+            let memory = GlobalAlloc(GMEM_MOVEABLE, size)?;
+            let target = GlobalLock(memory) as *mut u16;
+            if target.is_null() {
+                return Err(windows::core::Error::from_win32());
+            }
+            std::ptr::copy_nonoverlapping(wide.as_ptr(), target, wide.len());
+            // Returns an error once the lock count reaches zero, which is expected
+            let _ = GlobalUnlock(memory);
+            SetClipboardData(CF_UNICODETEXT, HANDLE(memory.0))?;
+            Ok(())
+        })();
+        let _ = CloseClipboard();
+        result.map_err(os_error)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn set_text(_text: &str) -> Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "clipboard access is only supported on Windows",
+    )
+    .into())
+}
@@ -0,0 +1,531 @@
+//! Global hotkeys for tracking mode.
+//!
+//! Hotkeys are written as `<action>=<keys>`, e.g. `export=ctrl+alt+e`:
+//!
+//! - actions: `export`, `pause`, `marquee`, `copy` (see [`HotkeyAction`])
+//! - keys: modifiers (`ctrl`, `alt`, `shift`, `win`) and one of `a`-`z`,
+//!   `0`-`9` or `f1`-`f24`, joined by `+`
+//!
+//! Every hotkey needs `ctrl`, `alt` or `win`: a bare or shift-only key would
+//! be taken from the game. Hotkeys are registered system-wide, so they work
+//! while the game has focus (Windows only).
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+use crate::session::SessionCommand;
+
+/// What a hotkey does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HotkeyAction {
+    /// Export the tracker file now
+    Export,
+    /// Pause or resume the session
+    Pause,
+    /// Hide or show the stream marquee
+    Marquee,
+    /// Copy the last play summary to the clipboard
+    Copy,
+}
+
+impl HotkeyAction {
+    /// All actions, in display order
+    pub const ALL: [Self; 4] = [Self::Export, Self::Pause, Self::Marquee, Self::Copy];
+
+    /// Name used in bindings
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Export => "export",
+            Self::Pause => "pause",
+            Self::Marquee => "marquee",
+            Self::Copy => "copy",
+        }
+    }
+
+    /// Session command queued for the tracker
+    pub fn command(self) -> SessionCommand {
+        match self {
+            Self::Export => SessionCommand::Export,
+            Self::Pause => SessionCommand::TogglePause,
+            Self::Marquee => SessionCommand::ToggleMarquee,
+            Self::Copy => SessionCommand::Copy,
+        }
+    }
+}
+
+impl fmt::Display for HotkeyAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for HotkeyAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                format!("unknown hotkey action {s:?} (expected export, pause, marquee or copy)")
+            })
+    }
+}
+
+/// Non-modifier key of a hotkey
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// `A`-`Z` or `0`-`9` (letters are stored uppercase)
+    Char(char),
+    /// `F1`-`F24`
+    Function(u8),
+}
+
+impl Key {
+    /// Windows virtual-key code
+    pub fn virtual_key(self) -> u32 {
+        match self {
+            // VK_0..VK_9 and VK_A..VK_Z are the ASCII codes
+            Self::Char(c) => c as u32,
+            // VK_F1 = 0x70
+            Self::Function(n) => 0x6F + n as u32,
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Char(c) => write!(f, "{c}"),
+            Self::Function(n) => write!(f, "F{n}"),
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next())
+            && c.is_ascii_alphanumeric()
+        {
+            return Ok(Self::Char(c.to_ascii_uppercase()));
+        }
+        if let Some(n) = s.strip_prefix(['f', 'F'])
+            && let Ok(n) = n.parse::<u8>()
+            && (1..=24).contains(&n)
+        {
+            return Ok(Self::Function(n));
+        }
+        Err(format!("unknown key {s:?} (expected a-z, 0-9 or f1-f24)"))
+    }
+}
+
+/// Key combination, e.g. `Ctrl+Alt+E`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hotkey {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub win: bool,
+    pub key: Key,
+}
+
+impl Hotkey {
+    /// Whether the combination leaves the key usable in the game
+    fn has_modifier(&self) -> bool {
+        self.ctrl || self.alt || self.win
+    }
+
+    /// Combinations the OS keeps for itself
+    fn is_reserved(&self) -> bool {
+        let only_alt = self.alt && !self.ctrl && !self.win;
+        (only_alt && self.key == Key::Function(4)) || (self.win && self.key == Key::Char('L'))
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (held, name) in [
+            (self.ctrl, "Ctrl+"),
+            (self.alt, "Alt+"),
+            (self.shift, "Shift+"),
+            (self.win, "Win+"),
+        ] {
+            if held {
+                f.write_str(name)?;
+            }
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for Hotkey {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let (key, modifiers) = parts.split_last().expect("split yields at least one part");
+        let mut hotkey = Hotkey {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            win: false,
+            key: key.parse()?,
+        };
+        for modifier in modifiers {
+            let held = match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut hotkey.ctrl,
+                "alt" => &mut hotkey.alt,
+                "shift" => &mut hotkey.shift,
+                "win" | "super" => &mut hotkey.win,
+                _ => return Err(format!("unknown modifier {modifier:?} in {s:?}")),
+            };
+            if *held {
+                return Err(format!("modifier {modifier:?} repeated in {s:?}"));
+            }
+            *held = true;
+        }
+        Ok(hotkey)
+    }
+}
+
+/// Hotkey bound to an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HotkeyBinding {
+    pub action: HotkeyAction,
+    pub hotkey: Hotkey,
+}
+
+impl fmt::Display for HotkeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.action, self.hotkey)
+    }
+}
+
+impl FromStr for HotkeyBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (action, hotkey) = s
+            .split_once('=')
+            .ok_or_else(|| format!("invalid hotkey {s:?} (expected <action>=<keys>)"))?;
+        Ok(Self {
+            action: action.parse()?,
+            hotkey: hotkey.parse()?,
+        })
+    }
+}
+
+/// Default bindings (`Ctrl+Alt+` the action's initial)
+pub const DEFAULT_HOTKEYS: [&str; 4] = [
+    "export=ctrl+alt+e",
+    "pause=ctrl+alt+p",
+    "marquee=ctrl+alt+m",
+    "copy=ctrl+alt+c",
+];
+
+/// Hotkey settings for tracking mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HotkeyConfig {
+    /// Register hotkeys at all
+    pub enabled: bool,
+    /// One binding per action (actions without a binding have no hotkey)
+    pub bindings: Vec<HotkeyBinding>,
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            bindings: DEFAULT_HOTKEYS
+                .iter()
+                .map(|binding| binding.parse().expect("default hotkeys are valid"))
+                .collect(),
+        }
+    }
+}
+
+impl HotkeyConfig {
+    /// No hotkeys
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Replace the binding of each overridden action
+    pub fn with_overrides(mut self, overrides: impl IntoIterator<Item = HotkeyBinding>) -> Self {
+        for binding in overrides {
+            self.bindings.retain(|b| b.action != binding.action);
+            self.bindings.push(binding);
+        }
+        self.bindings.sort_by_key(|b| b.action as u8);
+        self
+    }
+
+    /// Check for hotkeys the game or the OS would lose, and for two actions
+    /// on the same keys
+    pub fn validate(&self) -> Result<()> {
+        for (i, binding) in self.bindings.iter().enumerate() {
+            if !binding.hotkey.has_modifier() {
+                return Err(Error::invalid_config(
+                    "hotkeys",
+                    format!("{binding}: needs Ctrl, Alt or Win (the key is used in the game)"),
+                ));
+            }
+            if binding.hotkey.is_reserved() {
+                return Err(Error::invalid_config(
+                    "hotkeys",
+                    format!("{binding}: {} is reserved by Windows", binding.hotkey),
+                ));
+            }
+            if let Some(other) = self.bindings[..i]
+                .iter()
+                .find(|other| other.hotkey == binding.hotkey)
+            {
+                return Err(Error::invalid_config(
+                    "hotkeys",
+                    format!(
+                        "{} is bound to both {} and {}",
+                        binding.hotkey, other.action, binding.action
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Background thread receiving the registered hotkeys
+///
+/// Hotkeys are unregistered when the listener is dropped.
+pub struct HotkeyListener {
+    stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    handle: Option<std::thread::JoinHandle<()>>,
+    /// Bindings another application had already registered
+    unavailable: Vec<HotkeyBinding>,
+}
+
+impl HotkeyListener {
+    /// Bindings that could not be registered (in use by another application)
+    pub fn unavailable(&self) -> &[HotkeyBinding] {
+        &self.unavailable
+    }
+
+    /// Register `config`'s hotkeys and call `on_hotkey` from a background
+    /// thread when one is pressed
+    #[cfg(target_os = "windows")]
+    pub fn start<F>(config: &HotkeyConfig, mut on_hotkey: F) -> Result<Self>
+    where
+        F: FnMut(HotkeyAction) + Send + 'static,
+    {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::{Arc, mpsc};
+        use std::time::Duration;
+
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::UI::Input::KeyboardAndMouse::{
+            HOT_KEY_MODIFIERS, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT, MOD_WIN,
+            RegisterHotKey, UnregisterHotKey,
+        };
+        use windows::Win32::UI::WindowsAndMessaging::{MSG, PM_REMOVE, PeekMessageW, WM_HOTKEY};
+
+        config.validate()?;
+        let bindings = if config.enabled {
+            config.bindings.clone()
+        } else {
+            Vec::new()
+        };
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let (registered_tx, registered_rx) = mpsc::channel();
+
+        // Hotkeys registered without a window are delivered to the
+        // registering thread's message queue, so register them on the
+        // listener thread.
+        let handle = std::thread::spawn(move || {
+            let mut active = Vec::new();
+            let mut unavailable = Vec::new();
+            for (id, binding) in bindings.iter().enumerate() {
+                let hotkey = binding.hotkey;
+                let mut modifiers = MOD_NOREPEAT;
+                for (held, modifier) in [
+                    (hotkey.ctrl, MOD_CONTROL),
+                    (hotkey.alt, MOD_ALT),
+                    (hotkey.shift, MOD_SHIFT),
+                    (hotkey.win, MOD_WIN),
+                ] {
+                    if held {
+                        modifiers = HOT_KEY_MODIFIERS(modifiers.0 | modifier.0);
+                    }
+                }
+                // SAFETY: Registers a hotkey for this thread; unregistered below
+                match unsafe {
+                    RegisterHotKey(
+                        HWND::default(),
+                        id as i32,
+                        modifiers,
+                        hotkey.key.virtual_key(),
+                    )
+                } {
+                    Ok(()) => active.push(id),
+                    Err(_) => unavailable.push(*binding),
+                }
+            }
+            let _ = registered_tx.send(unavailable);
+
+            while !thread_stop.load(Ordering::SeqCst) {
+                let mut msg = MSG::default();
+                // SAFETY: `msg` is a valid out parameter; only WM_HOTKEY is removed
+                while unsafe {
+                    PeekMessageW(&mut msg, HWND::default(), WM_HOTKEY, WM_HOTKEY, PM_REMOVE)
+                }
+                .as_bool()
+                {
+                    if let Some(binding) = bindings.get(msg.wParam.0) {
+                        on_hotkey(binding.action);
+                    }
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+
+            for id in active {
+                // SAFETY: Same thread and id as the registration above
+                let _ = unsafe { UnregisterHotKey(HWND::default(), id as i32) };
+            }
+        });
+
+        let unavailable = registered_rx.recv().unwrap_or_default();
+        Ok(Self {
+            stop,
+            handle: Some(handle),
+            unavailable,
+        })
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    pub fn start<F>(config: &HotkeyConfig, _on_hotkey: F) -> Result<Self>
+    where
+        F: FnMut(HotkeyAction) + Send + 'static,
+    {
+        config.validate()?;
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "global hotkeys are only supported on Windows",
+        )
+        .into())
+    }
+}
+
+impl Drop for HotkeyListener {
+    fn drop(&mut self) {
+        self.stop.store(true, std::sync::atomic::Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(s: &str) -> HotkeyBinding {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_binding() {
+        let parsed = binding("export=Ctrl+Shift+F5");
+        assert_eq!(parsed.action, HotkeyAction::Export);
+        assert!(parsed.hotkey.ctrl && parsed.hotkey.shift);
+        assert!(!parsed.hotkey.alt && !parsed.hotkey.win);
+        assert_eq!(parsed.hotkey.key, Key::Function(5));
+        assert_eq!(parsed.to_string(), "export=Ctrl+Shift+F5");
+
+        assert_eq!(binding("copy=alt+ctrl+c").to_string(), "copy=Ctrl+Alt+C");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for s in [
+            "ctrl+alt+e",
+            "launch=ctrl+alt+e",
+            "export=ctrl+alt",
+            "export=ctrl+alt+enter",
+            "export=ctrl+f25",
+            "export=hyper+e",
+            "export=ctrl+ctrl+e",
+        ] {
+            assert!(s.parse::<HotkeyBinding>().is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_virtual_keys() {
+        assert_eq!(Key::Char('E').virtual_key(), 0x45);
+        assert_eq!(Key::Char('0').virtual_key(), 0x30);
+        assert_eq!(Key::Function(1).virtual_key(), 0x70);
+        assert_eq!(Key::Function(24).virtual_key(), 0x87);
+    }
+
+    #[test]
+    fn test_defaults_are_valid() {
+        let config = HotkeyConfig::default();
+        assert!(config.enabled);
+        assert_eq!(config.bindings.len(), HotkeyAction::ALL.len());
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_overrides_replace_action() {
+        let config = HotkeyConfig::default().with_overrides([binding("copy=ctrl+shift+c")]);
+        let copy: Vec<_> = config
+            .bindings
+            .iter()
+            .filter(|b| b.action == HotkeyAction::Copy)
+            .collect();
+        assert_eq!(copy.len(), 1);
+        assert_eq!(copy[0].hotkey, "ctrl+shift+c".parse().unwrap());
+        assert_eq!(config.bindings.len(), 4);
+    }
+
+    #[test]
+    fn test_validate_conflicts() {
+        let invalid = |overrides: &[&str]| {
+            let config =
+                HotkeyConfig::default().with_overrides(overrides.iter().map(|s| binding(s)));
+            match config.validate() {
+                Err(Error::InvalidConfig { field, message }) => {
+                    assert_eq!(field, "hotkeys");
+                    message
+                }
+                other => panic!("expected a conflict, got {other:?}"),
+            }
+        };
+
+        assert!(invalid(&["copy=ctrl+alt+e"]).contains("bound to both export and copy"));
+        assert!(invalid(&["pause=p"]).contains("needs Ctrl, Alt or Win"));
+        assert!(invalid(&["pause=shift+p"]).contains("needs Ctrl, Alt or Win"));
+        assert!(invalid(&["export=alt+f4"]).contains("reserved"));
+        assert!(invalid(&["export=win+l"]).contains("reserved"));
+    }
+
+    #[test]
+    fn test_disabled() {
+        let config = HotkeyConfig::disabled();
+        assert!(!config.enabled);
+        assert!(config.bindings.is_empty());
+    }
+
+    #[test]
+    fn test_action_commands() {
+        assert_eq!(HotkeyAction::Copy.command(), SessionCommand::Copy);
+        assert_eq!(HotkeyAction::Pause.command(), SessionCommand::TogglePause);
+    }
+}
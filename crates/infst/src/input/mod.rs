@@ -1,3 +1,6 @@
-//! Window management for game interaction.
+//! Window management, clipboard access and global hotkeys.
 
+pub mod clipboard;
+#[cfg(feature = "session")]
+pub mod hotkeys;
 pub mod window;
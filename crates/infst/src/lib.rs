@@ -10,78 +10,178 @@
 //!
 //! ## Feature Flags
 //!
+//! - `session` (default): The tracker loop (`Infst`), session files and tournaments.
+//! - `stream` (default): Stream overlay output (marquee text, result cards).
+//! - `network`: Web API client (lamp submission). `api` is accepted as an alias.
 //! - `debug-tools`: Enables debug utilities for memory analysis and offset verification.
 //!   This feature is intended for CLI tools and development, not production use.
+//! - `render`: Enables PNG rendering (result card images for stream overlays).
+//! - `notify`: Push notifications for milestones (enables `session`, `stream` and `network`).
+//!
+//! With `--no-default-features` the crate is a lean memory reading and
+//! parsing library: process access, offsets, charts, scores, exports and stats.
+//!
+//! ## Parsing without a process
+//!
+//! Song entries, score map nodes and the judge block are parsed through
+//! [`ReadMemory`] or from byte slices, never from a process handle directly.
+//! A [`MemorySnapshot`] captured from a live reader can stand in for the
+//! game, and tracker TSV/JSON generation works on in-memory data. Built with
+//! `--no-default-features` the crate compiles for `wasm32-unknown-unknown`
+//! (process access there always fails), so a browser viewer can reuse the
+//! parsers on snapshots and recorded sessions.
 
 pub mod chart;
+pub mod clock;
 pub mod config;
 #[cfg(feature = "debug-tools")]
 pub mod debug;
 pub mod error;
 pub mod export;
+#[cfg(feature = "session")]
 pub mod infst;
 pub mod input;
+#[cfg(feature = "notify")]
+pub mod notify;
 pub mod offset;
+pub mod paths;
 pub mod play;
 pub mod prelude;
 pub mod process;
+#[cfg(feature = "render")]
+pub mod render;
 pub mod retry;
 pub mod score;
+#[cfg(feature = "session")]
 pub mod session;
+#[cfg(feature = "sim")]
+pub mod sim;
+pub mod stats;
+pub mod storage;
+#[cfg(feature = "stream")]
+pub mod stream;
+#[cfg(feature = "session")]
+pub mod tournament;
+pub mod util;
 
 // Re-export from chart module
 pub use chart::{
-    Chart, ChartInfo, Difficulty, SongInfo, UnlockData, fetch_song_database,
+    AnchorReport, CanonicalSongList, ChartId, ChartInfo, ChartMatch, ChartQuery, CustomTypes,
+    DensityMap, DensityProfile, Difficulty, Purchasability, SongInfo, SongMismatch, UnlockData,
+    VerificationReport, bits_unlock_cost, check_anchor_songs, fetch_song_database,
     fetch_song_database_bulk, get_unlock_state_for_difficulty, get_unlock_states,
+    is_chart_unlocked, search_charts, verify_song_database,
 };
 
+// Re-export from clock module
+pub use clock::{Clock, MockClock, OffsetClock, SharedClock, SystemClock};
+
 // Re-export from config module
 pub use config::{check_version_match, extract_date_code, find_game_version};
 
 // Re-export from error module
-pub use error::{Error, Result};
+pub use error::{Error, ErrorCategory, Result};
 
 // Re-export from process module
 pub use process::launcher;
 pub use process::{
-    ByteBuffer, MemoryReader, ProcessHandle, ProcessInfo, ProcessProvider, ReadMemory,
+    AccessAudit, AuditReport, ByteBuffer, MemoryReader, MemorySnapshot, ModuleFingerprint,
+    ProcessCandidate, ProcessHandle, ProcessInfo, ProcessMatcher, ProcessProvider,
+    ProcessSelection, ReadMemory, ReadOnlyMemory, ReadPolicy, SystemProcessProvider,
     decode_shift_jis, decode_shift_jis_to_string,
 };
 
+// Re-export from notify module
+#[cfg(feature = "notify")]
+pub use notify::{Milestone, NotifyRule, PushConfig, PushNotifier};
+
 // Re-export from offset module
 pub use offset::{
-    CodeSignature, InteractiveSearchResult, JudgeInput, OffsetCache, OffsetDump, OffsetSearcher,
-    OffsetSearcherBuilder, OffsetSignatureEntry, OffsetSignatureSet, OffsetsCollection,
-    SearchPrompter, SearchResult, builtin_signatures, load_offsets, load_signatures, save_offsets,
-    save_offsets_to_cache, save_signatures, try_load_cached_offsets,
+    CodeSignature, GameLayout, InteractiveSearchResult, JudgeInput, LayoutRegistry, OffsetCache,
+    OffsetDump, OffsetSearcher, OffsetSearcherBuilder, OffsetSignatureEntry, OffsetSignatureSet,
+    OffsetsCollection, RelativeOffsets, ScriptedPrompter, SearchPrompter, SearchResult, SongAnchor,
+    builtin_signatures, invalidate_cached_offsets, load_offsets, load_signatures,
+    merge_signature_file, pin_song_anchors, save_offsets, save_offsets_to_cache, save_signatures,
+    song_list_moved, try_load_cached_offsets,
 };
 
+// Re-export from paths module
+pub use paths::Paths;
+
 // Re-export from play module
 pub use play::{
-    AssistType, GameState, GameStateDetector, PlayData, PlayType, RangeType, Settings, Style,
-    UnlockType, calculate_dj_points, calculate_dj_points_from_score,
+    AnomalyMode, AssistType, CourseResult, CourseTracker, Currency, DanRank, DuplicateGuard,
+    GameState, GameStateDetector, LaneCover, PlayAnomaly, PlayData, PlaySet, PlayType,
+    PrematureMode, ProfileInfo, RangeType, Settings, Style, UnlockType, calculate_dj_points,
+    calculate_dj_points_from_score, read_currency, read_lane_cover, read_profile,
 };
 
 // Re-export from infst module
-pub use infst::{ApiConfig, GameData, Infst, InfstConfig, InfstConfigBuilder};
+#[cfg(feature = "session")]
+pub use infst::{
+    ApiConfig, ConsentHandler, GameData, Infst, InfstBuilder, InfstConfig, InfstConfigBuilder,
+    PlayHandler, TrackerExit, UploadHandler,
+};
 
 // Re-export from retry module
-pub use retry::{ExponentialBackoff, FixedDelay, NoRetry, RetryStrategy};
+pub use retry::{
+    ExponentialBackoff, FixedDelay, NoRetry, RetryOperation, RetryOverride, RetryPolicies,
+    RetryPolicy, RetryStrategy,
+};
 
 // Re-export from score module
-pub use score::{Grade, Judge, Lamp, ScoreData, ScoreMap};
+pub use score::{
+    AssistedBests, AuditEntry, ChartBest, Grade, Judge, Lamp, ProfileMismatch, ScoreAudit,
+    ScoreData, ScoreDelta, ScoreMap,
+};
 
 // Re-export from export module
 pub use export::{
-    ExportFormat, JsonExporter, TsvExporter, TsvRowData, export_song_list, export_tracker_json,
-    export_tracker_tsv, format_tracker_tsv_header, generate_tracker_json, generate_tracker_tsv,
+    ChartImprovement, DisplayTimezone, ExportFilter, ExportFormat, JsonExporter, MergeConflict,
+    PlayStyle, SignatureStatus, SigningKey, TRACKER_JSON_SCHEMA_VERSION, TitleAliases,
+    TitleLanguage, TitleOptions, Tracker, TrackerJsonExtras, TrackerMerge, TsvExporter, TsvRowData,
+    diff_tracker_tsv, export_song_list, export_tracker_json, export_tracker_tsv,
+    format_tracker_tsv_header, generate_tracker_json, generate_tracker_json_filtered,
+    generate_tracker_tsv, generate_tracker_tsv_filtered, generate_tracker_tsv_with_analytics,
+    sign_export, tracker_changelog, tracker_json_schema, tracker_snapshot_path, verify_export,
 };
 
 // Re-export from session module
-pub use session::SessionManager;
+#[cfg(feature = "session")]
+pub use session::{
+    ExportAction, ExportScheduler, NoteKind, PauseMode, ScheduleEntry, ScheduleTrigger,
+    ScheduledExport, SessionCommand, SessionControl, SessionManager, SessionNote,
+    load_schedule_status, load_session_history, parse_session_tsv,
+};
+
+// Re-export from storage module
+pub use storage::{
+    BmsHashMap, IgnoreList, SongTags, TextEncoding, export_beatoraja, generate_beatoraja_json,
+    generate_beatoraja_json_filtered,
+};
+
+// Re-export from stats module
+pub use stats::{
+    BpiTable, ChartAnalytics, DenseFailReport, GreenNumberAlert, GreenNumberBaseline,
+    GreenNumberChange, GreenNumberLog, PlayCounters, PlayCounts, PlayTime, RecommendOptions,
+    Recommendation, RecommendationPlan, UnlockCandidate, UnlockPlan, UnlockPlanOptions,
+    chart_analytics, collect_chart_analytics, dense_section_fails, locked_bit_charts, plan_unlocks,
+    recommend_dj_points,
+};
+
+// Re-export from stream module
+#[cfg(feature = "stream")]
+pub use stream::{MarqueeTemplate, StreamConfig, StreamOutput};
+
+// Re-export from tournament module
+#[cfg(feature = "session")]
+pub use tournament::{HeadToHead, Participant, PointRules, Standing, Standings, Tournament};
 
 // Debug utilities (requires debug-tools feature)
 #[cfg(feature = "debug-tools")]
 pub use debug::{
-    DumpInfo, MemoryDump, OffsetStatus, OffsetValidation, ScanResult, ScannedSong, StatusInfo,
+    Annotation, CheckStatus, DiscoveredLayout, DumpInfo, FieldCandidate, FieldDiff,
+    LayoutDiscovery, MemoryAnnotator, MemoryDump, OffsetStatus, OffsetValidation, ParserCheck,
+    PointerKind, RawScoreMapDump, RawScoreNode, ScanResult, ScannedSong, SelfTestReport,
+    StatusInfo, StructLayout, SubsystemHealth, classify_pointer, diff_struct,
 };
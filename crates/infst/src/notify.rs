@@ -0,0 +1,492 @@
+//! Push notifications for milestones
+//!
+//! A [`PushNotifier`] posts a message to an HTTP endpoint (an ntfy.sh topic,
+//! Pushover, a chat webhook...) when a recorded play reaches one of the
+//! configured milestones, such as the first clear of a level 12 or AAA on a
+//! target chart. Each [`NotifyRule`] picks an event and optionally a level
+//! and a list of charts.
+//!
+//! The request body is a [`MarqueeTemplate`] with the marquee placeholders
+//! and `{milestone}`. When the content type is JSON, values are escaped so
+//! they can be placed inside JSON strings:
+//!
+//! ```text
+//! {{"token":"...","user":"...","message":"{milestone}: {title} [{diff}]"}}
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use tracing::{debug, info, warn};
+
+use crate::chart::ChartId;
+use crate::error::{Error, Result};
+use crate::export::DisplayTimezone;
+use crate::play::PlayData;
+use crate::retry::{RetryPolicy, RetryStrategy};
+use crate::score::{Grade, Lamp, ScoreData};
+use crate::stream::{MarqueeTemplate, marquee_values};
+
+/// Default notification body
+pub const DEFAULT_PUSH_TEMPLATE: &str =
+    "{milestone}: {title} [{diff} {level}] {lamp} {grade} EX {score}";
+
+/// Default request content type
+pub const DEFAULT_PUSH_CONTENT_TYPE: &str = "text/plain; charset=utf-8";
+
+/// Placeholders available in push templates besides the marquee ones
+pub const PUSH_PLACEHOLDERS: &[&str] = &["milestone"];
+
+/// Timeout of a notification request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Event a notification can be sent for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Milestone {
+    /// First play with at least this lamp on the chart
+    Lamp(Lamp),
+    /// First play with at least this grade on the chart
+    Grade(Grade),
+    /// EX score above the previous best
+    PersonalBest,
+}
+
+impl Milestone {
+    /// Whether `play` reaches the milestone, given the best before it
+    pub fn reached(&self, play: &PlayData, best: Option<&ScoreData>) -> bool {
+        let difficulty = play.chart.difficulty;
+        let best_score = best.map_or(0, |best| best.get_score(difficulty));
+        match *self {
+            Self::Lamp(lamp) => {
+                play.lamp >= lamp && best.map_or(Lamp::NoPlay, |b| b.get_lamp(difficulty)) < lamp
+            }
+            Self::Grade(grade) => {
+                play.grade >= grade
+                    && (best_score == 0
+                        || PlayData::calculate_grade(best_score, play.chart.total_notes) < grade)
+            }
+            Self::PersonalBest => best_score > 0 && play.ex_score > best_score,
+        }
+    }
+
+    /// Text for the `{milestone}` placeholder
+    pub fn label(&self) -> String {
+        match self {
+            Self::Lamp(lamp) => format!("First {}", lamp.expand_name()),
+            Self::Grade(grade) => format!("First {}", grade.short_name()),
+            Self::PersonalBest => "New personal best".to_string(),
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Self::Lamp(Lamp::HardClear) => "hard".to_string(),
+            Self::Lamp(Lamp::ExHardClear) => "exhard".to_string(),
+            Self::Lamp(Lamp::FullCombo) => "fc".to_string(),
+            Self::Lamp(lamp) => lamp.short_name().to_ascii_lowercase(),
+            Self::Grade(grade) => grade.short_name().to_ascii_lowercase(),
+            Self::PersonalBest => "pb".to_string(),
+        }
+    }
+}
+
+impl FromStr for Milestone {
+    type Err = Error;
+
+    /// Parse `clear`, `hard`, `exhard`, `fc`, `easy`, a grade (`aa`, `aaa`) or `pb`
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim().to_ascii_lowercase();
+        Ok(match name.as_str() {
+            "easy" => Self::Lamp(Lamp::EasyClear),
+            "clear" => Self::Lamp(Lamp::Clear),
+            "hard" => Self::Lamp(Lamp::HardClear),
+            "exhard" => Self::Lamp(Lamp::ExHardClear),
+            "fc" => Self::Lamp(Lamp::FullCombo),
+            "pb" => Self::PersonalBest,
+            _ => match name.to_ascii_uppercase().parse::<Grade>() {
+                Ok(grade) if grade >= Grade::F => Self::Grade(grade),
+                _ => {
+                    return Err(Error::parse(
+                        "milestone",
+                        format!(
+                            "unknown event {:?} (expected easy, clear, hard, exhard, fc, a grade or pb)",
+                            s
+                        ),
+                    ));
+                }
+            },
+        })
+    }
+}
+
+/// A milestone to notify about, optionally limited to a level or charts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotifyRule {
+    pub milestone: Milestone,
+    /// Only charts of this level
+    pub level: Option<u8>,
+    /// Only these charts (any chart when empty)
+    pub charts: Vec<ChartId>,
+}
+
+impl NotifyRule {
+    /// Whether `play` triggers this rule
+    pub fn matches(&self, play: &PlayData, best: Option<&ScoreData>) -> bool {
+        self.level.is_none_or(|level| play.chart.level == level)
+            && (self.charts.is_empty() || self.charts.contains(&play.chart.id()))
+            && self.milestone.reached(play, best)
+    }
+}
+
+impl FromStr for NotifyRule {
+    type Err = Error;
+
+    /// Parse `<event>[:<level>][@<chart>,...]`, e.g. `clear:12` or
+    /// `aaa@1000:SPA,1001:SPH`
+    fn from_str(s: &str) -> Result<Self> {
+        let (event, charts) = match s.split_once('@') {
+            Some((event, charts)) => (event, Some(charts)),
+            None => (s, None),
+        };
+        let (milestone, level) = match event.split_once(':') {
+            Some((milestone, level)) => (
+                milestone,
+                Some(level.trim().parse::<u8>().map_err(|_| {
+                    Error::parse("milestone", format!("invalid level {:?} in {:?}", level, s))
+                })?),
+            ),
+            None => (event, None),
+        };
+        let charts = match charts {
+            Some(charts) => charts
+                .split(',')
+                .map(str::parse)
+                .collect::<Result<Vec<ChartId>>>()?,
+            None => Vec::new(),
+        };
+        Ok(Self {
+            milestone: milestone.parse()?,
+            level,
+            charts,
+        })
+    }
+}
+
+impl fmt::Display for NotifyRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.milestone.name())?;
+        if let Some(level) = self.level {
+            write!(f, ":{}", level)?;
+        }
+        if !self.charts.is_empty() {
+            let charts: Vec<String> = self.charts.iter().map(ToString::to_string).collect();
+            write!(f, "@{}", charts.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Where and when push notifications are sent
+#[derive(Debug, Clone)]
+pub struct PushConfig {
+    /// URL the notification is posted to
+    pub url: String,
+    /// Request body template (see the module docs)
+    pub template: String,
+    /// `Content-Type` of the request
+    pub content_type: String,
+    /// Extra request headers (e.g. `Title` or `Authorization`)
+    pub headers: Vec<(String, String)>,
+    /// Milestones to notify about
+    pub rules: Vec<NotifyRule>,
+}
+
+impl PushConfig {
+    /// Notify `url` about `rules` with the default template
+    pub fn new(url: impl Into<String>, rules: Vec<NotifyRule>) -> Self {
+        Self {
+            url: url.into(),
+            template: DEFAULT_PUSH_TEMPLATE.to_string(),
+            content_type: DEFAULT_PUSH_CONTENT_TYPE.to_string(),
+            headers: Vec::new(),
+            rules,
+        }
+    }
+
+    /// Check the URL, rules and template
+    pub fn validate(&self) -> Result<()> {
+        if !(self.url.starts_with("http://") || self.url.starts_with("https://")) {
+            return Err(Error::invalid_config(
+                "push",
+                format!("URL {:?} is not an http(s) URL", self.url),
+            ));
+        }
+        if self.rules.is_empty() {
+            return Err(Error::invalid_config(
+                "push",
+                "no milestones to notify about",
+            ));
+        }
+        MarqueeTemplate::parse_with(&self.template, PUSH_PLACEHOLDERS)
+            .map_err(|e| Error::invalid_config("push", e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Sends push notifications for recorded plays
+pub struct PushNotifier {
+    config: PushConfig,
+    template: MarqueeTemplate,
+    timezone: DisplayTimezone,
+}
+
+impl PushNotifier {
+    pub fn new(config: PushConfig) -> Result<Self> {
+        config.validate()?;
+        let template = MarqueeTemplate::parse_with(&config.template, PUSH_PLACEHOLDERS)?;
+        Ok(Self {
+            config,
+            template,
+            timezone: DisplayTimezone::default(),
+        })
+    }
+
+    /// Format play times in `timezone`
+    pub fn with_timezone(mut self, timezone: DisplayTimezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Notification bodies for the milestones `play` reaches, one per milestone
+    pub fn messages(&self, play: &PlayData, best: Option<&ScoreData>) -> Vec<String> {
+        let mut reached: Vec<Milestone> = Vec::new();
+        for rule in &self.config.rules {
+            if !reached.contains(&rule.milestone) && rule.matches(play, best) {
+                reached.push(rule.milestone);
+            }
+        }
+        let json = self.config.content_type.contains("json");
+        reached
+            .iter()
+            .map(|milestone| {
+                let mut values = marquee_values(play, best, self.timezone);
+                values.insert("milestone", milestone.label());
+                if json {
+                    escape_json_values(&mut values);
+                }
+                self.template.render(&values)
+            })
+            .collect()
+    }
+
+    /// Send the notifications for `play` in the background
+    pub fn notify(&self, play: &PlayData, best: Option<&ScoreData>, retry: RetryPolicy) {
+        for body in self.messages(play, best) {
+            info!("Sending push notification: {}", body);
+            let config = self.config.clone();
+            thread::spawn(move || {
+                let sent = retry.execute(|attempt| {
+                    if attempt > 0 {
+                        debug!("Retrying push notification (attempt {})", attempt + 1);
+                    }
+                    send_push(&config, &body)
+                });
+                if let Err(e) = sent {
+                    warn!("Failed to send push notification: {}", e);
+                }
+            });
+        }
+    }
+}
+
+/// Parse a `Name: value` request header
+pub fn parse_header(s: &str) -> Result<(String, String)> {
+    match s.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(Error::parse(
+            "header",
+            format!("expected \"Name: value\", got {:?}", s),
+        )),
+    }
+}
+
+/// Escape values for use inside JSON strings
+fn escape_json_values(values: &mut HashMap<&'static str, String>) {
+    for value in values.values_mut() {
+        let quoted = serde_json::Value::String(std::mem::take(value)).to_string();
+        *value = quoted[1..quoted.len() - 1].to_string();
+    }
+}
+
+fn send_push(config: &PushConfig, body: &str) -> Result<()> {
+    let agent_config = ureq::Agent::config_builder()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .build();
+    let agent: ureq::Agent = agent_config.into();
+    let mut request = agent
+        .post(&config.url)
+        .header("Content-Type", &config.content_type);
+    for (name, value) in &config.headers {
+        request = request.header(name, value);
+    }
+    let response = request.send(body)?;
+    debug!("Push response: {}", response.status());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::Difficulty;
+    use crate::play::test_support::PlayBuilder;
+
+    fn play(level: u8, lamp: Lamp, ex_score: u32) -> PlayData {
+        PlayBuilder::new()
+            .title("Test \"Song\"")
+            .level(level)
+            .ex_score(ex_score)
+            .lamp(lamp)
+            .build()
+    }
+
+    fn best(lamp: Lamp, score: u32) -> ScoreData {
+        let mut best = ScoreData::new(1000);
+        best.set_lamp(Difficulty::SpA, lamp);
+        best.set_score(Difficulty::SpA, score);
+        best
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rule: NotifyRule = "clear:12".parse().unwrap();
+        assert_eq!(rule.milestone, Milestone::Lamp(Lamp::Clear));
+        assert_eq!(rule.level, Some(12));
+        assert!(rule.charts.is_empty());
+
+        let rule: NotifyRule = "AAA@1000:SPA,1001:sph".parse().unwrap();
+        assert_eq!(rule.milestone, Milestone::Grade(Grade::Aaa));
+        assert_eq!(
+            rule.charts,
+            vec![
+                ChartId::new(1000, Difficulty::SpA),
+                ChartId::new(1001, Difficulty::SpH)
+            ]
+        );
+        assert_eq!(rule.to_string(), "aaa@1000:SPA,1001:SPH");
+        assert_eq!(
+            "exhard:11".parse::<NotifyRule>().unwrap().to_string(),
+            "exhard:11"
+        );
+
+        assert!("clears".parse::<NotifyRule>().is_err());
+        assert!("clear:twelve".parse::<NotifyRule>().is_err());
+        assert!("aaa@1000".parse::<NotifyRule>().is_err());
+    }
+
+    #[test]
+    fn test_first_clear_of_a_12() {
+        let rule: NotifyRule = "clear:12".parse().unwrap();
+        let failed = best(Lamp::Failed, 1200);
+        assert!(rule.matches(&play(12, Lamp::HardClear, 1500), Some(&failed)));
+        assert!(rule.matches(&play(12, Lamp::Clear, 1500), None));
+        assert!(!rule.matches(&play(12, Lamp::EasyClear, 1500), Some(&failed)));
+        assert!(!rule.matches(&play(11, Lamp::Clear, 1500), Some(&failed)));
+        // Already cleared
+        let cleared = best(Lamp::Clear, 1400);
+        assert!(!rule.matches(&play(12, Lamp::Clear, 1500), Some(&cleared)));
+    }
+
+    #[test]
+    fn test_grade_and_personal_best() {
+        let aaa: NotifyRule = "aaa@1000:SPA".parse().unwrap();
+        let previous = best(Lamp::HardClear, 1700);
+        assert!(aaa.matches(&play(12, Lamp::Clear, 1800), Some(&previous)));
+        assert!(!aaa.matches(&play(12, Lamp::Clear, 1750), Some(&previous)));
+        assert!(!aaa.matches(&play(12, Lamp::Clear, 1800), Some(&best(Lamp::Clear, 1790))));
+
+        let pb: NotifyRule = "pb".parse().unwrap();
+        assert!(pb.matches(&play(12, Lamp::Failed, 1701), Some(&previous)));
+        assert!(!pb.matches(&play(12, Lamp::Failed, 1700), Some(&previous)));
+        assert!(!pb.matches(&play(12, Lamp::Failed, 1700), None));
+    }
+
+    #[test]
+    fn test_messages_once_per_milestone() {
+        let mut config = PushConfig::new(
+            "https://ntfy.sh/topic",
+            vec![
+                "clear:12".parse().unwrap(),
+                "clear".parse().unwrap(),
+                "hard".parse().unwrap(),
+                "fc".parse().unwrap(),
+            ],
+        );
+        config.template = "{milestone}: {title} [{diff}]".to_string();
+        let notifier = PushNotifier::new(config).unwrap();
+        let messages = notifier.messages(&play(12, Lamp::HardClear, 1500), None);
+        assert_eq!(
+            messages,
+            vec![
+                "First CLEAR: Test \"Song\" [SPA]",
+                "First HARD CLEAR: Test \"Song\" [SPA]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_template_escapes_values() {
+        let mut config = PushConfig::new(
+            "https://api.pushover.net/1/messages.json",
+            vec!["clear".parse().unwrap()],
+        );
+        config.template = r#"{{"message":"{title}"}}"#.to_string();
+        config.content_type = "application/json".to_string();
+        let notifier = PushNotifier::new(config).unwrap();
+        let messages = notifier.messages(&play(12, Lamp::Clear, 1500), None);
+        assert_eq!(messages, vec![r#"{"message":"Test \"Song\""}"#]);
+        let body: serde_json::Value = serde_json::from_str(&messages[0]).unwrap();
+        assert_eq!(body["message"], "Test \"Song\"");
+    }
+
+    #[test]
+    fn test_parse_header() {
+        assert_eq!(
+            parse_header("Title: New clear").unwrap(),
+            ("Title".to_string(), "New clear".to_string())
+        );
+        assert_eq!(
+            parse_header("Authorization: Bearer a:b").unwrap().1,
+            "Bearer a:b"
+        );
+        assert!(parse_header("Title").is_err());
+        assert!(parse_header(": value").is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let rules = vec!["clear".parse().unwrap()];
+        assert!(
+            PushConfig::new("https://ntfy.sh/topic", rules.clone())
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            PushConfig::new("ntfy.sh/topic", rules.clone())
+                .validate()
+                .is_err()
+        );
+        assert!(
+            PushConfig::new("https://ntfy.sh/topic", Vec::new())
+                .validate()
+                .is_err()
+        );
+        let mut config = PushConfig::new("https://ntfy.sh/topic", rules);
+        config.template = "{milestones}".to_string();
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,235 @@
+//! Song list drift detection
+//!
+//! When offsets are detected, a couple of song entries are pinned as
+//! anchors: their slot in the song list, song ID and a hash of the raw title
+//! bytes. The anchors are stored with the offsets (and so in the offset
+//! cache). While tracking, the entries at the pinned slots are re-read; if
+//! none of them still matches, the song list has moved. The tracker then
+//! searches for the song list again, rebases the offsets that live inside
+//! it ([`OffsetsCollection::rebase_song_list`]) and pins new anchors, instead
+//! of reading garbled titles from the stale address.
+
+use serde::{Deserialize, Serialize};
+
+use crate::chart::SongEntryLayout;
+use crate::process::ReadMemory;
+
+use super::OffsetsCollection;
+
+/// Anchors pinned per song list
+pub const ANCHOR_COUNT: usize = 2;
+
+/// Leading song list slots searched for anchor candidates
+const PIN_SCAN_SLOTS: u32 = 64;
+
+/// Song list entries spanned when deciding which offsets move with it
+pub const SONG_LIST_REGION_ENTRIES: u64 = 5000;
+
+/// Bytes of the title field at the start of a song entry
+const TITLE_SIZE: usize = 64;
+
+/// Fingerprint of one song list entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SongAnchor {
+    /// Index of the entry in the song list
+    pub slot: u32,
+    /// Song ID stored in the entry (0 on builds that keep it elsewhere)
+    pub song_id: u32,
+    /// FNV-1a hash of the title bytes up to the first NUL
+    pub title_hash: u64,
+}
+
+impl SongAnchor {
+    /// Fingerprint the entry at `slot`; `None` when it holds no title
+    pub fn read<R: ReadMemory>(
+        reader: &R,
+        song_list: u64,
+        slot: u32,
+        layout: &SongEntryLayout,
+    ) -> Option<Self> {
+        let address = song_list + u64::from(slot) * layout.entry_size as u64;
+        let entry = reader.read_bytes(address, layout.entry_size).ok()?;
+        let title = title_bytes(&entry);
+        if title.is_empty() {
+            return None;
+        }
+        let song_id = entry
+            .get(layout.song_id..layout.song_id + 4)
+            .map_or(0, |b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+        Some(Self {
+            slot,
+            song_id,
+            title_hash: title_hash(title),
+        })
+    }
+
+    /// Whether the entry at the pinned slot still carries this fingerprint
+    pub fn holds<R: ReadMemory>(
+        &self,
+        reader: &R,
+        song_list: u64,
+        layout: &SongEntryLayout,
+    ) -> bool {
+        Self::read(reader, song_list, self.slot, layout).as_ref() == Some(self)
+    }
+}
+
+/// Pin up to [`ANCHOR_COUNT`] anchors from the first populated song entries
+pub fn pin_song_anchors<R: ReadMemory>(
+    reader: &R,
+    song_list: u64,
+    layout: &SongEntryLayout,
+) -> Vec<SongAnchor> {
+    (0..PIN_SCAN_SLOTS)
+        .filter_map(|slot| SongAnchor::read(reader, song_list, slot, layout))
+        .take(ANCHOR_COUNT)
+        .collect()
+}
+
+/// Whether the song list moved away from `song_list`
+///
+/// True only when anchors were pinned and none of them still matches, so a
+/// single entry being rewritten in place doesn't count as a move.
+pub fn song_list_moved<R: ReadMemory>(
+    reader: &R,
+    song_list: u64,
+    anchors: &[SongAnchor],
+    layout: &SongEntryLayout,
+) -> bool {
+    !anchors.is_empty()
+        && !anchors
+            .iter()
+            .any(|anchor| anchor.holds(reader, song_list, layout))
+}
+
+impl OffsetsCollection {
+    /// Move the song list to `new_song_list`, shifting the offsets inside it
+    ///
+    /// Offsets within [`SONG_LIST_REGION_ENTRIES`] entries after the old
+    /// song list are taken to be part of the same allocation and moved by the
+    /// same distance; the others are left alone. Returns the names of the
+    /// rebased offsets, song list excluded.
+    pub fn rebase_song_list(
+        &mut self,
+        new_song_list: u64,
+        layout: &SongEntryLayout,
+    ) -> Vec<&'static str> {
+        let old = self.song_list;
+        let region = SONG_LIST_REGION_ENTRIES * layout.entry_size as u64;
+        let mut rebased = Vec::new();
+        for (name, offset) in [
+            ("dataMap", &mut self.data_map),
+            ("judgeData", &mut self.judge_data),
+            ("playData", &mut self.play_data),
+            ("playSettings", &mut self.play_settings),
+            ("unlockData", &mut self.unlock_data),
+            ("currentSong", &mut self.current_song),
+            ("profile", &mut self.profile),
+            ("currency", &mut self.currency),
+            ("laneCover", &mut self.lane_cover),
+        ] {
+            if *offset > old && *offset - old < region {
+                *offset = new_song_list + (*offset - old);
+                rebased.push(name);
+            }
+        }
+        self.song_list = new_song_list;
+        rebased
+    }
+}
+
+fn title_bytes(entry: &[u8]) -> &[u8] {
+    let field = &entry[..TITLE_SIZE.min(entry.len())];
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    &field[..end]
+}
+
+/// 64-bit FNV-1a, stable across builds and platforms
+fn title_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::{MockMemoryBuilder, MockMemoryReader};
+
+    const LAYOUT: SongEntryLayout = SongEntryLayout::CURRENT;
+
+    /// Memory with `titles` as consecutive song entries starting at `start`
+    fn song_list_at(start: usize, titles: &[(&str, u32)]) -> MockMemoryReader {
+        let mut builder = MockMemoryBuilder::new().with_size(start + LAYOUT.entry_size * 8);
+        for (i, (title, id)) in titles.iter().enumerate() {
+            let entry = start + i * LAYOUT.entry_size;
+            builder = builder
+                .write_shift_jis(entry, title)
+                .write_u32(entry + LAYOUT.song_id, *id);
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn test_pin_skips_empty_slots() {
+        let reader = song_list_at(
+            0,
+            &[
+                ("", 0),
+                ("5.1.1.", 1000),
+                ("GAMBOL", 1001),
+                ("piano ambient", 1002),
+            ],
+        );
+        let anchors = pin_song_anchors(&reader, 0x1000, &LAYOUT);
+        assert_eq!(anchors.len(), ANCHOR_COUNT);
+        assert_eq!((anchors[0].slot, anchors[0].song_id), (1, 1000));
+        assert_eq!((anchors[1].slot, anchors[1].song_id), (2, 1001));
+        assert!(!song_list_moved(&reader, 0x1000, &anchors, &LAYOUT));
+    }
+
+    #[test]
+    fn test_detects_moved_song_list() {
+        let titles = [("5.1.1.", 1000), ("GAMBOL", 1001)];
+        let before = song_list_at(0, &titles);
+        let anchors = pin_song_anchors(&before, 0x1000, &LAYOUT);
+
+        // The list now starts two entries later; the old slots hold other data
+        let after = song_list_at(LAYOUT.entry_size * 2, &titles);
+        assert!(song_list_moved(&after, 0x1000, &anchors, &LAYOUT));
+        let moved_to = 0x1000 + LAYOUT.entry_size as u64 * 2;
+        assert!(!song_list_moved(&after, moved_to, &anchors, &LAYOUT));
+    }
+
+    #[test]
+    fn test_single_rewritten_entry_is_not_a_move() {
+        let before = song_list_at(0, &[("5.1.1.", 1000), ("GAMBOL", 1001)]);
+        let anchors = pin_song_anchors(&before, 0x1000, &LAYOUT);
+        let after = song_list_at(0, &[("5.1.1.", 1000), ("GAMBOL (renamed)", 1001)]);
+        assert!(!song_list_moved(&after, 0x1000, &anchors, &LAYOUT));
+        assert!(!song_list_moved(&after, 0x1000, &[], &LAYOUT));
+    }
+
+    #[test]
+    fn test_title_hash_is_stable() {
+        assert_eq!(title_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(title_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_rebase_moves_offsets_inside_the_song_list() {
+        let mut offsets = OffsetsCollection {
+            song_list: 0x1000_0000,
+            unlock_data: 0x1000_8000,
+            judge_data: 0x0F00_0000,
+            play_data: 0x2000_0000,
+            ..Default::default()
+        };
+        let rebased = offsets.rebase_song_list(0x1100_0000, &LAYOUT);
+        assert_eq!(rebased, vec!["unlockData"]);
+        assert_eq!(offsets.song_list, 0x1100_0000);
+        assert_eq!(offsets.unlock_data, 0x1100_8000);
+        assert_eq!(offsets.judge_data, 0x0F00_0000);
+        assert_eq!(offsets.play_data, 0x2000_0000);
+    }
+}
@@ -10,10 +10,9 @@ use std::time::SystemTime;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
-use super::OffsetsCollection;
+use crate::paths::OFFSET_CACHE_FILE;
 
-/// Cache file name
-const CACHE_FILE: &str = ".infst-cache.json";
+use super::OffsetsCollection;
 
 /// Maximum age for cache validity (24 hours)
 const MAX_CACHE_AGE_SECS: u64 = 24 * 60 * 60;
@@ -44,9 +43,9 @@ impl OffsetCache {
         }
     }
 
-    /// Load cache from file
+    /// Load cache from the current directory
     pub fn load() -> Option<Self> {
-        Self::load_from_path(CACHE_FILE)
+        Self::load_from_path(OFFSET_CACHE_FILE)
     }
 
     /// Load cache from a specific path
@@ -76,9 +75,9 @@ impl OffsetCache {
         }
     }
 
-    /// Save cache to file
+    /// Save cache to the current directory
     pub fn save(&self) -> Result<(), std::io::Error> {
-        self.save_to_path(CACHE_FILE)
+        self.save_to_path(OFFSET_CACHE_FILE)
     }
 
     /// Save cache to a specific path
@@ -124,9 +123,12 @@ impl OffsetCache {
     }
 }
 
-/// Try to load cached offsets if valid for the given version
-pub fn try_load_cached_offsets(game_version: &str) -> Option<OffsetsCollection> {
-    let cache = OffsetCache::load()?;
+/// Try to load cached offsets from `path` if valid for the given version
+pub fn try_load_cached_offsets<P: AsRef<Path>>(
+    path: P,
+    game_version: &str,
+) -> Option<OffsetsCollection> {
+    let cache = OffsetCache::load_from_path(path)?;
 
     if cache.is_valid_for(game_version) {
         info!(
@@ -144,14 +146,35 @@ pub fn try_load_cached_offsets(game_version: &str) -> Option<OffsetsCollection>
     }
 }
 
-/// Save offsets to cache
-pub fn save_offsets_to_cache(version: &str, offsets: &OffsetsCollection) {
+/// Save offsets to the cache at `path`
+pub fn save_offsets_to_cache<P: AsRef<Path>>(path: P, version: &str, offsets: &OffsetsCollection) {
     let cache = OffsetCache::new(version.to_string(), offsets.clone());
-    if let Err(e) = cache.save() {
+    if let Err(e) = cache.save_to_path(path) {
         warn!("Failed to save offset cache: {}", e);
     }
 }
 
+/// Remove the offset cache at `path` if it was saved for `version`
+///
+/// Used when the game updates while running, so the stale offsets of the
+/// old version are not reused. Returns `true` if the cache was removed.
+pub fn invalidate_cached_offsets<P: AsRef<Path>>(path: P, version: &str) -> bool {
+    let path = path.as_ref();
+    match OffsetCache::load_from_path(path) {
+        Some(cache) if cache.version == version => match fs::remove_file(path) {
+            Ok(()) => {
+                info!("Invalidated offset cache for version {}", version);
+                true
+            }
+            Err(e) => {
+                warn!("Failed to remove offset cache: {}", e);
+                false
+            }
+        },
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,6 +194,10 @@ mod tests {
             current_song: 0x5000,
             data_map: 0x6000,
             unlock_data: 0x7000,
+            profile: 0,
+            currency: 0,
+            lane_cover: 0,
+            song_anchors: Vec::new(),
         };
 
         let cache = OffsetCache::new("P2D:J:B:A:2026012800".to_string(), offsets.clone());
@@ -192,6 +219,10 @@ mod tests {
             current_song: 0x5000,
             data_map: 0x6000,
             unlock_data: 0x7000,
+            profile: 0,
+            currency: 0,
+            lane_cover: 0,
+            song_anchors: Vec::new(),
         };
 
         let cache = OffsetCache::new("P2D:J:B:A:2026012800".to_string(), offsets);
@@ -199,6 +230,23 @@ mod tests {
         assert!(!cache.is_valid_for("P2D:J:B:A:2025122400"));
     }
 
+    #[test]
+    fn test_invalidate_cache_only_for_version() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().to_path_buf();
+
+        let cache = OffsetCache::new(
+            "P2D:J:B:A:2026012800".to_string(),
+            OffsetsCollection::default(),
+        );
+        cache.save_to_path(&path).unwrap();
+
+        assert!(!invalidate_cached_offsets(&path, "P2D:J:B:A:2026020400"));
+        assert!(path.exists());
+        assert!(invalidate_cached_offsets(&path, "P2D:J:B:A:2026012800"));
+        assert!(!path.exists());
+    }
+
     #[test]
     fn test_cache_invalid_offsets() {
         let offsets = OffsetsCollection::default(); // All zeros
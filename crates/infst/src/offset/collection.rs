@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::SongAnchor;
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct OffsetsCollection {
     pub version: String,
@@ -10,6 +12,18 @@ pub struct OffsetsCollection {
     pub play_settings: u64,
     pub unlock_data: u64,
     pub current_song: u64,
+    /// Player profile block (optional, 0 when unknown; not required by `is_valid`)
+    #[serde(default)]
+    pub profile: u64,
+    /// Bits and unlock tickets block (optional, 0 when unknown; not required by `is_valid`)
+    #[serde(default)]
+    pub currency: u64,
+    /// Lane cover and green number block (optional, 0 when unknown; not required by `is_valid`)
+    #[serde(default)]
+    pub lane_cover: u64,
+    /// Song entries pinned to detect the song list moving (see [`SongAnchor`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub song_anchors: Vec<SongAnchor>,
 }
 
 impl OffsetsCollection {
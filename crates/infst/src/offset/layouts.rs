@@ -0,0 +1,219 @@
+//! Registry of supported game builds
+//!
+//! Each [`GameLayout`] describes the memory layout of the builds starting at
+//! its date code: the song entry layout, the expected distances between the
+//! structures the searcher finds relative to each other, and the code
+//! signatures. The layout for a process is selected from the detected
+//! version string; builds older than the oldest known layout are rejected
+//! with [`Error::UnsupportedGameVersion`] instead of being parsed with the
+//! wrong offsets.
+
+use serde::{Deserialize, Serialize};
+
+use crate::chart::SongEntryLayout;
+use crate::config::extract_date_code;
+use crate::error::{Error, Result};
+
+use super::searcher::constants::{
+    JUDGE_TO_CURRENT_SONG, JUDGE_TO_PLAY_SETTINGS, JUDGE_TO_SONG_LIST, PLAY_SETTINGS_TO_PLAY_DATA,
+};
+use super::signature::{OffsetSignatureSet, builtin_signatures};
+
+/// Expected distances between structures found by relative search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelativeOffsets {
+    /// songList - judgeData
+    pub judge_to_song_list: u64,
+    /// judgeData - playSettings
+    pub judge_to_play_settings: u64,
+    /// playData - playSettings
+    pub play_settings_to_play_data: u64,
+    /// currentSong - judgeData
+    pub judge_to_current_song: u64,
+}
+
+impl RelativeOffsets {
+    /// Version 2026012800 and later
+    pub const CURRENT: Self = Self {
+        judge_to_song_list: JUDGE_TO_SONG_LIST,
+        judge_to_play_settings: JUDGE_TO_PLAY_SETTINGS,
+        play_settings_to_play_data: PLAY_SETTINGS_TO_PLAY_DATA,
+        judge_to_current_song: JUDGE_TO_CURRENT_SONG,
+    };
+
+    /// Measured on version 2025122400
+    pub const V2025122400: Self = Self {
+        judge_to_song_list: 0x94E374,
+        judge_to_play_settings: 0x2ACEE8,
+        play_settings_to_play_data: 0x2C0,
+        judge_to_current_song: 0x1E4,
+    };
+}
+
+impl Default for RelativeOffsets {
+    fn default() -> Self {
+        Self::CURRENT
+    }
+}
+
+/// Memory layout of a range of game builds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameLayout {
+    /// Date code of the first build with this layout (e.g. "2026012800")
+    pub since: String,
+    pub song_entry: SongEntryLayout,
+    pub relative: RelativeOffsets,
+    pub signatures: OffsetSignatureSet,
+}
+
+impl GameLayout {
+    /// Builds from 2026012800 (1200-byte song entries)
+    pub fn current() -> Self {
+        Self {
+            since: "2026012800".to_string(),
+            song_entry: SongEntryLayout::CURRENT,
+            relative: RelativeOffsets::CURRENT,
+            signatures: builtin_signatures(),
+        }
+    }
+
+    /// Builds from 2025122400 up to 2026012800 (1008-byte song entries)
+    ///
+    /// The builtin signatures match both binaries.
+    pub fn v2025122400() -> Self {
+        Self {
+            since: "2025122400".to_string(),
+            song_entry: SongEntryLayout::LEGACY_3F0,
+            relative: RelativeOffsets::V2025122400,
+            signatures: builtin_signatures(),
+        }
+    }
+}
+
+impl Default for GameLayout {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+/// Known layouts, newest first
+#[derive(Debug, Clone)]
+pub struct LayoutRegistry {
+    layouts: Vec<GameLayout>,
+}
+
+impl LayoutRegistry {
+    /// Registry of the given layouts
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layouts` is empty.
+    pub fn new(mut layouts: Vec<GameLayout>) -> Self {
+        assert!(!layouts.is_empty(), "layout registry needs a layout");
+        layouts.sort_by(|a, b| b.since.cmp(&a.since));
+        Self { layouts }
+    }
+
+    /// Layouts built into this version of infst
+    pub fn builtin() -> Self {
+        Self::new(vec![GameLayout::current(), GameLayout::v2025122400()])
+    }
+
+    /// All layouts, newest first
+    pub fn layouts(&self) -> &[GameLayout] {
+        &self.layouts
+    }
+
+    /// Layout of the newest known build (used when the version is unknown)
+    pub fn newest(&self) -> &GameLayout {
+        &self.layouts[0]
+    }
+
+    /// Date codes of the first build of each layout, newest first
+    pub fn supported_builds(&self) -> Vec<String> {
+        self.layouts.iter().map(|l| l.since.clone()).collect()
+    }
+
+    /// Layout for a version string (`P2D:J:B:A:YYYYMMDDNN`)
+    ///
+    /// Builds newer than every known layout use the newest one.
+    pub fn select(&self, version: &str) -> Result<&GameLayout> {
+        extract_date_code(version)
+            .and_then(|date_code| {
+                self.layouts
+                    .iter()
+                    .find(|layout| date_code >= layout.since.as_str())
+            })
+            .ok_or_else(|| Error::UnsupportedGameVersion {
+                version: version.to_string(),
+                supported: self.supported_builds(),
+            })
+    }
+}
+
+impl Default for LayoutRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_by_date_code() {
+        let registry = LayoutRegistry::builtin();
+        let select = |version: &str| registry.select(version).map(|l| l.since.as_str());
+
+        assert_eq!(select("P2D:J:B:A:2026012800").unwrap(), "2026012800");
+        assert_eq!(select("P2D:J:B:A:2026020400").unwrap(), "2026012800");
+        assert_eq!(select("P2D:J:B:A:2025122400").unwrap(), "2025122400");
+        assert_eq!(select("P2D:J:B:A:2026010100").unwrap(), "2025122400");
+    }
+
+    #[test]
+    fn test_unsupported_version_lists_builds() {
+        let registry = LayoutRegistry::builtin();
+        let err = registry.select("P2D:J:B:A:2025110500").unwrap_err();
+        assert!(matches!(err, Error::UnsupportedGameVersion { .. }));
+        let message = err.to_string();
+        assert!(message.contains("2025110500"));
+        assert!(message.contains("2026012800"));
+        assert!(message.contains("2025122400"));
+
+        assert!(registry.select("garbage").is_err());
+    }
+
+    #[test]
+    fn test_layouts_sorted_newest_first() {
+        let registry = LayoutRegistry::new(vec![GameLayout::v2025122400(), GameLayout::current()]);
+        assert_eq!(registry.newest().since, "2026012800");
+        assert_eq!(registry.supported_builds(), ["2026012800", "2025122400"]);
+    }
+
+    /// Distances measured on each build, from the offsets files of 2025122400
+    /// and 2026012800
+    #[test]
+    fn test_relative_offsets_match_measured_builds() {
+        let v1 = RelativeOffsets::V2025122400;
+        assert_eq!(0x14315A380 - 0x14280C00C, v1.judge_to_song_list);
+        assert_eq!(0x14280C00C - 0x14255F124, v1.judge_to_play_settings);
+        assert_eq!(0x14255F3E4 - 0x14255F124, v1.play_settings_to_play_data);
+        assert_eq!(0x14280C1F0 - 0x14280C00C, v1.judge_to_current_song);
+
+        let v2 = RelativeOffsets::CURRENT;
+        assert_eq!(0x1428380EC - 0x14258B144, v2.judge_to_play_settings);
+        assert_eq!(0x14258B3E4 - 0x14258B144, v2.play_settings_to_play_data);
+        assert_eq!(0x1428382D0 - 0x1428380EC, v2.judge_to_current_song);
+    }
+
+    #[test]
+    fn test_layout_json_roundtrip() {
+        let layout = GameLayout::v2025122400();
+        let json = serde_json::to_string(&layout).unwrap();
+        let parsed: GameLayout = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.song_entry, SongEntryLayout::LEGACY_3F0);
+        assert_eq!(parsed.relative, RelativeOffsets::V2025122400);
+    }
+}
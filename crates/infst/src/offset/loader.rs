@@ -2,17 +2,20 @@ use tracing::warn;
 
 use crate::error::{Error, Result};
 use crate::offset::OffsetsCollection;
+use crate::util::hex::parse_address;
 use std::fs;
 use std::path::Path;
 
 pub fn load_offsets<P: AsRef<Path>>(path: P) -> Result<OffsetsCollection> {
-    let content = fs::read_to_string(&path)?;
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(|e| Error::storage(path, e))?;
     parse_offsets(&content)
 }
 
 pub fn save_offsets<P: AsRef<Path>>(path: P, offsets: &OffsetsCollection) -> Result<()> {
+    let path = path.as_ref();
     let content = format_offsets(offsets);
-    fs::write(path, content)?;
+    fs::write(path, content).map_err(|e| Error::storage(path, e))?;
     Ok(())
 }
 
@@ -36,7 +39,7 @@ fn parse_offsets(content: &str) -> Result<OffsetsCollection> {
             let key = key.trim().to_lowercase();
             let value = value.trim();
 
-            let parsed_value = parse_hex_value(value)?;
+            let parsed_value = parse_address(value)?;
 
             match key.as_str() {
                 "songlist" => offsets.song_list = parsed_value,
@@ -46,6 +49,15 @@ fn parse_offsets(content: &str) -> Result<OffsetsCollection> {
                 "playsettings" => offsets.play_settings = parsed_value,
                 "unlockdata" => offsets.unlock_data = parsed_value,
                 "currentsong" => offsets.current_song = parsed_value,
+                "profile" => offsets.profile = parsed_value,
+                "currency" => offsets.currency = parsed_value,
+                // Legacy key: the balance is the first field of the currency block
+                "bitsbalance" => {
+                    if offsets.currency == 0 {
+                        offsets.currency = parsed_value;
+                    }
+                }
+                "lanecover" => offsets.lane_cover = parsed_value,
                 _ => {
                     warn!("Unknown offset key: '{}' (value: {})", key, value);
                 }
@@ -56,18 +68,6 @@ fn parse_offsets(content: &str) -> Result<OffsetsCollection> {
     Ok(offsets)
 }
 
-fn parse_hex_value(value: &str) -> Result<u64> {
-    let value = value.trim();
-    // Strip hex prefix (case-insensitive), only once
-    let value = value
-        .strip_prefix("0x")
-        .or_else(|| value.strip_prefix("0X"))
-        .unwrap_or(value);
-
-    u64::from_str_radix(value, 16)
-        .map_err(|e| Error::InvalidOffset(format!("Failed to parse '{}': {}", value, e)))
-}
-
 fn format_offsets(offsets: &OffsetsCollection) -> String {
     let mut lines = Vec::new();
 
@@ -79,6 +79,16 @@ fn format_offsets(offsets: &OffsetsCollection) -> String {
     lines.push(format!("playSettings = {:#x}", offsets.play_settings));
     lines.push(format!("unlockData = {:#x}", offsets.unlock_data));
     lines.push(format!("currentSong = {:#x}", offsets.current_song));
+    // Optional offsets: only written once known
+    if offsets.profile != 0 {
+        lines.push(format!("profile = {:#x}", offsets.profile));
+    }
+    if offsets.currency != 0 {
+        lines.push(format!("currency = {:#x}", offsets.currency));
+    }
+    if offsets.lane_cover != 0 {
+        lines.push(format!("laneCover = {:#x}", offsets.lane_cover));
+    }
 
     lines.join("\n")
 }
@@ -114,5 +124,32 @@ playData = 0x87654321
         let formatted = format_offsets(&offsets);
         assert!(formatted.contains("P2D:J:B:A:2025101500"));
         assert!(formatted.contains("songList = 0x1000"));
+        assert!(!formatted.contains("bitsBalance"));
+    }
+
+    #[test]
+    fn test_optional_offsets_roundtrip() {
+        let offsets = OffsetsCollection {
+            version: "P2D:J:B:A:2025101500".to_string(),
+            profile: 0x4000,
+            currency: 0x5000,
+            lane_cover: 0x6000,
+            ..Default::default()
+        };
+
+        let parsed = parse_offsets(&format_offsets(&offsets)).unwrap();
+        assert_eq!(parsed.profile, 0x4000);
+        assert_eq!(parsed.currency, 0x5000);
+        assert_eq!(parsed.lane_cover, 0x6000);
+    }
+
+    #[test]
+    fn test_legacy_bits_balance_key_is_currency() {
+        let legacy = "P2D:J:B:A:2025101500\nbitsBalance = 0x3000\n";
+        assert_eq!(parse_offsets(legacy).unwrap().currency, 0x3000);
+
+        // An explicit currency entry wins regardless of order
+        let both = "P2D:J:B:A:2025101500\ncurrency = 0x5000\nbitsBalance = 0x3000\n";
+        assert_eq!(parse_offsets(both).unwrap().currency, 0x5000);
     }
 }
@@ -18,17 +18,23 @@
 //! - [`OffsetsCollection`]: All detected memory offsets
 //! - [`OffsetSearcher`]: Signature-based offset finder
 //! - [`CodeSignature`]: Pattern definition for code scanning
+//! - [`LayoutRegistry`]: Memory layouts of the supported game builds
+//! - [`SongAnchor`]: Pinned song entries that reveal a moved song list
 
+mod anchor;
 mod cache;
 mod collection;
 mod dump;
+mod layouts;
 mod loader;
 mod searcher;
 mod signature;
 
+pub use anchor::*;
 pub use cache::*;
 pub use collection::*;
 pub use dump::*;
+pub use layouts::*;
 pub use loader::*;
 pub use searcher::*;
 pub use signature::*;
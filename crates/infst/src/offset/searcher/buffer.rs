@@ -3,6 +3,7 @@
 use crate::error::{Error, Result};
 use crate::play::PlayType;
 use crate::process::ReadMemory;
+use crate::process::pattern::MultiPattern;
 
 use super::OffsetSearcher;
 use super::constants::*;
@@ -17,8 +18,6 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
         judge: &JudgeInput,
         play_type: PlayType,
     ) -> Result<u64> {
-        self.load_buffer_around(base_hint, INITIAL_SEARCH_SIZE)?;
-
         let (pattern_p1, pattern_p2) = self.build_judge_patterns(judge);
 
         let patterns = if play_type == PlayType::P1 {
@@ -39,8 +38,6 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
         difficulty: u32,
         ex_score: u32,
     ) -> Result<u64> {
-        self.load_buffer_around(base_hint, INITIAL_SEARCH_SIZE)?;
-
         // Pattern: song_id, difficulty, ex_score
         let pattern =
             merge_byte_representations(&[song_id as i32, difficulty as i32, ex_score as i32]);
@@ -54,8 +51,6 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
         song_id: u32,
         difficulty: u32,
     ) -> Result<u64> {
-        self.load_buffer_around(base_hint, INITIAL_SEARCH_SIZE)?;
-
         let pattern = merge_byte_representations(&[song_id as i32, difficulty as i32]);
         self.fetch_and_search(base_hint, &pattern, 0, None)
     }
@@ -84,10 +79,10 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
 
         // Progressively expand search area, tolerating read errors
         while search_size <= MAX_SEARCH_SIZE {
-            if self.load_buffer_around(base_hint, search_size).is_ok()
-                && let Some(pos) = self.find_pattern(&pattern, None)
+            if let Ok(Some(found)) =
+                self.find_first_around(base_hint, search_size, &[&pattern], None)
             {
-                return Ok(self.buffer_base + pos as u64);
+                return Ok(found.address);
             }
             search_size *= 2;
         }
@@ -109,12 +104,10 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
         let mut search_size = INITIAL_SEARCH_SIZE;
 
         while search_size <= MAX_SEARCH_SIZE {
-            self.load_buffer_around(hint, search_size)?;
-
-            if let Some(pos) = self.find_pattern(pattern, ignore_address) {
-                let address =
-                    (self.buffer_base + pos as u64).wrapping_add_signed(offset_from_match);
-                return Ok(address);
+            if let Some(found) =
+                self.find_first_around(hint, search_size, &[pattern], ignore_address)?
+            {
+                return Ok(found.address.wrapping_add_signed(offset_from_match));
             }
 
             search_size *= 2;
@@ -140,9 +133,9 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
 
         // Keep expanding to find all matches across the readable memory area
         while search_size <= MAX_SEARCH_SIZE {
-            match self.load_buffer_around(hint, search_size) {
-                Ok(()) => {
-                    last_matches = self.find_all_matches(pattern);
+            match self.find_all_around(hint, search_size, pattern) {
+                Ok(matches) => {
+                    last_matches = matches;
                 }
                 Err(_) => {
                     // Memory read failed, use results from previous size
@@ -175,18 +168,16 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
     ) -> Result<SearchResult> {
         let mut search_size = INITIAL_SEARCH_SIZE;
 
+        let patterns: Vec<&[u8]> = patterns.iter().map(Vec::as_slice).collect();
+
         while search_size <= MAX_SEARCH_SIZE {
-            self.load_buffer_around(hint, search_size)?;
-
-            for (index, pattern) in patterns.iter().enumerate() {
-                if let Some(pos) = self.find_pattern(pattern, ignore_address) {
-                    let address =
-                        (self.buffer_base + pos as u64).wrapping_add_signed(offset_from_match);
-                    return Ok(SearchResult {
-                        address,
-                        pattern_index: index,
-                    });
-                }
+            if let Some(found) =
+                self.find_first_around(hint, search_size, &patterns, ignore_address)?
+            {
+                return Ok(SearchResult {
+                    address: found.address.wrapping_add_signed(offset_from_match),
+                    pattern_index: found.pattern_index,
+                });
             }
 
             search_size *= 2;
@@ -244,19 +235,218 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
         (pattern_p1, pattern_p2)
     }
 
-    /// Find a pattern in the current buffer
-    pub(crate) fn find_pattern(
+    /// Stream the +/-`distance` window around `center` in chunks of at most
+    /// the buffer cap
+    ///
+    /// Consecutive chunks share `overlap` bytes, so a pattern up to
+    /// `overlap + 1` bytes long that spans a chunk boundary is still seen
+    /// whole. `visit` gets each chunk's address, its bytes, and how many
+    /// leading bytes it owns: matches starting after that are reported again
+    /// by the next chunk. Returning `false` from `visit` stops the scan.
+    pub(crate) fn scan_chunks(
         &self,
-        pattern: &[u8],
+        center: u64,
+        distance: usize,
+        overlap: usize,
+        mut visit: impl FnMut(u64, &[u8], usize) -> bool,
+    ) -> Result<()> {
+        let (start, len) = self.search_window(center, distance);
+        let end = start + len as u64;
+        let chunk_size = self.max_buffer_size.max(overlap + 1);
+
+        let mut pos = start;
+        while pos < end {
+            let read_len = chunk_size.min((end - pos) as usize);
+            let chunk = self.reader.read_bytes(pos, read_len)?;
+            let owned = if pos + read_len as u64 >= end {
+                read_len
+            } else {
+                read_len - overlap
+            };
+            if !visit(pos, &chunk, owned) {
+                break;
+            }
+            pos += owned as u64;
+        }
+        Ok(())
+    }
+
+    /// First match in the window, preferring earlier patterns
+    ///
+    /// A match of `patterns[0]` anywhere in the window wins over an earlier
+    /// match of `patterns[1]`, and so on. Fails if part of the window can't
+    /// be read before the preferred pattern is found.
+    pub(crate) fn find_first_around(
+        &self,
+        center: u64,
+        distance: usize,
+        patterns: &[&[u8]],
         ignore_address: Option<u64>,
-    ) -> Option<usize> {
-        self.buffer
-            .windows(pattern.len())
+    ) -> Result<Option<SearchResult>> {
+        let searcher = MultiPattern::new(patterns)?;
+        let mut firsts: Vec<Option<u64>> = vec![None; patterns.len()];
+        self.scan_chunks(
+            center,
+            distance,
+            max_overlap(patterns),
+            |base, chunk, owned| {
+                for (index, pos) in searcher.find_iter(chunk) {
+                    let addr = base + pos as u64;
+                    if pos < owned && ignore_address != Some(addr) {
+                        let first = &mut firsts[index];
+                        *first = Some(first.map_or(addr, |current| current.min(addr)));
+                    }
+                }
+                firsts.first().is_some_and(Option::is_none)
+            },
+        )?;
+
+        Ok(firsts
+            .into_iter()
             .enumerate()
-            .find(|(pos, window)| {
-                let addr = self.buffer_base + *pos as u64;
-                *window == pattern && (ignore_address != Some(addr))
-            })
-            .map(|(pos, _)| pos)
+            .find_map(|(pattern_index, first)| {
+                first.map(|address| SearchResult {
+                    address,
+                    pattern_index,
+                })
+            }))
+    }
+
+    /// All matches of `pattern` in the window, in address order
+    pub(crate) fn find_all_around(
+        &self,
+        center: u64,
+        distance: usize,
+        pattern: &[u8],
+    ) -> Result<Vec<u64>> {
+        let mut matches = self.find_all_around_many(center, distance, &[pattern])?;
+        Ok(matches.pop().unwrap_or_default())
+    }
+
+    /// All matches of each pattern in the window, read in a single pass
+    pub(crate) fn find_all_around_many(
+        &self,
+        center: u64,
+        distance: usize,
+        patterns: &[&[u8]],
+    ) -> Result<Vec<Vec<u64>>> {
+        let searcher = MultiPattern::new(patterns)?;
+        let mut matches = vec![Vec::new(); patterns.len()];
+        self.scan_chunks(
+            center,
+            distance,
+            max_overlap(patterns),
+            |base, chunk, owned| {
+                for (found, chunk_matches) in matches.iter_mut().zip(searcher.find_all(chunk)) {
+                    found.extend(
+                        chunk_matches
+                            .into_iter()
+                            .take_while(|&pos| pos < owned)
+                            .map(|pos| base + pos as u64),
+                    );
+                }
+                true
+            },
+        )?;
+        Ok(matches)
+    }
+}
+
+/// Bytes consecutive chunks must share so no pattern is split
+fn max_overlap(patterns: &[&[u8]]) -> usize {
+    patterns
+        .iter()
+        .map(|pattern| pattern.len().saturating_sub(1))
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::MockMemoryBuilder;
+
+    const PATTERN: [u8; 8] = [0xDE, 0xAD, 0xBE, 0xEF, 0x01, 0x02, 0x03, 0x04];
+
+    #[test]
+    fn test_streamed_search_finds_matches_across_chunks() {
+        // 16KB of memory scanned with a 4KB cap: four chunks, one match
+        // straddling the first chunk boundary
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x4000)
+            .write_bytes(0x0FFC, &PATTERN)
+            .write_bytes(0x2100, &PATTERN)
+            .build();
+        let searcher = OffsetSearcher::builder(&reader)
+            .with_max_buffer_size(MIN_MAX_BUFFER_SIZE)
+            .build();
+
+        assert_eq!(
+            searcher.find_all_around(0x3000, 0x2000, &PATTERN).unwrap(),
+            vec![0x1FFC, 0x3100]
+        );
+        // Short patterns inside the shared bytes are reported once
+        assert_eq!(
+            searcher
+                .find_all_around(0x3000, 0x2000, &PATTERN[..2])
+                .unwrap(),
+            vec![0x1FFC, 0x3100]
+        );
+
+        let first = searcher
+            .find_first_around(0x3000, 0x2000, &[&PATTERN], Some(0x1FFC))
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.address, 0x3100);
+    }
+
+    #[test]
+    fn test_find_first_prefers_earlier_patterns() {
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x4000)
+            .write_bytes(0x0100, &[0xAA, 0xBB, 0xCC, 0xDD])
+            .write_bytes(0x3000, &PATTERN)
+            .build();
+        let searcher = OffsetSearcher::builder(&reader)
+            .with_max_buffer_size(MIN_MAX_BUFFER_SIZE)
+            .build();
+
+        let found = searcher
+            .find_first_around(0x3000, 0x2000, &[&PATTERN, &[0xAA, 0xBB, 0xCC, 0xDD]], None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.address, 0x4000);
+        assert_eq!(found.pattern_index, 0);
+    }
+
+    #[test]
+    fn test_load_buffer_around_respects_cap() {
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x4000)
+            .build();
+        let mut searcher = OffsetSearcher::builder(&reader)
+            .with_max_buffer_size(MIN_MAX_BUFFER_SIZE)
+            .build();
+
+        assert!(searcher.load_buffer_around(0x2000, 0x800).is_ok());
+        assert!(searcher.load_buffer_around(0x3000, 0x2000).is_err());
+        // The same window streams fine
+        assert!(searcher.find_all_around(0x3000, 0x2000, &PATTERN).is_ok());
+    }
+
+    #[test]
+    fn test_streamed_search_fails_on_unreadable_window() {
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x4000)
+            .build();
+        let searcher = OffsetSearcher::builder(&reader)
+            .with_max_buffer_size(MIN_MAX_BUFFER_SIZE)
+            .build();
+
+        assert!(searcher.find_all_around(0x3000, 0x4000, &PATTERN).is_err());
     }
 }
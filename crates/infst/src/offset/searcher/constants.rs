@@ -41,6 +41,12 @@
 pub const INITIAL_SEARCH_SIZE: usize = 2 * 1024 * 1024;
 /// Maximum half-window size for memory search (total read size is 2x)
 pub const MAX_SEARCH_SIZE: usize = 300 * 1024 * 1024;
+/// Default cap on the bytes held in memory at once while scanning (16MB)
+///
+/// Search windows larger than this are streamed in chunks of this size.
+pub const DEFAULT_MAX_BUFFER_SIZE: usize = 16 * 1024 * 1024;
+/// Smallest accepted buffer cap (patterns must fit in one chunk with room to advance)
+pub const MIN_MAX_BUFFER_SIZE: usize = 4 * 1024;
 
 /// Expected offset from base address to SongList (approximately 49MB)
 ///
@@ -63,6 +69,9 @@ pub const MIN_SONG_ID: i32 = 1000;
 /// Maximum valid song ID in IIDX (reasonable upper bound)
 pub const MAX_SONG_ID: i32 = 50000;
 
+/// Maximum plausible bit balance (for validation)
+pub const MAX_BITS_BALANCE: i32 = 10_000_000;
+
 // ============================================================================
 // Relative Offsets (derived from historical analysis of 9 versions)
 // ============================================================================
@@ -3,17 +3,51 @@
 use tracing::{debug, info};
 
 use crate::error::{Error, Result};
-use crate::offset::{OffsetSignatureSet, OffsetsCollection};
+use crate::offset::{GameLayout, OffsetSignatureSet, OffsetsCollection, RelativeOffsets};
 use crate::process::ReadMemory;
 
 use super::constants::*;
-use super::validation::{validate_basic_memory_access, validate_signature_offsets};
+use super::validation::{
+    clear_invalid_optional_offsets, validate_basic_memory_access, validate_signature_offsets,
+};
+
+/// Names of the offsets already found, in search order
+fn found_offsets(offsets: &OffsetsCollection) -> Vec<&'static str> {
+    [
+        ("songList", offsets.song_list),
+        ("judgeData", offsets.judge_data),
+        ("playSettings", offsets.play_settings),
+        ("playData", offsets.play_data),
+        ("currentSong", offsets.current_song),
+        ("dataMap", offsets.data_map),
+        ("unlockData", offsets.unlock_data),
+    ]
+    .into_iter()
+    .filter(|(_, value)| *value != 0)
+    .map(|(name, _)| name)
+    .collect()
+}
+
+/// Turn a failed search phase into [`Error::OffsetSearchPartial`]
+fn partial_failure(offsets: &OffsetsCollection, target: &'static str, error: Error) -> Error {
+    let message = match error {
+        Error::OffsetSearchFailed { message, .. } => message,
+        other => other.to_string(),
+    };
+    Error::OffsetSearchPartial {
+        found: found_offsets(offsets),
+        target,
+        message,
+    }
+}
 
 /// Builder for creating OffsetSearcher with optional configuration
 pub struct OffsetSearcherBuilder<'a, R: ReadMemory> {
     reader: &'a R,
     initial_buffer_size: usize,
+    max_buffer_size: usize,
     song_list_hint: Option<u64>,
+    relative: RelativeOffsets,
 }
 
 impl<'a, R: ReadMemory> OffsetSearcherBuilder<'a, R> {
@@ -22,16 +56,33 @@ impl<'a, R: ReadMemory> OffsetSearcherBuilder<'a, R> {
         Self {
             reader,
             initial_buffer_size: INITIAL_SEARCH_SIZE,
+            max_buffer_size: DEFAULT_MAX_BUFFER_SIZE,
             song_list_hint: None,
+            relative: RelativeOffsets::CURRENT,
         }
     }
 
+    /// Search with the relative offsets of a game layout (default: newest build)
+    pub fn with_layout(mut self, layout: &GameLayout) -> Self {
+        self.relative = layout.relative;
+        self
+    }
+
     /// Set the initial buffer size for searching
     pub fn with_buffer_size(mut self, size: usize) -> Self {
         self.initial_buffer_size = size;
         self
     }
 
+    /// Cap the bytes held in memory at once while scanning
+    ///
+    /// Larger search windows are streamed in chunks of this size.
+    /// Values below 4KB are raised to 4KB.
+    pub fn with_max_buffer_size(mut self, size: usize) -> Self {
+        self.max_buffer_size = size.max(MIN_MAX_BUFFER_SIZE);
+        self
+    }
+
     /// Set a hint for the SongList address to speed up searching
     pub fn with_song_list_hint(mut self, hint: u64) -> Self {
         self.song_list_hint = Some(hint);
@@ -42,9 +93,11 @@ impl<'a, R: ReadMemory> OffsetSearcherBuilder<'a, R> {
     pub fn build(self) -> OffsetSearcher<'a, R> {
         OffsetSearcher {
             reader: self.reader,
-            buffer: Vec::with_capacity(self.initial_buffer_size),
+            buffer: Vec::with_capacity(self.initial_buffer_size.min(self.max_buffer_size)),
             buffer_base: 0,
+            max_buffer_size: self.max_buffer_size,
             song_list_hint: self.song_list_hint,
+            relative: self.relative,
         }
     }
 }
@@ -54,7 +107,11 @@ pub struct OffsetSearcher<'a, R: ReadMemory> {
     pub(crate) reader: &'a R,
     pub(crate) buffer: Vec<u8>,
     pub(crate) buffer_base: u64,
+    /// Most bytes read into memory at once (see [`OffsetSearcherBuilder::with_max_buffer_size`])
+    pub(crate) max_buffer_size: usize,
     pub(crate) song_list_hint: Option<u64>,
+    /// Expected distances between structures (see [`OffsetSearcherBuilder::with_layout`])
+    pub(crate) relative: RelativeOffsets,
 }
 
 impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
@@ -64,7 +121,9 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
             reader,
             buffer: Vec::new(),
             buffer_base: 0,
+            max_buffer_size: DEFAULT_MAX_BUFFER_SIZE,
             song_list_hint: None,
+            relative: RelativeOffsets::CURRENT,
         }
     }
 
@@ -81,11 +140,15 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
     /// Search for all offsets using code signatures (AOB scan)
     ///
     /// This method relies on RIP-relative code references instead of data patterns,
-    /// making it more resilient to data layout changes.
+    /// making it more resilient to data layout changes. Optional offsets are
+    /// searched last and left at 0 when `signatures` has no entry for them.
     pub fn search_all_with_signatures(
         &mut self,
         signatures: &OffsetSignatureSet,
-    ) -> Result<OffsetsCollection> {
+    ) -> Result<OffsetsCollection>
+    where
+        R: Sync,
+    {
         debug!("Starting signature-based offset detection...");
         let version = if signatures.version.trim().is_empty() {
             "unknown".to_string()
@@ -108,37 +171,50 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
 
         // Phase 2: JudgeData (relative search from SongList)
         info!("Phase 2: Searching JudgeData via relative offset from SongList...");
-        offsets.judge_data = self.search_judge_data_near_song_list(offsets.song_list)?;
+        offsets.judge_data = self
+            .search_judge_data_near_song_list(offsets.song_list)
+            .map_err(|e| partial_failure(&offsets, "judgeData", e))?;
         info!("  JudgeData: 0x{:X}", offsets.judge_data);
 
         // Phase 3: PlaySettings (relative search from JudgeData)
         info!("Phase 3: Searching PlaySettings via relative offset from JudgeData...");
-        offsets.play_settings = self.search_play_settings_near_judge_data(offsets.judge_data)?;
+        offsets.play_settings = self
+            .search_play_settings_near_judge_data(offsets.judge_data)
+            .map_err(|e| partial_failure(&offsets, "playSettings", e))?;
         info!("  PlaySettings: 0x{:X}", offsets.play_settings);
 
         // Phase 4: PlayData (relative search from PlaySettings)
         info!("Phase 4: Searching PlayData via relative offset from PlaySettings...");
-        offsets.play_data = self.search_play_data_near_play_settings(offsets.play_settings)?;
+        offsets.play_data = self
+            .search_play_data_near_play_settings(offsets.play_settings)
+            .map_err(|e| partial_failure(&offsets, "playData", e))?;
         info!("  PlayData: 0x{:X}", offsets.play_data);
 
         // Phase 5: CurrentSong (relative search from JudgeData)
         info!("Phase 5: Searching CurrentSong via relative offset from JudgeData...");
-        offsets.current_song = self.search_current_song_near_judge_data(offsets.judge_data)?;
+        offsets.current_song = self
+            .search_current_song_near_judge_data(offsets.judge_data)
+            .map_err(|e| partial_failure(&offsets, "currentSong", e))?;
         info!("  CurrentSong: 0x{:X}", offsets.current_song);
 
         // Phase 6: DataMap / UnlockData (pattern search, using SongList as hint)
         debug!("Phase 6: Searching remaining offsets with patterns...");
         let base = self.reader.base_address();
-        offsets.data_map = self.search_data_map_offset(base).or_else(|e| {
-            debug!(
-                "  DataMap search from base failed: {}, trying from SongList",
-                e
-            );
-            self.search_data_map_offset(offsets.song_list)
-        })?;
+        offsets.data_map = self
+            .search_data_map_offset(base)
+            .or_else(|e| {
+                debug!(
+                    "  DataMap search from base failed: {}, trying from SongList",
+                    e
+                );
+                self.search_data_map_offset(offsets.song_list)
+            })
+            .map_err(|e| partial_failure(&offsets, "dataMap", e))?;
         debug!("  DataMap: 0x{:X}", offsets.data_map);
 
-        offsets.unlock_data = self.search_unlock_data_offset(offsets.song_list)?;
+        offsets.unlock_data = self
+            .search_unlock_data_offset(offsets.song_list)
+            .map_err(|e| partial_failure(&offsets, "unlockData", e))?;
         debug!("  UnlockData: 0x{:X}", offsets.unlock_data);
 
         if !offsets.is_valid() {
@@ -147,6 +223,10 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
             ));
         }
 
+        // Phase 7: Optional offsets (code signatures, never fatal)
+        debug!("Phase 7: Searching optional offsets via code signatures...");
+        self.search_optional_offsets(signatures, &mut offsets);
+
         debug!("Signature-based offset detection completed successfully");
         Ok(offsets)
     }
@@ -236,7 +316,16 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
     /// Validate all offsets in a collection (delegates to validation module)
     #[inline]
     pub fn validate_signature_offsets(&self, offsets: &OffsetsCollection) -> bool {
-        validate_signature_offsets(self.reader, offsets)
+        validate_signature_offsets(self.reader, offsets, &self.relative)
+    }
+
+    /// Clear optional offsets that don't point at their block (delegates to validation module)
+    #[inline]
+    pub fn clear_invalid_optional_offsets(
+        &self,
+        offsets: &mut OffsetsCollection,
+    ) -> Vec<&'static str> {
+        clear_invalid_optional_offsets(self.reader, offsets)
     }
 
     /// Validate basic memory access for file-loaded offsets (delegates to validation module)
@@ -256,12 +345,27 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
     }
 
     /// Load buffer around a center address for searching
+    ///
+    /// The whole window is held in memory, so it must fit in the buffer cap;
+    /// large searches stream instead (see `find_first_around` / `find_all_around`).
     pub fn load_buffer_around(&mut self, center: u64, distance: usize) -> Result<()> {
+        let (start, len) = self.search_window(center, distance);
+        if len > self.max_buffer_size {
+            return Err(Error::offset_search_failed(format!(
+                "Buffer of {} bytes exceeds the {} byte cap",
+                len, self.max_buffer_size
+            )));
+        }
+        self.buffer_base = start;
+        self.buffer = self.reader.read_bytes(start, len)?;
+        Ok(())
+    }
+
+    /// Start address and length of the +/-`distance` window around `center`
+    pub(crate) fn search_window(&self, center: u64, distance: usize) -> (u64, usize) {
         let base = self.reader.base_address();
         // Don't go below base address (unmapped memory region)
         let start = center.saturating_sub(distance as u64).max(base);
-        self.buffer_base = start;
-        self.buffer = self.reader.read_bytes(start, distance * 2)?;
-        Ok(())
+        (start, distance.saturating_mul(2))
     }
 }
@@ -60,11 +60,9 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
         let mut fallback: Option<u64> = None;
 
         while search_size <= MAX_SEARCH_SIZE {
-            if self.load_buffer_around(base_hint, search_size).is_err() {
+            let Ok(matches) = self.find_all_around(base_hint, search_size, &pattern) else {
                 break;
-            }
-
-            let matches = self.find_all_matches(&pattern);
+            };
             for match_addr in matches {
                 let candidate = match_addr.wrapping_add_signed(-24);
                 if fallback.is_none() {
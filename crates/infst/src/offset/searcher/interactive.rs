@@ -9,7 +9,6 @@ use crate::play::PlayType;
 use crate::process::ReadMemory;
 
 use super::OffsetSearcher;
-use super::constants::*;
 use super::types::{InteractiveSearchResult, JudgeInput, SearchPrompter};
 use super::utils::merge_byte_representations;
 
@@ -174,8 +173,6 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
         base_hint: u64,
         judge: &JudgeInput,
     ) -> Result<(u64, PlayType)> {
-        self.load_buffer_around(base_hint, INITIAL_SEARCH_SIZE)?;
-
         let (pattern_p1, pattern_p2) = self.build_judge_patterns(judge);
         let patterns = vec![pattern_p1, pattern_p2];
 
@@ -198,8 +195,6 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
         difficulty: u32,
         exclude: Option<u64>,
     ) -> Result<u64> {
-        self.load_buffer_around(base_hint, INITIAL_SEARCH_SIZE)?;
-
         let pattern = merge_byte_representations(&[song_id as i32, difficulty as i32]);
         self.fetch_and_search(base_hint, &pattern, 0, exclude)
     }
@@ -6,16 +6,15 @@
 use tracing::debug;
 
 use crate::error::{Error, Result};
-use crate::offset::{CodeSignature, OffsetSignatureSet};
-use crate::process::{ByteBuffer, ReadMemory};
+use crate::offset::OffsetSignatureSet;
+use crate::process::ReadMemory;
+use crate::process::pattern::MultiPattern;
 
 use super::OffsetSearcher;
-use super::constants::{
-    CODE_SCAN_CHUNK_SIZE, CODE_SCAN_LIMIT, MIN_EXPECTED_SONGS, MIN_VALID_DATA_ADDRESS,
-};
+use super::constants::MIN_EXPECTED_SONGS;
 use super::validation::OffsetValidation;
 
-impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
+impl<'a, R: ReadMemory + Sync> OffsetSearcher<'a, R> {
     /// Count songs using alternate structure (song_id + folder + ASCII data)
     ///
     /// Structure seems to be variable size, search for consecutive valid song_ids.
@@ -116,195 +115,6 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
         self.search_song_list_offset(base)
     }
 
-    /// Search for an offset using code signatures (AOB scan)
-    ///
-    /// NOTE: This method is currently unused because existing signatures don't work
-    /// on newer game versions (2026012800+). Kept for potential future use when
-    /// stable signatures are discovered.
-    pub fn search_offset_by_signature<F>(
-        &self,
-        signatures: &OffsetSignatureSet,
-        name: &str,
-        validate: F,
-    ) -> Result<u64>
-    where
-        F: Fn(&Self, u64) -> bool,
-    {
-        let entry = signatures.entry(name).ok_or_else(|| {
-            Error::offset_search_failed(format!("Signature entry '{}' not found", name))
-        })?;
-
-        for signature in &entry.signatures {
-            let candidates = self.resolve_signature_targets(signature)?;
-            if !candidates.is_empty() {
-                debug!(
-                    "  {}: signature {} found {} raw candidates: {:X?}",
-                    name,
-                    signature.pattern,
-                    candidates.len(),
-                    &candidates[..candidates.len().min(5)]
-                );
-            }
-            let mut valid: Vec<u64> = candidates
-                .into_iter()
-                .filter(|addr| addr.is_multiple_of(4))
-                .filter(|addr| validate(self, *addr))
-                .collect();
-
-            if !valid.is_empty() {
-                valid.sort_unstable();
-                let selected = valid[0];
-                debug!(
-                    "  {}: selected 0x{:X} (signature: {}, candidates: {})",
-                    name,
-                    selected,
-                    signature.pattern,
-                    valid.len()
-                );
-                return Ok(selected);
-            }
-        }
-
-        Err(Error::offset_search_failed(format!(
-            "No valid candidates found for {} via signatures",
-            name
-        )))
-    }
-
-    /// Resolve signature to target addresses
-    pub fn resolve_signature_targets(&self, signature: &CodeSignature) -> Result<Vec<u64>> {
-        let pattern = signature.pattern_bytes()?;
-        let matches = self.scan_code_for_pattern(&pattern)?;
-        let mut targets = Vec::new();
-
-        for match_addr in matches {
-            let instr_addr = match_addr + signature.instr_offset as u64;
-            let disp_addr = instr_addr + signature.disp_offset as u64;
-
-            let disp_bytes = match self.reader.read_bytes(disp_addr, 4) {
-                Ok(bytes) => bytes,
-                Err(_) => continue,
-            };
-
-            let disp = ByteBuffer::new(&disp_bytes).read_i32_at(0).unwrap_or(0);
-            let next_ip = instr_addr + signature.instr_len as u64;
-            let mut target = next_ip.wrapping_add_signed(disp as i64);
-
-            if signature.deref {
-                match self.reader.read_u64(target) {
-                    Ok(ptr) => target = ptr,
-                    Err(_) => continue,
-                }
-            }
-
-            if signature.addend != 0 {
-                target = target.wrapping_add_signed(signature.addend);
-            }
-
-            // Validate address is within expected range (above ImageBase)
-            if target < MIN_VALID_DATA_ADDRESS {
-                debug!(
-                    "  Rejecting invalid address 0x{:X} (below MIN_VALID_DATA_ADDRESS 0x{:X})",
-                    target, MIN_VALID_DATA_ADDRESS
-                );
-                continue;
-            }
-
-            if target != 0 {
-                targets.push(target);
-            }
-        }
-
-        targets.sort_unstable();
-        targets.dedup();
-        Ok(targets)
-    }
-
-    /// Scan code section for a pattern with wildcards
-    pub fn scan_code_for_pattern(&self, pattern: &[Option<u8>]) -> Result<Vec<u64>> {
-        let base = self.reader.base_address();
-        let mut results: Vec<u64> = Vec::new();
-        let mut offset: u64 = 0;
-        let mut scanned: usize = 0;
-        let mut tail: Vec<u8> = Vec::new();
-
-        while scanned < CODE_SCAN_LIMIT {
-            let remaining = CODE_SCAN_LIMIT - scanned;
-            let read_size = remaining.min(CODE_SCAN_CHUNK_SIZE);
-            let addr = base + offset;
-
-            let chunk = match self.reader.read_bytes(addr, read_size) {
-                Ok(bytes) => bytes,
-                Err(e) => {
-                    if scanned == 0 {
-                        return Err(Error::offset_search_failed(format!(
-                            "Failed to read code section: {}",
-                            e
-                        )));
-                    }
-                    debug!(
-                        "Code scan stopped at offset {:#x} (scanned {:#x} bytes): {}",
-                        offset, scanned, e
-                    );
-                    break;
-                }
-            };
-
-            let mut data = Vec::with_capacity(tail.len() + chunk.len());
-            data.extend_from_slice(&tail);
-            data.extend_from_slice(&chunk);
-
-            let data_base = addr.saturating_sub(tail.len() as u64);
-            results.extend(self.find_matches_with_wildcards(&data, data_base, pattern));
-
-            if pattern.len() > 1 {
-                let keep = pattern.len() - 1;
-                if data.len() >= keep {
-                    tail = data[data.len() - keep..].to_vec();
-                } else {
-                    tail = data;
-                }
-            } else {
-                tail.clear();
-            }
-
-            scanned += read_size;
-            offset += read_size as u64;
-        }
-
-        results.sort_unstable();
-        results.dedup();
-        Ok(results)
-    }
-
-    /// Find all matches of a pattern with wildcards in a buffer
-    fn find_matches_with_wildcards(
-        &self,
-        buffer: &[u8],
-        base_addr: u64,
-        pattern: &[Option<u8>],
-    ) -> Vec<u64> {
-        if pattern.is_empty() || buffer.len() < pattern.len() {
-            return Vec::new();
-        }
-
-        let mut results = Vec::new();
-        let last = buffer.len() - pattern.len();
-
-        'outer: for i in 0..=last {
-            for (j, byte) in pattern.iter().enumerate() {
-                if let Some(value) = byte
-                    && buffer[i + j] != *value
-                {
-                    continue 'outer;
-                }
-            }
-            results.push(base_addr + i as u64);
-        }
-
-        results
-    }
-
     /// Search for code that references a specific data address
     ///
     /// Looks for x64 RIP-relative LEA/MOV instructions.
@@ -319,29 +129,28 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
             [0x48, 0x8D, 0x05], // LEA rax
         ];
 
-        for prefix in lea_prefixes {
-            for (pos, window) in self.buffer.windows(7).enumerate() {
-                if window[0..3] == prefix {
-                    // Extract RIP-relative offset.
-                    let offset_bytes: [u8; 4] = match window[3..7].try_into() {
-                        Ok(bytes) => bytes,
-                        Err(_) => continue,
-                    };
-                    let rel_offset = i32::from_le_bytes(offset_bytes);
-
-                    // Calculate absolute address
-                    // RIP points to next instruction (current_pos + 7)
-                    let code_addr = self.buffer_base + pos as u64;
-                    let next_ip = code_addr + 7;
-                    let ref_addr = next_ip.wrapping_add_signed(rel_offset as i64);
+        let Ok(prefixes) = MultiPattern::new(&lea_prefixes) else {
+            return false;
+        };
+
+        prefixes.find_iter(&self.buffer).any(|(_, pos)| {
+            // Extract RIP-relative offset.
+            let Some(offset_bytes) = self
+                .buffer
+                .get(pos + 3..pos + 7)
+                .and_then(|bytes| <[u8; 4]>::try_from(bytes).ok())
+            else {
+                return false;
+            };
+            let rel_offset = i32::from_le_bytes(offset_bytes);
 
-                    if ref_addr == target_addr {
-                        return true;
-                    }
-                }
-            }
-        }
+            // Calculate absolute address
+            // RIP points to next instruction (current_pos + 7)
+            let code_addr = self.buffer_base + pos as u64;
+            let next_ip = code_addr + 7;
+            let ref_addr = next_ip.wrapping_add_signed(rel_offset as i64);
 
-        false
+            ref_addr == target_addr
+        })
     }
 }
@@ -13,6 +13,8 @@
 //! - [`data_map`]: DataMap and UnlockData detection
 //! - [`buffer`]: Buffer management and pattern search helpers
 //! - [`interactive`]: User-guided offset discovery workflow
+//! - [`optional`]: Optional offsets (profile, currency, lane cover) found by code signature
+//! - [`scripted`]: Prompter with pre-recorded answers for the interactive workflow
 //! - [`validation`]: Offset validation functions
 //! - [`pattern`]: Pattern search utilities
 //! - [`legacy`]: Legacy signature-based search (feature-gated)
@@ -27,16 +29,19 @@
 //! 4. **PlayData**: Relative offset from PlaySettings (~0x2A0 above)
 //! 5. **CurrentSong**: Relative offset from JudgeData (~0x1E4 above)
 //! 6. **DataMap/UnlockData**: Pattern search with validation
+//! 7. **Optional offsets**: Code signatures from a signature file (profile, currency, lane cover)
 
 mod buffer;
-mod constants;
+pub(crate) mod constants;
 mod core;
 mod data_map;
 mod interactive;
 #[cfg(feature = "legacy-signatures")]
 pub mod legacy;
+mod optional;
 pub mod pattern;
 mod relative_search;
+mod scripted;
 #[cfg(feature = "legacy-signatures")]
 pub mod search;
 mod song_list;
@@ -46,11 +51,12 @@ pub mod validation;
 
 // Re-export core types
 pub use core::{OffsetSearcher, OffsetSearcherBuilder};
+pub use scripted::ScriptedPrompter;
 pub use types::*;
 pub use utils::merge_byte_representations;
 
 // Re-export validation functions and trait
 pub use validation::{
-    OffsetValidation, validate_basic_memory_access, validate_new_version_text_table,
-    validate_signature_offsets,
+    OffsetValidation, clear_invalid_optional_offsets, validate_basic_memory_access,
+    validate_new_version_text_table, validate_signature_offsets,
 };
@@ -0,0 +1,325 @@
+//! Optional offsets located by code signature
+//!
+//! The profile, currency and lane cover blocks have no stable distance to the song list,
+//! so they are found through the code that references them (RIP-relative
+//! LEA/MOV), like the signature search of older builds. Their signatures
+//! aren't part of the builtin set; entries come from a signature file (see
+//! [`merge_signature_file`]). A missing entry or an unmatched signature
+//! leaves the offset at 0.
+//!
+//! [`merge_signature_file`]: crate::offset::merge_signature_file
+
+use tracing::{debug, info};
+
+use crate::error::{Error, Result};
+use crate::offset::{
+    CURRENCY_SIGNATURE, CodeSignature, LANE_COVER_SIGNATURE, OffsetSignatureSet, OffsetsCollection,
+    PROFILE_SIGNATURE,
+};
+use crate::process::{ByteBuffer, ReadMemory};
+
+use super::OffsetSearcher;
+use super::constants::MIN_VALID_DATA_ADDRESS;
+use super::pattern::{CodeScanOptions, scan_code_for_pattern};
+use super::validation::OffsetValidation;
+
+impl<R: ReadMemory + Sync> OffsetSearcher<'_, R> {
+    /// Search the optional offsets whose entries `signatures` has, filling
+    /// the ones still at 0
+    ///
+    /// Failures are logged and leave the offset at 0; the required offsets
+    /// don't depend on these.
+    pub fn search_optional_offsets(
+        &self,
+        signatures: &OffsetSignatureSet,
+        offsets: &mut OffsetsCollection,
+    ) {
+        self.search_optional_offset(
+            signatures,
+            PROFILE_SIGNATURE,
+            &mut offsets.profile,
+            Self::search_profile_offset,
+        );
+        self.search_optional_offset(
+            signatures,
+            CURRENCY_SIGNATURE,
+            &mut offsets.currency,
+            Self::search_currency_offset,
+        );
+        self.search_optional_offset(
+            signatures,
+            LANE_COVER_SIGNATURE,
+            &mut offsets.lane_cover,
+            Self::search_lane_cover_offset,
+        );
+    }
+
+    fn search_optional_offset(
+        &self,
+        signatures: &OffsetSignatureSet,
+        name: &str,
+        offset: &mut u64,
+        search: fn(&Self, &OffsetSignatureSet) -> Result<u64>,
+    ) {
+        if *offset != 0 || signatures.entry(name).is_none() {
+            return;
+        }
+        match search(self, signatures) {
+            Ok(addr) => {
+                info!("  {}: 0x{:X}", name, addr);
+                *offset = addr;
+            }
+            Err(e) => debug!("  {} not found: {}", name, e),
+        }
+    }
+
+    /// Search the player profile block using a `profile` signature entry
+    pub fn search_profile_offset(&self, signatures: &OffsetSignatureSet) -> Result<u64> {
+        self.search_offset_by_signature(signatures, PROFILE_SIGNATURE, |searcher, addr| {
+            searcher.reader.validate_profile_address(addr)
+        })
+    }
+
+    /// Search the currency block (bits and tickets) using a `currency`
+    /// signature entry
+    pub fn search_currency_offset(&self, signatures: &OffsetSignatureSet) -> Result<u64> {
+        self.search_offset_by_signature(signatures, CURRENCY_SIGNATURE, |searcher, addr| {
+            searcher.reader.validate_currency_address(addr)
+        })
+    }
+
+    /// Search the lane cover block using a `laneCover` signature entry
+    ///
+    /// Validated on the P1 side, which needs in-lane cover values and a
+    /// plausible green number.
+    pub fn search_lane_cover_offset(&self, signatures: &OffsetSignatureSet) -> Result<u64> {
+        self.search_offset_by_signature(signatures, LANE_COVER_SIGNATURE, |searcher, addr| {
+            searcher.reader.validate_lane_cover_address(addr)
+        })
+    }
+
+    /// Search for an offset using code signatures (AOB scan)
+    ///
+    /// The lowest candidate that passes `validate` wins.
+    pub fn search_offset_by_signature<F>(
+        &self,
+        signatures: &OffsetSignatureSet,
+        name: &str,
+        validate: F,
+    ) -> Result<u64>
+    where
+        F: Fn(&Self, u64) -> bool,
+    {
+        let entry = signatures.entry(name).ok_or_else(|| {
+            Error::offset_search_failed(format!("Signature entry '{}' not found", name))
+        })?;
+
+        for signature in &entry.signatures {
+            let candidates = self.resolve_signature_targets(signature)?;
+            if !candidates.is_empty() {
+                debug!(
+                    "  {}: signature {} found {} raw candidates: {:X?}",
+                    name,
+                    signature.pattern,
+                    candidates.len(),
+                    &candidates[..candidates.len().min(5)]
+                );
+            }
+            let mut valid: Vec<u64> = candidates
+                .into_iter()
+                .filter(|addr| addr.is_multiple_of(4))
+                .filter(|addr| validate(self, *addr))
+                .collect();
+
+            if !valid.is_empty() {
+                valid.sort_unstable();
+                let selected = valid[0];
+                debug!(
+                    "  {}: selected 0x{:X} (signature: {}, candidates: {})",
+                    name,
+                    selected,
+                    signature.pattern,
+                    valid.len()
+                );
+                return Ok(selected);
+            }
+        }
+
+        Err(Error::offset_search_failed(format!(
+            "No valid candidates found for {} via signatures",
+            name
+        )))
+    }
+
+    /// Resolve signature to target addresses
+    pub fn resolve_signature_targets(&self, signature: &CodeSignature) -> Result<Vec<u64>> {
+        let pattern = signature.pattern_bytes()?;
+        let matches = self.scan_code_for_pattern(&pattern)?;
+        let mut targets = Vec::new();
+
+        for match_addr in matches {
+            let instr_addr = match_addr + signature.instr_offset as u64;
+            let disp_addr = instr_addr + signature.disp_offset as u64;
+
+            let disp_bytes = match self.reader.read_bytes(disp_addr, 4) {
+                Ok(bytes) => bytes,
+                Err(_) => continue,
+            };
+
+            let disp = ByteBuffer::new(&disp_bytes).read_i32_at(0).unwrap_or(0);
+            let next_ip = instr_addr + signature.instr_len as u64;
+            let mut target = next_ip.wrapping_add_signed(disp as i64);
+
+            if signature.deref {
+                match self.reader.read_u64(target) {
+                    Ok(ptr) => target = ptr,
+                    Err(_) => continue,
+                }
+            }
+
+            if signature.addend != 0 {
+                target = target.wrapping_add_signed(signature.addend);
+            }
+
+            // Validate address is within expected range (above ImageBase)
+            if target < MIN_VALID_DATA_ADDRESS {
+                debug!(
+                    "  Rejecting invalid address 0x{:X} (below MIN_VALID_DATA_ADDRESS 0x{:X})",
+                    target, MIN_VALID_DATA_ADDRESS
+                );
+                continue;
+            }
+
+            if target != 0 {
+                targets.push(target);
+            }
+        }
+
+        targets.sort_unstable();
+        targets.dedup();
+        Ok(targets)
+    }
+
+    /// Scan code section for a pattern with wildcards
+    pub fn scan_code_for_pattern(&self, pattern: &[Option<u8>]) -> Result<Vec<u64>> {
+        scan_code_for_pattern(self.reader, pattern, CodeScanOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offset::OffsetSignatureEntry;
+    use crate::offset::searcher::constants::CODE_SCAN_CHUNK_SIZE;
+    use crate::process::{MockMemoryBuilder, MockMemoryReader};
+
+    const BASE: u64 = MIN_VALID_DATA_ADDRESS;
+    const PROFILE_AT: usize = 0x1000;
+    const CURRENCY_AT: usize = 0x1100;
+    const LANE_COVER_AT: usize = 0x1200;
+
+    /// `LEA reg, [rip+disp]` at `at` followed by `tail`
+    fn lea(at: usize, target: usize, tail: &[u8]) -> Vec<u8> {
+        let disp = (target - (at + 7)) as i32;
+        let mut bytes = vec![0x48, 0x8D, 0x0D];
+        bytes.extend_from_slice(&disp.to_le_bytes());
+        bytes.extend_from_slice(tail);
+        bytes
+    }
+
+    /// Code at 0x100 referencing a profile block, at 0x200 a currency block
+    /// and at 0x300 a lane cover block
+    fn game_memory() -> MockMemoryReader {
+        MockMemoryBuilder::new()
+            .base(BASE)
+            // The first code scan chunk must be readable
+            .with_size(CODE_SCAN_CHUNK_SIZE)
+            .write_bytes(0x100, &lea(0x100, PROFILE_AT, &[0x44, 0x89]))
+            .write_bytes(0x200, &lea(0x200, CURRENCY_AT, &[0x8B, 0x01]))
+            .write_bytes(PROFILE_AT, b"DJ-AB")
+            .write_i32(PROFILE_AT + 0x10, 18)
+            .write_i32(PROFILE_AT + 0x14, -1)
+            .write_u32(PROFILE_AT + 0x18, 1234)
+            .write_i32(CURRENCY_AT, 15000)
+            .write_i32(CURRENCY_AT + 0x04, 3)
+            .write_bytes(0x300, &lea(0x300, LANE_COVER_AT, &[0x8B, 0x41]))
+            .write_i32(LANE_COVER_AT, 250)
+            .write_i32(LANE_COVER_AT + 0x0C, 290)
+            .build()
+    }
+
+    fn entry(name: &str, pattern: &str) -> OffsetSignatureEntry {
+        OffsetSignatureEntry {
+            name: name.to_string(),
+            signatures: vec![CodeSignature {
+                pattern: pattern.to_string(),
+                instr_offset: 0,
+                disp_offset: 3,
+                instr_len: 7,
+                deref: false,
+                addend: 0,
+            }],
+        }
+    }
+
+    fn optional_signatures() -> OffsetSignatureSet {
+        OffsetSignatureSet {
+            version: "*".to_string(),
+            entries: vec![
+                entry(PROFILE_SIGNATURE, "48 8D 0D ?? ?? ?? ?? 44 89"),
+                entry(CURRENCY_SIGNATURE, "48 8D 0D ?? ?? ?? ?? 8B 01"),
+                entry(LANE_COVER_SIGNATURE, "48 8D 0D ?? ?? ?? ?? 8B 41"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_search_optional_offsets() {
+        let reader = game_memory();
+        let searcher = OffsetSearcher::new(&reader);
+
+        let mut offsets = OffsetsCollection::default();
+        searcher.search_optional_offsets(&optional_signatures(), &mut offsets);
+        assert_eq!(offsets.profile, BASE + PROFILE_AT as u64);
+        assert_eq!(offsets.currency, BASE + CURRENCY_AT as u64);
+        assert_eq!(offsets.lane_cover, BASE + LANE_COVER_AT as u64);
+
+        // No entry: left alone
+        let mut offsets = OffsetsCollection::default();
+        searcher.search_optional_offsets(&crate::offset::builtin_signatures(), &mut offsets);
+        assert_eq!(offsets.profile, 0);
+        assert_eq!(offsets.currency, 0);
+        assert_eq!(offsets.lane_cover, 0);
+    }
+
+    #[test]
+    fn test_clear_invalid_optional_offsets() {
+        let reader = game_memory();
+        let searcher = OffsetSearcher::new(&reader);
+
+        let mut offsets = OffsetsCollection {
+            profile: BASE + PROFILE_AT as u64,
+            currency: BASE + CURRENCY_AT as u64,
+            lane_cover: BASE + LANE_COVER_AT as u64,
+            ..Default::default()
+        };
+        assert!(
+            searcher
+                .clear_invalid_optional_offsets(&mut offsets)
+                .is_empty()
+        );
+        assert_eq!(offsets.profile, BASE + PROFILE_AT as u64);
+
+        // Code bytes: no DJ NAME, and counts far above their maximums
+        offsets.profile = BASE + 0x100;
+        offsets.currency = BASE + 0x100;
+        offsets.lane_cover = BASE + 0x100;
+        assert_eq!(
+            searcher.clear_invalid_optional_offsets(&mut offsets),
+            vec!["profile", "currency", "laneCover"]
+        );
+        assert_eq!(offsets.profile, 0);
+        assert_eq!(offsets.currency, 0);
+        assert_eq!(offsets.lane_cover, 0);
+    }
+}
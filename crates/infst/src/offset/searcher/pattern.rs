@@ -3,20 +3,29 @@
 //! Provides functions for finding byte patterns in memory buffers,
 //! including support for wildcard matching.
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use memchr::memmem;
 use tracing::debug;
 
 use crate::error::{Error, Result};
 use crate::offset::CodeSignature;
 use crate::process::ReadMemory;
+use crate::process::pattern::{find_masked_pattern, find_pattern};
 
 use super::constants::*;
 use super::types::SearchResult;
 
 /// Pattern search methods for OffsetSearcher
+///
+/// The buffer never holds more than the buffer cap (16MB by default), so the
+/// progressive searches stop widening once a window would exceed it.
 pub struct PatternSearcher<'a, R: ReadMemory> {
     reader: &'a R,
     buffer: Vec<u8>,
     buffer_base: u64,
+    max_buffer_size: usize,
 }
 
 impl<'a, R: ReadMemory> PatternSearcher<'a, R> {
@@ -25,19 +34,50 @@ impl<'a, R: ReadMemory> PatternSearcher<'a, R> {
             reader,
             buffer: Vec::new(),
             buffer_base: 0,
+            max_buffer_size: DEFAULT_MAX_BUFFER_SIZE,
         }
     }
 
+    /// Cap the bytes held in memory at once (values below 4KB are raised to 4KB)
+    pub fn with_max_buffer_size(mut self, size: usize) -> Self {
+        self.max_buffer_size = size.max(MIN_MAX_BUFFER_SIZE);
+        self
+    }
+
     /// Load a buffer around a central address
+    ///
+    /// Fails without reading if the +/-`distance` window exceeds the buffer cap.
     pub fn load_buffer_around(&mut self, center: u64, distance: usize) -> Result<()> {
+        let len = distance
+            .checked_mul(2)
+            .filter(|&len| len <= self.max_buffer_size)
+            .ok_or_else(|| {
+                Error::offset_search_failed(format!(
+                    "Buffer of +/-{} bytes exceeds the {} byte cap",
+                    distance, self.max_buffer_size
+                ))
+            })?;
         let base = self.reader.base_address();
         // Don't go below base address (unmapped memory region)
         let start = center.saturating_sub(distance as u64).max(base);
         self.buffer_base = start;
-        self.buffer = self.reader.read_bytes(start, distance * 2)?;
+        self.buffer = self.reader.read_bytes(start, len)?;
         Ok(())
     }
 
+    /// Largest half-window the progressive searches widen to
+    fn max_search_size(&self) -> usize {
+        MAX_SEARCH_SIZE.min(self.max_buffer_size / 2)
+    }
+
+    fn not_found(&self, what: &str) -> Error {
+        Error::offset_search_failed(format!(
+            "{} not found within +/-{} KB",
+            what,
+            self.max_search_size() / 1024
+        ))
+    }
+
     /// Get the current buffer base address
     pub fn buffer_base(&self) -> u64 {
         self.buffer_base
@@ -50,23 +90,18 @@ impl<'a, R: ReadMemory> PatternSearcher<'a, R> {
 
     /// Find the first match of a pattern in the current buffer
     pub fn find_pattern(&self, pattern: &[u8], ignore_address: Option<u64>) -> Option<usize> {
-        self.buffer
-            .windows(pattern.len())
-            .enumerate()
-            .find(|(pos, window)| {
-                let addr = self.buffer_base + *pos as u64;
-                *window == pattern && (ignore_address != Some(addr))
-            })
-            .map(|(pos, _)| pos)
+        if pattern.is_empty() {
+            return None;
+        }
+        memmem::find_iter(&self.buffer, pattern)
+            .find(|&pos| ignore_address != Some(self.buffer_base + pos as u64))
     }
 
     /// Find all matches of a pattern in the current buffer
     pub fn find_all_matches(&self, pattern: &[u8]) -> Vec<u64> {
-        self.buffer
-            .windows(pattern.len())
-            .enumerate()
-            .filter(|(_, window)| *window == pattern)
-            .map(|(pos, _)| self.buffer_base + pos as u64)
+        find_pattern(&self.buffer, pattern)
+            .into_iter()
+            .map(|pos| self.buffer_base + pos as u64)
             .collect()
     }
 
@@ -77,25 +112,10 @@ impl<'a, R: ReadMemory> PatternSearcher<'a, R> {
         base_addr: u64,
         pattern: &[Option<u8>],
     ) -> Vec<u64> {
-        if pattern.is_empty() || buffer.len() < pattern.len() {
-            return Vec::new();
-        }
-
-        let mut results = Vec::new();
-        let last = buffer.len() - pattern.len();
-
-        'outer: for i in 0..=last {
-            for (j, byte) in pattern.iter().enumerate() {
-                if let Some(value) = byte
-                    && buffer[i + j] != *value
-                {
-                    continue 'outer;
-                }
-            }
-            results.push(base_addr + i as u64);
-        }
-
-        results
+        find_masked_pattern(buffer, pattern)
+            .into_iter()
+            .map(|pos| base_addr + pos as u64)
+            .collect()
     }
 
     /// Search for a pattern with progressive buffer expansion
@@ -106,9 +126,9 @@ impl<'a, R: ReadMemory> PatternSearcher<'a, R> {
         offset_from_match: i64,
         ignore_address: Option<u64>,
     ) -> Result<u64> {
-        let mut search_size = INITIAL_SEARCH_SIZE;
+        let mut search_size = INITIAL_SEARCH_SIZE.min(self.max_search_size());
 
-        while search_size <= MAX_SEARCH_SIZE {
+        while search_size <= self.max_search_size() {
             self.load_buffer_around(hint, search_size)?;
 
             if let Some(pos) = self.find_pattern(pattern, ignore_address) {
@@ -120,10 +140,7 @@ impl<'a, R: ReadMemory> PatternSearcher<'a, R> {
             search_size *= 2;
         }
 
-        Err(Error::offset_search_failed(format!(
-            "Pattern not found within +/-{} MB",
-            MAX_SEARCH_SIZE / 1024 / 1024
-        )))
+        Err(self.not_found("Pattern"))
     }
 
     /// Search for a pattern, returning the LAST match
@@ -135,10 +152,10 @@ impl<'a, R: ReadMemory> PatternSearcher<'a, R> {
         pattern: &[u8],
         offset_from_match: i64,
     ) -> Result<u64> {
-        let mut search_size = INITIAL_SEARCH_SIZE;
+        let mut search_size = INITIAL_SEARCH_SIZE.min(self.max_search_size());
         let mut last_matches: Vec<u64> = Vec::new();
 
-        while search_size <= MAX_SEARCH_SIZE {
+        while search_size <= self.max_search_size() {
             match self.load_buffer_around(hint, search_size) {
                 Ok(()) => {
                     last_matches = self.find_all_matches(pattern);
@@ -151,10 +168,7 @@ impl<'a, R: ReadMemory> PatternSearcher<'a, R> {
         }
 
         if last_matches.is_empty() {
-            return Err(Error::offset_search_failed(format!(
-                "Pattern not found within +/-{} MB",
-                MAX_SEARCH_SIZE / 1024 / 1024
-            )));
+            return Err(self.not_found("Pattern"));
         }
 
         let last_match = *last_matches.last().expect("matches is non-empty");
@@ -170,9 +184,9 @@ impl<'a, R: ReadMemory> PatternSearcher<'a, R> {
         offset_from_match: i64,
         ignore_address: Option<u64>,
     ) -> Result<SearchResult> {
-        let mut search_size = INITIAL_SEARCH_SIZE;
+        let mut search_size = INITIAL_SEARCH_SIZE.min(self.max_search_size());
 
-        while search_size <= MAX_SEARCH_SIZE {
+        while search_size <= self.max_search_size() {
             self.load_buffer_around(hint, search_size)?;
 
             for (index, pattern) in patterns.iter().enumerate() {
@@ -189,72 +203,22 @@ impl<'a, R: ReadMemory> PatternSearcher<'a, R> {
             search_size *= 2;
         }
 
-        Err(Error::offset_search_failed(format!(
-            "None of {} patterns found within +/-{} MB",
-            patterns.len(),
-            MAX_SEARCH_SIZE / 1024 / 1024
-        )))
+        Err(self.not_found(&format!("None of {} patterns", patterns.len())))
     }
 
     /// Scan code section for a byte pattern with wildcards
-    pub fn scan_code_for_pattern(&self, pattern: &[Option<u8>]) -> Result<Vec<u64>> {
-        let base = self.reader.base_address();
-        let mut results: Vec<u64> = Vec::new();
-        let mut offset: u64 = 0;
-        let mut scanned: usize = 0;
-        let mut tail: Vec<u8> = Vec::new();
-
-        while scanned < CODE_SCAN_LIMIT {
-            let remaining = CODE_SCAN_LIMIT - scanned;
-            let read_size = remaining.min(CODE_SCAN_CHUNK_SIZE);
-            let addr = base + offset;
-
-            let chunk = match self.reader.read_bytes(addr, read_size) {
-                Ok(bytes) => bytes,
-                Err(e) => {
-                    if scanned == 0 {
-                        return Err(Error::offset_search_failed(format!(
-                            "Failed to read code section: {}",
-                            e
-                        )));
-                    }
-                    debug!(
-                        "Code scan stopped at offset {:#x} (scanned {:#x} bytes): {}",
-                        offset, scanned, e
-                    );
-                    break;
-                }
-            };
-
-            let mut data = Vec::with_capacity(tail.len() + chunk.len());
-            data.extend_from_slice(&tail);
-            data.extend_from_slice(&chunk);
-
-            let data_base = addr.saturating_sub(tail.len() as u64);
-            results.extend(self.find_matches_with_wildcards(&data, data_base, pattern));
-
-            if pattern.len() > 1 {
-                let keep = pattern.len() - 1;
-                if data.len() >= keep {
-                    tail = data[data.len() - keep..].to_vec();
-                } else {
-                    tail = data;
-                }
-            } else {
-                tail.clear();
-            }
-
-            scanned += read_size;
-            offset += read_size as u64;
-        }
-
-        results.sort_unstable();
-        results.dedup();
-        Ok(results)
+    pub fn scan_code_for_pattern(&self, pattern: &[Option<u8>]) -> Result<Vec<u64>>
+    where
+        R: Sync,
+    {
+        scan_code_for_pattern(self.reader, pattern, CodeScanOptions::default())
     }
 
     /// Resolve signature targets from code references
-    pub fn resolve_signature_targets(&self, signature: &CodeSignature) -> Result<Vec<u64>> {
+    pub fn resolve_signature_targets(&self, signature: &CodeSignature) -> Result<Vec<u64>>
+    where
+        R: Sync,
+    {
         let pattern = signature.pattern_bytes()?;
         let matches = self.scan_code_for_pattern(&pattern)?;
         let mut targets = Vec::new();
@@ -304,6 +268,129 @@ impl<'a, R: ReadMemory> PatternSearcher<'a, R> {
     }
 }
 
+/// Options for [`scan_code_for_pattern`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CodeScanOptions {
+    /// Worker threads; 0 uses one per available core, 1 scans serially
+    pub threads: usize,
+}
+
+impl CodeScanOptions {
+    /// Number of workers to use for `chunks` chunks of work
+    fn worker_count(&self, chunks: usize) -> usize {
+        let threads = match self.threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+        threads.clamp(1, chunks.max(1))
+    }
+}
+
+/// Scan the code section (`CODE_SCAN_LIMIT` bytes from the module base) for
+/// a byte pattern with wildcards
+///
+/// The range is split into `CODE_SCAN_CHUNK_SIZE` chunks scanned on
+/// `options.threads` workers. Each chunk also reads the first bytes of the
+/// next one so matches across chunk boundaries are found. Results are
+/// merged in address order; as with a serial scan, the scan ends at the
+/// first unreadable chunk, and fails only if the first chunk is unreadable.
+/// A panic in a worker is propagated to the caller.
+pub fn scan_code_for_pattern<R: ReadMemory + Sync>(
+    reader: &R,
+    pattern: &[Option<u8>],
+    options: CodeScanOptions,
+) -> Result<Vec<u64>> {
+    let base = reader.base_address();
+    let chunks = CODE_SCAN_LIMIT.div_ceil(CODE_SCAN_CHUNK_SIZE);
+    let workers = options.worker_count(chunks);
+
+    // Lowest chunk index that failed to read; later chunks are skipped
+    let first_failure = AtomicUsize::new(usize::MAX);
+    let next_chunk = AtomicUsize::new(0);
+    let scan_worker = || {
+        let mut scanned = Vec::new();
+        loop {
+            let index = next_chunk.fetch_add(1, Ordering::Relaxed);
+            if index >= chunks || index > first_failure.load(Ordering::Relaxed) {
+                break;
+            }
+            let result = scan_code_chunk(reader, base, index, pattern);
+            if result.is_err() {
+                first_failure.fetch_min(index, Ordering::Relaxed);
+            }
+            scanned.push((index, result));
+        }
+        scanned
+    };
+
+    let mut results: Vec<(usize, Result<Vec<u64>>)> = if workers == 1 {
+        scan_worker()
+    } else {
+        debug!("Code scan: {} chunks on {} threads", chunks, workers);
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers).map(|_| scope.spawn(scan_worker)).collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        })
+    };
+    results.sort_unstable_by_key(|(index, _)| *index);
+
+    let mut matches = Vec::new();
+    for (index, result) in results {
+        match result {
+            Ok(found) => matches.extend(found),
+            Err(e) if index == 0 => {
+                return Err(Error::offset_search_failed(format!(
+                    "Failed to read code section: {}",
+                    e
+                )));
+            }
+            Err(e) => {
+                debug!(
+                    "Code scan stopped at offset {:#x}: {}",
+                    index * CODE_SCAN_CHUNK_SIZE,
+                    e
+                );
+                break;
+            }
+        }
+    }
+    Ok(matches)
+}
+
+/// Matches starting inside chunk `index` of the code section
+fn scan_code_chunk<R: ReadMemory>(
+    reader: &R,
+    base: u64,
+    index: usize,
+    pattern: &[Option<u8>],
+) -> Result<Vec<u64>> {
+    let offset = index * CODE_SCAN_CHUNK_SIZE;
+    let owned = CODE_SCAN_CHUNK_SIZE.min(CODE_SCAN_LIMIT - offset);
+    let addr = base + offset as u64;
+
+    // Read into the next chunk for boundary matches; if that part is
+    // unreadable this is the last readable chunk, so read it alone
+    let overlap = pattern.len().saturating_sub(1);
+    let data = match reader.read_bytes(addr, owned + overlap) {
+        Ok(data) => data,
+        Err(_) if overlap > 0 => reader.read_bytes(addr, owned)?,
+        Err(e) => return Err(e),
+    };
+
+    Ok(find_masked_pattern(&data, pattern)
+        .into_iter()
+        .take_while(|&pos| pos < owned)
+        .map(|pos| addr + pos as u64)
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -326,6 +413,35 @@ mod tests {
         assert_eq!(matches.len(), 3);
     }
 
+    #[test]
+    fn test_load_buffer_is_capped() {
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x10000)
+            .write_bytes(0x8000, &[0xDE, 0xAD, 0xBE, 0xEF])
+            .build();
+        let mut searcher = PatternSearcher::new(&reader).with_max_buffer_size(0x2000);
+
+        assert!(searcher.load_buffer_around(0x9000, 0x1000).is_ok());
+        assert_eq!(searcher.buffer().len(), 0x2000);
+        assert!(searcher.load_buffer_around(0x9000, 0x1001).is_err());
+        assert!(searcher.load_buffer_around(0x9000, usize::MAX).is_err());
+
+        // Progressive searches stop widening at the cap
+        assert_eq!(
+            searcher
+                .fetch_and_search(0x9800, &[0xDE, 0xAD, 0xBE, 0xEF], 0, None)
+                .unwrap(),
+            0x9000
+        );
+        assert!(
+            searcher
+                .fetch_and_search(0xC000, &[0xDE, 0xAD, 0xBE, 0xEF], 0, None)
+                .is_err()
+        );
+        assert!(searcher.buffer().len() <= 0x2000);
+    }
+
     #[test]
     fn test_find_pattern() {
         let reader = MockMemoryBuilder::new()
@@ -357,4 +473,70 @@ mod tests {
         assert_eq!(matches[1], 0x1003);
         assert_eq!(matches[2], 0x1006);
     }
+
+    #[test]
+    fn test_scan_code_parallel_matches_serial() {
+        const MB: usize = 1024 * 1024;
+        // Two readable chunks plus part of a third, which ends the scan
+        let lea = [0x48, 0x8D, 0x0D, 0x78, 0x56, 0x34, 0x12];
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(2 * CODE_SCAN_CHUNK_SIZE + MB)
+            .write_bytes(MB, &lea)
+            .write_bytes(CODE_SCAN_CHUNK_SIZE - 2, &lea)
+            .write_bytes(2 * CODE_SCAN_CHUNK_SIZE + 16, &lea)
+            .build();
+        let pattern = [Some(0x48), Some(0x8D), Some(0x0D), None, None, None, None];
+
+        let serial =
+            scan_code_for_pattern(&reader, &pattern, CodeScanOptions { threads: 1 }).unwrap();
+        assert_eq!(
+            serial,
+            vec![0x1000 + MB as u64, 0x1000 + CODE_SCAN_CHUNK_SIZE as u64 - 2]
+        );
+        for threads in [0, 2, 4, 64] {
+            let parallel =
+                scan_code_for_pattern(&reader, &pattern, CodeScanOptions { threads }).unwrap();
+            assert_eq!(parallel, serial, "threads={threads}");
+        }
+    }
+
+    /// Reader that panics when reading past the first chunk
+    struct PanickingReader;
+
+    impl ReadMemory for PanickingReader {
+        fn read_bytes(&self, address: u64, size: usize) -> Result<Vec<u8>> {
+            assert!(
+                address < 0x1000 + CODE_SCAN_CHUNK_SIZE as u64,
+                "read failed"
+            );
+            Ok(vec![0; size])
+        }
+
+        fn base_address(&self) -> u64 {
+            0x1000
+        }
+    }
+
+    #[test]
+    fn test_scan_code_propagates_worker_panic() {
+        let result = std::panic::catch_unwind(|| {
+            scan_code_for_pattern(
+                &PanickingReader,
+                &[Some(0x48)],
+                CodeScanOptions { threads: 2 },
+            )
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_scan_code_fails_when_first_chunk_unreadable() {
+        let reader = MockMemoryBuilder::new()
+            .base(0x1000)
+            .with_size(0x1000)
+            .build();
+        let result = scan_code_for_pattern(&reader, &[Some(0x48)], CodeScanOptions::default());
+        assert!(result.is_err());
+    }
 }
@@ -53,7 +53,7 @@ impl<R: ReadMemory> OffsetSearcher<'_, R> {
 
     /// Search for JudgeData near SongList using relative offset
     pub(crate) fn search_judge_data_near_song_list(&self, song_list: u64) -> Result<u64> {
-        let expected = song_list.wrapping_sub(JUDGE_TO_SONG_LIST);
+        let expected = song_list.wrapping_sub(self.relative.judge_to_song_list);
 
         // First, try to find a candidate where both JudgeData and the inferred
         // CurrentSong position are valid. This cross-validation is more reliable.
@@ -62,7 +62,7 @@ impl<R: ReadMemory> OffsetSearcher<'_, R> {
                 return false;
             }
             // Cross-validate: check if CurrentSong at expected relative position is valid
-            let inferred_current_song = addr.wrapping_add(JUDGE_TO_CURRENT_SONG);
+            let inferred_current_song = addr.wrapping_add(this.relative.judge_to_current_song);
             this.reader
                 .validate_current_song_address(inferred_current_song)
         });
@@ -84,7 +84,7 @@ impl<R: ReadMemory> OffsetSearcher<'_, R> {
 
     /// Search for PlaySettings near JudgeData using relative offset
     pub(crate) fn search_play_settings_near_judge_data(&self, judge_data: u64) -> Result<u64> {
-        let expected = judge_data.wrapping_sub(JUDGE_TO_PLAY_SETTINGS);
+        let expected = judge_data.wrapping_sub(self.relative.judge_to_play_settings);
 
         // First, try to find a candidate where both PlaySettings and the inferred
         // PlayData position are valid. This cross-validation is more reliable.
@@ -94,7 +94,8 @@ impl<R: ReadMemory> OffsetSearcher<'_, R> {
                     return false;
                 }
                 // Cross-validate: check if PlayData at expected relative position is valid
-                let inferred_play_data = addr.wrapping_add(PLAY_SETTINGS_TO_PLAY_DATA);
+                let inferred_play_data =
+                    addr.wrapping_add(this.relative.play_settings_to_play_data);
                 this.reader.validate_play_data_address(inferred_play_data)
             });
 
@@ -115,7 +116,7 @@ impl<R: ReadMemory> OffsetSearcher<'_, R> {
 
     /// Search for PlayData near PlaySettings using relative offset
     pub(crate) fn search_play_data_near_play_settings(&self, play_settings: u64) -> Result<u64> {
-        let expected = play_settings.wrapping_add(PLAY_SETTINGS_TO_PLAY_DATA);
+        let expected = play_settings.wrapping_add(self.relative.play_settings_to_play_data);
         self.search_near_expected(expected, PLAY_DATA_SEARCH_RANGE, |this, addr| {
             this.reader.validate_play_data_address(addr)
         })
@@ -128,7 +129,7 @@ impl<R: ReadMemory> OffsetSearcher<'_, R> {
 
     /// Search for CurrentSong near JudgeData using relative offset
     pub(crate) fn search_current_song_near_judge_data(&self, judge_data: u64) -> Result<u64> {
-        let expected = judge_data.wrapping_add(JUDGE_TO_CURRENT_SONG);
+        let expected = judge_data.wrapping_add(self.relative.judge_to_current_song);
         self.search_near_expected(expected, CURRENT_SONG_SEARCH_RANGE, |this, addr| {
             this.reader.validate_current_song_address(addr)
         })
@@ -334,4 +335,33 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), expected_judge);
     }
+
+    #[test]
+    fn test_search_uses_layout_relative_offsets() {
+        use crate::offset::GameLayout;
+        use crate::process::layout::play;
+
+        // Play data candidates at both the current and the 2025122400 distance
+        let play_settings = 0x1000u64;
+        let mut builder = MockMemoryBuilder::new()
+            .base(play_settings)
+            .with_size(0x400);
+        for delta in [0x2A0, 0x2C0] {
+            builder = builder
+                .write_i32(delta + play::SONG_ID as usize, 1000)
+                .write_i32(delta + play::DIFFICULTY as usize, 3)
+                .write_i32(delta + play::LAMP as usize, 2);
+        }
+        let reader = builder.build();
+
+        let found = |layout: &GameLayout| {
+            OffsetSearcher::builder(&reader)
+                .with_layout(layout)
+                .build()
+                .search_play_data_near_play_settings(play_settings)
+                .unwrap()
+        };
+        assert_eq!(found(&GameLayout::current()), play_settings + 0x2A0);
+        assert_eq!(found(&GameLayout::v2025122400()), play_settings + 0x2C0);
+    }
 }
@@ -0,0 +1,142 @@
+//! Scripted prompter for running the interactive search without a user
+//!
+//! [`ScriptedPrompter`] answers number prompts from a pre-recorded list and
+//! records everything the search displays, so the interactive flow can run in
+//! tests against mock memory.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+
+use super::types::{JudgeInput, SearchPrompter};
+
+/// Hook run on each ENTER prompt
+type ContinueHook<'a> = Box<dyn FnMut(usize) + 'a>;
+
+/// [`SearchPrompter`] that feeds pre-recorded answers
+///
+/// Number prompts take the next recorded number and panic when the script
+/// runs out. An optional hook runs on each ENTER prompt with its index
+/// (0 = first), standing in for what the user does in the game before
+/// pressing ENTER.
+#[derive(Default)]
+pub struct ScriptedPrompter<'a> {
+    numbers: RefCell<VecDeque<u32>>,
+    on_continue: RefCell<Option<ContinueHook<'a>>>,
+    continues: Cell<usize>,
+    messages: RefCell<Vec<String>>,
+    warnings: RefCell<Vec<String>>,
+}
+
+impl<'a> ScriptedPrompter<'a> {
+    /// Answer number prompts with `numbers`, in order
+    pub fn new(numbers: impl IntoIterator<Item = u32>) -> Self {
+        Self {
+            numbers: RefCell::new(numbers.into_iter().collect()),
+            ..Self::default()
+        }
+    }
+
+    /// Answer the judge prompts of [`interactive_search`] with `judge`
+    ///
+    /// [`interactive_search`]: super::OffsetSearcher::interactive_search
+    pub fn with_judge(judge: &JudgeInput) -> Self {
+        Self::new([
+            judge.pgreat,
+            judge.great,
+            judge.good,
+            judge.bad,
+            judge.poor,
+            judge.combo_break,
+            judge.fast,
+            judge.slow,
+        ])
+    }
+
+    /// Run `hook` on each ENTER prompt, with the prompt's index
+    pub fn on_continue(self, hook: impl FnMut(usize) + 'a) -> Self {
+        *self.on_continue.borrow_mut() = Some(Box::new(hook));
+        self
+    }
+
+    /// ENTER prompts answered so far
+    pub fn continues(&self) -> usize {
+        self.continues.get()
+    }
+
+    /// Recorded numbers not asked for yet
+    pub fn remaining_numbers(&self) -> usize {
+        self.numbers.borrow().len()
+    }
+
+    /// Messages displayed so far
+    pub fn messages(&self) -> Vec<String> {
+        self.messages.borrow().clone()
+    }
+
+    /// Warnings displayed so far
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.borrow().clone()
+    }
+}
+
+impl std::fmt::Debug for ScriptedPrompter<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScriptedPrompter")
+            .field("numbers", &self.numbers)
+            .field("continues", &self.continues)
+            .field("messages", &self.messages)
+            .field("warnings", &self.warnings)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SearchPrompter for ScriptedPrompter<'_> {
+    fn prompt_continue(&self, _message: &str) {
+        let index = self.continues.get();
+        if let Some(hook) = self.on_continue.borrow_mut().as_mut() {
+            hook(index);
+        }
+        self.continues.set(index + 1);
+    }
+
+    fn prompt_number(&self, prompt: &str) -> u32 {
+        self.numbers
+            .borrow_mut()
+            .pop_front()
+            .unwrap_or_else(|| panic!("no scripted answer left for {:?}", prompt))
+    }
+
+    fn display_message(&self, message: &str) {
+        self.messages.borrow_mut().push(message.to_string());
+    }
+
+    fn display_warning(&self, message: &str) {
+        self.warnings.borrow_mut().push(message.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripted_answers_and_hook() {
+        let seen = RefCell::new(Vec::new());
+        let prompter = ScriptedPrompter::new([3, 5]).on_continue(|index| {
+            seen.borrow_mut().push(index);
+        });
+
+        prompter.prompt_continue("first");
+        assert_eq!(prompter.prompt_number("a"), 3);
+        prompter.prompt_continue("second");
+        prompter.display_message("hello");
+        prompter.display_warning("careful");
+
+        assert_eq!(prompter.remaining_numbers(), 1);
+        assert_eq!(prompter.continues(), 2);
+        assert_eq!(prompter.messages(), vec!["hello"]);
+        assert_eq!(prompter.warnings(), vec!["careful"]);
+        drop(prompter);
+        assert_eq!(seen.into_inner(), vec![0, 1]);
+    }
+}
@@ -9,11 +9,11 @@ use tracing::debug;
 use crate::error::{Error, Result};
 use crate::offset::searcher::validation::OffsetValidation;
 use crate::offset::{CodeSignature, OffsetSignatureSet};
+use crate::process::pattern::find_masked_pattern;
 use crate::process::{ByteBuffer, ReadMemory};
 
-use super::super::constants::{
-    CODE_SCAN_CHUNK_SIZE, CODE_SCAN_LIMIT, MIN_EXPECTED_SONGS, MIN_VALID_DATA_ADDRESS,
-};
+use super::super::constants::{MIN_EXPECTED_SONGS, MIN_VALID_DATA_ADDRESS};
+use super::super::pattern::{self, CodeScanOptions};
 
 /// Signature search functionality for OffsetSearcher
 pub trait SignatureSearch<R: ReadMemory> {
@@ -52,7 +52,7 @@ pub trait SignatureSearch<R: ReadMemory> {
 }
 
 /// Resolve signature targets from code references
-pub fn resolve_signature_targets<R: ReadMemory>(
+pub fn resolve_signature_targets<R: ReadMemory + Sync>(
     reader: &R,
     signature: &CodeSignature,
 ) -> Result<Vec<u64>> {
@@ -104,63 +104,11 @@ pub fn resolve_signature_targets<R: ReadMemory>(
 }
 
 /// Scan code section for a pattern with wildcards
-pub fn scan_code_for_pattern<R: ReadMemory>(
+pub fn scan_code_for_pattern<R: ReadMemory + Sync>(
     reader: &R,
     pattern: &[Option<u8>],
 ) -> Result<Vec<u64>> {
-    let base = reader.base_address();
-    let mut results: Vec<u64> = Vec::new();
-    let mut offset: u64 = 0;
-    let mut scanned: usize = 0;
-    let mut tail: Vec<u8> = Vec::new();
-
-    while scanned < CODE_SCAN_LIMIT {
-        let remaining = CODE_SCAN_LIMIT - scanned;
-        let read_size = remaining.min(CODE_SCAN_CHUNK_SIZE);
-        let addr = base + offset;
-
-        let chunk = match reader.read_bytes(addr, read_size) {
-            Ok(bytes) => bytes,
-            Err(e) => {
-                if scanned == 0 {
-                    return Err(Error::offset_search_failed(format!(
-                        "Failed to read code section: {}",
-                        e
-                    )));
-                }
-                debug!(
-                    "Code scan stopped at offset {:#x} (scanned {:#x} bytes): {}",
-                    offset, scanned, e
-                );
-                break;
-            }
-        };
-
-        let mut data = Vec::with_capacity(tail.len() + chunk.len());
-        data.extend_from_slice(&tail);
-        data.extend_from_slice(&chunk);
-
-        let data_base = addr.saturating_sub(tail.len() as u64);
-        results.extend(find_matches_with_wildcards(&data, data_base, pattern));
-
-        if pattern.len() > 1 {
-            let keep = pattern.len() - 1;
-            if data.len() >= keep {
-                tail = data[data.len() - keep..].to_vec();
-            } else {
-                tail = data;
-            }
-        } else {
-            tail.clear();
-        }
-
-        scanned += read_size;
-        offset += read_size as u64;
-    }
-
-    results.sort_unstable();
-    results.dedup();
-    Ok(results)
+    pattern::scan_code_for_pattern(reader, pattern, CodeScanOptions::default())
 }
 
 /// Find all matches of a pattern with wildcards in a buffer
@@ -169,32 +117,17 @@ pub fn find_matches_with_wildcards(
     base_addr: u64,
     pattern: &[Option<u8>],
 ) -> Vec<u64> {
-    if pattern.is_empty() || buffer.len() < pattern.len() {
-        return Vec::new();
-    }
-
-    let mut results = Vec::new();
-    let last = buffer.len() - pattern.len();
-
-    'outer: for i in 0..=last {
-        for (j, byte) in pattern.iter().enumerate() {
-            if let Some(value) = byte
-                && buffer[i + j] != *value
-            {
-                continue 'outer;
-            }
-        }
-        results.push(base_addr + i as u64);
-    }
-
-    results
+    find_masked_pattern(buffer, pattern)
+        .into_iter()
+        .map(|pos| base_addr + pos as u64)
+        .collect()
 }
 
 /// Search for song list offset using signature
 ///
 /// NOTE: Currently unused because signature search doesn't work on Version 2.
 #[allow(dead_code)]
-pub fn search_song_list_by_signature<R: ReadMemory>(
+pub fn search_song_list_by_signature<R: ReadMemory + Sync>(
     reader: &R,
     signatures: &OffsetSignatureSet,
 ) -> Result<u64> {
@@ -250,7 +183,7 @@ pub fn search_offset_by_signature<R, F>(
     validate: F,
 ) -> Result<u64>
 where
-    R: ReadMemory,
+    R: ReadMemory + Sync,
     F: Fn(u64) -> bool,
 {
     let entry = signatures.entry(name).ok_or_else(|| {
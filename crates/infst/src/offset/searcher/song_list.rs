@@ -1,5 +1,6 @@
 //! SongList offset search functionality
 
+use memchr::memmem;
 use tracing::{debug, info, warn};
 
 use crate::chart::SongInfo;
@@ -29,11 +30,9 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
         let mut all_candidates: Vec<(u64, usize)> = Vec::new();
 
         while search_size <= MAX_SEARCH_SIZE {
-            if self.load_buffer_around(base_hint, search_size).is_err() {
+            let Ok(matches) = self.find_all_around(base_hint, search_size, pattern) else {
                 break;
-            }
-
-            let matches = self.find_all_matches(pattern);
+            };
             debug!(
                 "  SongList pattern search: found {} matches at search_size={}MB",
                 matches.len(),
@@ -127,7 +126,7 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
         // Log all candidates for debugging
         if !all_candidates.is_empty() {
             // Sort by song count descending
-            all_candidates.sort_by(|a, b| b.1.cmp(&a.1));
+            all_candidates.sort_by_key(|c| std::cmp::Reverse(c.1));
             warn!(
                 "  SongList pattern search: no valid candidate found. Best candidates: {:?}",
                 all_candidates.iter().take(5).collect::<Vec<_>>()
@@ -153,18 +152,24 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
 
         let search_size = 32 * 1024 * 1024; // 32MB
 
-        if self.load_buffer_around(base_hint, search_size).is_err() {
-            return Err(Error::offset_search_failed(
-                "Failed to load buffer for song_id search".to_string(),
-            ));
-        }
-
         // Find song_id=1001 and song_id=1002 to locate new structure
         let pattern_1001 = merge_byte_representations(&[1001i32]);
         let pattern_1002 = merge_byte_representations(&[1002i32]);
+        // Song_id=1001 followed by folder=43 (alternative layout below)
+        let alt_pattern = merge_byte_representations(&[1001i32, 43i32]);
 
-        let matches_1001 = self.find_all_matches(&pattern_1001);
-        let matches_1002 = self.find_all_matches(&pattern_1002);
+        let Ok(mut matches) = self.find_all_around_many(
+            base_hint,
+            search_size,
+            &[&pattern_1001, &pattern_1002, &alt_pattern],
+        ) else {
+            return Err(Error::offset_search_failed(
+                "Failed to load buffer for song_id search".to_string(),
+            ));
+        };
+        let alt_matches = matches.pop().unwrap_or_default();
+        let matches_1002 = matches.pop().unwrap_or_default();
+        let matches_1001 = matches.pop().unwrap_or_default();
 
         debug!(
             "  song_id search: found {} matches for 1001, {} matches for 1002",
@@ -235,8 +240,6 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
         warn!("Trying new layout search (song_id at start)...");
 
         // Search for song_id=1001 followed by folder=43
-        let alt_pattern = merge_byte_representations(&[1001i32, 43i32]);
-        let alt_matches = self.find_all_matches(&alt_pattern);
 
         debug!("  Alt pattern search: found {} matches", alt_matches.len());
 
@@ -431,15 +434,13 @@ impl<'a, R: ReadMemory> OffsetSearcher<'a, R> {
         if let Ok(buffer) = self.reader.read_bytes(search_base, 0x200000) {
             let pattern = b"5.1.1.";
             let mut found_count = 0;
-            for (i, window) in buffer.windows(pattern.len()).enumerate() {
-                if window == pattern {
-                    let addr = search_base + i as u64;
-                    info!("  Found '5.1.1.' at 0x{:X}", addr);
-                    found_count += 1;
-                    if found_count >= 5 {
-                        info!("  ... (truncated, found more than 5 matches)");
-                        break;
-                    }
+            for pos in memmem::find_iter(&buffer, pattern) {
+                let addr = search_base + pos as u64;
+                info!("  Found '5.1.1.' at 0x{:X}", addr);
+                found_count += 1;
+                if found_count >= 5 {
+                    info!("  ... (truncated, found more than 5 matches)");
+                    break;
                 }
             }
             if found_count == 0 {
@@ -0,0 +1,13 @@
+//! Currency (bits and tickets) validation.
+
+use crate::play::Currency;
+use crate::process::ReadMemory;
+
+/// Validate currency address.
+pub fn validate_currency_address<R: ReadMemory + ?Sized>(reader: &R, addr: u64) -> bool {
+    // Both counts must be within their plausible ranges
+    match reader.read_bytes(addr, Currency::MEMORY_SIZE) {
+        Ok(bytes) => Currency::from_bytes(&bytes).is_some(),
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,13 @@
+//! Lane cover validation.
+
+use crate::play::{LaneCover, PlayType};
+use crate::process::ReadMemory;
+
+/// Validate lane cover address.
+pub fn validate_lane_cover_address<R: ReadMemory + ?Sized>(reader: &R, addr: u64) -> bool {
+    // The P1 side must hold in-lane covers and a plausible green number
+    match reader.read_bytes(addr, LaneCover::MEMORY_SIZE) {
+        Ok(bytes) => LaneCover::from_bytes(&bytes, PlayType::P1).is_some(),
+        Err(_) => false,
+    }
+}
@@ -3,24 +3,30 @@
 //! This module provides validation logic for verifying that candidate addresses
 //! actually point to valid game data structures.
 
+mod currency;
 mod current_song;
 mod data_map;
 mod judge;
+mod lane_cover;
 mod play;
+mod profile;
 mod song_list;
 mod unlock;
 
 use tracing::debug;
 
-use crate::offset::OffsetsCollection;
+use crate::offset::{OffsetsCollection, RelativeOffsets};
 use crate::process::ReadMemory;
 
 use super::constants::*;
 
+pub use currency::validate_currency_address;
 pub use current_song::validate_current_song_address;
 pub use data_map::{validate_data_map_address, validate_data_map_node};
 pub use judge::validate_judge_data_candidate;
+pub use lane_cover::validate_lane_cover_address;
 pub use play::{validate_play_data_address, validate_play_settings_at};
+pub use profile::validate_profile_address;
 pub use song_list::{count_songs_at_address, validate_new_version_text_table};
 pub use unlock::validate_unlock_data_address;
 
@@ -74,6 +80,30 @@ pub trait OffsetValidation: ReadMemory {
         validate_unlock_data_address(self, addr)
     }
 
+    /// Validate profile address.
+    fn validate_profile_address(&self, addr: u64) -> bool
+    where
+        Self: Sized,
+    {
+        validate_profile_address(self, addr)
+    }
+
+    /// Validate currency address.
+    fn validate_currency_address(&self, addr: u64) -> bool
+    where
+        Self: Sized,
+    {
+        validate_currency_address(self, addr)
+    }
+
+    /// Validate lane cover address.
+    fn validate_lane_cover_address(&self, addr: u64) -> bool
+    where
+        Self: Sized,
+    {
+        validate_lane_cover_address(self, addr)
+    }
+
     /// Validate a data map node.
     fn validate_data_map_node(&self, addr: u64) -> bool
     where
@@ -96,8 +126,13 @@ impl<T: ReadMemory> OffsetValidation for T {}
 
 /// Validate all offsets in a collection.
 ///
-/// Performs structural validation of offset relationships and memory access checks.
-pub fn validate_signature_offsets<R: ReadMemory>(reader: &R, offsets: &OffsetsCollection) -> bool {
+/// Performs structural validation of offset relationships (against the
+/// distances of the game layout in use) and memory access checks.
+pub fn validate_signature_offsets<R: ReadMemory>(
+    reader: &R,
+    offsets: &OffsetsCollection,
+    relative: &RelativeOffsets,
+) -> bool {
     // Check required offsets are non-zero
     if offsets.song_list == 0 {
         debug!("Validation failed: song_list is zero");
@@ -210,12 +245,12 @@ pub fn validate_signature_offsets<R: ReadMemory>(reader: &R, offsets: &OffsetsCo
     let judge_to_play = offsets.judge_data.wrapping_sub(offsets.play_settings);
     if !within_range(
         judge_to_play,
-        JUDGE_TO_PLAY_SETTINGS,
+        relative.judge_to_play_settings,
         PLAY_SETTINGS_SEARCH_RANGE as u64,
     ) {
         debug!(
             "Relative distance validation failed: judge_data - play_settings = 0x{:X} (expected ~0x{:X})",
-            judge_to_play, JUDGE_TO_PLAY_SETTINGS
+            judge_to_play, relative.judge_to_play_settings
         );
         return false;
     }
@@ -223,12 +258,12 @@ pub fn validate_signature_offsets<R: ReadMemory>(reader: &R, offsets: &OffsetsCo
     let song_to_judge = offsets.song_list.wrapping_sub(offsets.judge_data);
     if !within_range(
         song_to_judge,
-        JUDGE_TO_SONG_LIST,
+        relative.judge_to_song_list,
         JUDGE_DATA_SEARCH_RANGE as u64,
     ) {
         debug!(
             "Relative distance validation failed: song_list - judge_data = 0x{:X} (expected ~0x{:X})",
-            song_to_judge, JUDGE_TO_SONG_LIST
+            song_to_judge, relative.judge_to_song_list
         );
         return false;
     }
@@ -236,12 +271,12 @@ pub fn validate_signature_offsets<R: ReadMemory>(reader: &R, offsets: &OffsetsCo
     let play_data_delta = offsets.play_data.wrapping_sub(offsets.play_settings);
     if !within_range(
         play_data_delta,
-        PLAY_SETTINGS_TO_PLAY_DATA,
+        relative.play_settings_to_play_data,
         PLAY_DATA_SEARCH_RANGE as u64,
     ) {
         debug!(
             "Relative distance validation failed: play_data - play_settings = 0x{:X} (expected ~0x{:X})",
-            play_data_delta, PLAY_SETTINGS_TO_PLAY_DATA
+            play_data_delta, relative.play_settings_to_play_data
         );
         return false;
     }
@@ -249,12 +284,12 @@ pub fn validate_signature_offsets<R: ReadMemory>(reader: &R, offsets: &OffsetsCo
     let current_song_delta = offsets.current_song.wrapping_sub(offsets.judge_data);
     if !within_range(
         current_song_delta,
-        JUDGE_TO_CURRENT_SONG,
+        relative.judge_to_current_song,
         CURRENT_SONG_SEARCH_RANGE as u64,
     ) {
         debug!(
             "Relative distance validation failed: current_song - judge_data = 0x{:X} (expected ~0x{:X})",
-            current_song_delta, JUDGE_TO_CURRENT_SONG
+            current_song_delta, relative.judge_to_current_song
         );
         return false;
     }
@@ -263,6 +298,34 @@ pub fn validate_signature_offsets<R: ReadMemory>(reader: &R, offsets: &OffsetsCo
     true
 }
 
+/// Reset optional offsets that don't hold their block to 0
+///
+/// Offsets files and the cache can outlive a game update; a stale optional
+/// offset would read garbage instead of failing. Returns the names of the
+/// cleared offsets.
+pub fn clear_invalid_optional_offsets<R: ReadMemory>(
+    reader: &R,
+    offsets: &mut OffsetsCollection,
+) -> Vec<&'static str> {
+    let mut cleared = Vec::new();
+    if offsets.profile != 0 && !validate_profile_address(reader, offsets.profile) {
+        debug!("Profile validation failed at 0x{:X}", offsets.profile);
+        offsets.profile = 0;
+        cleared.push("profile");
+    }
+    if offsets.currency != 0 && !validate_currency_address(reader, offsets.currency) {
+        debug!("Currency validation failed at 0x{:X}", offsets.currency);
+        offsets.currency = 0;
+        cleared.push("currency");
+    }
+    if offsets.lane_cover != 0 && !validate_lane_cover_address(reader, offsets.lane_cover) {
+        debug!("Lane cover validation failed at 0x{:X}", offsets.lane_cover);
+        offsets.lane_cover = 0;
+        cleared.push("laneCover");
+    }
+    cleared
+}
+
 /// Validate basic memory access for file-loaded offsets.
 ///
 /// Skips relative distance checks which may differ between game versions.
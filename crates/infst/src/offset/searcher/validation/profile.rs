@@ -0,0 +1,13 @@
+//! Player profile validation.
+
+use crate::play::ProfileInfo;
+use crate::process::ReadMemory;
+
+/// Validate profile address.
+pub fn validate_profile_address<R: ReadMemory + ?Sized>(reader: &R, addr: u64) -> bool {
+    // DJ name and dan ranks must parse as a profile block
+    match reader.read_bytes(addr, ProfileInfo::MEMORY_SIZE) {
+        Ok(bytes) => ProfileInfo::from_bytes(&bytes).is_some(),
+        Err(_) => false,
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
+use tracing::{debug, warn};
 
 use crate::error::{Error, Result};
 
@@ -22,6 +23,15 @@ impl CodeSignature {
     }
 }
 
+/// Signature entry name for the player profile block (not part of the builtin set)
+pub const PROFILE_SIGNATURE: &str = "profile";
+
+/// Signature entry name for the currency block (not part of the builtin set)
+pub const CURRENCY_SIGNATURE: &str = "currency";
+
+/// Signature entry name for the lane cover block (not part of the builtin set)
+pub const LANE_COVER_SIGNATURE: &str = "laneCover";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OffsetSignatureEntry {
     pub name: String,
@@ -40,6 +50,15 @@ impl OffsetSignatureSet {
             .iter()
             .find(|entry| entry.name.eq_ignore_ascii_case(name))
     }
+
+    /// Add the entries of `other`, replacing entries of the same name
+    pub fn merge(&mut self, other: OffsetSignatureSet) {
+        for entry in other.entries {
+            self.entries
+                .retain(|existing| !existing.name.eq_ignore_ascii_case(&entry.name));
+            self.entries.push(entry);
+        }
+    }
 }
 
 pub fn load_signatures<P: AsRef<Path>>(path: P) -> Result<OffsetSignatureSet> {
@@ -48,6 +67,28 @@ pub fn load_signatures<P: AsRef<Path>>(path: P) -> Result<OffsetSignatureSet> {
     Ok(data)
 }
 
+/// Merge the signature file at `path` into `signatures`, if the file exists
+///
+/// Used for entries the builtin set doesn't have (e.g. `profile`); a file
+/// that fails to load is skipped with a warning.
+pub fn merge_signature_file<P: AsRef<Path>>(signatures: &mut OffsetSignatureSet, path: P) {
+    let path = path.as_ref();
+    if !path.is_file() {
+        return;
+    }
+    match load_signatures(path) {
+        Ok(extra) => {
+            debug!(
+                "Loaded {} signature entries from {:?}",
+                extra.entries.len(),
+                path
+            );
+            signatures.merge(extra);
+        }
+        Err(e) => warn!("Ignoring signature file {:?}: {}", path, e),
+    }
+}
+
 pub fn save_signatures<P: AsRef<Path>>(path: P, signatures: &OffsetSignatureSet) -> Result<()> {
     let content = serde_json::to_string_pretty(signatures)?;
     fs::write(path, content)?;
@@ -62,16 +103,13 @@ pub fn parse_pattern(pattern: &str) -> Result<Vec<Option<u8>>> {
             continue;
         }
 
-        let value = u8::from_str_radix(token, 16).map_err(|e| {
-            Error::InvalidOffset(format!("Invalid signature token '{}': {}", token, e))
-        })?;
+        let value = u8::from_str_radix(token, 16)
+            .map_err(|e| Error::parse("signature", format!("token '{}': {}", token, e)))?;
         bytes.push(Some(value));
     }
 
     if bytes.is_empty() {
-        return Err(Error::InvalidOffset(
-            "Signature pattern is empty".to_string(),
-        ));
+        return Err(Error::parse("signature", "pattern is empty"));
     }
 
     Ok(bytes)
@@ -170,6 +208,36 @@ mod tests {
         assert_eq!(bytes[3], None);
     }
 
+    #[test]
+    fn test_merge_signature_file() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("signatures.json");
+        let mut signatures = builtin_signatures();
+        let count = signatures.entries.len();
+
+        merge_signature_file(&mut signatures, &path);
+        assert_eq!(signatures.entries.len(), count);
+
+        let extra = OffsetSignatureSet {
+            version: "*".to_string(),
+            entries: vec![
+                OffsetSignatureEntry {
+                    name: PROFILE_SIGNATURE.to_string(),
+                    signatures: vec![],
+                },
+                OffsetSignatureEntry {
+                    name: "JUDGEDATA".to_string(),
+                    signatures: vec![],
+                },
+            ],
+        };
+        save_signatures(&path, &extra).unwrap();
+        merge_signature_file(&mut signatures, &path);
+        assert_eq!(signatures.entries.len(), count + 1);
+        assert!(signatures.entry(PROFILE_SIGNATURE).is_some());
+        assert!(signatures.entry("judgeData").unwrap().signatures.is_empty());
+    }
+
     #[test]
     fn test_format_pattern_roundtrip() {
         let pattern = vec![Some(0x48), Some(0x8D), Some(0x0D), None, Some(0xFF)];
@@ -0,0 +1,305 @@
+//! Where infst keeps its files
+//!
+//! All output and cache files live under one root directory, resolved once at
+//! startup and handed to the tracker, session manager, stream output and CLI
+//! commands:
+//!
+//! - the per-user data directory (`%APPDATA%\infst` on Windows) by default
+//! - the current directory in portable mode (`--portable`), the layout of
+//!   earlier versions
+//! - an explicit directory (`--data-dir`)
+//!
+//! With a profile (`--profile`), the tracker file, sessions and backups of
+//! that player live in `profiles/<name>` under the root, so two players on
+//! one PC don't share bests; settings and caches stay shared.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use tracing::info;
+
+use crate::error::{Error, Result};
+
+/// Directory name under the per-user data directory
+pub const APP_DIR_NAME: &str = "infst";
+
+/// Tracker TSV file name
+pub const TRACKER_FILE: &str = "tracker.tsv";
+
+/// Session directory name
+pub const SESSIONS_DIR: &str = "sessions";
+
+/// Stream overlay directory name
+pub const STREAM_DIR: &str = "stream";
+
+/// Scheduled backup directory name (next to the tracker file)
+pub const BACKUP_DIR: &str = "backups";
+
+/// Directory name of per-profile data under the root
+pub const PROFILES_DIR: &str = "profiles";
+
+/// Offsets file written by `find-offsets`
+pub const OFFSETS_FILE: &str = "offsets.txt";
+
+/// Extra code signatures merged into the builtin set (see
+/// [`crate::offset::merge_signature_file`])
+pub const SIGNATURES_FILE: &str = "signatures.json";
+
+/// Song tags file name
+pub const TAGS_FILE: &str = "tags.json";
+
+/// Ignore list file name (see [`crate::storage::IgnoreList`])
+pub const IGNORE_FILE: &str = "ignore.json";
+
+/// Chart note density file name (see [`crate::chart::density`])
+pub const CHART_DENSITY_FILE: &str = "chart_density.json";
+
+/// Title alias (romanization) file name
+pub const TITLE_ALIASES_FILE: &str = "title_aliases.json";
+
+/// Crash report directory name
+pub const CRASH_DIR: &str = "crashes";
+
+/// Offset cache file name
+pub const OFFSET_CACHE_FILE: &str = ".infst-cache.json";
+
+/// Offline lamp submission queue file name (in the session directory)
+pub const SUBMISSION_QUEUE_FILE: &str = "submission-queue.jsonl";
+
+/// Resolved locations of infst's files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths {
+    root: PathBuf,
+    profile: Option<String>,
+}
+
+impl Paths {
+    /// Files under `root`
+    pub fn in_dir<P: Into<PathBuf>>(root: P) -> Self {
+        Self {
+            root: root.into(),
+            profile: None,
+        }
+    }
+
+    /// Files relative to the current directory (portable mode)
+    pub fn portable() -> Self {
+        Self::in_dir("")
+    }
+
+    /// Files in the per-user data directory (`%APPDATA%\infst`)
+    pub fn user_data() -> Option<Self> {
+        dirs::data_dir().map(|dir| Self::in_dir(dir.join(APP_DIR_NAME)))
+    }
+
+    /// Resolve the paths for this run
+    ///
+    /// `data_dir` wins over `portable`. Without either, the current directory
+    /// is still used when it already holds a tracker or sessions from an
+    /// earlier version, so existing setups keep their files.
+    pub fn resolve(portable: bool, data_dir: Option<PathBuf>) -> Self {
+        Self::choose(portable, data_dir, Self::portable(), Self::user_data())
+    }
+
+    fn choose(
+        portable: bool,
+        data_dir: Option<PathBuf>,
+        current_dir: Self,
+        user_data: Option<Self>,
+    ) -> Self {
+        if let Some(dir) = data_dir {
+            return Self::in_dir(dir);
+        }
+        if portable {
+            return current_dir;
+        }
+        if current_dir.has_data() {
+            info!("Found existing tracker files in the current directory, using portable mode");
+            return current_dir;
+        }
+        user_data.unwrap_or(current_dir)
+    }
+
+    /// Keep the tracker file, sessions and backups of `profile` apart
+    pub fn with_profile(mut self, profile: &str) -> Result<Self> {
+        let profile = profile.trim();
+        if profile.is_empty()
+            || profile == "."
+            || profile == ".."
+            || profile.contains(['/', '\\', ':'])
+        {
+            return Err(Error::invalid_config(
+                "profile",
+                format!("{:?} is not a valid profile name", profile),
+            ));
+        }
+        self.profile = Some(profile.to_string());
+        Ok(self)
+    }
+
+    /// Selected profile, `None` for the default one
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Directory of the selected profile's tracker, sessions and backups
+    fn profile_root(&self) -> PathBuf {
+        match &self.profile {
+            Some(profile) => self.root.join(PROFILES_DIR).join(profile),
+            None => self.root.clone(),
+        }
+    }
+
+    /// Whether a tracker file or session directory already exists here
+    pub fn has_data(&self) -> bool {
+        self.tracker().is_file() || self.sessions().is_dir()
+    }
+
+    /// Whether files are kept relative to the current directory
+    pub fn is_portable(&self) -> bool {
+        self.root.as_os_str().is_empty()
+    }
+
+    /// Root directory (empty in portable mode)
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Create the root directory (and the profile's directory) if needed
+    pub fn create_root(&self) -> io::Result<()> {
+        if self.is_portable() && self.profile.is_none() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(self.profile_root())
+    }
+
+    pub fn tracker(&self) -> PathBuf {
+        self.profile_root().join(TRACKER_FILE)
+    }
+
+    pub fn sessions(&self) -> PathBuf {
+        self.profile_root().join(SESSIONS_DIR)
+    }
+
+    pub fn backups(&self) -> PathBuf {
+        self.profile_root().join(BACKUP_DIR)
+    }
+
+    pub fn stream(&self) -> PathBuf {
+        self.root.join(STREAM_DIR)
+    }
+
+    pub fn offsets(&self) -> PathBuf {
+        self.root.join(OFFSETS_FILE)
+    }
+
+    pub fn signatures(&self) -> PathBuf {
+        self.root.join(SIGNATURES_FILE)
+    }
+
+    pub fn tags(&self) -> PathBuf {
+        self.root.join(TAGS_FILE)
+    }
+
+    pub fn ignore_list(&self) -> PathBuf {
+        self.root.join(IGNORE_FILE)
+    }
+
+    pub fn chart_density(&self) -> PathBuf {
+        self.root.join(CHART_DENSITY_FILE)
+    }
+
+    pub fn title_aliases(&self) -> PathBuf {
+        self.root.join(TITLE_ALIASES_FILE)
+    }
+
+    pub fn offset_cache(&self) -> PathBuf {
+        self.root.join(OFFSET_CACHE_FILE)
+    }
+
+    pub fn crashes(&self) -> PathBuf {
+        self.root.join(CRASH_DIR)
+    }
+}
+
+impl Default for Paths {
+    fn default() -> Self {
+        Self::portable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portable_paths_match_legacy_layout() {
+        let paths = Paths::portable();
+        assert!(paths.is_portable());
+        assert_eq!(paths.tracker(), PathBuf::from("tracker.tsv"));
+        assert_eq!(paths.sessions(), PathBuf::from("sessions"));
+        assert_eq!(paths.offset_cache(), PathBuf::from(".infst-cache.json"));
+    }
+
+    #[test]
+    fn test_choose() {
+        let temp = tempfile::tempdir().unwrap();
+        let current = Paths::in_dir(temp.path().join("cwd"));
+        let user = Paths::in_dir(temp.path().join("appdata"));
+        let explicit = temp.path().join("explicit");
+
+        let choose = |portable, data_dir: Option<&Path>| {
+            Paths::choose(
+                portable,
+                data_dir.map(Path::to_path_buf),
+                current.clone(),
+                Some(user.clone()),
+            )
+        };
+        assert_eq!(choose(false, None), user);
+        assert_eq!(choose(true, None), current);
+        assert_eq!(choose(true, Some(&explicit)), Paths::in_dir(&explicit));
+
+        // An existing setup in the current directory stays there
+        current.create_root().unwrap();
+        std::fs::create_dir(current.sessions()).unwrap();
+        assert_eq!(choose(false, None), current);
+    }
+
+    #[test]
+    fn test_with_profile() {
+        let paths = Paths::in_dir("data").with_profile("sister").unwrap();
+        assert_eq!(paths.profile(), Some("sister"));
+        assert_eq!(
+            paths.tracker(),
+            Path::new("data")
+                .join("profiles")
+                .join("sister")
+                .join("tracker.tsv")
+        );
+        assert_eq!(
+            paths.sessions(),
+            Path::new("data")
+                .join("profiles")
+                .join("sister")
+                .join("sessions")
+        );
+        assert_eq!(paths.tags(), Path::new("data").join("tags.json"));
+
+        for name in ["", " ", "..", "a/b", "a\\b", "C:"] {
+            assert!(Paths::portable().with_profile(name).is_err(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn test_create_root() {
+        let temp = tempfile::tempdir().unwrap();
+        let paths = Paths::in_dir(temp.path().join("a").join("infst"));
+        paths.create_root().unwrap();
+        assert!(paths.root().is_dir());
+
+        let paths = paths.with_profile("sister").unwrap();
+        paths.create_root().unwrap();
+        assert!(paths.tracker().parent().unwrap().is_dir());
+    }
+}
@@ -0,0 +1,305 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::play::PlayData;
+use crate::score::{Judge, Lamp};
+
+/// Aggregate result of a course (dan / class course or other multi-stage course)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CourseResult {
+    /// Timestamp of the first stage
+    pub started_at: DateTime<Utc>,
+    /// Stage results in play order
+    pub stages: Vec<PlayData>,
+}
+
+impl CourseResult {
+    /// Whether every stage was cleared
+    pub fn passed(&self) -> bool {
+        !self.stages.is_empty() && self.failed_stage().is_none()
+    }
+
+    /// Index of the first failed stage (0-based)
+    pub fn failed_stage(&self) -> Option<usize> {
+        self.stages.iter().position(|s| s.lamp <= Lamp::Failed)
+    }
+
+    /// Judge counts summed over all stages
+    pub fn total_judge(&self) -> Judge {
+        let mut total = Judge {
+            play_type: self
+                .stages
+                .first()
+                .map(|s| s.judge.play_type)
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+        for stage in &self.stages {
+            let judge = &stage.judge;
+            total.pgreat += judge.pgreat;
+            total.great += judge.great;
+            total.good += judge.good;
+            total.bad += judge.bad;
+            total.poor += judge.poor;
+            total.fast += judge.fast;
+            total.slow += judge.slow;
+            total.combo_break += judge.combo_break;
+            total.premature_end |= judge.premature_end;
+        }
+        total
+    }
+
+    pub fn ex_score(&self) -> u32 {
+        self.stages.iter().map(|s| s.ex_score).sum()
+    }
+
+    /// Maximum EX score over all stages (0 if any note count is unknown)
+    pub fn max_ex_score(&self) -> u32 {
+        if self.stages.iter().any(|s| s.chart.total_notes == 0) {
+            return 0;
+        }
+        self.stages.iter().map(|s| s.chart.total_notes * 2).sum()
+    }
+
+    /// Course EX score as a percentage of the maximum (0.0 - 100.0)
+    pub fn percentage(&self) -> f64 {
+        let max = self.max_ex_score();
+        if max == 0 {
+            return 0.0;
+        }
+        self.ex_score() as f64 * 100.0 / max as f64
+    }
+
+    /// One-line summary (e.g. `COURSE 4 stages PASSED  EX 8000 (81.23%)`)
+    pub fn summary(&self) -> String {
+        let status = match self.failed_stage() {
+            None => "PASSED".to_string(),
+            Some(stage) => format!("FAILED at stage {}", stage + 1),
+        };
+        format!(
+            "COURSE {} stages {}  EX {} ({:.2}%)",
+            self.stages.len(),
+            status,
+            self.ex_score(),
+            self.percentage()
+        )
+    }
+}
+
+/// One credit's worth of plays: a standard-mode set or a course
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaySet {
+    /// Stage results and aggregates (total EX, failed stage)
+    pub result: CourseResult,
+    /// Whether the stages were played as a course
+    pub course: bool,
+}
+
+impl PlaySet {
+    /// Number of stages in a standard-mode credit
+    pub const STANDARD_STAGES: usize = 3;
+    /// Idle time after which the next play starts a new set (seconds)
+    pub const MAX_GAP_SECS: i64 = 10 * 60;
+
+    /// Start a standard-mode set with its first stage
+    pub fn standard(first: PlayData) -> Self {
+        Self {
+            result: CourseResult {
+                started_at: first.timestamp,
+                stages: vec![first],
+            },
+            course: false,
+        }
+    }
+
+    /// Wrap a detected course as a complete set
+    pub fn from_course(course: CourseResult) -> Self {
+        Self {
+            result: course,
+            course: true,
+        }
+    }
+
+    /// Whether no more stages belong to this set
+    pub fn is_complete(&self) -> bool {
+        self.course || self.result.stages.len() >= Self::STANDARD_STAGES
+    }
+
+    /// Whether `play` continues this set (not complete, no long break)
+    pub fn accepts(&self, play: &PlayData) -> bool {
+        let Some(last) = self.result.stages.last() else {
+            return false;
+        };
+        !self.is_complete()
+            && play.timestamp - last.timestamp <= TimeDelta::seconds(Self::MAX_GAP_SECS)
+    }
+
+    /// Stage the set failed on (1-based)
+    pub fn failed_on(&self) -> Option<usize> {
+        self.result.failed_stage().map(|i| i + 1)
+    }
+
+    /// One-line summary (e.g. `SET 3 stages  EX 4500 (75.00%)  failed on stage 2`)
+    pub fn summary(&self) -> String {
+        if self.course {
+            return self.result.summary();
+        }
+        let mut summary = format!(
+            "SET {} stages  EX {} ({:.2}%)",
+            self.result.stages.len(),
+            self.result.ex_score(),
+            self.result.percentage()
+        );
+        if let Some(stage) = self.failed_on() {
+            summary.push_str(&format!("  failed on stage {}", stage));
+        }
+        summary
+    }
+}
+
+/// Detects course mode from consecutive stage results
+///
+/// In a course the game moves from one stage's result straight into the next
+/// stage without returning to song select, so two or more results recorded
+/// between song select visits are treated as one course.
+#[derive(Debug, Default)]
+pub struct CourseTracker {
+    stages: Vec<PlayData>,
+}
+
+impl CourseTracker {
+    /// Minimum number of consecutive stages that counts as a course
+    pub const MIN_STAGES: usize = 2;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a captured stage result
+    pub fn record_stage(&mut self, play_data: PlayData) {
+        self.stages.push(play_data);
+    }
+
+    /// Whether the current run of stages is a course
+    pub fn in_course(&self) -> bool {
+        self.stages.len() >= Self::MIN_STAGES
+    }
+
+    /// Number of stages recorded since the last song select
+    pub fn stage_count(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// End the current run (on returning to song select)
+    ///
+    /// Returns the course result if the run was a course; single plays are discarded.
+    pub fn finish(&mut self) -> Option<CourseResult> {
+        self.finish_run().ok()
+    }
+
+    /// End the current run, handing back the stages when it wasn't a course
+    pub fn finish_run(&mut self) -> std::result::Result<CourseResult, Vec<PlayData>> {
+        let stages = std::mem::take(&mut self.stages);
+        if stages.len() < Self::MIN_STAGES {
+            return Err(stages);
+        }
+        Ok(CourseResult {
+            started_at: stages[0].timestamp,
+            stages,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::play::test_support::PlayBuilder;
+    use crate::score::Grade;
+
+    fn make_stage(ex_score: u32, lamp: Lamp) -> PlayData {
+        PlayBuilder::new()
+            .title("Stage")
+            .ex_score(ex_score)
+            .grade(Grade::Aa)
+            .lamp(lamp)
+            .judge(Judge {
+                pgreat: ex_score / 2,
+                bad: 5,
+                poor: 10,
+                ..Default::default()
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_single_play_is_not_a_course() {
+        let mut tracker = CourseTracker::new();
+        tracker.record_stage(make_stage(1600, Lamp::Clear));
+        assert!(!tracker.in_course());
+        assert!(tracker.finish().is_none());
+        assert_eq!(tracker.stage_count(), 0);
+    }
+
+    #[test]
+    fn test_course_passed() {
+        let mut tracker = CourseTracker::new();
+        // A dan course has four stages
+        for _ in 0..4 {
+            tracker.record_stage(make_stage(1600, Lamp::Clear));
+        }
+        assert!(tracker.in_course());
+
+        let course = tracker.finish().unwrap();
+        assert!(course.passed());
+        assert_eq!(course.ex_score(), 6400);
+        assert_eq!(course.max_ex_score(), 8000);
+        assert!((course.percentage() - 80.0).abs() < 1e-9);
+        assert_eq!(course.total_judge().poor, 40);
+        assert_eq!(course.summary(), "COURSE 4 stages PASSED  EX 6400 (80.00%)");
+    }
+
+    #[test]
+    fn test_course_failed_stage() {
+        let mut tracker = CourseTracker::new();
+        tracker.record_stage(make_stage(1600, Lamp::Clear));
+        tracker.record_stage(make_stage(800, Lamp::Failed));
+
+        let course = tracker.finish().unwrap();
+        assert!(!course.passed());
+        assert_eq!(course.failed_stage(), Some(1));
+        assert!(course.summary().contains("FAILED at stage 2"));
+    }
+
+    #[test]
+    fn test_finish_run_returns_single_play() {
+        let mut tracker = CourseTracker::new();
+        tracker.record_stage(make_stage(1600, Lamp::Clear));
+        let stages = tracker.finish_run().unwrap_err();
+        assert_eq!(stages.len(), 1);
+        assert!(tracker.finish_run().unwrap_err().is_empty());
+    }
+
+    #[test]
+    fn test_play_set_grouping() {
+        let first = make_stage(1600, Lamp::Clear);
+        let mut set = PlaySet::standard(first.clone());
+        let mut second = make_stage(800, Lamp::Failed);
+        second.timestamp = first.timestamp + TimeDelta::minutes(3);
+        assert!(set.accepts(&second));
+        set.result.stages.push(second.clone());
+
+        // A long break starts a new credit
+        let mut late = make_stage(1600, Lamp::Clear);
+        late.timestamp = second.timestamp + TimeDelta::minutes(30);
+        assert!(!set.accepts(&late));
+
+        set.result.stages.push(make_stage(1600, Lamp::Clear));
+        assert!(set.is_complete());
+        assert!(!set.accepts(&make_stage(1600, Lamp::Clear)));
+        assert_eq!(set.failed_on(), Some(2));
+        assert_eq!(
+            set.summary(),
+            "SET 3 stages  EX 4000 (66.67%)  failed on stage 2"
+        );
+    }
+}
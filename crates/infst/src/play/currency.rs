@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::offset::constants::MAX_BITS_BALANCE;
+use crate::process::layout::{Field, FieldKind};
+use crate::process::{ByteBuffer, ReadMemory};
+
+/// Consumables held by the player (bits and unlock tickets)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Currency {
+    /// Bit balance, spent on bit unlocks
+    pub bits: u32,
+    /// Unlock tickets
+    pub tickets: u32,
+}
+
+impl Currency {
+    /// Size of the currency block in memory
+    ///
+    /// Layout:
+    /// - 0x00: bit balance (i32)
+    /// - 0x04: unlock tickets (i32)
+    pub const MEMORY_SIZE: usize = 0x08;
+
+    const BITS_OFFSET: usize = 0x00;
+    const TICKETS_OFFSET: usize = 0x04;
+
+    /// Known fields as (offset, size, name, kind), for memory annotations and diffs
+    #[cfg_attr(not(feature = "debug-tools"), allow(dead_code))]
+    pub(crate) const FIELDS: &'static [Field] = &[
+        (Self::BITS_OFFSET, 4, "bits", FieldKind::Int),
+        (Self::TICKETS_OFFSET, 4, "tickets", FieldKind::Int),
+    ];
+
+    /// Maximum plausible ticket count (for validation)
+    pub const MAX_TICKETS: i32 = 9_999;
+
+    /// Parse from raw bytes
+    ///
+    /// Returns `None` if the block doesn't look like a currency block
+    /// (negative or implausibly large counts).
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < Self::MEMORY_SIZE {
+            return None;
+        }
+
+        let buf = ByteBuffer::new(bytes);
+        let bits = buf.read_i32_at(Self::BITS_OFFSET).ok()?;
+        let tickets = buf.read_i32_at(Self::TICKETS_OFFSET).ok()?;
+        if !(0..=MAX_BITS_BALANCE).contains(&bits) || !(0..=Self::MAX_TICKETS).contains(&tickets) {
+            return None;
+        }
+
+        Some(Self {
+            bits: bits as u32,
+            tickets: tickets as u32,
+        })
+    }
+
+    /// One-line summary (e.g. `12345 bits, 3 tickets`)
+    pub fn summary(&self) -> String {
+        format!("{} bits, {} tickets", self.bits, self.tickets)
+    }
+}
+
+/// Read the currency block
+pub fn read_currency<R: ReadMemory + ?Sized>(reader: &R, currency_addr: u64) -> Result<Currency> {
+    let bytes = reader.read_bytes(currency_addr, Currency::MEMORY_SIZE)?;
+    Currency::from_bytes(&bytes).ok_or_else(|| {
+        Error::InvalidOffset(format!("No valid currency data at 0x{:X}", currency_addr))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::MockMemoryBuilder;
+
+    #[test]
+    fn test_read_currency() {
+        let reader = MockMemoryBuilder::new()
+            .write_i32(0x10, 12_345)
+            .write_i32(0x14, 3)
+            .build();
+        let currency = read_currency(&reader, 0x1010).unwrap();
+        assert_eq!(
+            currency,
+            Currency {
+                bits: 12_345,
+                tickets: 3
+            }
+        );
+        assert_eq!(currency.summary(), "12345 bits, 3 tickets");
+    }
+
+    #[test]
+    fn test_read_currency_rejects_invalid() {
+        let reader = MockMemoryBuilder::new()
+            // Negative bits
+            .write_i32(0x00, -1)
+            .write_i32(0x04, 0)
+            // Implausible ticket count
+            .write_i32(0x08, 100)
+            .write_i32(0x0C, Currency::MAX_TICKETS + 1)
+            .build();
+        assert!(read_currency(&reader, 0x1000).is_err());
+        assert!(read_currency(&reader, 0x1008).is_err());
+        // Outside the readable memory
+        assert!(read_currency(&reader, 0x2000).is_err());
+    }
+
+    #[test]
+    fn test_from_bytes_bounds() {
+        let block = |bits: i32, tickets: i32| {
+            let mut bytes = bits.to_le_bytes().to_vec();
+            bytes.extend_from_slice(&tickets.to_le_bytes());
+            bytes
+        };
+        assert!(Currency::from_bytes(&block(0, 0)).is_some());
+        assert!(Currency::from_bytes(&block(MAX_BITS_BALANCE, Currency::MAX_TICKETS)).is_some());
+        assert!(Currency::from_bytes(&block(MAX_BITS_BALANCE + 1, 0)).is_none());
+        assert!(Currency::from_bytes(&[0; 4]).is_none());
+    }
+}
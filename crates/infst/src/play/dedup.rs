@@ -0,0 +1,100 @@
+//! Guard against recording the same result screen twice
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::chart::Difficulty;
+use crate::score::Judge;
+
+use super::PlayData;
+
+/// Default window in which an identical result counts as a duplicate
+pub const DEFAULT_DUPLICATE_WINDOW_SECS: u64 = 60;
+
+/// Detects repeated reads of one result screen
+///
+/// A play is a duplicate of the previous one when it has the same chart and
+/// judge counts and is timestamped within the window. Playing a chart takes
+/// longer than the default window, so a genuine replay is not suppressed.
+#[derive(Debug, Clone)]
+pub struct DuplicateGuard {
+    window: TimeDelta,
+    last: Option<(u32, Difficulty, Judge, DateTime<Utc>)>,
+}
+
+impl DuplicateGuard {
+    /// A guard with the given window (zero disables it)
+    pub fn new(window: std::time::Duration) -> Self {
+        Self {
+            window: TimeDelta::from_std(window).unwrap_or(TimeDelta::MAX),
+            last: None,
+        }
+    }
+
+    /// Check `play_data` against the last play seen and remember it
+    ///
+    /// Returns `true` when it repeats the previous play.
+    pub fn check(&mut self, play_data: &PlayData) -> bool {
+        let key = (
+            play_data.chart.song_id,
+            play_data.chart.difficulty,
+            play_data.judge.clone(),
+            play_data.timestamp,
+        );
+        let duplicate = !self.window.is_zero()
+            && self.last.as_ref().is_some_and(|(id, diff, judge, time)| {
+                (*id, *diff, judge) == (key.0, key.1, &key.2)
+                    && (key.3 - *time).abs() <= self.window
+            });
+        self.last = Some(key);
+        duplicate
+    }
+}
+
+impl Default for DuplicateGuard {
+    fn default() -> Self {
+        Self::new(std::time::Duration::from_secs(
+            DEFAULT_DUPLICATE_WINDOW_SECS,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::play::test_support::PlayBuilder;
+    use crate::score::Grade;
+
+    fn play(song_id: u32, pgreat: u32, seconds: i64) -> PlayData {
+        PlayBuilder::new()
+            .timestamp(DateTime::<Utc>::UNIX_EPOCH + TimeDelta::seconds(seconds))
+            .song_id(song_id)
+            .title("Song")
+            .judge_ex(Judge {
+                pgreat,
+                ..Judge::default()
+            })
+            .grade(Grade::A)
+            .build()
+    }
+
+    #[test]
+    fn test_repeated_result_within_window() {
+        let mut guard = DuplicateGuard::default();
+        assert!(!guard.check(&play(1000, 900, 0)));
+        assert!(guard.check(&play(1000, 900, 2)));
+        // Different judge counts or chart
+        assert!(!guard.check(&play(1000, 901, 3)));
+        assert!(!guard.check(&play(1001, 901, 4)));
+        // Same result after the window: a genuine replay
+        assert!(!guard.check(&play(1001, 901, 120)));
+    }
+
+    #[test]
+    fn test_zero_window_disables() {
+        let mut guard = DuplicateGuard::new(Duration::ZERO);
+        assert!(!guard.check(&play(1000, 900, 0)));
+        assert!(!guard.check(&play(1000, 900, 0)));
+    }
+}
@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::play::{PlayType, RangeType};
+use crate::process::layout::{Field, FieldKind};
+use crate::process::{ByteBuffer, ReadMemory};
+
+/// Lane cover positions and green number of one side
+///
+/// Cover positions are the white numbers shown in game (0-1000, thousandths
+/// of the lane). The green number is how long notes stay visible, in frames
+/// at 60 fps. Covers are recorded as set, whether or not the range option
+/// currently shows them; see [`LaneCover::sudden_if`] and friends.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaneCover {
+    /// SUDDEN+ cover (white number)
+    pub sudden: u16,
+    /// HIDDEN+ cover (white number)
+    pub hidden: u16,
+    /// LIFT height (white number)
+    pub lift: u16,
+    /// Note visibility time (green number)
+    pub green_number: u16,
+}
+
+impl LaneCover {
+    /// Size of the lane cover block in memory, covering both sides
+    ///
+    /// Layout per side (P2 at [`P2_OFFSET`](Self::P2_OFFSET)):
+    /// - 0x00: SUDDEN+ (i32)
+    /// - 0x04: HIDDEN+ (i32)
+    /// - 0x08: LIFT (i32)
+    /// - 0x0C: green number (i32)
+    pub const MEMORY_SIZE: usize = 0x20;
+
+    /// P2 side offset within the block
+    pub const P2_OFFSET: usize = 0x10;
+
+    const SUDDEN_OFFSET: usize = 0x00;
+    const HIDDEN_OFFSET: usize = 0x04;
+    const LIFT_OFFSET: usize = 0x08;
+    const GREEN_NUMBER_OFFSET: usize = 0x0C;
+
+    /// Known fields as (offset, size, name, kind), for memory annotations and diffs
+    #[cfg_attr(not(feature = "debug-tools"), allow(dead_code))]
+    pub(crate) const FIELDS: &'static [Field] = &[
+        (Self::SUDDEN_OFFSET, 4, "p1_sudden", FieldKind::Int),
+        (Self::HIDDEN_OFFSET, 4, "p1_hidden", FieldKind::Int),
+        (Self::LIFT_OFFSET, 4, "p1_lift", FieldKind::Int),
+        (
+            Self::GREEN_NUMBER_OFFSET,
+            4,
+            "p1_green_number",
+            FieldKind::Int,
+        ),
+        (
+            Self::P2_OFFSET + Self::SUDDEN_OFFSET,
+            4,
+            "p2_sudden",
+            FieldKind::Int,
+        ),
+        (
+            Self::P2_OFFSET + Self::HIDDEN_OFFSET,
+            4,
+            "p2_hidden",
+            FieldKind::Int,
+        ),
+        (
+            Self::P2_OFFSET + Self::LIFT_OFFSET,
+            4,
+            "p2_lift",
+            FieldKind::Int,
+        ),
+        (
+            Self::P2_OFFSET + Self::GREEN_NUMBER_OFFSET,
+            4,
+            "p2_green_number",
+            FieldKind::Int,
+        ),
+    ];
+
+    /// Maximum cover position (the whole lane)
+    pub const MAX_COVER: i32 = 1000;
+
+    /// Plausible green numbers (for validation)
+    pub const GREEN_NUMBER_RANGE: std::ops::RangeInclusive<i32> = 1..=9_999;
+
+    /// Parse the side used by `play_type` from raw block bytes
+    ///
+    /// DP reads the P1 side. Returns `None` if the side doesn't look like lane
+    /// cover data (covers outside 0-1000 or an implausible green number).
+    pub fn from_bytes(bytes: &[u8], play_type: PlayType) -> Option<Self> {
+        if bytes.len() < Self::MEMORY_SIZE {
+            return None;
+        }
+
+        let base = match play_type {
+            PlayType::P2 => Self::P2_OFFSET,
+            PlayType::P1 | PlayType::Dp => 0,
+        };
+        let buf = ByteBuffer::new(bytes);
+        let cover = |offset: usize| {
+            let value = buf.read_i32_at(base + offset).ok()?;
+            (0..=Self::MAX_COVER)
+                .contains(&value)
+                .then_some(value as u16)
+        };
+        let green_number = buf.read_i32_at(base + Self::GREEN_NUMBER_OFFSET).ok()?;
+        if !Self::GREEN_NUMBER_RANGE.contains(&green_number) {
+            return None;
+        }
+
+        Some(Self {
+            sudden: cover(Self::SUDDEN_OFFSET)?,
+            hidden: cover(Self::HIDDEN_OFFSET)?,
+            lift: cover(Self::LIFT_OFFSET)?,
+            green_number: green_number as u16,
+        })
+    }
+
+    /// SUDDEN+ position when `range` shows it
+    pub fn sudden_if(&self, range: RangeType) -> Option<u16> {
+        matches!(
+            range,
+            RangeType::SuddenPlus | RangeType::SudHid | RangeType::LiftSud
+        )
+        .then_some(self.sudden)
+    }
+
+    /// HIDDEN+ position when `range` shows it
+    pub fn hidden_if(&self, range: RangeType) -> Option<u16> {
+        matches!(range, RangeType::HiddenPlus | RangeType::SudHid).then_some(self.hidden)
+    }
+
+    /// LIFT height when `range` shows it
+    pub fn lift_if(&self, range: RangeType) -> Option<u16> {
+        matches!(range, RangeType::Lift | RangeType::LiftSud).then_some(self.lift)
+    }
+}
+
+/// Read the lane cover block for the side used by `play_type`
+pub fn read_lane_cover<R: ReadMemory + ?Sized>(
+    reader: &R,
+    lane_cover_addr: u64,
+    play_type: PlayType,
+) -> Result<LaneCover> {
+    let bytes = reader.read_bytes(lane_cover_addr, LaneCover::MEMORY_SIZE)?;
+    LaneCover::from_bytes(&bytes, play_type).ok_or_else(|| {
+        Error::InvalidOffset(format!(
+            "No valid lane cover data at 0x{:X}",
+            lane_cover_addr
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::{MockMemoryBuilder, MockMemoryReader};
+
+    fn reader() -> MockMemoryReader {
+        MockMemoryBuilder::new()
+            .write_i32(0x00, 250)
+            .write_i32(0x04, 0)
+            .write_i32(0x08, 120)
+            .write_i32(0x0C, 290)
+            .write_i32(0x10, 300)
+            .write_i32(0x14, 100)
+            .write_i32(0x18, 0)
+            .write_i32(0x1C, 310)
+            .build()
+    }
+
+    #[test]
+    fn test_read_lane_cover_sides() {
+        let reader = reader();
+        let p1 = read_lane_cover(&reader, 0x1000, PlayType::P1).unwrap();
+        assert_eq!(
+            p1,
+            LaneCover {
+                sudden: 250,
+                hidden: 0,
+                lift: 120,
+                green_number: 290
+            }
+        );
+        assert_eq!(read_lane_cover(&reader, 0x1000, PlayType::Dp).unwrap(), p1);
+
+        let p2 = read_lane_cover(&reader, 0x1000, PlayType::P2).unwrap();
+        assert_eq!((p2.sudden, p2.hidden, p2.green_number), (300, 100, 310));
+    }
+
+    #[test]
+    fn test_read_lane_cover_rejects_invalid() {
+        let reader = MockMemoryBuilder::new()
+            // Green number 0 on P1, cover past the lane on P2
+            .write_i32(0x10, 1001)
+            .write_i32(0x1C, 300)
+            .build();
+        assert!(read_lane_cover(&reader, 0x1000, PlayType::P1).is_err());
+        assert!(read_lane_cover(&reader, 0x1000, PlayType::P2).is_err());
+        // Outside the readable memory
+        assert!(read_lane_cover(&reader, 0x2000, PlayType::P1).is_err());
+    }
+
+    #[test]
+    fn test_covers_shown_by_range() {
+        let cover = LaneCover {
+            sudden: 250,
+            hidden: 100,
+            lift: 120,
+            green_number: 290,
+        };
+        assert_eq!(cover.sudden_if(RangeType::Off), None);
+        assert_eq!(cover.sudden_if(RangeType::LiftSud), Some(250));
+        assert_eq!(cover.hidden_if(RangeType::SudHid), Some(100));
+        assert_eq!(cover.hidden_if(RangeType::SuddenPlus), None);
+        assert_eq!(cover.lift_if(RangeType::Lift), Some(120));
+        assert_eq!(cover.lift_if(RangeType::HiddenPlus), None);
+    }
+}
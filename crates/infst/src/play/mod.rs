@@ -6,14 +6,38 @@
 //! - `GameState` - game states (Unknown, SongSelect, Playing, ResultScreen)
 //! - `PlayData` - complete play data
 //! - `Settings` - play settings
+//! - `LaneCover` - lane cover positions and green number
 //! - `GameStateDetector` - game state detection
+//! - `ProfileInfo` - player profile (DJ name, dan ranks, play count)
+//! - `Currency` - bits and unlock tickets held by the player
+//! - `CourseResult` - aggregate result of a dan / class course
+//! - `PlaySet` - one credit's plays (standard-mode set or course)
+//! - `PlayAnomaly` - impossible values found by `PlayData::validate`
+//! - `DuplicateGuard` - detects a result screen read twice
+//! - `PrematureMode` - how plays quit before the end are recorded
 
+mod course;
+mod currency;
+mod dedup;
 mod enums;
+mod lane_cover;
 mod play_data;
+mod premature;
+mod profile;
 mod settings;
 mod state;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+mod validate;
 
+pub use course::*;
+pub use currency::*;
+pub use dedup::*;
 pub use enums::*;
+pub use lane_cover::*;
 pub use play_data::*;
+pub use premature::*;
+pub use profile::*;
 pub use settings::*;
 pub use state::*;
+pub use validate::*;
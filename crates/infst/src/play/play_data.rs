@@ -17,6 +17,13 @@ pub struct PlayData {
     pub settings: Settings,
     /// False if play data isn't available (H-RAN, BATTLE or assist options enabled)
     pub data_available: bool,
+    /// BPI, when reference data for the chart is loaded
+    #[serde(default)]
+    pub bpi: Option<f64>,
+    /// How far into the chart a failed play got (0-100), see
+    /// [`fail_point_percent`](Self::fail_point_percent)
+    #[serde(default)]
+    pub failed_at_percent: Option<f64>,
 }
 
 impl PlayData {
@@ -26,6 +33,11 @@ impl PlayData {
         self.data_available && !self.judge.premature_end && self.settings.assist == AssistType::Off
     }
 
+    /// Whether the play used an assist option (A-SCR, 5KEYS, ...)
+    pub fn is_assisted(&self) -> bool {
+        self.settings.assist != AssistType::Off
+    }
+
     /// Get miss count (bad + poor)
     pub fn miss_count(&self) -> u32 {
         self.judge.miss_count()
@@ -40,6 +52,47 @@ impl PlayData {
         let ratio = ex_score as f64 / max_ex as f64;
         Grade::from_score_ratio(ratio)
     }
+
+    /// EX score as a fraction of the maximum EX score (0.0 - 1.0)
+    pub fn score_rate(&self) -> f64 {
+        if self.chart.total_notes == 0 {
+            return 0.0;
+        }
+        self.ex_score as f64 / (self.chart.total_notes * 2) as f64
+    }
+
+    /// Percentage of the chart's notes judged, for failed plays
+    ///
+    /// Derived from the judge counts at the time of the fail. POOR also
+    /// counts empty poors, so the value is capped at 100. `None` for plays
+    /// that didn't fail or when the note count is unknown.
+    pub fn fail_point_percent(&self) -> Option<f64> {
+        let total_notes = self.chart.total_notes;
+        if self.lamp != Lamp::Failed || total_notes == 0 {
+            return None;
+        }
+        let judge = &self.judge;
+        let judged: u64 = [judge.pgreat, judge.great, judge.good, judge.bad, judge.poor]
+            .into_iter()
+            .map(u64::from)
+            .sum();
+        Some(judged.min(u64::from(total_notes)) as f64 * 100.0 / total_notes as f64)
+    }
+
+    /// Next grade above this play and the EX score still needed to reach it
+    ///
+    /// Returns `None` at AAA or when the chart's note count is unknown.
+    pub fn next_grade_gap(&self) -> Option<(Grade, u32)> {
+        let total_notes = self.chart.total_notes;
+        if total_notes == 0 {
+            return None;
+        }
+        let current = Self::calculate_grade(self.ex_score, total_notes);
+        Grade::thresholds(total_notes)
+            .into_iter()
+            .find(|(grade, _)| *grade > current)
+            .map(|(grade, score)| (grade, score.saturating_sub(self.ex_score)))
+    }
 }
 
 // DJ Points calculation constants
@@ -97,6 +150,7 @@ pub fn calculate_dj_points_from_score(ex_score: u32, total_notes: u32, lamp: Lam
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::play::test_support::PlayBuilder;
 
     #[test]
     fn test_calculate_dj_points() {
@@ -118,4 +172,44 @@ mod tests {
         // DJ Points = 500 * 100 / 10000 = 5.0
         assert!((djp - 5.0).abs() < 0.01);
     }
+
+    fn make_play(ex_score: u32, total_notes: u32) -> PlayData {
+        PlayBuilder::new()
+            .total_notes(total_notes)
+            .ex_score(ex_score)
+            .build()
+    }
+
+    #[test]
+    fn test_fail_point_percent() {
+        let mut play = make_play(500, 1000);
+        play.judge.pgreat = 200;
+        play.judge.great = 100;
+        play.judge.poor = 100;
+        assert_eq!(play.fail_point_percent(), None);
+
+        play.lamp = Lamp::Failed;
+        assert_eq!(play.fail_point_percent(), Some(40.0));
+        play.judge.poor = 2000;
+        assert_eq!(play.fail_point_percent(), Some(100.0));
+        play.chart.total_notes = 0;
+        assert_eq!(play.fail_point_percent(), None);
+    }
+
+    #[test]
+    fn test_score_rate() {
+        assert!((make_play(1600, 1000).score_rate() - 0.8).abs() < f64::EPSILON);
+        assert!(make_play(0, 0).score_rate().abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_next_grade_gap() {
+        assert_eq!(
+            make_play(1600, 1000).next_grade_gap(),
+            Some((Grade::Aaa, 178))
+        );
+        assert_eq!(make_play(1555, 1000).next_grade_gap(), Some((Grade::Aa, 1)));
+        assert_eq!(make_play(1800, 1000).next_grade_gap(), None);
+        assert_eq!(make_play(0, 0).next_grade_gap(), None);
+    }
 }
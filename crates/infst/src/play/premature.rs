@@ -0,0 +1,71 @@
+//! Handling of plays quit before the end of the chart
+
+use crate::score::{Grade, Lamp};
+
+use super::PlayData;
+
+/// What the tracker does with plays that ended early (`Judge::premature_end`)
+///
+/// Whatever the mode, a premature play never counts as a personal best
+/// improvement, a tournament score or an API lamp update.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PrematureMode {
+    /// Record the play as read, flagged as a premature end
+    #[default]
+    Flag,
+    /// Don't record the play
+    Ignore,
+    /// Record the judge counts only (see [`PlayData::judge_only`])
+    JudgeOnly,
+}
+
+impl PlayData {
+    /// Whether the play was quit before the end of the chart
+    pub fn is_premature(&self) -> bool {
+        self.judge.premature_end
+    }
+
+    /// The play with its result (lamp, grade, BPI) cleared, keeping the
+    /// judge counts and EX score
+    pub fn judge_only(&self) -> Self {
+        Self {
+            lamp: Lamp::NoPlay,
+            grade: Grade::NoPlay,
+            bpi: None,
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::play::test_support::PlayBuilder;
+    use crate::score::Judge;
+
+    #[test]
+    fn test_judge_only_clears_result() {
+        let play = PlayBuilder::new()
+            .title("Song")
+            .ex_score(500)
+            .grade(Grade::F)
+            .lamp(Lamp::Failed)
+            .judge(Judge {
+                pgreat: 200,
+                great: 100,
+                premature_end: true,
+                ..Judge::default()
+            })
+            .bpi(-10.0)
+            .failed_at_percent(Some(30.0))
+            .build();
+        assert!(play.is_premature());
+
+        let judge_only = play.judge_only();
+        assert_eq!(judge_only.lamp, Lamp::NoPlay);
+        assert_eq!(judge_only.grade, Grade::NoPlay);
+        assert_eq!(judge_only.bpi, None);
+        assert_eq!(judge_only.ex_score, 500);
+        assert_eq!(judge_only.judge, play.judge);
+    }
+}
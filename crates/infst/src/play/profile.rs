@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+use strum::{FromRepr, IntoStaticStr};
+
+use crate::error::Result;
+use crate::process::layout::{Field, FieldKind};
+use crate::process::{ByteBuffer, ReadMemory};
+
+/// Dan (段位) rank
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    FromRepr,
+    IntoStaticStr,
+)]
+#[repr(u8)]
+pub enum DanRank {
+    #[strum(serialize = "七級")]
+    Kyu7 = 0,
+    #[strum(serialize = "六級")]
+    Kyu6 = 1,
+    #[strum(serialize = "五級")]
+    Kyu5 = 2,
+    #[strum(serialize = "四級")]
+    Kyu4 = 3,
+    #[strum(serialize = "三級")]
+    Kyu3 = 4,
+    #[strum(serialize = "二級")]
+    Kyu2 = 5,
+    #[strum(serialize = "一級")]
+    Kyu1 = 6,
+    #[strum(serialize = "初段")]
+    Dan1 = 7,
+    #[strum(serialize = "二段")]
+    Dan2 = 8,
+    #[strum(serialize = "三段")]
+    Dan3 = 9,
+    #[strum(serialize = "四段")]
+    Dan4 = 10,
+    #[strum(serialize = "五段")]
+    Dan5 = 11,
+    #[strum(serialize = "六段")]
+    Dan6 = 12,
+    #[strum(serialize = "七段")]
+    Dan7 = 13,
+    #[strum(serialize = "八段")]
+    Dan8 = 14,
+    #[strum(serialize = "九段")]
+    Dan9 = 15,
+    #[strum(serialize = "十段")]
+    Dan10 = 16,
+    #[strum(serialize = "中伝")]
+    Chuuden = 17,
+    #[strum(serialize = "皆伝")]
+    Kaiden = 18,
+}
+
+impl DanRank {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Self::from_repr(value)
+    }
+
+    /// Parse the raw in-memory value (negative = no rank)
+    fn from_raw(value: i32) -> Option<Self> {
+        u8::try_from(value).ok().and_then(Self::from_u8)
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        self.into()
+    }
+}
+
+impl std::fmt::Display for DanRank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display_name())
+    }
+}
+
+/// Player profile (DJ name, dan ranks, play count)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileInfo {
+    pub dj_name: String,
+    pub sp_dan: Option<DanRank>,
+    pub dp_dan: Option<DanRank>,
+    pub play_count: u32,
+}
+
+impl ProfileInfo {
+    /// Size of the profile block in memory
+    ///
+    /// Layout:
+    /// - 0x00: DJ name (16 bytes, NUL-padded ASCII)
+    /// - 0x10: SP dan rank (i32, -1 = none)
+    /// - 0x14: DP dan rank (i32, -1 = none)
+    /// - 0x18: total play count (u32)
+    pub const MEMORY_SIZE: usize = 0x20;
+
+    const DJ_NAME_SIZE: usize = 16;
+    const SP_DAN_OFFSET: usize = 0x10;
+    const DP_DAN_OFFSET: usize = 0x14;
+    const PLAY_COUNT_OFFSET: usize = 0x18;
+
+    /// Known fields as (offset, size, name, kind), for memory annotations and diffs
+    #[cfg_attr(not(feature = "debug-tools"), allow(dead_code))]
+    pub(crate) const FIELDS: &'static [Field] = &[
+        (0, Self::DJ_NAME_SIZE, "dj_name", FieldKind::Text),
+        (Self::SP_DAN_OFFSET, 4, "sp_dan", FieldKind::Int),
+        (Self::DP_DAN_OFFSET, 4, "dp_dan", FieldKind::Int),
+        (Self::PLAY_COUNT_OFFSET, 4, "play_count", FieldKind::Int),
+    ];
+
+    /// Maximum DJ name length accepted by the game
+    pub const MAX_DJ_NAME_LEN: usize = 6;
+
+    /// Characters allowed in a DJ name
+    const DJ_NAME_SYMBOLS: &'static str = "-#.*!&$?@ ";
+
+    /// Parse from raw bytes
+    ///
+    /// Returns `None` if the block doesn't look like a profile (invalid DJ
+    /// name or out-of-range dan values).
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < Self::MEMORY_SIZE {
+            return None;
+        }
+
+        let name_bytes = &bytes[..Self::DJ_NAME_SIZE];
+        let name_len = name_bytes
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(Self::DJ_NAME_SIZE);
+        let dj_name = std::str::from_utf8(&name_bytes[..name_len]).ok()?;
+        if !Self::is_valid_dj_name(dj_name) {
+            return None;
+        }
+
+        let buf = ByteBuffer::new(bytes);
+        let sp_raw = buf.read_i32_at(Self::SP_DAN_OFFSET).ok()?;
+        let dp_raw = buf.read_i32_at(Self::DP_DAN_OFFSET).ok()?;
+        let play_count = buf.read_u32_at(Self::PLAY_COUNT_OFFSET).ok()?;
+
+        let sp_dan = DanRank::from_raw(sp_raw);
+        let dp_dan = DanRank::from_raw(dp_raw);
+        // Anything other than "no rank" must be a known rank
+        if (sp_raw >= 0 && sp_dan.is_none()) || (dp_raw >= 0 && dp_dan.is_none()) {
+            return None;
+        }
+
+        Some(Self {
+            dj_name: dj_name.to_string(),
+            sp_dan,
+            dp_dan,
+            play_count,
+        })
+    }
+
+    /// Whether `name` is a plausible DJ name (1-6 uppercase letters, digits or symbols)
+    pub fn is_valid_dj_name(name: &str) -> bool {
+        !name.trim().is_empty()
+            && name.len() <= Self::MAX_DJ_NAME_LEN
+            && name.chars().all(|c| {
+                c.is_ascii_uppercase() || c.is_ascii_digit() || Self::DJ_NAME_SYMBOLS.contains(c)
+            })
+    }
+
+    /// One-line summary (e.g. `DJ NAME  SP 皆伝 / DP 十段  1234 plays`)
+    pub fn summary(&self) -> String {
+        let dan = |d: Option<DanRank>| d.map(|d| d.display_name()).unwrap_or("-");
+        format!(
+            "DJ {}  SP {} / DP {}  {} plays",
+            self.dj_name,
+            dan(self.sp_dan),
+            dan(self.dp_dan),
+            self.play_count
+        )
+    }
+}
+
+/// Read the player profile block
+pub fn read_profile<R: ReadMemory + ?Sized>(reader: &R, profile_addr: u64) -> Result<ProfileInfo> {
+    let bytes = reader.read_bytes(profile_addr, ProfileInfo::MEMORY_SIZE)?;
+    ProfileInfo::from_bytes(&bytes).ok_or_else(|| {
+        crate::error::Error::InvalidOffset(format!("No valid profile data at 0x{:X}", profile_addr))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile_bytes(name: &str, sp: i32, dp: i32, plays: u32) -> Vec<u8> {
+        let mut bytes = vec![0u8; ProfileInfo::MEMORY_SIZE];
+        bytes[..name.len()].copy_from_slice(name.as_bytes());
+        bytes[0x10..0x14].copy_from_slice(&sp.to_le_bytes());
+        bytes[0x14..0x18].copy_from_slice(&dp.to_le_bytes());
+        bytes[0x18..0x1C].copy_from_slice(&plays.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let profile = ProfileInfo::from_bytes(&profile_bytes("DJ-AB", 18, -1, 1234)).unwrap();
+        assert_eq!(profile.dj_name, "DJ-AB");
+        assert_eq!(profile.sp_dan, Some(DanRank::Kaiden));
+        assert_eq!(profile.dp_dan, None);
+        assert_eq!(profile.play_count, 1234);
+        assert_eq!(profile.summary(), "DJ DJ-AB  SP 皆伝 / DP -  1234 plays");
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_invalid() {
+        // Lowercase / too long names
+        assert!(ProfileInfo::from_bytes(&profile_bytes("abc", 0, 0, 0)).is_none());
+        assert!(ProfileInfo::from_bytes(&profile_bytes("ABCDEFG", 0, 0, 0)).is_none());
+        // Empty name
+        assert!(ProfileInfo::from_bytes(&profile_bytes("", 0, 0, 0)).is_none());
+        // Unknown dan value
+        assert!(ProfileInfo::from_bytes(&profile_bytes("ABC", 19, 0, 0)).is_none());
+    }
+
+    #[test]
+    fn test_dan_rank_display() {
+        assert_eq!(DanRank::Kyu7.to_string(), "七級");
+        assert_eq!(DanRank::Dan10.to_string(), "十段");
+        assert_eq!(DanRank::from_u8(17), Some(DanRank::Chuuden));
+    }
+}
@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
-use strum::{Display, IntoStaticStr};
+use strum::{Display, EnumString, IntoStaticStr};
 use thiserror::Error;
 use tracing::warn;
 
-use crate::play::PlayType;
+use crate::play::{LaneCover, PlayType};
+use crate::process::view::{FromBytes, field};
 
 /// Error for invalid enum value conversion
 #[derive(Debug, Error)]
@@ -29,6 +30,9 @@ pub struct Settings {
     pub flip: bool,
     pub battle: bool,
     pub h_ran: bool,
+    /// Lane covers and green number (when the lane cover offset is known)
+    #[serde(default)]
+    pub lane_cover: Option<LaneCover>,
 }
 
 /// Raw settings values read directly from memory
@@ -44,6 +48,54 @@ pub struct RawSettings {
     pub h_ran: i32,
 }
 
+/// Option words per side (style at word 0 through H-RAN at word 9)
+const SIDE_WORDS: usize = 10;
+
+/// PlaySettings block as laid out in memory, covering both sides
+///
+/// Word layout per side: 0 style, 2 assist, 4 range, 9 H-RAN. Flip (3),
+/// battle (8) and the DP right-side style (5) are only read from the P1 side.
+#[derive(Debug, Clone, Default)]
+pub struct SettingsBlock {
+    p1: [i32; SIDE_WORDS],
+    p2: [i32; SIDE_WORDS],
+}
+
+impl FromBytes for SettingsBlock {
+    const SIZE: usize = Settings::P2_OFFSET as usize + SIDE_WORDS * Settings::WORD_SIZE as usize;
+
+    fn from_le_slice(bytes: &[u8]) -> Self {
+        Self {
+            p1: field(bytes, 0),
+            p2: field(bytes, Settings::P2_OFFSET as usize),
+        }
+    }
+}
+
+impl SettingsBlock {
+    /// Select the option words that apply to `play_type`
+    pub fn to_raw(&self, play_type: PlayType) -> RawSettings {
+        let side = match play_type {
+            PlayType::P2 => &self.p2,
+            PlayType::P1 | PlayType::Dp => &self.p1,
+        };
+        RawSettings {
+            play_type,
+            style: side[0],
+            style2: if play_type == PlayType::Dp {
+                self.p1[5]
+            } else {
+                0
+            },
+            assist: side[2],
+            range: side[4],
+            flip: self.p1[3],
+            battle: self.p1[8],
+            h_ran: side[9],
+        }
+    }
+}
+
 impl Settings {
     /// P2 settings offset (4 * 15 = 60 bytes)
     pub const P2_OFFSET: u64 = 60;
@@ -86,8 +138,46 @@ impl Settings {
             flip: raw.flip == 1,
             battle: raw.battle == 1,
             h_ran: raw.h_ran == 1,
+            lane_cover: None,
         }
     }
+
+    /// SUDDEN+ position, when known and shown by the range option
+    pub fn sudden(&self) -> Option<u16> {
+        self.lane_cover?.sudden_if(self.range)
+    }
+
+    /// HIDDEN+ position, when known and shown by the range option
+    pub fn hidden(&self) -> Option<u16> {
+        self.lane_cover?.hidden_if(self.range)
+    }
+
+    /// LIFT height, when known and shown by the range option
+    pub fn lift(&self) -> Option<u16> {
+        self.lane_cover?.lift_if(self.range)
+    }
+
+    /// Green number, when known
+    pub fn green_number(&self) -> Option<u16> {
+        self.lane_cover.map(|cover| cover.green_number)
+    }
+
+    /// Shown covers and green number, e.g. `SUD+ 250 / LIFT 120 / GN 290`
+    ///
+    /// `None` when the lane covers weren't read.
+    pub fn lane_cover_summary(&self) -> Option<String> {
+        let green_number = self.green_number()?;
+        let parts: Vec<String> = [
+            ("SUD+", self.sudden()),
+            ("HID+", self.hidden()),
+            ("LIFT", self.lift()),
+            ("GN", Some(green_number)),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| Some(format!("{} {}", label, value?)))
+        .collect();
+        Some(parts.join(" / "))
+    }
 }
 
 #[derive(
@@ -178,7 +268,17 @@ impl AssistType {
 }
 
 #[derive(
-    Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, IntoStaticStr, Display,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+    IntoStaticStr,
+    Display,
+    EnumString,
 )]
 #[repr(i32)]
 pub enum RangeType {
@@ -223,6 +323,28 @@ impl RangeType {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_settings_block_to_raw() {
+        let mut bytes = vec![0u8; SettingsBlock::SIZE];
+        let mut put = |offset: usize, value: i32| {
+            bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+        put(0, 1); // P1 style
+        put(3 * 4, 1); // flip
+        put(5 * 4, 4); // DP right style
+        put(8 * 4, 1); // battle
+        put(Settings::P2_OFFSET as usize, 2); // P2 style
+        put(Settings::P2_OFFSET as usize + 9 * 4, 1); // P2 H-RAN
+        let block = SettingsBlock::from_le_slice(&bytes);
+
+        let p2 = block.to_raw(PlayType::P2);
+        assert_eq!((p2.style, p2.style2, p2.h_ran), (2, 0, 1));
+        assert_eq!((p2.flip, p2.battle), (1, 1));
+
+        let dp = block.to_raw(PlayType::Dp);
+        assert_eq!((dp.style, dp.style2, dp.h_ran), (1, 4, 0));
+    }
+
     #[test]
     fn test_style_try_from_valid() {
         assert_eq!(Style::try_from(0).unwrap(), Style::Off);
@@ -321,6 +443,43 @@ mod tests {
         assert_eq!(settings.range, RangeType::Off);
     }
 
+    #[test]
+    fn test_lane_cover_accessors() {
+        let mut settings = Settings {
+            range: RangeType::SuddenPlus,
+            ..Settings::default()
+        };
+        assert_eq!(settings.green_number(), None);
+        assert_eq!(settings.lane_cover_summary(), None);
+
+        settings.lane_cover = Some(LaneCover {
+            sudden: 250,
+            hidden: 100,
+            lift: 120,
+            green_number: 290,
+        });
+        assert_eq!(settings.sudden(), Some(250));
+        assert_eq!(settings.hidden(), None);
+        assert_eq!(settings.lift(), None);
+        assert_eq!(settings.green_number(), Some(290));
+        assert_eq!(settings.lane_cover_summary().unwrap(), "SUD+ 250 / GN 290");
+
+        settings.range = RangeType::LiftSud;
+        assert_eq!(
+            settings.lane_cover_summary().unwrap(),
+            "SUD+ 250 / LIFT 120 / GN 290"
+        );
+    }
+
+    #[test]
+    fn test_range_type_from_str() {
+        assert_eq!(
+            "LIFT & SUD+".parse::<RangeType>().unwrap(),
+            RangeType::LiftSud
+        );
+        assert!("SUDDEN".parse::<RangeType>().is_err());
+    }
+
     #[test]
     fn test_invalid_enum_value_error_display() {
         let err = InvalidEnumValueError::new("TestEnum", 42);
@@ -0,0 +1,161 @@
+//! `PlayData` builder for tests
+//!
+//! Starts from a cleared 1000-note SPA 12 with 1500 EX; the grade follows
+//! the EX score unless set.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::chart::{ChartInfo, Difficulty};
+use crate::play::{PlayData, Settings};
+use crate::score::{Grade, Judge, Lamp};
+
+/// Builds a [`PlayData`] from test defaults
+#[derive(Debug, Clone)]
+pub struct PlayBuilder {
+    play: PlayData,
+    grade: Option<Grade>,
+}
+
+impl Default for PlayBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlayBuilder {
+    pub fn new() -> Self {
+        Self {
+            play: PlayData {
+                timestamp: Utc::now(),
+                chart: ChartInfo {
+                    song_id: 1000,
+                    title: Arc::from("Test Song"),
+                    title_english: Arc::from(""),
+                    artist: Arc::from(""),
+                    genre: Arc::from(""),
+                    bpm: Arc::from("150"),
+                    difficulty: Difficulty::SpA,
+                    level: 12,
+                    total_notes: 1000,
+                    unlocked: true,
+                },
+                ex_score: 1500,
+                grade: Grade::NoPlay,
+                lamp: Lamp::Clear,
+                judge: Judge::default(),
+                settings: Settings::default(),
+                data_available: true,
+                bpi: None,
+                failed_at_percent: None,
+            },
+            grade: None,
+        }
+    }
+
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.play.timestamp = timestamp;
+        self
+    }
+
+    pub fn song_id(mut self, song_id: u32) -> Self {
+        self.play.chart.song_id = song_id;
+        self
+    }
+
+    pub fn title(mut self, title: &str) -> Self {
+        self.play.chart.title = Arc::from(title);
+        self
+    }
+
+    pub fn title_english(mut self, title_english: &str) -> Self {
+        self.play.chart.title_english = Arc::from(title_english);
+        self
+    }
+
+    pub fn artist(mut self, artist: &str) -> Self {
+        self.play.chart.artist = Arc::from(artist);
+        self
+    }
+
+    pub fn genre(mut self, genre: &str) -> Self {
+        self.play.chart.genre = Arc::from(genre);
+        self
+    }
+
+    pub fn bpm(mut self, bpm: &str) -> Self {
+        self.play.chart.bpm = Arc::from(bpm);
+        self
+    }
+
+    pub fn difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.play.chart.difficulty = difficulty;
+        self
+    }
+
+    pub fn level(mut self, level: u8) -> Self {
+        self.play.chart.level = level;
+        self
+    }
+
+    pub fn total_notes(mut self, total_notes: u32) -> Self {
+        self.play.chart.total_notes = total_notes;
+        self
+    }
+
+    pub fn ex_score(mut self, ex_score: u32) -> Self {
+        self.play.ex_score = ex_score;
+        self
+    }
+
+    pub fn grade(mut self, grade: Grade) -> Self {
+        self.grade = Some(grade);
+        self
+    }
+
+    pub fn lamp(mut self, lamp: Lamp) -> Self {
+        self.play.lamp = lamp;
+        self
+    }
+
+    /// Judge counts; the EX score is not updated (see [`Self::judge_ex`])
+    pub fn judge(mut self, judge: Judge) -> Self {
+        self.play.judge = judge;
+        self
+    }
+
+    /// Judge counts with the EX score they add up to
+    pub fn judge_ex(self, judge: Judge) -> Self {
+        let ex_score = judge.ex_score();
+        self.judge(judge).ex_score(ex_score)
+    }
+
+    pub fn settings(mut self, settings: Settings) -> Self {
+        self.play.settings = settings;
+        self
+    }
+
+    pub fn data_available(mut self, data_available: bool) -> Self {
+        self.play.data_available = data_available;
+        self
+    }
+
+    pub fn bpi(mut self, bpi: f64) -> Self {
+        self.play.bpi = Some(bpi);
+        self
+    }
+
+    pub fn failed_at_percent(mut self, percent: Option<f64>) -> Self {
+        self.play.failed_at_percent = percent;
+        self
+    }
+
+    pub fn build(self) -> PlayData {
+        let mut play = self.play;
+        play.grade = self
+            .grade
+            .unwrap_or_else(|| PlayData::calculate_grade(play.ex_score, play.chart.total_notes));
+        play
+    }
+}
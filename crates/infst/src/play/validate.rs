@@ -0,0 +1,155 @@
+//! Sanity checks for play results read from memory
+
+use std::fmt;
+
+use super::PlayData;
+
+/// An impossible value in a play result, usually caused by a corrupted read
+/// or stale offsets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayAnomaly {
+    /// EX score above the chart maximum (2 × notes)
+    ExScoreOverMax { ex_score: u32, max: u64 },
+    /// More PGREAT/GREAT/GOOD/BAD judgments than notes
+    ///
+    /// POOR is excluded because it also counts empty poors.
+    JudgedOverNotes { judged: u64, notes: u32 },
+    /// EX score that doesn't match the judge counts
+    ExScoreMismatch { ex_score: u32, from_judge: u64 },
+}
+
+impl fmt::Display for PlayAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ExScoreOverMax { ex_score, max } => {
+                write!(f, "EX score {} exceeds maximum {}", ex_score, max)
+            }
+            Self::JudgedOverNotes { judged, notes } => {
+                write!(f, "{} judgments for {} notes", judged, notes)
+            }
+            Self::ExScoreMismatch {
+                ex_score,
+                from_judge,
+            } => write!(
+                f,
+                "EX score {} does not match judge counts ({})",
+                ex_score, from_judge
+            ),
+        }
+    }
+}
+
+/// What the tracker does with plays that fail [`PlayData::validate`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AnomalyMode {
+    /// Record the play and note the anomaly on the session timeline
+    #[default]
+    Flag,
+    /// Don't record the play (the anomaly is still noted)
+    Drop,
+}
+
+impl PlayData {
+    /// Check the result for values that can't come from a real play
+    ///
+    /// Returns an empty list for plausible results. Note-count checks are
+    /// skipped when the chart's note count is unknown.
+    pub fn validate(&self) -> Vec<PlayAnomaly> {
+        let judge = &self.judge;
+        let notes = self.chart.total_notes;
+        let from_judge = u64::from(judge.pgreat) * 2 + u64::from(judge.great);
+        let mut anomalies = Vec::new();
+
+        if notes > 0 {
+            let max = u64::from(notes) * 2;
+            if u64::from(self.ex_score) > max {
+                anomalies.push(PlayAnomaly::ExScoreOverMax {
+                    ex_score: self.ex_score,
+                    max,
+                });
+            }
+            let judged = [judge.pgreat, judge.great, judge.good, judge.bad]
+                .into_iter()
+                .map(u64::from)
+                .sum();
+            if judged > u64::from(notes) {
+                anomalies.push(PlayAnomaly::JudgedOverNotes { judged, notes });
+            }
+        }
+        if u64::from(self.ex_score) != from_judge {
+            anomalies.push(PlayAnomaly::ExScoreMismatch {
+                ex_score: self.ex_score,
+                from_judge,
+            });
+        }
+        anomalies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::play::test_support::PlayBuilder;
+    use crate::score::{Grade, Judge};
+
+    fn play(pgreat: u32, great: u32, bad: u32, notes: u32) -> PlayData {
+        PlayBuilder::new()
+            .total_notes(notes)
+            .judge_ex(Judge {
+                pgreat,
+                great,
+                bad,
+                poor: 30,
+                ..Judge::default()
+            })
+            .grade(Grade::Aa)
+            .build()
+    }
+
+    #[test]
+    fn test_plausible_play_has_no_anomalies() {
+        // Empty poors may push the total past the note count
+        assert!(play(900, 90, 10, 1000).validate().is_empty());
+        // Unknown note count: only the EX score consistency is checked
+        assert!(play(900, 90, 10, 0).validate().is_empty());
+    }
+
+    #[test]
+    fn test_impossible_counts() {
+        assert_eq!(
+            play(1000, 100, 0, 1000).validate(),
+            vec![
+                PlayAnomaly::ExScoreOverMax {
+                    ex_score: 2100,
+                    max: 2000
+                },
+                PlayAnomaly::JudgedOverNotes {
+                    judged: 1100,
+                    notes: 1000
+                },
+            ]
+        );
+        assert_eq!(
+            play(0, 0, 1001, 1000).validate(),
+            vec![PlayAnomaly::JudgedOverNotes {
+                judged: 1001,
+                notes: 1000
+            }]
+        );
+
+        let mut mismatched = play(900, 90, 10, 1000);
+        mismatched.ex_score = 1000;
+        let anomalies = mismatched.validate();
+        assert_eq!(
+            anomalies,
+            vec![PlayAnomaly::ExScoreMismatch {
+                ex_score: 1000,
+                from_judge: 1890
+            }]
+        );
+        assert_eq!(
+            anomalies[0].to_string(),
+            "EX score 1000 does not match judge counts (1890)"
+        );
+    }
+}
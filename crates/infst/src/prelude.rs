@@ -17,6 +17,7 @@
 //! - Error handling: `Error`, `Result`
 
 // Core application types
+#[cfg(feature = "session")]
 pub use crate::infst::{ApiConfig, GameData, Infst, InfstConfig, InfstConfigBuilder};
 
 // Error handling
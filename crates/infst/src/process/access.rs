@@ -0,0 +1,229 @@
+//! Read throttling and memory access auditing.
+//!
+//! A [`ReadPolicy`] can be attached to a [`MemoryReader`](super::MemoryReader)
+//! to cap the global read rate and record which memory regions are read and
+//! how often, so users can verify and constrain what the tracker touches.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::error::Result;
+
+/// Global read-rate limiter (minimum interval between reads)
+#[derive(Debug)]
+pub struct ReadRateLimiter {
+    min_interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl ReadRateLimiter {
+    /// Allow at most `max_reads_per_sec` reads per second (0 is treated as 1)
+    pub fn new(max_reads_per_sec: u32) -> Self {
+        Self {
+            min_interval: Duration::from_secs(1) / max_reads_per_sec.max(1),
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn min_interval(&self) -> Duration {
+        self.min_interval
+    }
+
+    /// Block until the next read is allowed
+    pub fn acquire(&self) {
+        let wait = {
+            let mut next = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let slot = (*next).max(now);
+            *next = slot + self.min_interval;
+            slot - now
+        };
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+/// Read statistics for one audited region
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RegionStats {
+    pub reads: u64,
+    pub bytes: u64,
+}
+
+/// One region in an [`AuditReport`]
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRegion {
+    /// Region start address (aligned to [`AccessAudit::REGION_SIZE`])
+    pub start: String,
+    pub reads: u64,
+    pub bytes: u64,
+    pub reads_per_sec: f64,
+}
+
+/// Summary of recorded memory accesses
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditReport {
+    pub duration_secs: f64,
+    pub total_reads: u64,
+    pub total_bytes: u64,
+    /// Regions ordered by address
+    pub regions: Vec<AuditRegion>,
+}
+
+/// Audit log of memory access patterns, grouped by region
+#[derive(Debug)]
+pub struct AccessAudit {
+    started: Instant,
+    regions: Mutex<BTreeMap<u64, RegionStats>>,
+}
+
+impl Default for AccessAudit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AccessAudit {
+    /// Granularity of audited regions (one page)
+    pub const REGION_SIZE: u64 = 0x1000;
+
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            regions: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Record a read of `size` bytes at `address`
+    pub fn record(&self, address: u64, size: usize) {
+        let start = address & !(Self::REGION_SIZE - 1);
+        let mut regions = self.regions.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = regions.entry(start).or_default();
+        stats.reads += 1;
+        stats.bytes += size as u64;
+    }
+
+    /// Per-region statistics keyed by region start address
+    pub fn snapshot(&self) -> BTreeMap<u64, RegionStats> {
+        self.regions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn report(&self) -> AuditReport {
+        let duration_secs = self.started.elapsed().as_secs_f64();
+        let snapshot = self.snapshot();
+        let regions = snapshot
+            .iter()
+            .map(|(&start, stats)| AuditRegion {
+                start: format!("0x{:X}", start),
+                reads: stats.reads,
+                bytes: stats.bytes,
+                reads_per_sec: if duration_secs > 0.0 {
+                    stats.reads as f64 / duration_secs
+                } else {
+                    0.0
+                },
+            })
+            .collect();
+        AuditReport {
+            duration_secs,
+            total_reads: snapshot.values().map(|s| s.reads).sum(),
+            total_bytes: snapshot.values().map(|s| s.bytes).sum(),
+            regions,
+        }
+    }
+
+    /// Write the report as JSON
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.report())?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+/// Throttling and auditing applied to every read of a [`MemoryReader`](super::MemoryReader)
+#[derive(Debug, Default)]
+pub struct ReadPolicy {
+    limiter: Option<ReadRateLimiter>,
+    audit: Option<AccessAudit>,
+}
+
+impl ReadPolicy {
+    pub fn new(max_reads_per_sec: Option<u32>, audit: bool) -> Self {
+        Self {
+            limiter: max_reads_per_sec.map(ReadRateLimiter::new),
+            audit: audit.then(AccessAudit::new),
+        }
+    }
+
+    /// Whether the policy neither throttles nor audits
+    pub fn is_noop(&self) -> bool {
+        self.limiter.is_none() && self.audit.is_none()
+    }
+
+    pub fn audit(&self) -> Option<&AccessAudit> {
+        self.audit.as_ref()
+    }
+
+    /// Called before each read
+    pub fn before_read(&self, address: u64, size: usize) {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire();
+        }
+        if let Some(audit) = &self.audit {
+            audit.record(address, size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_groups_by_region() {
+        let audit = AccessAudit::new();
+        audit.record(0x1000_0010, 4);
+        audit.record(0x1000_0FFC, 4);
+        audit.record(0x1000_1000, 16);
+
+        let snapshot = audit.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[&0x1000_0000], RegionStats { reads: 2, bytes: 8 });
+
+        let report = audit.report();
+        assert_eq!(report.total_reads, 3);
+        assert_eq!(report.total_bytes, 24);
+        assert_eq!(report.regions[1].start, "0x10001000");
+    }
+
+    #[test]
+    fn test_rate_limiter_spaces_reads() {
+        let limiter = ReadRateLimiter::new(100);
+        assert_eq!(limiter.min_interval(), Duration::from_millis(10));
+
+        let start = Instant::now();
+        for _ in 0..4 {
+            limiter.acquire();
+        }
+        // First read is immediate, the remaining three wait one interval each
+        assert!(start.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[test]
+    fn test_policy_records_reads() {
+        let policy = ReadPolicy::new(None, true);
+        assert!(!policy.is_noop());
+        policy.before_read(0x2000, 8);
+        assert_eq!(policy.audit().unwrap().report().total_reads, 1);
+
+        assert!(ReadPolicy::default().is_noop());
+    }
+}
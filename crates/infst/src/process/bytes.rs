@@ -10,6 +10,7 @@ use encoding_rs::SHIFT_JIS;
 use tracing::debug;
 
 use crate::error::{Error, Result};
+use crate::process::view::FromBytes;
 
 /// A position-tracking byte reader for parsing binary data structures.
 ///
@@ -224,6 +225,16 @@ impl<'a> ByteBuffer<'a> {
             bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
         ]))
     }
+
+    /// Decodes a fixed-layout structure at the specified offset without advancing position.
+    pub fn read_struct_at<T: FromBytes>(&self, offset: usize) -> Result<T> {
+        Ok(T::from_le_slice(self.slice_at(offset, T::SIZE)?))
+    }
+
+    /// Decodes a fixed-layout structure and advances the position.
+    pub fn read_struct<T: FromBytes>(&mut self) -> Result<T> {
+        Ok(T::from_le_slice(self.read_bytes(T::SIZE)?))
+    }
 }
 
 /// Decodes Shift-JIS bytes to `Arc<str>`, removing null terminators.
@@ -303,6 +314,17 @@ mod tests {
         assert_eq!(buf.position(), 0); // Position unchanged
     }
 
+    #[test]
+    fn test_byte_buffer_read_struct() {
+        let data = [0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x03];
+        let mut buf = ByteBuffer::new(&data);
+
+        assert_eq!(buf.read_struct_at::<u32>(4).unwrap(), 2);
+        assert_eq!(buf.read_struct::<[u32; 2]>().unwrap(), [1, 2]);
+        assert_eq!(buf.position(), 8);
+        assert!(buf.read_struct::<u32>().is_err());
+    }
+
     #[test]
     fn test_byte_buffer_skip() {
         let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
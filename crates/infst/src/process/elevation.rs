@@ -0,0 +1,177 @@
+//! Privilege diagnostics — UAC elevation checks and re-launch as administrator.
+
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::{CloseHandle, HANDLE};
+#[cfg(target_os = "windows")]
+use windows::Win32::Security::{GetTokenInformation, TOKEN_ELEVATION, TOKEN_QUERY, TokenElevation};
+#[cfg(target_os = "windows")]
+use windows::Win32::System::Threading::{
+    GetCurrentProcess, OpenProcess, OpenProcessToken, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+
+/// Whether infst itself is running elevated (as administrator)
+#[cfg(target_os = "windows")]
+pub fn is_elevated() -> bool {
+    // SAFETY: GetCurrentProcess returns a pseudo handle that doesn't need closing.
+    token_elevated(unsafe { GetCurrentProcess() }).unwrap_or(false)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn is_elevated() -> bool {
+    false
+}
+
+/// Whether the process `pid` is running elevated
+///
+/// Returns `None` if the process can't be queried.
+#[cfg(target_os = "windows")]
+pub fn is_process_elevated(pid: u32) -> Option<bool> {
+    // SAFETY: OpenProcess with PROCESS_QUERY_LIMITED_INFORMATION only allows querying;
+    // the handle is closed before returning.
+    let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) }.ok()?;
+    let elevated = token_elevated(handle);
+    // SAFETY: handle is a valid handle from OpenProcess that has not been closed yet
+    let _ = unsafe { CloseHandle(handle) };
+    elevated
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn is_process_elevated(_pid: u32) -> Option<bool> {
+    None
+}
+
+#[cfg(target_os = "windows")]
+fn token_elevated(process: HANDLE) -> Option<bool> {
+    let mut token = HANDLE::default();
+    // SAFETY: process is a valid process handle; token receives a handle closed below.
+    unsafe { OpenProcessToken(process, TOKEN_QUERY, &mut token) }.ok()?;
+
+    let mut elevation = TOKEN_ELEVATION::default();
+    let mut returned = 0u32;
+    // SAFETY: elevation is a properly sized TOKEN_ELEVATION buffer for TokenElevation.
+    let result = unsafe {
+        GetTokenInformation(
+            token,
+            TokenElevation,
+            Some(&mut elevation as *mut TOKEN_ELEVATION as *mut std::ffi::c_void),
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut returned,
+        )
+    };
+    // SAFETY: token is a valid handle from OpenProcessToken
+    let _ = unsafe { CloseHandle(token) };
+
+    result.ok()?;
+    Some(elevation.TokenIsElevated != 0)
+}
+
+/// Re-launch the current executable as administrator with the same arguments
+///
+/// Shows the UAC prompt. On success the caller should exit; the elevated
+/// instance continues independently.
+#[cfg(target_os = "windows")]
+pub fn relaunch_as_admin() -> anyhow::Result<()> {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+    use windows::core::HSTRING;
+
+    let exe = std::env::current_exe()
+        .map_err(|e| anyhow::anyhow!("Failed to get current executable path: {e}"))?;
+    let params = quote_args(std::env::args().skip(1));
+
+    // SAFETY: All string parameters are valid null-terminated HSTRINGs alive for the call.
+    let result = unsafe {
+        ShellExecuteW(
+            HWND::default(),
+            &HSTRING::from("runas"),
+            &HSTRING::from(exe.as_os_str()),
+            &HSTRING::from(params),
+            None,
+            SW_SHOWNORMAL,
+        )
+    };
+
+    // Values <= 32 indicate an error (including the user declining the UAC prompt)
+    if result.0 as isize <= 32 {
+        anyhow::bail!(
+            "Failed to re-launch as administrator (code {})",
+            result.0 as isize
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn relaunch_as_admin() -> anyhow::Result<()> {
+    anyhow::bail!("Re-launching as administrator is only supported on Windows")
+}
+
+/// Join arguments into a Windows command line, quoting where needed
+///
+/// Follows the `CommandLineToArgvW` rules: inside quotes, backslashes are
+/// literal unless they precede a quote, so runs of backslashes before an
+/// embedded quote or the closing quote are doubled.
+pub fn quote_args<I, S>(args: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    args.into_iter()
+        .map(|arg| quote_arg(arg.as_ref()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '\n', '\x0B', '"']) {
+        return arg.to_string();
+    }
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+                quoted.push(c);
+                backslashes = 0;
+            }
+            _ => {
+                quoted.extend(std::iter::repeat_n('\\', backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_args() {
+        assert_eq!(quote_args(["status", "--pid", "1234"]), "status --pid 1234");
+        assert_eq!(
+            quote_args(["--offsets-file", "C:\\My Files\\offsets.txt", ""]),
+            "--offsets-file \"C:\\My Files\\offsets.txt\" \"\""
+        );
+        assert_eq!(quote_args(["say \"hi\""]), "\"say \\\"hi\\\"\"");
+    }
+
+    #[test]
+    fn test_quote_args_backslashes() {
+        // Backslashes not followed by a quote stay as they are
+        assert_eq!(quote_args(["C:\\dir\\"]), "C:\\dir\\");
+        // Trailing backslashes are doubled before the closing quote
+        assert_eq!(quote_args(["C:\\My Dir\\"]), "\"C:\\My Dir\\\\\"");
+        // Backslashes before an embedded quote are doubled, plus one for the quote
+        assert_eq!(quote_args(["a\\\"b c"]), "\"a\\\\\\\"b c\"");
+        assert_eq!(quote_args(["a\\b c"]), "\"a\\b c\"");
+    }
+}
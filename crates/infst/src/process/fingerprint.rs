@@ -0,0 +1,101 @@
+//! Game module fingerprinting
+//!
+//! Identifies the loaded game executable by its image base, `SizeOfImage`
+//! and the PE header `TimeDateStamp`, so the tracker can notice when the
+//! game updates itself while a session is running.
+
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::process::ReadMemory;
+
+/// Offset of `e_lfanew` (file offset of the PE header) in the DOS header
+const DOS_E_LFANEW: u64 = 0x3C;
+/// "PE\0\0"
+const PE_SIGNATURE: u32 = 0x0000_4550;
+/// Offset of `TimeDateStamp` from the PE signature
+const PE_TIME_DATE_STAMP: u64 = 0x08;
+/// Offset of `SizeOfImage` from the PE signature (signature + file header + 0x38)
+const PE_SIZE_OF_IMAGE: u64 = 0x50;
+
+/// Identity of the loaded game module
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModuleFingerprint {
+    pub base_address: u64,
+    /// `SizeOfImage` from the PE optional header
+    pub size: u32,
+    /// Link timestamp from the PE file header
+    pub timestamp: u32,
+}
+
+impl ModuleFingerprint {
+    /// Read the fingerprint from the PE headers of the module at `base_address`
+    pub fn read<R: ReadMemory + ?Sized>(reader: &R, base_address: u64) -> Result<Self> {
+        let e_lfanew = reader.read_u32(base_address + DOS_E_LFANEW)? as u64;
+        let pe_header = base_address + e_lfanew;
+        let signature = reader.read_u32(pe_header)?;
+        if signature != PE_SIGNATURE {
+            return Err(Error::MemoryReadFailed {
+                address: pe_header,
+                message: format!("bad PE signature 0x{:08X}", signature),
+            });
+        }
+        Ok(Self {
+            base_address,
+            size: reader.read_u32(pe_header + PE_SIZE_OF_IMAGE)?,
+            timestamp: reader.read_u32(pe_header + PE_TIME_DATE_STAMP)?,
+        })
+    }
+}
+
+impl fmt::Display for ModuleFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "base=0x{:X} size=0x{:X} timestamp={}",
+            self.base_address, self.size, self.timestamp
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::MockMemoryBuilder;
+
+    fn pe_image(base: u64, size: u32, timestamp: u32) -> crate::process::MockMemoryReader {
+        MockMemoryBuilder::new()
+            .base(base)
+            .with_size(0x200)
+            .write_u32(0x3C, 0x80)
+            .write_u32(0x80, PE_SIGNATURE)
+            .write_u32(0x88, timestamp)
+            .write_u32(0xD0, size)
+            .build()
+    }
+
+    #[test]
+    fn test_read_fingerprint() {
+        let reader = pe_image(0x1_4000_0000, 0x0123_4000, 0x6789_ABCD);
+        let fingerprint = ModuleFingerprint::read(&reader, 0x1_4000_0000).unwrap();
+        assert_eq!(fingerprint.size, 0x0123_4000);
+        assert_eq!(fingerprint.timestamp, 0x6789_ABCD);
+        assert_eq!(
+            fingerprint.to_string(),
+            "base=0x140000000 size=0x1234000 timestamp=1737075661"
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_changes_with_update() {
+        let old = ModuleFingerprint::read(&pe_image(0x1000, 0x5000, 1), 0x1000).unwrap();
+        let new = ModuleFingerprint::read(&pe_image(0x1000, 0x5000, 2), 0x1000).unwrap();
+        assert_ne!(old, new);
+    }
+
+    #[test]
+    fn test_bad_signature() {
+        let reader = MockMemoryBuilder::new().with_size(0x200).build();
+        assert!(ModuleFingerprint::read(&reader, 0).is_err());
+    }
+}
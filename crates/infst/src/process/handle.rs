@@ -1,17 +1,29 @@
 #![cfg_attr(not(target_os = "windows"), allow(dead_code))]
 
+use std::sync::Arc;
+
 use crate::error::{Error, Result};
-use crate::process::provider::ProcessInfo;
+use crate::process::provider::{
+    DEFAULT_PROCESS_NAME, GameProcess, ProcessCandidate, ProcessInfo, ProcessMatcher,
+    ProcessProvider, ProcessSelection,
+};
+use crate::process::{MemoryReader, ReadPolicy};
 
 #[cfg(target_os = "windows")]
 use tracing::warn;
 
 #[cfg(target_os = "windows")]
-use std::ffi::OsString;
+use std::ffi::{OsString, c_void};
 #[cfg(target_os = "windows")]
 use std::os::windows::ffi::OsStringExt;
 #[cfg(target_os = "windows")]
-use windows::Win32::Foundation::{CloseHandle, HANDLE};
+use std::sync::Mutex;
+#[cfg(target_os = "windows")]
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::{
+    BOOL, CloseHandle, E_ACCESSDENIED, FILETIME, HANDLE, HWND, LPARAM,
+};
 #[cfg(target_os = "windows")]
 use windows::Win32::System::Diagnostics::ToolHelp::{
     CreateToolhelp32Snapshot, PROCESSENTRY32W, Process32FirstW, Process32NextW, TH32CS_SNAPPROCESS,
@@ -22,19 +34,48 @@ use windows::Win32::System::ProcessStatus::{
 };
 #[cfg(target_os = "windows")]
 use windows::Win32::System::Threading::{
-    GetExitCodeProcess, OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ,
+    GetExitCodeProcess, GetProcessTimes, OpenProcess, PROCESS_NAME_WIN32,
+    PROCESS_QUERY_INFORMATION, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_VM_READ,
+    QueryFullProcessImageNameW,
 };
+#[cfg(target_os = "windows")]
+use windows::Win32::UI::WindowsAndMessaging::{
+    EnumWindows, GetWindowTextW, GetWindowThreadProcessId, IsWindowVisible,
+};
+#[cfg(target_os = "windows")]
+use windows::core::PWSTR;
 
-const PROCESS_NAME: &str = "bm2dx.exe";
+const PROCESS_NAME: &str = DEFAULT_PROCESS_NAME;
 
 #[cfg(target_os = "windows")]
 pub struct ProcessHandle {
-    handle: HANDLE,
+    /// Raw `HANDLE`; swapped by [`ProcessHandle::refresh`]
+    handle: AtomicPtr<c_void>,
+    /// Calls of [`ProcessHandle::with_handle`] in progress
+    readers: AtomicUsize,
+    /// Handles replaced by [`ProcessHandle::refresh`], closed once no call
+    /// that may have picked them up is still running
+    retired: Mutex<Vec<*mut c_void>>,
     pub pid: u32,
     pub base_address: u64,
     pub module_size: u32,
 }
 
+// SAFETY: The process handles are only used for ReadProcessMemory and query
+// calls, which Windows allows from any thread concurrently. Every use goes
+// through `with_handle`, which counts itself in `readers` before loading the
+// handle. `refresh` swaps in a new handle atomically and retires the old one;
+// retired handles are closed only while `readers` is zero (checked under the
+// `retired` lock), or in Drop. A call that loaded a handle before it was
+// swapped out keeps `readers` above zero until it returns, and later calls
+// load the current handle, which is never retired. Every handle is therefore
+// closed exactly once, after its last use. This lets code scans read the
+// game's memory from worker threads.
+#[cfg(target_os = "windows")]
+unsafe impl Send for ProcessHandle {}
+#[cfg(target_os = "windows")]
+unsafe impl Sync for ProcessHandle {}
+
 #[cfg(not(target_os = "windows"))]
 pub struct ProcessHandle {
     pub pid: u32,
@@ -53,32 +94,135 @@ impl ProcessHandle {
         Self::open(pid)
     }
 
-    pub fn open(pid: u32) -> Result<Self> {
-        // SAFETY: OpenProcess is called with valid flags (PROCESS_QUERY_INFORMATION | PROCESS_VM_READ)
-        // and a process ID obtained from CreateToolhelp32Snapshot. The returned handle is managed
-        // by this struct and closed in Drop.
-        let handle = unsafe {
-            OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, pid).map_err(|e| {
-                tracing::debug!("OpenProcess failed for PID {}: {}", pid, e);
-                Error::ProcessOpenFailed(e.to_string())
-            })?
-        };
+    /// Open the game process chosen by `selection` among all running candidates
+    pub fn find_and_open_with(selection: &ProcessSelection) -> Result<Self> {
+        SystemProcessProvider::default().find_process_with(selection)
+    }
+
+    /// Open the process chosen by `selection` among those `matcher` accepts
+    pub fn find_and_open_matching(
+        matcher: &ProcessMatcher,
+        selection: &ProcessSelection,
+    ) -> Result<Self> {
+        SystemProcessProvider::new(matcher.clone()).find_process_with(selection)
+    }
 
+    /// List every running game process with its window title, module path and start time
+    pub fn enumerate() -> Result<Vec<ProcessCandidate>> {
+        Self::enumerate_matching(&ProcessMatcher::default())
+    }
+
+    /// List every running process `matcher` accepts, with its window title,
+    /// module path and start time
+    pub fn enumerate_matching(matcher: &ProcessMatcher) -> Result<Vec<ProcessCandidate>> {
+        let titles = visible_window_titles();
+        let mut candidates = Vec::new();
+        for (pid, exe_name) in list_processes()? {
+            // Cheap checks first: module paths are only queried for processes
+            // whose name and window title already match
+            if !matcher.matches_exe_name(&exe_name) {
+                continue;
+            }
+            let own_titles: Vec<&String> = titles
+                .iter()
+                .filter(|(owner, _)| *owner == pid)
+                .map(|(_, title)| title)
+                .collect();
+            let window_title = own_titles
+                .iter()
+                .find(|title| matcher.matches_window_title(Some(title.as_str())))
+                .or(own_titles.first())
+                .map(|title| title.to_string());
+            if !matcher.matches_window_title(window_title.as_deref()) {
+                continue;
+            }
+            let (module_path, start_time) = query_process_details(pid);
+            let candidate = ProcessCandidate {
+                pid,
+                exe_name,
+                module_path,
+                window_title,
+                start_time,
+            };
+            if matcher.matches(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+
+        if candidates.is_empty() {
+            return Err(Error::ProcessNotFound(format!(
+                "No process matches {}",
+                matcher
+            )));
+        }
+        Ok(candidates)
+    }
+
+    pub fn open(pid: u32) -> Result<Self> {
+        let handle = open_handle(pid)?;
         let (base_address, module_size) = get_module_info(handle).map_err(|e| {
             tracing::debug!("get_module_info failed: {}", e);
+            // SAFETY: handle was just opened and is not stored anywhere
+            let _ = unsafe { CloseHandle(handle) };
             e
         })?;
 
         Ok(Self {
-            handle,
+            handle: AtomicPtr::new(handle.0),
+            readers: AtomicUsize::new(0),
+            retired: Mutex::new(Vec::new()),
             pid,
             base_address,
             module_size,
         })
     }
 
-    pub fn handle(&self) -> HANDLE {
-        self.handle
+    /// Run `f` with the current OS handle
+    ///
+    /// The handle stays open until `f` returns, even if another thread
+    /// refreshes it meanwhile.
+    pub fn with_handle<T>(&self, f: impl FnOnce(HANDLE) -> T) -> T {
+        self.readers.fetch_add(1, Ordering::SeqCst);
+        let _reading = ReadingGuard(self);
+        f(HANDLE(self.handle.load(Ordering::SeqCst)))
+    }
+
+    /// Replace the OS handle with a freshly opened one to the same process
+    ///
+    /// Used when the handle stops granting access while the game runs;
+    /// readers borrowing this handle pick up the new one on their next read.
+    /// The old handle is closed once no concurrent read can still be using
+    /// it: right away when none is running, otherwise when the last one ends.
+    pub fn refresh(&self) -> Result<()> {
+        let fresh = open_handle(self.pid)?;
+        let old = self.handle.swap(fresh.0, Ordering::SeqCst);
+        self.retired
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(old);
+        self.close_retired();
+        Ok(())
+    }
+
+    /// Close the retired handles unless a read is in progress
+    fn close_retired(&self) {
+        let mut retired = self.retired.lock().unwrap_or_else(|e| e.into_inner());
+        if retired.is_empty() || self.readers.load(Ordering::SeqCst) != 0 {
+            return;
+        }
+        for raw in retired.drain(..) {
+            // SAFETY: retired handles are unreachable through `handle`, and no
+            // `with_handle` call that could have loaded them is running
+            unsafe { close_handle(HANDLE(raw)) };
+        }
+    }
+
+    /// Re-query the main module's base address and image size
+    ///
+    /// Unlike the cached `base_address`/`module_size`, this reflects the
+    /// module currently loaded in the process.
+    pub fn query_module_info(&self) -> Result<(u64, u32)> {
+        self.with_handle(get_module_info)
     }
 
     /// Check if the process is still running
@@ -88,13 +232,9 @@ impl ProcessHandle {
         let mut exit_code: u32 = 0;
         // SAFETY: GetExitCodeProcess is called with a valid process handle obtained from OpenProcess.
         // The exit_code variable is properly initialized and passed by mutable reference.
-        unsafe {
-            if GetExitCodeProcess(self.handle, &mut exit_code).is_ok() {
-                exit_code == STILL_ACTIVE
-            } else {
-                false
-            }
-        }
+        let queried =
+            self.with_handle(|handle| unsafe { GetExitCodeProcess(handle, &mut exit_code) });
+        queried.is_ok() && exit_code == STILL_ACTIVE
     }
 }
 
@@ -131,6 +271,39 @@ impl ProcessHandle {
         ))
     }
 
+    pub fn find_and_open_with(_selection: &ProcessSelection) -> Result<Self> {
+        Self::find_and_open()
+    }
+
+    pub fn find_and_open_matching(
+        _matcher: &ProcessMatcher,
+        _selection: &ProcessSelection,
+    ) -> Result<Self> {
+        Self::find_and_open()
+    }
+
+    pub fn enumerate() -> Result<Vec<ProcessCandidate>> {
+        Err(Error::ProcessNotFound(
+            "Windows only: process access not supported on this platform".to_string(),
+        ))
+    }
+
+    pub fn enumerate_matching(_matcher: &ProcessMatcher) -> Result<Vec<ProcessCandidate>> {
+        Self::enumerate()
+    }
+
+    pub fn refresh(&self) -> Result<()> {
+        Err(Error::ProcessNotFound(
+            "Windows only: process access not supported on this platform".to_string(),
+        ))
+    }
+
+    pub fn query_module_info(&self) -> Result<(u64, u32)> {
+        Err(Error::ProcessNotFound(
+            "Windows only: process access not supported on this platform".to_string(),
+        ))
+    }
+
     /// Check if the process is still running (stub for non-Windows)
     pub fn is_alive(&self) -> bool {
         false
@@ -156,19 +329,127 @@ impl ProcessInfo for ProcessHandle {
     }
 }
 
+impl GameProcess for ProcessHandle {
+    type Reader<'a> = MemoryReader<'a>;
+
+    fn reader(&self, policy: &Arc<ReadPolicy>) -> MemoryReader<'_> {
+        MemoryReader::new(self).with_policy(Arc::clone(policy))
+    }
+
+    fn query_module_base(&self) -> Result<u64> {
+        self.query_module_info()
+            .map(|(base_address, _)| base_address)
+    }
+
+    fn reopen(&self) -> Result<Self> {
+        Self::open(self.pid)
+    }
+
+    fn refresh_handle(&self) -> Result<()> {
+        self.refresh()
+    }
+}
+
+/// [`ProcessProvider`] backed by the running system's processes
+///
+/// Processes are matched by `bm2dx.exe` unless another [`ProcessMatcher`] is given.
+#[derive(Debug, Clone, Default)]
+pub struct SystemProcessProvider {
+    matcher: ProcessMatcher,
+}
+
+impl SystemProcessProvider {
+    /// Provider for the processes `matcher` accepts
+    pub fn new(matcher: ProcessMatcher) -> Self {
+        Self { matcher }
+    }
+}
+
+impl ProcessProvider for SystemProcessProvider {
+    type Process = ProcessHandle;
+
+    fn find_process(&self) -> Result<Self::Process> {
+        if self.matcher.is_default() {
+            ProcessHandle::find_and_open()
+        } else {
+            self.find_process_with(&ProcessSelection::First)
+        }
+    }
+
+    fn open_process(&self, pid: u32) -> Result<Self::Process> {
+        ProcessHandle::open(pid)
+    }
+
+    fn enumerate_processes(&self) -> Result<Vec<ProcessCandidate>> {
+        ProcessHandle::enumerate_matching(&self.matcher)
+    }
+}
+
+/// Counts a [`ProcessHandle::with_handle`] call as running until dropped;
+/// the last one to end closes the handles retired meanwhile
+#[cfg(target_os = "windows")]
+struct ReadingGuard<'a>(&'a ProcessHandle);
+
+#[cfg(target_os = "windows")]
+impl Drop for ReadingGuard<'_> {
+    fn drop(&mut self) {
+        if self.0.readers.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.close_retired();
+        }
+    }
+}
+
 #[cfg(target_os = "windows")]
 impl Drop for ProcessHandle {
     fn drop(&mut self) {
-        if !self.handle.is_invalid() {
-            // SAFETY: self.handle is a valid handle obtained from OpenProcess and has not been
-            // closed yet. CloseHandle is safe to call on a valid handle.
-            if let Err(e) = unsafe { CloseHandle(self.handle) } {
-                warn!("Failed to close process handle: {}", e);
-            }
+        let retired = std::mem::take(self.retired.get_mut().unwrap_or_else(|e| e.into_inner()));
+        for raw in retired.into_iter().chain([*self.handle.get_mut()]) {
+            // SAFETY: nothing can borrow the struct (and so read through the
+            // handle) once Drop runs
+            unsafe { close_handle(HANDLE(raw)) };
         }
     }
 }
 
+/// Close a handle owned by a [`ProcessHandle`], logging failures
+///
+/// # Safety
+///
+/// `handle` must come from OpenProcess, not be closed yet and not be used
+/// afterwards.
+#[cfg(target_os = "windows")]
+unsafe fn close_handle(handle: HANDLE) {
+    if handle.is_invalid() {
+        return;
+    }
+    // SAFETY: guaranteed by the caller
+    if let Err(e) = unsafe { CloseHandle(handle) } {
+        warn!("Failed to close process handle: {}", e);
+    }
+}
+
+/// Open `pid` with read and query rights only
+#[cfg(target_os = "windows")]
+fn open_handle(pid: u32) -> Result<HANDLE> {
+    // SAFETY: OpenProcess is called with valid flags (PROCESS_QUERY_INFORMATION | PROCESS_VM_READ)
+    // and a process ID obtained from CreateToolhelp32Snapshot. The caller owns the returned
+    // handle and closes it (ProcessHandle closes the handles it replaced once no read uses them,
+    // and the current one in Drop).
+    unsafe {
+        OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, pid).map_err(|e| {
+            tracing::debug!("OpenProcess failed for PID {}: {}", pid, e);
+            if e.code() == E_ACCESSDENIED {
+                Error::ProcessAccessDenied {
+                    pid,
+                    game_elevated: crate::process::elevation::is_process_elevated(pid),
+                }
+            } else {
+                Error::ProcessOpenFailed(e.to_string())
+            }
+        })
+    }
+}
+
 #[cfg(target_os = "windows")]
 fn find_process_id(name: &str) -> Result<u32> {
     // SAFETY: CreateToolhelp32Snapshot with TH32CS_SNAPPROCESS is safe to call.
@@ -221,6 +502,124 @@ fn find_process_id(name: &str) -> Result<u32> {
     result
 }
 
+/// ID and executable name of every running process
+#[cfg(target_os = "windows")]
+fn list_processes() -> Result<Vec<(u32, String)>> {
+    // SAFETY: CreateToolhelp32Snapshot with TH32CS_SNAPPROCESS is safe to call.
+    // The returned handle is closed at the end of this function.
+    let snapshot = unsafe {
+        CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0)
+            .map_err(|e| Error::ProcessNotFound(e.to_string()))?
+    };
+
+    let mut entry = PROCESSENTRY32W {
+        dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+        ..Default::default()
+    };
+
+    let mut processes = Vec::new();
+    // SAFETY: Process32FirstW and Process32NextW are safe to call with a valid snapshot handle
+    // and properly initialized PROCESSENTRY32W structure (see find_process_id).
+    unsafe {
+        if Process32FirstW(snapshot, &mut entry).is_ok() {
+            loop {
+                let exe_name = OsString::from_wide(
+                    &entry.szExeFile[..entry
+                        .szExeFile
+                        .iter()
+                        .position(|&c| c == 0)
+                        .unwrap_or(entry.szExeFile.len())],
+                );
+                processes.push((entry.th32ProcessID, exe_name.to_string_lossy().into_owned()));
+                if Process32NextW(snapshot, &mut entry).is_err() {
+                    break;
+                }
+            }
+        }
+        let _ = CloseHandle(snapshot);
+    }
+    Ok(processes)
+}
+
+/// Query the module path and start time of a process (best effort)
+#[cfg(target_os = "windows")]
+fn query_process_details(pid: u32) -> (Option<String>, Option<chrono::DateTime<chrono::Utc>>) {
+    // SAFETY: OpenProcess with PROCESS_QUERY_LIMITED_INFORMATION only allows querying;
+    // the handle is closed before returning.
+    let Ok(handle) = (unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) }) else {
+        return (None, None);
+    };
+
+    let mut path_buf = [0u16; 1024];
+    let mut path_len = path_buf.len() as u32;
+    // SAFETY: path_buf is valid for path_len UTF-16 units; the API writes at most that many.
+    let module_path = unsafe {
+        QueryFullProcessImageNameW(
+            handle,
+            PROCESS_NAME_WIN32,
+            PWSTR(path_buf.as_mut_ptr()),
+            &mut path_len,
+        )
+    }
+    .ok()
+    .map(|_| String::from_utf16_lossy(&path_buf[..path_len as usize]));
+
+    let mut creation = FILETIME::default();
+    let mut exit = FILETIME::default();
+    let mut kernel = FILETIME::default();
+    let mut user = FILETIME::default();
+    // SAFETY: All FILETIME out-parameters are valid, initialized structures.
+    let start_time =
+        unsafe { GetProcessTimes(handle, &mut creation, &mut exit, &mut kernel, &mut user) }
+            .ok()
+            .and_then(|_| filetime_to_utc(&creation));
+
+    // SAFETY: handle is a valid handle from OpenProcess that has not been closed yet
+    let _ = unsafe { CloseHandle(handle) };
+    (module_path, start_time)
+}
+
+/// Convert a FILETIME (100ns ticks since 1601-01-01) to UTC
+#[cfg(target_os = "windows")]
+fn filetime_to_utc(time: &FILETIME) -> Option<chrono::DateTime<chrono::Utc>> {
+    const TICKS_PER_SECOND: u64 = 10_000_000;
+    const UNIX_EPOCH_OFFSET_SECS: u64 = 11_644_473_600;
+
+    let ticks = ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64;
+    let secs = (ticks / TICKS_PER_SECOND).checked_sub(UNIX_EPOCH_OFFSET_SECS)?;
+    let nanos = (ticks % TICKS_PER_SECOND) * 100;
+    chrono::DateTime::from_timestamp(secs as i64, nanos as u32)
+}
+
+/// Titles of all visible top-level windows with their owning process IDs
+#[cfg(target_os = "windows")]
+fn visible_window_titles() -> Vec<(u32, String)> {
+    unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        // SAFETY: lparam carries the pointer to the Vec passed to EnumWindows below,
+        // which outlives the enumeration.
+        let titles = unsafe { &mut *(lparam.0 as *mut Vec<(u32, String)>) };
+        // SAFETY: hwnd is a valid window handle supplied by EnumWindows.
+        unsafe {
+            if IsWindowVisible(hwnd).as_bool() {
+                let mut buf = [0u16; 256];
+                let len = GetWindowTextW(hwnd, &mut buf);
+                if len > 0 {
+                    let mut pid = 0u32;
+                    GetWindowThreadProcessId(hwnd, Some(&mut pid as *mut u32));
+                    titles.push((pid, String::from_utf16_lossy(&buf[..len as usize])));
+                }
+            }
+        }
+        BOOL::from(true)
+    }
+
+    let mut titles: Vec<(u32, String)> = Vec::new();
+    // SAFETY: The callback only dereferences lparam as the `titles` Vec, which is
+    // alive for the duration of the call.
+    let _ = unsafe { EnumWindows(Some(collect), LPARAM(&mut titles as *mut _ as isize)) };
+    titles
+}
+
 #[cfg(target_os = "windows")]
 fn get_module_info(handle: HANDLE) -> Result<(u64, u32)> {
     let mut modules = [windows::Win32::Foundation::HMODULE::default(); 1024];
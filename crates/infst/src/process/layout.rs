@@ -18,6 +18,22 @@
 //! - PlaySettings + PlayData ≈ 0x2A0
 //! - JudgeData + CurrentSong ≈ 0x1E4
 
+/// How a known field's bytes are rendered in memory diffs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(feature = "debug-tools"), allow(dead_code))]
+pub(crate) enum FieldKind {
+    /// Little-endian i32 words
+    Int,
+    /// Unsigned bytes
+    Bytes,
+    /// Shift-JIS text
+    Text,
+}
+
+/// A known field as (offset, size, name, kind)
+#[cfg_attr(not(feature = "debug-tools"), allow(dead_code))]
+pub(crate) type Field = (usize, usize, &'static str, FieldKind);
+
 /// Memory layout constants for JudgeData structure
 ///
 /// # Structure Layout
@@ -87,6 +103,9 @@ pub mod judge {
     /// P1 (5) + P2 (5) + CB (2) + Fast/Slow (4) + MeasureEnd (2) = 18
     pub const INITIAL_ZERO_SIZE: usize = 72;
 
+    /// Size of the judge counters decoded as `RawJudgeData` (P1/P2 counts through MeasureEnd)
+    pub const BLOCK_SIZE: usize = (P2_MEASURE_END + WORD) as usize;
+
     // Validation constants for during-play detection
     /// Maximum total notes in a song (realistic upper bound)
     pub const MAX_NOTES: i32 = 3000;
@@ -118,6 +137,9 @@ pub mod timing {
     /// Interval between game state checks in the main loop (ms)
     pub const GAME_STATE_POLL_INTERVAL_MS: u64 = 100;
 
+    /// Interval between checks for game module changes (self-update) (ms)
+    pub const MODULE_CHECK_INTERVAL_MS: u64 = 5000;
+
     /// Delay between API requests when syncing scores to avoid server overload (ms)
     pub const SERVER_SYNC_REQUEST_DELAY_MS: u64 = 20;
 }
@@ -2,9 +2,14 @@
 //!
 //! Provides a configurable mock implementation of ReadMemory trait
 //! that reads from an in-memory buffer instead of a real process.
+//!
+//! Buffers can also be loaded from fixture files (see [`format_fixture`]),
+//! so parser tests can run against regions captured from the real game
+//! instead of hand-built layouts.
 
 use crate::error::{Error, Result};
 use crate::process::ReadMemory;
+use crate::util::hex::parse_address;
 
 /// Mock memory reader for testing
 ///
@@ -38,6 +43,94 @@ impl MockMemoryReader {
     }
 }
 
+/// First line of every fixture file
+pub const FIXTURE_HEADER: &str = "# infst memory fixture v1";
+/// Bytes per fixture row
+const FIXTURE_ROW: usize = 32;
+
+/// Encode a memory region in the fixture format
+///
+/// The format is line-based text: the header, `#` comment lines,
+/// `base 0x...` and `size N`, then `OFFSET: HEX` rows of up to 32 bytes.
+/// Rows that are entirely zero are omitted, which keeps sparse structures
+/// such as song entries small and diffable.
+pub fn format_fixture(base: u64, bytes: &[u8], comments: &[&str]) -> String {
+    let mut out = format!("{FIXTURE_HEADER}\n");
+    for comment in comments {
+        out.push_str(&format!("# {comment}\n"));
+    }
+    out.push_str(&format!("base 0x{base:X}\nsize {}\n", bytes.len()));
+    for (i, row) in bytes.chunks(FIXTURE_ROW).enumerate() {
+        if row.iter().all(|&b| b == 0) {
+            continue;
+        }
+        let hex: String = row.iter().map(|b| format!("{b:02x}")).collect();
+        out.push_str(&format!("{:06x}: {hex}\n", i * FIXTURE_ROW));
+    }
+    out
+}
+
+impl MockMemoryReader {
+    /// Load a reader from a fixture written by [`format_fixture`]
+    pub fn from_fixture(text: &str) -> Result<Self> {
+        let err = |line: usize, message: &str| {
+            Error::parse("memory fixture", format!("line {}: {}", line + 1, message))
+        };
+        let mut lines = text.lines().enumerate();
+        if lines.next().map(|(_, l)| l.trim_end()) != Some(FIXTURE_HEADER) {
+            return Err(err(0, "missing fixture header"));
+        }
+
+        let mut base = None;
+        let mut data: Option<Vec<u8>> = None;
+        for (n, line) in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(value) = line.strip_prefix("base ") {
+                base = Some(parse_address(value).map_err(|_| err(n, "invalid base"))?);
+            } else if let Some(value) = line.strip_prefix("size ") {
+                let size = value.trim().parse().map_err(|_| err(n, "invalid size"))?;
+                data = Some(vec![0; size]);
+            } else if let Some((offset, hex)) = line.split_once(':') {
+                let data = data.as_mut().ok_or_else(|| err(n, "row before size"))?;
+                let offset = usize::from_str_radix(offset.trim(), 16)
+                    .map_err(|_| err(n, "invalid offset"))?;
+                let hex = hex.trim();
+                if !hex.is_ascii() {
+                    return Err(err(n, "invalid hex"));
+                }
+                if hex.len() % 2 != 0 {
+                    return Err(err(n, "odd number of hex digits"));
+                }
+                let bytes = (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+                    .collect::<std::result::Result<Vec<u8>, _>>()
+                    .map_err(|_| err(n, "invalid hex"))?;
+                let dest = offset
+                    .checked_add(bytes.len())
+                    .and_then(|end| data.get_mut(offset..end))
+                    .ok_or_else(|| err(n, "row past end of region"))?;
+                dest.copy_from_slice(&bytes);
+            } else {
+                return Err(err(n, "unrecognized line"));
+            }
+        }
+
+        match (base, data) {
+            (Some(base), Some(data)) => Ok(Self { data, base }),
+            _ => Err(err(0, "base and size are required")),
+        }
+    }
+
+    /// Encode this reader's buffer as a fixture
+    pub fn to_fixture(&self, comments: &[&str]) -> String {
+        format_fixture(self.base, &self.data, comments)
+    }
+}
+
 impl ReadMemory for MockMemoryReader {
     fn read_bytes(&self, address: u64, size: usize) -> Result<Vec<u8>> {
         if address < self.base {
@@ -47,7 +140,10 @@ impl ReadMemory for MockMemoryReader {
             });
         }
         let offset = (address - self.base) as usize;
-        if offset + size > self.data.len() {
+        if offset
+            .checked_add(size)
+            .is_none_or(|end| end > self.data.len())
+        {
             return Err(Error::MemoryReadFailed {
                 address,
                 message: format!(
@@ -188,6 +284,38 @@ mod tests {
         assert_eq!(bytes, vec![0x01, 0x02, 0x03, 0x04]);
     }
 
+    #[test]
+    fn test_fixture_round_trip() {
+        let reader = MockMemoryBuilder::new()
+            .base(0x140000000)
+            .with_size(100)
+            .write_u32(4, 0xDEADBEEF)
+            .write_u32(96, 7)
+            .build();
+        let text = reader.to_fixture(&["sample"]);
+        // The all-zero middle row is omitted
+        assert_eq!(text.lines().filter(|l| l.contains(": ")).count(), 2);
+
+        let loaded = MockMemoryReader::from_fixture(&text).unwrap();
+        assert_eq!(loaded.base_address(), 0x140000000);
+        assert_eq!(loaded.len(), 100);
+        assert_eq!(loaded.read_u32(0x140000004).unwrap(), 0xDEADBEEF);
+        assert_eq!(loaded.read_u32(0x140000060).unwrap(), 7);
+    }
+
+    #[test]
+    fn test_fixture_errors() {
+        assert!(MockMemoryReader::from_fixture("base 0x1000\nsize 4\n").is_err());
+        let bad = |body: &str| MockMemoryReader::from_fixture(&format!("{FIXTURE_HEADER}\n{body}"));
+        assert!(bad("base 0x1000\n").is_err());
+        assert!(bad("base 0x1000\nsize 4\n000000: 0102030405\n").is_err());
+        assert!(bad("base 0x1000\nsize 4\n000000: 0g\n").is_err());
+        // Multi-byte characters and offsets near usize::MAX fail without panicking
+        assert!(bad("base 0x1000\nsize 4\n000000: 0é0\n").is_err());
+        assert!(bad("base 0x1000\nsize 4\nffffffffffffffff: 01\n").is_err());
+        assert!(bad("base 0x1000\nsize 4\n000000: 01020304\n").is_ok());
+    }
+
     #[test]
     fn test_mock_reader_out_of_bounds() {
         let data = vec![0x01, 0x02];
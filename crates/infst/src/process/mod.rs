@@ -1,22 +1,36 @@
+pub mod access;
 mod bytes;
 pub mod chunked_reader;
+pub mod elevation;
+mod fingerprint;
 mod handle;
 pub mod launcher;
 pub mod layout;
 pub mod pattern;
 pub mod provider;
+mod read_only;
 mod reader;
+mod snapshot;
+pub mod view;
 
 // Mock memory reader for testing (always available for unit and integration tests)
 #[doc(hidden)]
 pub mod mock;
 
+pub use access::{AccessAudit, AuditReport, ReadPolicy, ReadRateLimiter};
 pub use bytes::{ByteBuffer, decode_shift_jis, decode_shift_jis_to_string};
 pub use chunked_reader::{ChunkedMemoryIterator, DEFAULT_CHUNK_SIZE, MemoryChunk};
+pub use fingerprint::ModuleFingerprint;
 pub use handle::*;
-pub use provider::{ProcessInfo, ProcessProvider};
-pub use reader::{MemoryReader, ReadMemory};
+pub use provider::{
+    GameProcess, ProcessCandidate, ProcessInfo, ProcessMatcher, ProcessProvider, ProcessSelection,
+    with_handle_recovery,
+};
+pub use read_only::ReadOnlyMemory;
+pub use reader::{COALESCE_MAX_GAP, COALESCE_MAX_SPAN, MemoryReader, ReadMemory, read_coalesced};
+pub use snapshot::MemorySnapshot;
+pub use view::FromBytes;
 
 // Re-export mock for convenient access in tests
 #[doc(hidden)]
-pub use mock::{MockMemoryBuilder, MockMemoryReader};
+pub use mock::{FIXTURE_HEADER, MockMemoryBuilder, MockMemoryReader, format_fixture};
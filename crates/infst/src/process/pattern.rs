@@ -1,12 +1,20 @@
 //! Pattern matching utilities for memory searching.
 //!
 //! This module provides functions for searching byte patterns in memory buffers,
-//! with support for wildcard bytes.
+//! with support for wildcard bytes and for several patterns at once.
 //!
-//! Uses the `memchr` crate for SIMD-optimized searching when possible.
+//! Single patterns use the `memchr` crate (SIMD `memmem`); pattern sets use
+//! `aho-corasick`, whose packed SIMD searcher reads the buffer once for all
+//! patterns. Wildcard patterns search for their longest fixed run with
+//! `memmem` and verify the remaining bytes only at those candidates.
+//!
+//! `benches/pattern_scan.rs` compares these against naive `windows()` scans.
 
+use aho_corasick::AhoCorasick;
 use memchr::memmem;
 
+use crate::error::{Error, Result};
+
 /// Find all occurrences of a pattern in a buffer.
 ///
 /// Returns the byte offsets where the pattern starts.
@@ -59,24 +67,10 @@ pub fn find_pattern_with_wildcards(
     pattern: &[u8],
     wildcard_mask: &[bool],
 ) -> Vec<usize> {
-    if pattern.is_empty() || pattern.len() > buffer.len() || pattern.len() != wildcard_mask.len() {
+    if pattern.len() != wildcard_mask.len() {
         return Vec::new();
     }
-
-    let mut results = Vec::new();
-    for i in 0..=(buffer.len() - pattern.len()) {
-        let mut matches = true;
-        for (j, (&byte, &is_wildcard)) in pattern.iter().zip(wildcard_mask.iter()).enumerate() {
-            if !is_wildcard && buffer[i + j] != byte {
-                matches = false;
-                break;
-            }
-        }
-        if matches {
-            results.push(i);
-        }
-    }
-    results
+    find_masked_pattern(buffer, &to_masked(pattern, wildcard_mask))
 }
 
 /// Find the first occurrence of a pattern in a buffer.
@@ -99,23 +93,164 @@ pub fn find_first_pattern_with_wildcards(
     pattern: &[u8],
     wildcard_mask: &[bool],
 ) -> Option<usize> {
-    if pattern.is_empty() || pattern.len() > buffer.len() || pattern.len() != wildcard_mask.len() {
+    if pattern.len() != wildcard_mask.len() {
         return None;
     }
+    find_first_masked_pattern(buffer, &to_masked(pattern, wildcard_mask))
+}
+
+/// Find all occurrences of a pattern whose `None` bytes match any byte.
+///
+/// This is the form produced by [`CodeSignature::pattern_bytes`](crate::offset::CodeSignature::pattern_bytes).
+///
+/// # Example
+///
+/// ```
+/// use infst::process::pattern::find_masked_pattern;
+///
+/// let buffer = [0x48, 0x8D, 0x0D, 0x10, 0x48, 0x8D, 0x15, 0x20];
+/// let matches = find_masked_pattern(&buffer, &[Some(0x48), Some(0x8D), None]);
+/// assert_eq!(matches, vec![0, 4]);
+/// ```
+pub fn find_masked_pattern(buffer: &[u8], pattern: &[Option<u8>]) -> Vec<usize> {
+    let mut matches = Vec::new();
+    for_each_masked_match(buffer, pattern, |pos| {
+        matches.push(pos);
+        true
+    });
+    matches
+}
 
-    for i in 0..=(buffer.len() - pattern.len()) {
-        let mut matches = true;
-        for (j, (&byte, &is_wildcard)) in pattern.iter().zip(wildcard_mask.iter()).enumerate() {
-            if !is_wildcard && buffer[i + j] != byte {
-                matches = false;
-                break;
+/// Find the first occurrence of a pattern whose `None` bytes match any byte.
+pub fn find_first_masked_pattern(buffer: &[u8], pattern: &[Option<u8>]) -> Option<usize> {
+    let mut first = None;
+    for_each_masked_match(buffer, pattern, |pos| {
+        first = Some(pos);
+        false
+    });
+    first
+}
+
+fn to_masked(pattern: &[u8], wildcard_mask: &[bool]) -> Vec<Option<u8>> {
+    pattern
+        .iter()
+        .zip(wildcard_mask)
+        .map(|(&byte, &is_wildcard)| (!is_wildcard).then_some(byte))
+        .collect()
+}
+
+/// Longest run of fixed bytes in a masked pattern as (start, length)
+fn longest_fixed_run(pattern: &[Option<u8>]) -> (usize, usize) {
+    let mut best = (0, 0);
+    let mut run_start = 0;
+    for (i, byte) in pattern.iter().enumerate() {
+        if byte.is_none() {
+            run_start = i + 1;
+        } else if i + 1 - run_start > best.1 {
+            best = (run_start, i + 1 - run_start);
+        }
+    }
+    best
+}
+
+fn masked_matches_at(window: &[u8], pattern: &[Option<u8>]) -> bool {
+    window
+        .iter()
+        .zip(pattern)
+        .all(|(byte, expected)| expected.is_none_or(|value| *byte == value))
+}
+
+/// Call `visit` with each match position in order until it returns `false`
+fn for_each_masked_match(
+    buffer: &[u8],
+    pattern: &[Option<u8>],
+    mut visit: impl FnMut(usize) -> bool,
+) {
+    if pattern.is_empty() || pattern.len() > buffer.len() {
+        return;
+    }
+    let last = buffer.len() - pattern.len();
+
+    let (anchor_start, anchor_len) = longest_fixed_run(pattern);
+    if anchor_len == 0 {
+        // Only wildcards: every position matches
+        for pos in 0..=last {
+            if !visit(pos) {
+                return;
             }
         }
-        if matches {
-            return Some(i);
+        return;
+    }
+
+    // A match of the anchor at `pos` in this slice means the whole pattern
+    // would start at `pos` in the buffer
+    let anchor: Vec<u8> = pattern[anchor_start..anchor_start + anchor_len]
+        .iter()
+        .flatten()
+        .copied()
+        .collect();
+    let haystack = &buffer[anchor_start..last + anchor_start + anchor_len];
+    for pos in memmem::find_iter(haystack, &anchor) {
+        if masked_matches_at(&buffer[pos..pos + pattern.len()], pattern) && !visit(pos) {
+            return;
+        }
+    }
+}
+
+/// A set of byte patterns searched in a single pass
+///
+/// # Example
+///
+/// ```
+/// use infst::process::pattern::MultiPattern;
+///
+/// let patterns = MultiPattern::new(&[&b"5.1.1."[..], &b"5.1.2."[..]]).unwrap();
+/// let matches = patterns.find_all(b"..5.1.2...5.1.1.");
+/// assert_eq!(matches, vec![vec![10], vec![2]]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiPattern {
+    searcher: AhoCorasick,
+}
+
+impl MultiPattern {
+    /// Build a searcher for `patterns` (indices follow the given order)
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P]) -> Result<Self> {
+        let searcher =
+            AhoCorasick::new(patterns).map_err(|e| Error::parse("pattern set", e.to_string()))?;
+        Ok(Self { searcher })
+    }
+
+    /// Number of patterns in the set
+    pub fn len(&self) -> usize {
+        self.searcher.patterns_len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every match as (pattern index, start offset), including overlapping ones
+    ///
+    /// Matches are ordered by end offset. Empty patterns never match.
+    pub fn find_iter<'a>(&'a self, buffer: &'a [u8]) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.searcher
+            .find_overlapping_iter(buffer)
+            .filter(|m| !m.is_empty())
+            .map(|m| (m.pattern().as_usize(), m.start()))
+    }
+
+    /// Start offsets of each pattern's matches, ascending, indexed like the patterns
+    pub fn find_all(&self, buffer: &[u8]) -> Vec<Vec<usize>> {
+        let mut matches = vec![Vec::new(); self.len()];
+        for (index, start) in self.find_iter(buffer) {
+            matches[index].push(start);
+        }
+        for found in &mut matches {
+            found.sort_unstable();
         }
+        matches
     }
-    None
 }
 
 #[cfg(test)]
@@ -207,4 +342,81 @@ mod tests {
         let result = find_first_pattern_with_wildcards(&buffer, &[1, 0, 3], &[false, true, false]);
         assert_eq!(result, Some(0));
     }
+
+    /// Reference implementation the optimized searches must agree with
+    fn naive_masked(buffer: &[u8], pattern: &[Option<u8>]) -> Vec<usize> {
+        if pattern.is_empty() || pattern.len() > buffer.len() {
+            return Vec::new();
+        }
+        buffer
+            .windows(pattern.len())
+            .enumerate()
+            .filter(|(_, window)| masked_matches_at(window, pattern))
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+
+    #[test]
+    fn test_find_masked_pattern_matches_naive_scan() {
+        // Small alphabet so partial matches are frequent
+        let buffer: Vec<u8> = (0u32..4096)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 29) as u8)
+            .collect();
+        let patterns: [&[Option<u8>]; 5] = [
+            &[Some(1), None, Some(3)],
+            &[None, Some(2), Some(2), None],
+            &[Some(0), Some(1), Some(2), None, None, Some(7)],
+            &[None, None, Some(5)],
+            &[Some(4)],
+        ];
+        for pattern in patterns {
+            assert_eq!(
+                find_masked_pattern(&buffer, pattern),
+                naive_masked(&buffer, pattern),
+                "pattern {pattern:?}"
+            );
+            assert_eq!(
+                find_first_masked_pattern(&buffer, pattern),
+                naive_masked(&buffer, pattern).first().copied()
+            );
+        }
+    }
+
+    #[test]
+    fn test_longest_fixed_run() {
+        assert_eq!(
+            longest_fixed_run(&[None, Some(1), Some(2), None, Some(3)]),
+            (1, 2)
+        );
+        assert_eq!(
+            longest_fixed_run(&[Some(1), None, Some(2), Some(3), Some(4)]),
+            (2, 3)
+        );
+        assert_eq!(longest_fixed_run(&[None, None]), (0, 0));
+    }
+
+    #[test]
+    fn test_multi_pattern_finds_overlapping_matches() {
+        let patterns = MultiPattern::new(&[&[1u8, 2, 3][..], &[2, 3][..], &[3, 1][..]]).unwrap();
+        assert_eq!(patterns.len(), 3);
+        let buffer = [1, 2, 3, 1, 2, 3, 9];
+        assert_eq!(
+            patterns.find_all(&buffer),
+            vec![vec![0, 3], vec![1, 4], vec![2]]
+        );
+    }
+
+    #[test]
+    fn test_multi_pattern_matches_single_searches() {
+        let buffer: Vec<u8> = (0u32..8192)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 28) as u8)
+            .collect();
+        let patterns: [&[u8]; 4] = [&[1, 2], &[3, 3, 3], &[0, 15, 1], &[7]];
+        let multi = MultiPattern::new(&patterns).unwrap();
+        let expected: Vec<Vec<usize>> = patterns
+            .iter()
+            .map(|pattern| find_pattern(&buffer, pattern))
+            .collect();
+        assert_eq!(multi.find_all(&buffer), expected);
+    }
 }
@@ -3,7 +3,18 @@
 //! This module provides traits that abstract process discovery and access,
 //! enabling mock implementations for testing without a running game process.
 
-use crate::error::Result;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use regex_automata::meta::Regex;
+use regex_automata::util::syntax;
+
+use tracing::{debug, info};
+
+use crate::error::{Error, Result};
+use crate::process::{ReadMemory, ReadPolicy};
 
 /// Trait for accessing process information.
 ///
@@ -23,6 +34,233 @@ pub trait ProcessInfo {
     fn is_alive(&self) -> bool;
 }
 
+/// Executable name of the game
+pub const DEFAULT_PROCESS_NAME: &str = "bm2dx.exe";
+
+/// Which running processes count as the game.
+///
+/// By default a process matches when its executable is `bm2dx.exe`. Setting
+/// a window title pattern or module path drops the name requirement (for
+/// renamed launchers) unless a name is also given; every criterion that is
+/// set must match.
+#[derive(Debug, Clone)]
+pub struct ProcessMatcher {
+    exe_name: Option<String>,
+    window_title: Option<(String, Regex)>,
+    module_path: Option<String>,
+}
+
+impl Default for ProcessMatcher {
+    fn default() -> Self {
+        Self {
+            exe_name: Some(DEFAULT_PROCESS_NAME.to_string()),
+            window_title: None,
+            module_path: None,
+        }
+    }
+}
+
+impl ProcessMatcher {
+    /// Build a matcher from an executable name, a window title regex
+    /// (case-insensitive) and a full module path; the default matcher when
+    /// all are `None`
+    pub fn new(
+        exe_name: Option<&str>,
+        window_title: Option<&str>,
+        module_path: Option<&str>,
+    ) -> Result<Self> {
+        if exe_name.is_none() && window_title.is_none() && module_path.is_none() {
+            return Ok(Self::default());
+        }
+        let window_title = window_title
+            .map(|pattern| {
+                Regex::builder()
+                    .syntax(syntax::Config::new().case_insensitive(true))
+                    .build(pattern)
+                    .map(|regex| (pattern.to_string(), regex))
+                    .map_err(|e| Error::invalid_config("process window title", e.to_string()))
+            })
+            .transpose()?;
+        Ok(Self {
+            exe_name: exe_name.map(str::to_string),
+            window_title,
+            module_path: module_path.map(normalize_path),
+        })
+    }
+
+    /// Executable name to look for, if matching by name
+    pub fn exe_name(&self) -> Option<&str> {
+        self.exe_name.as_deref()
+    }
+
+    /// Whether this is the default `bm2dx.exe` name match
+    pub fn is_default(&self) -> bool {
+        self.window_title.is_none()
+            && self.module_path.is_none()
+            && self
+                .exe_name
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(DEFAULT_PROCESS_NAME))
+    }
+
+    /// Whether an executable name passes the name criterion
+    pub fn matches_exe_name(&self, exe_name: &str) -> bool {
+        self.exe_name
+            .as_deref()
+            .is_none_or(|name| name.eq_ignore_ascii_case(exe_name))
+    }
+
+    /// Whether a window title passes the title criterion
+    pub fn matches_window_title(&self, title: Option<&str>) -> bool {
+        match &self.window_title {
+            Some((_, regex)) => title.is_some_and(|title| regex.is_match(title)),
+            None => true,
+        }
+    }
+
+    /// Whether a module path passes the path criterion
+    /// (case-insensitive, `/` and `\` are equivalent)
+    pub fn matches_module_path(&self, path: Option<&str>) -> bool {
+        match &self.module_path {
+            Some(expected) => path.is_some_and(|path| normalize_path(path) == *expected),
+            None => true,
+        }
+    }
+
+    /// Whether `candidate` passes every criterion
+    pub fn matches(&self, candidate: &ProcessCandidate) -> bool {
+        self.matches_exe_name(&candidate.exe_name)
+            && self.matches_window_title(candidate.window_title.as_deref())
+            && self.matches_module_path(candidate.module_path.as_deref())
+    }
+}
+
+fn normalize_path(path: &str) -> String {
+    path.trim().replace('/', "\\").to_lowercase()
+}
+
+impl fmt::Display for ProcessMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut criteria = Vec::new();
+        if let Some(name) = &self.exe_name {
+            criteria.push(name.clone());
+        }
+        if let Some((pattern, _)) = &self.window_title {
+            criteria.push(format!("title /{}/", pattern));
+        }
+        if let Some(path) = &self.module_path {
+            criteria.push(format!("path {}", path));
+        }
+        f.write_str(&criteria.join(", "))
+    }
+}
+
+/// A process that matches the game executable name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessCandidate {
+    pub pid: u32,
+    /// Executable file name (e.g. `bm2dx.exe`)
+    pub exe_name: String,
+    /// Full path of the main module, if it could be queried
+    pub module_path: Option<String>,
+    /// Title of the process's main visible window, if any
+    pub window_title: Option<String>,
+    /// Process start time, if it could be queried
+    pub start_time: Option<DateTime<Utc>>,
+}
+
+impl fmt::Display for ProcessCandidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PID {}", self.pid)?;
+        if let Some(title) = &self.window_title {
+            write!(f, "  \"{}\"", title)?;
+        }
+        if let Some(start) = &self.start_time {
+            write!(f, "  started {}", start.format("%Y-%m-%d %H:%M:%S UTC"))?;
+        }
+        write!(
+            f,
+            "  {}",
+            self.module_path.as_deref().unwrap_or(&self.exe_name)
+        )
+    }
+}
+
+/// Rule for choosing one process when several candidates match.
+///
+/// Parsed from `first`, `newest`, `oldest`, `title:<text>` or `path:<text>`
+/// (`title`/`path` match case-insensitive substrings).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ProcessSelection {
+    /// First candidate in enumeration order
+    #[default]
+    First,
+    /// Most recently started candidate
+    Newest,
+    /// Earliest started candidate
+    Oldest,
+    /// Window title contains the text
+    WindowTitle(String),
+    /// Module path contains the text
+    ModulePath(String),
+}
+
+impl ProcessSelection {
+    /// Choose a candidate according to this rule.
+    pub fn select<'a>(&self, candidates: &'a [ProcessCandidate]) -> Option<&'a ProcessCandidate> {
+        let contains = |value: &Option<String>, needle: &str| {
+            value
+                .as_deref()
+                .is_some_and(|v| v.to_lowercase().contains(&needle.to_lowercase()))
+        };
+        match self {
+            Self::First => candidates.first(),
+            // Candidates without a start time sort before those with one
+            Self::Newest => candidates.iter().max_by_key(|c| c.start_time),
+            Self::Oldest => candidates
+                .iter()
+                .filter(|c| c.start_time.is_some())
+                .min_by_key(|c| c.start_time)
+                .or_else(|| candidates.first()),
+            Self::WindowTitle(text) => candidates.iter().find(|c| contains(&c.window_title, text)),
+            Self::ModulePath(text) => candidates.iter().find(|c| contains(&c.module_path, text)),
+        }
+    }
+}
+
+impl FromStr for ProcessSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "first" => return Ok(Self::First),
+            "newest" => return Ok(Self::Newest),
+            "oldest" => return Ok(Self::Oldest),
+            _ => {}
+        }
+        match s.split_once(':') {
+            Some(("title", text)) if !text.is_empty() => Ok(Self::WindowTitle(text.to_string())),
+            Some(("path", text)) if !text.is_empty() => Ok(Self::ModulePath(text.to_string())),
+            _ => Err(format!(
+                "invalid process selection '{}' (expected first, newest, oldest, title:<text> or path:<text>)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ProcessSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::First => write!(f, "first"),
+            Self::Newest => write!(f, "newest"),
+            Self::Oldest => write!(f, "oldest"),
+            Self::WindowTitle(text) => write!(f, "title:{}", text),
+            Self::ModulePath(text) => write!(f, "path:{}", text),
+        }
+    }
+}
+
 /// Trait for finding and opening processes.
 ///
 /// This trait abstracts process discovery, allowing mock implementations
@@ -36,12 +274,83 @@ pub trait ProcessProvider {
 
     /// Open a process by its PID.
     fn open_process(&self, pid: u32) -> Result<Self::Process>;
+
+    /// List every running process that matches the game executable.
+    fn enumerate_processes(&self) -> Result<Vec<ProcessCandidate>>;
+
+    /// Find the game process chosen by `selection` among all candidates and open it.
+    fn find_process_with(&self, selection: &ProcessSelection) -> Result<Self::Process> {
+        let candidates = self.enumerate_processes()?;
+        let candidate = selection.select(&candidates).ok_or_else(|| {
+            Error::ProcessNotFound(format!(
+                "No process matches '{}' ({} candidates)",
+                selection,
+                candidates.len()
+            ))
+        })?;
+        self.open_process(candidate.pid)
+    }
+}
+
+/// A game process the tracking loop can attach to.
+///
+/// Implemented by [`ProcessHandle`](crate::process::ProcessHandle) for the
+/// real game and by the simulated game used in end-to-end tests.
+pub trait GameProcess: ProcessInfo {
+    /// Memory reader borrowing the process.
+    type Reader<'a>: ReadMemory
+    where
+        Self: 'a;
+
+    /// Create a memory reader that applies `policy` (throttling and auditing).
+    fn reader(&self, policy: &Arc<ReadPolicy>) -> Self::Reader<'_>;
+
+    /// Re-query the main module's base address, bypassing cached values.
+    fn query_module_base(&self) -> Result<u64>;
+
+    /// Open a fresh handle to the same process.
+    fn reopen(&self) -> Result<Self>
+    where
+        Self: Sized;
+
+    /// Replace the OS handle in place with a fresh one to the same process.
+    ///
+    /// Unlike [`reopen`](Self::reopen), readers borrowing the process stay
+    /// valid and use the new handle from their next read.
+    fn refresh_handle(&self) -> Result<()>;
+}
+
+/// Run `read`, refreshing the handle of `process` and retrying when it fails
+/// with [`Error::ProcessHandleLost`] while the process is still running.
+///
+/// At most `max_refreshes` refreshes are made; after that (or when a refresh
+/// fails) the access error is returned.
+pub fn with_handle_recovery<P: GameProcess, T>(
+    process: &P,
+    max_refreshes: u32,
+    mut read: impl FnMut() -> Result<T>,
+) -> Result<T> {
+    let mut refreshes = 0;
+    loop {
+        match read() {
+            Err(e @ Error::ProcessHandleLost(_))
+                if refreshes < max_refreshes && process.is_alive() =>
+            {
+                refreshes += 1;
+                if let Err(refresh_error) = process.refresh_handle() {
+                    debug!("Failed to refresh process handle: {}", refresh_error);
+                    return Err(e);
+                }
+                info!("Refreshed process handle after losing access ({})", e);
+            }
+            result => return result,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::error::Error;
 
     /// Mock process info for testing.
     pub struct MockProcessInfo {
@@ -101,6 +410,24 @@ mod tests {
                 })
                 .ok_or_else(|| Error::ProcessNotFound(format!("Mock process {} not found", pid)))
         }
+
+        fn enumerate_processes(&self) -> Result<Vec<ProcessCandidate>> {
+            Ok(self
+                .process
+                .iter()
+                .map(|p| candidate(p.pid, None, None))
+                .collect())
+        }
+    }
+
+    fn candidate(pid: u32, title: Option<&str>, start_secs: Option<i64>) -> ProcessCandidate {
+        ProcessCandidate {
+            pid,
+            exe_name: "bm2dx.exe".to_string(),
+            module_path: Some(format!("C:\\Games\\{}\\bm2dx.exe", pid)),
+            window_title: title.map(str::to_string),
+            start_time: start_secs.and_then(|secs| DateTime::from_timestamp(secs, 0)),
+        }
     }
 
     #[test]
@@ -158,4 +485,195 @@ mod tests {
         let result = provider.open_process(9999);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_mock_provider_find_process_with() {
+        let provider = MockProcessProvider {
+            process: Some(MockProcessInfo {
+                pid: 1234,
+                base_address: 0x140000000,
+                module_size: 0x1000000,
+                alive: true,
+            }),
+        };
+
+        let process = provider
+            .find_process_with(&ProcessSelection::Newest)
+            .unwrap();
+        assert_eq!(process.pid(), 1234);
+
+        let selection = ProcessSelection::WindowTitle("spice".to_string());
+        assert!(provider.find_process_with(&selection).is_err());
+    }
+
+    #[test]
+    fn test_process_selection_select() {
+        let candidates = vec![
+            candidate(100, Some("beatmania IIDX INFINITAS"), Some(2000)),
+            candidate(200, Some("spicetools launcher"), Some(1000)),
+            candidate(300, None, None),
+        ];
+
+        let pick = |s: &str| {
+            s.parse::<ProcessSelection>()
+                .unwrap()
+                .select(&candidates)
+                .map(|c| c.pid)
+        };
+        assert_eq!(pick("first"), Some(100));
+        assert_eq!(pick("newest"), Some(100));
+        assert_eq!(pick("oldest"), Some(200));
+        assert_eq!(pick("title:SPICE"), Some(200));
+        assert_eq!(pick("path:\\300\\"), Some(300));
+        assert_eq!(pick("title:nothing"), None);
+    }
+
+    #[test]
+    fn test_process_selection_parse() {
+        assert_eq!(
+            "title:INFINITAS".parse::<ProcessSelection>(),
+            Ok(ProcessSelection::WindowTitle("INFINITAS".to_string()))
+        );
+        assert!("title:".parse::<ProcessSelection>().is_err());
+        assert!("latest".parse::<ProcessSelection>().is_err());
+        assert_eq!(
+            ProcessSelection::ModulePath("app".into()).to_string(),
+            "path:app"
+        );
+    }
+
+    #[test]
+    fn test_default_matcher_matches_game_exe() {
+        let matcher = ProcessMatcher::default();
+        assert!(matcher.is_default());
+        assert!(matcher.matches(&candidate(1, None, None)));
+        assert!(matcher.matches_exe_name("BM2DX.EXE"));
+        assert!(!matcher.matches_exe_name("launcher.exe"));
+        assert!(ProcessMatcher::new(None, None, None).unwrap().is_default());
+    }
+
+    #[test]
+    fn test_matcher_by_title_and_path() {
+        let renamed = ProcessCandidate {
+            exe_name: "spice64.exe".to_string(),
+            module_path: Some("C:/Games/IIDX/spice64.exe".to_string()),
+            ..candidate(7, Some("beatmania IIDX 32 Pinky Crush"), None)
+        };
+
+        let by_title = ProcessMatcher::new(None, Some(r"beatmania iidx \d+"), None).unwrap();
+        assert!(!by_title.is_default());
+        assert!(by_title.matches(&renamed));
+        assert!(!by_title.matches(&candidate(1, Some("Notepad"), None)));
+        assert!(!by_title.matches(&candidate(1, None, None)));
+
+        let by_path = ProcessMatcher::new(None, None, Some(r"c:\games\iidx\SPICE64.exe")).unwrap();
+        assert!(by_path.matches(&renamed));
+        assert!(!by_path.matches(&candidate(1, None, None)));
+
+        let name_and_title =
+            ProcessMatcher::new(Some("bm2dx.exe"), Some("beatmania"), None).unwrap();
+        assert!(!name_and_title.matches(&renamed));
+        assert_eq!(name_and_title.to_string(), "bm2dx.exe, title /beatmania/");
+
+        assert!(ProcessMatcher::new(None, Some("("), None).is_err());
+    }
+
+    /// Process whose reads fail with a lost handle until refreshed `refreshes_needed` times
+    struct FlakyHandle {
+        alive: bool,
+        refreshes_needed: u32,
+        refreshes: std::cell::Cell<u32>,
+    }
+
+    impl FlakyHandle {
+        fn read(&self) -> Result<u32> {
+            if self.refreshes.get() < self.refreshes_needed {
+                Err(Error::ProcessHandleLost("access denied".to_string()))
+            } else {
+                Ok(42)
+            }
+        }
+    }
+
+    impl ProcessInfo for FlakyHandle {
+        fn pid(&self) -> u32 {
+            1
+        }
+
+        fn base_address(&self) -> u64 {
+            0
+        }
+
+        fn module_size(&self) -> u32 {
+            0
+        }
+
+        fn is_alive(&self) -> bool {
+            self.alive
+        }
+    }
+
+    impl GameProcess for FlakyHandle {
+        type Reader<'a> = crate::process::MockMemoryReader;
+
+        fn reader(&self, _policy: &Arc<ReadPolicy>) -> Self::Reader<'_> {
+            crate::process::MockMemoryReader::new(Vec::new())
+        }
+
+        fn query_module_base(&self) -> Result<u64> {
+            Ok(0)
+        }
+
+        fn reopen(&self) -> Result<Self> {
+            Err(Error::ProcessNotFound("mock".to_string()))
+        }
+
+        fn refresh_handle(&self) -> Result<()> {
+            self.refreshes.set(self.refreshes.get() + 1);
+            Ok(())
+        }
+    }
+
+    fn flaky(alive: bool, refreshes_needed: u32) -> FlakyHandle {
+        FlakyHandle {
+            alive,
+            refreshes_needed,
+            refreshes: std::cell::Cell::new(0),
+        }
+    }
+
+    #[test]
+    fn test_handle_recovery_retries_after_refresh() {
+        let process = flaky(true, 2);
+        assert_eq!(
+            with_handle_recovery(&process, 3, || process.read()).unwrap(),
+            42
+        );
+        assert_eq!(process.refreshes.get(), 2);
+    }
+
+    #[test]
+    fn test_handle_recovery_is_bounded() {
+        let process = flaky(true, 10);
+        let result = with_handle_recovery(&process, 3, || process.read());
+        assert!(matches!(result, Err(Error::ProcessHandleLost(_))));
+        assert_eq!(process.refreshes.get(), 3);
+    }
+
+    #[test]
+    fn test_handle_recovery_skips_exited_process_and_other_errors() {
+        let exited = flaky(false, 1);
+        assert!(with_handle_recovery(&exited, 3, || exited.read()).is_err());
+        assert_eq!(exited.refreshes.get(), 0);
+
+        let process = flaky(true, 0);
+        let result: Result<u32> = with_handle_recovery(&process, 3, || {
+            Err(Error::MemoryReadFailed {
+                address: 0,
+                message: "unmapped".to_string(),
+            })
+        });
+        assert!(matches!(result, Err(Error::MemoryReadFailed { .. })));
+        assert_eq!(process.refreshes.get(), 0);
+    }
 }
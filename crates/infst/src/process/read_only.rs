@@ -0,0 +1,59 @@
+//! Read-only view of game memory
+//!
+//! infst never writes to the game process: handles are opened with
+//! `PROCESS_VM_READ` and query rights only, and no `WriteProcessMemory` (or
+//! `VirtualProtectEx`) binding is used anywhere outside `debug-tools`. The
+//! `read_only_audit` integration test enforces this on the source tree.
+//!
+//! [`ReadOnlyMemory`] makes the same guarantee visible in types: it is the
+//! reader handed to the tracking loop, and it exposes nothing but
+//! [`ReadMemory`]. The wrapped reader (and the process handle behind it)
+//! can't be reached through it.
+
+use super::ReadMemory;
+use crate::error::Result;
+
+/// A memory reader that can only read
+#[derive(Debug)]
+pub struct ReadOnlyMemory<R>(R);
+
+impl<R: ReadMemory> ReadOnlyMemory<R> {
+    /// Wrap `reader`; it is only reachable again through [`ReadMemory`]
+    pub fn new(reader: R) -> Self {
+        Self(reader)
+    }
+}
+
+impl<R: ReadMemory> ReadMemory for ReadOnlyMemory<R> {
+    fn read_bytes(&self, address: u64, size: usize) -> Result<Vec<u8>> {
+        self.0.read_bytes(address, size)
+    }
+
+    fn base_address(&self) -> u64 {
+        self.0.base_address()
+    }
+
+    fn read_many(&self, ranges: &[(u64, usize)]) -> Vec<Result<Vec<u8>>> {
+        self.0.read_many(ranges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::MockMemoryBuilder;
+
+    #[test]
+    fn test_reads_pass_through() {
+        let mock = MockMemoryBuilder::new()
+            .with_size(0x20)
+            .write_i32(0x8, -5)
+            .build();
+        let reader = ReadOnlyMemory::new(mock);
+        assert_eq!(reader.base_address(), 0x1000);
+        assert_eq!(reader.read_i32(0x1008).unwrap(), -5);
+        let many = reader.read_many(&[(0x1000, 4), (0x5000, 4)]);
+        assert!(many[0].is_ok());
+        assert!(many[1].is_err());
+    }
+}
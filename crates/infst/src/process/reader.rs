@@ -1,9 +1,17 @@
 #![cfg_attr(not(target_os = "windows"), allow(dead_code, unused_variables))]
 
+use std::sync::Arc;
+
+use crate::config::watchdog::MAX_HANDLE_REFRESHES;
 use crate::error::{Error, Result};
 use crate::process::ProcessHandle;
+use crate::process::access::ReadPolicy;
 use crate::process::bytes::decode_shift_jis_to_string;
+use crate::process::provider::with_handle_recovery;
+use crate::process::view::FromBytes;
 
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::{E_ACCESSDENIED, E_HANDLE};
 #[cfg(target_os = "windows")]
 use windows::Win32::System::Diagnostics::Debug::ReadProcessMemory;
 
@@ -45,6 +53,33 @@ pub trait ReadMemory {
         ]))
     }
 
+    /// Read a fixed-layout structure with a single read
+    fn read_struct<T: FromBytes>(&self, address: u64) -> Result<T>
+    where
+        Self: Sized,
+    {
+        let bytes = self.read_bytes(address, T::SIZE)?;
+        if bytes.len() < T::SIZE {
+            return Err(Error::MemoryReadFailed {
+                address,
+                message: format!("Expected {} bytes, read {}", T::SIZE, bytes.len()),
+            });
+        }
+        Ok(T::from_le_slice(&bytes))
+    }
+
+    /// Read several ranges at once, one result per `(address, size)` pair
+    ///
+    /// The default implementation reads each range separately. Readers backed
+    /// by a real process override this with [`read_coalesced`] so nearby
+    /// ranges share a single syscall.
+    fn read_many(&self, ranges: &[(u64, usize)]) -> Vec<Result<Vec<u8>>> {
+        ranges
+            .iter()
+            .map(|&(address, size)| self.read_bytes(address, size))
+            .collect()
+    }
+
     /// Read a Shift-JIS encoded string from memory
     ///
     /// Delegates to `decode_shift_jis_to_string` for decoding.
@@ -66,13 +101,108 @@ pub trait ReadMemory {
     }
 }
 
+/// Largest hole between two ranges that [`read_coalesced`] reads through
+pub const COALESCE_MAX_GAP: usize = 4096;
+
+/// Upper bound on the size of a single coalesced read
+pub const COALESCE_MAX_SPAN: usize = 256 * 1024;
+
+/// Ranges merged into one read: `(start, len, member indices)`
+type CoalescedSpan = (u64, usize, Vec<usize>);
+
+/// Group ranges that lie within [`COALESCE_MAX_GAP`] of each other
+///
+/// Zero-length and overflowing ranges are left out of every span.
+fn coalesce_ranges(ranges: &[(u64, usize)]) -> Vec<CoalescedSpan> {
+    let mut order: Vec<usize> = (0..ranges.len())
+        .filter(|&i| {
+            let (address, size) = ranges[i];
+            size > 0 && address.checked_add(size as u64).is_some()
+        })
+        .collect();
+    order.sort_by_key(|&i| ranges[i].0);
+
+    let mut spans: Vec<CoalescedSpan> = Vec::new();
+    for i in order {
+        let (address, size) = ranges[i];
+        let end = address + size as u64;
+        if let Some((start, len, members)) = spans.last_mut() {
+            let span_end = *start + *len as u64;
+            let merged_end = span_end.max(end);
+            if address <= span_end.saturating_add(COALESCE_MAX_GAP as u64)
+                && merged_end - *start <= COALESCE_MAX_SPAN as u64
+            {
+                *len = (merged_end - *start) as usize;
+                members.push(i);
+                continue;
+            }
+        }
+        spans.push((address, size, vec![i]));
+    }
+    spans
+}
+
+/// [`ReadMemory::read_many`] that merges nearby ranges into fewer reads
+///
+/// If a merged read fails (e.g. the hole crosses an unmapped page), the
+/// ranges in that span are retried one by one so each still gets its own
+/// result.
+pub fn read_coalesced<R: ReadMemory + ?Sized>(
+    reader: &R,
+    ranges: &[(u64, usize)],
+) -> Vec<Result<Vec<u8>>> {
+    let mut results: Vec<Option<Result<Vec<u8>>>> = (0..ranges.len()).map(|_| None).collect();
+
+    for (start, len, members) in coalesce_ranges(ranges) {
+        if let [index] = members[..] {
+            let (address, size) = ranges[index];
+            results[index] = Some(reader.read_bytes(address, size));
+            continue;
+        }
+        match reader.read_bytes(start, len) {
+            Ok(buffer) => {
+                for index in members {
+                    let (address, size) = ranges[index];
+                    let offset = (address - start) as usize;
+                    results[index] = Some(Ok(buffer[offset..offset + size].to_vec()));
+                }
+            }
+            Err(_) => {
+                for index in members {
+                    let (address, size) = ranges[index];
+                    results[index] = Some(reader.read_bytes(address, size));
+                }
+            }
+        }
+    }
+
+    // Zero-length and overflowing ranges never joined a span
+    results
+        .into_iter()
+        .zip(ranges)
+        .map(|(result, &(address, size))| {
+            result.unwrap_or_else(|| reader.read_bytes(address, size))
+        })
+        .collect()
+}
+
 pub struct MemoryReader<'a> {
     process: &'a ProcessHandle,
+    policy: Option<Arc<ReadPolicy>>,
 }
 
 impl<'a> MemoryReader<'a> {
     pub fn new(process: &'a ProcessHandle) -> Self {
-        Self { process }
+        Self {
+            process,
+            policy: None,
+        }
+    }
+
+    /// Throttle and/or audit every read through `policy`
+    pub fn with_policy(mut self, policy: Arc<ReadPolicy>) -> Self {
+        self.policy = (!policy.is_noop()).then_some(policy);
+        self
     }
 
     #[cfg(target_os = "windows")]
@@ -81,24 +211,34 @@ impl<'a> MemoryReader<'a> {
         let mut bytes_read = 0;
 
         // SAFETY: ReadProcessMemory is called with:
-        // - A valid process handle from ProcessHandle (obtained via OpenProcess with PROCESS_VM_READ)
+        // - A valid process handle from ProcessHandle (obtained via OpenProcess with PROCESS_VM_READ,
+        //   kept open for the duration of the call by `with_handle`)
         // - An address within the target process's address space
         // - A properly allocated buffer of the requested size
         // - A pointer to receive the actual bytes read
         // The function may fail if the address is invalid, but this is handled via Result.
-        unsafe {
-            ReadProcessMemory(
-                self.process.handle(),
-                address as *const _,
-                buffer.as_mut_ptr() as *mut _,
-                size,
-                Some(&mut bytes_read),
-            )
-            .map_err(|e| Error::MemoryReadFailed {
-                address,
-                message: e.to_string(),
+        self.process
+            .with_handle(|handle| unsafe {
+                ReadProcessMemory(
+                    handle,
+                    address as *const _,
+                    buffer.as_mut_ptr() as *mut _,
+                    size,
+                    Some(&mut bytes_read),
+                )
+            })
+            .map_err(|e| {
+                // A handle that stops granting access (rather than an unmapped
+                // page, ERROR_PARTIAL_COPY) can be recovered by reopening it
+                if e.code() == E_ACCESSDENIED || e.code() == E_HANDLE {
+                    Error::ProcessHandleLost(format!("read at {:#x}: {}", address, e))
+                } else {
+                    Error::MemoryReadFailed {
+                        address,
+                        message: e.to_string(),
+                    }
+                }
             })?;
-        }
 
         // This function guarantees all-or-nothing reads. Partial reads are treated as errors
         // because game memory structures require complete data for correct interpretation.
@@ -125,7 +265,17 @@ impl<'a> MemoryReader<'a> {
 
 impl ReadMemory for MemoryReader<'_> {
     fn read_bytes(&self, address: u64, size: usize) -> Result<Vec<u8>> {
-        self.read_bytes_impl(address, size)
+        if let Some(policy) = &self.policy {
+            policy.before_read(address, size);
+        }
+        // Lost handles are refreshed here so the tracker keeps its state
+        with_handle_recovery(self.process, MAX_HANDLE_REFRESHES, || {
+            self.read_bytes_impl(address, size)
+        })
+    }
+
+    fn read_many(&self, ranges: &[(u64, usize)]) -> Vec<Result<Vec<u8>>> {
+        read_coalesced(self, ranges)
     }
 
     fn base_address(&self) -> u64 {
@@ -216,4 +366,86 @@ mod tests {
         let reader = MockMemoryReader::new(vec![]);
         assert_eq!(reader.base_address(), 0x1000);
     }
+
+    /// Mock that records every `read_bytes` call and coalesces `read_many`
+    struct CountingReader {
+        inner: MockMemoryReader,
+        reads: std::cell::RefCell<Vec<(u64, usize)>>,
+    }
+
+    impl CountingReader {
+        fn new(len: usize) -> Self {
+            let data = (0..len).map(|i| i as u8).collect();
+            Self {
+                inner: MockMemoryReader::new(data),
+                reads: Default::default(),
+            }
+        }
+    }
+
+    impl ReadMemory for CountingReader {
+        fn read_bytes(&self, address: u64, size: usize) -> Result<Vec<u8>> {
+            self.reads.borrow_mut().push((address, size));
+            self.inner.read_bytes(address, size)
+        }
+
+        fn base_address(&self) -> u64 {
+            self.inner.base_address()
+        }
+
+        fn read_many(&self, ranges: &[(u64, usize)]) -> Vec<Result<Vec<u8>>> {
+            read_coalesced(self, ranges)
+        }
+    }
+
+    #[test]
+    fn test_read_many_default_reads_each_range() {
+        let reader = MockMemoryReader::new((0..16).collect());
+        let results = reader.read_many(&[(0x1004, 2), (0x1000, 1), (0x2000, 4)]);
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &vec![4, 5]);
+        assert_eq!(results[1].as_ref().unwrap(), &vec![0]);
+        assert!(results[2].is_err());
+    }
+
+    #[test]
+    fn test_read_coalesced_merges_nearby_ranges() {
+        let reader = CountingReader::new(0x100);
+        let ranges = [(0x1040, 8), (0x1000, 8), (0x1010, 16), (0x1044, 2)];
+        let results = reader.read_many(&ranges);
+
+        assert_eq!(reader.reads.borrow().as_slice(), &[(0x1000, 0x48)]);
+        for (result, &(address, size)) in results.iter().zip(&ranges) {
+            assert_eq!(
+                result.as_ref().unwrap(),
+                &reader.inner.read_bytes(address, size).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_coalesced_splits_distant_ranges() {
+        let reader = CountingReader::new(0x4000);
+        let far = 0x1000 + (COALESCE_MAX_GAP as u64) + 0x100;
+        let results = reader.read_many(&[(0x1000, 4), (far, 4)]);
+
+        assert_eq!(reader.reads.borrow().len(), 2);
+        assert!(results.iter().all(|r| r.is_ok()));
+    }
+
+    #[test]
+    fn test_read_coalesced_falls_back_when_merged_read_fails() {
+        let reader = CountingReader::new(0x20);
+        // The second range runs past the end, so the merged read fails
+        let results = reader.read_many(&[(0x1000, 4), (0x1018, 16), (0x1008, 0)]);
+
+        assert_eq!(results[0].as_ref().unwrap(), &vec![0, 1, 2, 3]);
+        assert!(results[1].is_err());
+        assert!(results[2].as_ref().unwrap().is_empty());
+        assert_eq!(
+            reader.reads.borrow().as_slice(),
+            &[(0x1000, 0x28), (0x1000, 4), (0x1018, 16), (0x1008, 0)]
+        );
+    }
 }
@@ -0,0 +1,164 @@
+//! Recorded memory regions that can be parsed without the game process
+//!
+//! [`MemorySnapshot::capture`] copies chosen regions (song list, score map
+//! nodes, judge block) out of a live reader; anything that takes a
+//! [`ReadMemory`] — the song, score map and judge parsers — can then run on
+//! the snapshot, including in a wasm32 build with no process access at all.
+//!
+//! The serialized form is `IDMP`, a u32 region count, then per region the
+//! base address (u64), length (u64) and bytes, all little-endian.
+
+use crate::error::{Error, Result};
+use crate::process::ReadMemory;
+
+/// A set of memory regions keyed by their address in the game process
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemorySnapshot {
+    /// (base address, bytes), sorted by base address
+    regions: Vec<(u64, Vec<u8>)>,
+}
+
+impl MemorySnapshot {
+    /// Leading bytes of the serialized form
+    pub const MAGIC: &[u8; 4] = b"IDMP";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a region; a region at the same base address is replaced
+    pub fn add_region(&mut self, base: u64, bytes: Vec<u8>) {
+        match self.regions.binary_search_by_key(&base, |(b, _)| *b) {
+            Ok(i) => self.regions[i].1 = bytes,
+            Err(i) => self.regions.insert(i, (base, bytes)),
+        }
+    }
+
+    /// Read `ranges` from a live reader into a new snapshot
+    ///
+    /// Unreadable ranges are skipped rather than failing the capture.
+    pub fn capture<R: ReadMemory + ?Sized>(reader: &R, ranges: &[(u64, usize)]) -> Self {
+        let mut snapshot = Self::new();
+        for (&(base, _), bytes) in ranges.iter().zip(reader.read_many(ranges)) {
+            if let Ok(bytes) = bytes {
+                snapshot.add_region(base, bytes);
+            }
+        }
+        snapshot
+    }
+
+    /// Recorded regions as (base address, bytes), sorted by address
+    pub fn regions(&self) -> impl Iterator<Item = (u64, &[u8])> {
+        self.regions
+            .iter()
+            .map(|(base, bytes)| (*base, bytes.as_slice()))
+    }
+
+    /// Encode in the `IDMP` format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let len = self
+            .regions
+            .iter()
+            .map(|(_, b)| 16 + b.len())
+            .sum::<usize>();
+        let mut out = Vec::with_capacity(8 + len);
+        out.extend_from_slice(Self::MAGIC);
+        out.extend_from_slice(&(self.regions.len() as u32).to_le_bytes());
+        for (base, bytes) in &self.regions {
+            out.extend_from_slice(&base.to_le_bytes());
+            out.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+            out.extend_from_slice(bytes);
+        }
+        out
+    }
+
+    /// Decode the `IDMP` format
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let mut rest = data
+            .strip_prefix(Self::MAGIC)
+            .ok_or_else(|| Error::parse("memory snapshot", "missing IDMP header"))?;
+        let u64_at = |b: &[u8]| u64::from_le_bytes(b.try_into().expect("8 bytes"));
+
+        let count = u32::from_le_bytes(take(&mut rest, 4)?.try_into().expect("4 bytes"));
+        let mut snapshot = Self::new();
+        for _ in 0..count {
+            let base = u64_at(take(&mut rest, 8)?);
+            let len = usize::try_from(u64_at(take(&mut rest, 8)?))
+                .map_err(|_| Error::parse("memory snapshot", "region is too large"))?;
+            snapshot.add_region(base, take(&mut rest, len)?.to_vec());
+        }
+        Ok(snapshot)
+    }
+}
+
+/// Split `n` bytes off the front of `rest`
+fn take<'a>(rest: &mut &'a [u8], n: usize) -> Result<&'a [u8]> {
+    if rest.len() < n {
+        return Err(Error::parse("memory snapshot", "truncated"));
+    }
+    let (head, tail) = rest.split_at(n);
+    *rest = tail;
+    Ok(head)
+}
+
+impl ReadMemory for MemorySnapshot {
+    /// Reads must fall entirely inside one recorded region
+    fn read_bytes(&self, address: u64, size: usize) -> Result<Vec<u8>> {
+        let idx = self.regions.partition_point(|(base, _)| *base <= address);
+        let region = idx.checked_sub(1).map(|i| &self.regions[i]);
+        if let Some((base, bytes)) = region
+            && let Some(slice) = usize::try_from(address - base)
+                .ok()
+                .and_then(|start| Some(start..start.checked_add(size)?))
+                .and_then(|range| bytes.get(range))
+        {
+            return Ok(slice.to_vec());
+        }
+        Err(Error::MemoryReadFailed {
+            address,
+            message: format!("{size} bytes not recorded in snapshot"),
+        })
+    }
+
+    /// Lowest recorded address (0 for an empty snapshot)
+    fn base_address(&self) -> u64 {
+        self.regions.first().map_or(0, |(base, _)| *base)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::MockMemoryReader;
+
+    #[test]
+    fn test_reads_within_regions() {
+        let mut snapshot = MemorySnapshot::new();
+        snapshot.add_region(0x2000, vec![5, 6, 7, 8]);
+        snapshot.add_region(0x1000, 7u32.to_le_bytes().to_vec());
+
+        assert_eq!(snapshot.base_address(), 0x1000);
+        assert_eq!(snapshot.read_u32(0x1000).unwrap(), 7);
+        assert_eq!(snapshot.read_bytes(0x2002, 2).unwrap(), vec![7, 8]);
+        // Past the end of a region, and in the gap between regions
+        assert!(snapshot.read_bytes(0x2002, 4).is_err());
+        assert!(snapshot.read_bytes(0x1800, 1).is_err());
+        assert!(snapshot.read_bytes(0x10, 1).is_err());
+    }
+
+    #[test]
+    fn test_capture_and_round_trip() {
+        let reader = MockMemoryReader::new((0u8..64).collect());
+        let snapshot = MemorySnapshot::capture(&reader, &[(0x1000, 8), (0x1020, 4), (0x9000, 4)]);
+        assert_eq!(snapshot.regions().count(), 2);
+        assert_eq!(
+            snapshot.read_bytes(0x1020, 4).unwrap(),
+            vec![32, 33, 34, 35]
+        );
+
+        let encoded = snapshot.to_bytes();
+        assert_eq!(MemorySnapshot::from_bytes(&encoded).unwrap(), snapshot);
+        assert!(MemorySnapshot::from_bytes(&encoded[..encoded.len() - 1]).is_err());
+        assert!(MemorySnapshot::from_bytes(b"NOPE").is_err());
+    }
+}
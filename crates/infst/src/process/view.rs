@@ -0,0 +1,89 @@
+//! Typed views over fixed-layout memory structures
+//!
+//! Game structures such as the judge block are runs of little-endian words.
+//! Implementing [`FromBytes`] for such a structure lets it be decoded straight
+//! from a borrowed slice (`ByteBuffer::read_struct_at`) or fetched with a
+//! single read (`ReadMemory::read_struct`), instead of one read per field.
+
+/// A fixed-size structure decoded from little-endian bytes
+pub trait FromBytes: Sized {
+    /// Encoded size in bytes
+    const SIZE: usize;
+
+    /// Decode from a slice of exactly [`Self::SIZE`] bytes
+    ///
+    /// Callers go through `ByteBuffer::read_struct_at` or
+    /// `ReadMemory::read_struct`, which check the length first.
+    fn from_le_slice(bytes: &[u8]) -> Self;
+}
+
+/// Decode the field of type `T` at `offset` within a structure being decoded
+///
+/// For use inside [`FromBytes::from_le_slice`] implementations.
+pub fn field<T: FromBytes>(bytes: &[u8], offset: usize) -> T {
+    T::from_le_slice(&bytes[offset..offset + T::SIZE])
+}
+
+macro_rules! impl_from_bytes_for_int {
+    ($($ty:ty),*) => {
+        $(
+            impl FromBytes for $ty {
+                const SIZE: usize = size_of::<$ty>();
+
+                fn from_le_slice(bytes: &[u8]) -> Self {
+                    let mut raw = [0u8; size_of::<$ty>()];
+                    raw.copy_from_slice(&bytes[..Self::SIZE]);
+                    <$ty>::from_le_bytes(raw)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_bytes_for_int!(u8, i8, u16, i16, u32, i32, u64, i64);
+
+impl<T: FromBytes, const N: usize> FromBytes for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+
+    fn from_le_slice(bytes: &[u8]) -> Self {
+        std::array::from_fn(|i| field(bytes, i * T::SIZE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Pair {
+        id: u32,
+        value: i64,
+    }
+
+    impl FromBytes for Pair {
+        const SIZE: usize = 12;
+
+        fn from_le_slice(bytes: &[u8]) -> Self {
+            Self {
+                id: field(bytes, 0),
+                value: field(bytes, 4),
+            }
+        }
+    }
+
+    #[test]
+    fn test_primitives_and_arrays() {
+        let bytes = [0x01, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF];
+        assert_eq!(u32::from_le_slice(&bytes), 1);
+        assert_eq!(<[i32; 2]>::from_le_slice(&bytes), [1, -1]);
+        assert_eq!(<[u16; 4]>::SIZE, 8);
+    }
+
+    #[test]
+    fn test_struct_fields() {
+        let mut bytes = vec![0u8; Pair::SIZE];
+        bytes[..4].copy_from_slice(&7u32.to_le_bytes());
+        bytes[4..].copy_from_slice(&(-2i64).to_le_bytes());
+        assert_eq!(Pair::from_le_slice(&bytes), Pair { id: 7, value: -2 });
+    }
+}
@@ -0,0 +1,213 @@
+//! RGBA canvas with PNG encoding
+
+use std::io::Write;
+use std::path::Path;
+
+use flate2::Compression;
+use flate2::Crc;
+use flate2::write::ZlibEncoder;
+
+use crate::error::Result;
+use crate::storage::write_atomic;
+
+use super::font::{GLYPH_ADVANCE, GLYPH_HEIGHT, glyph};
+
+/// RGBA color
+pub type Rgba = [u8; 4];
+
+/// Parse a `#RRGGBB` color (as returned by `Difficulty::color_code`)
+pub fn parse_hex_color(hex: &str) -> Option<Rgba> {
+    let hex = hex.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    Some([(value >> 16) as u8, (value >> 8) as u8, value as u8, 0xFF])
+}
+
+/// In-memory RGBA image
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    /// Create a canvas filled with `background`
+    pub fn new(width: u32, height: u32, background: Rgba) -> Self {
+        let pixels = background
+            .iter()
+            .copied()
+            .cycle()
+            .take((width * height * 4) as usize)
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Color of a pixel (`None` outside the canvas)
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Rgba> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = ((y * self.width + x) * 4) as usize;
+        Some([
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ])
+    }
+
+    fn set_pixel(&mut self, x: u32, y: u32, color: Rgba) {
+        if x < self.width && y < self.height {
+            let i = ((y * self.width + x) * 4) as usize;
+            self.pixels[i..i + 4].copy_from_slice(&color);
+        }
+    }
+
+    /// Fill a rectangle (clipped to the canvas)
+    pub fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: Rgba) {
+        for py in y..y.saturating_add(height).min(self.height) {
+            for px in x..x.saturating_add(width).min(self.width) {
+                self.set_pixel(px, py, color);
+            }
+        }
+    }
+
+    /// Draw text with the built-in 5x7 font, each font pixel scaled to `scale` pixels
+    ///
+    /// Only printable ASCII is supported; other characters are drawn as '?'.
+    pub fn draw_text(&mut self, x: u32, y: u32, text: &str, scale: u32, color: Rgba) {
+        let mut cursor = x;
+        for c in text.chars() {
+            for (col, bits) in glyph(c).iter().enumerate() {
+                for row in 0..GLYPH_HEIGHT {
+                    if bits & (1 << row) != 0 {
+                        self.fill_rect(
+                            cursor + col as u32 * scale,
+                            y + row * scale,
+                            scale,
+                            scale,
+                            color,
+                        );
+                    }
+                }
+            }
+            cursor += GLYPH_ADVANCE * scale;
+        }
+    }
+
+    /// Encode as PNG (8-bit RGBA, no filtering)
+    pub fn encode_png(&self) -> Result<Vec<u8>> {
+        let mut raw = Vec::with_capacity(self.pixels.len() + self.height as usize);
+        for row in self.pixels.chunks_exact((self.width * 4) as usize) {
+            raw.push(0); // filter type: None
+            raw.extend_from_slice(row);
+        }
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&raw)?;
+        let compressed = encoder.finish()?;
+
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend_from_slice(&self.width.to_be_bytes());
+        ihdr.extend_from_slice(&self.height.to_be_bytes());
+        // bit depth 8, color type 6 (RGBA), compression, filter, interlace
+        ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        write_chunk(&mut png, b"IHDR", &ihdr);
+        write_chunk(&mut png, b"IDAT", &compressed);
+        write_chunk(&mut png, b"IEND", &[]);
+        Ok(png)
+    }
+
+    /// Encode as PNG and write to `path`
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_atomic(path, self.encode_png()?)?;
+        Ok(())
+    }
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+
+    let mut crc = Crc::new();
+    crc.update(kind);
+    crc.update(data);
+    png.extend_from_slice(&crc.sum().to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    use flate2::read::ZlibDecoder;
+
+    const BLACK: Rgba = [0, 0, 0, 0xFF];
+    const WHITE: Rgba = [0xFF, 0xFF, 0xFF, 0xFF];
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#E52B19"), Some([0xE5, 0x2B, 0x19, 0xFF]));
+        assert_eq!(parse_hex_color("E52B19"), None);
+        assert_eq!(parse_hex_color("#E52B"), None);
+    }
+
+    #[test]
+    fn test_fill_rect_clips() {
+        let mut canvas = Canvas::new(4, 4, BLACK);
+        canvas.fill_rect(2, 2, 10, 10, WHITE);
+        assert_eq!(canvas.pixel(1, 1), Some(BLACK));
+        assert_eq!(canvas.pixel(3, 3), Some(WHITE));
+        assert_eq!(canvas.pixel(4, 4), None);
+    }
+
+    #[test]
+    fn test_draw_text() {
+        let mut canvas = Canvas::new(20, 10, BLACK);
+        canvas.draw_text(0, 0, "I", 1, WHITE);
+        // Middle column of 'I' is a full vertical bar
+        for y in 0..7 {
+            assert_eq!(canvas.pixel(2, y), Some(WHITE));
+        }
+        assert_eq!(canvas.pixel(0, 3), Some(BLACK));
+    }
+
+    #[test]
+    fn test_encode_png() {
+        let canvas = Canvas::new(3, 2, WHITE);
+        let png = canvas.encode_png().unwrap();
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 3);
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 2);
+
+        // IDAT follows IHDR (8 + 25 bytes)
+        let idat_len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&png[37..41], b"IDAT");
+        let mut raw = Vec::new();
+        ZlibDecoder::new(&png[41..41 + idat_len])
+            .read_to_end(&mut raw)
+            .unwrap();
+        assert_eq!(raw.len(), 2 * (1 + 3 * 4));
+        assert!(png.ends_with(&[0xAE, 0x42, 0x60, 0x82]));
+    }
+}
@@ -0,0 +1,156 @@
+//! Result card image for a single play
+
+use crate::play::PlayData;
+use crate::score::{Grade, Lamp};
+
+use super::canvas::{Canvas, Rgba, parse_hex_color};
+
+/// Result card width in pixels
+pub const CARD_WIDTH: u32 = 480;
+/// Result card height in pixels
+pub const CARD_HEIGHT: u32 = 200;
+
+pub(super) const BACKGROUND: Rgba = [0x18, 0x18, 0x20, 0xFF];
+pub(super) const TEXT: Rgba = [0xF0, 0xF0, 0xF0, 0xFF];
+pub(super) const DIM: Rgba = [0x90, 0x90, 0xA0, 0xFF];
+const FAST: Rgba = [0x40, 0x80, 0xFF, 0xFF];
+const SLOW: Rgba = [0xFF, 0x50, 0x40, 0xFF];
+
+pub(super) fn lamp_color(lamp: Lamp) -> Rgba {
+    match lamp {
+        Lamp::NoPlay => DIM,
+        Lamp::Failed => [0xC0, 0x30, 0x30, 0xFF],
+        Lamp::AssistClear => [0xA0, 0x60, 0xFF, 0xFF],
+        Lamp::EasyClear => [0x80, 0xFF, 0x00, 0xFF],
+        Lamp::Clear => [0x40, 0xC0, 0xFF, 0xFF],
+        Lamp::HardClear => TEXT,
+        Lamp::ExHardClear => [0xFF, 0xD0, 0x00, 0xFF],
+        Lamp::FullCombo => [0x00, 0xFF, 0xFF, 0xFF],
+    }
+}
+
+pub(super) fn grade_color(grade: Grade) -> Rgba {
+    match grade {
+        Grade::Aaa => [0xFF, 0xC8, 0x00, 0xFF],
+        Grade::Aa => [0xC0, 0xC0, 0xC0, 0xFF],
+        Grade::A => [0x00, 0xFF, 0xFF, 0xFF],
+        _ => TEXT,
+    }
+}
+
+/// Title to draw with the ASCII-only font
+///
+/// Uses the English title when the in-game title has non-ASCII characters.
+fn card_title(play_data: &PlayData) -> &str {
+    let title = &*play_data.chart.title;
+    if title.is_ascii() || play_data.chart.title_english.is_empty() {
+        title
+    } else {
+        &play_data.chart.title_english
+    }
+}
+
+/// Render a result card (title, grade, lamp and judge breakdown)
+pub fn render_result_card(play_data: &PlayData) -> Canvas {
+    let mut canvas = Canvas::new(CARD_WIDTH, CARD_HEIGHT, BACKGROUND);
+    let chart = &play_data.chart;
+    let judge = &play_data.judge;
+
+    // Difficulty color bar
+    let diff_color = parse_hex_color(chart.difficulty.color_code()).unwrap_or(DIM);
+    canvas.fill_rect(0, 0, 8, CARD_HEIGHT, diff_color);
+
+    // Title (truncated to fit at scale 2)
+    let max_chars = ((CARD_WIDTH - 32) / 12) as usize;
+    let title: String = card_title(play_data).chars().take(max_chars).collect();
+    canvas.draw_text(20, 14, &title, 2, TEXT);
+    canvas.draw_text(
+        20,
+        36,
+        &format!("{} Lv.{}", chart.difficulty.short_name(), chart.level),
+        2,
+        diff_color,
+    );
+
+    // Grade and score
+    canvas.draw_text(
+        20,
+        66,
+        play_data.grade.short_name(),
+        6,
+        grade_color(play_data.grade),
+    );
+    canvas.draw_text(200, 70, &format!("EX {}", play_data.ex_score), 3, TEXT);
+    canvas.draw_text(
+        200,
+        100,
+        play_data.lamp.expand_name(),
+        2,
+        lamp_color(play_data.lamp),
+    );
+
+    // Judge breakdown
+    canvas.draw_text(
+        20,
+        140,
+        &format!(
+            "PG {}  GR {}  GD {}  BD {}  PR {}",
+            judge.pgreat, judge.great, judge.good, judge.bad, judge.poor
+        ),
+        2,
+        TEXT,
+    );
+    canvas.draw_text(20, 168, &format!("FAST {}", judge.fast), 2, FAST);
+    canvas.draw_text(140, 168, &format!("SLOW {}", judge.slow), 2, SLOW);
+    let miss = if play_data.miss_count_valid() {
+        play_data.miss_count().to_string()
+    } else {
+        "-".to_string()
+    };
+    canvas.draw_text(260, 168, &format!("MISS {}", miss), 2, DIM);
+
+    canvas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::Difficulty;
+    use crate::play::test_support::PlayBuilder;
+    use crate::score::Judge;
+
+    fn make_play(title: &str, title_english: &str) -> PlayData {
+        PlayBuilder::new()
+            .title(title)
+            .title_english(title_english)
+            .ex_score(1800)
+            .grade(Grade::Aaa)
+            .lamp(Lamp::HardClear)
+            .judge(Judge {
+                pgreat: 850,
+                great: 100,
+                ..Default::default()
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_card_title_prefers_ascii() {
+        assert_eq!(card_title(&make_play("Title", "Other")), "Title");
+        assert_eq!(card_title(&make_play("冥", "Mei")), "Mei");
+        assert_eq!(card_title(&make_play("冥", "")), "冥");
+    }
+
+    #[test]
+    fn test_render_result_card() {
+        let canvas = render_result_card(&make_play("Test Song", ""));
+        assert_eq!(canvas.width(), CARD_WIDTH);
+        assert_eq!(canvas.height(), CARD_HEIGHT);
+        // Difficulty bar uses the SPA color
+        assert_eq!(
+            canvas.pixel(0, 0),
+            parse_hex_color(Difficulty::SpA.color_code())
+        );
+        assert!(canvas.encode_png().is_ok());
+    }
+}
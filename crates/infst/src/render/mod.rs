@@ -0,0 +1,17 @@
+//! Image rendering (requires the `render` feature).
+//!
+//! A small self-contained drawing backend for overlay images:
+//! - `Canvas` - RGBA buffer with rectangle/text drawing and PNG encoding
+//! - built-in 5x7 bitmap font (printable ASCII only)
+//! - `render_result_card` - result card for a single play
+//! - `render_session_summary` - session summary (plays and improved charts)
+
+mod canvas;
+mod card;
+mod font;
+mod summary;
+
+pub use canvas::*;
+pub use card::*;
+pub use font::{GLYPH_ADVANCE, GLYPH_HEIGHT, GLYPH_WIDTH, text_width};
+pub use summary::*;
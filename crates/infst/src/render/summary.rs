@@ -0,0 +1,172 @@
+//! Session summary image shown at session close
+
+use crate::chart::Difficulty;
+use crate::export::ChartImprovement;
+use crate::stats::{PlayTime, format_play_time};
+
+use super::canvas::{Canvas, parse_hex_color};
+use super::card::{BACKGROUND, DIM, TEXT, grade_color, lamp_color};
+use super::font::GLYPH_HEIGHT;
+
+/// Session summary image width in pixels
+pub const SUMMARY_WIDTH: u32 = 480;
+/// Improved charts listed before the rest are folded into "+N more"
+pub const SUMMARY_MAX_ROWS: usize = 12;
+
+const HEADER_HEIGHT: u32 = 112;
+const ROW_HEIGHT: u32 = 20;
+const PADDING: u32 = 16;
+
+/// What the session summary image shows
+#[derive(Debug, Clone, Copy)]
+pub struct SessionSummaryCard<'a> {
+    /// Heading, usually the session date
+    pub heading: &'a str,
+    pub dj_name: Option<&'a str>,
+    pub plays: usize,
+    pub play_time: &'a PlayTime,
+    /// Charts improved this session; titles should already be ASCII
+    /// (non-ASCII characters are drawn as `?`)
+    pub improvements: &'a [ChartImprovement],
+}
+
+/// Render a session summary (plays, play time and improved charts)
+///
+/// Lamp gains are listed first, then grade and score improvements.
+pub fn render_session_summary(summary: &SessionSummaryCard) -> Canvas {
+    let mut rows: Vec<&ChartImprovement> = summary.improvements.iter().collect();
+    rows.sort_by_key(|improvement| (improvement.lamp.is_none(), improvement.grade.is_none()));
+    let hidden = rows.len().saturating_sub(SUMMARY_MAX_ROWS);
+    rows.truncate(SUMMARY_MAX_ROWS);
+
+    let listed = rows.len().max(1) as u32 + u32::from(hidden > 0);
+    let height = HEADER_HEIGHT + listed * ROW_HEIGHT + PADDING;
+    let mut canvas = Canvas::new(SUMMARY_WIDTH, height, BACKGROUND);
+
+    canvas.draw_text(20, 14, summary.heading, 2, TEXT);
+    if let Some(dj_name) = summary.dj_name {
+        canvas.draw_text(20, 36, &format!("DJ {}", dj_name), 2, DIM);
+    }
+
+    let mut stats = format!("{} plays", summary.plays);
+    if !summary.play_time.is_empty() {
+        stats.push_str(&format!(
+            "  {}  {} notes",
+            format_play_time(summary.play_time.total_time()),
+            summary.play_time.notes_hit()
+        ));
+    }
+    canvas.draw_text(20, 58, &stats, 2, TEXT);
+
+    let count =
+        |f: fn(&ChartImprovement) -> bool| summary.improvements.iter().filter(|i| f(i)).count();
+    canvas.draw_text(
+        20,
+        80,
+        &format!(
+            "{} lamps  {} grades  {} PBs",
+            count(|i| i.lamp.is_some()),
+            count(|i| i.grade.is_some()),
+            count(|i| i.ex_score.is_some())
+        ),
+        2,
+        DIM,
+    );
+    canvas.fill_rect(20, HEADER_HEIGHT - 10, SUMMARY_WIDTH - 40, 2, DIM);
+
+    if rows.is_empty() {
+        canvas.draw_text(20, HEADER_HEIGHT, "No improvements", 2, DIM);
+    }
+    let max_title_chars = ((SUMMARY_WIDTH - 68 - 96) / 12) as usize;
+    for (i, improvement) in rows.iter().enumerate() {
+        let y = HEADER_HEIGHT + i as u32 * ROW_HEIGHT;
+        let diff_color = (0..10)
+            .filter_map(Difficulty::from_index)
+            .find(|difficulty| difficulty.short_name() == improvement.difficulty)
+            .and_then(|difficulty| parse_hex_color(difficulty.color_code()))
+            .unwrap_or(DIM);
+        canvas.fill_rect(8, y, 4, GLYPH_HEIGHT * 2, diff_color);
+        canvas.draw_text(20, y, &improvement.difficulty, 2, diff_color);
+        let title: String = improvement.title.chars().take(max_title_chars).collect();
+        canvas.draw_text(68, y, &title, 2, TEXT);
+
+        let (result, color) = match (improvement.lamp, improvement.grade, improvement.ex_score) {
+            (Some(lamp), _, _) => (lamp.new.short_name().to_string(), lamp_color(lamp.new)),
+            (None, Some(grade), _) => (grade.new.short_name().to_string(), grade_color(grade.new)),
+            (None, None, Some(score)) => {
+                (format!("+{}", score.new.saturating_sub(score.old)), TEXT)
+            }
+            (None, None, None) => continue,
+        };
+        canvas.draw_text(SUMMARY_WIDTH - 84, y, &result, 2, color);
+    }
+    if hidden > 0 {
+        let y = HEADER_HEIGHT + rows.len() as u32 * ROW_HEIGHT;
+        canvas.draw_text(20, y, &format!("+{} more", hidden), 2, DIM);
+    }
+
+    canvas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::Change;
+    use crate::score::Lamp;
+
+    fn improvement(song_id: u32, lamp: Option<Lamp>) -> ChartImprovement {
+        ChartImprovement {
+            song_id: song_id.to_string(),
+            title: format!("Song {}", song_id),
+            difficulty: "SPA".to_string(),
+            lamp: lamp.map(|new| Change {
+                old: Lamp::Failed,
+                new,
+            }),
+            grade: None,
+            ex_score: Some(Change {
+                old: 1500,
+                new: 1600,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_render_session_summary() {
+        let play_time = PlayTime::new();
+        let improvements = vec![
+            improvement(1000, None),
+            improvement(1001, Some(Lamp::HardClear)),
+        ];
+        let canvas = render_session_summary(&SessionSummaryCard {
+            heading: "2025-01-01",
+            dj_name: Some("TEST"),
+            plays: 5,
+            play_time: &play_time,
+            improvements: &improvements,
+        });
+        assert_eq!(canvas.width(), SUMMARY_WIDTH);
+        assert_eq!(canvas.height(), HEADER_HEIGHT + 2 * ROW_HEIGHT + PADDING);
+        // Lamp gain is listed first, with the SPA color
+        assert_eq!(
+            canvas.pixel(8, HEADER_HEIGHT),
+            parse_hex_color(Difficulty::SpA.color_code())
+        );
+        assert!(canvas.encode_png().is_ok());
+    }
+
+    #[test]
+    fn test_render_session_summary_folds_extra_rows() {
+        let play_time = PlayTime::new();
+        let improvements: Vec<_> = (0..20).map(|id| improvement(id, None)).collect();
+        let canvas = render_session_summary(&SessionSummaryCard {
+            heading: "2025-01-01",
+            dj_name: None,
+            plays: 20,
+            play_time: &play_time,
+            improvements: &improvements,
+        });
+        let rows = SUMMARY_MAX_ROWS as u32 + 1;
+        assert_eq!(canvas.height(), HEADER_HEIGHT + rows * ROW_HEIGHT + PADDING);
+    }
+}
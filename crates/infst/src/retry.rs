@@ -1,11 +1,19 @@
 //! Retry strategy abstraction for resilient operations.
 //!
 //! This module provides traits and implementations for retry logic with
-//! configurable backoff strategies.
+//! configurable backoff strategies. [`RetryPolicy`] is the configurable
+//! one: [`RetryPolicies`] holds a policy per retried operation (memory reads,
+//! song database loading, offset search, API submissions), with defaults
+//! from [`crate::config`].
 
+use std::fmt;
+use std::str::FromStr;
 use std::time::Duration;
 
-use crate::config::retry as retry_config;
+use strum::{Display, EnumString};
+
+use crate::config::{database, retry as retry_config};
+use crate::error::Error;
 
 /// Trait for defining retry strategies.
 ///
@@ -124,6 +132,247 @@ impl RetryStrategy for NoRetry {
     }
 }
 
+/// Attempts and delays for a retried operation
+///
+/// Written as `<attempts>x<delay>` for a fixed delay (`12x5s`) or
+/// `<attempts>x<delay>..<max delay>` for delays doubling up to a maximum
+/// (`5x100ms..1600ms`); `forever` instead of a count retries until success
+/// or shutdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts before giving up (`None`: until success or shutdown)
+    pub max_attempts: Option<u32>,
+    /// Delay after the first failure
+    pub delay: Duration,
+    /// Delays double after each failure up to this (equal to `delay` for a fixed delay)
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// The same delay after every failure
+    pub const fn fixed(max_attempts: Option<u32>, delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            delay,
+            max_delay: delay,
+        }
+    }
+
+    /// Delays doubling from `delay` up to `max_delay`
+    pub const fn exponential(
+        max_attempts: Option<u32>,
+        delay: Duration,
+        max_delay: Duration,
+    ) -> Self {
+        Self {
+            max_attempts,
+            delay,
+            max_delay,
+        }
+    }
+
+    /// Check that the policy makes at least one attempt and its delays are ordered
+    pub fn validate(&self) -> crate::error::Result<()> {
+        if self.max_attempts == Some(0) {
+            return Err(Error::invalid_config(
+                "retry",
+                "at least one attempt is required",
+            ));
+        }
+        if self.delay > self.max_delay {
+            return Err(Error::invalid_config(
+                "retry",
+                format!(
+                    "delay {} is longer than the maximum delay {}",
+                    format_duration(self.delay),
+                    format_duration(self.max_delay)
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether another attempt is allowed after `attempts` failed ones
+    pub fn allows_attempt(&self, attempts: u32) -> bool {
+        self.max_attempts.is_none_or(|max| attempts < max)
+    }
+
+    /// Delay after the failed attempt `attempt` (0-indexed)
+    pub fn delay_after(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.min(31));
+        self.delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+impl RetryStrategy for RetryPolicy {
+    fn max_attempts(&self) -> u32 {
+        self.max_attempts.unwrap_or(u32::MAX)
+    }
+
+    fn delay_for_attempt(&self, attempt: u32) -> Option<Duration> {
+        Some(self.delay_after(attempt))
+    }
+}
+
+/// Parse `500ms`, `5s` or `2m`
+fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let (number, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit())?);
+    let number: u64 = number.parse().ok()?;
+    match unit {
+        "ms" => Some(Duration::from_millis(number)),
+        "s" => Some(Duration::from_secs(number)),
+        "m" => Some(Duration::from_secs(number * 60)),
+        _ => None,
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let ms = duration.as_millis();
+    if ms.is_multiple_of(1000) {
+        format!("{}s", ms / 1000)
+    } else {
+        format!("{}ms", ms)
+    }
+}
+
+impl FromStr for RetryPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid retry policy {:?} (expected <attempts|forever>x<delay>[..<max delay>], e.g. 5x1s..8s)",
+                s
+            )
+        };
+        let (attempts, delays) = s.trim().split_once('x').ok_or_else(invalid)?;
+        let max_attempts = match attempts {
+            "forever" => None,
+            n => Some(n.parse().map_err(|_| invalid())?),
+        };
+        let (delay, max_delay) = match delays.split_once("..") {
+            Some((delay, max)) => (
+                parse_duration(delay).ok_or_else(invalid)?,
+                parse_duration(max).ok_or_else(invalid)?,
+            ),
+            None => {
+                let delay = parse_duration(delays).ok_or_else(invalid)?;
+                (delay, delay)
+            }
+        };
+        let policy = Self::exponential(max_attempts, delay, max_delay);
+        policy.validate().map_err(|e| e.to_string())?;
+        Ok(policy)
+    }
+}
+
+impl fmt::Display for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max_attempts {
+            Some(n) => write!(f, "{}", n)?,
+            None => f.write_str("forever")?,
+        }
+        write!(f, "x{}", format_duration(self.delay))?;
+        if self.max_delay != self.delay {
+            write!(f, "..{}", format_duration(self.max_delay))?;
+        }
+        Ok(())
+    }
+}
+
+/// An operation retried under a [`RetryPolicy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum RetryOperation {
+    /// Memory access check each poll of the tracking loop
+    MemoryRead,
+    /// Waiting for the game to populate the song database
+    SongDb,
+    /// Signature search for offsets
+    OffsetSearch,
+    /// Lamp submission to the web API
+    Api,
+}
+
+/// Retry policy of every retried operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicies {
+    pub memory_read: RetryPolicy,
+    pub song_db: RetryPolicy,
+    pub offset_search: RetryPolicy,
+    pub api: RetryPolicy,
+}
+
+impl Default for RetryPolicies {
+    fn default() -> Self {
+        let delays = retry_config::RETRY_DELAYS_MS;
+        Self {
+            memory_read: RetryPolicy::exponential(
+                Some(retry_config::MAX_READ_RETRIES),
+                Duration::from_millis(delays[0]),
+                Duration::from_millis(delays[delays.len() - 1]),
+            ),
+            song_db: RetryPolicy::fixed(Some(database::MAX_LOAD_ATTEMPTS), database::RETRY_DELAY),
+            offset_search: RetryPolicy::fixed(None, database::RETRY_DELAY),
+            api: RetryPolicy::exponential(
+                Some(retry_config::API_ATTEMPTS),
+                Duration::from_millis(retry_config::API_DELAY_MS),
+                Duration::from_millis(retry_config::API_MAX_DELAY_MS),
+            ),
+        }
+    }
+}
+
+impl RetryPolicies {
+    /// Policy for `operation`
+    pub fn get(&self, operation: RetryOperation) -> RetryPolicy {
+        match operation {
+            RetryOperation::MemoryRead => self.memory_read,
+            RetryOperation::SongDb => self.song_db,
+            RetryOperation::OffsetSearch => self.offset_search,
+            RetryOperation::Api => self.api,
+        }
+    }
+
+    /// Replace the policy for `operation`
+    pub fn set(&mut self, operation: RetryOperation, policy: RetryPolicy) {
+        match operation {
+            RetryOperation::MemoryRead => self.memory_read = policy,
+            RetryOperation::SongDb => self.song_db = policy,
+            RetryOperation::OffsetSearch => self.offset_search = policy,
+            RetryOperation::Api => self.api = policy,
+        }
+    }
+}
+
+/// A policy for one operation, written `<operation>=<policy>` (`song-db=20x5s`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryOverride {
+    pub operation: RetryOperation,
+    pub policy: RetryPolicy,
+}
+
+impl FromStr for RetryOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (operation, policy) = s.split_once('=').ok_or_else(|| {
+            format!(
+                "invalid retry setting {:?} (expected <operation>=<policy>, operations: memory-read, song-db, offset-search, api)",
+                s
+            )
+        })?;
+        Ok(Self {
+            operation: operation
+                .trim()
+                .parse()
+                .map_err(|_| format!("unknown retry operation {:?}", operation.trim()))?,
+            policy: policy.parse()?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -230,4 +479,54 @@ mod tests {
         assert_eq!(result, Err("failed"));
         assert_eq!(attempts, 1);
     }
+
+    #[test]
+    fn test_default_policies_match_config() {
+        let policies = RetryPolicies::default();
+        let backoff = ExponentialBackoff::new();
+        let memory_read = policies.memory_read;
+        assert_eq!(memory_read.max_attempts(), backoff.max_attempts());
+        for attempt in 0..backoff.max_attempts() {
+            assert_eq!(
+                memory_read.delay_for_attempt(attempt),
+                backoff.delay_for_attempt(attempt)
+            );
+        }
+        assert_eq!(policies.song_db.to_string(), "12x5s");
+        assert_eq!(policies.offset_search.to_string(), "foreverx5s");
+        assert!(policies.offset_search.allows_attempt(u32::MAX - 1));
+        assert_eq!(policies.api.to_string(), "3x1s..4s");
+    }
+
+    #[test]
+    fn test_parse_retry_policy() {
+        let policy: RetryPolicy = "5x100ms..1s".parse().unwrap();
+        assert_eq!(policy.max_attempts, Some(5));
+        assert_eq!(policy.delay_after(0), Duration::from_millis(100));
+        assert_eq!(policy.delay_after(3), Duration::from_millis(800));
+        assert_eq!(policy.delay_after(4), Duration::from_secs(1));
+        assert_eq!(policy.delay_after(40), Duration::from_secs(1));
+        assert!(policy.allows_attempt(4));
+        assert!(!policy.allows_attempt(5));
+
+        let policy: RetryPolicy = "foreverx2m".parse().unwrap();
+        assert_eq!(policy, RetryPolicy::fixed(None, Duration::from_secs(120)));
+        assert_eq!(policy.to_string().parse::<RetryPolicy>(), Ok(policy));
+
+        for invalid in ["5", "0x1s", "3x", "3x1h", "3x5s..1s", "ax1s"] {
+            assert!(invalid.parse::<RetryPolicy>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_retry_override() {
+        let setting: RetryOverride = "song-db=20x5s".parse().unwrap();
+        assert_eq!(setting.operation, RetryOperation::SongDb);
+        let mut policies = RetryPolicies::default();
+        policies.set(setting.operation, setting.policy);
+        assert_eq!(policies.get(RetryOperation::SongDb).max_attempts, Some(20));
+
+        assert!("disk=3x1s".parse::<RetryOverride>().is_err());
+        assert!("api".parse::<RetryOverride>().is_err());
+    }
 }
@@ -0,0 +1,263 @@
+//! Bests of plays with assist options, kept apart from clean bests
+//!
+//! The game keeps one best per chart. Plays with A-SCR, 5KEYS, LEGACY NOTE,
+//! KEY ASSIST or ANY KEY shouldn't count as clean bests, so the tracker
+//! records their bests itself in `assisted_bests.json` in the session
+//! directory (snapshot format, see [`super::delta`]) and overlays them on the
+//! score map read from the game ([`AssistedBests::apply`]).
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use super::{ChartBest, Lamp, ScoreData, ScoreMap};
+use crate::chart::{ChartId, Difficulty};
+use crate::error::{Error, Result};
+use crate::play::PlayData;
+use crate::storage::write_atomic;
+
+/// Assisted bests file name inside the session directory
+pub const ASSISTED_BESTS_FILE: &str = "assisted_bests.json";
+
+impl ChartBest {
+    /// Merge `other` in, keeping the better of each value; whether anything
+    /// improved
+    fn merge(&mut self, other: ChartBest) -> bool {
+        let before = *self;
+        self.lamp = self.lamp.max(other.lamp);
+        self.score = self.score.max(other.score);
+        self.miss_count = match (self.miss_count, other.miss_count) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        *self != before
+    }
+}
+
+impl ScoreData {
+    /// Best of assisted plays on a chart, `None` when there are none
+    pub fn get_assisted(&self, difficulty: Difficulty) -> Option<ChartBest> {
+        self.assisted[difficulty.index()]
+    }
+
+    /// Merge an assisted result into the chart's assisted best; whether it
+    /// improved
+    pub fn record_assisted(&mut self, difficulty: Difficulty, best: ChartBest) -> bool {
+        match &mut self.assisted[difficulty.index()] {
+            Some(current) => current.merge(best),
+            slot => {
+                *slot = Some(best);
+                true
+            }
+        }
+    }
+
+    /// The assisted bests in place of the clean ones, for comparing an
+    /// assisted play
+    pub fn assisted_view(&self) -> ScoreData {
+        let mut view = ScoreData::new(self.song_id);
+        for difficulty in Difficulty::ALL {
+            if let Some(best) = self.get_assisted(difficulty) {
+                view.set_lamp(difficulty, best.lamp);
+                view.set_score(difficulty, best.score);
+                view.set_miss_count(difficulty, best.miss_count);
+            }
+        }
+        view.assisted = self.assisted;
+        view
+    }
+}
+
+impl ScoreMap {
+    /// Whether any chart has an assisted best
+    pub fn has_assisted(&self) -> bool {
+        self.iter()
+            .any(|(_, data)| data.assisted.iter().any(Option::is_some))
+    }
+}
+
+/// Assisted bests recorded by the tracker, by chart
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AssistedBests {
+    charts: BTreeMap<ChartId, ChartBest>,
+}
+
+impl AssistedBests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the assisted bests file (empty when it doesn't exist yet)
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(e) => return Err(Error::storage(path, e)),
+        };
+        let raw: BTreeMap<String, ChartBest> = serde_json::from_str(&content)?;
+        let mut bests = Self::new();
+        for (key, best) in raw {
+            bests.charts.insert(key.parse()?, best);
+        }
+        Ok(bests)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let raw: BTreeMap<String, ChartBest> = self
+            .charts
+            .iter()
+            .map(|(chart, best)| (chart.to_string(), *best))
+            .collect();
+        write_atomic(path, serde_json::to_string_pretty(&raw)?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.charts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.charts.is_empty()
+    }
+
+    pub fn get(&self, chart: ChartId) -> Option<ChartBest> {
+        self.charts.get(&chart).copied()
+    }
+
+    /// Record an assisted play; whether the chart's assisted best improved
+    ///
+    /// Premature plays and plays without assist options are not recorded.
+    pub fn record(&mut self, play_data: &PlayData) -> bool {
+        if !play_data.is_assisted() || play_data.is_premature() {
+            return false;
+        }
+        let best = ChartBest {
+            lamp: play_data.lamp,
+            score: play_data.ex_score,
+            miss_count: play_data.miss_count_valid().then(|| play_data.miss_count()),
+        };
+        match self.charts.get_mut(&play_data.chart.id()) {
+            Some(current) => current.merge(best),
+            None => {
+                self.charts.insert(play_data.chart.id(), best);
+                true
+            }
+        }
+    }
+
+    /// Overlay the assisted bests on a score map read from the game
+    ///
+    /// The game records ASSIST CLEAR as the chart's lamp, which only assisted
+    /// plays reach; it moves to the assisted best and the clean lamp becomes
+    /// FAILED (the game keeps the higher lamp, so there is no clean clear).
+    pub fn apply(&self, score_map: &mut ScoreMap) {
+        let song_ids: Vec<u32> = score_map.iter().map(|(id, _)| *id).collect();
+        for song_id in song_ids {
+            let Some(data) = score_map.get_mut(song_id) else {
+                continue;
+            };
+            for difficulty in Difficulty::ALL {
+                if data.get_lamp(difficulty) == Lamp::AssistClear {
+                    data.set_lamp(difficulty, Lamp::Failed);
+                    data.record_assisted(
+                        difficulty,
+                        ChartBest {
+                            lamp: Lamp::AssistClear,
+                            ..ChartBest::default()
+                        },
+                    );
+                }
+            }
+        }
+        for (chart, best) in &self.charts {
+            score_map
+                .get_or_insert(chart.song_id)
+                .record_assisted(chart.difficulty, *best);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::play::test_support::PlayBuilder;
+    use crate::play::{AssistType, Settings};
+    use crate::score::Grade;
+
+    fn play(assist: AssistType, lamp: Lamp, ex_score: u32) -> PlayData {
+        PlayBuilder::new()
+            .title("Song")
+            .ex_score(ex_score)
+            .grade(Grade::A)
+            .lamp(lamp)
+            .settings(Settings {
+                assist,
+                ..Settings::default()
+            })
+            .data_available(assist == AssistType::Off)
+            .build()
+    }
+
+    #[test]
+    fn test_record_keeps_better_values() {
+        let mut bests = AssistedBests::new();
+        assert!(!bests.record(&play(AssistType::Off, Lamp::Clear, 1500)));
+        assert!(bests.record(&play(AssistType::AutoScratch, Lamp::AssistClear, 1200)));
+        assert!(bests.record(&play(AssistType::AutoScratch, Lamp::Failed, 1300)));
+        assert!(!bests.record(&play(AssistType::KeyAssist, Lamp::Failed, 1000)));
+
+        let chart = ChartId::new(1000, Difficulty::SpA);
+        assert_eq!(
+            bests.get(chart),
+            Some(ChartBest {
+                lamp: Lamp::AssistClear,
+                score: 1300,
+                miss_count: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_apply_keeps_clean_bests_apart() {
+        let mut score_map = ScoreMap::new();
+        let data = score_map.get_or_insert(1000);
+        data.set_lamp(Difficulty::SpA, Lamp::Clear);
+        data.set_score(Difficulty::SpA, 1500);
+        data.set_lamp(Difficulty::SpH, Lamp::AssistClear);
+
+        let mut bests = AssistedBests::new();
+        bests.record(&play(AssistType::AutoScratch, Lamp::HardClear, 1600));
+        bests.apply(&mut score_map);
+
+        let data = score_map.get(1000).unwrap();
+        assert_eq!(data.get_lamp(Difficulty::SpA), Lamp::Clear);
+        assert_eq!(data.get_score(Difficulty::SpA), 1500);
+        assert_eq!(
+            data.get_assisted(Difficulty::SpA).map(|best| best.score),
+            Some(1600)
+        );
+        assert_eq!(data.get_lamp(Difficulty::SpH), Lamp::Failed);
+        assert_eq!(
+            data.get_assisted(Difficulty::SpH).map(|best| best.lamp),
+            Some(Lamp::AssistClear)
+        );
+        assert!(score_map.has_assisted());
+
+        let view = score_map.get(1000).unwrap().assisted_view();
+        assert_eq!(view.get_lamp(Difficulty::SpA), Lamp::HardClear);
+        assert_eq!(view.get_lamp(Difficulty::SpN), Lamp::NoPlay);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(ASSISTED_BESTS_FILE);
+        assert!(AssistedBests::load(&path).unwrap().is_empty());
+
+        let mut bests = AssistedBests::new();
+        bests.record(&play(AssistType::AnyKey, Lamp::AssistClear, 900));
+        bests.save(&path).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains("\"1000:SPA\""));
+        assert_eq!(AssistedBests::load(&path).unwrap(), bests);
+    }
+}
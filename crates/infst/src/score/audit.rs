@@ -0,0 +1,248 @@
+//! Protection of stored bests against lower reads, and their audit trail
+//!
+//! The game never lowers a chart's best on its own, so a lower value read from
+//! memory is either a broken read or a different profile. The tracker keeps
+//! the bests it has seen in `bests.json` in the session directory (snapshot
+//! format, see [`super::delta`]) and never lets a read lower them
+//! ([`ScoreMap::keep_bests`]). Reads that are lower by a suspicious margin are
+//! appended to `score_audit.jsonl`, one JSON object per line.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{ChartBest, ScoreDelta, ScoreMap};
+use crate::chart::ChartId;
+use crate::error::{Error, Result};
+
+/// Stored bests file name inside the session directory
+pub const STORED_BESTS_FILE: &str = "bests.json";
+
+/// EX score drop from a stored best that is recorded in the audit trail
+pub const SUSPICIOUS_SCORE_DROP: u32 = 50;
+
+impl ScoreDelta {
+    /// Whether the new best is lower by a suspicious margin: a lower lamp,
+    /// or at least [`SUSPICIOUS_SCORE_DROP`] fewer EX score
+    pub fn is_suspicious(&self) -> bool {
+        self.after.lamp < self.before.lamp
+            || self.before.score >= self.after.score + SUSPICIOUS_SCORE_DROP
+    }
+}
+
+impl ScoreMap {
+    /// Raise every best below its `stored` value back to it; the charts that
+    /// read lower, `before` being the stored best and `after` the read one
+    pub fn keep_bests(&mut self, stored: &ScoreMap) -> Vec<ScoreDelta> {
+        let mut lowered = Vec::new();
+        let song_ids: Vec<u32> = stored.iter().map(|(id, _)| *id).collect();
+        for chart in song_ids.into_iter().flat_map(ChartId::all) {
+            let (kept, read) = (stored.best(chart), self.best(chart));
+            let delta = ScoreDelta {
+                chart,
+                before: kept,
+                after: read,
+            };
+            if !delta.is_regression() {
+                continue;
+            }
+            let data = self.get_or_insert(chart.song_id);
+            data.set_lamp(chart.difficulty, read.lamp.max(kept.lamp));
+            data.set_score(chart.difficulty, read.score.max(kept.score));
+            if let Some(miss_count) = kept.miss_count {
+                data.set_miss_count(
+                    chart.difficulty,
+                    Some(read.miss_count.map_or(miss_count, |m| m.min(miss_count))),
+                );
+            }
+            lowered.push(delta);
+        }
+        lowered.sort_by_key(|delta| delta.chart);
+        lowered
+    }
+
+    /// Load the stored bests (empty when the file doesn't exist yet)
+    pub fn load_stored_bests<P: AsRef<Path>>(path: P) -> Result<Self> {
+        match Self::load_snapshot(path) {
+            Err(e) if e.is_not_found() => Ok(Self::new()),
+            result => result,
+        }
+    }
+}
+
+/// A read lower than the stored best by a suspicious margin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub chart: ChartId,
+    /// Best kept by the tracker
+    pub stored: ChartBest,
+    /// Best read from the game
+    pub incoming: ChartBest,
+}
+
+impl AuditEntry {
+    pub fn new(timestamp: DateTime<Utc>, delta: &ScoreDelta) -> Self {
+        Self {
+            timestamp,
+            chart: delta.chart,
+            stored: delta.before,
+            incoming: delta.after,
+        }
+    }
+
+    /// The drop as a change from the stored to the incoming best
+    pub fn delta(&self) -> ScoreDelta {
+        ScoreDelta {
+            chart: self.chart,
+            before: self.stored,
+            after: self.incoming,
+        }
+    }
+}
+
+/// The audit trail file, `score_audit.jsonl` in the session directory
+#[derive(Debug, Clone)]
+pub struct ScoreAudit {
+    path: PathBuf,
+}
+
+impl ScoreAudit {
+    pub const FILE_NAME: &'static str = "score_audit.jsonl";
+
+    pub fn new<P: AsRef<Path>>(session_dir: P) -> Self {
+        Self {
+            path: session_dir.as_ref().join(Self::FILE_NAME),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append entries to the trail
+    pub fn append(&self, entries: &[AuditEntry]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| Error::storage(&self.path, e))?;
+        for entry in entries {
+            writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        }
+        Ok(())
+    }
+
+    /// All entries, oldest first (none when the file doesn't exist)
+    pub fn entries(&self) -> Result<Vec<AuditEntry>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(Error::storage(&self.path, e)),
+        };
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::Difficulty;
+    use crate::score::Lamp;
+
+    fn best(lamp: Lamp, score: u32, miss_count: Option<u32>) -> ChartBest {
+        ChartBest {
+            lamp,
+            score,
+            miss_count,
+        }
+    }
+
+    fn score_map(entries: &[(u32, Difficulty, ChartBest)]) -> ScoreMap {
+        let mut map = ScoreMap::new();
+        for &(song_id, difficulty, best) in entries {
+            let data = map.get_or_insert(song_id);
+            data.set_lamp(difficulty, best.lamp);
+            data.set_score(difficulty, best.score);
+            data.set_miss_count(difficulty, best.miss_count);
+        }
+        map
+    }
+
+    #[test]
+    fn test_keep_bests() {
+        let stored = score_map(&[
+            (1000, Difficulty::SpA, best(Lamp::HardClear, 1500, Some(10))),
+            (1001, Difficulty::SpH, best(Lamp::Clear, 1200, Some(20))),
+            (1002, Difficulty::SpN, best(Lamp::Clear, 800, None)),
+        ]);
+        let mut read = score_map(&[
+            (1000, Difficulty::SpA, best(Lamp::HardClear, 1520, Some(8))),
+            (1001, Difficulty::SpH, best(Lamp::Failed, 1190, Some(25))),
+        ]);
+
+        let lowered = read.keep_bests(&stored);
+        assert_eq!(lowered.len(), 2);
+        assert_eq!(lowered[0].chart, ChartId::new(1001, Difficulty::SpH));
+        assert!(lowered[0].is_suspicious());
+        assert_eq!(lowered[1].chart, ChartId::new(1002, Difficulty::SpN));
+        assert_eq!(lowered[1].after, ChartBest::default());
+
+        assert_eq!(
+            read.best(ChartId::new(1000, Difficulty::SpA)),
+            best(Lamp::HardClear, 1520, Some(8))
+        );
+        assert_eq!(
+            read.best(ChartId::new(1001, Difficulty::SpH)),
+            best(Lamp::Clear, 1200, Some(20))
+        );
+        assert_eq!(
+            read.best(ChartId::new(1002, Difficulty::SpN)),
+            best(Lamp::Clear, 800, None)
+        );
+        assert!(read.keep_bests(&stored).is_empty());
+    }
+
+    #[test]
+    fn test_is_suspicious() {
+        let delta = |before: ChartBest, after: ChartBest| ScoreDelta {
+            chart: ChartId::new(1000, Difficulty::SpA),
+            before,
+            after,
+        };
+        let stored = best(Lamp::Clear, 1500, Some(10));
+        assert!(!delta(stored, best(Lamp::Clear, 1451, Some(12))).is_suspicious());
+        assert!(delta(stored, best(Lamp::Clear, 1450, Some(10))).is_suspicious());
+        assert!(delta(stored, best(Lamp::Failed, 1500, Some(10))).is_suspicious());
+    }
+
+    #[test]
+    fn test_audit_append_and_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit = ScoreAudit::new(dir.path());
+        assert!(audit.entries().unwrap().is_empty());
+
+        let entry = AuditEntry {
+            timestamp: Utc::now(),
+            chart: ChartId::new(1000, Difficulty::SpA),
+            stored: best(Lamp::HardClear, 1500, None),
+            incoming: ChartBest::default(),
+        };
+        audit.append(&[entry]).unwrap();
+        audit.append(&[entry, entry]).unwrap();
+        assert_eq!(audit.entries().unwrap(), vec![entry; 3]);
+    }
+}
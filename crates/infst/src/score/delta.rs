@@ -0,0 +1,215 @@
+//! Score map snapshots and the changes between two of them
+//!
+//! A snapshot is the score map written to JSON, keyed by chart
+//! (`song_id:DIFFICULTY`) with only played charts listed:
+//!
+//! ```json
+//! {
+//!   "1000:SPA": { "lamp": "HardClear", "score": 1850, "miss_count": 12 }
+//! }
+//! ```
+//!
+//! Comparing snapshots taken before and after a session shows what the game
+//! recorded as bests, independent of what the tracker saw.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Lamp, ScoreMap};
+use crate::chart::ChartId;
+use crate::error::{Error, Result};
+
+/// A chart's in-game best in a snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ChartBest {
+    pub lamp: Lamp,
+    pub score: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub miss_count: Option<u32>,
+}
+
+impl ChartBest {
+    pub(crate) fn is_played(&self) -> bool {
+        self.lamp != Lamp::NoPlay || self.score > 0 || self.miss_count.is_some()
+    }
+}
+
+/// A chart whose in-game best differs between two score maps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ScoreDelta {
+    pub chart: ChartId,
+    pub before: ChartBest,
+    pub after: ChartBest,
+}
+
+impl ScoreDelta {
+    /// EX score change (negative when the best went down)
+    pub fn score_change(&self) -> i64 {
+        i64::from(self.after.score) - i64::from(self.before.score)
+    }
+
+    /// Whether any value got worse, which the game never does on its own
+    /// (a different profile or a broken read)
+    pub fn is_regression(&self) -> bool {
+        self.after.lamp < self.before.lamp
+            || self.after.score < self.before.score
+            || matches!(
+                (self.before.miss_count, self.after.miss_count),
+                (Some(before), Some(after)) if after > before
+            )
+    }
+}
+
+impl fmt::Display for ScoreDelta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (before, after) = (&self.before, &self.after);
+        let mut changes = Vec::new();
+        if before.lamp != after.lamp {
+            changes.push(format!("{} -> {}", before.lamp, after.lamp));
+        }
+        if before.score != after.score {
+            changes.push(format!(
+                "EX {} -> {} ({:+})",
+                before.score,
+                after.score,
+                self.score_change()
+            ));
+        }
+        if before.miss_count != after.miss_count {
+            let miss = |m: Option<u32>| m.map_or("-".to_string(), |m| m.to_string());
+            changes.push(format!(
+                "MISS {} -> {}",
+                miss(before.miss_count),
+                miss(after.miss_count)
+            ));
+        }
+        write!(f, "{}  {}", self.chart, changes.join(", "))
+    }
+}
+
+impl ScoreMap {
+    /// Best of one chart
+    pub fn best(&self, chart: ChartId) -> ChartBest {
+        ChartBest {
+            lamp: self.lamp(chart),
+            score: self.score(chart),
+            miss_count: self.miss_count(chart),
+        }
+    }
+
+    /// Charts whose best changed since `old`, sorted by chart
+    pub fn diff(&self, old: &ScoreMap) -> Vec<ScoreDelta> {
+        let songs: BTreeSet<u32> = self.iter().chain(old.iter()).map(|(id, _)| *id).collect();
+        songs
+            .into_iter()
+            .flat_map(ChartId::all)
+            .filter_map(|chart| {
+                let (before, after) = (old.best(chart), self.best(chart));
+                (before != after).then_some(ScoreDelta {
+                    chart,
+                    before,
+                    after,
+                })
+            })
+            .collect()
+    }
+
+    /// Parse a snapshot's content
+    pub fn from_json(content: &str) -> Result<Self> {
+        let raw: BTreeMap<String, ChartBest> = serde_json::from_str(content)?;
+        let mut map = Self::new();
+        for (key, best) in raw {
+            let chart: ChartId = key.parse()?;
+            let data = map.get_or_insert(chart.song_id);
+            data.set_lamp(chart.difficulty, best.lamp);
+            data.set_score(chart.difficulty, best.score);
+            data.set_miss_count(chart.difficulty, best.miss_count);
+        }
+        Ok(map)
+    }
+
+    /// Load a snapshot file
+    pub fn load_snapshot<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| Error::storage(path, e))?;
+        Self::from_json(&content)
+    }
+
+    /// Serialize to the snapshot format (played charts, sorted by chart)
+    pub fn to_json(&self) -> Result<String> {
+        let sorted: BTreeMap<String, ChartBest> = self
+            .iter()
+            .flat_map(|(id, _)| ChartId::all(*id))
+            .map(|chart| (chart, self.best(chart)))
+            .filter(|(_, best)| best.is_played())
+            .map(|(chart, best)| (chart.to_string(), best))
+            .collect();
+        Ok(serde_json::to_string_pretty(&sorted)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::Difficulty;
+
+    fn score_map(entries: &[(u32, Difficulty, Lamp, u32)]) -> ScoreMap {
+        let mut map = ScoreMap::new();
+        for &(song_id, difficulty, lamp, score) in entries {
+            let data = map.get_or_insert(song_id);
+            data.set_lamp(difficulty, lamp);
+            data.set_score(difficulty, score);
+        }
+        map
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = score_map(&[
+            (1000, Difficulty::SpA, Lamp::Clear, 1500),
+            (1001, Difficulty::SpH, Lamp::HardClear, 1200),
+        ]);
+        let new = score_map(&[
+            (1000, Difficulty::SpA, Lamp::HardClear, 1550),
+            (1001, Difficulty::SpH, Lamp::HardClear, 1200),
+            (1002, Difficulty::SpN, Lamp::Failed, 400),
+        ]);
+
+        let deltas = new.diff(&old);
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[0].chart, ChartId::new(1000, Difficulty::SpA));
+        assert_eq!(deltas[0].score_change(), 50);
+        assert_eq!(
+            deltas[0].to_string(),
+            "1000:SPA  CLEAR -> HARD, EX 1500 -> 1550 (+50)"
+        );
+        assert_eq!(deltas[1].before, ChartBest::default());
+        assert!(!deltas[1].is_regression());
+
+        let reverse = old.diff(&new);
+        assert!(reverse.iter().all(ScoreDelta::is_regression));
+        assert!(new.diff(&new).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut map = score_map(&[(1000, Difficulty::SpA, Lamp::ExHardClear, 1800)]);
+        map.get_or_insert(1000)
+            .set_miss_count(Difficulty::SpA, Some(3));
+        let json = map.to_json().unwrap();
+        assert!(json.contains("\"1000:SPA\""));
+        assert!(!json.contains("SPB"));
+
+        let loaded = ScoreMap::from_json(&json).unwrap();
+        assert!(loaded.diff(&map).is_empty());
+        assert_eq!(
+            loaded.miss_count(ChartId::new(1000, Difficulty::SpA)),
+            Some(3)
+        );
+        assert!(ScoreMap::from_json(r#"{"1000": {"lamp": "Clear", "score": 1}}"#).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use strum::{FromRepr, IntoStaticStr};
+use strum::{EnumString, FromRepr, IntoStaticStr};
 
 #[derive(
     Debug,
@@ -14,6 +14,7 @@ use strum::{FromRepr, IntoStaticStr};
     Deserialize,
     Default,
     FromRepr,
+    EnumString,
     IntoStaticStr,
 )]
 #[repr(u8)]
@@ -61,6 +62,29 @@ impl Grade {
     pub fn short_name(&self) -> &'static str {
         self.into()
     }
+
+    /// Minimum EX score for this grade on a chart with `total_notes` notes
+    pub fn min_score(&self, total_notes: u32) -> u32 {
+        match self {
+            Self::NoPlay | Self::F => 0,
+            _ => (total_notes * 2 * *self as u32).div_ceil(9),
+        }
+    }
+
+    /// EX score boundaries for every grade from F to AAA
+    pub fn thresholds(total_notes: u32) -> [(Grade, u32); 8] {
+        [
+            Self::F,
+            Self::E,
+            Self::D,
+            Self::C,
+            Self::B,
+            Self::A,
+            Self::Aa,
+            Self::Aaa,
+        ]
+        .map(|grade| (grade, grade.min_score(total_notes)))
+    }
 }
 
 impl std::fmt::Display for Grade {
@@ -86,4 +110,24 @@ mod tests {
         assert_eq!(Grade::from_score_ratio(2.0 / 9.0), Grade::E);
         assert_eq!(Grade::from_score_ratio(0.1), Grade::F);
     }
+
+    #[test]
+    fn test_grade_thresholds() {
+        let thresholds = Grade::thresholds(1000);
+        assert_eq!(thresholds[0], (Grade::F, 0));
+        assert_eq!(thresholds[5], (Grade::A, 1334));
+        assert_eq!(thresholds[6], (Grade::Aa, 1556));
+        assert_eq!(thresholds[7], (Grade::Aaa, 1778));
+
+        // Each boundary must map back to its own grade
+        for total_notes in [500, 900, 1000, 1234, 2001] {
+            let max_ex = (total_notes * 2) as f64;
+            for (grade, score) in Grade::thresholds(total_notes) {
+                assert_eq!(Grade::from_score_ratio(score as f64 / max_ex), grade);
+                if score > 0 {
+                    assert!(Grade::from_score_ratio((score - 1) as f64 / max_ex) < grade);
+                }
+            }
+        }
+    }
 }
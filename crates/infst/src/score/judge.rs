@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 
 use crate::play::PlayType;
+use crate::process::layout::judge;
+use crate::process::view::{FromBytes, field};
 
 /// Raw judge data for a single player side (P1 or P2)
 #[derive(Debug, Clone, Default)]
@@ -30,8 +32,40 @@ pub struct RawJudgeData {
     pub p2: PlayerJudge,
 }
 
+impl FromBytes for RawJudgeData {
+    const SIZE: usize = judge::BLOCK_SIZE;
+
+    fn from_le_slice(bytes: &[u8]) -> Self {
+        let word = |offset: u64| field::<u32>(bytes, offset as usize);
+        Self {
+            p1: PlayerJudge {
+                pgreat: word(judge::P1_PGREAT),
+                great: word(judge::P1_GREAT),
+                good: word(judge::P1_GOOD),
+                bad: word(judge::P1_BAD),
+                poor: word(judge::P1_POOR),
+                combo_break: word(judge::P1_COMBO_BREAK),
+                fast: word(judge::P1_FAST),
+                slow: word(judge::P1_SLOW),
+                measure_end: word(judge::P1_MEASURE_END),
+            },
+            p2: PlayerJudge {
+                pgreat: word(judge::P2_PGREAT),
+                great: word(judge::P2_GREAT),
+                good: word(judge::P2_GOOD),
+                bad: word(judge::P2_BAD),
+                poor: word(judge::P2_POOR),
+                combo_break: word(judge::P2_COMBO_BREAK),
+                fast: word(judge::P2_FAST),
+                slow: word(judge::P2_SLOW),
+                measure_end: word(judge::P2_MEASURE_END),
+            },
+        }
+    }
+}
+
 /// Judge information from a play
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Judge {
     pub play_type: PlayType,
     pub pgreat: u32,
@@ -57,7 +91,7 @@ impl Judge {
 
     /// Calculate EX score (pgreat * 2 + great)
     pub fn ex_score(&self) -> u32 {
-        self.pgreat * 2 + self.great
+        self.pgreat.saturating_mul(2).saturating_add(self.great)
     }
 
     /// Calculate miss count (bad + poor)
@@ -97,6 +131,23 @@ impl Judge {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_raw_judge_data_from_bytes() {
+        let words: Vec<u8> = (1..=18u32).flat_map(u32::to_le_bytes).collect();
+        let raw = RawJudgeData::from_le_slice(&words);
+
+        assert_eq!(raw.p1.pgreat, 1);
+        assert_eq!(raw.p1.poor, 5);
+        assert_eq!(raw.p2.pgreat, 6);
+        assert_eq!(raw.p1.combo_break, 11);
+        assert_eq!(raw.p2.combo_break, 12);
+        assert_eq!(
+            (raw.p1.fast, raw.p2.fast, raw.p1.slow, raw.p2.slow),
+            (13, 14, 15, 16)
+        );
+        assert_eq!((raw.p1.measure_end, raw.p2.measure_end), (17, 18));
+    }
+
     #[test]
     fn test_player_judge_total_notes() {
         let pj = PlayerJudge {
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use strum::{FromRepr, IntoStaticStr};
+use strum::{EnumString, FromRepr, IntoStaticStr};
 
 #[derive(
     Debug,
@@ -14,6 +14,7 @@ use strum::{FromRepr, IntoStaticStr};
     Deserialize,
     Default,
     FromRepr,
+    EnumString,
     IntoStaticStr,
 )]
 #[repr(u8)]
@@ -5,13 +5,25 @@
 //! - `Lamp` - clear lamps (NO PLAY, FAILED, ASSIST, EASY, CLEAR, HARD, EX HARD, FC)
 //! - `Judge` - judge data from a play
 //! - `ScoreData`, `ScoreMap` - score storage
+//! - `ScoreDelta` - changed bests between two score maps (snapshots)
+//! - `AssistedBests` - bests of assisted plays, kept apart from clean bests
+//! - `ScoreAudit` - reads lower than the stored bests
+//! - `ProfileMismatch` - a score map that looks like another player's
 
+mod assisted;
+mod audit;
+mod delta;
 mod grade;
 mod judge;
 mod lamp;
+mod profile_switch;
 mod score_map;
 
+pub use assisted::*;
+pub use audit::*;
+pub use delta::*;
 pub use grade::*;
 pub use judge::*;
 pub use lamp::*;
+pub use profile_switch::*;
 pub use score_map::*;
@@ -0,0 +1,110 @@
+//! Detection of another player's profile in the game
+//!
+//! When a different account logs in on the same PC, most charts the tracker
+//! has stored bests for read lower at once. A few lower charts are bad reads
+//! ([`super::audit`]); a large share of them means the score map belongs to
+//! someone else, and merging it into the stored bests would mix two players'
+//! scores.
+
+use super::{ScoreDelta, ScoreMap};
+use crate::chart::ChartId;
+
+/// Share of stored charts reading lower above which the game is taken to
+/// hold another profile
+pub const PROFILE_SWITCH_RATIO: f64 = 0.5;
+
+/// Played charts the stored bests need before a switch is detected
+pub const PROFILE_SWITCH_MIN_CHARTS: usize = 20;
+
+/// How far a score map read from the game diverges from the stored bests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProfileMismatch {
+    /// Charts with a stored best
+    pub compared: usize,
+    /// Of those, charts that read lower
+    pub lowered: usize,
+}
+
+impl ProfileMismatch {
+    /// Share of compared charts that read lower (0 when nothing was compared)
+    pub fn ratio(&self) -> f64 {
+        if self.compared == 0 {
+            return 0.0;
+        }
+        self.lowered as f64 / self.compared as f64
+    }
+
+    /// Whether the score map looks like another player's
+    pub fn is_switch(&self) -> bool {
+        self.compared >= PROFILE_SWITCH_MIN_CHARTS && self.ratio() >= PROFILE_SWITCH_RATIO
+    }
+}
+
+impl ScoreMap {
+    /// Compare against the stored bests, chart by chart
+    pub fn profile_mismatch(&self, stored: &ScoreMap) -> ProfileMismatch {
+        let mut mismatch = ProfileMismatch::default();
+        for (&song_id, _) in stored.iter() {
+            for chart in ChartId::all(song_id) {
+                let before = stored.best(chart);
+                if !before.is_played() {
+                    continue;
+                }
+                mismatch.compared += 1;
+                let delta = ScoreDelta {
+                    chart,
+                    before,
+                    after: self.best(chart),
+                };
+                if delta.is_regression() {
+                    mismatch.lowered += 1;
+                }
+            }
+        }
+        mismatch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::Difficulty;
+    use crate::score::Lamp;
+
+    fn score_map(songs: std::ops::Range<u32>, lamp: Lamp, score: u32) -> ScoreMap {
+        let mut map = ScoreMap::new();
+        for song_id in songs {
+            let data = map.get_or_insert(song_id);
+            data.set_lamp(Difficulty::SpA, lamp);
+            data.set_score(Difficulty::SpA, score);
+        }
+        map
+    }
+
+    #[test]
+    fn test_profile_mismatch() {
+        let stored = score_map(1000..1030, Lamp::HardClear, 1500);
+
+        let read = score_map(1000..1030, Lamp::FullCombo, 1600);
+        let mismatch = read.profile_mismatch(&stored);
+        assert_eq!(mismatch.compared, 30);
+        assert_eq!(mismatch.lowered, 0);
+        assert!(!mismatch.is_switch());
+
+        let mut other = score_map(1000..1010, Lamp::HardClear, 1500);
+        for (&song_id, data) in score_map(1010..1030, Lamp::Clear, 900).iter() {
+            other.insert(song_id, data.clone());
+        }
+        let mismatch = other.profile_mismatch(&stored);
+        assert_eq!(mismatch.lowered, 20);
+        assert!(mismatch.is_switch());
+
+        // Too few stored charts to tell
+        let few = score_map(1000..1010, Lamp::HardClear, 1500);
+        assert!(!ScoreMap::new().profile_mismatch(&few).is_switch());
+        assert_eq!(
+            ScoreMap::new().profile_mismatch(&ScoreMap::new()).ratio(),
+            0.0
+        );
+    }
+}
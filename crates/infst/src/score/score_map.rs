@@ -1,9 +1,9 @@
 use std::collections::{HashMap, HashSet};
 
-use crate::chart::{Difficulty, SongInfo};
-use crate::error::Result;
+use crate::chart::{ChartId, Difficulty, SongInfo};
+use crate::error::{Error, Result};
 use crate::process::{ByteBuffer, ReadMemory};
-use crate::score::Lamp;
+use crate::score::{ChartBest, Lamp};
 
 /// Score data for a single song (all difficulties)
 #[derive(Debug, Clone, Default)]
@@ -17,6 +17,9 @@ pub struct ScoreData {
     pub miss_count: [Option<u32>; 10],
     /// DJ Points for each difficulty
     pub dj_points: [f64; 10],
+    /// Best of assisted plays for each difficulty, recorded by the tracker
+    /// (see [`AssistedBests`](super::AssistedBests))
+    pub assisted: [Option<ChartBest>; 10],
 }
 
 impl ScoreData {
@@ -28,48 +31,52 @@ impl ScoreData {
     }
 
     pub fn get_lamp(&self, difficulty: Difficulty) -> Lamp {
-        self.lamp
-            .get(difficulty as usize)
-            .copied()
-            .unwrap_or(Lamp::NoPlay)
+        self.lamp[difficulty.index()]
     }
 
     pub fn get_score(&self, difficulty: Difficulty) -> u32 {
-        self.score.get(difficulty as usize).copied().unwrap_or(0)
+        self.score[difficulty.index()]
+    }
+
+    pub fn get_miss_count(&self, difficulty: Difficulty) -> Option<u32> {
+        self.miss_count[difficulty.index()]
     }
 
     pub fn set_lamp(&mut self, difficulty: Difficulty, lamp: Lamp) {
-        if let Some(slot) = self.lamp.get_mut(difficulty as usize) {
-            *slot = lamp;
-        }
+        self.lamp[difficulty.index()] = lamp;
     }
 
     pub fn set_score(&mut self, difficulty: Difficulty, score: u32) {
-        if let Some(slot) = self.score.get_mut(difficulty as usize) {
-            *slot = score;
-        }
+        self.score[difficulty.index()] = score;
+    }
+
+    pub fn set_miss_count(&mut self, difficulty: Difficulty, miss_count: Option<u32>) {
+        self.miss_count[difficulty.index()] = miss_count;
     }
 }
 
 /// A node in the INFINITAS score hashmap linked list
+///
+/// One node holds the best score for one chart; [`ScoreMap::from_nodes`]
+/// builds a score map from decoded nodes.
 #[derive(Debug, Clone, Default)]
-struct ListNode {
-    next: u64,
-    /// Previous node pointer (unused but required for memory layout compatibility)
-    #[allow(dead_code)]
-    prev: u64,
-    diff: i32,
-    song: i32,
-    playtype: i32,
-    score: u32,
-    miss_count: u32,
-    lamp: i32,
+pub struct ListNode {
+    pub next: u64,
+    /// Previous node pointer (only used by the raw debug dump)
+    pub prev: u64,
+    pub diff: i32,
+    pub song: i32,
+    pub playtype: i32,
+    pub score: u32,
+    pub miss_count: u32,
+    pub lamp: i32,
 }
 
 impl ListNode {
-    const SIZE: usize = 64;
+    pub const SIZE: usize = 64;
 
-    fn from_bytes(bytes: &[u8]) -> Self {
+    /// Decode a node from its 64 bytes; missing fields read as zero
+    pub fn from_bytes(bytes: &[u8]) -> Self {
         let buf = ByteBuffer::new(bytes);
         Self {
             next: buf.read_u64_at(0).unwrap_or(0),
@@ -85,8 +92,73 @@ impl ListNode {
         }
     }
 
-    fn key(&self) -> (u32, i32, i32) {
-        (self.song as u32, self.diff, self.playtype)
+    /// Chart this node holds the score for, `None` for out-of-range fields
+    pub fn chart_id(&self) -> Option<ChartId> {
+        let song_id = u32::try_from(self.song).ok()?;
+        Difficulty::from_tier(self.diff, self.playtype).map(|d| ChartId::new(song_id, d))
+    }
+}
+
+/// Largest bucket array accepted, far above the real table size; larger
+/// values come from a wrong `data_map` offset
+const MAX_TABLE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Head of the score hashmap: null object and bucket entry points
+pub(crate) struct HashTable {
+    /// Sentinel node address used to mark empty buckets and chain ends
+    pub(crate) null_obj: u64,
+    #[cfg_attr(not(feature = "debug-tools"), allow(dead_code))]
+    pub(crate) start_address: u64,
+    #[cfg_attr(not(feature = "debug-tools"), allow(dead_code))]
+    pub(crate) end_address: u64,
+    /// Non-empty buckets as (bucket index, first node address)
+    pub(crate) entry_points: Vec<(usize, u64)>,
+}
+
+impl HashTable {
+    pub(crate) fn read<R: ReadMemory + ?Sized>(reader: &R, data_map_addr: u64) -> Result<Self> {
+        // Null object address (used to skip empty entries), then the start
+        // and end addresses of the hash table, fetched in one batch
+        let header = reader
+            .read_many(&[
+                (data_map_addr.wrapping_sub(16), 8),
+                (data_map_addr, 8),
+                (data_map_addr.wrapping_add(8), 8),
+            ])
+            .into_iter()
+            .map(|bytes| bytes.and_then(|bytes| ByteBuffer::new(&bytes).read_u64_at(0)))
+            .collect::<Result<Vec<u64>>>()?;
+        let (null_obj, start_address, end_address) = (header[0], header[1], header[2]);
+
+        let mut entry_points = Vec::new();
+        if end_address > start_address {
+            let buffer_size = (end_address - start_address) as usize;
+            if buffer_size > MAX_TABLE_SIZE {
+                return Err(Error::parse(
+                    "score map",
+                    format!("hash table of {buffer_size} bytes exceeds {MAX_TABLE_SIZE}"),
+                ));
+            }
+            let buffer = reader.read_bytes(start_address, buffer_size)?;
+
+            // Collect entry points from the hash table
+            let buf = ByteBuffer::new(&buffer);
+            for i in 0..(buffer_size / 8) {
+                let addr = buf.read_u64_at(i * 8).unwrap_or(0);
+
+                // Skip null entries and magic number entries
+                if addr != 0 && addr != null_obj && addr != 0x494fdce0 {
+                    entry_points.push((i, addr));
+                }
+            }
+        }
+
+        Ok(Self {
+            null_obj,
+            start_address,
+            end_address,
+            entry_points,
+        })
     }
 }
 
@@ -107,105 +179,100 @@ impl ScoreMap {
         data_map_addr: u64,
         song_db: &HashMap<u32, SongInfo>,
     ) -> Result<Self> {
-        let mut nodes: HashMap<(u32, i32, i32), ListNode> = HashMap::new();
-
-        // Read null object address (used to skip empty entries)
-        let null_obj = reader.read_u64(data_map_addr.wrapping_sub(16))?;
-
-        // Read start and end addresses of the hash table
-        let start_address = reader.read_u64(data_map_addr)?;
-        let end_address = reader.read_u64(data_map_addr + 8)?;
-
-        if end_address <= start_address {
-            return Ok(Self::new());
-        }
+        let table = HashTable::read(reader, data_map_addr)?;
 
-        let buffer_size = (end_address - start_address) as usize;
-        let buffer = reader.read_bytes(start_address, buffer_size)?;
-
-        // Collect entry points from the hash table
-        let buf = ByteBuffer::new(&buffer);
-        let mut entry_points = Vec::new();
-        for i in 0..(buffer_size / 8) {
-            let addr = buf.read_u64_at(i * 8).unwrap_or(0);
+        // Follow linked lists from each entry point; the first node seen for a
+        // key wins, in bucket order
+        let chains =
+            Self::follow_linked_lists(reader, &table.entry_points, table.null_obj, song_db);
+        Ok(Self::from_nodes(chains.into_iter().flatten()))
+    }
 
-            // Skip null entries and magic number entries
-            if addr != 0 && addr != null_obj && addr != 0x494fdce0 {
-                entry_points.push(addr);
+    /// Build a score map from decoded hashmap nodes
+    ///
+    /// When several nodes share a chart, the first one wins. Nodes with an
+    /// out-of-range difficulty are ignored.
+    pub fn from_nodes(nodes: impl IntoIterator<Item = ListNode>) -> Self {
+        let mut unique: HashMap<ChartId, ListNode> = HashMap::new();
+        for node in nodes {
+            if let Some(chart) = node.chart_id() {
+                unique.entry(chart).or_insert(node);
             }
         }
 
-        // Follow linked lists from each entry point
-        for entry_point in entry_points {
-            Self::follow_linked_list(reader, entry_point, null_obj, song_db, &mut nodes);
-        }
-
         // Convert nodes to ScoreData
         let mut result = Self::new();
-        for ((song_id, diff, playtype), node) in nodes {
-            // Calculate difficulty index: diff + playtype * 5
-            let difficulty_index = (diff + playtype * 5) as usize;
-            if difficulty_index >= 10 {
-                continue;
-            }
-
-            let score_data = result.get_or_insert(song_id);
-            score_data.lamp[difficulty_index] =
-                Lamp::from_u8(node.lamp as u8).unwrap_or(Lamp::NoPlay);
-            score_data.score[difficulty_index] = node.score;
+        for (chart, node) in unique {
+            let score_data = result.get_or_insert(chart.song_id);
+            score_data.set_lamp(
+                chart.difficulty,
+                Lamp::from_u8(node.lamp as u8).unwrap_or(Lamp::NoPlay),
+            );
+            score_data.set_score(chart.difficulty, node.score);
             // INFINITAS uses u32::MAX as sentinel value to indicate miss_count data is unavailable
             // (e.g., for legacy scores or when the game doesn't track this information)
-            score_data.miss_count[difficulty_index] = if node.miss_count == u32::MAX {
-                None
-            } else {
-                Some(node.miss_count)
-            };
+            let miss_count = (node.miss_count != u32::MAX).then_some(node.miss_count);
+            score_data.set_miss_count(chart.difficulty, miss_count);
         }
 
-        Ok(result)
+        result
     }
 
-    fn follow_linked_list<R: ReadMemory>(
+    /// Walk every bucket chain, returning each chain's nodes in list order
+    ///
+    /// Chains are advanced in lockstep so that each step issues a single
+    /// batched `read_many` for the current node of every live chain, instead
+    /// of one read per node.
+    fn follow_linked_lists<R: ReadMemory>(
         reader: &R,
-        entry_point: u64,
+        entry_points: &[(usize, u64)],
         null_obj: u64,
         song_db: &HashMap<u32, SongInfo>,
-        nodes: &mut HashMap<(u32, i32, i32), ListNode>,
-    ) {
-        let mut visited: HashSet<u64> = HashSet::new();
-        let mut current_addr = entry_point;
-
-        loop {
+    ) -> Vec<Vec<ListNode>> {
+        let mut chains: Vec<Vec<ListNode>> = vec![Vec::new(); entry_points.len()];
+        let mut visited: Vec<HashSet<u64>> = vec![HashSet::new(); entry_points.len()];
+        // (chain index, address of the node to read next)
+        let mut frontier: Vec<(usize, u64)> = entry_points
+            .iter()
+            .enumerate()
+            .map(|(chain, &(_, addr))| (chain, addr))
+            .collect();
+
+        while !frontier.is_empty() {
             // Prevent infinite loops
-            if visited.contains(&current_addr) {
-                break;
-            }
-            visited.insert(current_addr);
-
-            // Read error = end of chain (not a fatal error)
-            let Ok(buffer) = reader.read_bytes(current_addr, ListNode::SIZE) else {
-                break;
-            };
-            let node = ListNode::from_bytes(&buffer);
-            let song_id = node.song as u32;
-            let next_addr = node.next;
-
-            // Break on unknown songs (matches C# reference behavior)
-            // Score map is reloaded when new songs are discovered (Fix 3)
-            if !song_db.contains_key(&song_id) {
-                break;
-            }
-
-            if let std::collections::hash_map::Entry::Vacant(e) = nodes.entry(node.key()) {
-                e.insert(node);
+            frontier.retain(|&(chain, addr)| visited[chain].insert(addr));
+
+            let ranges: Vec<(u64, usize)> = frontier
+                .iter()
+                .map(|&(_, addr)| (addr, ListNode::SIZE))
+                .collect();
+            let buffers = reader.read_many(&ranges);
+
+            let mut next_frontier = Vec::with_capacity(frontier.len());
+            for (&(chain, _), buffer) in frontier.iter().zip(buffers) {
+                // Read error = end of chain (not a fatal error)
+                let Ok(buffer) = buffer else {
+                    continue;
+                };
+                let node = ListNode::from_bytes(&buffer);
+                let next_addr = node.next;
+
+                // Break on unknown songs (matches C# reference behavior)
+                // Score map is reloaded when new songs are discovered (Fix 3)
+                if !song_db.contains_key(&(node.song as u32)) {
+                    continue;
+                }
+                chains[chain].push(node);
+
+                // Check for end of linked list
+                if next_addr != 0 && next_addr != null_obj {
+                    next_frontier.push((chain, next_addr));
+                }
             }
-
-            // Check for end of linked list
-            if next_addr == 0 || next_addr == null_obj {
-                break;
-            }
-            current_addr = next_addr;
+            frontier = next_frontier;
         }
+
+        chains
     }
 
     pub fn get(&self, song_id: u32) -> Option<&ScoreData> {
@@ -226,6 +293,24 @@ impl ScoreMap {
             .or_insert_with(|| ScoreData::new(song_id))
     }
 
+    /// Lamp of a chart (`NoPlay` for songs without scores)
+    pub fn lamp(&self, chart: ChartId) -> Lamp {
+        self.get(chart.song_id)
+            .map_or(Lamp::NoPlay, |s| s.get_lamp(chart.difficulty))
+    }
+
+    /// EX score of a chart (0 for songs without scores)
+    pub fn score(&self, chart: ChartId) -> u32 {
+        self.get(chart.song_id)
+            .map_or(0, |s| s.get_score(chart.difficulty))
+    }
+
+    /// Miss count of a chart, when recorded
+    pub fn miss_count(&self, chart: ChartId) -> Option<u32> {
+        self.get(chart.song_id)
+            .and_then(|s| s.get_miss_count(chart.difficulty))
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&u32, &ScoreData)> {
         self.scores.iter()
     }
@@ -272,14 +357,24 @@ mod tests {
 
     #[test]
     fn test_list_node_difficulty_index_calculation() {
-        // Test that diff + playtype * 5 gives correct indices
         // SP difficulties: diff 0-4 + playtype 0 = indices 0-4
         // DP difficulties: diff 0-4 + playtype 1 = indices 5-9
-        assert_eq!(0, 0); // SPB: 0 + 0 * 5
-        assert_eq!(1, 1); // SPN: 1 + 0 * 5
-        assert_eq!(3, 3); // SPA: 3 + 0 * 5
-        assert_eq!(5, 5); // DPB: 0 + 1 * 5
-        assert_eq!(8, 8); // DPA: 3 + 1 * 5
+        let index = |diff, playtype| {
+            let node = ListNode {
+                song: 1000,
+                diff,
+                playtype,
+                ..Default::default()
+            };
+            node.chart_id().map(ChartId::index)
+        };
+        assert_eq!(index(0, 0), Some(0)); // SPB
+        assert_eq!(index(1, 0), Some(1)); // SPN
+        assert_eq!(index(3, 0), Some(3)); // SPA
+        assert_eq!(index(0, 1), Some(5)); // DPB
+        assert_eq!(index(3, 1), Some(8)); // DPA
+        assert_eq!(index(5, 0), None);
+        assert_eq!(index(0, 2), None);
     }
 
     /// Test ScoreMap::load_from_memory with a simple mock setup
@@ -317,6 +412,63 @@ mod tests {
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn test_load_from_memory_follows_chains() {
+        // Layout (offsets from base - 16):
+        //   0: null_obj, 16: table_start, 24: table_end
+        //   32..48: two buckets
+        //   64..: nodes (64 bytes each)
+        let base = 0x1000u64;
+        let mem = base - 16;
+        let null_obj = 0xDEAD_0000u64;
+        let node_addr = |i: u64| mem + 64 + i * ListNode::SIZE as u64;
+        let node =
+            |builder: MockMemoryBuilder, i: u64, next: u64, song: i32, diff: i32, score: u32| {
+                let offset = (64 + i * ListNode::SIZE as u64) as usize;
+                builder
+                    .write_u64(offset, next)
+                    .write_i32(offset + 16, diff)
+                    .write_i32(offset + 20, song)
+                    .write_i32(offset + 24, 0)
+                    .write_u32(offset + 32, score)
+                    .write_u32(offset + 36, u32::MAX)
+                    .write_i32(offset + 48, Lamp::HardClear as i32)
+            };
+
+        let mut builder = MockMemoryBuilder::new()
+            .base(mem)
+            .with_size(64 + 5 * ListNode::SIZE)
+            .write_u64(0, null_obj)
+            .write_u64(16, base + 16)
+            .write_u64(24, base + 32)
+            .write_u64(32, node_addr(0))
+            .write_u64(40, node_addr(2));
+        // Bucket 0: 1000/SPN -> 1001/SPH -> end
+        builder = node(builder, 0, node_addr(1), 1000, 1, 100);
+        builder = node(builder, 1, null_obj, 1001, 2, 200);
+        // Bucket 1: 1002/SPA -> unknown song (stops) -> 1003 (never reached)
+        builder = node(builder, 2, node_addr(3), 1002, 3, 300);
+        builder = node(builder, 3, node_addr(4), 4242, 3, 999);
+        builder = node(builder, 4, null_obj, 1003, 3, 400);
+        let reader = builder.build();
+
+        let song_db: HashMap<u32, SongInfo> = [1000, 1001, 1002, 1003]
+            .into_iter()
+            .map(|id| (id, SongInfo::default()))
+            .collect();
+
+        let result = ScoreMap::load_from_memory(&reader, base, &song_db).unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result.get(1000).unwrap().get_score(Difficulty::SpN), 100);
+        assert_eq!(result.get(1001).unwrap().get_score(Difficulty::SpH), 200);
+        assert_eq!(
+            result.get(1002).unwrap().get_lamp(Difficulty::SpA),
+            Lamp::HardClear
+        );
+        assert_eq!(result.get(1002).unwrap().miss_count[3], None);
+        assert!(result.get(1003).is_none());
+    }
+
     #[test]
     fn test_score_data_get_set() {
         let mut data = ScoreData::new(1000);
@@ -359,6 +511,33 @@ mod tests {
         assert_eq!(map.len(), 1);
     }
 
+    #[test]
+    fn test_from_nodes_first_wins() {
+        let node = |score, lamp| ListNode {
+            song: 1000,
+            diff: 3,
+            playtype: 0,
+            score,
+            miss_count: u32::MAX,
+            lamp,
+            ..ListNode::default()
+        };
+        let map = ScoreMap::from_nodes([
+            node(1500, 4),
+            node(1200, 2),
+            ListNode {
+                diff: 7,
+                ..node(1, 1)
+            },
+        ]);
+
+        let data = map.get(1000).unwrap();
+        assert_eq!(data.get_score(Difficulty::SpA), 1500);
+        assert_eq!(data.get_lamp(Difficulty::SpA), Lamp::Clear);
+        assert_eq!(data.miss_count[3], None);
+        assert_eq!(map.len(), 1);
+    }
+
     #[test]
     fn test_list_node_from_bytes() {
         // Create test bytes for ListNode (64 bytes)
@@ -390,6 +569,6 @@ mod tests {
         assert_eq!(node.score, 2500);
         assert_eq!(node.miss_count, 15);
         assert_eq!(node.lamp, 5);
-        assert_eq!(node.key(), (1000, 3, 0));
+        assert_eq!(node.chart_id(), Some(ChartId::new(1000, Difficulty::SpA)));
     }
 }
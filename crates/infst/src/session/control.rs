@@ -0,0 +1,153 @@
+//! Session control commands (pause, resume, annotations)
+//!
+//! Commands are queued as JSON lines in `control.jsonl` inside the session
+//! directory, so `infst session ...` can reach a tracker running in another
+//! process. The tracker takes the queue on each poll.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::Result;
+
+/// A command for the running session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum SessionCommand {
+    /// Stop recording plays normally (see [`PauseMode`])
+    Pause,
+    Resume,
+    /// Attach a free-text note to the session timeline
+    Annotate {
+        text: String,
+    },
+    /// Copy the last play summary to the clipboard
+    Copy,
+    /// Export the tracker file now
+    Export,
+    /// Pause a running session, or resume a paused one
+    TogglePause,
+    /// Blank the stream marquee, or show it again
+    ToggleMarquee,
+}
+
+/// How plays are handled while the session is paused
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PauseMode {
+    /// Record plays, marked as played during a pause
+    #[default]
+    Flag,
+    /// Don't record plays (session files, courses, API)
+    Ignore,
+}
+
+/// File-based command queue shared by the CLI and the tracker
+#[derive(Debug, Clone)]
+pub struct SessionControl {
+    path: PathBuf,
+}
+
+impl SessionControl {
+    pub const FILE_NAME: &'static str = "control.jsonl";
+
+    pub fn new<P: AsRef<Path>>(session_dir: P) -> Self {
+        Self {
+            path: session_dir.as_ref().join(Self::FILE_NAME),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Queue a command for the tracker
+    pub fn send(&self, command: &SessionCommand) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(command)?)?;
+        Ok(())
+    }
+
+    /// Remove and return all queued commands (oldest first)
+    ///
+    /// The queue is renamed to a temporary path before reading so commands
+    /// sent meanwhile land in a fresh file instead of being lost. A missing
+    /// queue shows up as a failed rename, not a separate existence check.
+    pub fn take(&self) -> Result<Vec<SessionCommand>> {
+        let taken = self.path.with_extension("jsonl.taken");
+        match fs::rename(&self.path, &taken) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        }
+        let content = fs::read_to_string(&taken);
+        fs::remove_file(&taken)?;
+
+        let commands = content?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(command) => Some(command),
+                Err(e) => {
+                    warn!("Ignoring invalid session command {:?}: {}", line, e);
+                    None
+                }
+            })
+            .collect();
+        Ok(commands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_send_and_take() {
+        let temp = TempDir::new().unwrap();
+        let control = SessionControl::new(temp.path().join("sessions"));
+        assert!(control.take().unwrap().is_empty());
+
+        control.send(&SessionCommand::Pause).unwrap();
+        control
+            .send(&SessionCommand::Annotate {
+                text: "new controller".to_string(),
+            })
+            .unwrap();
+        let content = fs::read_to_string(control.path()).unwrap();
+        assert!(content.starts_with("{\"command\":\"pause\"}\n"));
+
+        assert_eq!(
+            control.take().unwrap(),
+            vec![
+                SessionCommand::Pause,
+                SessionCommand::Annotate {
+                    text: "new controller".to_string()
+                }
+            ]
+        );
+        assert!(!control.path().exists());
+        assert!(control.take().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_take_skips_invalid_lines() {
+        let temp = TempDir::new().unwrap();
+        let control = SessionControl::new(temp.path());
+        fs::write(
+            control.path(),
+            "{\"command\":\"resume\"}\nnot json\n{\"command\":\"reboot\"}\n",
+        )
+        .unwrap();
+        assert_eq!(control.take().unwrap(), vec![SessionCommand::Resume]);
+    }
+}
@@ -0,0 +1,354 @@
+//! Session history loading (reads back TSV session files)
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use tracing::{debug, warn};
+
+use crate::chart::{ChartInfo, Difficulty, SongInfo, intern};
+use crate::error::Result;
+use crate::play::{LaneCover, PlayData, PlayType, Settings};
+use crate::score::{Grade, Judge, Lamp};
+use crate::storage::migrate::migrate;
+use crate::storage::{FileFormat, decode_text};
+
+/// Parse play data rows from a session TSV file's content.
+///
+/// Columns are located by header name, so files written by older versions
+/// with fewer columns still load. Song IDs are not stored in session files;
+/// they are resolved by title against `song_db` (0 when the title is unknown).
+pub fn parse_session_tsv(content: &str, song_db: &HashMap<u32, SongInfo>) -> Vec<PlayData> {
+    // Skip comment lines (profile and course result lines). They are written
+    // as `# ...`; a bare `#` starts a row whose title begins with it
+    let mut lines = content.lines().filter(|line| !line.starts_with("# "));
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let columns: HashMap<&str, usize> = header
+        .split('\t')
+        .enumerate()
+        .map(|(i, name)| (name, i))
+        .collect();
+
+    let title_to_id: HashMap<&str, u32> = song_db
+        .iter()
+        .map(|(&id, song)| (&*song.title, id))
+        .collect();
+
+    let mut plays = Vec::new();
+    for line in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').collect();
+        match parse_session_row(&fields, &columns, &title_to_id) {
+            Some(play) => plays.push(play),
+            None => debug!("Skipping malformed session row: {}", line),
+        }
+    }
+    plays
+}
+
+fn parse_session_row(
+    fields: &[&str],
+    columns: &HashMap<&str, usize>,
+    title_to_id: &HashMap<&str, u32>,
+) -> Option<PlayData> {
+    let get = |name: &str| columns.get(name).and_then(|&i| fields.get(i)).copied();
+    let num = |name: &str| get(name).and_then(|v| v.parse::<u32>().ok()).unwrap_or(0);
+
+    let title = get("title")?;
+    let difficulty: Difficulty = get("difficulty")?.parse().ok()?;
+    let timestamp = get("date")
+        .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+        .map(|t| t.with_timezone(&Utc))?;
+
+    let play_type = match get("playtype") {
+        Some("2P") => PlayType::P2,
+        Some("DP") => PlayType::Dp,
+        _ => PlayType::P1,
+    };
+    let lamp = get("lamp")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(Lamp::NoPlay);
+    let grade = get("grade")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(Grade::NoPlay);
+    let miss_count_recorded = get("misscount").is_some_and(|v| v != "-");
+
+    let judge = Judge {
+        play_type,
+        pgreat: num("pgreat"),
+        great: num("great"),
+        good: num("good"),
+        bad: num("bad"),
+        poor: num("poor"),
+        fast: num("fast"),
+        slow: num("slow"),
+        combo_break: num("combobreak"),
+        premature_end: get("premature") == Some("1"),
+    };
+
+    let text = |name: &str| -> Arc<str> { intern(get(name).unwrap_or("")) };
+
+    // Only the range and lane covers are read back: enough to track the
+    // green number and cover positions across sessions
+    let cover = |name: &str| get(name).and_then(|v| v.parse::<u16>().ok());
+    let settings = Settings {
+        range: get("range")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default(),
+        lane_cover: cover("greennumber").map(|green_number| LaneCover {
+            sudden: cover("sudden").unwrap_or(0),
+            hidden: cover("hidden").unwrap_or(0),
+            lift: cover("lift").unwrap_or(0),
+            green_number,
+        }),
+        ..Settings::default()
+    };
+
+    Some(PlayData {
+        timestamp,
+        chart: ChartInfo {
+            song_id: title_to_id.get(title).copied().unwrap_or(0),
+            title: intern(title),
+            title_english: text("title2"),
+            artist: text("artist"),
+            genre: text("genre"),
+            bpm: text("bpm"),
+            difficulty,
+            level: get("level").and_then(|v| v.parse().ok()).unwrap_or(0),
+            total_notes: num("notecount"),
+            unlocked: true,
+        },
+        ex_score: num("exscore"),
+        grade,
+        lamp,
+        judge,
+        settings,
+        // Session files only record the miss count for plays where it was valid
+        data_available: miss_count_recorded,
+        bpi: get("bpi").and_then(|v| v.parse().ok()),
+        failed_at_percent: get("failedat").and_then(|v| v.parse().ok()),
+    })
+}
+
+/// Load every `Session_*.tsv` file in `dir`, oldest first.
+pub fn load_session_history<P: AsRef<Path>>(
+    dir: P,
+    song_db: &HashMap<u32, SongInfo>,
+) -> Result<Vec<PlayData>> {
+    let mut paths: Vec<_> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "tsv")
+                && path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with("Session_"))
+        })
+        .collect();
+    paths.sort();
+
+    let mut plays = Vec::new();
+    for path in paths {
+        let content = match fs::read(&path) {
+            Ok(bytes) => decode_text(&bytes),
+            Err(e) => {
+                warn!("Failed to read session file {:?}: {}", path, e);
+                continue;
+            }
+        };
+        match migrate(FileFormat::SessionTsv, &content) {
+            Ok(migration) => plays.extend(parse_session_tsv(&migration.content, song_db)),
+            Err(e) => warn!("Skipping session file {:?}: {}", path, e),
+        }
+    }
+    plays.sort_by_key(|p| p.timestamp);
+    Ok(plays)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{format_full_tsv_header, format_full_tsv_row};
+    use crate::play::test_support::PlayBuilder;
+
+    fn make_play(title: &str, ex_score: u32) -> PlayData {
+        PlayBuilder::new()
+            .title(title)
+            .artist("Artist")
+            .genre("Genre")
+            .ex_score(ex_score)
+            .grade(Grade::Aa)
+            .lamp(Lamp::HardClear)
+            .judge(Judge {
+                pgreat: 700,
+                great: 200,
+                bad: 3,
+                poor: 4,
+                fast: 40,
+                slow: 60,
+                combo_break: 5,
+                ..Default::default()
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_parse_session_tsv_roundtrip() {
+        let play = make_play("Test Song", 1600);
+        let content = format!(
+            "{}\n{}\n",
+            format_full_tsv_header(),
+            format_full_tsv_row(&play)
+        );
+
+        let mut song_db = HashMap::new();
+        song_db.insert(
+            1000,
+            SongInfo {
+                id: 1000,
+                title: Arc::from("Test Song"),
+                ..Default::default()
+            },
+        );
+
+        let plays = parse_session_tsv(&content, &song_db);
+        assert_eq!(plays.len(), 1);
+        let parsed = &plays[0];
+        assert_eq!(parsed.chart.song_id, 1000);
+        assert_eq!(parsed.chart.difficulty, Difficulty::SpA);
+        assert_eq!(parsed.ex_score, 1600);
+        assert_eq!(parsed.lamp, Lamp::HardClear);
+        assert_eq!(parsed.grade, Grade::Aa);
+        assert_eq!(parsed.judge.poor, 4);
+        assert_eq!(parsed.judge.combo_break, 5);
+        assert_eq!(parsed.miss_count(), 7);
+        assert!(parsed.miss_count_valid());
+    }
+
+    #[test]
+    fn test_parse_session_tsv_premature_flag() {
+        let mut play = make_play("Test Song", 1600);
+        play.judge.premature_end = true;
+        let content = format!(
+            "{}\n{}\n{}\n",
+            format_full_tsv_header(),
+            format_full_tsv_row(&play),
+            format_full_tsv_row(&make_play("Test Song", 1500))
+        );
+
+        let plays = parse_session_tsv(&content, &HashMap::new());
+        assert!(plays[0].judge.premature_end);
+        assert!(!plays[1].judge.premature_end);
+    }
+
+    #[test]
+    fn test_parse_session_tsv_failed_at_percent() {
+        let mut play = make_play("Test Song", 600);
+        play.lamp = Lamp::Failed;
+        play.failed_at_percent = Some(42.5);
+        let content = format!(
+            "{}\n{}\n{}\n",
+            format_full_tsv_header(),
+            format_full_tsv_row(&play),
+            format_full_tsv_row(&make_play("Test Song", 1500))
+        );
+
+        let plays = parse_session_tsv(&content, &HashMap::new());
+        assert_eq!(plays[0].failed_at_percent, Some(42.5));
+        assert_eq!(plays[1].failed_at_percent, None);
+    }
+
+    #[test]
+    fn test_parse_session_tsv_lane_cover() {
+        let mut play = make_play("Test Song", 1600);
+        play.settings.range = crate::play::RangeType::LiftSud;
+        play.settings.lane_cover = Some(LaneCover {
+            sudden: 250,
+            hidden: 100,
+            lift: 120,
+            green_number: 290,
+        });
+        let content = format!(
+            "{}\n{}\n{}\n",
+            format_full_tsv_header(),
+            format_full_tsv_row(&play),
+            format_full_tsv_row(&make_play("Test Song", 1500))
+        );
+
+        let plays = parse_session_tsv(&content, &HashMap::new());
+        let settings = &plays[0].settings;
+        assert_eq!(settings.range, crate::play::RangeType::LiftSud);
+        assert_eq!(settings.sudden(), Some(250));
+        assert_eq!(settings.lift(), Some(120));
+        // HIDDEN+ isn't shown with LIFT & SUD+, so it isn't recorded
+        assert_eq!(settings.lane_cover.unwrap().hidden, 0);
+        assert_eq!(settings.green_number(), Some(290));
+        assert_eq!(plays[1].settings.lane_cover, None);
+    }
+
+    #[test]
+    fn test_parse_session_tsv_unknown_title() {
+        let play = make_play("Unknown", 1000);
+        let content = format!(
+            "{}\n{}",
+            format_full_tsv_header(),
+            format_full_tsv_row(&play)
+        );
+
+        let plays = parse_session_tsv(&content, &HashMap::new());
+        assert_eq!(plays.len(), 1);
+        assert_eq!(plays[0].chart.song_id, 0);
+    }
+
+    #[test]
+    fn test_parse_session_tsv_skips_profile_comment() {
+        let play = make_play("Test Song", 1600);
+        let content = format!(
+            "# DJ ABC  SP - / DP -  10 plays\n{}\n{}\n",
+            format_full_tsv_header(),
+            format_full_tsv_row(&play)
+        );
+
+        let plays = parse_session_tsv(&content, &HashMap::new());
+        assert_eq!(plays.len(), 1);
+        assert_eq!(plays[0].ex_score, 1600);
+    }
+
+    #[test]
+    fn test_parse_session_tsv_keeps_hash_titles() {
+        let content = format!(
+            "# DJ ABC  SP - / DP -  10 plays\n{}\n{}\n{}\n",
+            format_full_tsv_header(),
+            format_full_tsv_row(&make_play("#HASHTAG", 1600)),
+            format_full_tsv_row(&make_play("Test Song", 1500))
+        );
+
+        let plays = parse_session_tsv(&content, &HashMap::new());
+        assert_eq!(plays.len(), 2);
+        assert_eq!(&*plays[0].chart.title, "#HASHTAG");
+        assert_eq!(plays[0].ex_score, 1600);
+    }
+
+    #[test]
+    fn test_parse_session_tsv_empty() {
+        assert!(parse_session_tsv("", &HashMap::new()).is_empty());
+        assert!(parse_session_tsv(&format_full_tsv_header(), &HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_parse_session_tsv_skips_timeline_lines() {
+        let play = make_play("Test Song", 1600);
+        let content = format!(
+            "{}\n# NOTE 2025-01-01T20:00:00+09:00 new controller\n# PAUSE 2025-01-01T20:05:00+09:00\n{}\n# RESUME 2025-01-01T20:10:00+09:00\n",
+            format_full_tsv_header(),
+            format_full_tsv_row(&play)
+        );
+
+        assert_eq!(parse_session_tsv(&content, &HashMap::new()).len(), 1);
+    }
+}
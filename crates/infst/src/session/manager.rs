@@ -1,17 +1,150 @@
+use crate::chart::ChartId;
+use crate::clock::{SharedClock, SystemClock};
 use crate::error::Result;
-use crate::export::{format_full_tsv_header, format_full_tsv_row, format_json_entry};
-use crate::play::PlayData;
-use chrono::{DateTime, Local};
+use crate::export::{
+    Change, ChartImprovement, DisplayTimezone, compare_with_personal_best, format_course_json,
+    format_full_tsv_header, format_full_tsv_row_in, format_json_entry, format_set_json,
+};
+use crate::play::{CourseResult, PlayAnomaly, PlayData, PlaySet, ProfileInfo};
+use crate::score::{Lamp, ScoreData};
+use crate::stats::{GreenNumberLog, PlayTime, notes_hit};
+use crate::storage::migrate::session_json_document;
+use crate::storage::{
+    FileFormat, TextEncoding, WriteOptions, encode_for_file, write_atomic_with, write_text_atomic,
+};
+use chrono::{DateTime, Local, SecondsFormat, SubsecRound, Utc};
+use serde::Serialize;
 use serde_json::Value as JsonValue;
 use std::fs::{self};
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// What a session timeline entry records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteKind {
+    Note,
+    Pause,
+    Resume,
+    /// Play that failed [`PlayData::validate`]
+    Anomaly,
+    /// Play of a chart on the ignore list (see [`crate::storage::IgnoreList`])
+    Ignored,
+}
+
+impl NoteKind {
+    fn tag(self) -> &'static str {
+        match self {
+            Self::Note => "NOTE",
+            Self::Pause => "PAUSE",
+            Self::Resume => "RESUME",
+            Self::Anomaly => "ANOMALY",
+            Self::Ignored => "IGNORED",
+        }
+    }
+}
+
+/// An annotation or pause/resume marker on the session timeline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionNote {
+    pub timestamp: DateTime<Local>,
+    pub kind: NoteKind,
+    /// Free text (empty for pause/resume markers)
+    pub text: String,
+}
+
+impl SessionNote {
+    /// TSV comment form, e.g. `# NOTE 2025-01-01T20:00:00+09:00 switched to DP`,
+    /// dated in `timezone`
+    pub fn tsv_line(&self, timezone: DisplayTimezone) -> String {
+        let line = format!(
+            "# {} {} {}",
+            self.kind.tag(),
+            timezone
+                .convert(&self.timestamp.with_timezone(&Utc))
+                .to_rfc3339_opts(SecondsFormat::Secs, false),
+            self.text
+        );
+        line.trim_end().to_string()
+    }
+
+    /// Parse a line written by [`tsv_line`](Self::tsv_line)
+    pub fn parse_tsv_line(line: &str) -> Option<Self> {
+        let rest = line.strip_prefix("# ")?;
+        let (tag, rest) = rest.split_once(' ')?;
+        let kind = [
+            NoteKind::Note,
+            NoteKind::Pause,
+            NoteKind::Resume,
+            NoteKind::Anomaly,
+            NoteKind::Ignored,
+        ]
+        .into_iter()
+        .find(|kind| kind.tag() == tag)?;
+        let (timestamp, text) = rest.split_once(' ').unwrap_or((rest, ""));
+        let timestamp = DateTime::parse_from_rfc3339(timestamp).ok()?;
+        Some(Self {
+            timestamp: timestamp.with_timezone(&Local),
+            kind,
+            text: text.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for SessionNote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let time = self.timestamp.format("%H:%M");
+        match self.kind {
+            NoteKind::Note => write!(f, "{} {}", time, self.text),
+            NoteKind::Pause => write!(f, "{} paused", time),
+            NoteKind::Resume => write!(f, "{} resumed", time),
+            NoteKind::Anomaly => write!(f, "{} anomaly: {}", time, self.text),
+            NoteKind::Ignored => write!(f, "{} ignored: {}", time, self.text),
+        }
+    }
+}
+
+/// Rewrite the JSON session file as a versioned document
+fn write_json_session(path: &Path, entries: &[JsonValue]) -> Result<()> {
+    write_atomic_with(
+        path,
+        serde_json::to_string_pretty(&session_json_document(entries))?,
+        WriteOptions::DURABLE,
+    )?;
+    Ok(())
+}
 
 pub struct SessionManager {
     base_dir: PathBuf,
     current_tsv_session: Option<PathBuf>,
     current_json_session: Option<PathBuf>,
     json_data: Vec<JsonValue>,
+    profile: Option<ProfileInfo>,
+    paused: bool,
+    notes: Vec<SessionNote>,
+    plays: usize,
+    paused_plays: usize,
+    /// Plays that failed validation (recorded or not)
+    anomalous_plays: usize,
+    /// Repeated results that were not recorded again
+    suppressed_duplicates: usize,
+    /// Recorded plays of ignored charts
+    ignored_plays: usize,
+    sets: Vec<PlaySet>,
+    /// Whether the last set can still take stages
+    set_open: bool,
+    /// Time in the Playing state and notes hit
+    play_time: PlayTime,
+    /// Green numbers of recorded plays
+    green_numbers: GreenNumberLog,
+    /// Charts improved this session, in the order first improved
+    improvements: Vec<ChartImprovement>,
+    clock: SharedClock,
+    timezone: DisplayTimezone,
+    encoding: TextEncoding,
+    /// Print what would be written instead of writing session files
+    dry_run: bool,
 }
 
 impl SessionManager {
@@ -21,22 +154,84 @@ impl SessionManager {
             current_tsv_session: None,
             current_json_session: None,
             json_data: Vec::new(),
+            profile: None,
+            paused: false,
+            notes: Vec::new(),
+            plays: 0,
+            paused_plays: 0,
+            anomalous_plays: 0,
+            suppressed_duplicates: 0,
+            ignored_plays: 0,
+            sets: Vec::new(),
+            set_open: false,
+            play_time: PlayTime::new(),
+            green_numbers: GreenNumberLog::new(),
+            improvements: Vec::new(),
+            clock: SystemClock::shared(),
+            timezone: DisplayTimezone::default(),
+            encoding: TextEncoding::default(),
+            dry_run: false,
         }
     }
 
+    /// Use `clock` for session file names and timeline timestamps
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Date TSV rows and timeline lines in `timezone` (local by default)
+    pub fn with_timezone(mut self, timezone: DisplayTimezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Write the TSV session file in `encoding` (UTF-8 by default)
+    pub fn with_encoding(mut self, encoding: TextEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Print the lines that would be written instead of writing session files
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Set the player profile written to the header of new TSV sessions
+    pub fn set_profile(&mut self, profile: Option<ProfileInfo>) {
+        self.profile = profile;
+    }
+
     /// Start a session with TSV header
+    ///
+    /// In dry-run mode the file is named but never created.
     pub fn start_tsv_session(&mut self) -> Result<PathBuf> {
-        let now: DateTime<Local> = Local::now();
-        fs::create_dir_all(&self.base_dir)?;
+        let now: DateTime<Local> = self.clock.now_local();
 
         // TSV session file (C# compatible naming)
         let tsv_file = self
             .base_dir
             .join(format!("Session_{}.tsv", now.format("%Y_%m_%d_%H_%M_%S")));
+        if self.dry_run {
+            self.current_tsv_session = Some(tsv_file.clone());
+            return Ok(tsv_file);
+        }
+        fs::create_dir_all(&self.base_dir)?;
 
-        // Write header
+        // Write header (preceded by `#` comments: the profile when known and
+        // the timezone of the `date` column)
         let header = format_full_tsv_header();
-        fs::write(&tsv_file, format!("{}\n", header))?;
+        let mut content = format!("{}\n", FileFormat::SessionTsv.marker());
+        if let Some(profile) = &self.profile {
+            content.push_str(&format!("# {}\n", profile.summary()));
+        }
+        content.push_str(&format!(
+            "# timezone: {}\n",
+            self.timezone.describe_at(&now.with_timezone(&Utc))
+        ));
+        content.push_str(&format!("{}\n", header));
+        write_text_atomic(&tsv_file, &content, self.encoding, WriteOptions::default())?;
 
         self.current_tsv_session = Some(tsv_file.clone());
 
@@ -45,16 +240,17 @@ impl SessionManager {
 
     /// Start a JSON session file
     pub fn start_json_session(&mut self) -> Result<PathBuf> {
-        let now: DateTime<Local> = Local::now();
-        fs::create_dir_all(&self.base_dir)?;
-
+        let now: DateTime<Local> = self.clock.now_local();
         let json_file = self
             .base_dir
             .join(format!("Session_{}.json", now.format("%Y_%m_%d_%H_%M_%S")));
 
         // Initialize as empty array
         self.json_data = Vec::new();
-        fs::write(&json_file, "[]")?;
+        if !self.dry_run {
+            fs::create_dir_all(&self.base_dir)?;
+            write_json_session(&json_file, &[])?;
+        }
 
         self.current_json_session = Some(json_file.clone());
 
@@ -62,21 +258,361 @@ impl SessionManager {
     }
 
     /// Append a TSV row to the session file
-    pub fn append_tsv_row(&self, play_data: &PlayData) -> Result<()> {
+    ///
+    /// Rows written while paused sit between the `# PAUSE` / `# RESUME`
+    /// timeline lines.
+    pub fn append_tsv_row(&mut self, play_data: &PlayData) -> Result<()> {
+        self.plays += 1;
+        if self.paused {
+            self.paused_plays += 1;
+        }
+        let row = format_full_tsv_row_in(play_data, self.timezone);
+        self.append_tsv_line(&row)
+    }
+
+    /// Append a line to the TSV session file, if one is open
+    fn append_tsv_line(&self, line: &str) -> Result<()> {
+        if self.dry_run {
+            println!("(dry run) session: {}", line);
+            return Ok(());
+        }
         if let Some(ref path) = self.current_tsv_session {
-            let row = format_full_tsv_row(play_data);
+            let bytes = encode_for_file(path, &format!("{}\n", line), self.encoding);
             let mut file = fs::OpenOptions::new().append(true).open(path)?;
-            writeln!(file, "{}", row)?;
+            file.write_all(&bytes)?;
         }
         Ok(())
     }
 
-    /// Append a JSON entry to the session file
+    /// Append a JSON entry to the session file (`"paused": true` while paused)
     pub fn append_json_entry(&mut self, play_data: &PlayData) -> Result<()> {
         if let Some(path) = &self.current_json_session {
-            let entry = format_json_entry(play_data);
+            let mut entry = format_json_entry(play_data);
+            if self.paused
+                && let Some(object) = entry.as_object_mut()
+            {
+                object.insert("paused".to_string(), JsonValue::Bool(true));
+            }
             self.json_data.push(entry);
-            fs::write(path, serde_json::to_string_pretty(&self.json_data)?)?;
+            self.write_json(path)?;
+        }
+        Ok(())
+    }
+
+    /// Rewrite the JSON session file (nothing in dry-run mode)
+    fn write_json(&self, path: &Path) -> Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        write_json_session(path, &self.json_data)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pause the session; returns `false` if it was already paused
+    pub fn pause(&mut self) -> Result<bool> {
+        if self.paused {
+            return Ok(false);
+        }
+        self.paused = true;
+        self.add_note(NoteKind::Pause, "")?;
+        Ok(true)
+    }
+
+    /// Resume a paused session; returns `false` if it wasn't paused
+    pub fn resume(&mut self) -> Result<bool> {
+        if !self.paused {
+            return Ok(false);
+        }
+        self.paused = false;
+        self.add_note(NoteKind::Resume, "")?;
+        Ok(true)
+    }
+
+    /// Attach a free-text note to the session timeline
+    pub fn annotate(&mut self, text: &str) -> Result<()> {
+        self.add_note(NoteKind::Note, text.trim())
+    }
+
+    /// Note a play that failed [`PlayData::validate`] on the timeline
+    ///
+    /// `dropped` records that the play itself was not saved.
+    pub fn flag_anomalies(
+        &mut self,
+        play_data: &PlayData,
+        anomalies: &[PlayAnomaly],
+        dropped: bool,
+    ) -> Result<()> {
+        self.anomalous_plays += 1;
+        let details: Vec<String> = anomalies.iter().map(ToString::to_string).collect();
+        let mut text = format!(
+            "{} [{}]: {}",
+            play_data.chart.title,
+            play_data.chart.difficulty.short_name(),
+            details.join("; ")
+        );
+        if dropped {
+            text.push_str(" (not recorded)");
+        }
+        self.add_note(NoteKind::Anomaly, &text)
+    }
+
+    /// Number of plays that failed validation this session
+    pub fn anomaly_count(&self) -> usize {
+        self.anomalous_plays
+    }
+
+    /// Note a recorded play of an ignored chart on the timeline (it is kept
+    /// out of exports and submissions)
+    pub fn flag_ignored(&mut self, play_data: &PlayData) -> Result<()> {
+        self.ignored_plays += 1;
+        let text = format!(
+            "{} [{}]",
+            play_data.chart.title,
+            play_data.chart.difficulty.short_name()
+        );
+        self.add_note(NoteKind::Ignored, &text)
+    }
+
+    /// Number of recorded plays of ignored charts this session
+    pub fn ignored_count(&self) -> usize {
+        self.ignored_plays
+    }
+
+    /// Count a repeated result that was not recorded
+    pub fn note_duplicate(&mut self) {
+        self.suppressed_duplicates += 1;
+    }
+
+    /// Number of repeated results suppressed this session
+    pub fn duplicate_count(&self) -> usize {
+        self.suppressed_duplicates
+    }
+
+    /// Count time spent in the Playing state on `chart`
+    pub fn record_play_time(&mut self, chart: ChartId, time: Duration) {
+        self.play_time.record_time(chart, time);
+    }
+
+    /// Count the notes hit in a recorded play
+    pub fn record_notes_hit(&mut self, play_data: &PlayData) {
+        self.play_time
+            .record_notes(play_data.chart.id(), notes_hit(&play_data.judge));
+    }
+
+    /// Play time and notes hit this session
+    pub fn play_time(&self) -> &PlayTime {
+        &self.play_time
+    }
+
+    /// Log the green number of a recorded play (when it was read)
+    pub fn record_green_number(&mut self, play_data: &PlayData) {
+        self.green_numbers.record(play_data);
+    }
+
+    /// Green numbers played this session
+    pub fn green_numbers(&self) -> &GreenNumberLog {
+        &self.green_numbers
+    }
+
+    /// Note what a recorded play improved over `best`, the personal best
+    /// before the session's plays on the chart were loaded
+    ///
+    /// A lamp counts when it is better and at least ASSIST CLEAR (so a first
+    /// clear of an unplayed chart counts too); grade and EX score as in
+    /// [`compare_with_personal_best`]. Improvements on the same chart are
+    /// merged, keeping the oldest value and the best new one.
+    pub fn record_improvement(&mut self, play_data: &PlayData, best: Option<&ScoreData>) {
+        if play_data.is_premature() {
+            return;
+        }
+        let difficulty = play_data.chart.difficulty;
+        let comparison = compare_with_personal_best(play_data, best);
+        let best_lamp = best.map_or(Lamp::NoPlay, |best| best.get_lamp(difficulty));
+        let best_score = best.map_or(0, |best| best.get_score(difficulty));
+        let improvement = ChartImprovement {
+            song_id: play_data.chart.song_id.to_string(),
+            title: play_data.chart.title.to_string(),
+            difficulty: difficulty.short_name().to_string(),
+            lamp: (play_data.lamp > best_lamp && play_data.lamp >= Lamp::AssistClear).then_some(
+                Change {
+                    old: best_lamp,
+                    new: play_data.lamp,
+                },
+            ),
+            grade: comparison.previous_grade.map(|old| Change {
+                old,
+                new: play_data.grade,
+            }),
+            ex_score: comparison.score_diff.map(|_| Change {
+                old: best_score,
+                new: play_data.ex_score,
+            }),
+        };
+        if improvement.lamp.is_none()
+            && improvement.grade.is_none()
+            && improvement.ex_score.is_none()
+        {
+            return;
+        }
+
+        let existing = self.improvements.iter_mut().find(|existing| {
+            existing.song_id == improvement.song_id && existing.difficulty == improvement.difficulty
+        });
+        match existing {
+            Some(existing) => {
+                existing.lamp = merge_change(existing.lamp, improvement.lamp);
+                existing.grade = merge_change(existing.grade, improvement.grade);
+                existing.ex_score = merge_change(existing.ex_score, improvement.ex_score);
+            }
+            None => self.improvements.push(improvement),
+        }
+    }
+
+    /// Charts improved this session
+    pub fn improvements(&self) -> &[ChartImprovement] {
+        &self.improvements
+    }
+
+    /// Plays recorded this session
+    pub fn play_count(&self) -> usize {
+        self.plays
+    }
+
+    /// Timeline entries added during this session
+    pub fn notes(&self) -> &[SessionNote] {
+        &self.notes
+    }
+
+    /// Record a timeline entry in memory and in the session files
+    fn add_note(&mut self, kind: NoteKind, text: &str) -> Result<()> {
+        let note = SessionNote {
+            // Whole seconds, as written to the TSV timeline
+            timestamp: self.clock.now_local().trunc_subsecs(0),
+            kind,
+            text: text.to_string(),
+        };
+        self.append_tsv_line(&note.tsv_line(self.timezone))?;
+        if let Some(path) = &self.current_json_session {
+            self.json_data.push(serde_json::json!({ "note": note }));
+            self.write_json(path)?;
+        }
+        self.notes.push(note);
+        Ok(())
+    }
+
+    /// Session summary: play count and timeline entries, one per line
+    ///
+    /// `None` when nothing was recorded.
+    pub fn summary(&self) -> Option<String> {
+        if self.plays == 0 && self.notes.is_empty() {
+            return None;
+        }
+        let mut summary = format!("Session: {} plays", self.plays);
+        if !self.sets.is_empty() {
+            summary.push_str(&format!(" in {} sets", self.sets.len()));
+        }
+        if self.paused_plays > 0 {
+            summary.push_str(&format!(" ({} while paused)", self.paused_plays));
+        }
+        if self.ignored_plays > 0 {
+            summary.push_str(&format!(" ({} ignored)", self.ignored_plays));
+        }
+        if self.anomalous_plays > 0 {
+            summary.push_str(&format!(
+                ", {} anomalous (check offsets)",
+                self.anomalous_plays
+            ));
+        }
+        if self.suppressed_duplicates > 0 {
+            summary.push_str(&format!(
+                ", {} duplicates suppressed",
+                self.suppressed_duplicates
+            ));
+        }
+        if !self.play_time.is_empty() {
+            summary.push_str(&format!(", {}", self.play_time.summary()));
+        }
+        if let Some(green_numbers) = self.green_numbers.summary() {
+            summary.push_str(&format!(", {}", green_numbers));
+        }
+        if !self.improvements.is_empty() {
+            summary.push_str(&format!(", {} charts improved", self.improvements.len()));
+        }
+        for note in &self.notes {
+            summary.push_str(&format!("\n  {}", note));
+        }
+        Some(summary)
+    }
+
+    /// Append a course result to the session files
+    ///
+    /// The TSV gets a `# ` comment line after the stage rows; the JSON session
+    /// gets a `course` entry.
+    pub fn append_course_result(&mut self, course: &CourseResult) -> Result<()> {
+        self.append_tsv_line(&format!("# {}", course.summary()))?;
+        if let Some(path) = &self.current_json_session {
+            self.json_data.push(format_course_json(course));
+            self.write_json(path)?;
+        }
+        Ok(())
+    }
+
+    /// Sets (credits) played this session, oldest first
+    ///
+    /// The last set may still be open.
+    pub fn sets(&self) -> &[PlaySet] {
+        &self.sets
+    }
+
+    /// Add a play outside a course to the current standard-mode set
+    ///
+    /// A new set starts when the current one is full or after a long break;
+    /// full sets are closed right away.
+    pub fn record_set_play(&mut self, play_data: PlayData) -> Result<()> {
+        match self.sets.last_mut() {
+            Some(set) if self.set_open && set.accepts(&play_data) => {
+                set.result.stages.push(play_data);
+            }
+            _ => {
+                self.close_set()?;
+                self.sets.push(PlaySet::standard(play_data));
+                self.set_open = true;
+            }
+        }
+        if self.sets.last().is_some_and(PlaySet::is_complete) {
+            self.close_set()?;
+        }
+        Ok(())
+    }
+
+    /// Record a course as its own set
+    ///
+    /// Nothing is written: the course entry from
+    /// [`append_course_result`](Self::append_course_result) covers it.
+    pub fn record_course_set(&mut self, course: CourseResult) -> Result<()> {
+        self.close_set()?;
+        self.sets.push(PlaySet::from_course(course));
+        Ok(())
+    }
+
+    /// Close the open set and append its aggregates to the session files
+    ///
+    /// The TSV gets a `# SET ...` comment line; the JSON session gets a
+    /// `set` entry.
+    pub fn close_set(&mut self) -> Result<()> {
+        if !std::mem::take(&mut self.set_open) {
+            return Ok(());
+        }
+        let Some(set) = self.sets.last() else {
+            return Ok(());
+        };
+        self.append_tsv_line(&format!("# {}", set.summary()))?;
+        if let Some(path) = &self.current_json_session {
+            self.json_data.push(format_set_json(set));
+            self.write_json(path)?;
         }
         Ok(())
     }
@@ -90,9 +626,24 @@ impl SessionManager {
     }
 }
 
+/// Keep the oldest value of `existing` and the better new value
+fn merge_change<T: Copy + Ord>(
+    existing: Option<Change<T>>,
+    latest: Option<Change<T>>,
+) -> Option<Change<T>> {
+    match (existing, latest) {
+        (Some(existing), Some(latest)) => Some(Change {
+            old: existing.old.min(latest.old),
+            new: existing.new.max(latest.new),
+        }),
+        (existing, latest) => existing.or(latest),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::play::test_support::PlayBuilder;
     use std::fs;
     use tempfile::TempDir;
 
@@ -109,6 +660,342 @@ mod tests {
         assert!(manager.current_json_session_path().is_none());
     }
 
+    #[test]
+    fn test_clock_names_sessions_and_stamps_notes() {
+        use crate::clock::MockClock;
+        use chrono::{TimeDelta, TimeZone, Utc};
+        use std::sync::Arc;
+
+        let temp_dir = TempDir::new().unwrap();
+        let start = Local.with_ymd_and_hms(2026, 3, 1, 23, 59, 58).unwrap();
+        let clock = Arc::new(MockClock::new(start.with_timezone(&Utc)));
+        let mut manager = SessionManager::new(temp_dir.path()).with_clock(clock.clone());
+
+        let path = manager.start_tsv_session().unwrap();
+        assert_eq!(path.file_name().unwrap(), "Session_2026_03_01_23_59_58.tsv");
+
+        clock.advance(TimeDelta::seconds(3));
+        manager.annotate("after midnight").unwrap();
+        assert_eq!(manager.notes()[0].timestamp, start + TimeDelta::seconds(3));
+        let path = manager.start_tsv_session().unwrap();
+        assert_eq!(path.file_name().unwrap(), "Session_2026_03_02_00_00_01.tsv");
+    }
+
+    #[test]
+    fn test_start_tsv_session_with_profile() {
+        let (manager, _temp) = create_temp_session_manager();
+        let mut manager = manager.with_timezone(DisplayTimezone::Utc);
+        manager.set_profile(Some(ProfileInfo {
+            dj_name: "ABC".to_string(),
+            sp_dan: None,
+            dp_dan: None,
+            play_count: 10,
+        }));
+        let path = manager.start_tsv_session().unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let mut lines = content.lines();
+        assert_eq!(lines.next(), Some(FileFormat::SessionTsv.marker().as_str()));
+        assert_eq!(lines.next(), Some("# DJ ABC  SP - / DP -  10 plays"));
+        assert_eq!(lines.next(), Some("# timezone: UTC (+00:00)"));
+        assert_eq!(lines.next(), Some(format_full_tsv_header().as_str()));
+    }
+
+    #[test]
+    fn test_display_timezone_dates_rows_and_notes() {
+        use crate::clock::MockClock;
+        use chrono::TimeZone;
+        use std::sync::Arc;
+
+        let temp_dir = TempDir::new().unwrap();
+        let time = Utc.with_ymd_and_hms(2026, 3, 1, 14, 59, 30).unwrap();
+        let mut manager = SessionManager::new(temp_dir.path())
+            .with_clock(Arc::new(MockClock::new(time)))
+            .with_timezone("+09:00".parse().unwrap());
+        let path = manager.start_tsv_session().unwrap();
+        let mut play = sample_play();
+        play.timestamp = time;
+        manager.append_tsv_row(&play).unwrap();
+        manager.annotate("late").unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("# timezone: +09:00 (+09:00)\n"));
+        assert!(content.contains("\t2026-03-01T23:59:30+09:00\t"));
+        assert!(content.contains("# NOTE 2026-03-01T23:59:30+09:00 late"));
+        // Stored instants are unchanged
+        let rows = crate::session::parse_session_tsv(&content, &Default::default());
+        assert_eq!(rows[0].timestamp, time);
+    }
+
+    #[test]
+    fn test_shift_jis_session_reads_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager =
+            SessionManager::new(temp_dir.path()).with_encoding(TextEncoding::ShiftJis);
+        let path = manager.start_tsv_session().unwrap();
+        let mut play = sample_play();
+        play.chart.title = "冥".into();
+        manager.append_tsv_row(&play).unwrap();
+        manager.annotate("段位 ☆").unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        assert!(std::str::from_utf8(&bytes).is_err());
+        assert!(bytes.windows(2).any(|pair| pair == b"\x96\xBB"));
+
+        let plays =
+            crate::session::load_session_history(temp_dir.path(), &Default::default()).unwrap();
+        assert_eq!(plays.len(), 1);
+        assert_eq!(&*plays[0].chart.title, "冥");
+    }
+
+    #[test]
+    fn test_append_course_result() {
+        let stage = PlayBuilder::new().title("Stage").build();
+        let course = CourseResult {
+            started_at: stage.timestamp,
+            stages: vec![stage.clone(), stage],
+        };
+
+        let (mut manager, _temp) = create_temp_session_manager();
+        let tsv_path = manager.start_tsv_session().unwrap();
+        let json_path = manager.start_json_session().unwrap();
+        manager.append_course_result(&course).unwrap();
+
+        let content = fs::read_to_string(&tsv_path).unwrap();
+        assert_eq!(
+            content.lines().last(),
+            Some("# COURSE 2 stages PASSED  EX 3000 (75.00%)")
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(json["version"], 2);
+        let json = &json["entries"];
+        assert_eq!(json[0]["course"]["passed"], true);
+        assert_eq!(json[0]["course"]["stages"].as_array().unwrap().len(), 2);
+    }
+
+    fn sample_play() -> PlayData {
+        PlayBuilder::new().title("Song").build()
+    }
+
+    #[test]
+    fn test_pause_resume_and_annotations() {
+        let (mut manager, _temp) = create_temp_session_manager();
+        let tsv_path = manager.start_tsv_session().unwrap();
+        let json_path = manager.start_json_session().unwrap();
+        assert!(manager.summary().is_none());
+
+        manager.annotate("switched to DP").unwrap();
+        assert!(manager.pause().unwrap());
+        assert!(!manager.pause().unwrap());
+        manager.append_tsv_row(&sample_play()).unwrap();
+        manager.append_json_entry(&sample_play()).unwrap();
+        assert!(manager.resume().unwrap());
+        assert!(!manager.resume().unwrap());
+        assert!(!manager.is_paused());
+
+        let content = fs::read_to_string(&tsv_path).unwrap();
+        let comments: Vec<&str> = content.lines().filter(|l| l.starts_with('#')).collect();
+        assert_eq!(comments.len(), 5);
+        assert_eq!(comments[0], FileFormat::SessionTsv.marker());
+        assert!(comments[1].starts_with("# timezone: local "));
+        assert!(comments[2].starts_with("# NOTE "));
+        assert!(comments[2].ends_with(" switched to DP"));
+        assert!(comments[3].starts_with("# PAUSE "));
+        assert!(comments[4].starts_with("# RESUME "));
+        assert!(SessionNote::parse_tsv_line(comments[0]).is_none());
+        assert!(SessionNote::parse_tsv_line(comments[1]).is_none());
+        let note = SessionNote::parse_tsv_line(comments[2]).unwrap();
+        assert_eq!(note, manager.notes()[0].clone());
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(json["version"], 2);
+        let json = &json["entries"];
+        assert_eq!(json[0]["note"]["kind"], "note");
+        assert_eq!(json[0]["note"]["text"], "switched to DP");
+        assert_eq!(json[1]["note"]["kind"], "pause");
+        assert_eq!(json[2]["paused"], true);
+        assert_eq!(json[3]["note"]["kind"], "resume");
+
+        let summary = manager.summary().unwrap();
+        let mut lines = summary.lines();
+        assert_eq!(lines.next(), Some("Session: 1 plays (1 while paused)"));
+        assert!(lines.next().unwrap().ends_with(" switched to DP"));
+        assert!(lines.next().unwrap().ends_with(" paused"));
+        assert!(lines.next().unwrap().ends_with(" resumed"));
+    }
+
+    #[test]
+    fn test_flag_anomalies() {
+        let (mut manager, _temp) = create_temp_session_manager();
+        let tsv_path = manager.start_tsv_session().unwrap();
+
+        // sample_play has an EX score but no judge counts
+        let play = sample_play();
+        let anomalies = play.validate();
+        assert_eq!(anomalies.len(), 1);
+        manager.flag_anomalies(&play, &anomalies, true).unwrap();
+        assert_eq!(manager.anomaly_count(), 1);
+
+        let content = fs::read_to_string(&tsv_path).unwrap();
+        let line = content
+            .lines()
+            .find(|line| line.starts_with("# ANOMALY "))
+            .unwrap();
+        assert!(line.ends_with(
+            " Song [SPA]: EX score 1500 does not match judge counts (0) (not recorded)"
+        ));
+        let note = SessionNote::parse_tsv_line(line).unwrap();
+        assert_eq!(note.kind, NoteKind::Anomaly);
+        let summary = manager.summary().unwrap();
+        assert!(summary.starts_with("Session: 0 plays, 1 anomalous (check offsets)"));
+        assert!(summary.contains(" anomaly: Song [SPA]"));
+    }
+
+    #[test]
+    fn test_flag_ignored() {
+        let (mut manager, _temp) = create_temp_session_manager();
+        let tsv_path = manager.start_tsv_session().unwrap();
+
+        let play = sample_play();
+        manager.append_tsv_row(&play).unwrap();
+        manager.flag_ignored(&play).unwrap();
+        assert_eq!(manager.ignored_count(), 1);
+
+        let content = fs::read_to_string(&tsv_path).unwrap();
+        let line = content
+            .lines()
+            .find(|line| line.starts_with("# IGNORED "))
+            .unwrap();
+        assert!(line.ends_with(" Song [SPA]"));
+        assert_eq!(
+            SessionNote::parse_tsv_line(line).unwrap().kind,
+            NoteKind::Ignored
+        );
+        let summary = manager.summary().unwrap();
+        assert!(summary.starts_with("Session: 1 plays (1 ignored)"));
+        assert!(summary.contains(" ignored: Song [SPA]"));
+    }
+
+    #[test]
+    fn test_duplicates_in_summary() {
+        let (mut manager, _temp) = create_temp_session_manager();
+        manager.append_tsv_row(&sample_play()).unwrap();
+        manager.note_duplicate();
+        assert_eq!(manager.duplicate_count(), 1);
+        assert_eq!(
+            manager.summary().unwrap(),
+            "Session: 1 plays, 1 duplicates suppressed"
+        );
+    }
+
+    #[test]
+    fn test_play_time_in_summary() {
+        let (mut manager, _temp) = create_temp_session_manager();
+        let mut play = sample_play();
+        play.judge.pgreat = 1200;
+        play.judge.great = 300;
+        play.judge.good = 0;
+        manager.record_play_time(play.chart.id(), Duration::from_secs(150));
+        manager.append_tsv_row(&play).unwrap();
+        manager.record_notes_hit(&play);
+
+        assert_eq!(manager.play_time().notes_hit(), 1500);
+        assert_eq!(
+            manager.summary().unwrap(),
+            "Session: 1 plays, play time 2m 30s, 1500 notes (600.0/min)"
+        );
+    }
+
+    #[test]
+    fn test_green_number_in_summary() {
+        let (mut manager, _temp) = create_temp_session_manager();
+        let mut play = sample_play();
+        manager.append_tsv_row(&play).unwrap();
+        manager.record_green_number(&play);
+        assert!(manager.green_numbers().is_empty());
+
+        for green_number in [300, 290] {
+            play.settings.lane_cover = Some(crate::play::LaneCover {
+                green_number,
+                ..Default::default()
+            });
+            manager.append_tsv_row(&play).unwrap();
+            manager.record_green_number(&play);
+        }
+        assert_eq!(
+            manager.summary().unwrap(),
+            "Session: 3 plays, green number 300 -> 290 (1 change, 290-300)"
+        );
+    }
+
+    #[test]
+    fn test_set_grouping() {
+        let (manager, _temp) = create_temp_session_manager();
+        let mut manager = manager.with_timezone(DisplayTimezone::Utc);
+        let tsv_path = manager.start_tsv_session().unwrap();
+        let json_path = manager.start_json_session().unwrap();
+
+        let start = sample_play().timestamp;
+        let play_at = |minutes: i64| {
+            let mut play = sample_play();
+            play.timestamp = start + chrono::TimeDelta::minutes(minutes);
+            play
+        };
+        // A full 3-stage set, then a single play before a long break
+        for minutes in [0, 3, 6, 9] {
+            manager.record_set_play(play_at(minutes)).unwrap();
+        }
+        manager.record_set_play(play_at(60)).unwrap();
+        manager
+            .record_course_set(CourseResult {
+                started_at: play_at(65).timestamp,
+                stages: vec![play_at(65), play_at(68)],
+            })
+            .unwrap();
+        manager.close_set().unwrap();
+
+        let sets = manager.sets();
+        assert_eq!(sets.len(), 4);
+        assert_eq!(sets[0].result.stages.len(), 3);
+        assert_eq!(sets[0].result.ex_score(), 4500);
+        assert_eq!(sets[1].result.stages.len(), 1);
+        assert_eq!(sets[2].result.stages.len(), 1);
+        assert!(sets[3].course);
+
+        let content = fs::read_to_string(&tsv_path).unwrap();
+        let comments: Vec<&str> = content.lines().filter(|l| l.starts_with('#')).collect();
+        assert_eq!(
+            comments,
+            [
+                FileFormat::SessionTsv.marker().as_str(),
+                "# timezone: UTC (+00:00)",
+                "# SET 3 stages  EX 4500 (75.00%)",
+                "# SET 1 stages  EX 1500 (75.00%)",
+                "# SET 1 stages  EX 1500 (75.00%)",
+            ]
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(json["version"], 2);
+        let json = &json["entries"];
+        assert_eq!(json[0]["set"]["ex_score"], 4500);
+        assert_eq!(json[0]["set"]["course"], false);
+        assert_eq!(json.as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_parse_tsv_line_rejects_other_comments() {
+        assert!(SessionNote::parse_tsv_line("# DJ ABC  SP - / DP -  10 plays").is_none());
+        assert!(SessionNote::parse_tsv_line("# NOTE yesterday text").is_none());
+        let note = SessionNote::parse_tsv_line("# PAUSE 2025-01-01T20:00:00+09:00").unwrap();
+        assert_eq!(note.kind, NoteKind::Pause);
+        assert_eq!(note.text, "");
+    }
+
     #[test]
     fn test_start_json_session() {
         let (mut manager, _temp) = create_temp_session_manager();
@@ -118,10 +1005,54 @@ mod tests {
         assert!(manager.current_json_session_path().is_some());
         assert!(path.extension().unwrap() == "json");
 
-        // Verify JSON structure is an empty array
+        // Verify JSON structure is a versioned document with no entries
         let content = fs::read_to_string(&path).unwrap();
         let json: serde_json::Value = serde_json::from_str(&content).unwrap();
-        assert!(json.is_array());
-        assert!(json.as_array().unwrap().is_empty());
+        assert_eq!(json["format"], "session-json");
+        assert_eq!(json["version"], 2);
+        assert!(json["entries"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_record_improvement_merges_per_chart() {
+        use crate::chart::Difficulty;
+        use crate::score::Grade;
+
+        let (mut manager, _temp) = create_temp_session_manager();
+        let mut best = ScoreData::new(1000);
+        best.set_lamp(Difficulty::SpA, Lamp::EasyClear);
+        best.set_score(Difficulty::SpA, 1400);
+
+        // Same lamp and grade, lower score: nothing improved
+        let mut play = sample_play();
+        play.lamp = Lamp::EasyClear;
+        play.ex_score = 1300;
+        manager.record_improvement(&play, Some(&best));
+        assert!(manager.improvements().is_empty());
+
+        let play = sample_play();
+        manager.record_improvement(&play, Some(&best));
+        best.set_lamp(Difficulty::SpA, Lamp::Clear);
+        best.set_score(Difficulty::SpA, 1500);
+
+        let mut play = sample_play();
+        play.lamp = Lamp::HardClear;
+        play.ex_score = 1600;
+        play.grade = Grade::Aa;
+        manager.record_improvement(&play, Some(&best));
+
+        let improvements = manager.improvements();
+        assert_eq!(improvements.len(), 1);
+        let lamp = improvements[0].lamp.unwrap();
+        assert_eq!((lamp.old, lamp.new), (Lamp::EasyClear, Lamp::HardClear));
+        let score = improvements[0].ex_score.unwrap();
+        assert_eq!((score.old, score.new), (1400, 1600));
+
+        // A first clear of an unplayed chart counts
+        let mut play = sample_play();
+        play.chart.song_id = 1001;
+        manager.record_improvement(&play, None);
+        assert_eq!(manager.improvements().len(), 2);
+        assert_eq!(manager.improvements()[1].lamp.unwrap().old, Lamp::NoPlay);
     }
 }
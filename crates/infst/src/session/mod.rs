@@ -1,5 +1,11 @@
 //! Session management for tracking play data.
 
+mod control;
+mod history;
 mod manager;
+mod schedule;
 
+pub use control::*;
+pub use history::*;
 pub use manager::*;
+pub use schedule::*;
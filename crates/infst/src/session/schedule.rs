@@ -0,0 +1,442 @@
+//! Scheduled exports while tracking
+//!
+//! Jobs are written as `<action>@<when>`:
+//! - actions: `snapshot` (dated tracker snapshot), `backup` (tracker and
+//!   session files copied into `backups/`), `upload:<title-mapping.json>`
+//!   (bulk upload of the tracker to the web service)
+//! - when: `daily HH:MM`, `weekly <weekday> HH:MM` (local time) or
+//!   `session-end`
+//!
+//! The last and next run of every job are kept in `schedule.json` in the
+//! session directory, so `infst status` can show them and a run missed while
+//! the tracker was not running happens on the next start. A run is only
+//! recorded once the export succeeds; a failed timed job is retried after
+//! [`ExportScheduler::RETRY_DELAY`].
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDateTime, NaiveTime, TimeDelta, Weekday};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::{Error, Result};
+use crate::storage::write_atomic;
+
+/// When a scheduled export runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleTrigger {
+    /// Every day at this local time
+    Daily(NaiveTime),
+    /// Every week on this day at this local time
+    Weekly(Weekday, NaiveTime),
+    /// When the tracking session ends (game closed or tracker stopped)
+    SessionEnd,
+}
+
+impl ScheduleTrigger {
+    /// First time after `after` this trigger fires (`None` for [`Self::SessionEnd`])
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let (time, weekday) = match *self {
+            Self::Daily(time) => (time, None),
+            Self::Weekly(weekday, time) => (time, Some(weekday)),
+            Self::SessionEnd => return None,
+        };
+        let mut next = after.date().and_time(time);
+        while next <= after || weekday.is_some_and(|w| next.weekday() != w) {
+            next += TimeDelta::days(1);
+        }
+        Some(next)
+    }
+}
+
+impl FromStr for ScheduleTrigger {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let parse_time = |time: &str| {
+            NaiveTime::parse_from_str(time, "%H:%M")
+                .map_err(|_| format!("invalid time {:?} (expected HH:MM)", time))
+        };
+        let words: Vec<&str> = s.split_whitespace().collect();
+        match words.as_slice() {
+            ["session-end"] => Ok(Self::SessionEnd),
+            ["daily", time] => Ok(Self::Daily(parse_time(time)?)),
+            ["weekly", weekday, time] => {
+                let weekday = weekday
+                    .parse()
+                    .map_err(|_| format!("invalid weekday {:?}", weekday))?;
+                Ok(Self::Weekly(weekday, parse_time(time)?))
+            }
+            _ => Err(format!(
+                "invalid schedule {:?} (expected \"daily HH:MM\", \"weekly <day> HH:MM\" or \"session-end\")",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ScheduleTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Daily(time) => write!(f, "daily {}", time.format("%H:%M")),
+            Self::Weekly(weekday, time) => write!(
+                f,
+                "weekly {} {}",
+                weekday.to_string().to_lowercase(),
+                time.format("%H:%M")
+            ),
+            Self::SessionEnd => f.write_str("session-end"),
+        }
+    }
+}
+
+/// What a scheduled export does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportAction {
+    /// Write a dated tracker snapshot (`tracker-YYYY-MM-DD.tsv`)
+    Snapshot,
+    /// Copy the tracker and session files into a dated backup directory
+    Backup,
+    /// Upload the tracker to the web service with this title mapping
+    Upload { mapping: PathBuf },
+}
+
+impl FromStr for ExportAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "snapshot" => Ok(Self::Snapshot),
+            None if s == "backup" => Ok(Self::Backup),
+            Some(("upload", mapping)) if !mapping.is_empty() => Ok(Self::Upload {
+                mapping: mapping.into(),
+            }),
+            _ => Err(format!(
+                "invalid export action {:?} (expected \"snapshot\", \"backup\" or \"upload:<title-mapping.json>\")",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ExportAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Snapshot => f.write_str("snapshot"),
+            Self::Backup => f.write_str("backup"),
+            Self::Upload { mapping } => write!(f, "upload:{}", mapping.display()),
+        }
+    }
+}
+
+/// An export and when it runs (`snapshot@daily 04:00`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledExport {
+    pub action: ExportAction,
+    pub trigger: ScheduleTrigger,
+}
+
+impl FromStr for ScheduledExport {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (action, trigger) = s.rsplit_once('@').ok_or_else(|| {
+            format!(
+                "invalid scheduled export {:?} (expected <action>@<when>)",
+                s
+            )
+        })?;
+        Ok(Self {
+            action: action.trim().parse()?,
+            trigger: trigger.trim().parse()?,
+        })
+    }
+}
+
+impl fmt::Display for ScheduledExport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.action, self.trigger)
+    }
+}
+
+/// Last and next run of a scheduled export, as stored in `schedule.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleEntry {
+    /// The job as written on the command line
+    pub job: String,
+    pub last_run: Option<NaiveDateTime>,
+    /// Next run in local time (`None` for session-end jobs)
+    pub next_run: Option<NaiveDateTime>,
+}
+
+/// Decides which scheduled exports are due
+#[derive(Debug)]
+pub struct ExportScheduler {
+    path: PathBuf,
+    jobs: Vec<(ScheduledExport, ScheduleEntry)>,
+}
+
+impl ExportScheduler {
+    pub const FILE_NAME: &'static str = "schedule.json";
+    /// Wait before retrying a failed timed job
+    pub const RETRY_DELAY: TimeDelta = TimeDelta::minutes(15);
+
+    /// Scheduler for `jobs`, resuming the run times stored in `session_dir`
+    ///
+    /// A job that should have run since its last recorded run is due
+    /// immediately; a job without a recorded run waits for its next time.
+    pub fn new<P: AsRef<Path>>(
+        jobs: Vec<ScheduledExport>,
+        session_dir: P,
+        now: NaiveDateTime,
+    ) -> Self {
+        let path = session_dir.as_ref().join(Self::FILE_NAME);
+        let stored = match load_schedule_status(session_dir) {
+            Ok(stored) => stored,
+            Err(e) => {
+                warn!("Ignoring unreadable {}: {}", path.display(), e);
+                Vec::new()
+            }
+        };
+        let jobs = jobs
+            .into_iter()
+            .map(|job| {
+                let name = job.to_string();
+                let last_run = stored
+                    .iter()
+                    .find(|entry| entry.job == name)
+                    .and_then(|entry| entry.last_run);
+                let next_run = job.trigger.next_after(last_run.unwrap_or(now));
+                let entry = ScheduleEntry {
+                    job: name,
+                    last_run,
+                    next_run,
+                };
+                (job, entry)
+            })
+            .collect();
+        Self { path, jobs }
+    }
+
+    /// Last and next run of every job
+    pub fn entries(&self) -> Vec<ScheduleEntry> {
+        self.jobs.iter().map(|(_, entry)| entry.clone()).collect()
+    }
+
+    /// Jobs whose time has come
+    pub fn due(&self, now: NaiveDateTime) -> Vec<ScheduledExport> {
+        self.jobs
+            .iter()
+            .filter(|(_, entry)| entry.next_run.is_some_and(|next| next <= now))
+            .map(|(job, _)| job.clone())
+            .collect()
+    }
+
+    /// Session-end jobs
+    pub fn session_end(&self) -> Vec<ScheduledExport> {
+        self.jobs
+            .iter()
+            .filter(|(job, _)| job.trigger == ScheduleTrigger::SessionEnd)
+            .map(|(job, _)| job.clone())
+            .collect()
+    }
+
+    /// Record a successful run of `job` at `now`
+    pub fn record_run(&mut self, job: &ScheduledExport, now: NaiveDateTime) {
+        self.update(job, |entry| {
+            entry.last_run = Some(now);
+            entry.next_run = job.trigger.next_after(now);
+        });
+    }
+
+    /// Try a failed timed `job` again after [`Self::RETRY_DELAY`], keeping its
+    /// last successful run
+    pub fn retry_later(&mut self, job: &ScheduledExport, now: NaiveDateTime) {
+        self.update(job, |entry| {
+            if entry.next_run.is_some() {
+                entry.next_run = Some(now + Self::RETRY_DELAY);
+            }
+        });
+    }
+
+    fn update(&mut self, job: &ScheduledExport, f: impl FnOnce(&mut ScheduleEntry)) {
+        let Some((_, entry)) = self.jobs.iter_mut().find(|(j, _)| j == job) else {
+            return;
+        };
+        f(entry);
+        if let Err(e) = self.save() {
+            warn!("Failed to write {}: {}", self.path.display(), e);
+        }
+    }
+
+    /// Write the run times to `schedule.json`
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| Error::storage(dir, e))?;
+        }
+        write_atomic(&self.path, serde_json::to_string_pretty(&self.entries())?)
+    }
+}
+
+/// Run times stored by a tracker using `session_dir` (empty when none)
+pub fn load_schedule_status<P: AsRef<Path>>(session_dir: P) -> Result<Vec<ScheduleEntry>> {
+    let path = session_dir.as_ref().join(ExportScheduler::FILE_NAME);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| Error::storage(&path, e))?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn job(s: &str) -> ScheduledExport {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_jobs() {
+        assert_eq!(
+            job("snapshot@daily 04:00"),
+            ScheduledExport {
+                action: ExportAction::Snapshot,
+                trigger: ScheduleTrigger::Daily(NaiveTime::from_hms_opt(4, 0, 0).unwrap()),
+            }
+        );
+        assert_eq!(
+            job("upload:C:\\infst\\mapping.json@weekly Sun 03:30").action,
+            ExportAction::Upload {
+                mapping: "C:\\infst\\mapping.json".into()
+            }
+        );
+        assert_eq!(
+            job("backup@session-end").trigger,
+            ScheduleTrigger::SessionEnd
+        );
+
+        for s in [
+            "snapshot",
+            "snapshot@hourly",
+            "snapshot@daily 25:00",
+            "snapshot@weekly someday 04:00",
+            "upload:@daily 04:00",
+            "archive@daily 04:00",
+        ] {
+            assert!(s.parse::<ScheduledExport>().is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_display_roundtrip() {
+        for s in [
+            "snapshot@daily 04:00",
+            "backup@weekly sun 03:30",
+            "upload:mapping.json@session-end",
+        ] {
+            assert_eq!(job(s).to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_next_after() {
+        let daily = job("snapshot@daily 04:00").trigger;
+        assert_eq!(
+            daily.next_after(at("2025-06-01 03:59")),
+            Some(at("2025-06-01 04:00"))
+        );
+        assert_eq!(
+            daily.next_after(at("2025-06-01 04:00")),
+            Some(at("2025-06-02 04:00"))
+        );
+
+        // 2025-06-01 is a Sunday
+        let weekly = job("snapshot@weekly wed 20:00").trigger;
+        assert_eq!(
+            weekly.next_after(at("2025-06-01 12:00")),
+            Some(at("2025-06-04 20:00"))
+        );
+        assert_eq!(
+            weekly.next_after(at("2025-06-04 21:00")),
+            Some(at("2025-06-11 20:00"))
+        );
+
+        assert_eq!(
+            ScheduleTrigger::SessionEnd.next_after(at("2025-06-01 12:00")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_due_and_session_end() {
+        let temp = TempDir::new().unwrap();
+        let jobs = vec![job("snapshot@daily 04:00"), job("backup@session-end")];
+        let mut scheduler = ExportScheduler::new(jobs, temp.path(), at("2025-06-01 03:00"));
+
+        assert!(scheduler.due(at("2025-06-01 03:59")).is_empty());
+        assert_eq!(
+            scheduler.due(at("2025-06-01 04:01")),
+            vec![job("snapshot@daily 04:00")]
+        );
+        scheduler.record_run(&job("snapshot@daily 04:00"), at("2025-06-01 04:01"));
+        assert!(scheduler.due(at("2025-06-01 05:00")).is_empty());
+
+        assert_eq!(scheduler.session_end(), vec![job("backup@session-end")]);
+        scheduler.record_run(&job("backup@session-end"), at("2025-06-01 05:00"));
+
+        let entries = load_schedule_status(temp.path()).unwrap();
+        assert_eq!(entries, scheduler.entries());
+        assert_eq!(entries[0].last_run, Some(at("2025-06-01 04:01")));
+        assert_eq!(entries[0].next_run, Some(at("2025-06-02 04:00")));
+        assert_eq!(entries[1].next_run, None);
+    }
+
+    #[test]
+    fn test_missed_run_is_due_on_start() {
+        let temp = TempDir::new().unwrap();
+        let jobs = vec![job("snapshot@daily 04:00")];
+        let mut scheduler = ExportScheduler::new(jobs.clone(), temp.path(), at("2025-06-01 03:00"));
+        scheduler.record_run(&jobs[0], at("2025-06-01 04:00"));
+
+        // Tracker not running on 2025-06-02 04:00
+        let scheduler = ExportScheduler::new(jobs, temp.path(), at("2025-06-02 20:00"));
+        assert_eq!(
+            scheduler.entries()[0].next_run,
+            Some(at("2025-06-02 04:00"))
+        );
+        assert_eq!(scheduler.due(at("2025-06-02 20:00")).len(), 1);
+    }
+
+    #[test]
+    fn test_failed_run_is_retried() {
+        let temp = TempDir::new().unwrap();
+        let jobs = vec![job("snapshot@daily 04:00"), job("backup@session-end")];
+        let mut scheduler = ExportScheduler::new(jobs.clone(), temp.path(), at("2025-06-01 03:00"));
+
+        scheduler.retry_later(&jobs[0], at("2025-06-01 04:00"));
+        scheduler.retry_later(&jobs[1], at("2025-06-01 04:00"));
+        let entries = load_schedule_status(temp.path()).unwrap();
+        assert_eq!(entries[0].last_run, None);
+        assert_eq!(entries[0].next_run, Some(at("2025-06-01 04:15")));
+        assert_eq!(entries[1].next_run, None);
+
+        assert!(scheduler.due(at("2025-06-01 04:14")).is_empty());
+        assert_eq!(scheduler.due(at("2025-06-01 04:15")), vec![jobs[0].clone()]);
+    }
+
+    #[test]
+    fn test_missing_status_file() {
+        let temp = TempDir::new().unwrap();
+        assert!(load_schedule_status(temp.path()).unwrap().is_empty());
+    }
+}
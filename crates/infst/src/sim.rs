@@ -0,0 +1,398 @@
+//! Simulated game for end-to-end tests of the tracking loop
+//!
+//! [`SimGame`] stands in for the game process in [`Infst::run`]. Its memory
+//! is a [`MockMemoryReader`] rebuilt for each scripted screen, laid out at the
+//! addresses in [`SimGame::offsets`]. The script advances by polls rather than
+//! wall-clock time: every game state read by the tracker counts as one poll,
+//! so runs are deterministic however slow the machine is. The process "exits"
+//! once the last screen has been polled for its full duration.
+//!
+//! ```ignore
+//! use infst::sim::{SimGame, SimResult, SimScreen};
+//!
+//! let game = SimGame::new([
+//!     (SimScreen::SongSelect, 2),
+//!     (SimScreen::Playing { song_id: 1000, difficulty: Difficulty::SpA }, 2),
+//!     (SimScreen::Result(result), 2),
+//!     (SimScreen::SongSelect, 2),
+//! ])
+//! .with_unlocked([1000]);
+//!
+//! let mut infst = Infst::builder()
+//!     .offsets(SimGame::offsets())
+//!     .song_db(song_db)
+//!     .build()?;
+//! infst.run(&game, &AtomicBool::new(false))?;
+//! ```
+//!
+//! [`Infst::run`]: crate::Infst::run
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::chart::{Difficulty, UnlockData};
+use crate::config::watchdog::MAX_HANDLE_REFRESHES;
+use crate::error::{Error, Result};
+use crate::offset::OffsetsCollection;
+use crate::play::PlayType;
+use crate::process::layout::{judge, play, settings};
+use crate::process::{
+    GameProcess, MockMemoryBuilder, MockMemoryReader, ProcessInfo, ReadMemory, ReadPolicy,
+    with_handle_recovery,
+};
+use crate::score::{Judge, Lamp};
+
+/// Base address of the simulated module
+pub const SIM_BASE: u64 = 0x1_4000_0000;
+
+const JUDGE_DATA: u64 = 0x1000;
+const PLAY_SETTINGS: u64 = 0x2000;
+const PLAY_DATA: u64 = 0x3000;
+const CURRENT_SONG: u64 = 0x3100;
+const SONG_LIST: u64 = 0x4000;
+const DATA_MAP: u64 = 0x6000;
+const UNLOCK_DATA: u64 = 0x8000;
+const MEMORY_SIZE: usize = 0x10000;
+
+/// Most unlock entries that fit after [`UNLOCK_DATA`]
+const MAX_UNLOCKED: usize = (MEMORY_SIZE - UNLOCK_DATA as usize) / UnlockData::MEMORY_SIZE;
+
+/// What the simulated game is showing
+#[derive(Debug, Clone)]
+pub enum SimScreen {
+    SongSelect,
+    Playing {
+        song_id: u32,
+        difficulty: Difficulty,
+    },
+    Result(SimResult),
+}
+
+/// Contents of a simulated result screen
+#[derive(Debug, Clone)]
+pub struct SimResult {
+    pub song_id: u32,
+    pub difficulty: Difficulty,
+    pub lamp: Lamp,
+    /// Judge counts, written to the side given by `judge.play_type`
+    ///
+    /// DP counts are split between both sides, as the game does.
+    pub judge: Judge,
+}
+
+/// Scripted fake game process
+pub struct SimGame {
+    /// Screens and the number of polls each one lasts
+    script: Vec<(SimScreen, u32)>,
+    /// Songs with an unlock entry (all difficulties unlocked)
+    unlocked: Vec<u32>,
+    /// Step on entering which the handle loses access until refreshed
+    handle_loss_at: Option<usize>,
+    state: Mutex<SimState>,
+}
+
+struct SimState {
+    /// Index into `script`; past the end once the game has exited
+    step: usize,
+    /// Polls seen on the current step
+    polls: u32,
+    memory: MockMemoryReader,
+    /// Reads fail with [`Error::ProcessHandleLost`] until the handle is refreshed
+    handle_lost: bool,
+    handle_refreshes: u32,
+}
+
+impl SimGame {
+    /// Create a game that plays `script`, a list of screens and how many
+    /// polls each lasts (at least one)
+    pub fn new(script: impl IntoIterator<Item = (SimScreen, u32)>) -> Self {
+        let game = Self {
+            script: script.into_iter().collect(),
+            unlocked: Vec::new(),
+            handle_loss_at: None,
+            state: Mutex::new(SimState {
+                step: 0,
+                polls: 0,
+                memory: MockMemoryReader::new(Vec::new()),
+                handle_lost: false,
+                handle_refreshes: 0,
+            }),
+        };
+        game.enter_step(0);
+        game
+    }
+
+    /// Write unlock entries for `song_ids`, so they appear in the tracker export
+    pub fn with_unlocked(mut self, song_ids: impl IntoIterator<Item = u32>) -> Self {
+        self.unlocked = song_ids.into_iter().take(MAX_UNLOCKED).collect();
+        self.enter_step(0);
+        self
+    }
+
+    /// Lose handle access when the screen at index `step` is entered, as a
+    /// focus-loss hiccup would; reads fail until the handle is refreshed
+    pub fn with_handle_loss_at(mut self, step: usize) -> Self {
+        self.handle_loss_at = Some(step);
+        self
+    }
+
+    /// Times the handle was refreshed after losing access
+    pub fn handle_refreshes(&self) -> u32 {
+        self.lock().handle_refreshes
+    }
+
+    /// Offsets matching the simulated memory layout
+    pub fn offsets() -> OffsetsCollection {
+        OffsetsCollection {
+            version: "SIM".to_string(),
+            song_list: SIM_BASE + SONG_LIST,
+            data_map: SIM_BASE + DATA_MAP,
+            judge_data: SIM_BASE + JUDGE_DATA,
+            play_data: SIM_BASE + PLAY_DATA,
+            play_settings: SIM_BASE + PLAY_SETTINGS,
+            unlock_data: SIM_BASE + UNLOCK_DATA,
+            current_song: SIM_BASE + CURRENT_SONG,
+            ..OffsetsCollection::default()
+        }
+    }
+
+    /// Index of the screen currently shown (equal to the script length once exited)
+    pub fn step(&self) -> usize {
+        self.lock().step
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SimState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Count one poll of the game state, moving to the next screen when due
+    fn poll(&self) {
+        let mut state = self.lock();
+        let Some((_, polls)) = self.script.get(state.step) else {
+            return;
+        };
+        state.polls += 1;
+        if state.polls > *polls {
+            let next = state.step + 1;
+            drop(state);
+            self.enter_step(next);
+            self.lock().polls = 1;
+        }
+    }
+
+    fn enter_step(&self, step: usize) {
+        let mut state = self.lock();
+        state.step = step;
+        state.polls = 0;
+        if self.handle_loss_at == Some(step) {
+            state.handle_lost = true;
+        }
+        // After the script ends the last screen stays in memory
+        if let Some((screen, _)) = self.script.get(step) {
+            state.memory = self.screen_memory(screen);
+        }
+    }
+
+    fn screen_memory(&self, screen: &SimScreen) -> MockMemoryReader {
+        let offset = |address: u64| address as usize;
+        let mut memory = MockMemoryBuilder::new()
+            .base(SIM_BASE)
+            .with_size(MEMORY_SIZE);
+
+        for (i, &song_id) in self.unlocked.iter().enumerate() {
+            let entry = offset(UNLOCK_DATA) + i * UnlockData::MEMORY_SIZE;
+            memory = memory
+                .write_u32(entry, song_id)
+                .write_i32(entry + 4, 1)
+                .write_i32(entry + 8, -1);
+        }
+
+        match screen {
+            SimScreen::SongSelect => memory
+                .write_i32(offset(PLAY_SETTINGS - settings::SONG_SELECT_MARKER), 1)
+                .build(),
+            SimScreen::Playing {
+                song_id,
+                difficulty,
+            } => memory
+                .write_i32(offset(JUDGE_DATA + judge::STATE_MARKER_1), 1)
+                .write_i32(offset(JUDGE_DATA + judge::STATE_MARKER_2), 1)
+                .write_u32(offset(CURRENT_SONG), *song_id)
+                .write_i32(offset(CURRENT_SONG + 4), *difficulty as i32)
+                .build(),
+            SimScreen::Result(result) => write_judge(memory, &result.judge)
+                .write_u32(offset(PLAY_DATA + play::SONG_ID), result.song_id)
+                .write_i32(
+                    offset(PLAY_DATA + play::DIFFICULTY),
+                    result.difficulty as i32,
+                )
+                .write_i32(offset(PLAY_DATA + play::LAMP), result.lamp as i32)
+                .build(),
+        }
+    }
+}
+
+/// Write judge counts into the judge block
+fn write_judge(memory: MockMemoryBuilder, judge: &Judge) -> MockMemoryBuilder {
+    let counts = [
+        (judge::P1_PGREAT, judge::P2_PGREAT, judge.pgreat),
+        (judge::P1_GREAT, judge::P2_GREAT, judge.great),
+        (judge::P1_GOOD, judge::P2_GOOD, judge.good),
+        (judge::P1_BAD, judge::P2_BAD, judge.bad),
+        (judge::P1_POOR, judge::P2_POOR, judge.poor),
+        (
+            judge::P1_COMBO_BREAK,
+            judge::P2_COMBO_BREAK,
+            judge.combo_break,
+        ),
+        (judge::P1_FAST, judge::P2_FAST, judge.fast),
+        (judge::P1_SLOW, judge::P2_SLOW, judge.slow),
+        (
+            judge::P1_MEASURE_END,
+            judge::P2_MEASURE_END,
+            u32::from(judge.premature_end),
+        ),
+    ];
+    counts.into_iter().fold(memory, |memory, (p1, p2, count)| {
+        let (p1_count, p2_count) = match judge.play_type {
+            PlayType::P1 => (count, 0),
+            PlayType::P2 => (0, count),
+            PlayType::Dp => (count - count / 2, count / 2),
+        };
+        memory
+            .write_u32((JUDGE_DATA + p1) as usize, p1_count)
+            .write_u32((JUDGE_DATA + p2) as usize, p2_count)
+    })
+}
+
+impl ProcessInfo for SimGame {
+    fn pid(&self) -> u32 {
+        0
+    }
+
+    fn base_address(&self) -> u64 {
+        SIM_BASE
+    }
+
+    fn module_size(&self) -> u32 {
+        MEMORY_SIZE as u32
+    }
+
+    fn is_alive(&self) -> bool {
+        self.lock().step < self.script.len()
+    }
+}
+
+/// Reader over the current screen's memory
+pub struct SimReader<'a> {
+    game: &'a SimGame,
+}
+
+impl ReadMemory for SimReader<'_> {
+    /// Reading the game state markers counts as one poll
+    fn read_bytes(&self, address: u64, size: usize) -> Result<Vec<u8>> {
+        with_handle_recovery(self.game, MAX_HANDLE_REFRESHES, || {
+            if self.game.lock().handle_lost {
+                return Err(Error::ProcessHandleLost(format!(
+                    "simulated access loss at {:#x}",
+                    address
+                )));
+            }
+            if address == SIM_BASE + JUDGE_DATA + judge::STATE_MARKER_1 {
+                self.game.poll();
+            }
+            self.game.lock().memory.read_bytes(address, size)
+        })
+    }
+
+    fn base_address(&self) -> u64 {
+        SIM_BASE
+    }
+}
+
+impl GameProcess for SimGame {
+    type Reader<'a> = SimReader<'a>;
+
+    /// Read limits and auditing do not apply to the simulation
+    fn reader(&self, _policy: &Arc<ReadPolicy>) -> SimReader<'_> {
+        SimReader { game: self }
+    }
+
+    fn query_module_base(&self) -> Result<u64> {
+        Ok(SIM_BASE)
+    }
+
+    fn reopen(&self) -> Result<Self> {
+        Err(Error::ProcessNotFound(
+            "simulated game cannot be reopened".to_string(),
+        ))
+    }
+
+    fn refresh_handle(&self) -> Result<()> {
+        let mut state = self.lock();
+        state.handle_lost = false;
+        state.handle_refreshes += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_advances_per_poll() {
+        let offsets = SimGame::offsets();
+        let game = SimGame::new([
+            (SimScreen::SongSelect, 1),
+            (
+                SimScreen::Playing {
+                    song_id: 1000,
+                    difficulty: Difficulty::SpA,
+                },
+                2,
+            ),
+        ]);
+        let reader = game.reader(&Arc::new(ReadPolicy::new(None, false)));
+        let marker = offsets.judge_data + judge::STATE_MARKER_1;
+
+        assert_eq!(reader.read_i32(marker).unwrap(), 0);
+        assert_eq!(
+            reader
+                .read_i32(offsets.play_settings - settings::SONG_SELECT_MARKER)
+                .unwrap(),
+            1
+        );
+        assert_eq!(reader.read_i32(marker).unwrap(), 1);
+        assert_eq!(reader.read_u32(offsets.current_song).unwrap(), 1000);
+        assert!(game.is_alive());
+        assert_eq!(reader.read_i32(marker).unwrap(), 1);
+        assert!(game.is_alive());
+        // The last screen has been shown for its two polls
+        reader.read_i32(marker).unwrap();
+        assert!(!game.is_alive());
+        assert_eq!(game.step(), 2);
+    }
+
+    #[test]
+    fn test_dp_judge_is_split_between_sides() {
+        let judge = Judge {
+            play_type: PlayType::Dp,
+            pgreat: 101,
+            great: 10,
+            ..Judge::default()
+        };
+        let memory = write_judge(
+            MockMemoryBuilder::new()
+                .base(SIM_BASE)
+                .with_size(MEMORY_SIZE),
+            &judge,
+        )
+        .build();
+        let raw = memory
+            .read_struct::<crate::score::RawJudgeData>(SIM_BASE + JUDGE_DATA)
+            .unwrap();
+        let read = Judge::from_raw_data(raw);
+        assert_eq!(read.play_type, PlayType::Dp);
+        assert_eq!((read.pgreat, read.great), (101, 10));
+    }
+}
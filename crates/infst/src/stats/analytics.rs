@@ -0,0 +1,177 @@
+//! Per-chart analytics aggregated from recorded plays
+
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::chart::ChartId;
+use crate::play::PlayData;
+use crate::score::Lamp;
+
+/// Aggregated statistics for a single chart
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct ChartAnalytics {
+    /// Number of plays aggregated (premature ends excluded)
+    pub play_count: u32,
+    /// Average EX score
+    pub average_score: f64,
+    /// Population standard deviation of EX score
+    pub score_std_dev: f64,
+    /// Average bad + poor count (plays without a valid miss count are skipped)
+    pub average_miss_count: Option<f64>,
+    /// Total fast judgments divided by total slow judgments
+    pub fast_slow_ratio: Option<f64>,
+    /// Share of plays short of a full combo by exactly one combo break
+    ///
+    /// Not a choke rate: memory doesn't expose where the combo was lost, so a
+    /// break on the first note counts the same as one on the last.
+    pub single_break_rate: f64,
+}
+
+/// Compute analytics for one chart from a list of plays
+///
+/// Plays for other charts are ignored. Returns `None` if no usable play exists.
+pub fn chart_analytics(plays: &[PlayData], chart: &ChartId) -> Option<ChartAnalytics> {
+    let chart_plays: Vec<&PlayData> = plays
+        .iter()
+        .filter(|p| p.chart.song_id == chart.song_id && p.chart.difficulty == chart.difficulty)
+        .collect();
+    aggregate(&chart_plays)
+}
+
+/// Compute analytics for every chart that appears in `plays`
+///
+/// Plays whose song ID could not be resolved (song_id == 0) are skipped.
+pub fn collect_chart_analytics(plays: &[PlayData]) -> HashMap<ChartId, ChartAnalytics> {
+    let mut grouped: HashMap<ChartId, Vec<&PlayData>> = HashMap::new();
+    for play in plays.iter().filter(|p| p.chart.song_id != 0) {
+        grouped.entry(play.chart.id()).or_default().push(play);
+    }
+
+    grouped
+        .into_iter()
+        .filter_map(|(chart, plays)| aggregate(&plays).map(|a| (chart, a)))
+        .collect()
+}
+
+fn aggregate(plays: &[&PlayData]) -> Option<ChartAnalytics> {
+    let plays: Vec<&PlayData> = plays
+        .iter()
+        .copied()
+        .filter(|p| !p.judge.premature_end)
+        .collect();
+    if plays.is_empty() {
+        return None;
+    }
+
+    let count = plays.len() as f64;
+    let average_score = plays.iter().map(|p| p.ex_score as f64).sum::<f64>() / count;
+    let variance = plays
+        .iter()
+        .map(|p| (p.ex_score as f64 - average_score).powi(2))
+        .sum::<f64>()
+        / count;
+
+    let misses: Vec<f64> = plays
+        .iter()
+        .filter(|p| p.miss_count_valid())
+        .map(|p| p.miss_count() as f64)
+        .collect();
+    let average_miss_count = if misses.is_empty() {
+        None
+    } else {
+        Some(misses.iter().sum::<f64>() / misses.len() as f64)
+    };
+
+    let fast: u32 = plays.iter().map(|p| p.judge.fast).sum();
+    let slow: u32 = plays.iter().map(|p| p.judge.slow).sum();
+    let fast_slow_ratio = if slow > 0 {
+        Some(fast as f64 / slow as f64)
+    } else {
+        None
+    };
+
+    let single_breaks = plays
+        .iter()
+        .filter(|p| p.lamp < Lamp::FullCombo && p.judge.combo_break == 1)
+        .count();
+
+    Some(ChartAnalytics {
+        play_count: plays.len() as u32,
+        average_score,
+        score_std_dev: variance.sqrt(),
+        average_miss_count,
+        fast_slow_ratio,
+        single_break_rate: single_breaks as f64 / count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::Difficulty;
+    use crate::play::test_support::PlayBuilder;
+    use crate::score::{Grade, Judge};
+
+    fn make_play(song_id: u32, ex_score: u32, bad: u32, combo_break: u32, lamp: Lamp) -> PlayData {
+        PlayBuilder::new()
+            .song_id(song_id)
+            .ex_score(ex_score)
+            .grade(Grade::Aa)
+            .lamp(lamp)
+            .judge(Judge {
+                bad,
+                fast: 30,
+                slow: 10,
+                combo_break,
+                ..Default::default()
+            })
+            .build()
+    }
+
+    fn spa(song_id: u32) -> ChartId {
+        ChartId::new(song_id, Difficulty::SpA)
+    }
+
+    #[test]
+    fn test_chart_analytics_basic() {
+        let plays = vec![
+            make_play(1000, 1500, 4, 3, Lamp::Clear),
+            make_play(1000, 1700, 2, 1, Lamp::HardClear),
+            make_play(2000, 1900, 0, 0, Lamp::FullCombo),
+        ];
+
+        let analytics = chart_analytics(&plays, &spa(1000)).unwrap();
+        assert_eq!(analytics.play_count, 2);
+        assert!((analytics.average_score - 1600.0).abs() < f64::EPSILON);
+        assert!((analytics.score_std_dev - 100.0).abs() < f64::EPSILON);
+        assert_eq!(analytics.average_miss_count, Some(3.0));
+        assert_eq!(analytics.fast_slow_ratio, Some(3.0));
+        assert!((analytics.single_break_rate - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_chart_analytics_no_plays() {
+        let plays = vec![make_play(1000, 1500, 4, 3, Lamp::Clear)];
+        assert!(chart_analytics(&plays, &spa(2000)).is_none());
+    }
+
+    #[test]
+    fn test_chart_analytics_skips_premature_end() {
+        let mut play = make_play(1000, 200, 0, 0, Lamp::Failed);
+        play.judge.premature_end = true;
+        assert!(chart_analytics(&[play], &spa(1000)).is_none());
+    }
+
+    #[test]
+    fn test_collect_chart_analytics_skips_unresolved() {
+        let plays = vec![
+            make_play(0, 1500, 4, 3, Lamp::Clear),
+            make_play(1000, 1700, 2, 1, Lamp::HardClear),
+        ];
+        let all = collect_chart_analytics(&plays);
+        assert_eq!(all.len(), 1);
+        assert!(all.contains_key(&spa(1000)));
+    }
+}
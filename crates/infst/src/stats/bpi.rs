@@ -0,0 +1,192 @@
+//! BPI (Beat Power Index) computation
+//!
+//! BPI rates an EX score against the kaiden (皆伝) average and the world
+//! record of a chart: 0 is the kaiden average, 100 is the world record.
+//!
+//! Reference data is not bundled; it is loaded from a JSON file with one
+//! object per chart:
+//!
+//! ```json
+//! [
+//!   {
+//!     "title": "冥",
+//!     "difficulty": "SPA",
+//!     "kaiden_average": 2810,
+//!     "world_record": 3282,
+//!     "coefficient": 1.175
+//!   }
+//! ]
+//! ```
+//!
+//! - `title` must match the in-game title
+//! - `difficulty` uses the short names (`SPN`, `SPH`, `SPA`, `SPL`, `DPN`, ...)
+//! - `coefficient` is optional and defaults to [`DEFAULT_BPI_COEFFICIENT`]
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::chart::Difficulty;
+use crate::error::Result;
+use crate::play::PlayData;
+
+/// Coefficient used when a chart doesn't specify its own
+pub const DEFAULT_BPI_COEFFICIENT: f64 = 1.175;
+
+/// Lowest BPI value reported
+const BPI_MIN: f64 = -15.0;
+
+/// Reference data for one chart
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BpiEntry {
+    pub title: String,
+    #[serde(deserialize_with = "deserialize_short_difficulty")]
+    pub difficulty: Difficulty,
+    pub kaiden_average: u32,
+    pub world_record: u32,
+    #[serde(default)]
+    pub coefficient: Option<f64>,
+}
+
+pub(crate) fn deserialize_short_difficulty<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Difficulty, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    name.parse()
+        .map_err(|_| serde::de::Error::custom(format!("unknown difficulty: {}", name)))
+}
+
+/// BPI reference data keyed by title and difficulty
+#[derive(Debug, Clone, Default)]
+pub struct BpiTable {
+    entries: HashMap<(String, Difficulty), BpiEntry>,
+}
+
+impl BpiTable {
+    /// Parse a BPI data file's content
+    pub fn from_json(content: &str) -> Result<Self> {
+        let list: Vec<BpiEntry> = serde_json::from_str(content)?;
+        let entries = list
+            .into_iter()
+            .map(|entry| ((entry.title.clone(), entry.difficulty), entry))
+            .collect();
+        Ok(Self { entries })
+    }
+
+    /// Load a BPI data file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn get(&self, title: &str, difficulty: Difficulty) -> Option<&BpiEntry> {
+        self.entries.get(&(title.to_string(), difficulty))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// BPI of a play, if the chart has reference data
+    pub fn bpi_for(&self, play: &PlayData) -> Option<f64> {
+        let entry = self.get(&play.chart.title, play.chart.difficulty)?;
+        calculate_bpi(
+            play.ex_score,
+            play.chart.total_notes,
+            entry.kaiden_average,
+            entry.world_record,
+            entry.coefficient.unwrap_or(DEFAULT_BPI_COEFFICIENT),
+        )
+    }
+}
+
+/// Score transform used by the BPI formula
+fn pgf(score: u32, max_ex: u32) -> f64 {
+    if score >= max_ex {
+        return max_ex as f64 * 0.8;
+    }
+    let rate = score as f64 / max_ex as f64;
+    1.0 + (rate - 0.5) / (1.0 - rate)
+}
+
+/// Calculate BPI for an EX score
+///
+/// Returns `None` if the reference data is unusable (no notes, or a world
+/// record not above the kaiden average). Result is rounded to 2 decimals and
+/// clamped at -15.
+pub fn calculate_bpi(
+    ex_score: u32,
+    total_notes: u32,
+    kaiden_average: u32,
+    world_record: u32,
+    coefficient: f64,
+) -> Option<f64> {
+    let max_ex = total_notes * 2;
+    if max_ex == 0 || world_record <= kaiden_average || kaiden_average == 0 {
+        return None;
+    }
+
+    let kaiden = pgf(kaiden_average, max_ex);
+    let s = (pgf(ex_score, max_ex) / kaiden).ln();
+    let z = (pgf(world_record, max_ex) / kaiden).ln();
+
+    let bpi = if s >= 0.0 {
+        100.0 * s.powf(coefficient) / z.powf(coefficient)
+    } else {
+        (-100.0 * (-s).powf(coefficient) / z.powf(coefficient)).max(BPI_MIN)
+    };
+    Some((bpi * 100.0).round() / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_bpi_reference_points() {
+        let kaiden = calculate_bpi(1600, 1000, 1600, 1900, DEFAULT_BPI_COEFFICIENT).unwrap();
+        assert!(kaiden.abs() < 0.01);
+
+        let wr = calculate_bpi(1900, 1000, 1600, 1900, DEFAULT_BPI_COEFFICIENT).unwrap();
+        assert!((wr - 100.0).abs() < 0.01);
+
+        let between = calculate_bpi(1750, 1000, 1600, 1900, DEFAULT_BPI_COEFFICIENT).unwrap();
+        assert!(between > 0.0 && between < 100.0);
+    }
+
+    #[test]
+    fn test_calculate_bpi_clamps_low_scores() {
+        let bpi = calculate_bpi(100, 1000, 1600, 1900, DEFAULT_BPI_COEFFICIENT).unwrap();
+        assert!((bpi - BPI_MIN).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_calculate_bpi_invalid_reference() {
+        assert!(calculate_bpi(1600, 0, 1600, 1900, DEFAULT_BPI_COEFFICIENT).is_none());
+        assert!(calculate_bpi(1600, 1000, 1900, 1900, DEFAULT_BPI_COEFFICIENT).is_none());
+    }
+
+    #[test]
+    fn test_bpi_table_from_json() {
+        let json = r#"[
+            {"title": "Test Song", "difficulty": "SPA", "kaiden_average": 1600, "world_record": 1900},
+            {"title": "Other", "difficulty": "DPA", "kaiden_average": 1500, "world_record": 1800, "coefficient": 1.5}
+        ]"#;
+        let table = BpiTable::from_json(json).unwrap();
+        assert_eq!(table.len(), 2);
+
+        let entry = table.get("Test Song", Difficulty::SpA).unwrap();
+        assert_eq!(entry.kaiden_average, 1600);
+        assert_eq!(entry.coefficient, None);
+        assert_eq!(
+            table.get("Other", Difficulty::DpA).unwrap().coefficient,
+            Some(1.5)
+        );
+        assert!(table.get("Test Song", Difficulty::SpH).is_none());
+    }
+}
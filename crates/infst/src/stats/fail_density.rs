@@ -0,0 +1,172 @@
+//! Where failed plays end, against chart note density
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::chart::{ChartId, DensityMap};
+use crate::play::PlayData;
+
+/// Fails needed before [`DenseFailReport::fails_on_dense_sections`] draws a conclusion
+pub const MIN_FAILS_FOR_TENDENCY: u32 = 5;
+
+/// Fails cluster on dense sections when their share of fails is this many
+/// times the share of notes in dense sections
+pub const DENSE_FAIL_TENDENCY: f64 = 1.25;
+
+/// Failed plays on one chart
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChartDenseFails {
+    pub chart: ChartId,
+    pub title: String,
+    /// Failed plays with a known fail point
+    pub fails: u32,
+    /// Of those, fails in a dense section
+    pub dense_fails: u32,
+    /// Notes per second of the chart's densest section
+    pub peak_notes_per_second: f64,
+}
+
+/// Fail points of failed plays on charts with a density profile
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DenseFailReport {
+    /// Failed plays with a known fail point
+    pub fails: u32,
+    /// Of those, fails in a dense section
+    pub dense_fails: u32,
+    /// Share of fails expected in dense sections if density made no
+    /// difference (share of notes in dense sections, averaged over the fails)
+    pub expected_dense_share: f64,
+    /// Charts with fails, most dense fails first
+    pub charts: Vec<ChartDenseFails>,
+}
+
+impl DenseFailReport {
+    /// Share of fails in dense sections (`None` without fails)
+    pub fn dense_share(&self) -> Option<f64> {
+        (self.fails > 0).then(|| f64::from(self.dense_fails) / f64::from(self.fails))
+    }
+
+    /// Whether fails land in dense sections more often than their share of
+    /// notes explains ("you fail on dense sections")
+    pub fn fails_on_dense_sections(&self) -> bool {
+        self.fails >= MIN_FAILS_FOR_TENDENCY
+            && self.dense_share().is_some_and(|share| {
+                share > 0.0 && share >= self.expected_dense_share * DENSE_FAIL_TENDENCY
+            })
+    }
+}
+
+/// Match the fail point of each failed play against its chart's density
+///
+/// Only plays with [`PlayData::failed_at_percent`] on charts in `densities`
+/// count; premature ends are skipped since the player chose where they ended.
+pub fn dense_section_fails(plays: &[PlayData], densities: &DensityMap) -> DenseFailReport {
+    let mut report = DenseFailReport::default();
+    let mut expected = 0.0;
+    let mut charts: HashMap<ChartId, ChartDenseFails> = HashMap::new();
+
+    for play in plays.iter().filter(|p| !p.judge.premature_end) {
+        let Some(percent) = play.failed_at_percent else {
+            continue;
+        };
+        let chart = play.chart.id();
+        let Some(profile) = densities.get(chart) else {
+            continue;
+        };
+        let Some(section) = profile.section_at_note_percent(percent) else {
+            continue;
+        };
+        let dense = profile.is_dense(section);
+
+        report.fails += 1;
+        report.dense_fails += u32::from(dense);
+        expected += profile.dense_note_share();
+        let entry = charts.entry(chart).or_insert_with(|| ChartDenseFails {
+            chart,
+            title: play.chart.title.to_string(),
+            fails: 0,
+            dense_fails: 0,
+            peak_notes_per_second: profile.peak_notes_per_second(),
+        });
+        entry.fails += 1;
+        entry.dense_fails += u32::from(dense);
+    }
+
+    if report.fails > 0 {
+        report.expected_dense_share = expected / f64::from(report.fails);
+    }
+    report.charts = charts.into_values().collect();
+    report.charts.sort_by(|a, b| {
+        b.dense_fails
+            .cmp(&a.dense_fails)
+            .then(b.fails.cmp(&a.fails))
+            .then(a.chart.cmp(&b.chart))
+    });
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::chart::{DensityProfile, Difficulty};
+    use crate::play::test_support::PlayBuilder;
+    use crate::score::{Grade, Lamp};
+
+    fn failed_play(song_id: u32, percent: Option<f64>) -> PlayData {
+        PlayBuilder::new()
+            .song_id(song_id)
+            .title(&format!("Song {}", song_id))
+            .total_notes(100)
+            .ex_score(50)
+            .grade(Grade::F)
+            .lamp(Lamp::Failed)
+            .failed_at_percent(percent)
+            .build()
+    }
+
+    fn densities() -> DensityMap {
+        let mut map = DensityMap::new();
+        // Section 2 is dense and holds half the notes (notes 21-70)
+        map.insert(
+            ChartId::new(1000, Difficulty::SpA),
+            DensityProfile {
+                section_ms: 1000,
+                notes: vec![10, 10, 50, 10, 20],
+            },
+        );
+        map
+    }
+
+    #[test]
+    fn test_dense_section_fails() {
+        let mut plays: Vec<_> = [30.0, 40.0, 60.0, 65.0, 90.0, 95.0]
+            .into_iter()
+            .map(|percent| failed_play(1000, Some(percent)))
+            .collect();
+        // Not counted: no fail point, no profile, quit
+        plays.push(failed_play(1000, None));
+        plays.push(failed_play(2000, Some(50.0)));
+        let mut quit = failed_play(1000, Some(50.0));
+        quit.judge.premature_end = true;
+        plays.push(quit);
+
+        let report = dense_section_fails(&plays, &densities());
+        assert_eq!((report.fails, report.dense_fails), (6, 4));
+        assert!((report.expected_dense_share - 0.5).abs() < 1e-9);
+        assert!(report.fails_on_dense_sections());
+        assert_eq!(report.charts.len(), 1);
+        assert_eq!(report.charts[0].dense_fails, 4);
+        assert!((report.charts[0].peak_notes_per_second - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_no_tendency_without_enough_fails() {
+        let plays = vec![failed_play(1000, Some(50.0)), failed_play(1000, Some(60.0))];
+        let report = dense_section_fails(&plays, &densities());
+        assert_eq!(report.dense_share(), Some(1.0));
+        assert!(!report.fails_on_dense_sections());
+        assert_eq!(dense_section_fails(&[], &densities()).dense_share(), None);
+    }
+}
@@ -0,0 +1,312 @@
+//! Green number changes over time
+//!
+//! The green number (how long notes stay visible) is the option players
+//! retune most between plays. The log keeps the value of every play where
+//! the lane covers were read and reports where it changed; the baseline
+//! learns the usual value per BPM band and flags plays that stray from it.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::chart::ChartId;
+use crate::play::PlayData;
+
+/// The green number changed between two consecutive plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct GreenNumberChange {
+    /// Time of the first play with the new value
+    pub timestamp: DateTime<Utc>,
+    /// Chart of the first play with the new value
+    pub chart: ChartId,
+    pub from: u16,
+    pub to: u16,
+}
+
+/// Green numbers of plays, oldest first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GreenNumberLog {
+    entries: Vec<(DateTime<Utc>, ChartId, u16)>,
+}
+
+impl GreenNumberLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log of plays read back from session files (any order)
+    pub fn from_plays<'a>(plays: impl IntoIterator<Item = &'a PlayData>) -> Self {
+        let mut log = Self::new();
+        for play in plays {
+            log.record(play);
+        }
+        log.entries.sort_by_key(|(timestamp, _, _)| *timestamp);
+        log
+    }
+
+    /// Add a play (ignored when its green number wasn't read)
+    pub fn record(&mut self, play: &PlayData) {
+        if let Some(green_number) = play.settings.green_number() {
+            self.entries
+                .push((play.timestamp, play.chart.id(), green_number));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Green number of the first logged play
+    pub fn first(&self) -> Option<u16> {
+        self.entries.first().map(|(_, _, value)| *value)
+    }
+
+    /// Green number of the last logged play
+    pub fn last(&self) -> Option<u16> {
+        self.entries.last().map(|(_, _, value)| *value)
+    }
+
+    /// Lowest and highest green number played
+    pub fn range(&self) -> Option<(u16, u16)> {
+        let values = self.entries.iter().map(|(_, _, value)| *value);
+        Some((values.clone().min()?, values.max()?))
+    }
+
+    /// Points where the green number differs from the previous play
+    pub fn changes(&self) -> Vec<GreenNumberChange> {
+        self.entries
+            .windows(2)
+            .filter(|pair| pair[0].2 != pair[1].2)
+            .map(|pair| GreenNumberChange {
+                timestamp: pair[1].0,
+                chart: pair[1].1,
+                from: pair[0].2,
+                to: pair[1].2,
+            })
+            .collect()
+    }
+
+    /// One-line summary, e.g. `green number 290 -> 310 (2 changes, 280-310)`
+    ///
+    /// `None` when no green number was logged.
+    pub fn summary(&self) -> Option<String> {
+        let (first, last) = (self.first()?, self.last()?);
+        let changes = self.changes().len();
+        if changes == 0 {
+            return Some(format!("green number {}", first));
+        }
+        let (min, max) = self.range()?;
+        Some(format!(
+            "green number {} -> {} ({} change{}, {}-{})",
+            first,
+            last,
+            changes,
+            if changes == 1 { "" } else { "s" },
+            min,
+            max
+        ))
+    }
+}
+
+/// Width of the BPM bands green numbers are compared within
+pub const GREEN_NUMBER_BPM_BAND: u32 = 20;
+
+/// Plays in a BPM band needed before it has a usual green number
+pub const GREEN_NUMBER_MIN_SAMPLES: usize = 3;
+
+/// Lower bound of the BPM band of a chart's BPM text (`150` or `075~150`)
+///
+/// Charts with BPM changes are banded by their highest BPM.
+pub fn bpm_band(bpm: &str) -> Option<u32> {
+    let max: u32 = bpm.rsplit('~').next()?.trim().parse().ok()?;
+    (max > 0).then_some(max / GREEN_NUMBER_BPM_BAND * GREEN_NUMBER_BPM_BAND)
+}
+
+/// A green number away from the usual one for the chart's BPM band
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct GreenNumberAlert {
+    pub green_number: u16,
+    /// Median green number played in the band
+    pub usual: u16,
+    /// Lower bound of the BPM band
+    pub band: u32,
+}
+
+impl GreenNumberAlert {
+    /// Signed distance from the usual green number
+    pub fn deviation(&self) -> i32 {
+        i32::from(self.green_number) - i32::from(self.usual)
+    }
+}
+
+impl fmt::Display for GreenNumberAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Green number {} ({:+}) differs from your usual {} at {}-{} BPM",
+            self.green_number,
+            self.deviation(),
+            self.usual,
+            self.band,
+            self.band + GREEN_NUMBER_BPM_BAND - 1
+        )
+    }
+}
+
+/// Green numbers played per BPM band
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GreenNumberBaseline {
+    bands: BTreeMap<u32, Vec<u16>>,
+}
+
+impl GreenNumberBaseline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Baseline from plays read back from session files
+    pub fn from_plays<'a>(plays: impl IntoIterator<Item = &'a PlayData>) -> Self {
+        let mut baseline = Self::new();
+        for play in plays {
+            baseline.record(play);
+        }
+        baseline
+    }
+
+    /// Add a play (ignored without a green number or a known BPM)
+    pub fn record(&mut self, play: &PlayData) {
+        if let (Some(green_number), Some(band)) =
+            (play.settings.green_number(), bpm_band(&play.chart.bpm))
+        {
+            self.bands.entry(band).or_default().push(green_number);
+        }
+    }
+
+    /// Usual (median) green number in the band of `bpm`
+    ///
+    /// `None` until the band has [`GREEN_NUMBER_MIN_SAMPLES`] plays.
+    pub fn usual(&self, bpm: &str) -> Option<u16> {
+        let values = self.bands.get(&bpm_band(bpm)?)?;
+        if values.len() < GREEN_NUMBER_MIN_SAMPLES {
+            return None;
+        }
+        let mut sorted = values.clone();
+        sorted.sort_unstable();
+        Some(sorted[sorted.len() / 2])
+    }
+
+    /// Alert when `green_number` is more than `threshold` away from the
+    /// usual value for `bpm`
+    pub fn check(&self, bpm: &str, green_number: u16, threshold: u16) -> Option<GreenNumberAlert> {
+        let alert = GreenNumberAlert {
+            green_number,
+            usual: self.usual(bpm)?,
+            band: bpm_band(bpm)?,
+        };
+        (alert.deviation().unsigned_abs() > u32::from(threshold)).then_some(alert)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::Difficulty;
+    use crate::play::test_support::PlayBuilder;
+    use crate::play::{LaneCover, Settings};
+    use crate::score::{Grade, Lamp};
+    use chrono::TimeZone;
+
+    fn play(minute: u32, green_number: Option<u16>) -> PlayData {
+        play_at_bpm(minute, "150", green_number)
+    }
+
+    fn play_at_bpm(minute: u32, bpm: &str, green_number: Option<u16>) -> PlayData {
+        PlayBuilder::new()
+            .timestamp(Utc.with_ymd_and_hms(2025, 1, 1, 20, minute, 0).unwrap())
+            .song_id(1000 + minute)
+            .bpm(bpm)
+            .ex_score(0)
+            .grade(Grade::NoPlay)
+            .lamp(Lamp::NoPlay)
+            .settings(Settings {
+                lane_cover: green_number.map(|green_number| LaneCover {
+                    green_number,
+                    ..LaneCover::default()
+                }),
+                ..Settings::default()
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_changes() {
+        let plays = [
+            play(3, Some(280)),
+            play(0, Some(300)),
+            play(1, Some(300)),
+            play(2, None),
+            play(4, Some(310)),
+        ];
+        let log = GreenNumberLog::from_plays(&plays);
+
+        let changes = log.changes();
+        assert_eq!(changes.len(), 2);
+        assert_eq!((changes[0].from, changes[0].to), (300, 280));
+        assert_eq!(changes[0].chart, ChartId::new(1003, Difficulty::SpA));
+        assert_eq!((changes[1].from, changes[1].to), (280, 310));
+        assert_eq!(log.range(), Some((280, 310)));
+        assert_eq!(
+            log.summary().unwrap(),
+            "green number 300 -> 310 (2 changes, 280-310)"
+        );
+    }
+
+    #[test]
+    fn test_unchanged_and_empty() {
+        let mut log = GreenNumberLog::new();
+        assert!(log.is_empty());
+        assert_eq!(log.summary(), None);
+
+        log.record(&play(0, Some(300)));
+        log.record(&play(1, None));
+        log.record(&play(2, Some(300)));
+        assert!(log.changes().is_empty());
+        assert_eq!(log.summary().unwrap(), "green number 300");
+    }
+
+    #[test]
+    fn test_bpm_band() {
+        assert_eq!(bpm_band("150"), Some(140));
+        assert_eq!(bpm_band("075~160"), Some(160));
+        assert_eq!(bpm_band(""), None);
+        assert_eq!(bpm_band("000"), None);
+    }
+
+    #[test]
+    fn test_baseline_alerts() {
+        let plays = [
+            play_at_bpm(0, "150", Some(290)),
+            play_at_bpm(1, "145", Some(300)),
+            play_at_bpm(2, "155", Some(280)),
+            play_at_bpm(3, "150", None),
+            // Only two plays at 180-199 BPM
+            play_at_bpm(4, "185", Some(250)),
+            play_at_bpm(5, "190", Some(250)),
+        ];
+        let baseline = GreenNumberBaseline::from_plays(&plays);
+        assert_eq!(baseline.usual("140"), Some(290));
+        assert_eq!(baseline.usual("185"), None);
+
+        assert_eq!(baseline.check("150", 310, 20), None);
+        let alert = baseline.check("150", 340, 20).unwrap();
+        assert_eq!(alert.deviation(), 50);
+        assert_eq!(
+            alert.to_string(),
+            "Green number 340 (+50) differs from your usual 290 at 140-159 BPM"
+        );
+        assert_eq!(baseline.check("150", 250, 20).unwrap().deviation(), -40);
+        assert_eq!(baseline.check("185", 400, 20), None);
+    }
+}
@@ -0,0 +1,29 @@
+//! Statistics derived from the player's own play history.
+//!
+//! This module aggregates plays recorded in session files:
+//! - `ChartAnalytics` - per-chart averages, fast/slow balance, score spread and single-break rate
+//! - `BpiTable` - BPI reference data and score-to-BPI conversion
+//! - `recommend_dj_points` - charts whose next grade or lamp gains the most DJ points
+//! - `plan_unlocks` - bit unlocks with the most new charts per bit
+//! - `PlayTime` - time spent playing and notes hit per minute
+//! - `PlayCounters` - plays and notes hit today and this week, kept across sessions
+//! - `GreenNumberLog` - green number changes between plays
+//! - `dense_section_fails` - whether failed plays end in dense chart sections
+
+mod analytics;
+pub mod bpi;
+mod fail_density;
+mod green_number;
+mod play_counts;
+mod play_time;
+mod recommend;
+mod unlock_plan;
+
+pub use analytics::*;
+pub use bpi::{BpiEntry, BpiTable, calculate_bpi};
+pub use fail_density::*;
+pub use green_number::*;
+pub use play_counts::*;
+pub use play_time::*;
+pub use recommend::*;
+pub use unlock_plan::*;
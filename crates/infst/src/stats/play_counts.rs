@@ -0,0 +1,170 @@
+//! Plays and notes hit per day, kept across sessions
+//!
+//! Counts are stored per calendar day in `play_counts.json` in the session
+//! directory, so "plays today" survives restarting the tracker. Days older
+//! than [`PLAY_COUNT_DAYS`] are dropped when a play is recorded; weeks start
+//! on Monday.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chrono::{Datelike, NaiveDate, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::storage::write_atomic;
+
+/// Play counts file name inside the session directory
+pub const PLAY_COUNTS_FILE: &str = "play_counts.json";
+
+/// Days of counts kept (enough for the current week)
+pub const PLAY_COUNT_DAYS: i64 = 14;
+
+/// Plays and notes hit over a period
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayCount {
+    pub plays: u32,
+    pub notes_hit: u64,
+}
+
+impl DayCount {
+    fn add(&mut self, other: DayCount) {
+        self.plays += other.plays;
+        self.notes_hit += other.notes_hit;
+    }
+}
+
+/// Counts for today and the current week
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PlayCounts {
+    pub today: DayCount,
+    pub this_week: DayCount,
+}
+
+impl fmt::Display for PlayCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Today: {} plays, {} notes hit / This week: {} plays, {} notes hit",
+            self.today.plays, self.today.notes_hit, self.this_week.plays, self.this_week.notes_hit
+        )
+    }
+}
+
+/// Plays and notes hit per calendar day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayCounters {
+    days: BTreeMap<NaiveDate, DayCount>,
+}
+
+impl PlayCounters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the counts file (empty counts when it doesn't exist yet)
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(Error::storage(path, e)),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_atomic(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Count a play on `date` and drop days that are too old
+    pub fn record(&mut self, date: NaiveDate, notes_hit: u32) {
+        let day = self.days.entry(date).or_default();
+        day.plays += 1;
+        day.notes_hit += u64::from(notes_hit);
+
+        let oldest = date - TimeDelta::days(PLAY_COUNT_DAYS - 1);
+        self.days.retain(|day, _| *day >= oldest);
+    }
+
+    /// Counts of `today` and of its week up to `today`
+    pub fn counts(&self, today: NaiveDate) -> PlayCounts {
+        let week_start = today - TimeDelta::days(i64::from(today.weekday().num_days_from_monday()));
+        let mut counts = PlayCounts {
+            today: self.days.get(&today).copied().unwrap_or_default(),
+            ..Default::default()
+        };
+        for (_, day) in self.days.range(week_start..=today) {
+            counts.this_week.add(*day);
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        // 2025-06-02 is a Monday
+        NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+    }
+
+    #[test]
+    fn test_counts_today_and_week() {
+        let mut counters = PlayCounters::new();
+        counters.record(date(1), 500); // Sunday of the previous week
+        counters.record(date(2), 1000);
+        counters.record(date(4), 800);
+        counters.record(date(4), 700);
+
+        let counts = counters.counts(date(4));
+        assert_eq!(
+            counts.today,
+            DayCount {
+                plays: 2,
+                notes_hit: 1500
+            }
+        );
+        assert_eq!(
+            counts.this_week,
+            DayCount {
+                plays: 3,
+                notes_hit: 2500
+            }
+        );
+        assert_eq!(counters.counts(date(9)), PlayCounts::default());
+        assert_eq!(
+            counts.to_string(),
+            "Today: 2 plays, 1500 notes hit / This week: 3 plays, 2500 notes hit"
+        );
+    }
+
+    #[test]
+    fn test_record_drops_old_days() {
+        let mut counters = PlayCounters::new();
+        counters.record(date(1), 100);
+        counters.record(date(14), 100);
+        assert_eq!(counters.days.len(), 2);
+        counters.record(date(15), 100);
+        assert_eq!(counters.days.len(), 2);
+        assert!(!counters.days.contains_key(&date(1)));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(PLAY_COUNTS_FILE);
+        assert_eq!(PlayCounters::load(&path).unwrap(), PlayCounters::new());
+
+        let mut counters = PlayCounters::new();
+        counters.record(date(2), 1234);
+        counters.save(&path).unwrap();
+        assert_eq!(PlayCounters::load(&path).unwrap(), counters);
+
+        fs::write(&path, "not json").unwrap();
+        assert!(PlayCounters::load(&path).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Time spent playing and notes hit, per session and per chart
+//!
+//! Play time is the time the game spent in the Playing state (song select and
+//! result screens are excluded). Notes hit are PGREAT + GREAT + GOOD: the
+//! judgments that keep the combo.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::chart::ChartId;
+use crate::score::Judge;
+
+/// Notes counted as hit in a play's judgments
+pub fn notes_hit(judge: &Judge) -> u32 {
+    judge
+        .pgreat
+        .saturating_add(judge.great)
+        .saturating_add(judge.good)
+}
+
+/// Notes hit per minute of play time (`None` without play time)
+fn per_minute(notes: u64, time: Duration) -> Option<f64> {
+    let minutes = time.as_secs_f64() / 60.0;
+    (minutes > 0.0).then(|| notes as f64 / minutes)
+}
+
+/// `1h 02m`, `12m 30s` or `45s`
+pub fn format_play_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    match (secs / 3600, secs % 3600 / 60, secs % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {:02}s", m, s),
+        (h, m, _) => format!("{}h {:02}m", h, m),
+    }
+}
+
+/// Play time and notes hit on one chart
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChartPlayTime {
+    /// Times the chart was started
+    pub plays: u32,
+    /// Time spent in the Playing state
+    pub time: Duration,
+    pub notes_hit: u64,
+}
+
+impl ChartPlayTime {
+    /// Notes hit per minute of play time ("stamina")
+    pub fn notes_per_minute(&self) -> Option<f64> {
+        per_minute(self.notes_hit, self.time)
+    }
+}
+
+/// Play time and notes hit accumulated over a session
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayTime {
+    charts: HashMap<ChartId, ChartPlayTime>,
+}
+
+impl PlayTime {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a stretch in the Playing state on `chart`
+    pub fn record_time(&mut self, chart: ChartId, time: Duration) {
+        let entry = self.charts.entry(chart).or_default();
+        entry.plays += 1;
+        entry.time += time;
+    }
+
+    /// Count the notes hit in a play of `chart`
+    pub fn record_notes(&mut self, chart: ChartId, notes: u32) {
+        self.charts.entry(chart).or_default().notes_hit += u64::from(notes);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.charts.is_empty()
+    }
+
+    /// Total time in the Playing state
+    pub fn total_time(&self) -> Duration {
+        self.charts.values().map(|chart| chart.time).sum()
+    }
+
+    /// Total notes hit
+    pub fn notes_hit(&self) -> u64 {
+        self.charts.values().map(|chart| chart.notes_hit).sum()
+    }
+
+    /// Notes hit per minute over the whole session
+    pub fn notes_per_minute(&self) -> Option<f64> {
+        per_minute(self.notes_hit(), self.total_time())
+    }
+
+    /// Totals for one chart
+    pub fn chart(&self, chart: &ChartId) -> Option<&ChartPlayTime> {
+        self.charts.get(chart)
+    }
+
+    /// Charts by time played, longest first
+    pub fn charts(&self) -> Vec<(ChartId, ChartPlayTime)> {
+        let mut charts: Vec<_> = self.charts.iter().map(|(id, time)| (*id, *time)).collect();
+        charts.sort_by(|a, b| b.1.time.cmp(&a.1.time).then(a.0.cmp(&b.0)));
+        charts
+    }
+
+    /// One-line summary, e.g. `play time 12m 30s, 4321 notes (345.7/min)`
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "play time {}, {} notes",
+            format_play_time(self.total_time()),
+            self.notes_hit()
+        );
+        if let Some(rate) = self.notes_per_minute() {
+            summary.push_str(&format!(" ({:.1}/min)", rate));
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::Difficulty;
+
+    const SPA: ChartId = ChartId::new(1000, Difficulty::SpA);
+    const SPH: ChartId = ChartId::new(1001, Difficulty::SpH);
+
+    #[test]
+    fn test_notes_hit() {
+        let judge = Judge {
+            pgreat: 900,
+            great: 80,
+            good: 10,
+            bad: 5,
+            poor: 20,
+            ..Judge::default()
+        };
+        assert_eq!(notes_hit(&judge), 990);
+    }
+
+    #[test]
+    fn test_totals_and_rates() {
+        let mut time = PlayTime::new();
+        time.record_time(SPA, Duration::from_secs(120));
+        time.record_notes(SPA, 1500);
+        time.record_time(SPA, Duration::from_secs(60));
+        time.record_notes(SPA, 300);
+        time.record_time(SPH, Duration::from_secs(60));
+        time.record_notes(SPH, 600);
+
+        assert_eq!(time.total_time(), Duration::from_secs(240));
+        assert_eq!(time.notes_hit(), 2400);
+        assert_eq!(time.notes_per_minute(), Some(600.0));
+
+        let spa = time.chart(&SPA).unwrap();
+        assert_eq!(spa.plays, 2);
+        assert_eq!(spa.notes_per_minute(), Some(600.0));
+
+        let order: Vec<_> = time.charts().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(order, vec![SPA, SPH]);
+        assert_eq!(time.summary(), "play time 4m 00s, 2400 notes (600.0/min)");
+    }
+
+    #[test]
+    fn test_no_time() {
+        let mut time = PlayTime::new();
+        assert!(time.is_empty());
+        assert_eq!(time.notes_per_minute(), None);
+
+        time.record_notes(SPA, 100);
+        assert_eq!(time.notes_per_minute(), None);
+        assert_eq!(time.summary(), "play time 0s, 100 notes");
+    }
+
+    #[test]
+    fn test_format_play_time() {
+        assert_eq!(format_play_time(Duration::from_secs(45)), "45s");
+        assert_eq!(format_play_time(Duration::from_secs(750)), "12m 30s");
+        assert_eq!(format_play_time(Duration::from_secs(3725)), "1h 02m");
+    }
+}
@@ -0,0 +1,394 @@
+//! DJ point improvement recommendations
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::chart::{ChartId, SongInfo, UnlockData, is_chart_unlocked};
+use crate::play::{PlayData, calculate_dj_points_from_score};
+use crate::score::{Grade, Lamp, ScoreMap};
+
+/// Number of best charts that count toward the SP/DP DJ point total
+pub const DJ_POINTS_COUNTED_CHARTS: usize = 50;
+
+/// Options for [`recommend_dj_points`]
+#[derive(Debug, Clone)]
+pub struct RecommendOptions {
+    /// DJ points to gain on top of the current total
+    pub target_gain: f64,
+    /// Skip charts above this level
+    pub max_level: Option<u8>,
+    /// Recommend DP charts instead of SP charts
+    pub double_play: bool,
+    /// Only recommend charts of these songs (e.g. the songs with a tag);
+    /// other charts still count toward the DJ point total
+    pub songs: Option<HashSet<u32>>,
+}
+
+impl Default for RecommendOptions {
+    fn default() -> Self {
+        Self {
+            target_gain: 100.0,
+            max_level: None,
+            double_play: false,
+            songs: None,
+        }
+    }
+}
+
+/// A single suggested improvement
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Recommendation {
+    pub chart: ChartId,
+    pub title: String,
+    pub level: u8,
+    pub current_score: u32,
+    pub current_lamp: Lamp,
+    pub target_score: u32,
+    pub target_lamp: Lamp,
+    /// Increase of the DJ point total if this goal is reached
+    pub gain: f64,
+}
+
+/// Result of a recommendation run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RecommendationPlan {
+    /// Current DJ point total (best [`DJ_POINTS_COUNTED_CHARTS`] charts)
+    pub current_total: f64,
+    /// DJ point total after every recommendation is reached
+    pub projected_total: f64,
+    pub recommendations: Vec<Recommendation>,
+}
+
+impl RecommendationPlan {
+    /// Whether the recommendations reach the requested gain
+    pub fn reaches(&self, target_gain: f64) -> bool {
+        self.projected_total - self.current_total >= target_gain
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ChartState {
+    chart: ChartId,
+    level: u8,
+    total_notes: u32,
+    score: u32,
+    lamp: Lamp,
+}
+
+impl ChartState {
+    fn dj_points(&self) -> f64 {
+        calculate_dj_points_from_score(self.score, self.total_notes, self.lamp)
+    }
+}
+
+/// Next reachable goals for a chart: a higher grade, or a better lamp
+///
+/// Grade and lamp goals never go below A / CLEAR, since lower steps
+/// barely move DJ points.
+fn candidate_goals(state: &ChartState) -> Vec<(u32, Lamp)> {
+    let mut goals = Vec::new();
+
+    let grade = PlayData::calculate_grade(state.score, state.total_notes);
+    let next_grade = Grade::from_u8(grade as u8 + 1)
+        .unwrap_or(Grade::Aaa)
+        .max(Grade::A);
+    if grade < Grade::Aaa {
+        goals.push((next_grade.min_score(state.total_notes), state.lamp));
+    }
+
+    if state.lamp < Lamp::FullCombo {
+        let next_lamp = Lamp::from_u8(state.lamp as u8 + 1)
+            .unwrap_or(Lamp::FullCombo)
+            .max(Lamp::Clear);
+        goals.push((state.score, next_lamp));
+    }
+
+    goals
+}
+
+fn total_dj_points(points: &[f64]) -> f64 {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| b.total_cmp(a));
+    sorted.iter().take(DJ_POINTS_COUNTED_CHARTS).sum()
+}
+
+/// Indices of the charts currently counted, and the lowest counted value
+/// (`None` while fewer charts than the limit have points)
+fn counted_charts(points: &[f64]) -> (Vec<bool>, Option<f64>) {
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&a, &b| points[b].total_cmp(&points[a]));
+
+    let mut counted = vec![false; points.len()];
+    for &i in order.iter().take(DJ_POINTS_COUNTED_CHARTS) {
+        counted[i] = true;
+    }
+    let threshold = order
+        .get(DJ_POINTS_COUNTED_CHARTS - 1)
+        .map(|&i| points[i])
+        .filter(|_| points.len() > DJ_POINTS_COUNTED_CHARTS);
+    (counted, threshold)
+}
+
+/// Recommend charts whose improvement raises the DJ point total the most
+///
+/// Goals are picked greedily: each step takes the single next grade or lamp
+/// that adds the most to the best-[`DJ_POINTS_COUNTED_CHARTS`] total, until
+/// `target_gain` is reached or no goal improves the total. A chart may appear
+/// more than once when successive goals on it are still the best option.
+///
+/// With `unlock_db`, locked charts are never recommended (they can't be
+/// played); they still count toward the DJ point total.
+pub fn recommend_dj_points(
+    song_db: &HashMap<u32, SongInfo>,
+    unlock_db: Option<&HashMap<u32, UnlockData>>,
+    score_map: &ScoreMap,
+    options: &RecommendOptions,
+) -> RecommendationPlan {
+    let mut states: Vec<ChartState> = Vec::new();
+    for (&song_id, song) in song_db {
+        for chart in ChartId::all(song_id) {
+            let difficulty = chart.difficulty;
+            if difficulty.is_dp() != options.double_play {
+                continue;
+            }
+            let level = song.level_for(difficulty);
+            let total_notes = song.total_notes_for(difficulty);
+            if level == 0 || total_notes == 0 {
+                continue;
+            }
+            if options.max_level.is_some_and(|max| level > max) {
+                continue;
+            }
+            states.push(ChartState {
+                chart,
+                level,
+                total_notes,
+                score: score_map.score(chart),
+                lamp: score_map.lamp(chart),
+            });
+        }
+    }
+    // Deterministic ordering for tie-breaking
+    states.sort_by_key(|s| s.chart);
+
+    let mut points: Vec<f64> = states.iter().map(ChartState::dj_points).collect();
+    let current_total = total_dj_points(&points);
+    let mut projected_total = current_total;
+    let mut recommendations = Vec::new();
+
+    while projected_total - current_total < options.target_gain {
+        let (counted, threshold) = counted_charts(&points);
+        let mut best: Option<(usize, u32, Lamp, f64, f64)> = None;
+
+        for (i, state) in states.iter().enumerate() {
+            if options
+                .songs
+                .as_ref()
+                .is_some_and(|songs| !songs.contains(&state.chart.song_id))
+            {
+                continue;
+            }
+            if unlock_db
+                .is_some_and(|unlock_db| !is_chart_unlocked(unlock_db, song_db, state.chart))
+            {
+                continue;
+            }
+            for (score, lamp) in candidate_goals(state) {
+                let new_points = calculate_dj_points_from_score(score, state.total_notes, lamp);
+                // A counted chart adds its own improvement; any other chart
+                // only adds what it gains over the chart it pushes out
+                let gain = match threshold {
+                    Some(t) if !counted[i] => new_points - t,
+                    _ => new_points - points[i],
+                };
+                if gain > 0.0 && best.is_none_or(|(_, _, _, _, g)| gain > g) {
+                    best = Some((i, score, lamp, new_points, gain));
+                }
+            }
+        }
+
+        let Some((i, score, lamp, new_points, gain)) = best else {
+            break;
+        };
+
+        let state = &mut states[i];
+        let title = song_db
+            .get(&state.chart.song_id)
+            .map(|s| s.title.to_string())
+            .unwrap_or_default();
+        recommendations.push(Recommendation {
+            chart: state.chart,
+            title,
+            level: state.level,
+            current_score: state.score,
+            current_lamp: state.lamp,
+            target_score: score,
+            target_lamp: lamp,
+            gain,
+        });
+
+        state.score = score;
+        state.lamp = lamp;
+        points[i] = new_points;
+        projected_total = total_dj_points(&points);
+    }
+
+    RecommendationPlan {
+        current_total,
+        projected_total,
+        recommendations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::Difficulty;
+    use crate::play::UnlockType;
+    use std::sync::Arc;
+
+    fn make_song(id: u32, spa_level: u8) -> SongInfo {
+        SongInfo {
+            id,
+            title: Arc::from(format!("Song {}", id)),
+            levels: [0, 0, 0, spa_level, 0, 0, 0, 0, 0, 0],
+            total_notes: [0, 0, 0, 1000, 0, 0, 0, 0, 0, 0],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_recommend_reaches_target() {
+        let mut song_db = HashMap::new();
+        song_db.insert(1, make_song(1, 10));
+        song_db.insert(2, make_song(2, 11));
+
+        let mut score_map = ScoreMap::new();
+        let data = score_map.get_or_insert(1);
+        data.set_score(Difficulty::SpA, 1400);
+        data.set_lamp(Difficulty::SpA, Lamp::Clear);
+
+        let options = RecommendOptions {
+            target_gain: 10.0,
+            ..Default::default()
+        };
+        let plan = recommend_dj_points(&song_db, None, &score_map, &options);
+
+        assert!(plan.current_total > 0.0);
+        assert!(plan.reaches(10.0));
+        assert!(!plan.recommendations.is_empty());
+        let total_gain: f64 = plan.recommendations.iter().map(|r| r.gain).sum();
+        assert!((plan.projected_total - plan.current_total - total_gain).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_recommend_respects_max_level() {
+        let mut song_db = HashMap::new();
+        song_db.insert(1, make_song(1, 10));
+        song_db.insert(2, make_song(2, 12));
+
+        let options = RecommendOptions {
+            target_gain: 1000.0,
+            max_level: Some(11),
+            ..Default::default()
+        };
+        let plan = recommend_dj_points(&song_db, None, &ScoreMap::new(), &options);
+
+        assert!(!plan.recommendations.is_empty());
+        assert!(plan.recommendations.iter().all(|r| r.chart.song_id == 1));
+        // Single chart can't reach +1000
+        assert!(!plan.reaches(1000.0));
+    }
+
+    #[test]
+    fn test_recommend_respects_songs() {
+        let mut song_db = HashMap::new();
+        song_db.insert(1, make_song(1, 10));
+        song_db.insert(2, make_song(2, 12));
+
+        let mut score_map = ScoreMap::new();
+        let data = score_map.get_or_insert(2);
+        data.set_score(Difficulty::SpA, 1400);
+        data.set_lamp(Difficulty::SpA, Lamp::Clear);
+
+        let options = RecommendOptions {
+            target_gain: 1000.0,
+            songs: Some(HashSet::from([1])),
+            ..Default::default()
+        };
+        let plan = recommend_dj_points(&song_db, None, &score_map, &options);
+
+        assert!(!plan.recommendations.is_empty());
+        assert!(plan.recommendations.iter().all(|r| r.chart.song_id == 1));
+        // Song 2 is not recommended but still counts toward the total
+        let song2 = calculate_dj_points_from_score(1400, 1000, Lamp::Clear);
+        assert!((plan.current_total - song2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_recommend_skips_locked_charts() {
+        let mut song_db = HashMap::new();
+        song_db.insert(1, make_song(1, 10));
+        song_db.insert(2, make_song(2, 12));
+        let unlock = |song_id, unlocks| UnlockData {
+            song_id,
+            unlock_type: UnlockType::Bits,
+            unlocks,
+        };
+        // Song 2's SPA is locked
+        let unlock_db = HashMap::from([(1, unlock(1, 0x3FF)), (2, unlock(2, 0x007))]);
+
+        let options = RecommendOptions {
+            target_gain: 1000.0,
+            ..Default::default()
+        };
+        let plan = recommend_dj_points(&song_db, Some(&unlock_db), &ScoreMap::new(), &options);
+        assert!(!plan.recommendations.is_empty());
+        assert!(plan.recommendations.iter().all(|r| r.chart.song_id == 1));
+
+        // Without unlock data every chart is a candidate
+        let plan = recommend_dj_points(&song_db, None, &ScoreMap::new(), &options);
+        assert!(plan.recommendations.iter().any(|r| r.chart.song_id == 2));
+    }
+
+    #[test]
+    fn test_recommend_maxed_chart_has_no_goals() {
+        let mut song_db = HashMap::new();
+        song_db.insert(1, make_song(1, 12));
+
+        let mut score_map = ScoreMap::new();
+        let data = score_map.get_or_insert(1);
+        data.set_score(Difficulty::SpA, 2000);
+        data.set_lamp(Difficulty::SpA, Lamp::FullCombo);
+
+        let plan = recommend_dj_points(&song_db, None, &score_map, &RecommendOptions::default());
+        assert!(plan.recommendations.is_empty());
+        assert!((plan.current_total - plan.projected_total).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_recommend_only_counts_best_charts() {
+        let mut song_db = HashMap::new();
+        let mut score_map = ScoreMap::new();
+        for id in 1..=(DJ_POINTS_COUNTED_CHARTS as u32 + 1) {
+            song_db.insert(id, make_song(id, 10));
+            let data = score_map.get_or_insert(id);
+            data.set_score(Difficulty::SpA, 1800);
+            data.set_lamp(Difficulty::SpA, Lamp::HardClear);
+        }
+
+        let options = RecommendOptions {
+            target_gain: 1.0,
+            ..Default::default()
+        };
+        let plan = recommend_dj_points(&song_db, None, &score_map, &options);
+        let first = &plan.recommendations[0];
+
+        // Improving one chart only adds what it gains over the 50th chart
+        let before = calculate_dj_points_from_score(1800, 1000, Lamp::HardClear);
+        let after = calculate_dj_points_from_score(first.target_score, 1000, first.target_lamp);
+        assert!((first.gain - (after - before)).abs() < 1e-9);
+        assert!((plan.current_total - before * DJ_POINTS_COUNTED_CHARTS as f64).abs() < 1e-6);
+    }
+}
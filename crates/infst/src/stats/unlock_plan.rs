@@ -0,0 +1,272 @@
+//! Bit unlock planning
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::chart::{CustomTypes, Difficulty, Purchasability, SongInfo, UnlockData};
+use crate::export::PlayStyle;
+use crate::play::UnlockType;
+
+/// Options for [`plan_unlocks`]
+#[derive(Debug, Clone, Default)]
+pub struct UnlockPlanOptions {
+    /// Bits available to spend
+    pub budget: u32,
+    /// Only charts at or above this level count as value
+    pub min_level: Option<u8>,
+    /// Only SP or DP charts count as value
+    pub style: Option<PlayStyle>,
+}
+
+/// A locked N/H/A tier of a bit song that can be bought
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnlockCandidate {
+    pub song_id: u32,
+    pub title: String,
+    /// Custom type label, or the unlock type name
+    pub label: String,
+    /// Locked charts opened by this purchase (SP and/or DP of the tier)
+    pub difficulties: Vec<Difficulty>,
+    /// Levels of `difficulties`
+    pub levels: Vec<u8>,
+    pub cost: u32,
+    /// Number of opened charts that match the options
+    pub value: u32,
+}
+
+impl UnlockCandidate {
+    /// Matching charts gained per 1000 bits
+    pub fn value_per_1000_bits(&self) -> f64 {
+        if self.cost == 0 {
+            return 0.0;
+        }
+        self.value as f64 * 1000.0 / self.cost as f64
+    }
+}
+
+/// Result of [`plan_unlocks`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UnlockPlan {
+    pub budget: u32,
+    /// Total cost of `purchases`
+    pub spent: u32,
+    /// Purchases chosen within the budget, best value first
+    pub purchases: Vec<UnlockCandidate>,
+}
+
+impl UnlockPlan {
+    pub fn remaining(&self) -> u32 {
+        self.budget.saturating_sub(self.spent)
+    }
+
+    /// Matching charts gained by the plan
+    pub fn total_value(&self) -> u32 {
+        self.purchases.iter().map(|p| p.value).sum()
+    }
+}
+
+fn counts_as_value(level: u8, difficulty: Difficulty, options: &UnlockPlanOptions) -> bool {
+    if options.min_level.is_some_and(|min| level < min) {
+        return false;
+    }
+    match options.style {
+        Some(PlayStyle::Sp) => difficulty.is_sp(),
+        Some(PlayStyle::Dp) => difficulty.is_dp(),
+        None => true,
+    }
+}
+
+/// List every locked N/H/A tier of bit songs, best value per bit first
+///
+/// Ties are broken by lower cost, then song ID.
+pub fn locked_bit_charts(
+    song_db: &HashMap<u32, SongInfo>,
+    unlock_db: &HashMap<u32, UnlockData>,
+    custom_types: &CustomTypes,
+    options: &UnlockPlanOptions,
+) -> Vec<UnlockCandidate> {
+    let mut candidates = Vec::new();
+
+    for (&song_id, unlock) in unlock_db {
+        if unlock.unlock_type != UnlockType::Bits {
+            continue;
+        }
+        let Some(song) = song_db.get(&song_id) else {
+            continue;
+        };
+
+        for (sp, dp) in [
+            (Difficulty::SpN, Difficulty::DpN),
+            (Difficulty::SpH, Difficulty::DpH),
+            (Difficulty::SpA, Difficulty::DpA),
+        ] {
+            let purchasable: Vec<(Difficulty, u32)> = [sp, dp]
+                .into_iter()
+                .filter_map(|diff| match unlock.purchasability(song, diff) {
+                    Purchasability::Bits { cost } => Some((diff, cost)),
+                    _ => None,
+                })
+                .collect();
+            // Both charts of a tier share one purchase
+            let Some(&(_, cost)) = purchasable.first() else {
+                continue;
+            };
+            let locked: Vec<Difficulty> = purchasable.iter().map(|&(diff, _)| diff).collect();
+
+            let levels: Vec<u8> = locked.iter().map(|&d| song.level_for(d)).collect();
+            let value = locked
+                .iter()
+                .zip(&levels)
+                .filter(|&(&diff, &level)| counts_as_value(level, diff, options))
+                .count() as u32;
+
+            candidates.push(UnlockCandidate {
+                song_id,
+                title: song.title.to_string(),
+                label: custom_types
+                    .get(song_id)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| "Bits".to_string()),
+                difficulties: locked,
+                levels,
+                cost,
+                value,
+            });
+        }
+    }
+
+    candidates.sort_by(|a, b| {
+        b.value_per_1000_bits()
+            .total_cmp(&a.value_per_1000_bits())
+            .then(a.cost.cmp(&b.cost))
+            .then(a.song_id.cmp(&b.song_id))
+            .then(a.difficulties[0].cmp(&b.difficulties[0]))
+    });
+    candidates
+}
+
+/// Choose bit unlocks within `options.budget`
+///
+/// Candidates are taken in [`locked_bit_charts`] order, skipping those that
+/// no longer fit or add no matching charts.
+pub fn plan_unlocks(
+    song_db: &HashMap<u32, SongInfo>,
+    unlock_db: &HashMap<u32, UnlockData>,
+    custom_types: &CustomTypes,
+    options: &UnlockPlanOptions,
+) -> UnlockPlan {
+    let mut plan = UnlockPlan {
+        budget: options.budget,
+        ..Default::default()
+    };
+
+    for candidate in locked_bit_charts(song_db, unlock_db, custom_types, options) {
+        if candidate.value == 0 || candidate.cost > plan.remaining() {
+            continue;
+        }
+        plan.spent += candidate.cost;
+        plan.purchases.push(candidate);
+    }
+
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn bit_song(id: u32, levels: [u8; 10]) -> SongInfo {
+        SongInfo {
+            id,
+            title: Arc::from(format!("Song {}", id)),
+            title_english: Arc::from(""),
+            artist: Arc::from(""),
+            genre: Arc::from(""),
+            bpm: Arc::from("150"),
+            folder: 1,
+            levels,
+            total_notes: levels.map(|l| if l > 0 { 1000 } else { 0 }),
+            unlock_type: UnlockType::Bits,
+        }
+    }
+
+    fn setup() -> (HashMap<u32, SongInfo>, HashMap<u32, UnlockData>) {
+        let songs = [
+            // SPA 12 / DPA 12: expensive, two 12s
+            bit_song(1000, [0, 5, 9, 12, 0, 0, 5, 9, 12, 0]),
+            // SPA 12 / DPA 10: cheaper, one 12
+            bit_song(1001, [0, 3, 7, 12, 0, 0, 3, 7, 10, 0]),
+        ];
+        let song_db: HashMap<u32, SongInfo> = songs.into_iter().map(|s| (s.id, s)).collect();
+        let unlock_db = song_db
+            .keys()
+            .map(|&id| {
+                let mut unlock = UnlockData {
+                    song_id: id,
+                    unlock_type: UnlockType::Bits,
+                    unlocks: 0,
+                };
+                // Only N tier unlocked
+                unlock.apply_bits_purchase(Difficulty::SpN);
+                (id, unlock)
+            })
+            .collect();
+        (song_db, unlock_db)
+    }
+
+    #[test]
+    fn test_locked_bit_charts_sorted_by_value() {
+        let (song_db, unlock_db) = setup();
+        let options = UnlockPlanOptions {
+            min_level: Some(12),
+            ..Default::default()
+        };
+        let candidates = locked_bit_charts(&song_db, &unlock_db, &CustomTypes::default(), &options);
+
+        // H and A tiers of both songs are locked
+        assert_eq!(candidates.len(), 4);
+        // 2 per 12000 bits beats 1 per 11000 bits
+        assert_eq!(candidates[0].song_id, 1000);
+        assert_eq!(
+            candidates[0].difficulties,
+            vec![Difficulty::SpA, Difficulty::DpA]
+        );
+        assert_eq!(candidates[0].cost, 12000);
+        assert_eq!(candidates[0].value, 2);
+        assert_eq!(candidates[1].song_id, 1001);
+        assert_eq!(candidates[1].value, 1);
+        assert_eq!(candidates[2].value, 0);
+    }
+
+    #[test]
+    fn test_plan_unlocks_within_budget() {
+        let (song_db, unlock_db) = setup();
+        let mut custom_types = CustomTypes::parse("1000,DJT\n");
+        let options = UnlockPlanOptions {
+            budget: 20000,
+            min_level: Some(12),
+            style: None,
+        };
+        let plan = plan_unlocks(&song_db, &unlock_db, &custom_types, &options);
+
+        assert_eq!(plan.purchases.len(), 1);
+        assert_eq!(plan.purchases[0].label, "DJT");
+        assert_eq!(plan.spent, 12000);
+        assert_eq!(plan.remaining(), 8000);
+        assert_eq!(plan.total_value(), 2);
+
+        // SP only: both A tiers are worth one SP 12 each
+        custom_types = CustomTypes::default();
+        let options = UnlockPlanOptions {
+            budget: 30000,
+            min_level: Some(12),
+            style: Some(PlayStyle::Sp),
+        };
+        let plan = plan_unlocks(&song_db, &unlock_db, &custom_types, &options);
+        assert_eq!(plan.purchases.len(), 2);
+        assert_eq!(plan.purchases[0].song_id, 1001);
+        assert_eq!(plan.total_value(), 2);
+    }
+}
@@ -0,0 +1,113 @@
+//! Atomic file writes
+//!
+//! Content is written to a temporary file in the same directory and renamed
+//! over the target, so readers (OBS text sources, spreadsheets, a crash mid
+//! write) see either the old or the new file, never a truncated one.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+
+/// Rename attempts when the target is briefly locked by a reader (Windows)
+const RENAME_ATTEMPTS: u32 = 5;
+const RENAME_RETRY_DELAY: Duration = Duration::from_millis(20);
+
+/// Options for [`write_atomic_with`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WriteOptions {
+    /// Flush the file to disk before renaming (slower; survives power loss)
+    pub fsync: bool,
+}
+
+impl WriteOptions {
+    /// Options for files that hold the user's data (tracker, sessions)
+    pub const DURABLE: Self = Self { fsync: true };
+}
+
+/// Atomically replace `path` with `contents`
+pub fn write_atomic<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<()> {
+    write_atomic_with(path, contents, WriteOptions::default())
+}
+
+/// Atomically replace `path` with `contents` using `options`
+pub fn write_atomic_with<P: AsRef<Path>, C: AsRef<[u8]>>(
+    path: P,
+    contents: C,
+    options: WriteOptions,
+) -> Result<()> {
+    let path = path.as_ref();
+    let temp = temp_path(path);
+    let result = write_temp(&temp, contents.as_ref(), options).and_then(|()| rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Temporary file next to `path` (`.tracker.tsv.<pid>.tmp`)
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()))
+}
+
+fn write_temp(temp: &Path, contents: &[u8], options: WriteOptions) -> Result<()> {
+    let mut file = fs::File::create(temp).map_err(|e| Error::storage(temp, e))?;
+    file.write_all(contents)
+        .map_err(|e| Error::storage(temp, e))?;
+    if options.fsync {
+        file.sync_all().map_err(|e| Error::storage(temp, e))?;
+    }
+    Ok(())
+}
+
+fn rename(temp: &Path, path: &Path) -> Result<()> {
+    let mut attempt = 1;
+    loop {
+        match fs::rename(temp, path) {
+            Ok(()) => return Ok(()),
+            Err(e)
+                if attempt < RENAME_ATTEMPTS
+                    && e.kind() == std::io::ErrorKind::PermissionDenied =>
+            {
+                attempt += 1;
+                thread::sleep(RENAME_RETRY_DELAY);
+            }
+            Err(e) => return Err(Error::storage(path, e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_atomic_replaces_content() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("tracker.tsv");
+        write_atomic(&path, "old").unwrap();
+        write_atomic_with(&path, "new", WriteOptions::DURABLE).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+
+        // No temporary files are left behind
+        let names: Vec<_> = fs::read_dir(temp.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["tracker.tsv"]);
+    }
+
+    #[test]
+    fn test_write_atomic_missing_dir_fails_cleanly() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("missing").join("tracker.tsv");
+        let err = write_atomic(&path, "data").unwrap_err();
+        assert!(err.is_not_found());
+        assert!(!temp_path(&path).exists());
+    }
+}
@@ -0,0 +1,322 @@
+//! beatoraja / LR2-compatible score export
+//!
+//! INFINITAS charts have no BMS hashes, so the chart-to-hash mapping is
+//! supplied by the user as a JSON file with one object per chart:
+//!
+//! ```json
+//! [
+//!   {
+//!     "song_id": 1000,
+//!     "difficulty": "SPA",
+//!     "sha256": "0123abcd...",
+//!     "md5": "4567ef01..."
+//!   }
+//! ]
+//! ```
+//!
+//! - `difficulty` uses the short names (`SPN`, `SPH`, `SPA`, `SPL`, `DPN`, ...)
+//! - `sha256` (beatoraja) and `md5` (LR2) are both optional, but an entry
+//!   without either is ignored
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chart::{ChartId, Difficulty, SongInfo, UnlockData};
+use crate::error::Result;
+use crate::export::ExportFilter;
+use crate::score::{Lamp, ScoreMap};
+use crate::stats::bpi::deserialize_short_difficulty;
+use crate::storage::write_atomic;
+
+/// BMS hashes for one INFINITAS chart
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct BmsHashEntry {
+    pub song_id: u32,
+    #[serde(deserialize_with = "deserialize_short_difficulty")]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub md5: Option<String>,
+}
+
+/// Chart-to-BMS-hash mapping keyed by chart
+#[derive(Debug, Clone, Default)]
+pub struct BmsHashMap {
+    entries: HashMap<ChartId, BmsHashEntry>,
+}
+
+impl BmsHashMap {
+    /// Parse a mapping file's content
+    pub fn from_json(content: &str) -> Result<Self> {
+        let list: Vec<BmsHashEntry> = serde_json::from_str(content)?;
+        let entries = list
+            .into_iter()
+            .filter(|entry| entry.sha256.is_some() || entry.md5.is_some())
+            .map(|entry| (ChartId::new(entry.song_id, entry.difficulty), entry))
+            .collect();
+        Ok(Self { entries })
+    }
+
+    /// Load a mapping file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn get(&self, chart: &ChartId) -> Option<&BmsHashEntry> {
+        self.entries.get(chart)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// beatoraja clear type (`ClearType` ordinal)
+pub fn beatoraja_clear_type(lamp: Lamp) -> u8 {
+    match lamp {
+        Lamp::NoPlay => 0,
+        Lamp::Failed => 1,
+        Lamp::AssistClear => 2, // AssistEasy
+        Lamp::EasyClear => 4,
+        Lamp::Clear => 5,
+        Lamp::HardClear => 6,
+        Lamp::ExHardClear => 7,
+        Lamp::FullCombo => 8,
+    }
+}
+
+/// LR2 clear value
+///
+/// LR2 has no assist or EX-HARD clears: assist clears count as failed and
+/// EX-HARD as hard.
+pub fn lr2_clear_type(lamp: Lamp) -> u8 {
+    match lamp {
+        Lamp::NoPlay => 0,
+        Lamp::Failed | Lamp::AssistClear => 1,
+        Lamp::EasyClear => 2,
+        Lamp::Clear => 3,
+        Lamp::HardClear | Lamp::ExHardClear => 4,
+        Lamp::FullCombo => 5,
+    }
+}
+
+/// A personal best as a BMS score database entry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BmsScoreEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub md5: Option<String>,
+    pub title: String,
+    pub difficulty: String,
+    /// beatoraja clear type
+    pub clear: u8,
+    /// LR2 clear value
+    pub lr2_clear: u8,
+    pub exscore: u32,
+    pub notes: u32,
+    /// Minimum BP (miss count), if known
+    pub minbp: Option<u32>,
+}
+
+/// Build score entries for every played chart that has a hash mapping
+///
+/// Entries are sorted by song ID, then difficulty.
+pub fn generate_beatoraja_scores(
+    song_db: &HashMap<u32, SongInfo>,
+    score_map: &ScoreMap,
+    hash_map: &BmsHashMap,
+) -> Vec<BmsScoreEntry> {
+    generate_beatoraja_scores_filtered(
+        song_db,
+        &HashMap::new(),
+        score_map,
+        hash_map,
+        &ExportFilter::default(),
+    )
+}
+
+/// Build score entries for played charts matching `filter` that have a
+/// hash mapping
+pub fn generate_beatoraja_scores_filtered(
+    song_db: &HashMap<u32, SongInfo>,
+    unlock_db: &HashMap<u32, UnlockData>,
+    score_map: &ScoreMap,
+    hash_map: &BmsHashMap,
+    filter: &ExportFilter,
+) -> Vec<BmsScoreEntry> {
+    let mut charts: Vec<&ChartId> = hash_map.entries.keys().collect();
+    charts.sort();
+
+    charts
+        .into_iter()
+        .filter_map(|chart| {
+            let song = song_db.get(&chart.song_id)?;
+            if !filter.matches_chart(song, chart.difficulty, unlock_db, song_db, score_map) {
+                return None;
+            }
+            let scores = score_map.get(chart.song_id)?;
+            let lamp = scores.get_lamp(chart.difficulty);
+            if lamp == Lamp::NoPlay {
+                return None;
+            }
+            let hashes = &hash_map.entries[chart];
+
+            Some(BmsScoreEntry {
+                sha256: hashes.sha256.clone(),
+                md5: hashes.md5.clone(),
+                title: song.title.to_string(),
+                difficulty: chart.difficulty.short_name().to_string(),
+                clear: beatoraja_clear_type(lamp),
+                lr2_clear: lr2_clear_type(lamp),
+                exscore: scores.get_score(chart.difficulty),
+                notes: song.total_notes_for(chart.difficulty),
+                minbp: scores.get_miss_count(chart.difficulty),
+            })
+        })
+        .collect()
+}
+
+/// Generate beatoraja / LR2-compatible score JSON (for stdout output)
+pub fn generate_beatoraja_json(
+    song_db: &HashMap<u32, SongInfo>,
+    score_map: &ScoreMap,
+    hash_map: &BmsHashMap,
+) -> Result<String> {
+    let scores = generate_beatoraja_scores(song_db, score_map, hash_map);
+    Ok(serde_json::to_string_pretty(&scores)?)
+}
+
+/// Generate beatoraja / LR2-compatible score JSON for charts matching `filter`
+pub fn generate_beatoraja_json_filtered(
+    song_db: &HashMap<u32, SongInfo>,
+    unlock_db: &HashMap<u32, UnlockData>,
+    score_map: &ScoreMap,
+    hash_map: &BmsHashMap,
+    filter: &ExportFilter,
+) -> Result<String> {
+    let scores =
+        generate_beatoraja_scores_filtered(song_db, unlock_db, score_map, hash_map, filter);
+    Ok(serde_json::to_string_pretty(&scores)?)
+}
+
+/// Export personal bests as beatoraja / LR2-compatible score JSON
+pub fn export_beatoraja<P: AsRef<Path>>(
+    path: P,
+    song_db: &HashMap<u32, SongInfo>,
+    score_map: &ScoreMap,
+    hash_map: &BmsHashMap,
+) -> Result<()> {
+    write_atomic(path, generate_beatoraja_json(song_db, score_map, hash_map)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::export::PlayStyle;
+    use crate::play::UnlockType;
+    use crate::score::ScoreData;
+
+    const MAPPING: &str = r#"[
+        {"song_id": 1000, "difficulty": "SPA", "sha256": "aaaa", "md5": "bbbb"},
+        {"song_id": 1000, "difficulty": "SPH", "sha256": "cccc"},
+        {"song_id": 1000, "difficulty": "DPA"},
+        {"song_id": 1001, "difficulty": "SPA", "md5": "dddd"}
+    ]"#;
+
+    fn song_db() -> HashMap<u32, SongInfo> {
+        let song = SongInfo {
+            id: 1000,
+            title: Arc::from("Test Song"),
+            title_english: Arc::from(""),
+            artist: Arc::from(""),
+            genre: Arc::from(""),
+            bpm: Arc::from("150"),
+            folder: 1,
+            levels: [0, 5, 8, 10, 12, 0, 5, 8, 10, 12],
+            total_notes: [0, 500, 800, 1000, 1200, 0, 500, 800, 1000, 1200],
+            unlock_type: UnlockType::Base,
+        };
+        HashMap::from([(1000, song)])
+    }
+
+    #[test]
+    fn test_parse_hash_map() {
+        let hash_map = BmsHashMap::from_json(MAPPING).unwrap();
+        // DPA entry has no hashes
+        assert_eq!(hash_map.len(), 3);
+        let chart = ChartId::new(1000, Difficulty::SpA);
+        assert_eq!(hash_map.get(&chart).unwrap().md5.as_deref(), Some("bbbb"));
+    }
+
+    #[test]
+    fn test_clear_type_mapping() {
+        assert_eq!(beatoraja_clear_type(Lamp::ExHardClear), 7);
+        assert_eq!(beatoraja_clear_type(Lamp::FullCombo), 8);
+        assert_eq!(lr2_clear_type(Lamp::AssistClear), 1);
+        assert_eq!(lr2_clear_type(Lamp::ExHardClear), 4);
+    }
+
+    #[test]
+    fn test_generate_beatoraja_scores() {
+        let hash_map = BmsHashMap::from_json(MAPPING).unwrap();
+        let mut score_map = ScoreMap::new();
+        let mut scores = ScoreData::new(1000);
+        scores.lamp[Difficulty::SpA.index()] = Lamp::HardClear;
+        scores.score[Difficulty::SpA.index()] = 1800;
+        scores.miss_count[Difficulty::SpA.index()] = Some(12);
+        score_map.insert(1000, scores);
+
+        let entries = generate_beatoraja_scores(&song_db(), &score_map, &hash_map);
+        // SPH is unplayed, song 1001 is not in the song database
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.sha256.as_deref(), Some("aaaa"));
+        assert_eq!(entry.clear, 6);
+        assert_eq!(entry.lr2_clear, 4);
+        assert_eq!(entry.exscore, 1800);
+        assert_eq!(entry.notes, 1000);
+        assert_eq!(entry.minbp, Some(12));
+    }
+
+    #[test]
+    fn test_generate_beatoraja_scores_filtered() {
+        let hash_map = BmsHashMap::from_json(MAPPING).unwrap();
+        let mut score_map = ScoreMap::new();
+        let mut scores = ScoreData::new(1000);
+        scores.lamp[Difficulty::SpH.index()] = Lamp::Clear;
+        scores.lamp[Difficulty::SpA.index()] = Lamp::Clear;
+        score_map.insert(1000, scores);
+        let song_db = song_db();
+        let unlock_db = HashMap::new();
+        let filtered = |filter: &ExportFilter| {
+            generate_beatoraja_scores_filtered(&song_db, &unlock_db, &score_map, &hash_map, filter)
+        };
+
+        assert_eq!(filtered(&ExportFilter::default()).len(), 2);
+        let entries = filtered(&ExportFilter {
+            min_level: Some(9),
+            ..Default::default()
+        });
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].difficulty, "SPA");
+        assert!(
+            filtered(&ExportFilter {
+                style: Some(PlayStyle::Dp),
+                ..Default::default()
+            })
+            .is_empty()
+        );
+    }
+}
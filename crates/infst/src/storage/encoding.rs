@@ -0,0 +1,200 @@
+//! Text encodings for files read by other tools
+//!
+//! Files are UTF-8 unless configured otherwise. Some legacy overlay tools and
+//! spreadsheets expect a BOM or Shift-JIS; characters Shift-JIS can't
+//! represent are written as `?` and reported with a warning.
+//!
+//! Files read back by infst go through [`decode_text`], which accepts all of
+//! these encodings.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use encoding_rs::{EncoderResult, SHIFT_JIS};
+use tracing::warn;
+
+use crate::error::Result;
+
+use super::atomic::{WriteOptions, write_atomic_with};
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Encoding of a text output file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    /// UTF-8 starting with a byte order mark
+    Utf8Bom,
+    ShiftJis,
+}
+
+/// Text encoded with a [`TextEncoding`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedText {
+    pub bytes: Vec<u8>,
+    /// Characters replaced with `?` because the encoding can't represent them
+    pub unmappable: Vec<char>,
+}
+
+impl TextEncoding {
+    /// Bytes written at the start of a file
+    pub fn bom(&self) -> &'static [u8] {
+        match self {
+            Self::Utf8Bom => UTF8_BOM,
+            Self::Utf8 | Self::ShiftJis => b"",
+        }
+    }
+
+    /// Encode `text` (without the BOM), replacing unmappable characters with `?`
+    pub fn encode(&self, text: &str) -> EncodedText {
+        match self {
+            Self::Utf8 | Self::Utf8Bom => EncodedText {
+                bytes: text.as_bytes().to_vec(),
+                unmappable: Vec::new(),
+            },
+            Self::ShiftJis => encode_shift_jis(text),
+        }
+    }
+}
+
+impl fmt::Display for TextEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utf8 => write!(f, "utf-8"),
+            Self::Utf8Bom => write!(f, "utf-8-bom"),
+            Self::ShiftJis => write!(f, "shift-jis"),
+        }
+    }
+}
+
+impl FromStr for TextEncoding {
+    type Err = String;
+
+    /// `utf-8`, `utf-8-bom` or `shift-jis` (also `utf8`, `sjis`, `cp932`)
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "utf-8" | "utf8" => Ok(Self::Utf8),
+            "utf-8-bom" | "utf8-bom" => Ok(Self::Utf8Bom),
+            "shift-jis" | "sjis" | "cp932" => Ok(Self::ShiftJis),
+            _ => Err(format!(
+                "invalid encoding '{}' (expected utf-8, utf-8-bom or shift-jis)",
+                s
+            )),
+        }
+    }
+}
+
+fn encode_shift_jis(text: &str) -> EncodedText {
+    let mut encoder = SHIFT_JIS.new_encoder();
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut unmappable = Vec::new();
+    let mut remaining = text;
+    loop {
+        let capacity = encoder
+            .max_buffer_length_from_utf8_without_replacement(remaining.len())
+            .unwrap_or(remaining.len() * 2);
+        bytes.reserve(capacity);
+        let (result, read) =
+            encoder.encode_from_utf8_to_vec_without_replacement(remaining, &mut bytes, true);
+        remaining = &remaining[read..];
+        match result {
+            EncoderResult::InputEmpty => break,
+            EncoderResult::OutputFull => {}
+            EncoderResult::Unmappable(c) => {
+                bytes.push(b'?');
+                unmappable.push(c);
+            }
+        }
+    }
+    EncodedText { bytes, unmappable }
+}
+
+/// Decode a text file written in any [`TextEncoding`]
+///
+/// A UTF-8 BOM is stripped; content that isn't valid UTF-8 is read as
+/// Shift-JIS.
+pub fn decode_text(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => SHIFT_JIS.decode_without_bom_handling(bytes).0.into_owned(),
+    }
+}
+
+/// Encode `text` for appending to `path`, warning about unmappable characters
+pub fn encode_for_file(path: &Path, text: &str, encoding: TextEncoding) -> Vec<u8> {
+    let encoded = encoding.encode(text);
+    if !encoded.unmappable.is_empty() {
+        let mut chars = encoded.unmappable;
+        chars.sort_unstable();
+        chars.dedup();
+        warn!(
+            "{}: characters not representable in {} were written as '?': {}",
+            path.display(),
+            encoding,
+            chars.into_iter().collect::<String>()
+        );
+    }
+    encoded.bytes
+}
+
+/// Atomically replace `path` with `text` in `encoding` (including the BOM)
+pub fn write_text_atomic<P: AsRef<Path>>(
+    path: P,
+    text: &str,
+    encoding: TextEncoding,
+    options: WriteOptions,
+) -> Result<()> {
+    let path = path.as_ref();
+    let mut bytes = encoding.bom().to_vec();
+    bytes.extend(encode_for_file(path, text, encoding));
+    write_atomic_with(path, bytes, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_encoding() {
+        assert_eq!("UTF-8".parse(), Ok(TextEncoding::Utf8));
+        assert_eq!("utf8_bom".parse(), Ok(TextEncoding::Utf8Bom));
+        assert_eq!("sjis".parse(), Ok(TextEncoding::ShiftJis));
+        assert!("latin1".parse::<TextEncoding>().is_err());
+        for encoding in [
+            TextEncoding::Utf8,
+            TextEncoding::Utf8Bom,
+            TextEncoding::ShiftJis,
+        ] {
+            assert_eq!(encoding.to_string().parse(), Ok(encoding));
+        }
+    }
+
+    #[test]
+    fn test_shift_jis_roundtrip() {
+        let encoded = TextEncoding::ShiftJis.encode("冥 [SPA] 2000");
+        assert!(encoded.unmappable.is_empty());
+        assert_eq!(&encoded.bytes[..2], b"\x96\xBB");
+        assert_eq!(decode_text(&encoded.bytes), "冥 [SPA] 2000");
+    }
+
+    #[test]
+    fn test_shift_jis_unmappable_replaced() {
+        let encoded = TextEncoding::ShiftJis.encode("A☆é😀B");
+        assert_eq!(encoded.unmappable, vec!['é', '😀']);
+        assert_eq!(decode_text(&encoded.bytes), "A☆??B");
+    }
+
+    #[test]
+    fn test_write_text_with_bom() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.txt");
+        write_text_atomic(&path, "曲", TextEncoding::Utf8Bom, WriteOptions::default()).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.starts_with(UTF8_BOM));
+        assert_eq!(decode_text(&bytes), "曲");
+    }
+}
@@ -0,0 +1,238 @@
+//! Charts kept out of exports and remote submissions
+//!
+//! Plays of ignored charts (test plays, content the player doesn't want
+//! uploaded) are still recorded in the session files, flagged as ignored, but
+//! their bests are left out of tracker exports and their results are never
+//! submitted. The ignore file lists whole songs, single charts and title
+//! patterns (`*` matches any run of characters; case-insensitive, against the
+//! in-game or English title):
+//!
+//! ```json
+//! {
+//!   "songs": [1000],
+//!   "charts": ["25096:SPA"],
+//!   "titles": ["*(practice)*"]
+//! }
+//! ```
+
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::chart::{ChartId, SongInfo};
+use crate::error::{Error, Result};
+use crate::play::PlayData;
+use crate::score::{Lamp, ScoreMap};
+
+/// Songs, charts and title patterns to ignore
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IgnoreList {
+    songs: BTreeSet<u32>,
+    charts: BTreeSet<ChartId>,
+    /// Lowercase title patterns
+    titles: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct IgnoreFile {
+    songs: Vec<u32>,
+    charts: Vec<String>,
+    titles: Vec<String>,
+}
+
+impl IgnoreList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse an ignore file's content
+    pub fn from_json(content: &str) -> Result<Self> {
+        let raw: IgnoreFile = serde_json::from_str(content)?;
+        let mut list = Self::new();
+        list.songs.extend(raw.songs);
+        for chart in raw.charts {
+            list.charts.insert(chart.parse()?);
+        }
+        for pattern in raw.titles {
+            list.add_title_pattern(&pattern)?;
+        }
+        Ok(list)
+    }
+
+    /// Load an ignore file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| Error::storage(path, e))?;
+        Self::from_json(&content)
+    }
+
+    pub fn add_song(&mut self, song_id: u32) {
+        self.songs.insert(song_id);
+    }
+
+    pub fn add_chart(&mut self, chart: ChartId) {
+        self.charts.insert(chart);
+    }
+
+    pub fn add_title_pattern(&mut self, pattern: &str) -> Result<()> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() || pattern.chars().all(|c| c == '*') {
+            return Err(Error::invalid_config(
+                "ignore",
+                format!("title pattern {:?} would match every song", pattern),
+            ));
+        }
+        self.titles.push(pattern.to_lowercase());
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.songs.is_empty() && self.charts.is_empty() && self.titles.is_empty()
+    }
+
+    /// Whether `chart`, of a song with these titles, is ignored
+    pub fn ignores(&self, chart: ChartId, title: &str, title_english: &str) -> bool {
+        if self.songs.contains(&chart.song_id) || self.charts.contains(&chart) {
+            return true;
+        }
+        if self.titles.is_empty() {
+            return false;
+        }
+        let (title, title_english) = (title.to_lowercase(), title_english.to_lowercase());
+        self.titles.iter().any(|pattern| {
+            wildcard_match(pattern, &title)
+                || (!title_english.is_empty() && wildcard_match(pattern, &title_english))
+        })
+    }
+
+    /// Whether the chart of a play is ignored
+    pub fn ignores_play(&self, play_data: &PlayData) -> bool {
+        let chart = &play_data.chart;
+        self.ignores(chart.id(), &chart.title, &chart.title_english)
+    }
+
+    /// `score_map` without the bests of ignored charts (borrowed when
+    /// nothing is ignored)
+    pub fn mask_scores<'a>(
+        &self,
+        score_map: &'a ScoreMap,
+        song_db: &HashMap<u32, SongInfo>,
+    ) -> Cow<'a, ScoreMap> {
+        if self.is_empty() {
+            return Cow::Borrowed(score_map);
+        }
+        let mut masked = score_map.clone();
+        for (&song_id, song) in song_db {
+            for chart in ChartId::all(song_id) {
+                if !self.ignores(chart, &song.title, &song.title_english) {
+                    continue;
+                }
+                if let Some(data) = masked.get_mut(song_id) {
+                    data.set_lamp(chart.difficulty, Lamp::NoPlay);
+                    data.set_score(chart.difficulty, 0);
+                    data.set_miss_count(chart.difficulty, None);
+                    data.dj_points[chart.index()] = 0.0;
+                    data.assisted[chart.index()] = None;
+                }
+            }
+        }
+        Cow::Owned(masked)
+    }
+}
+
+/// Whether `text` matches `pattern` as a whole, `*` matching any run of
+/// characters
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*`: exact match
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::Difficulty;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("5.1.1.", "5.1.1."));
+        assert!(!wildcard_match("5.1.1.", "5.1.1. (practice)"));
+        assert!(wildcard_match("*practice*", "5.1.1. practice mix"));
+        assert!(wildcard_match("5.1*", "5.1.1."));
+        assert!(wildcard_match("*mix", "some mix"));
+        assert!(!wildcard_match("*mix", "mix it"));
+        assert!(wildcard_match("a*b*c", "a-b-b-c"));
+        assert!(!wildcard_match("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn test_from_json_and_ignores() {
+        let list = IgnoreList::from_json(
+            r#"{"songs": [1000], "charts": ["2000:spa"], "titles": ["*Practice*"]}"#,
+        )
+        .unwrap();
+        assert!(list.ignores(ChartId::new(1000, Difficulty::DpN), "5.1.1.", ""));
+        assert!(list.ignores(ChartId::new(2000, Difficulty::SpA), "x", ""));
+        assert!(!list.ignores(ChartId::new(2000, Difficulty::SpH), "x", ""));
+        assert!(list.ignores(ChartId::new(3000, Difficulty::SpN), "曲", "Song (PRACTICE)"));
+        assert!(!list.ignores(ChartId::new(3000, Difficulty::SpN), "曲", "Song"));
+
+        assert!(IgnoreList::from_json(r#"{"titles": ["**"]}"#).is_err());
+        assert!(IgnoreList::from_json(r#"{"charts": ["2000"]}"#).is_err());
+        assert!(IgnoreList::from_json(r#"{"song": [1]}"#).is_err());
+        assert!(IgnoreList::from_json("{}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_mask_scores() {
+        let song_db = HashMap::from([(
+            1000,
+            SongInfo {
+                id: 1000,
+                title: "5.1.1.".into(),
+                ..Default::default()
+            },
+        )]);
+        let mut score_map = ScoreMap::new();
+        let data = score_map.get_or_insert(1000);
+        data.set_lamp(Difficulty::SpA, Lamp::HardClear);
+        data.set_score(Difficulty::SpA, 1500);
+        data.set_lamp(Difficulty::SpH, Lamp::Clear);
+
+        assert!(matches!(
+            IgnoreList::new().mask_scores(&score_map, &song_db),
+            Cow::Borrowed(_)
+        ));
+
+        let mut list = IgnoreList::new();
+        list.add_chart(ChartId::new(1000, Difficulty::SpA));
+        let masked = list.mask_scores(&score_map, &song_db);
+        assert_eq!(
+            masked.lamp(ChartId::new(1000, Difficulty::SpA)),
+            Lamp::NoPlay
+        );
+        assert_eq!(masked.score(ChartId::new(1000, Difficulty::SpA)), 0);
+        assert_eq!(
+            masked.lamp(ChartId::new(1000, Difficulty::SpH)),
+            Lamp::Clear
+        );
+    }
+}
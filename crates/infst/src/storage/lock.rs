@@ -0,0 +1,145 @@
+//! Single tracker instance lock
+//!
+//! Two trackers writing the same `tracker.tsv` and session files corrupt
+//! each other. The tracker holds an OS file lock on `infst.lock` in the
+//! session directory while it runs; the OS releases it when the process
+//! exits, so a crash never leaves a stale lock behind.
+
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+
+/// State of the instance lock in a directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockState {
+    Free,
+    /// Held by a running tracker (PID when readable)
+    Held {
+        pid: Option<u32>,
+    },
+}
+
+/// Held instance lock, released on drop
+#[derive(Debug)]
+pub struct InstanceLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    pub const FILE_NAME: &'static str = "infst.lock";
+
+    /// Lock file path for `dir`
+    pub fn path_in<P: AsRef<Path>>(dir: P) -> PathBuf {
+        dir.as_ref().join(Self::FILE_NAME)
+    }
+
+    /// Take the lock in `dir`, failing with [`Error::InstanceLocked`] if
+    /// another tracker holds it
+    pub fn acquire<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).map_err(|e| Error::storage(dir, e))?;
+        let path = Self::path_in(dir);
+        let mut file = open(&path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                return Err(Error::InstanceLocked {
+                    pid: read_pid(&path),
+                    path,
+                });
+            }
+            Err(TryLockError::Error(e)) => return Err(Error::storage(&path, e)),
+        }
+
+        // Record our PID for the error message of the next instance
+        file.set_len(0)
+            .and_then(|()| file.rewind())
+            .and_then(|()| write!(file, "{}", std::process::id()))
+            .map_err(|e| Error::storage(&path, e))?;
+        Ok(Self { file, path })
+    }
+
+    /// Check whether a tracker holds the lock in `dir` without keeping it
+    pub fn state<P: AsRef<Path>>(dir: P) -> Result<LockState> {
+        let path = Self::path_in(dir);
+        if !path.exists() {
+            return Ok(LockState::Free);
+        }
+        let file = open(&path)?;
+        match file.try_lock_shared() {
+            Ok(()) => Ok(LockState::Free),
+            Err(TryLockError::WouldBlock) => Ok(LockState::Held {
+                pid: read_pid(&path),
+            }),
+            Err(TryLockError::Error(e)) => Err(Error::storage(&path, e)),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+fn open(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| Error::storage(path, e))
+}
+
+/// PID written by the holder (unreadable on Windows while locked)
+fn read_pid(path: &Path) -> Option<u32> {
+    let mut content = String::new();
+    File::open(path).ok()?.read_to_string(&mut content).ok()?;
+    content.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_second_acquire_fails_until_released() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("sessions");
+        assert_eq!(InstanceLock::state(&dir).unwrap(), LockState::Free);
+
+        let lock = InstanceLock::acquire(&dir).unwrap();
+        assert_eq!(lock.path(), InstanceLock::path_in(&dir));
+        assert!(matches!(
+            InstanceLock::state(&dir).unwrap(),
+            LockState::Held { .. }
+        ));
+        let err = InstanceLock::acquire(&dir).unwrap_err();
+        assert!(matches!(err, Error::InstanceLocked { .. }));
+        assert!(err.hint().unwrap().contains("--force"));
+
+        drop(lock);
+        assert_eq!(InstanceLock::state(&dir).unwrap(), LockState::Free);
+        InstanceLock::acquire(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_error_reports_holder_pid() {
+        let temp = TempDir::new().unwrap();
+        let _lock = InstanceLock::acquire(temp.path()).unwrap();
+        match InstanceLock::acquire(temp.path()).unwrap_err() {
+            Error::InstanceLocked { pid, .. } => assert_eq!(pid, Some(std::process::id())),
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+}
@@ -0,0 +1,489 @@
+//! Format versions and migrations for persisted files
+//!
+//! Every file infst writes for its own later use carries a version marker:
+//! - TSV files (session, tracker): a first line `# infst-format: <kind> <version>`
+//! - Session JSON: an object `{"format": "session-json", "version": 2, "entries": [...]}`
+//!
+//! Files without a marker were written before markers existed and are
+//! version 1. Readers pass file content through [`migrate`] so old files
+//! load as the current format; files from a newer version are rejected
+//! instead of being misread. Unlock state is read from memory on every start
+//! and is never persisted, so it has no format here.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value as JsonValue, json};
+
+use crate::error::{Error, Result};
+use crate::export::format_full_tsv_header;
+use crate::play::PlayData;
+use crate::score::Grade;
+use crate::storage::{decode_text, write_atomic};
+
+/// Prefix of the version marker line in TSV files
+const MARKER_PREFIX: &str = "# infst-format: ";
+
+/// A persisted file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    /// `sessions/Session_*.tsv`
+    SessionTsv,
+    /// `sessions/Session_*.json`
+    SessionJson,
+    /// `tracker.tsv` and its dated snapshots
+    TrackerTsv,
+}
+
+impl FileFormat {
+    pub const ALL: [FileFormat; 3] = [Self::SessionTsv, Self::SessionJson, Self::TrackerTsv];
+
+    /// Name used in version markers
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SessionTsv => "session-tsv",
+            Self::SessionJson => "session-json",
+            Self::TrackerTsv => "tracker-tsv",
+        }
+    }
+
+    /// Version written by this build
+    ///
+    /// - session-tsv 2: marker; `nextgrade`, `nextgradegap`, `scorerate`, `bpi` columns
+    /// - session-tsv 3: `premature` column
+    /// - session-tsv 4: `failedat` column
+    /// - session-tsv 5: `sudden`, `hidden`, `lift`, `greennumber` columns
+    /// - session-json 2: versioned object instead of a bare array
+    /// - tracker-tsv 2: marker
+    pub fn current_version(self) -> u32 {
+        match self {
+            Self::SessionTsv => 5,
+            Self::SessionJson | Self::TrackerTsv => 2,
+        }
+    }
+
+    /// Marker line for TSV formats (e.g. `# infst-format: tracker-tsv 2`)
+    pub fn marker(self) -> String {
+        self.marker_for(self.current_version())
+    }
+
+    fn marker_for(self, version: u32) -> String {
+        format!("{}{} {}", MARKER_PREFIX, self.name(), version)
+    }
+}
+
+/// Parse a TSV marker line into format name and version
+fn parse_marker(line: &str) -> Option<(&str, u32)> {
+    let (name, version) = line.strip_prefix(MARKER_PREFIX)?.trim().split_once(' ')?;
+    Some((name, version.parse().ok()?))
+}
+
+/// Detect the format version of a file's content (1 when unmarked)
+pub fn detect_version(format: FileFormat, content: &str) -> Result<u32> {
+    let (name, version) = match format {
+        FileFormat::SessionTsv | FileFormat::TrackerTsv => {
+            let first = content.lines().next().unwrap_or_default();
+            if !first.starts_with(MARKER_PREFIX) {
+                return Ok(1);
+            }
+            let (name, version) = parse_marker(first)
+                .ok_or_else(|| Error::parse(format.name(), format!("bad marker: {}", first)))?;
+            (name.to_string(), version)
+        }
+        FileFormat::SessionJson => match serde_json::from_str::<JsonValue>(content)? {
+            JsonValue::Array(_) => return Ok(1),
+            document => {
+                let name = document["format"].as_str().unwrap_or_default().to_string();
+                let version = document["version"]
+                    .as_u64()
+                    .ok_or_else(|| Error::parse(format.name(), "missing version"))?;
+                (name, version as u32)
+            }
+        },
+    };
+    if name != format.name() {
+        return Err(Error::parse(
+            format.name(),
+            format!("file is marked as {}", name),
+        ));
+    }
+    if version > format.current_version() {
+        return Err(Error::UnsupportedFormatVersion {
+            format: format.name(),
+            version,
+            supported: format.current_version(),
+        });
+    }
+    Ok(version)
+}
+
+/// Result of loading a file through [`migrate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    pub format: FileFormat,
+    /// Version the content was written in
+    pub from_version: u32,
+    /// Content in the current format
+    pub content: String,
+}
+
+impl Migration {
+    /// Whether the content was converted from an older version
+    pub fn upgraded(&self) -> bool {
+        self.from_version < self.format.current_version()
+    }
+}
+
+/// Upgrade file content to the current version of `format`
+pub fn migrate(format: FileFormat, content: &str) -> Result<Migration> {
+    let from_version = detect_version(format, content)?;
+    let mut content = content.to_string();
+    // One step per version, so old files pass through every layout change
+    for version in from_version..format.current_version() {
+        content = match format {
+            FileFormat::SessionTsv => match version {
+                1 => session_tsv_v1_to_v2(&content),
+                2 => session_tsv_v2_to_v3(&content),
+                3 => session_tsv_v3_to_v4(&content),
+                _ => session_tsv_v4_to_v5(&content),
+            },
+            FileFormat::SessionJson => session_json_v1_to_v2(&content)?,
+            FileFormat::TrackerTsv => format!("{}\n{}", format.marker(), content),
+        };
+    }
+    Ok(Migration {
+        format,
+        from_version,
+        content,
+    })
+}
+
+/// Upgrade a file in place, keeping the original as `<file>.v<N>.bak`
+///
+/// Returns the version the file was written in.
+pub fn migrate_file<P: AsRef<Path>>(format: FileFormat, path: P) -> Result<u32> {
+    let path = path.as_ref();
+    let content = decode_text(&fs::read(path).map_err(|e| Error::storage(path, e))?);
+    let migration = migrate(format, &content)?;
+    if migration.upgraded() {
+        let backup = backup_path(path, migration.from_version);
+        fs::copy(path, &backup).map_err(|e| Error::storage(&backup, e))?;
+        write_atomic(path, &migration.content)?;
+    }
+    Ok(migration.from_version)
+}
+
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{}.bak", version));
+    path.with_file_name(name)
+}
+
+/// Wrap session JSON entries in the current versioned document
+pub fn session_json_document(entries: &[JsonValue]) -> JsonValue {
+    json!({
+        "format": FileFormat::SessionJson.name(),
+        "version": FileFormat::SessionJson.current_version(),
+        "entries": entries,
+    })
+}
+
+/// Session JSON v1 was a bare array of entries
+fn session_json_v1_to_v2(content: &str) -> Result<String> {
+    let entries: Vec<JsonValue> = serde_json::from_str(content)?;
+    Ok(serde_json::to_string_pretty(&session_json_document(
+        &entries,
+    ))?)
+}
+
+/// Session TSV columns added after version 2, by the version that added them
+const SESSION_TSV_ADDED_COLUMNS: &[(u32, &[&str])] = &[
+    (3, &["premature"]),
+    (4, &["failedat"]),
+    (5, &["sudden", "hidden", "lift", "greennumber"]),
+];
+
+/// Session TSV columns written by `version` (2 or later), plus newer ones
+/// already in `existing`
+fn session_tsv_columns(version: u32, existing: &[&str]) -> Vec<String> {
+    let newer: Vec<&str> = SESSION_TSV_ADDED_COLUMNS
+        .iter()
+        .filter(|(added, _)| *added > version)
+        .flat_map(|(_, columns)| columns.iter().copied())
+        .collect();
+    format_full_tsv_header()
+        .split('\t')
+        .filter(|column| !newer.contains(column) || existing.contains(column))
+        .map(String::from)
+        .collect()
+}
+
+/// Session TSV v1 lacked the derived `nextgrade`, `nextgradegap` and
+/// `scorerate` columns (recomputed here) and `bpi` (left empty)
+fn session_tsv_v1_to_v2(content: &str) -> String {
+    rewrite_session_tsv(content, 2)
+}
+
+/// Session TSV v3 added `premature` (0 for older plays)
+fn session_tsv_v2_to_v3(content: &str) -> String {
+    rewrite_session_tsv(content, 3)
+}
+
+/// Session TSV v4 added `failedat` (empty for older plays)
+fn session_tsv_v3_to_v4(content: &str) -> String {
+    rewrite_session_tsv(content, 4)
+}
+
+/// Session TSV v5 added the lane cover columns (empty for older plays)
+fn session_tsv_v4_to_v5(content: &str) -> String {
+    rewrite_session_tsv(content, 5)
+}
+
+/// Rewrite rows in the column order of `version`, filling in missing columns
+///
+/// Columns are matched by name, so files that already have some of the new
+/// columns keep them. `# ` comment lines other than the marker are kept.
+fn rewrite_session_tsv(content: &str, version: u32) -> String {
+    let mut lines = vec![FileFormat::SessionTsv.marker_for(version)];
+
+    let mut old_columns: Option<Vec<&str>> = None;
+    let mut columns = Vec::new();
+    for line in content.lines() {
+        if line.starts_with(MARKER_PREFIX) {
+            continue;
+        }
+        // Rows of titles starting with `#` aren't comments
+        if line.starts_with("# ") {
+            lines.push(line.to_string());
+            continue;
+        }
+        let Some(old) = &old_columns else {
+            let old = line.split('\t').collect::<Vec<_>>();
+            columns = session_tsv_columns(version, &old);
+            lines.push(columns.join("\t"));
+            old_columns = Some(old);
+            continue;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').collect();
+        let get = |name: &str| {
+            old.iter()
+                .position(|c| *c == name)
+                .and_then(|i| fields.get(i))
+                .copied()
+        };
+        let ex_score = get("exscore").and_then(|v| v.parse().ok()).unwrap_or(0);
+        let total_notes = get("notecount").and_then(|v| v.parse().ok()).unwrap_or(0);
+        let row: Vec<String> = columns
+            .iter()
+            .map(|column| match get(column) {
+                Some(value) => value.to_string(),
+                None => derived_column(column, ex_score, total_notes),
+            })
+            .collect();
+        lines.push(row.join("\t"));
+    }
+    if old_columns.is_none() {
+        lines.push(session_tsv_columns(version, &[]).join("\t"));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Value of a column added after v1 for a row without it (matches
+/// `format_full_tsv_row`)
+fn derived_column(column: &str, ex_score: u32, total_notes: u32) -> String {
+    let next_grade = || -> Option<(Grade, u32)> {
+        if total_notes == 0 {
+            return None;
+        }
+        let current = PlayData::calculate_grade(ex_score, total_notes);
+        Grade::thresholds(total_notes)
+            .into_iter()
+            .find(|(grade, _)| *grade > current)
+            .map(|(grade, score)| (grade, score.saturating_sub(ex_score)))
+    };
+    match column {
+        "nextgrade" => next_grade()
+            .map(|(grade, _)| grade.short_name().to_string())
+            .unwrap_or_default(),
+        "nextgradegap" => next_grade()
+            .map(|(_, gap)| gap.to_string())
+            .unwrap_or_default(),
+        "scorerate" if total_notes > 0 => {
+            format!("{:.2}", ex_score as f64 * 100.0 / (total_notes * 2) as f64)
+        }
+        "scorerate" => format!("{:.2}", 0.0),
+        "premature" => "0".to_string(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::play::test_support::PlayBuilder;
+
+    #[test]
+    fn test_markers() {
+        assert_eq!(
+            FileFormat::TrackerTsv.marker(),
+            "# infst-format: tracker-tsv 2"
+        );
+        for format in FileFormat::ALL {
+            assert_eq!(
+                parse_marker(&format.marker()),
+                Some((format.name(), format.current_version()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_detect_version() {
+        assert_eq!(
+            detect_version(FileFormat::TrackerTsv, "Song ID\tTitle").unwrap(),
+            1
+        );
+        let current = format!("{}\nSong ID\tTitle", FileFormat::TrackerTsv.marker());
+        assert_eq!(detect_version(FileFormat::TrackerTsv, &current).unwrap(), 2);
+        assert_eq!(detect_version(FileFormat::SessionJson, "[]").unwrap(), 1);
+
+        // Newer versions and other formats are rejected
+        let newer = "# infst-format: tracker-tsv 3\nSong ID";
+        assert!(matches!(
+            detect_version(FileFormat::TrackerTsv, newer),
+            Err(Error::UnsupportedFormatVersion { version: 3, .. })
+        ));
+        assert!(detect_version(FileFormat::SessionTsv, &current).is_err());
+        let json = r#"{"format": "session-json", "version": 9, "entries": []}"#;
+        assert!(detect_version(FileFormat::SessionJson, json).is_err());
+    }
+
+    #[test]
+    fn test_current_content_is_unchanged() {
+        let content = serde_json::to_string_pretty(&session_json_document(&[])).unwrap();
+        let migration = migrate(FileFormat::SessionJson, &content).unwrap();
+        assert!(!migration.upgraded());
+        assert_eq!(migration.content, content);
+    }
+
+    /// Session TSV in the `version` layout with one row of `play`
+    fn session_tsv(version: u32, play: &PlayData) -> String {
+        let header = format_full_tsv_header();
+        let row = crate::export::format_full_tsv_row(play);
+        let columns = session_tsv_columns(version, &[]);
+        let (header, row): (Vec<&str>, Vec<&str>) = header
+            .split('\t')
+            .zip(row.split('\t'))
+            .filter(|(column, _)| columns.iter().any(|c| c == column))
+            .unzip();
+        format!(
+            "{}\n{}\n{}\n",
+            FileFormat::SessionTsv.marker_for(version),
+            header.join("\t"),
+            row.join("\t")
+        )
+    }
+
+    #[test]
+    fn test_session_tsv_v2_to_v3() {
+        let play = PlayBuilder::new().build();
+        let v2 = session_tsv(2, &play);
+        assert!(!v2.contains("premature"));
+
+        let migration = migrate(FileFormat::SessionTsv, &v2).unwrap();
+        assert_eq!(migration.from_version, 2);
+        let current = FileFormat::SessionTsv.current_version();
+        assert_eq!(migration.content, session_tsv(current, &play));
+    }
+
+    #[test]
+    fn test_session_tsv_v3_to_v4() {
+        let play = PlayBuilder::new()
+            .lamp(crate::score::Lamp::Failed)
+            .failed_at_percent(Some(42.0))
+            .build();
+        let v3 = session_tsv(3, &play);
+        assert!(!v3.contains("failedat"));
+
+        let migration = migrate(FileFormat::SessionTsv, &v3).unwrap();
+        assert_eq!(migration.from_version, 3);
+        // The fail point is not known for older plays
+        let unknown = PlayData {
+            failed_at_percent: None,
+            ..play
+        };
+        let current = FileFormat::SessionTsv.current_version();
+        assert_eq!(migration.content, session_tsv(current, &unknown));
+    }
+
+    #[test]
+    fn test_session_tsv_v4_to_v5() {
+        let play = PlayBuilder::new()
+            .settings(crate::play::Settings {
+                lane_cover: Some(crate::play::LaneCover {
+                    sudden: 300,
+                    green_number: 290,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .build();
+        let v4 = session_tsv(4, &play);
+        assert!(!v4.contains("greennumber"));
+
+        let migration = migrate(FileFormat::SessionTsv, &v4).unwrap();
+        assert_eq!(migration.from_version, 4);
+        let unknown = PlayData {
+            settings: crate::play::Settings::default(),
+            ..play
+        };
+        let current = FileFormat::SessionTsv.current_version();
+        assert_eq!(migration.content, session_tsv(current, &unknown));
+    }
+
+    #[test]
+    fn test_session_tsv_rewrites_hash_titles() {
+        let play = PlayBuilder::new().title("#HASHTAG").build();
+        let migration = migrate(FileFormat::SessionTsv, &session_tsv(2, &play)).unwrap();
+        let current = FileFormat::SessionTsv.current_version();
+        assert_eq!(migration.content, session_tsv(current, &play));
+    }
+
+    #[test]
+    fn test_session_tsv_keeps_newer_columns() {
+        // Written by a build that added columns without bumping the version
+        let play = PlayBuilder::new().build();
+        let current = session_tsv(FileFormat::SessionTsv.current_version(), &play);
+        let marked_v2 = current.replace(
+            &FileFormat::SessionTsv.marker(),
+            &FileFormat::SessionTsv.marker_for(2),
+        );
+        let migration = migrate(FileFormat::SessionTsv, &marked_v2).unwrap();
+        assert_eq!(migration.content, current);
+    }
+
+    #[test]
+    fn test_migrate_file_keeps_backup() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("tracker.tsv");
+        fs::write(&path, "Song ID\tTitle\n01000\tSong").unwrap();
+
+        assert_eq!(migrate_file(FileFormat::TrackerTsv, &path).unwrap(), 1);
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            content,
+            "# infst-format: tracker-tsv 2\nSong ID\tTitle\n01000\tSong"
+        );
+        let backup = temp.path().join("tracker.tsv.v1.bak");
+        assert_eq!(
+            fs::read_to_string(backup).unwrap(),
+            "Song ID\tTitle\n01000\tSong"
+        );
+
+        // Already current: nothing to do
+        assert_eq!(migrate_file(FileFormat::TrackerTsv, &path).unwrap(), 2);
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+    }
+}
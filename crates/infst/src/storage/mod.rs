@@ -0,0 +1,25 @@
+//! Score storage formats shared with other tools.
+//!
+//! - `export_beatoraja` - personal bests as beatoraja / LR2 score entries
+//! - `migrate` - format version markers and upgrades of old files
+//! - `write_atomic` - crash-safe file replacement (temp file + rename)
+//! - `TextEncoding` - UTF-8 / UTF-8 BOM / Shift-JIS output for text files
+//! - `InstanceLock` - lock preventing two trackers from sharing files
+//! - `SongTags` - user-defined song tags
+//! - `IgnoreList` - charts kept out of exports and remote submissions
+
+mod atomic;
+mod beatoraja;
+mod encoding;
+mod ignore;
+mod lock;
+pub mod migrate;
+mod tags;
+
+pub use atomic::*;
+pub use beatoraja::*;
+pub use encoding::*;
+pub use ignore::*;
+pub use lock::*;
+pub use migrate::{FileFormat, Migration};
+pub use tags::*;
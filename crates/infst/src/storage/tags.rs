@@ -0,0 +1,164 @@
+//! User-defined song tags
+//!
+//! Tags group songs the way the game's custom folders do, but outside the
+//! game: a tags file maps each tag name to the song IDs it contains.
+//!
+//! ```json
+//! {
+//!   "scratch heavy": [1000, 25096],
+//!   "speed practice": [1204]
+//! }
+//! ```
+//!
+//! A song may carry any number of tags. Tag names are trimmed; empty names
+//! are rejected. Exports list each song's tags, and exports and
+//! recommendations can be restricted to the songs with given tags.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Tag names mapped to the song IDs they contain
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SongTags {
+    tags: BTreeMap<String, BTreeSet<u32>>,
+}
+
+impl SongTags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a tags file's content
+    pub fn from_json(content: &str) -> Result<Self> {
+        let raw: BTreeMap<String, BTreeSet<u32>> = serde_json::from_str(content)?;
+        let mut tags = Self::new();
+        for (name, song_ids) in raw {
+            // Tags without songs are kept so they can still be listed
+            tags.entry(&name)?.extend(song_ids);
+        }
+        Ok(tags)
+    }
+
+    /// Load a tags file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| Error::storage(path, e))?;
+        Self::from_json(&content)
+    }
+
+    /// Tag a song
+    pub fn insert(&mut self, tag: &str, song_id: u32) -> Result<()> {
+        self.entry(tag)?.insert(song_id);
+        Ok(())
+    }
+
+    fn entry(&mut self, tag: &str) -> Result<&mut BTreeSet<u32>> {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return Err(Error::invalid_config("tags", "tag names must not be empty"));
+        }
+        Ok(self.tags.entry(tag.to_string()).or_default())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// Tag names in alphabetical order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tags.keys().map(String::as_str)
+    }
+
+    /// Songs with a tag (`None` for an unknown tag)
+    pub fn songs(&self, tag: &str) -> Option<&BTreeSet<u32>> {
+        self.tags.get(tag.trim())
+    }
+
+    /// Tags of a song in alphabetical order
+    pub fn tags_for(&self, song_id: u32) -> Vec<&str> {
+        self.tags
+            .iter()
+            .filter(|(_, songs)| songs.contains(&song_id))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Songs carrying any of `tags`
+    ///
+    /// An unknown tag is an error rather than an empty match, so a typo does
+    /// not silently filter out everything.
+    pub fn songs_with_any(&self, tags: &[String]) -> Result<HashSet<u32>> {
+        let mut songs = HashSet::new();
+        for tag in tags {
+            let Some(tagged) = self.songs(tag) else {
+                return Err(Error::invalid_config(
+                    "tags",
+                    format!("unknown tag '{}'", tag.trim()),
+                ));
+            };
+            songs.extend(tagged);
+        }
+        Ok(songs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TAGS: &str = r#"{
+        "scratch heavy": [1000, 25096],
+        " speed practice ": [1000, 1204],
+        "someday": []
+    }"#;
+
+    #[test]
+    fn test_parse_tags() {
+        let tags = SongTags::from_json(TAGS).unwrap();
+        assert_eq!(
+            tags.names().collect::<Vec<_>>(),
+            vec!["scratch heavy", "someday", "speed practice"]
+        );
+        assert_eq!(tags.tags_for(1000), vec!["scratch heavy", "speed practice"]);
+        assert_eq!(tags.tags_for(1204), vec!["speed practice"]);
+        assert!(tags.tags_for(9999).is_empty());
+        assert!(tags.songs("someday").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_songs_with_any() {
+        let tags = SongTags::from_json(TAGS).unwrap();
+        let songs = tags
+            .songs_with_any(&["scratch heavy".to_string(), "speed practice".to_string()])
+            .unwrap();
+        assert_eq!(songs, HashSet::from([1000, 1204, 25096]));
+
+        let err = tags.songs_with_any(&["scrach heavy".to_string()]);
+        assert!(err.unwrap_err().to_string().contains("unknown tag"));
+    }
+
+    #[test]
+    fn test_empty_tag_name_is_rejected() {
+        assert!(SongTags::from_json(r#"{" ": [1000]}"#).is_err());
+        assert!(SongTags::new().insert("", 1000).is_err());
+    }
+
+    #[test]
+    fn test_load_and_serialize() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("tags.json");
+        let mut tags = SongTags::new();
+        tags.insert("scratch heavy", 1000).unwrap();
+        fs::write(&path, serde_json::to_string(&tags).unwrap()).unwrap();
+        assert_eq!(SongTags::load(&path).unwrap(), tags);
+
+        let missing = SongTags::load(temp.path().join("missing.json")).unwrap_err();
+        assert!(missing.is_not_found());
+    }
+}
@@ -0,0 +1,401 @@
+//! Marquee text templating
+//!
+//! Templates are plain text with placeholders:
+//!
+//! - `{title}` is replaced by the value of `title`
+//! - `{?pb_diff} ({pb_diff}){/pb_diff}` is only rendered when `pb_diff` is non-empty
+//! - `{{` and `}}` produce literal braces
+//!
+//! Available placeholders are listed in [`MARQUEE_PLACEHOLDERS`].
+
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::export::DisplayTimezone;
+use crate::play::PlayData;
+use crate::score::ScoreData;
+use crate::stats::PlayCounts;
+
+/// Default marquee template
+pub const DEFAULT_MARQUEE_TEMPLATE: &str =
+    "{title} [{diff}] {score} {lamp}{?pb_diff} ({pb_diff}){/pb_diff}";
+
+/// Default template for the last play summary copied to the clipboard
+pub const DEFAULT_CLIPBOARD_TEMPLATE: &str = "{title} [{diff} {level}] {lamp} {grade} EX {score} ({rate}){?pb_diff} PB {pb_diff}{/pb_diff}{?miss} / MISS {miss}{/miss}";
+
+/// Default text shown while no result is displayed
+pub const DEFAULT_MARQUEE_IDLE_TEXT: &str = "INFINITAS";
+
+/// Placeholder names accepted in marquee templates
+pub const MARQUEE_PLACEHOLDERS: &[&str] = &[
+    "title",
+    "title2",
+    "artist",
+    "genre",
+    "bpm",
+    "diff",
+    "level",
+    "score",
+    "grade",
+    "lamp",
+    "miss",
+    "pb_diff",
+    "next_grade",
+    "next_gap",
+    "rate",
+    "bpi",
+    "date",
+    "time",
+    "premature",
+    "failed_at",
+    "today_plays",
+    "today_notes",
+    "week_plays",
+    "week_notes",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Placeholder(String),
+    Conditional { key: String, body: Vec<Segment> },
+}
+
+/// Parsed marquee template
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarqueeTemplate {
+    segments: Vec<Segment>,
+}
+
+impl MarqueeTemplate {
+    /// Parse a template string
+    ///
+    /// Fails on unknown placeholders, unclosed braces and mismatched
+    /// conditional sections.
+    pub fn parse(template: &str) -> Result<Self> {
+        Self::parse_with(template, &[])
+    }
+
+    /// Parse a template that may also use the placeholders in `extra`
+    pub fn parse_with(template: &str, extra: &[&str]) -> Result<Self> {
+        let mut stack: Vec<(String, Vec<Segment>)> = Vec::new();
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut tag = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => tag.push(c),
+                            None => {
+                                return Err(Error::InvalidTemplate(format!(
+                                    "unclosed placeholder '{{{}'",
+                                    tag
+                                )));
+                            }
+                        }
+                    }
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+
+                    if let Some(key) = tag.strip_prefix('?') {
+                        validate_key(key, extra)?;
+                        stack.push((key.to_string(), std::mem::take(&mut segments)));
+                    } else if let Some(key) = tag.strip_prefix('/') {
+                        let Some((open_key, parent)) = stack.pop() else {
+                            return Err(Error::InvalidTemplate(format!(
+                                "'{{/{}}}' without matching '{{?{}}}'",
+                                key, key
+                            )));
+                        };
+                        if open_key != key {
+                            return Err(Error::InvalidTemplate(format!(
+                                "'{{/{}}}' closes '{{?{}}}'",
+                                key, open_key
+                            )));
+                        }
+                        let body = std::mem::replace(&mut segments, parent);
+                        segments.push(Segment::Conditional {
+                            key: open_key,
+                            body,
+                        });
+                    } else {
+                        validate_key(&tag, extra)?;
+                        segments.push(Segment::Placeholder(tag));
+                    }
+                }
+                '}' => {
+                    return Err(Error::InvalidTemplate(
+                        "unmatched '}' (use '}}' for a literal brace)".to_string(),
+                    ));
+                }
+                c => literal.push(c),
+            }
+        }
+
+        if let Some((key, _)) = stack.pop() {
+            return Err(Error::InvalidTemplate(format!(
+                "'{{?{}}}' is never closed",
+                key
+            )));
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Render the template; missing values render as empty text
+    pub fn render(&self, values: &HashMap<&str, String>) -> String {
+        let mut output = String::new();
+        render_segments(&self.segments, values, &mut output);
+        output
+    }
+}
+
+impl Default for MarqueeTemplate {
+    fn default() -> Self {
+        Self::parse(DEFAULT_MARQUEE_TEMPLATE).expect("default marquee template is valid")
+    }
+}
+
+fn validate_key(key: &str, extra: &[&str]) -> Result<()> {
+    if MARQUEE_PLACEHOLDERS.contains(&key) || extra.contains(&key) {
+        Ok(())
+    } else {
+        Err(Error::InvalidTemplate(format!(
+            "unknown placeholder '{}'",
+            key
+        )))
+    }
+}
+
+fn render_segments(segments: &[Segment], values: &HashMap<&str, String>, output: &mut String) {
+    for segment in segments {
+        match segment {
+            Segment::Literal(text) => output.push_str(text),
+            Segment::Placeholder(key) => {
+                if let Some(value) = values.get(key.as_str()) {
+                    output.push_str(value);
+                }
+            }
+            Segment::Conditional { key, body } => {
+                if values.get(key.as_str()).is_some_and(|v| !v.is_empty()) {
+                    render_segments(body, values, output);
+                }
+            }
+        }
+    }
+}
+
+/// Build placeholder values for a play
+///
+/// `pb_diff` is the signed EX score difference to `personal_best` and is
+/// empty when there is no previous score or the play ended prematurely.
+/// `premature` is `QUIT` for premature plays and empty otherwise. `failed_at`
+/// is the chart completion of a failed play (`80.0%`) and empty otherwise.
+/// `date` and `time` are the play time in `timezone`.
+pub fn marquee_values(
+    play_data: &PlayData,
+    personal_best: Option<&ScoreData>,
+    timezone: DisplayTimezone,
+) -> HashMap<&'static str, String> {
+    let chart = &play_data.chart;
+    let next_grade = play_data.next_grade_gap();
+    let best_score = personal_best
+        .map(|best| best.get_score(chart.difficulty))
+        .unwrap_or(0);
+
+    let mut values = HashMap::new();
+    values.insert("title", chart.title.to_string());
+    values.insert("title2", chart.title_english.to_string());
+    values.insert("artist", chart.artist.to_string());
+    values.insert("genre", chart.genre.to_string());
+    values.insert("bpm", chart.bpm.to_string());
+    values.insert("diff", chart.difficulty.short_name().to_string());
+    values.insert("level", chart.level.to_string());
+    values.insert("score", play_data.ex_score.to_string());
+    values.insert("grade", play_data.grade.short_name().to_string());
+    values.insert("lamp", play_data.lamp.short_name().to_string());
+    values.insert(
+        "miss",
+        if play_data.miss_count_valid() {
+            play_data.miss_count().to_string()
+        } else {
+            String::new()
+        },
+    );
+    values.insert(
+        "pb_diff",
+        if best_score > 0 && !play_data.is_premature() {
+            format!("{:+}", play_data.ex_score as i64 - best_score as i64)
+        } else {
+            String::new()
+        },
+    );
+    values.insert(
+        "next_grade",
+        next_grade
+            .map(|(grade, _)| grade.short_name().to_string())
+            .unwrap_or_default(),
+    );
+    values.insert(
+        "next_gap",
+        next_grade
+            .map(|(_, gap)| gap.to_string())
+            .unwrap_or_default(),
+    );
+    values.insert("rate", format!("{:.2}%", play_data.score_rate() * 100.0));
+    values.insert(
+        "bpi",
+        play_data
+            .bpi
+            .map(|bpi| format!("{:.2}", bpi))
+            .unwrap_or_default(),
+    );
+    values.insert(
+        "premature",
+        if play_data.is_premature() {
+            "QUIT".to_string()
+        } else {
+            String::new()
+        },
+    );
+    values.insert(
+        "failed_at",
+        play_data
+            .failed_at_percent
+            .map(|percent| format!("{:.1}%", percent))
+            .unwrap_or_default(),
+    );
+    values.insert("date", timezone.format(&play_data.timestamp, "%Y-%m-%d"));
+    values.insert("time", timezone.format(&play_data.timestamp, "%H:%M"));
+    values
+}
+
+/// Add the play count placeholders: plays and notes hit today
+/// (`today_plays`, `today_notes`) and this week (`week_plays`, `week_notes`)
+pub fn insert_play_counts(values: &mut HashMap<&'static str, String>, counts: &PlayCounts) {
+    values.insert("today_plays", counts.today.plays.to_string());
+    values.insert("today_notes", counts.today.notes_hit.to_string());
+    values.insert("week_plays", counts.this_week.plays.to_string());
+    values.insert("week_notes", counts.this_week.notes_hit.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&'static str, &str)]) -> HashMap<&'static str, String> {
+        pairs.iter().map(|(k, v)| (*k, v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_render_placeholders() {
+        let template = MarqueeTemplate::parse("{title} [{diff}] {score}").unwrap();
+        let rendered = template.render(&values(&[
+            ("title", "冥"),
+            ("diff", "SPA"),
+            ("score", "2500"),
+        ]));
+        assert_eq!(rendered, "冥 [SPA] 2500");
+    }
+
+    #[test]
+    fn test_render_conditional() {
+        let template = MarqueeTemplate::parse("{score}{?pb_diff} ({pb_diff}){/pb_diff}").unwrap();
+        assert_eq!(
+            template.render(&values(&[("score", "2500"), ("pb_diff", "+12")])),
+            "2500 (+12)"
+        );
+        assert_eq!(
+            template.render(&values(&[("score", "2500"), ("pb_diff", "")])),
+            "2500"
+        );
+        assert_eq!(template.render(&values(&[("score", "2500")])), "2500");
+    }
+
+    #[test]
+    fn test_render_escaped_braces() {
+        let template = MarqueeTemplate::parse("{{{lamp}}}").unwrap();
+        assert_eq!(template.render(&values(&[("lamp", "HARD")])), "{HARD}");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(MarqueeTemplate::parse("{titel}").is_err());
+        assert!(MarqueeTemplate::parse("{title").is_err());
+        assert!(MarqueeTemplate::parse("title}").is_err());
+        assert!(MarqueeTemplate::parse("{?bpi}{bpi}").is_err());
+        assert!(MarqueeTemplate::parse("{?bpi}{bpi}{/rate}").is_err());
+        assert!(MarqueeTemplate::parse("{/bpi}").is_err());
+    }
+
+    #[test]
+    fn test_parse_with_extra_placeholders() {
+        assert!(MarqueeTemplate::parse("{milestone} {title}").is_err());
+        let template = MarqueeTemplate::parse_with("{milestone} {title}", &["milestone"]).unwrap();
+        assert_eq!(
+            template.render(&values(&[("milestone", "First clear"), ("title", "冥")])),
+            "First clear 冥"
+        );
+    }
+
+    #[test]
+    fn test_date_placeholders_parse() {
+        let template = MarqueeTemplate::parse("{title} ({date} {time})").unwrap();
+        let rendered = template.render(&values(&[
+            ("title", "Song"),
+            ("date", "2026-03-01"),
+            ("time", "23:59"),
+        ]));
+        assert_eq!(rendered, "Song (2026-03-01 23:59)");
+    }
+
+    #[test]
+    fn test_default_template_parses() {
+        let template = MarqueeTemplate::default();
+        let rendered = template.render(&values(&[
+            ("title", "Song"),
+            ("diff", "SPH"),
+            ("score", "1500"),
+            ("lamp", "CLEAR"),
+        ]));
+        assert_eq!(rendered, "Song [SPH] 1500 CLEAR");
+    }
+
+    #[test]
+    fn test_default_clipboard_template() {
+        let template = MarqueeTemplate::parse(DEFAULT_CLIPBOARD_TEMPLATE).unwrap();
+        let rendered = template.render(&values(&[
+            ("title", "Song"),
+            ("diff", "SPA"),
+            ("level", "12"),
+            ("lamp", "HARD"),
+            ("grade", "AA"),
+            ("score", "2500"),
+            ("rate", "80.00%"),
+            ("pb_diff", "+12"),
+            ("miss", "5"),
+        ]));
+        assert_eq!(
+            rendered,
+            "Song [SPA 12] HARD AA EX 2500 (80.00%) PB +12 / MISS 5"
+        );
+    }
+}
@@ -0,0 +1,17 @@
+//! Stream overlay output.
+//!
+//! Writes text files that streaming software (e.g. OBS text sources) can
+//! display:
+//! - `MarqueeTemplate` - placeholder template engine for the marquee text
+//! - `StreamOutput` - writes the marquee after each play and at song select, and
+//!   chart alerts (e.g. an unusual green number) while playing
+//! - `RecentPlays` - the latest plays for `recent_plays.json`
+//! - `play_counts.txt` - plays today and this week (see `PlayCounters`)
+
+mod marquee;
+mod output;
+mod recent;
+
+pub use marquee::*;
+pub use output::*;
+pub use recent::*;
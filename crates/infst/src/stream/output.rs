@@ -0,0 +1,451 @@
+//! Overlay file output for streaming software
+
+use std::borrow::Cow;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::export::{DisplayTimezone, TitleOptions};
+use crate::paths::Paths;
+use crate::play::PlayData;
+use crate::score::ScoreData;
+use crate::stats::PlayCounts;
+use crate::storage::{TextEncoding, WriteOptions, write_atomic, write_text_atomic};
+
+use super::marquee::{
+    DEFAULT_MARQUEE_IDLE_TEXT, DEFAULT_MARQUEE_TEMPLATE, MarqueeTemplate, insert_play_counts,
+    marquee_values,
+};
+use super::recent::{DEFAULT_RECENT_PLAYS, RECENT_PLAYS_FILE, RecentPlays};
+
+/// Marquee file name inside the stream directory
+pub const MARQUEE_FILE: &str = "marquee.txt";
+
+/// Alert file name inside the stream directory (empty when there is no alert)
+pub const ALERT_FILE: &str = "alert.txt";
+
+/// Play counts file name inside the stream directory
+pub const PLAY_COUNTS_TEXT_FILE: &str = "play_counts.txt";
+
+/// Result card image file name inside the stream directory
+pub const RESULT_CARD_FILE: &str = "latest.png";
+
+/// Configuration for stream overlay output
+#[derive(Debug, Clone)]
+pub struct StreamConfig {
+    /// Directory the overlay files are written to
+    pub dir: PathBuf,
+    /// Template for the marquee text after a play
+    pub marquee_template: String,
+    /// Marquee text shown at song select
+    pub idle_text: String,
+    /// Also render a result card image after each play (requires the
+    /// `render` feature; ignored with a warning without it)
+    pub result_card: bool,
+    /// Number of plays kept in `recent_plays.json` (0 disables the file)
+    pub recent_plays: usize,
+    /// Encoding of `marquee.txt` (`recent_plays.json` is always UTF-8)
+    pub encoding: TextEncoding,
+    /// Titles shown as `title` and `title2` (in-game and English by default)
+    pub titles: TitleOptions,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            dir: Paths::portable().stream(),
+            marquee_template: DEFAULT_MARQUEE_TEMPLATE.to_string(),
+            idle_text: DEFAULT_MARQUEE_IDLE_TEXT.to_string(),
+            result_card: false,
+            recent_plays: DEFAULT_RECENT_PLAYS,
+            encoding: TextEncoding::default(),
+            titles: TitleOptions::default(),
+        }
+    }
+}
+
+/// Writes overlay text files (e.g. for OBS text sources)
+#[derive(Debug, Clone)]
+pub struct StreamOutput {
+    dir: PathBuf,
+    template: MarqueeTemplate,
+    idle_text: String,
+    result_card: bool,
+    recent: RecentPlays,
+    encoding: TextEncoding,
+    titles: TitleOptions,
+    timezone: DisplayTimezone,
+    /// Plays today and this week, written to `play_counts.txt` and
+    /// available to the marquee template
+    play_counts: Option<PlayCounts>,
+    /// Marquee blanked (see [`StreamOutput::toggle_marquee`])
+    hidden: bool,
+}
+
+impl StreamOutput {
+    /// Create a stream output, validating the marquee template
+    pub fn new(config: &StreamConfig) -> Result<Self> {
+        let result_card = config.result_card && cfg!(feature = "render");
+        if config.result_card && !result_card {
+            tracing::warn!("Result card output requires the `render` feature; disabled");
+        }
+        Ok(Self {
+            dir: config.dir.clone(),
+            template: MarqueeTemplate::parse(&config.marquee_template)?,
+            idle_text: config.idle_text.clone(),
+            result_card,
+            recent: RecentPlays::new(config.recent_plays),
+            encoding: config.encoding,
+            titles: config.titles.clone(),
+            timezone: DisplayTimezone::default(),
+            play_counts: None,
+            hidden: false,
+        })
+    }
+
+    /// Show play times in `timezone` (local by default)
+    pub fn with_timezone(mut self, timezone: DisplayTimezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Set the play counts shown from the next write on
+    pub fn set_play_counts(&mut self, counts: PlayCounts) {
+        self.play_counts = Some(counts);
+    }
+
+    /// Write the marquee, recent plays, play counts (and result card, if
+    /// enabled) for a finished play
+    pub fn write_play(
+        &mut self,
+        play_data: &PlayData,
+        personal_best: Option<&ScoreData>,
+    ) -> Result<()> {
+        let play_data = &*self.with_titles(play_data);
+        let mut values = marquee_values(play_data, personal_best, self.timezone);
+        if let Some(counts) = &self.play_counts {
+            insert_play_counts(&mut values, counts);
+        }
+        self.write_marquee(&self.template.render(&values))?;
+        self.write_play_counts()?;
+
+        self.recent.push(play_data);
+        if !self.recent.is_empty() {
+            let json = serde_json::to_string_pretty(&self.recent.to_json())?;
+            write_atomic(self.dir.join(RECENT_PLAYS_FILE), json)?;
+        }
+
+        if self.result_card {
+            self.write_result_card(play_data)?;
+        }
+        Ok(())
+    }
+
+    /// `play_data` with the configured titles as the chart's title and
+    /// English title
+    fn with_titles<'a>(&self, play_data: &'a PlayData) -> Cow<'a, PlayData> {
+        if self.titles == TitleOptions::default() {
+            return Cow::Borrowed(play_data);
+        }
+        let chart = &play_data.chart;
+        let title = self
+            .titles
+            .primary_title(&chart.title, &chart.title_english);
+        let title2 = self
+            .titles
+            .secondary_title(&chart.title, &chart.title_english);
+        let mut play_data = play_data.clone();
+        play_data.chart.title = Arc::from(title);
+        if let Some(title2) = title2 {
+            play_data.chart.title_english = Arc::from(title2);
+        }
+        Cow::Owned(play_data)
+    }
+
+    #[cfg(feature = "render")]
+    fn write_result_card(&self, play_data: &PlayData) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        crate::render::render_result_card(play_data).save_png(self.dir.join(RESULT_CARD_FILE))
+    }
+
+    /// Never called: `new` disables the result card without the feature
+    #[cfg(not(feature = "render"))]
+    fn write_result_card(&self, _play_data: &PlayData) -> Result<()> {
+        Ok(())
+    }
+
+    /// Write the idle marquee text (and the play counts)
+    pub fn write_idle(&self) -> Result<()> {
+        self.write_marquee(&self.idle_text)?;
+        self.write_play_counts()
+    }
+
+    /// Blank the marquee, or show it again (with the idle text); returns
+    /// whether it is now hidden
+    pub fn toggle_marquee(&mut self) -> Result<bool> {
+        self.hidden = !self.hidden;
+        self.write_idle()?;
+        Ok(self.hidden)
+    }
+
+    /// Write an alert for the current chart (empty text clears it)
+    pub fn write_alert(&self, text: &str) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        write_text_atomic(
+            self.dir.join(ALERT_FILE),
+            text,
+            self.encoding,
+            WriteOptions::default(),
+        )?;
+        Ok(())
+    }
+
+    fn write_play_counts(&self) -> Result<()> {
+        let Some(counts) = &self.play_counts else {
+            return Ok(());
+        };
+        fs::create_dir_all(&self.dir)?;
+        write_text_atomic(
+            self.dir.join(PLAY_COUNTS_TEXT_FILE),
+            &format!(
+                "Today's plays: {}\nThis week: {}",
+                counts.today.plays, counts.this_week.plays
+            ),
+            self.encoding,
+            WriteOptions::default(),
+        )?;
+        Ok(())
+    }
+
+    fn write_marquee(&self, text: &str) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        write_text_atomic(
+            self.dir.join(MARQUEE_FILE),
+            if self.hidden { "" } else { text },
+            self.encoding,
+            WriteOptions::default(),
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::play::test_support::PlayBuilder;
+
+    #[test]
+    fn test_write_idle() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = StreamConfig {
+            dir: dir.path().to_path_buf(),
+            idle_text: "Waiting...".to_string(),
+            ..Default::default()
+        };
+        let output = StreamOutput::new(&config).unwrap();
+        output.write_idle().unwrap();
+
+        let text = fs::read_to_string(dir.path().join(MARQUEE_FILE)).unwrap();
+        assert_eq!(text, "Waiting...");
+    }
+
+    #[test]
+    fn test_write_alert() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = StreamConfig {
+            dir: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let output = StreamOutput::new(&config).unwrap();
+        let alert = || fs::read_to_string(dir.path().join(ALERT_FILE)).unwrap();
+
+        output.write_alert("Green number 340 (+50)").unwrap();
+        assert_eq!(alert(), "Green number 340 (+50)");
+        output.write_alert("").unwrap();
+        assert_eq!(alert(), "");
+    }
+
+    #[test]
+    fn test_toggle_marquee() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = StreamConfig {
+            dir: dir.path().to_path_buf(),
+            idle_text: "Waiting...".to_string(),
+            ..Default::default()
+        };
+        let mut output = StreamOutput::new(&config).unwrap();
+        let marquee = || fs::read_to_string(dir.path().join(MARQUEE_FILE)).unwrap();
+
+        assert!(output.toggle_marquee().unwrap());
+        assert_eq!(marquee(), "");
+        output.write_play(&sample_play(), None).unwrap();
+        assert_eq!(marquee(), "");
+
+        assert!(!output.toggle_marquee().unwrap());
+        assert_eq!(marquee(), "Waiting...");
+    }
+
+    #[test]
+    fn test_marquee_shift_jis() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = StreamConfig {
+            dir: dir.path().to_path_buf(),
+            idle_text: "選曲中".to_string(),
+            encoding: TextEncoding::ShiftJis,
+            ..Default::default()
+        };
+        StreamOutput::new(&config).unwrap().write_idle().unwrap();
+
+        let bytes = fs::read(dir.path().join(MARQUEE_FILE)).unwrap();
+        assert_eq!(bytes, b"\x91\x49\x8B\xC8\x92\x86");
+    }
+
+    fn sample_play() -> PlayData {
+        PlayBuilder::new().build()
+    }
+
+    #[test]
+    fn test_write_play_recent_plays() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = StreamConfig {
+            dir: dir.path().to_path_buf(),
+            recent_plays: 2,
+            ..Default::default()
+        };
+        let mut output = StreamOutput::new(&config).unwrap();
+        for _ in 0..3 {
+            output.write_play(&sample_play(), None).unwrap();
+        }
+
+        let json = fs::read_to_string(dir.path().join(RECENT_PLAYS_FILE)).unwrap();
+        let doc: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(doc["schema_version"], 1);
+        assert_eq!(doc["plays"].as_array().unwrap().len(), 2);
+        assert_eq!(doc["plays"][0]["title"], "Test Song");
+    }
+
+    #[test]
+    fn test_write_play_with_titles() {
+        use crate::export::{TitleAliases, TitleLanguage};
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut aliases = TitleAliases::new();
+        aliases.insert("冥", "Mei");
+        let config = StreamConfig {
+            dir: dir.path().to_path_buf(),
+            marquee_template: "{title} / {title2}".to_string(),
+            titles: TitleOptions {
+                primary: TitleLanguage::Romanized,
+                secondary: Some(TitleLanguage::Original),
+                aliases,
+            },
+            ..Default::default()
+        };
+        let mut play = sample_play();
+        play.chart.title = Arc::from("冥");
+        StreamOutput::new(&config)
+            .unwrap()
+            .write_play(&play, None)
+            .unwrap();
+
+        let text = fs::read_to_string(dir.path().join(MARQUEE_FILE)).unwrap();
+        assert_eq!(text, "Mei / 冥");
+        let json = fs::read_to_string(dir.path().join(RECENT_PLAYS_FILE)).unwrap();
+        let doc: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(doc["plays"][0]["title"], "Mei");
+        assert_eq!(doc["plays"][0]["title2"], "冥");
+    }
+
+    #[test]
+    fn test_write_play_counts() {
+        use crate::stats::DayCount;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = StreamConfig {
+            dir: dir.path().to_path_buf(),
+            marquee_template: "{title} ({today_plays} today)".to_string(),
+            ..Default::default()
+        };
+        let mut output = StreamOutput::new(&config).unwrap();
+        output.write_idle().unwrap();
+        assert!(!dir.path().join(PLAY_COUNTS_TEXT_FILE).exists());
+
+        output.set_play_counts(PlayCounts {
+            today: DayCount {
+                plays: 37,
+                notes_hit: 40_000,
+            },
+            this_week: DayCount {
+                plays: 120,
+                notes_hit: 130_000,
+            },
+        });
+        output.write_play(&sample_play(), None).unwrap();
+        let text = fs::read_to_string(dir.path().join(MARQUEE_FILE)).unwrap();
+        assert_eq!(text, "Test Song (37 today)");
+        let counts = fs::read_to_string(dir.path().join(PLAY_COUNTS_TEXT_FILE)).unwrap();
+        assert_eq!(counts, "Today's plays: 37\nThis week: 120");
+    }
+
+    #[test]
+    fn test_recent_plays_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = StreamConfig {
+            dir: dir.path().to_path_buf(),
+            recent_plays: 0,
+            ..Default::default()
+        };
+        let mut output = StreamOutput::new(&config).unwrap();
+        output.write_play(&sample_play(), None).unwrap();
+        assert!(!dir.path().join(RECENT_PLAYS_FILE).exists());
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn test_write_play_with_result_card() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = StreamConfig {
+            dir: dir.path().to_path_buf(),
+            marquee_template: "{title} {lamp}".to_string(),
+            result_card: true,
+            ..Default::default()
+        };
+
+        StreamOutput::new(&config)
+            .unwrap()
+            .write_play(&sample_play(), None)
+            .unwrap();
+
+        let text = fs::read_to_string(dir.path().join(MARQUEE_FILE)).unwrap();
+        assert_eq!(text, "Test Song CLEAR");
+        let png = fs::read(dir.path().join(RESULT_CARD_FILE)).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+
+    #[cfg(not(feature = "render"))]
+    #[test]
+    fn test_result_card_disabled_without_render() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = StreamConfig {
+            dir: dir.path().to_path_buf(),
+            result_card: true,
+            ..Default::default()
+        };
+
+        let mut output = StreamOutput::new(&config).unwrap();
+        assert!(!output.result_card);
+        output.write_play(&sample_play(), None).unwrap();
+        assert!(dir.path().join(MARQUEE_FILE).exists());
+        assert!(!dir.path().join(RESULT_CARD_FILE).exists());
+    }
+
+    #[test]
+    fn test_invalid_template_rejected() {
+        let config = StreamConfig {
+            marquee_template: "{nope}".to_string(),
+            ..Default::default()
+        };
+        assert!(StreamOutput::new(&config).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! Rolling list of the latest plays for overlays
+//!
+//! `recent_plays.json` has the following shape (schema version 1, see
+//! [`RECENT_PLAYS_JSON_SCHEMA`] for the full JSON Schema):
+//!
+//! ```json
+//! {
+//!   "schema_version": 1,
+//!   "plays": [
+//!     {
+//!       "timestamp": "2026-03-01T14:59:30+00:00",
+//!       "song_id": 1000,
+//!       "title": "...",
+//!       "title2": "...",
+//!       "artist": "...",
+//!       "difficulty": "SPA",
+//!       "level": 12,
+//!       "notes": 1000,
+//!       "ex_score": 1900,
+//!       "score_rate": 0.95,
+//!       "grade": "AAA",
+//!       "lamp": "FC",
+//!       "miss_count": 0,
+//!       "judge": {
+//!         "pgreat": 900, "great": 100, "good": 0, "bad": 0, "poor": 0,
+//!         "fast": 30, "slow": 20, "combo_break": 0
+//!       },
+//!       "premature_end": false,
+//!       "failed_at_percent": null,
+//!       "bpi": null
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! `plays` is newest first. Nullable fields are `miss_count` (not valid for
+//! assisted or premature plays), `failed_at_percent` and `bpi`. The schema
+//! version is bumped whenever a field is removed or changes meaning; new
+//! fields may be added within a version.
+
+use std::collections::VecDeque;
+
+use serde_json::{Value as JsonValue, json};
+
+use crate::play::PlayData;
+
+/// Recent plays file name inside the stream directory
+pub const RECENT_PLAYS_FILE: &str = "recent_plays.json";
+
+/// Version of the `recent_plays.json` format
+pub const RECENT_PLAYS_SCHEMA_VERSION: u32 = 1;
+
+/// Default number of plays kept in `recent_plays.json`
+pub const DEFAULT_RECENT_PLAYS: usize = 10;
+
+/// JSON Schema (draft 2020-12) describing `recent_plays.json`
+pub const RECENT_PLAYS_JSON_SCHEMA: &str = include_str!("recent_plays.schema.json");
+
+/// The last `limit` plays, newest first
+#[derive(Debug, Clone)]
+pub struct RecentPlays {
+    limit: usize,
+    plays: VecDeque<JsonValue>,
+}
+
+impl RecentPlays {
+    /// An empty list keeping at most `limit` plays
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            plays: VecDeque::with_capacity(limit),
+        }
+    }
+
+    /// Add a play, dropping the oldest one past the limit
+    pub fn push(&mut self, play_data: &PlayData) {
+        if self.limit == 0 {
+            return;
+        }
+        self.plays.push_front(recent_play_entry(play_data));
+        self.plays.truncate(self.limit);
+    }
+
+    /// Number of plays held
+    pub fn len(&self) -> usize {
+        self.plays.len()
+    }
+
+    /// Whether no play has been added yet
+    pub fn is_empty(&self) -> bool {
+        self.plays.is_empty()
+    }
+
+    /// The `recent_plays.json` document
+    pub fn to_json(&self) -> JsonValue {
+        json!({
+            "schema_version": RECENT_PLAYS_SCHEMA_VERSION,
+            "plays": self.plays,
+        })
+    }
+}
+
+/// One entry of `plays` in `recent_plays.json`
+pub fn recent_play_entry(play_data: &PlayData) -> JsonValue {
+    let chart = &play_data.chart;
+    let judge = &play_data.judge;
+    json!({
+        "timestamp": play_data.timestamp.to_rfc3339(),
+        "song_id": chart.song_id,
+        "title": chart.title,
+        "title2": chart.title_english,
+        "artist": chart.artist,
+        "difficulty": chart.difficulty.short_name(),
+        "level": chart.level,
+        "notes": chart.total_notes,
+        "ex_score": play_data.ex_score,
+        "score_rate": play_data.score_rate(),
+        "grade": play_data.grade.short_name(),
+        "lamp": play_data.lamp.short_name(),
+        "miss_count": play_data.miss_count_valid().then(|| play_data.miss_count()),
+        "judge": {
+            "pgreat": judge.pgreat,
+            "great": judge.great,
+            "good": judge.good,
+            "bad": judge.bad,
+            "poor": judge.poor,
+            "fast": judge.fast,
+            "slow": judge.slow,
+            "combo_break": judge.combo_break,
+        },
+        "premature_end": judge.premature_end,
+        "failed_at_percent": play_data.failed_at_percent,
+        "bpi": play_data.bpi,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::play::test_support::PlayBuilder;
+    use crate::score::Grade;
+
+    fn play(ex_score: u32) -> PlayData {
+        PlayBuilder::new()
+            .ex_score(ex_score)
+            .grade(Grade::Aa)
+            .build()
+    }
+
+    #[test]
+    fn test_keeps_latest_plays_newest_first() {
+        let mut recent = RecentPlays::new(2);
+        for score in [1000, 1100, 1200] {
+            recent.push(&play(score));
+        }
+        assert_eq!(recent.len(), 2);
+
+        let doc = recent.to_json();
+        assert_eq!(doc["schema_version"], RECENT_PLAYS_SCHEMA_VERSION);
+        let scores: Vec<u64> = doc["plays"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["ex_score"].as_u64().unwrap())
+            .collect();
+        assert_eq!(scores, vec![1200, 1100]);
+    }
+
+    #[test]
+    fn test_zero_limit_keeps_nothing() {
+        let mut recent = RecentPlays::new(0);
+        recent.push(&play(1000));
+        assert!(recent.is_empty());
+    }
+
+    #[test]
+    fn test_entry_matches_schema() {
+        let schema: JsonValue = serde_json::from_str(RECENT_PLAYS_JSON_SCHEMA).unwrap();
+        assert_eq!(
+            schema["properties"]["schema_version"]["const"],
+            RECENT_PLAYS_SCHEMA_VERSION
+        );
+
+        let keys = |value: &JsonValue| -> Vec<String> {
+            let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+            keys.sort();
+            keys
+        };
+        let required = |value: &JsonValue| -> Vec<String> {
+            let mut keys: Vec<String> = value
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|key| key.as_str().unwrap().to_string())
+                .collect();
+            keys.sort();
+            keys
+        };
+
+        let entry = recent_play_entry(&play(1600));
+        let play_schema = &schema["$defs"]["play"];
+        assert_eq!(keys(&entry), required(&play_schema["required"]));
+        assert_eq!(keys(&entry), keys(&play_schema["properties"]));
+        let judge_schema = &play_schema["properties"]["judge"];
+        assert_eq!(keys(&entry["judge"]), required(&judge_schema["required"]));
+    }
+}
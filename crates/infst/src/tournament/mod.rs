@@ -0,0 +1,27 @@
+//! Local tournament scoring (Arena / BPL style).
+//!
+//! A tournament file lists a chart pool and the participants. The local
+//! player's scores come from the plays of the running session; other
+//! participants are imported from rival score files:
+//!
+//! ```json
+//! {
+//!   "name": "Weekly Arena",
+//!   "pool": [{ "song_id": 25001, "difficulty": "SPA" }],
+//!   "participants": [
+//!     { "name": "ME", "local": true },
+//!     { "name": "RIVAL", "scores": "rival.json" }
+//!   ]
+//! }
+//! ```
+//!
+//! Every pair of participants plays head-to-head on each pool chart: the
+//! higher EX score wins the chart's points.
+//! - `Tournament` - pool, participants and point rules
+//! - `Standings` - head-to-head results and the table written for overlays
+
+mod setup;
+mod standings;
+
+pub use setup::*;
+pub use standings::*;
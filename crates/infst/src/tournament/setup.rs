@@ -0,0 +1,281 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::chart::{ChartId, Difficulty};
+use crate::error::{Error, Result};
+use crate::play::PlayData;
+use crate::stats::bpi::deserialize_short_difficulty;
+
+/// Points awarded per chart in a head-to-head
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct PointRules {
+    pub win: u32,
+    pub draw: u32,
+}
+
+impl Default for PointRules {
+    fn default() -> Self {
+        Self { win: 2, draw: 1 }
+    }
+}
+
+/// A tournament participant and their best EX score per pool chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct Participant {
+    pub name: String,
+    /// Scores are taken from the running session's plays
+    pub local: bool,
+    pub scores: HashMap<ChartId, u32>,
+}
+
+impl Participant {
+    pub fn ex_score(&self, chart: &ChartId) -> Option<u32> {
+        self.scores.get(chart).copied()
+    }
+}
+
+/// A chart in a tournament file or rival score file
+#[derive(Debug, Deserialize)]
+struct ChartEntry {
+    song_id: u32,
+    #[serde(deserialize_with = "deserialize_short_difficulty")]
+    difficulty: Difficulty,
+}
+
+impl ChartEntry {
+    fn chart(&self) -> ChartId {
+        ChartId::new(self.song_id, self.difficulty)
+    }
+}
+
+/// One line of a rival score file
+#[derive(Debug, Deserialize)]
+struct RivalScore {
+    #[serde(flatten)]
+    chart: ChartEntry,
+    ex_score: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ParticipantEntry {
+    name: String,
+    #[serde(default)]
+    local: bool,
+    /// Rival score file, relative to the tournament file
+    #[serde(default)]
+    scores: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TournamentFile {
+    name: String,
+    pool: Vec<ChartEntry>,
+    participants: Vec<ParticipantEntry>,
+    #[serde(default)]
+    points: PointRules,
+}
+
+/// A chart pool, its participants and point rules
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    pub name: String,
+    pub pool: Vec<ChartId>,
+    pub participants: Vec<Participant>,
+    pub points: PointRules,
+    /// Where the standings JSON is written (set by [`load`](Self::load))
+    pub standings_path: Option<PathBuf>,
+}
+
+impl Tournament {
+    /// Parse a tournament file's content
+    ///
+    /// Rival score file paths are resolved against `base_dir`.
+    pub fn from_json(content: &str, base_dir: &Path) -> Result<Self> {
+        let file: TournamentFile = serde_json::from_str(content)?;
+        if file.pool.is_empty() {
+            return Err(Error::parse("tournament", "chart pool is empty"));
+        }
+        if file.participants.len() < 2 {
+            return Err(Error::parse("tournament", "need at least two participants"));
+        }
+        let mut names = HashSet::new();
+        if let Some(entry) = file.participants.iter().find(|p| !names.insert(&p.name)) {
+            return Err(Error::parse(
+                "tournament",
+                format!("duplicate participant: {}", entry.name),
+            ));
+        }
+
+        let pool: Vec<ChartId> = file.pool.iter().map(ChartEntry::chart).collect();
+        let participants = file
+            .participants
+            .into_iter()
+            .map(|entry| {
+                let scores = match &entry.scores {
+                    Some(path) => load_rival_scores(&base_dir.join(path), &pool)?,
+                    None => HashMap::new(),
+                };
+                Ok(Participant {
+                    name: entry.name,
+                    local: entry.local,
+                    scores,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            name: file.name,
+            pool,
+            participants,
+            points: file.points,
+            standings_path: None,
+        })
+    }
+
+    /// Load a tournament file; standings go next to it (`arena.json` → `arena.standings.json`)
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| Error::storage(path, e))?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        let mut tournament = Self::from_json(&content, base_dir)?;
+        tournament.standings_path = Some(path.with_extension("standings.json"));
+        Ok(tournament)
+    }
+
+    pub fn in_pool(&self, chart: &ChartId) -> bool {
+        self.pool.contains(chart)
+    }
+
+    /// Record a play for the local participants
+    ///
+    /// Keeps the best EX score per chart; returns `true` if a score improved.
+    /// Premature plays are not counted.
+    pub fn record_play(&mut self, play_data: &PlayData) -> bool {
+        let chart = play_data.chart.id();
+        if !play_data.data_available || play_data.is_premature() || !self.in_pool(&chart) {
+            return false;
+        }
+        let mut improved = false;
+        for participant in self.participants.iter_mut().filter(|p| p.local) {
+            match participant.scores.get_mut(&chart) {
+                Some(best) if *best >= play_data.ex_score => {}
+                Some(best) => {
+                    *best = play_data.ex_score;
+                    improved = true;
+                }
+                None => {
+                    participant.scores.insert(chart, play_data.ex_score);
+                    improved = true;
+                }
+            }
+        }
+        improved
+    }
+}
+
+/// Load a rival score file, keeping the best score per pool chart
+fn load_rival_scores(path: &Path, pool: &[ChartId]) -> Result<HashMap<ChartId, u32>> {
+    let content = fs::read_to_string(path).map_err(|e| Error::storage(path, e))?;
+    let list: Vec<RivalScore> = serde_json::from_str(&content)?;
+    let mut scores: HashMap<ChartId, u32> = HashMap::new();
+    for entry in list {
+        let chart = entry.chart.chart();
+        if pool.contains(&chart) {
+            let best = scores.entry(chart).or_default();
+            *best = (*best).max(entry.ex_score);
+        }
+    }
+    Ok(scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::play::test_support::PlayBuilder;
+    use crate::score::Grade;
+    use tempfile::TempDir;
+
+    const TOURNAMENT: &str = r#"{
+        "name": "Weekly Arena",
+        "pool": [
+            { "song_id": 25001, "difficulty": "SPA" },
+            { "song_id": 25002, "difficulty": "SPH" }
+        ],
+        "participants": [
+            { "name": "ME", "local": true },
+            { "name": "RIVAL", "scores": "rival.json" }
+        ]
+    }"#;
+
+    fn play(song_id: u32, difficulty: Difficulty, ex_score: u32) -> PlayData {
+        PlayBuilder::new()
+            .song_id(song_id)
+            .title("Song")
+            .difficulty(difficulty)
+            .total_notes(1500)
+            .ex_score(ex_score)
+            .grade(Grade::Aa)
+            .build()
+    }
+
+    #[test]
+    fn test_load_with_rival_file() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("rival.json"),
+            r#"[
+                { "song_id": 25001, "difficulty": "SPA", "ex_score": 2500 },
+                { "song_id": 25001, "difficulty": "SPA", "ex_score": 2600 },
+                { "song_id": 9999, "difficulty": "SPA", "ex_score": 3000 }
+            ]"#,
+        )
+        .unwrap();
+        let path = temp.path().join("arena.json");
+        fs::write(&path, TOURNAMENT).unwrap();
+
+        let tournament = Tournament::load(&path).unwrap();
+        assert_eq!(tournament.name, "Weekly Arena");
+        assert_eq!(tournament.pool.len(), 2);
+        assert_eq!(tournament.points, PointRules::default());
+        assert_eq!(
+            tournament.standings_path,
+            Some(temp.path().join("arena.standings.json"))
+        );
+        let rival = &tournament.participants[1];
+        assert_eq!(rival.scores.len(), 1);
+        assert_eq!(rival.ex_score(&tournament.pool[0]), Some(2600));
+    }
+
+    #[test]
+    fn test_invalid_tournaments() {
+        let base = Path::new(".");
+        let no_pool =
+            r#"{ "name": "x", "pool": [], "participants": [{ "name": "A" }, { "name": "B" }] }"#;
+        assert!(Tournament::from_json(no_pool, base).is_err());
+        let one = r#"{ "name": "x", "pool": [{ "song_id": 1, "difficulty": "SPA" }], "participants": [{ "name": "A" }] }"#;
+        assert!(Tournament::from_json(one, base).is_err());
+        let duplicate = r#"{ "name": "x", "pool": [{ "song_id": 1, "difficulty": "SPA" }], "participants": [{ "name": "A" }, { "name": "A" }] }"#;
+        assert!(Tournament::from_json(duplicate, base).is_err());
+        // Rival file missing
+        assert!(Tournament::from_json(TOURNAMENT, Path::new("/nonexistent")).is_err());
+    }
+
+    #[test]
+    fn test_record_play_keeps_best_pool_score() {
+        let content = TOURNAMENT.replace(r#", "scores": "rival.json""#, "");
+        let mut tournament = Tournament::from_json(&content, Path::new(".")).unwrap();
+        let chart = tournament.pool[0];
+
+        assert!(tournament.record_play(&play(25001, Difficulty::SpA, 2400)));
+        assert!(!tournament.record_play(&play(25001, Difficulty::SpA, 2300)));
+        assert!(tournament.record_play(&play(25001, Difficulty::SpA, 2700)));
+        // Not in the pool (other difficulty)
+        assert!(!tournament.record_play(&play(25001, Difficulty::SpH, 2900)));
+
+        assert_eq!(tournament.participants[0].ex_score(&chart), Some(2700));
+        assert!(tournament.participants[1].scores.is_empty());
+    }
+}
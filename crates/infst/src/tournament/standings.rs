@@ -0,0 +1,248 @@
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::chart::ChartId;
+use crate::error::Result;
+use crate::storage::write_atomic;
+
+use super::Tournament;
+
+/// Outcome of one head-to-head on one chart
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HeadToHead {
+    pub chart: ChartId,
+    pub a: String,
+    pub b: String,
+    pub a_ex: Option<u32>,
+    pub b_ex: Option<u32>,
+    /// `None` for a draw
+    pub winner: Option<String>,
+}
+
+/// A participant's row in the standings table
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Standing {
+    pub name: String,
+    pub points: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// EX score summed over the pool charts played
+    pub total_ex: u32,
+    pub charts_played: usize,
+}
+
+/// Standings table (best first) with every head-to-head result
+#[derive(Debug, Clone, Serialize)]
+pub struct Standings {
+    pub tournament: String,
+    pub updated_at: DateTime<Utc>,
+    pub standings: Vec<Standing>,
+    pub matches: Vec<HeadToHead>,
+}
+
+impl Tournament {
+    /// Compute head-to-head results and standings from the current scores
+    ///
+    /// A chart neither side has played is skipped; a missing score loses
+    /// to any score. `now` is recorded as the update time.
+    pub fn standings(&self, now: DateTime<Utc>) -> Standings {
+        let mut table: Vec<Standing> = self
+            .participants
+            .iter()
+            .map(|p| Standing {
+                name: p.name.clone(),
+                total_ex: self.pool.iter().filter_map(|c| p.ex_score(c)).sum(),
+                charts_played: self
+                    .pool
+                    .iter()
+                    .filter(|c| p.scores.contains_key(c))
+                    .count(),
+                ..Default::default()
+            })
+            .collect();
+
+        let mut matches = Vec::new();
+        for chart in &self.pool {
+            for i in 0..self.participants.len() {
+                for j in i + 1..self.participants.len() {
+                    let (a, b) = (&self.participants[i], &self.participants[j]);
+                    let (a_ex, b_ex) = (a.ex_score(chart), b.ex_score(chart));
+                    if a_ex.is_none() && b_ex.is_none() {
+                        continue;
+                    }
+                    let winner = match a_ex.cmp(&b_ex) {
+                        std::cmp::Ordering::Greater => Some(i),
+                        std::cmp::Ordering::Less => Some(j),
+                        std::cmp::Ordering::Equal => None,
+                    };
+                    match winner {
+                        Some(w) => {
+                            let l = if w == i { j } else { i };
+                            table[w].points += self.points.win;
+                            table[w].wins += 1;
+                            table[l].losses += 1;
+                        }
+                        None => {
+                            for k in [i, j] {
+                                table[k].points += self.points.draw;
+                                table[k].draws += 1;
+                            }
+                        }
+                    }
+                    matches.push(HeadToHead {
+                        chart: *chart,
+                        a: a.name.clone(),
+                        b: b.name.clone(),
+                        a_ex,
+                        b_ex,
+                        winner: winner.map(|w| self.participants[w].name.clone()),
+                    });
+                }
+            }
+        }
+
+        table.sort_by(|x, y| {
+            y.points
+                .cmp(&x.points)
+                .then(y.total_ex.cmp(&x.total_ex))
+                .then(x.name.cmp(&y.name))
+        });
+        Standings {
+            tournament: self.name.clone(),
+            updated_at: now,
+            standings: table,
+            matches,
+        }
+    }
+}
+
+impl Standings {
+    /// Write the standings as pretty JSON (read by stream overlays)
+    pub fn write_json<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        write_atomic(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Text table, one participant per line (e.g. `1. ME  5 pts  2-1-0  EX 5600`)
+    pub fn format_table(&self) -> Vec<String> {
+        self.standings
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                format!(
+                    "{}. {}  {} pts  {}-{}-{}  EX {}",
+                    i + 1,
+                    s.name,
+                    s.points,
+                    s.wins,
+                    s.draws,
+                    s.losses,
+                    s.total_ex
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::Difficulty;
+    use crate::tournament::{Participant, PointRules};
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn chart(song_id: u32) -> ChartId {
+        ChartId::new(song_id, Difficulty::SpA)
+    }
+
+    fn participant(name: &str, scores: &[(u32, u32)]) -> Participant {
+        Participant {
+            name: name.to_string(),
+            local: false,
+            scores: scores
+                .iter()
+                .map(|&(song_id, ex)| (chart(song_id), ex))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn test_standings() {
+        let tournament = Tournament {
+            name: "Arena".to_string(),
+            pool: vec![chart(1), chart(2), chart(3)],
+            participants: vec![
+                participant("A", &[(1, 2000), (2, 1800)]),
+                participant("B", &[(1, 1900), (2, 1800), (3, 1500)]),
+                participant("C", &[]),
+            ],
+            points: PointRules::default(),
+            standings_path: None,
+        };
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 20, 0, 0).unwrap();
+        let standings = tournament.standings(now);
+        assert_eq!(standings.updated_at, now);
+
+        // Chart 1: A beats B, both beat C; chart 2: A-B draw; chart 3: B beats A and C
+        // (A vs C on chart 3 is skipped: neither played it)
+        assert_eq!(standings.matches.len(), 8);
+        let table = &standings.standings;
+        assert_eq!(table[0].name, "B");
+        assert_eq!(
+            (
+                table[0].points,
+                table[0].wins,
+                table[0].draws,
+                table[0].losses
+            ),
+            (9, 4, 1, 1)
+        );
+        assert_eq!(table[0].total_ex, 5200);
+        assert_eq!(table[1].name, "A");
+        assert_eq!(
+            (
+                table[1].points,
+                table[1].wins,
+                table[1].draws,
+                table[1].losses
+            ),
+            (7, 3, 1, 1)
+        );
+        assert_eq!(table[2].name, "C");
+        assert_eq!(table[2].losses, 5);
+
+        let draw = &standings.matches[3];
+        assert_eq!((draw.a.as_str(), draw.b.as_str()), ("A", "B"));
+        assert_eq!(draw.winner, None);
+        assert_eq!(standings.format_table()[0], "1. B  9 pts  4-1-1  EX 5200");
+    }
+
+    #[test]
+    fn test_write_json() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let tournament = Tournament {
+            name: "Arena".to_string(),
+            pool: vec![chart(1)],
+            participants: vec![participant("A", &[(1, 10)]), participant("B", &[])],
+            points: PointRules { win: 3, draw: 1 },
+            standings_path: None,
+        };
+        let path = temp.path().join("standings.json");
+        tournament
+            .standings(Utc.with_ymd_and_hms(2025, 6, 1, 20, 0, 0).unwrap())
+            .write_json(&path)
+            .unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["tournament"], "Arena");
+        assert_eq!(json["standings"][0]["name"], "A");
+        assert_eq!(json["standings"][0]["points"], 3);
+        assert_eq!(json["matches"][0]["winner"], "A");
+        assert_eq!(json["matches"][0]["chart"]["song_id"], 1);
+    }
+}
@@ -0,0 +1,127 @@
+//! Hex address parsing and formatting
+//!
+//! Addresses are written as `0x` followed by uppercase hex digits (logs,
+//! CLI output, offset files) and accepted with or without the prefix.
+//! Offsets between addresses are signed and carry an explicit `+`/`-`.
+
+use crate::error::{Error, Result};
+
+/// Parse a hex address, with or without a `0x`/`0X` prefix
+///
+/// # Examples
+///
+/// ```
+/// use infst::util::hex::parse_address;
+///
+/// assert_eq!(parse_address("0x1000").unwrap(), 0x1000);
+/// assert_eq!(parse_address("1000").unwrap(), 0x1000);
+/// assert_eq!(parse_address("0X1000").unwrap(), 0x1000);
+/// ```
+pub fn parse_address(s: &str) -> Result<u64> {
+    let s = s.trim();
+    let digits = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    u64::from_str_radix(digits, 16)
+        .map_err(|e| Error::parse("hex address", format!("'{}': {}", s, e)))
+}
+
+/// Parse a signed hex offset (`+0x10`, `-10`, `0x10`)
+pub fn parse_offset(s: &str) -> Result<i64> {
+    let s = s.trim();
+    let (negative, magnitude) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let value = parse_address(magnitude)
+        .ok()
+        .and_then(|value| i64::try_from(value).ok())
+        .ok_or_else(|| Error::parse("hex offset", format!("'{}'", s)))?;
+    Ok(if negative { -value } else { value })
+}
+
+/// Format an address as `0x` + uppercase hex
+///
+/// # Examples
+///
+/// ```
+/// use infst::util::hex::format_address;
+///
+/// assert_eq!(format_address(0x1000), "0x1000");
+/// ```
+pub fn format_address(address: u64) -> String {
+    format!("0x{:X}", address)
+}
+
+/// Format an address zero-padded to `width` hex digits
+pub fn format_address_padded(address: u64, width: usize) -> String {
+    format!("0x{:0width$X}", address, width = width)
+}
+
+/// Format a signed offset as `+0x10` / `-0x10`
+pub fn format_offset(offset: i64) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    format!("{}0x{:X}", sign, offset.unsigned_abs())
+}
+
+/// Signed distance from `from` to `to`
+pub fn offset_between(from: u64, to: u64) -> i64 {
+    to.wrapping_sub(from) as i64
+}
+
+/// `address` moved by `offset`
+pub fn apply_offset(address: u64, offset: i64) -> u64 {
+    address.wrapping_add_signed(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(parse_address("0x1000").unwrap(), 0x1000);
+        assert_eq!(parse_address("0X1000").unwrap(), 0x1000);
+        assert_eq!(parse_address("1000").unwrap(), 0x1000);
+        assert_eq!(parse_address("DEADBEEF").unwrap(), 0xDEADBEEF);
+        assert_eq!(parse_address(" 0x1431B08A0 ").unwrap(), 0x1431B08A0);
+    }
+
+    #[test]
+    fn test_parse_address_invalid() {
+        assert!(parse_address("GHIJK").is_err());
+        assert!(parse_address("0xZZZ").is_err());
+        assert!(parse_address("0x0x10").is_err());
+        assert!(parse_address("").is_err());
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("+0x10").unwrap(), 0x10);
+        assert_eq!(parse_offset("-10").unwrap(), -0x10);
+        assert_eq!(parse_offset("0x7E0").unwrap(), 0x7E0);
+        assert!(parse_offset("+").is_err());
+        assert!(parse_offset("0xFFFFFFFFFFFFFFFF").is_err());
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format_address(0), "0x0");
+        assert_eq!(format_address(0xDEADBEEF), "0xDEADBEEF");
+        assert_eq!(format_address_padded(0x1000, 8), "0x00001000");
+        assert_eq!(format_address_padded(0x123456789, 4), "0x123456789");
+        assert_eq!(format_offset(0x10), "+0x10");
+        assert_eq!(format_offset(-0x10), "-0x10");
+        assert_eq!(format_offset(0), "+0x0");
+    }
+
+    #[test]
+    fn test_offset_arithmetic() {
+        assert_eq!(offset_between(0x1000, 0x1010), 0x10);
+        assert_eq!(offset_between(0x1010, 0x1000), -0x10);
+        assert_eq!(apply_offset(0x1000, -0x10), 0xFF0);
+        let (from, to) = (0x1_4000_0000, 0x1_3180_0000);
+        assert_eq!(apply_offset(from, offset_between(from, to)), to);
+    }
+}
@@ -0,0 +1,3 @@
+//! Small helpers shared by the library, the CLI and embedders.
+
+pub mod hex;
@@ -0,0 +1,218 @@
+//! Scripted runs of the interactive offset search against mock memory
+//!
+//! Each image holds the patterns the interactive search looks for; the
+//! play settings change between the two settings prompts like they do when
+//! the user changes options in the game.
+
+use std::cell::Cell;
+
+use infst::error::Result;
+use infst::offset::{
+    InteractiveSearchResult, JudgeInput, OffsetSearcher, OffsetsCollection, ScriptedPrompter,
+};
+use infst::play::{PlayType, Settings};
+use infst::process::{MockMemoryBuilder, MockMemoryReader, ReadMemory};
+
+const BASE: u64 = 0x140000000;
+const IMAGE_SIZE: usize = 0x400000;
+
+const SONG_LIST: usize = 0x10000;
+const UNLOCK_DATA: usize = 0x20000;
+const DATA_MAP: usize = 0x30000;
+const JUDGE_DATA: usize = 0x100000;
+const PLAY_DATA: usize = 0x200000;
+const CURRENT_SONG: usize = 0x200100;
+const PLAY_SETTINGS: usize = 0x300000;
+
+/// Index of the ENTER prompt after which the second settings are set
+const SECOND_SETTINGS_PROMPT: usize = 3;
+
+fn judge() -> JudgeInput {
+    JudgeInput {
+        pgreat: 800,
+        great: 150,
+        good: 10,
+        bad: 2,
+        poor: 5,
+        combo_break: 4,
+        fast: 60,
+        slow: 40,
+    }
+}
+
+fn write_i32s(mut builder: MockMemoryBuilder, offset: usize, values: &[i32]) -> MockMemoryBuilder {
+    for (i, &value) in values.iter().enumerate() {
+        builder = builder.write_i32(offset + i * 4, value);
+    }
+    builder
+}
+
+/// Memory after the given play, with the play settings `[style, gauge,
+/// assist, flip, range]` at `settings` (relative to the image start)
+fn image(play_type: PlayType, settings: &[(usize, [i32; 5])]) -> MockMemoryReader {
+    let j = judge();
+    let judge_values = [
+        j.pgreat as i32,
+        j.great as i32,
+        j.good as i32,
+        j.bad as i32,
+        j.poor as i32,
+    ];
+    let mut judge_row = [0; 16];
+    match play_type {
+        PlayType::P1 => {
+            judge_row[..5].copy_from_slice(&judge_values);
+            judge_row[10] = j.combo_break as i32;
+            judge_row[12] = j.fast as i32;
+            judge_row[14] = j.slow as i32;
+        }
+        _ => {
+            judge_row[5..10].copy_from_slice(&judge_values);
+            judge_row[11] = j.combo_break as i32;
+            judge_row[13] = j.fast as i32;
+            judge_row[15] = j.slow as i32;
+        }
+    }
+    let ex_score = (j.pgreat * 2 + j.great) as i32;
+
+    // New-version text table: title entry plus metadata (song_id, folder)
+    let mut builder = MockMemoryBuilder::new()
+        .base(BASE)
+        .with_size(IMAGE_SIZE)
+        .write_shift_jis(SONG_LIST, "5.1.1.")
+        .write_i32(SONG_LIST + 0x7E0, 1001)
+        .write_i32(SONG_LIST + 0x7E4, 1);
+    builder = write_i32s(builder, UNLOCK_DATA, &[1000, 1, 462]);
+    builder = write_i32s(builder, DATA_MAP + 24, &[0x7FFF, 0]);
+    builder = write_i32s(builder, JUDGE_DATA, &judge_row);
+    builder = write_i32s(builder, PLAY_DATA, &[25094, 3, ex_score]);
+    builder = write_i32s(builder, CURRENT_SONG, &[25094, 3]);
+    for (offset, values) in settings {
+        builder = write_i32s(builder, *offset, values);
+    }
+    builder.build()
+}
+
+/// Reader that switches to the next image when told to
+struct SwitchingReader {
+    images: Vec<MockMemoryReader>,
+    current: Cell<usize>,
+}
+
+impl SwitchingReader {
+    fn new(images: Vec<MockMemoryReader>) -> Self {
+        Self {
+            images,
+            current: Cell::new(0),
+        }
+    }
+
+    fn advance(&self) {
+        self.current.set(self.current.get() + 1);
+    }
+}
+
+impl ReadMemory for SwitchingReader {
+    fn read_bytes(&self, address: u64, size: usize) -> Result<Vec<u8>> {
+        self.images[self.current.get()].read_bytes(address, size)
+    }
+
+    fn base_address(&self) -> u64 {
+        BASE
+    }
+}
+
+/// Run the interactive search on a play of `play_type`, switching to the
+/// second settings at the second settings prompt
+fn run(play_type: PlayType) -> (InteractiveSearchResult, Vec<String>) {
+    let settings_at = match play_type {
+        PlayType::P1 => PLAY_SETTINGS,
+        _ => PLAY_SETTINGS + Settings::P2_OFFSET as usize,
+    };
+    let reader = SwitchingReader::new(vec![
+        image(play_type, &[(settings_at, [1, 4, 0, 0, 1])]),
+        image(play_type, &[(settings_at, [4, 2, 1, 0, 2])]),
+    ]);
+    let prompter = ScriptedPrompter::with_judge(&judge()).on_continue(|index| {
+        if index == SECOND_SETTINGS_PROMPT {
+            reader.advance();
+        }
+    });
+
+    let mut searcher = OffsetSearcher::new(&reader);
+    let result = searcher
+        .interactive_search(
+            &prompter,
+            &OffsetsCollection::default(),
+            "P2D:J:B:A:2026012800",
+        )
+        .unwrap();
+
+    assert_eq!(prompter.continues(), 4);
+    assert_eq!(prompter.remaining_numbers(), 0);
+    assert!(prompter.warnings().is_empty(), "{:?}", prompter.warnings());
+    assert_eq!(
+        prompter.messages().last().map(String::as_str),
+        Some("Offset search complete!")
+    );
+    (result, prompter.messages())
+}
+
+fn assert_offsets(offsets: &OffsetsCollection) {
+    let at = |offset: usize| BASE + offset as u64;
+    assert_eq!(offsets.version, "P2D:J:B:A:2026012800");
+    assert_eq!(offsets.song_list, at(SONG_LIST));
+    assert_eq!(offsets.unlock_data, at(UNLOCK_DATA));
+    assert_eq!(offsets.data_map, at(DATA_MAP));
+    assert_eq!(offsets.judge_data, at(JUDGE_DATA));
+    assert_eq!(offsets.play_data, at(PLAY_DATA));
+    // The first CurrentSong match is PlayData itself and is skipped
+    assert_eq!(offsets.current_song, at(CURRENT_SONG));
+    assert_eq!(offsets.play_settings, at(PLAY_SETTINGS));
+}
+
+#[test]
+fn test_interactive_search_p1() {
+    let (result, messages) = run(PlayType::P1);
+    assert_eq!(result.play_type, PlayType::P1);
+    assert_offsets(&result.offsets);
+    assert!(
+        messages.iter().any(|m| m.ends_with("(1P)")),
+        "{:?}",
+        messages
+    );
+}
+
+#[test]
+fn test_interactive_search_p2() {
+    let (result, messages) = run(PlayType::P2);
+    assert_eq!(result.play_type, PlayType::P2);
+    assert_offsets(&result.offsets);
+    assert!(
+        messages.iter().any(|m| m.ends_with("(2P)")),
+        "{:?}",
+        messages
+    );
+}
+
+#[test]
+fn test_interactive_search_warns_on_settings_mismatch() {
+    // Both settings in one image at different addresses: the options were
+    // never changed, so the second search finds an unrelated match
+    let reader = image(
+        PlayType::P1,
+        &[
+            (PLAY_SETTINGS, [1, 4, 0, 0, 1]),
+            (PLAY_SETTINGS + 0x1000, [4, 2, 1, 0, 2]),
+        ],
+    );
+    let prompter = ScriptedPrompter::with_judge(&judge());
+
+    let mut searcher = OffsetSearcher::new(&reader);
+    let result = searcher
+        .interactive_search(&prompter, &OffsetsCollection::default(), "test")
+        .unwrap();
+
+    assert_eq!(prompter.warnings().len(), 1);
+    assert_eq!(result.offsets.play_settings, BASE + PLAY_SETTINGS as u64);
+}
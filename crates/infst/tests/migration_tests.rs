@@ -0,0 +1,150 @@
+//! Migration tests with files written by earlier releases.
+//!
+//! `fixtures/v1` holds files from before format version markers existed:
+//! session TSVs without the derived columns, session JSON as a bare array
+//! and an unmarked tracker TSV.
+
+#[cfg(feature = "session")]
+use std::collections::HashMap;
+
+#[cfg(feature = "session")]
+use infst::chart::SongInfo;
+#[cfg(feature = "session")]
+use infst::parse_session_tsv;
+use infst::storage::FileFormat;
+use infst::storage::migrate::{detect_version, migrate};
+use infst::{SignatureStatus, SigningKey, Tracker, diff_tracker_tsv, sign_export, verify_export};
+
+const SESSION_TSV_V1: &str = include_str!("fixtures/v1/Session_2025_01_01_20_00_00.tsv");
+const SESSION_JSON_V1: &str = include_str!("fixtures/v1/Session_2025_01_01_20_00_00.json");
+const TRACKER_TSV_V1: &str = include_str!("fixtures/v1/tracker.tsv");
+
+#[test]
+fn test_v1_fixtures_are_detected() {
+    assert_eq!(
+        detect_version(FileFormat::SessionTsv, SESSION_TSV_V1).unwrap(),
+        1
+    );
+    assert_eq!(
+        detect_version(FileFormat::SessionJson, SESSION_JSON_V1).unwrap(),
+        1
+    );
+    assert_eq!(
+        detect_version(FileFormat::TrackerTsv, TRACKER_TSV_V1).unwrap(),
+        1
+    );
+}
+
+#[cfg(feature = "session")]
+#[test]
+fn test_session_tsv_v1() {
+    let migration = migrate(FileFormat::SessionTsv, SESSION_TSV_V1).unwrap();
+    assert!(migration.upgraded());
+    let lines: Vec<&str> = migration.content.lines().collect();
+    assert_eq!(lines[0], FileFormat::SessionTsv.marker());
+    // Profile and course comments are kept in place
+    assert!(lines[1].starts_with("# DJ ABC"));
+    assert!(lines[5].starts_with("# COURSE "));
+
+    let header: Vec<&str> = lines[2].split('\t').collect();
+    let row: Vec<&str> = lines[3].split('\t').collect();
+    assert_eq!(header.len(), row.len());
+    let col = |name: &str| row[header.iter().position(|h| *h == name).unwrap()];
+    assert_eq!(col("title"), "Song A");
+    assert_eq!(col("exscore"), "1700");
+    assert_eq!(col("nextgrade"), "AAA");
+    assert_eq!(col("nextgradegap"), "78");
+    assert_eq!(col("scorerate"), "85.00");
+    assert_eq!(col("bpi"), "");
+    assert_eq!(col("premature"), "0");
+    assert_eq!(col("failedat"), "");
+    assert_eq!(col("greennumber"), "");
+
+    // Migrated content is current and loads like a new session file
+    let again = migrate(FileFormat::SessionTsv, &migration.content).unwrap();
+    assert!(!again.upgraded());
+    assert_eq!(again.content, migration.content);
+    let plays = parse_session_tsv(&migration.content, &HashMap::<u32, SongInfo>::new());
+    assert_eq!(plays.len(), 2);
+    assert_eq!(plays[1].ex_score, 700);
+    assert_eq!(plays[1].chart.total_notes, 500);
+}
+
+#[test]
+fn test_session_json_v1() {
+    let migration = migrate(FileFormat::SessionJson, SESSION_JSON_V1).unwrap();
+    assert!(migration.upgraded());
+    let json: serde_json::Value = serde_json::from_str(&migration.content).unwrap();
+    assert_eq!(json["format"], "session-json");
+    assert_eq!(json["version"], 2);
+
+    let old: serde_json::Value = serde_json::from_str(SESSION_JSON_V1).unwrap();
+    assert_eq!(json["entries"], old);
+}
+
+#[test]
+fn test_tracker_tsv_v1() {
+    let migration = migrate(FileFormat::TrackerTsv, TRACKER_TSV_V1).unwrap();
+    assert!(migration.upgraded());
+    assert_eq!(
+        migration.content,
+        format!("# infst-format: tracker-tsv 2\n{}", TRACKER_TSV_V1)
+    );
+
+    // An old snapshot compares cleanly against a current one
+    let current = migration
+        .content
+        .replace("\tHARD\tAA\t1700\t", "\tEX HARD\tAA\t1720\t");
+    let changes = diff_tracker_tsv(TRACKER_TSV_V1, &current);
+    let columns: Vec<&str> = changes.iter().map(|c| c.column.as_str()).collect();
+    assert_eq!(columns, ["SPA Lamp", "SPA EX Score"]);
+}
+
+#[test]
+fn test_tracker_merge_with_v1_snapshot() {
+    let old = Tracker::parse(
+        &migrate(FileFormat::TrackerTsv, TRACKER_TSV_V1)
+            .unwrap()
+            .content,
+    );
+
+    // Merging a tracker with itself keeps every score
+    let merge = old.merge(&old);
+    assert!(merge.conflicts.is_empty());
+    assert_eq!(merge.tracker.get("1000", "SPA EX Score"), Some("1700"));
+    assert_eq!(merge.tracker.get("1001", "SPA Unlocked"), Some("FALSE"));
+
+    // The other PC improved the lamp but not the score
+    let other = Tracker::parse(
+        &migrate(FileFormat::TrackerTsv, TRACKER_TSV_V1)
+            .unwrap()
+            .content
+            .replace("\tHARD\tAA\t1700\t25\t", "\tEX HARD\tA\t1500\t40\t"),
+    );
+    let merge = old.merge(&other);
+    assert!(merge.conflicts.is_empty());
+    let merged = &merge.tracker;
+    assert_eq!(merged.get("1000", "SPA Lamp"), Some("EX HARD"));
+    assert_eq!(merged.get("1000", "SPA EX Score"), Some("1700"));
+    assert_eq!(merged.get("1000", "SPA Letter"), Some("AA"));
+    assert_eq!(merged.get("1000", "SPA Miss Count"), Some("25"));
+    assert_eq!(merged.len(), old.len());
+}
+
+#[test]
+fn test_signed_tracker_still_loads() {
+    let key = SigningKey::new("leaderboard secret").unwrap();
+    let current = migrate(FileFormat::TrackerTsv, TRACKER_TSV_V1)
+        .unwrap()
+        .content;
+    let signed = sign_export(&current, &key);
+    assert_eq!(verify_export(&signed, &key), SignatureStatus::Valid);
+
+    // Readers skip the signature line like the format marker
+    assert_eq!(
+        detect_version(FileFormat::TrackerTsv, &signed).unwrap(),
+        FileFormat::TrackerTsv.current_version()
+    );
+    assert_eq!(Tracker::parse(&signed), Tracker::parse(&current));
+    assert!(diff_tracker_tsv(&current, &signed).is_empty());
+}
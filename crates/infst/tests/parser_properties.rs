@@ -0,0 +1,192 @@
+//! Randomized property tests for parsers that consume untrusted bytes
+//!
+//! Song entries, score map nodes and text files come from game memory or
+//! user-edited files, so the parsers must return errors (or skip data) on
+//! malformed input rather than panic or loop forever. Each property runs a
+//! fixed number of seeded cases; a failure prints the seed so it can be
+//! replayed with `INFST_PROPTEST_SEED`. `INFST_PROPTEST_CASES` raises the
+//! case count for longer local runs. The `fuzz/` directory holds cargo-fuzz
+//! targets for the same entry points.
+
+use std::collections::HashMap;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use fastrand::Rng;
+use infst::chart::{
+    SongEntryLayout, SongInfo, fetch_song_database_from_memory_scan, parse_song_database_tsv,
+};
+use infst::process::{MockMemoryReader, decode_shift_jis, decode_shift_jis_to_string};
+use infst::score::{ListNode, ScoreMap};
+use infst::{MemorySnapshot, ReadMemory};
+
+const DEFAULT_CASES: u64 = 256;
+const BASE: u64 = 0x1000;
+const ENTRIES_4B0: &str = include_str!("fixtures/memory/song_entries_4b0.fixture");
+
+/// Run `property` on `cases` seeded generators, reporting the failing seed
+fn check(name: &str, property: impl Fn(&mut Rng)) {
+    let env = |key: &str| std::env::var(key).ok().and_then(|v| v.parse::<u64>().ok());
+    let seeds: Vec<u64> = match env("INFST_PROPTEST_SEED") {
+        Some(seed) => vec![seed],
+        None => (0..env("INFST_PROPTEST_CASES").unwrap_or(DEFAULT_CASES)).collect(),
+    };
+    for seed in seeds {
+        let mut rng = Rng::with_seed(seed);
+        if catch_unwind(AssertUnwindSafe(|| property(&mut rng))).is_err() {
+            panic!("property {name} failed; replay with INFST_PROPTEST_SEED={seed}");
+        }
+    }
+}
+
+/// Random bytes, biased towards lengths around typical structure sizes
+fn bytes(rng: &mut Rng, max_len: usize) -> Vec<u8> {
+    let len = rng.usize(..=max_len);
+    let mut out = vec![0u8; len];
+    rng.fill(&mut out);
+    out
+}
+
+/// `base` with a handful of bytes overwritten, truncated at random
+fn mutate(rng: &mut Rng, base: &[u8]) -> Vec<u8> {
+    let mut out = base.to_vec();
+    for _ in 0..rng.usize(1..16) {
+        if !out.is_empty() {
+            let i = rng.usize(..out.len());
+            out[i] = rng.u8(..);
+        }
+    }
+    if rng.bool() {
+        out.truncate(rng.usize(..=out.len()));
+    }
+    out
+}
+
+/// Random text made of TSV-ish fragments
+fn tsv_text(rng: &mut Rng) -> String {
+    const PIECES: &[&str] = &[
+        "\t",
+        "\n",
+        "\r\n",
+        "#",
+        "title",
+        "SPA",
+        "DPL",
+        "12",
+        "-1",
+        "99999999999",
+        "",
+        "曲",
+        "FULLCOMBO",
+        "2025-01-01T20:00:00+09:00",
+        "\u{0}",
+        "CLEAR",
+        "AAA",
+        "  ",
+    ];
+    (0..rng.usize(..200))
+        .map(|_| PIECES[rng.usize(..PIECES.len())])
+        .collect()
+}
+
+fn fixture_bytes() -> Vec<u8> {
+    let reader = MockMemoryReader::from_fixture(ENTRIES_4B0).unwrap();
+    reader
+        .read_bytes(reader.base_address(), reader.len())
+        .unwrap()
+}
+
+#[test]
+fn song_entry_parsing_never_panics() {
+    let fixture = fixture_bytes();
+    check("song_entry", |rng| {
+        let buffer = if rng.bool() {
+            mutate(rng, &fixture)
+        } else {
+            bytes(rng, 2 * SongInfo::MEMORY_SIZE)
+        };
+        let offset = match rng.u8(..4) {
+            0 => usize::MAX - rng.usize(..16),
+            _ => rng.usize(..=buffer.len()),
+        };
+        let _ = SongInfo::parse_from_buffer(&buffer, offset);
+        for layout in [SongEntryLayout::CURRENT, SongEntryLayout::LEGACY_3F0] {
+            let _ = SongInfo::parse_with_layout(&buffer, offset, &layout);
+        }
+    });
+}
+
+#[test]
+fn shift_jis_decoding_never_panics() {
+    check("shift_jis", |rng| {
+        let raw = bytes(rng, 128);
+        let decoded = decode_shift_jis(&raw);
+        assert_eq!(&*decoded, decode_shift_jis_to_string(&raw));
+        assert!(!decoded.contains('\0'));
+    });
+}
+
+#[test]
+fn score_node_parsing_never_panics() {
+    check("score_node", |rng| {
+        let raw = bytes(rng, ListNode::SIZE + 8);
+        let node = ListNode::from_bytes(&raw);
+        let _ = ScoreMap::from_nodes([node]);
+    });
+}
+
+#[test]
+fn score_map_load_terminates_on_garbage() {
+    check("score_map", |rng| {
+        // Small address space full of pointers back into itself, so chains
+        // are long, cyclic or point out of bounds
+        let words = rng.usize(4..256);
+        let mut data = vec![0u8; words * 8];
+        for word in data.chunks_exact_mut(8) {
+            let value = match rng.u8(..4) {
+                0 => BASE + rng.u64(..(words as u64 * 8)),
+                1 => rng.u64(..),
+                _ => rng.u64(..64),
+            };
+            word.copy_from_slice(&value.to_le_bytes());
+        }
+        let reader = MockMemoryReader::with_base(data, BASE);
+        let song_db: HashMap<u32, SongInfo> = (0..64).map(|id| (id, SongInfo::default())).collect();
+        let data_map = match rng.u8(..4) {
+            0 => u64::MAX - rng.u64(..32),
+            _ => BASE + rng.u64(..(words as u64 * 8)),
+        };
+        let _ = ScoreMap::load_from_memory(&reader, data_map, &song_db);
+    });
+}
+
+#[test]
+fn song_memory_scan_never_panics() {
+    let fixture = fixture_bytes();
+    check("song_scan", |rng| {
+        let reader = MockMemoryReader::with_base(mutate(rng, &fixture), BASE);
+        let scan = rng.usize(..2 * fixture.len());
+        let _ = fetch_song_database_from_memory_scan(&reader, BASE + rng.u64(..64), scan);
+    });
+}
+
+#[test]
+fn text_imports_never_panic() {
+    check("text_imports", |rng| {
+        let text = tsv_text(rng);
+        let _ = parse_song_database_tsv(text.as_bytes());
+        #[cfg(feature = "session")]
+        let _ = infst::parse_session_tsv(&text, &HashMap::new());
+        let _ = MockMemoryReader::from_fixture(&text);
+    });
+}
+
+#[test]
+fn snapshot_decoding_never_panics() {
+    let mut snapshot = MemorySnapshot::new();
+    snapshot.add_region(BASE, fixture_bytes());
+    let encoded = snapshot.to_bytes();
+    check("snapshot", |rng| {
+        let _ = MemorySnapshot::from_bytes(&mutate(rng, &encoded[..64.min(encoded.len())]));
+        let _ = MemorySnapshot::from_bytes(&bytes(rng, 64));
+    });
+}
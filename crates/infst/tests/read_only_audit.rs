@@ -0,0 +1,100 @@
+//! Audit that infst has no way to write to the game process
+//!
+//! The source of every crate in the workspace is scanned for Win32 APIs and
+//! access rights that modify another process. Code under `debug/` (the
+//! `debug-tools` feature) is exempt, as is the mock reader, which only
+//! fills its own buffer.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Identifiers that write to, or open for writing, another process's memory
+const FORBIDDEN: &[&str] = &[
+    "WriteProcessMemory",
+    "VirtualProtectEx",
+    "VirtualAllocEx",
+    "CreateRemoteThread",
+    "PROCESS_VM_WRITE",
+    "PROCESS_VM_OPERATION",
+    "PROCESS_ALL_ACCESS",
+];
+
+fn crates_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf()
+}
+
+fn is_exempt(path: &Path) -> bool {
+    path.components().any(|c| c.as_os_str() == "debug")
+        || path.file_name().is_some_and(|name| name == "mock.rs")
+}
+
+fn rust_sources(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            rust_sources(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "rs") && !is_exempt(&path) {
+            files.push(path);
+        }
+    }
+}
+
+/// `(file, line number, identifier)` for every forbidden identifier in code
+/// (comments may mention them)
+fn forbidden_uses(files: &[PathBuf], forbidden: &[&str]) -> Vec<(PathBuf, usize, String)> {
+    let mut found = Vec::new();
+    for file in files {
+        let content = fs::read_to_string(file).unwrap();
+        for (number, line) in content.lines().enumerate() {
+            let code = line.split("//").next().unwrap_or_default();
+            for name in forbidden {
+                if code.contains(name) {
+                    found.push((file.clone(), number + 1, name.to_string()));
+                }
+            }
+        }
+    }
+    found
+}
+
+#[test]
+fn test_no_process_write_apis() {
+    let mut files = Vec::new();
+    for krate in fs::read_dir(crates_dir()).unwrap() {
+        let src = krate.unwrap().path().join("src");
+        if src.is_dir() {
+            rust_sources(&src, &mut files);
+        }
+    }
+    assert!(
+        files.iter().any(|file| file.ends_with("process/handle.rs")),
+        "the scan must cover the process module"
+    );
+    let found = forbidden_uses(&files, FORBIDDEN);
+    assert!(found.is_empty(), "process write APIs used: {:?}", found);
+}
+
+#[test]
+fn test_memory_module_has_no_write_functions() {
+    let mut files = Vec::new();
+    rust_sources(&crates_dir().join("infst/src/process"), &mut files);
+    let found = forbidden_uses(&files, &["pub fn write", "fn write_memory"]);
+    assert!(found.is_empty(), "write functions in process/: {:?}", found);
+}
+
+#[test]
+fn test_forbidden_identifiers_are_detected() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("patch.rs");
+    fs::write(
+        &file,
+        "// WriteProcessMemory in a comment is fine\nunsafe { WriteProcessMemory(h, a, b, n, None) };\n",
+    )
+    .unwrap();
+    let found = forbidden_uses(&[file], FORBIDDEN);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].1, 2);
+}
@@ -250,6 +250,10 @@ fn test_offsets_collection_is_valid() {
         current_song: 0x5000,
         data_map: 0x6000,
         unlock_data: 0x7000,
+        profile: 0,
+        currency: 0,
+        lane_cover: 0,
+        song_anchors: Vec::new(),
     };
     assert!(valid.is_valid());
 
@@ -262,6 +266,10 @@ fn test_offsets_collection_is_valid() {
         current_song: 0x5000,
         data_map: 0x6000,
         unlock_data: 0x7000,
+        profile: 0,
+        currency: 0,
+        lane_cover: 0,
+        song_anchors: Vec::new(),
     };
     assert!(!invalid.is_valid());
 }
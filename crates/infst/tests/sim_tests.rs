@@ -0,0 +1,594 @@
+//! End-to-end tests of the tracking loop against the simulated game
+//!
+//! `Infst::run` drives a scripted [`SimGame`] through song select, play and
+//! result screens; the tests check what a user would see afterwards: play
+//! handler events, the session TSV and the exported tracker file.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+
+use chrono::{TimeZone, Utc};
+use infst::chart::{ChartId, Difficulty, SongInfo};
+use infst::play::{PlayData, PlayType};
+use infst::score::{Judge, Lamp};
+use infst::sim::{SimGame, SimResult, SimScreen};
+use infst::{
+    AnomalyMode, Infst, InfstConfig, MockClock, PrematureMode, SessionCommand, SessionControl,
+    TrackerExit, parse_session_tsv,
+};
+
+fn song(id: u32, title: &str, notes: u32) -> SongInfo {
+    let mut song = SongInfo {
+        id,
+        title: title.into(),
+        title_english: title.into(),
+        artist: "Sim Artist".into(),
+        genre: "SIM".into(),
+        bpm: "150".into(),
+        ..SongInfo::default()
+    };
+    song.levels[Difficulty::SpA.index()] = 12;
+    song.total_notes[Difficulty::SpA.index()] = notes;
+    song
+}
+
+fn result(song_id: u32, lamp: Lamp, pgreat: u32, great: u32, poor: u32) -> SimScreen {
+    SimScreen::Result(SimResult {
+        song_id,
+        difficulty: Difficulty::SpA,
+        lamp,
+        judge: Judge {
+            play_type: PlayType::P1,
+            pgreat,
+            great,
+            poor,
+            ..Judge::default()
+        },
+    })
+}
+
+fn playing(song_id: u32) -> SimScreen {
+    SimScreen::Playing {
+        song_id,
+        difficulty: Difficulty::SpA,
+    }
+}
+
+#[test]
+fn test_run_records_scripted_plays() {
+    let dir = tempfile::tempdir().unwrap();
+    let song_db: HashMap<u32, SongInfo> = [song(1000, "Sim Song", 1000), song(1001, "Other", 800)]
+        .into_iter()
+        .map(|song| (song.id, song))
+        .collect();
+
+    let game = SimGame::new([
+        (SimScreen::SongSelect, 2),
+        (playing(1000), 2),
+        (result(1000, Lamp::HardClear, 900, 90, 10), 2),
+        (SimScreen::SongSelect, 2),
+        (playing(1001), 2),
+        (result(1001, Lamp::Failed, 300, 100, 50), 2),
+        (SimScreen::SongSelect, 2),
+    ])
+    .with_unlocked([1000, 1001]);
+
+    let start = Utc.with_ymd_and_hms(2026, 4, 1, 20, 0, 0).unwrap();
+    let events: Arc<Mutex<Vec<PlayData>>> = Arc::default();
+    let recorded = Arc::clone(&events);
+    let mut infst = Infst::builder()
+        .offsets(SimGame::offsets())
+        .session_dir(dir.path().join("sessions"))
+        .tracker_path(dir.path().join("tracker.tsv"))
+        .song_db(song_db.clone())
+        .clock(Arc::new(MockClock::new(start)))
+        .on_play(move |play| recorded.lock().unwrap().push(play.clone()))
+        .build()
+        .unwrap();
+
+    let exit = infst.run(&game, &AtomicBool::new(false)).unwrap();
+    assert_eq!(exit, TrackerExit::ProcessExited);
+
+    // Events, in play order
+    let events = events.lock().unwrap();
+    let summary: Vec<_> = events
+        .iter()
+        .map(|play| (play.chart.song_id, play.lamp, play.ex_score))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![(1000, Lamp::HardClear, 1890), (1001, Lamp::Failed, 700)]
+    );
+    assert_eq!(events[0].chart.title.as_ref(), "Sim Song");
+    assert_eq!(events[0].chart.total_notes, 1000);
+    assert!(events.iter().all(|play| play.timestamp == start));
+
+    // Play time (the mock clock stands still) and notes hit
+    let play_time = infst.play_time();
+    assert_eq!(play_time.notes_hit(), 990 + 400);
+    assert_eq!(
+        play_time
+            .chart(&ChartId::new(1001, Difficulty::SpA))
+            .unwrap()
+            .plays,
+        1
+    );
+    assert_eq!(play_time.notes_per_minute(), None);
+
+    // Last play summary (copied by the `c` hotkey / `infst session copy`)
+    #[cfg(feature = "stream")]
+    assert!(
+        infst
+            .last_play_summary()
+            .is_some_and(|summary| summary.starts_with("Other [") && summary.contains("EX 700"))
+    );
+
+    // Session TSV
+    let sessions: Vec<_> = std::fs::read_dir(dir.path().join("sessions"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "tsv"))
+        .collect();
+    assert_eq!(sessions.len(), 1);
+    let rows = parse_session_tsv(&std::fs::read_to_string(&sessions[0]).unwrap(), &song_db);
+    let rows: Vec<_> = rows
+        .iter()
+        .map(|play| (play.chart.title.to_string(), play.lamp, play.ex_score))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            ("Sim Song".to_string(), Lamp::HardClear, 1890),
+            ("Other".to_string(), Lamp::Failed, 700),
+        ]
+    );
+
+    // Tracker export (written at every song select)
+    let tracker = std::fs::read_to_string(dir.path().join("tracker.tsv")).unwrap();
+    assert!(
+        tracker
+            .lines()
+            .any(|line| line.starts_with("1000\tSim Song"))
+    );
+    assert!(tracker.lines().any(|line| line.starts_with("1001\tOther")));
+}
+
+#[test]
+fn test_anomalous_play_is_dropped_and_noted() {
+    let dir = tempfile::tempdir().unwrap();
+    // Judge counts far above the chart's 100 notes, as with stale offsets
+    let game = SimGame::new([
+        (SimScreen::SongSelect, 2),
+        (playing(1000), 2),
+        (result(1000, Lamp::Clear, 900, 90, 10), 2),
+        (SimScreen::SongSelect, 2),
+    ]);
+    let events: Arc<Mutex<Vec<PlayData>>> = Arc::default();
+    let recorded = Arc::clone(&events);
+    let mut infst = Infst::builder()
+        .offsets(SimGame::offsets())
+        .config(
+            InfstConfig::builder()
+                .session_dir(dir.path().join("sessions"))
+                .tracker_path(dir.path().join("tracker.tsv"))
+                .anomaly_mode(AnomalyMode::Drop)
+                .build(),
+        )
+        .song_db(HashMap::from([(1000, song(1000, "Tiny", 100))]))
+        .on_play(move |play| recorded.lock().unwrap().push(play.clone()))
+        .build()
+        .unwrap();
+
+    infst.run(&game, &AtomicBool::new(false)).unwrap();
+
+    assert!(events.lock().unwrap().is_empty());
+    let session = std::fs::read_dir(dir.path().join("sessions"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "tsv"))
+        .unwrap();
+    let content = std::fs::read_to_string(session).unwrap();
+    assert!(parse_session_tsv(&content, &HashMap::new()).is_empty());
+    let note = content
+        .lines()
+        .find(|line| line.starts_with("# ANOMALY "))
+        .unwrap();
+    assert!(note.contains("Tiny [SPA]: EX score 1890 exceeds maximum 200"));
+    assert!(note.ends_with("(not recorded)"));
+}
+
+#[test]
+fn test_repeated_result_screen_is_recorded_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let game = SimGame::new([
+        (SimScreen::SongSelect, 2),
+        (playing(1000), 2),
+        (result(1000, Lamp::Clear, 900, 90, 10), 2),
+        // The playing marker flickers while the result screen is shown
+        (playing(1000), 1),
+        (result(1000, Lamp::Clear, 900, 90, 10), 2),
+        (SimScreen::SongSelect, 2),
+    ]);
+    let events: Arc<Mutex<Vec<PlayData>>> = Arc::default();
+    let recorded = Arc::clone(&events);
+    let start = Utc.with_ymd_and_hms(2026, 4, 1, 20, 0, 0).unwrap();
+    let mut infst = Infst::builder()
+        .offsets(SimGame::offsets())
+        .session_dir(dir.path().join("sessions"))
+        .tracker_path(dir.path().join("tracker.tsv"))
+        .song_db(HashMap::from([(1000, song(1000, "Sim Song", 1000))]))
+        .clock(Arc::new(MockClock::new(start)))
+        .on_play(move |play| recorded.lock().unwrap().push(play.clone()))
+        .build()
+        .unwrap();
+
+    infst.run(&game, &AtomicBool::new(false)).unwrap();
+    assert_eq!(events.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn test_premature_play_recorded_judge_only() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut quit = result(1000, Lamp::Failed, 200, 50, 20);
+    if let SimScreen::Result(result) = &mut quit {
+        result.judge.premature_end = true;
+    }
+    let game = SimGame::new([
+        (SimScreen::SongSelect, 2),
+        (playing(1000), 2),
+        (quit, 2),
+        (SimScreen::SongSelect, 2),
+    ]);
+    let events: Arc<Mutex<Vec<PlayData>>> = Arc::default();
+    let recorded = Arc::clone(&events);
+    let song_db = HashMap::from([(1000, song(1000, "Sim Song", 1000))]);
+    let mut infst = Infst::builder()
+        .offsets(SimGame::offsets())
+        .config(
+            InfstConfig::builder()
+                .session_dir(dir.path().join("sessions"))
+                .tracker_path(dir.path().join("tracker.tsv"))
+                .premature_mode(PrematureMode::JudgeOnly)
+                .build(),
+        )
+        .song_db(song_db.clone())
+        .on_play(move |play| recorded.lock().unwrap().push(play.clone()))
+        .build()
+        .unwrap();
+
+    infst.run(&game, &AtomicBool::new(false)).unwrap();
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert!(events[0].is_premature());
+    assert_eq!(events[0].lamp, Lamp::NoPlay);
+    assert_eq!(events[0].ex_score, 450);
+
+    let session = std::fs::read_dir(dir.path().join("sessions"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "tsv"))
+        .unwrap();
+    let rows = parse_session_tsv(&std::fs::read_to_string(session).unwrap(), &song_db);
+    assert!(rows[0].is_premature());
+    assert_eq!(rows[0].lamp, Lamp::NoPlay);
+}
+
+#[test]
+fn test_shutdown_flag_stops_run() {
+    let dir = tempfile::tempdir().unwrap();
+    let game = SimGame::new([(SimScreen::SongSelect, u32::MAX)]);
+    let mut infst = Infst::builder()
+        .offsets(SimGame::offsets())
+        .session_dir(dir.path().join("sessions"))
+        .tracker_path(dir.path().join("tracker.tsv"))
+        .build()
+        .unwrap();
+
+    let exit = infst.run(&game, &AtomicBool::new(true)).unwrap();
+    assert_eq!(exit, TrackerExit::Shutdown);
+    assert_eq!(game.step(), 0);
+}
+
+#[test]
+fn test_session_end_exports() {
+    let dir = tempfile::tempdir().unwrap();
+    let song_db: HashMap<u32, SongInfo> = [song(1000, "Sim Song", 1000)]
+        .into_iter()
+        .map(|song| (song.id, song))
+        .collect();
+    let game = SimGame::new([
+        (SimScreen::SongSelect, 2),
+        (playing(1000), 2),
+        (result(1000, Lamp::HardClear, 900, 90, 10), 2),
+        (SimScreen::SongSelect, 2),
+    ])
+    .with_unlocked([1000]);
+
+    let mapping = dir.path().join("title-mapping.json");
+    std::fs::write(&mapping, "{}").unwrap();
+    let uploads: Arc<Mutex<Vec<(PathBuf, PathBuf)>>> = Arc::default();
+    let recorded = Arc::clone(&uploads);
+    let start = Utc.with_ymd_and_hms(2026, 4, 1, 20, 0, 0).unwrap();
+    let mut infst = Infst::builder()
+        .offsets(SimGame::offsets())
+        .config(
+            InfstConfig::builder()
+                .auto_export(false)
+                .scheduled_export("backup@session-end".parse().unwrap())
+                .scheduled_export(
+                    format!("upload:{}@session-end", mapping.display())
+                        .parse()
+                        .unwrap(),
+                )
+                .scheduled_export("snapshot@daily 04:00".parse().unwrap())
+                .build(),
+        )
+        .session_dir(dir.path().join("sessions"))
+        .tracker_path(dir.path().join("tracker.tsv"))
+        .song_db(song_db)
+        .clock(Arc::new(MockClock::new(start)))
+        .on_upload(move |tracker, mapping| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((tracker.to_path_buf(), mapping.to_path_buf()));
+            Ok(())
+        })
+        .build()
+        .unwrap();
+
+    infst.run(&game, &AtomicBool::new(false)).unwrap();
+
+    // Backup: the tracker and the session files
+    let backups: Vec<_> = std::fs::read_dir(infst::Paths::in_dir(dir.path()).backups())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(backups.len(), 1);
+    let tracker = std::fs::read_to_string(backups[0].join("tracker.tsv")).unwrap();
+    assert!(
+        tracker
+            .lines()
+            .any(|line| line.starts_with("1000\tSim Song"))
+    );
+    let sessions = std::fs::read_dir(backups[0].join("sessions"))
+        .unwrap()
+        .count();
+    assert!(sessions >= 1);
+
+    // Upload: handed the freshly written tracker
+    let uploads = uploads.lock().unwrap();
+    assert_eq!(
+        *uploads,
+        vec![(dir.path().join("tracker.tsv"), mapping.clone())]
+    );
+
+    // The daily snapshot has not come yet; its next run is recorded for `status`
+    let entries = infst::load_schedule_status(dir.path().join("sessions")).unwrap();
+    assert_eq!(entries.len(), 3);
+    let snapshot = entries
+        .iter()
+        .find(|entry| entry.job == "snapshot@daily 04:00")
+        .unwrap();
+    assert_eq!(snapshot.last_run, None);
+    assert!(snapshot.next_run.is_some());
+    assert!(
+        entries
+            .iter()
+            .filter(|entry| entry.job.ends_with("@session-end"))
+            .all(|entry| entry.last_run.is_some())
+    );
+}
+
+#[test]
+fn test_failed_upload_is_not_recorded() {
+    let dir = tempfile::tempdir().unwrap();
+    let song_db: HashMap<u32, SongInfo> = [song(1000, "Sim Song", 1000)]
+        .into_iter()
+        .map(|song| (song.id, song))
+        .collect();
+    let game = SimGame::new([
+        (SimScreen::SongSelect, 2),
+        (playing(1000), 2),
+        (result(1000, Lamp::HardClear, 900, 90, 10), 2),
+        (SimScreen::SongSelect, 2),
+    ])
+    .with_unlocked([1000]);
+
+    let mapping = dir.path().join("title-mapping.json");
+    std::fs::write(&mapping, "{}").unwrap();
+    let mut infst = Infst::builder()
+        .offsets(SimGame::offsets())
+        .config(
+            InfstConfig::builder()
+                .auto_export(false)
+                .scheduled_export(
+                    format!("upload:{}@session-end", mapping.display())
+                        .parse()
+                        .unwrap(),
+                )
+                .build(),
+        )
+        .session_dir(dir.path().join("sessions"))
+        .tracker_path(dir.path().join("tracker.tsv"))
+        .song_db(song_db)
+        .on_upload(|_, _| {
+            Err(infst::Error::Network {
+                message: "server unavailable".to_string(),
+                retryable: true,
+            })
+        })
+        .build()
+        .unwrap();
+
+    infst.run(&game, &AtomicBool::new(false)).unwrap();
+
+    let entries = infst::load_schedule_status(dir.path().join("sessions")).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].last_run, None);
+}
+
+#[test]
+fn test_hotkey_commands_are_applied() {
+    let dir = tempfile::tempdir().unwrap();
+    let sessions = dir.path().join("sessions");
+    let game = SimGame::new([
+        (SimScreen::SongSelect, 2),
+        (playing(1000), 2),
+        (result(1000, Lamp::Clear, 900, 90, 10), 2),
+        (SimScreen::SongSelect, 2),
+    ])
+    .with_unlocked([1000]);
+    let mut infst = Infst::builder()
+        .offsets(SimGame::offsets())
+        .config(InfstConfig {
+            auto_export: false,
+            ..InfstConfig::default()
+        })
+        .session_dir(&sessions)
+        .tracker_path(dir.path().join("tracker.tsv"))
+        .song_db(HashMap::from([(1000, song(1000, "Sim Song", 1000))]))
+        .build()
+        .unwrap();
+
+    // Queued the way the hotkey listener does
+    let control = SessionControl::new(&sessions);
+    control.send(&SessionCommand::TogglePause).unwrap();
+    control.send(&SessionCommand::Export).unwrap();
+    control.send(&SessionCommand::ToggleMarquee).unwrap();
+
+    infst.run(&game, &AtomicBool::new(false)).unwrap();
+
+    // Exported although auto-export is off
+    assert!(dir.path().join("tracker.tsv").is_file());
+    let session = std::fs::read_dir(&sessions)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "tsv"))
+        .unwrap();
+    let content = std::fs::read_to_string(session).unwrap();
+    assert!(content.lines().any(|line| line.starts_with("# PAUSE ")));
+}
+
+#[test]
+fn test_dry_run_writes_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let game = SimGame::new([
+        (SimScreen::SongSelect, 2),
+        (playing(1000), 2),
+        (result(1000, Lamp::HardClear, 900, 90, 10), 2),
+        (SimScreen::SongSelect, 2),
+    ])
+    .with_unlocked([1000]);
+
+    let events: Arc<Mutex<Vec<PlayData>>> = Arc::default();
+    let recorded = Arc::clone(&events);
+    let mut infst = Infst::builder()
+        .offsets(SimGame::offsets())
+        .config(
+            InfstConfig::builder()
+                .dry_run(true)
+                .scheduled_export("backup@session-end".parse().unwrap())
+                .build(),
+        )
+        .session_dir(dir.path().join("sessions"))
+        .tracker_path(dir.path().join("tracker.tsv"))
+        .song_db(HashMap::from([(1000, song(1000, "Sim Song", 1000))]))
+        .on_play(move |play| recorded.lock().unwrap().push(play.clone()))
+        .build()
+        .unwrap();
+
+    infst.run(&game, &AtomicBool::new(false)).unwrap();
+
+    // Plays are still read and handed to handlers
+    assert_eq!(events.lock().unwrap().len(), 1);
+    assert_eq!(infst.play_time().notes_hit(), 990);
+    // No session file, tracker export, backup or schedule
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_session_is_closed_out_after_a_panic() {
+    let dir = tempfile::tempdir().unwrap();
+    let sessions = dir.path().join("sessions");
+    let game = SimGame::new([
+        (SimScreen::SongSelect, 2),
+        (playing(1000), 2),
+        (result(1000, Lamp::Clear, 900, 90, 10), 2),
+        (SimScreen::SongSelect, 2),
+    ])
+    .with_unlocked([1000]);
+    let mut infst = Infst::builder()
+        .offsets(SimGame::offsets())
+        .session_dir(&sessions)
+        .tracker_path(dir.path().join("tracker.tsv"))
+        .song_db(HashMap::from([(1000, song(1000, "Sim Song", 1000))]))
+        .on_play(|_| panic!("handler bug"))
+        .build()
+        .unwrap();
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        infst.run(&game, &AtomicBool::new(false))
+    }));
+    assert!(result.is_err());
+    infst.finish_session();
+
+    // The play was written before the handler panicked
+    let session = std::fs::read_dir(&sessions)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "tsv"))
+        .unwrap();
+    let song_db = HashMap::from([(1000, song(1000, "Sim Song", 1000))]);
+    let rows = parse_session_tsv(&std::fs::read_to_string(session).unwrap(), &song_db);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(infst.play_time().notes_hit(), 990);
+}
+
+#[test]
+fn test_lost_handle_is_refreshed_without_losing_the_play() {
+    let dir = tempfile::tempdir().unwrap();
+    let sessions = dir.path().join("sessions");
+    // Access is lost as the result screen appears, mid-way through the play
+    let game = SimGame::new([
+        (SimScreen::SongSelect, 2),
+        (playing(1000), 2),
+        (result(1000, Lamp::HardClear, 950, 45, 5), 2),
+        (SimScreen::SongSelect, 2),
+    ])
+    .with_handle_loss_at(2);
+    let events: Arc<Mutex<Vec<PlayData>>> = Arc::default();
+    let recorded = Arc::clone(&events);
+    let mut infst = Infst::builder()
+        .offsets(SimGame::offsets())
+        .session_dir(&sessions)
+        .tracker_path(dir.path().join("tracker.tsv"))
+        .song_db(HashMap::from([(1000, song(1000, "Sim Song", 1000))]))
+        .on_play(move |play| recorded.lock().unwrap().push(play.clone()))
+        .build()
+        .unwrap();
+
+    let exit = infst.run(&game, &AtomicBool::new(false)).unwrap();
+    assert_eq!(exit, TrackerExit::ProcessExited);
+    assert_eq!(game.handle_refreshes(), 1);
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].lamp, Lamp::HardClear);
+    // Still the one session the run started with
+    let session_files = std::fs::read_dir(&sessions)
+        .unwrap()
+        .filter(|entry| {
+            entry
+                .as_ref()
+                .unwrap()
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == "tsv")
+        })
+        .count();
+    assert_eq!(session_files, 1);
+}
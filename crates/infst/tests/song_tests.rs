@@ -410,3 +410,101 @@ mod song_info_defaults {
         assert_eq!(SongInfo::MEMORY_SIZE, 1200);
     }
 }
+
+/// Song lists captured with `infst fixture`, one per entry layout
+mod layout_fixtures {
+    use infst::LayoutRegistry;
+    use infst::chart::{
+        SongEntryLayout, SongInfo, fetch_song_database_from_memory_scan,
+        fetch_song_database_from_memory_scan_with_layout,
+    };
+    use infst::process::{MockMemoryReader, ReadMemory};
+
+    const ENTRIES_4B0: &str = include_str!("fixtures/memory/song_entries_4b0.fixture");
+    const ENTRIES_3F0: &str = include_str!("fixtures/memory/song_entries_3f0.fixture");
+
+    fn parse_all(fixture: &str, layout: &SongEntryLayout) -> Vec<Option<SongInfo>> {
+        let reader = MockMemoryReader::from_fixture(fixture).unwrap();
+        let bytes = reader
+            .read_bytes(reader.base_address(), reader.len())
+            .unwrap();
+        assert_eq!(bytes.len() % layout.entry_size, 0);
+        (0..bytes.len() / layout.entry_size)
+            .map(|i| SongInfo::parse_with_layout(&bytes, i * layout.entry_size, layout).unwrap())
+            .collect()
+    }
+
+    fn assert_fixture_songs(songs: &[Option<SongInfo>]) {
+        assert_eq!(songs.len(), 4);
+        assert!(songs[3].is_none(), "empty slot ends the list");
+
+        let first = songs[0].as_ref().unwrap();
+        assert_eq!(first.id, 1000);
+        assert_eq!(&*first.title, "5.1.1.");
+        assert_eq!(&*first.artist, "dj nagureo");
+        assert_eq!(&*first.genre, "PIANO AMBIENT");
+        assert_eq!(&*first.bpm, "167");
+        assert_eq!(first.folder, 1);
+        assert_eq!(first.levels, [1, 3, 6, 0, 0, 0, 3, 6, 0, 0]);
+        assert_eq!(first.total_notes[2], 629);
+
+        let second = songs[1].as_ref().unwrap();
+        assert_eq!(second.id, 28045);
+        assert_eq!(&*second.title, "テスト曲～前編～");
+        assert_eq!(&*second.title_english, "Test Song -Part 1-");
+        assert_eq!(&*second.artist, "テストアーティスト");
+        assert_eq!(&*second.bpm, "090~180");
+        assert_eq!(second.levels[3], 12);
+        assert_eq!(second.total_notes[3], 1867);
+
+        let third = songs[2].as_ref().unwrap();
+        assert_eq!(third.id, 31002);
+        assert_eq!(
+            third.total_notes,
+            [300, 650, 1200, 1650, 2001, 0, 640, 1180, 1630, 1999]
+        );
+    }
+
+    #[test]
+    fn test_current_layout_fixture() {
+        assert_fixture_songs(&parse_all(ENTRIES_4B0, &SongEntryLayout::CURRENT));
+    }
+
+    #[test]
+    fn test_legacy_layout_fixture() {
+        assert_fixture_songs(&parse_all(ENTRIES_3F0, &SongEntryLayout::LEGACY_3F0));
+    }
+
+    #[test]
+    fn test_memory_scan_on_current_fixture() {
+        let reader = MockMemoryReader::from_fixture(ENTRIES_4B0).unwrap();
+        let db = fetch_song_database_from_memory_scan(&reader, reader.base_address(), reader.len());
+        let mut ids: Vec<u32> = db.keys().copied().collect();
+        ids.sort_unstable();
+        assert_eq!(ids, [1000, 28045, 31002]);
+    }
+
+    /// Each build's song list is scanned with the layout selected for its version
+    #[test]
+    fn test_memory_scan_with_registry_layout() {
+        let registry = LayoutRegistry::builtin();
+        for (version, fixture) in [
+            ("P2D:J:B:A:2026012800", ENTRIES_4B0),
+            ("P2D:J:B:A:2025122400", ENTRIES_3F0),
+        ] {
+            let layout = registry.select(version).unwrap();
+            let reader = MockMemoryReader::from_fixture(fixture).unwrap();
+            let db = fetch_song_database_from_memory_scan_with_layout(
+                &reader,
+                reader.base_address(),
+                reader.len(),
+                &layout.song_entry,
+            );
+            let mut ids: Vec<u32> = db.keys().copied().collect();
+            ids.sort_unstable();
+            assert_eq!(ids, [1000, 28045, 31002], "{}", version);
+            assert_eq!(&*db[&28045].title, "テスト曲～前編～");
+            assert_eq!(db[&28045].total_notes[3], 1867);
+        }
+    }
+}
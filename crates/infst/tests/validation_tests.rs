@@ -634,6 +634,10 @@ mod offsets_collection {
             current_song: 0x5000,
             data_map: 0x6000,
             unlock_data: 0x7000,
+            profile: 0,
+            currency: 0,
+            lane_cover: 0,
+            song_anchors: Vec::new(),
         };
 
         assert!(offsets.is_valid());
@@ -650,6 +654,10 @@ mod offsets_collection {
             current_song: 0x5000,
             data_map: 0x6000,
             unlock_data: 0x7000,
+            profile: 0,
+            currency: 0,
+            lane_cover: 0,
+            song_anchors: Vec::new(),
         };
 
         assert!(!offsets.is_valid());
@@ -666,6 +674,10 @@ mod offsets_collection {
             current_song: 0x5000,
             data_map: 0x6000,
             unlock_data: 0x7000,
+            profile: 0,
+            currency: 0,
+            lane_cover: 0,
+            song_anchors: Vec::new(),
         };
 
         assert!(!offsets.is_valid());
@@ -683,6 +695,10 @@ mod offsets_collection {
             current_song: 0x5000,
             data_map: 0,    // Optional
             unlock_data: 0, // Optional
+            profile: 0,
+            currency: 0,
+            lane_cover: 0,
+            song_anchors: Vec::new(),
         };
 
         // Note: is_valid() checks all fields are non-zero